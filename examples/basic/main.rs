@@ -18,8 +18,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!(
         "add(3,4) = {}",
         hub.call(FunctionCall {
+            id: None,
             name: "add".into(),
             arguments: json!({ "pair": [3, 4] }),
+            repaired: false,
         })
         .await?
     );
@@ -1,5 +1,10 @@
+use std::time::Duration;
+
 use serde_json::{Value, json};
-use tools_rs::{FunctionCall, collect_tools, tool};
+use tools_rs::{
+    AgentProfile, FunctionCall, Profiled, Provider, Secrets, TierTracker, ToolChoice,
+    choice::gemini, collect_tools, hash, tool,
+};
 
 #[tool]
 /// Gets the current temperature for given coordinates
@@ -45,24 +50,174 @@ async fn search_web(query: String, max_result: usize) -> Result<String, String>
     Ok(out)
 }
 
-async fn gemini_chat(
-    mut history: Vec<Value>,
-    tools: &tools_rs::ToolCollection,
+/// What the model said in one response: text parts in order, and the
+/// function calls it asked for. Gemini may send both in the same turn.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct TurnOutput {
+    pub(crate) text_parts: Vec<String>,
+    pub(crate) calls: Vec<FunctionCall>,
+}
+
+impl TurnOutput {
+    /// Split a Gemini `content` object into text and calls.
+    pub(crate) fn from_gemini(content: &Value) -> Self {
+        let mut out = TurnOutput::default();
+        for part in content["parts"].as_array().into_iter().flatten() {
+            if let Some(fc) = part.get("functionCall") {
+                out.calls.push(FunctionCall {
+                    id: None,
+                    name: fc["name"].as_str().unwrap_or_default().to_string(),
+                    arguments: fc["args"].clone(),
+                    repaired: false,
+                });
+            } else if let Some(text) = part["text"].as_str() {
+                out.text_parts.push(text.to_string());
+            }
+        }
+        out
+    }
+}
+
+/// How long one user turn may run.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct LoopLimits {
+    /// Model responses allowed per user turn before giving up.
+    pub(crate) max_rounds: usize,
+    /// After this many identical calls in a row (same tool, same canonical
+    /// arguments), answer with an error instead of running the tool.
+    /// `None` never intervenes.
+    pub(crate) repeat_limit: Option<usize>,
+}
+
+impl Default for LoopLimits {
+    fn default() -> Self {
+        LoopLimits {
+            max_rounds: 10,
+            repeat_limit: Some(3),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) enum AgentError {
+    /// The model was still asking for calls after `rounds` responses.
+    MaxRoundsExceeded { rounds: usize },
+}
+
+impl std::fmt::Display for AgentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AgentError::MaxRoundsExceeded { rounds } => {
+                write!(f, "model still calling tools after {rounds} rounds")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AgentError {}
+
+/// Counts consecutive identical calls within a turn.
+#[derive(Debug, Default)]
+pub(crate) struct RepeatGuard {
+    last: Option<(String, u64)>,
+    run: usize,
+}
+
+impl RepeatGuard {
+    /// Record `call` and return how many times in a row it has been made.
+    pub(crate) fn observe(&mut self, call: &FunctionCall) -> usize {
+        let key = (call.name.clone(), hash::canonical_hash(&call.arguments));
+        if self.last.as_ref() == Some(&key) {
+            self.run += 1;
+        } else {
+            self.last = Some(key);
+            self.run = 1;
+        }
+        self.run
+    }
+}
+
+/// Run one user turn against `model`, which receives the history so far
+/// and returns the next Gemini `content`. Text the model sends alongside
+/// calls is passed to `on_text` as it arrives; the turn ends with the
+/// first response that asks for no calls, whose text is returned.
+pub(crate) async fn run_turn<M>(
+    history: &mut Vec<Value>,
+    tools: &Profiled<'_, M>,
+    limits: LoopLimits,
+    mut model: impl AsyncFnMut(&[Value]) -> Result<Value, Box<dyn std::error::Error>>,
+    mut on_text: impl FnMut(&str),
+) -> Result<String, Box<dyn std::error::Error>> {
+    let mut guard = RepeatGuard::default();
+    for _ in 0..limits.max_rounds {
+        let content = model(history).await?;
+        let turn = TurnOutput::from_gemini(&content);
+        // Gemini wants the model's parts as sent, text and calls together,
+        // followed by one function turn answering every call.
+        history.push(json!({"role": "model", "parts": content["parts"]}));
+        if turn.calls.is_empty() {
+            return Ok(turn.text_parts.concat());
+        }
+        for text in &turn.text_parts {
+            on_text(text);
+        }
+        let mut function_responses = Vec::with_capacity(turn.calls.len());
+        for call in turn.calls {
+            let name = call.name.clone();
+            let run = guard.observe(&call);
+            let response = match limits.repeat_limit {
+                Some(limit) if run > limit => json!({
+                    "error": format!(
+                        "`{name}` was called with the same arguments {run} times in a row. \
+                         Do not repeat it; answer with the results you already have."
+                    )
+                }),
+                // Failures go back to the model, with what to fix when the
+                // arguments were wrong.
+                _ => match tools.call(call.clone()).await {
+                    Ok(result) => json!({"value": result}),
+                    Err(e) => json!({"error": tools.report_failure(&call, &e)}),
+                },
+            };
+            function_responses.push(json!({
+                "functionResponse": {"name": name, "response": response}
+            }));
+        }
+        history.push(json!({"role": "function", "parts": function_responses}));
+    }
+    Err(AgentError::MaxRoundsExceeded {
+        rounds: limits.max_rounds,
+    }
+    .into())
+}
+
+/// [`run_turn`] against Gemini. `choice` applies to the first model
+/// response only; after that the model decides. Full declarations go out
+/// on the first turn, terse ones after that; see `tiers`.
+async fn gemini_chat<M>(
+    history: &mut Vec<Value>,
+    tools: &Profiled<'_, M>,
+    tiers: &mut TierTracker,
+    mut choice: ToolChoice,
     api_key: &str,
+    on_text: impl FnMut(&str),
 ) -> Result<String, Box<dyn std::error::Error>> {
     let client = reqwest::Client::new();
     let url = format!(
         "https://generativelanguage.googleapis.com/v1beta/models/gemini-2.0-flash:generateContent?key={}",
         api_key
     );
-    let tools_decl = tools.json()?;
+    let tier = tiers.tier(tools.registry_hash());
+    let tools_decl = tools.declarations_for_tier(Provider::Gemini, tier)?;
 
-    loop {
+    let model = async |history: &[Value]| -> Result<Value, Box<dyn std::error::Error>> {
+        let tool_config = gemini::tool_config(&std::mem::take(&mut choice), tools)?;
         let response = client
             .post(&url)
             .json(&json!({
-                "contents": &history,
-                "tools": {"functionDeclarations": tools_decl}
+                "contents": history,
+                "tools": {"functionDeclarations": tools_decl},
+                "toolConfig": tool_config
             }))
             .send()
             .await?;
@@ -76,39 +231,39 @@ async fn gemini_chat(
             return Err(format!("Gemini API error: {}", json).into());
         }
 
-        let res: Value = response.json().await?;
-
-        let content = &res["candidates"][0]["content"];
-        history.push(json!({"role": "model", "parts": content["parts"]}));
-
-        let parts = content["parts"].as_array().unwrap();
-        let mut function_responses: Vec<Value> = vec![];
-        for part in parts {
-            if let Some(fc) = part.get("functionCall") {
-                let result = tools
-                    .call(FunctionCall {
-                        name: fc["name"].as_str().unwrap().to_string(),
-                        arguments: fc["args"].clone(),
-                    })
-                    .await?;
-                function_responses.push(json!({
-                    "functionResponse": {"name": fc["name"], "response": {"value": result}}
-                }));
-            } else if let Some(text) = part["text"].as_str() {
-                return Ok(text.to_string());
-            }
-        }
-        history.push(json!({
-            "role": "function",
-            "parts": function_responses
-        }));
-    }
+        let mut res: Value = response.json().await?;
+        Ok(res["candidates"][0]["content"].take())
+    };
+    run_turn(history, tools, LoopLimits::default(), model, on_text).await
 }
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut history: Vec<Value> = vec![];
-    let api_key = std::env::var("GEMINI_API_KEY").expect("Api key not found!");
-    let tools = collect_tools();
+    let mut tiers = TierTracker::new();
+    let api_key = Secrets::env().require("GEMINI_API_KEY")?;
+    let mut tools = collect_tools();
+
+    // Two agents share the collection, each with its own tools and budget.
+    // Pick one with the first argument: `chatbot researcher` or
+    // `chatbot coder`.
+    tools.set_profile(
+        AgentProfile::new("researcher")
+            .allow(["search_web", "get_weather", "count_instance"])
+            .budget(50)
+            .timeout(Duration::from_secs(20)),
+    );
+    tools.set_profile(
+        AgentProfile::new("coder")
+            .allow(["count_instance", "send_email"])
+            .budget(20),
+    );
+    let name = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "researcher".into());
+    let agent = tools
+        .profile(&name)
+        .ok_or_else(|| format!("unknown profile `{name}`"))?;
 
     loop {
         print!(">");
@@ -116,9 +271,25 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         let mut prompt = String::new();
         std::io::stdin().read_line(&mut prompt)?;
 
+        // `/use <tool> <prompt>` forces the first call to go to <tool>.
+        let mut choice = ToolChoice::Auto;
+        if let Some(rest) = prompt.strip_prefix("/use ") {
+            let (tool, rest) = rest.split_once(' ').unwrap_or((rest.trim(), ""));
+            choice = ToolChoice::specific(tool);
+            prompt = rest.to_string();
+        }
+
         history.push(json!({"role": "user", "parts": [{"text": prompt.clone()}]}));
-        let value = gemini_chat(history.clone(), &tools, &api_key).await?;
-        history.push(json!({"role": "model", "parts": [{"text": value.clone() }]}));
+        let value = gemini_chat(
+            &mut history,
+            &agent,
+            &mut tiers,
+            choice,
+            api_key.expose(),
+            |text| println!("Model (working):\t{text}"),
+        )
+        .await?;
         println!("Model:\t{}", value);
+        println!("({name}: {:?})", agent.usage());
     }
 }
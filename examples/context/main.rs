@@ -18,13 +18,13 @@ use tools_rs::{FunctionCall, tool};
 
 // ---------- shared context ----------
 
-struct Context {
+pub(crate) struct Context {
     mailer_url: String,
     db_name: String,
 }
 
 impl Context {
-    fn build() -> Arc<Self> {
+    pub(crate) fn build() -> Arc<Self> {
         Arc::new(Context {
             mailer_url: "smtp://mail.example.com".into(),
             db_name: "app_production".into(),
@@ -79,7 +79,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             .is_none()
             && tools.get(name).unwrap().decl.parameters["properties"]
                 .as_object()
-                .map_or(true, |p| !p.is_empty())
+                .is_none_or(|p| !p.is_empty())
         {
             ""
         } else {
@@ -8,7 +8,7 @@
 //!
 //! 1. **`today()`**&nbsp;→ `String` &nbsp;— returns *today* as
 //!    `YYYY-MM-DD`.  
-//! 2. **`factorial(n)`**&nbsp;→ `u64` &nbsp;— computes `n!`.  
+//! 2. **`factorial(n)`**&nbsp;→ `u64` &nbsp;— computes `n!`, failing past `20!`.  
 //! 3. **`weather(loc)`**&nbsp;→ `WeatherInfo` &nbsp;— dummy forecast.
 //!
 //! Under the hood the [`tools_rs::tool`] macro
@@ -33,7 +33,9 @@
 
 use serde::{Deserialize, Serialize};
 use serde_json::{Value as JsonValue, json};
-use tools_rs::{FunctionCall, collect_tools, function_declarations, tool, ToolSchema};
+use tools_rs::{
+    FunctionCall, ToolError, ToolSchema, collect_tools, function_declarations, tool, tool_error,
+};
 
 // ────────────────────────────────────────────────────────────────────────────
 // Data Transfer Objects
@@ -66,8 +68,12 @@ async fn today() -> String {
 }
 #[tool]
 /// Compute the factorial of **`n`** (`0! = 1`, `1! = 1`, …).
-async fn factorial(n: u64) -> u64 {
-    (1..=n).product()
+async fn factorial(n: u64) -> Result<u64, ToolError> {
+    // 21! no longer fits in a u64; fail the call instead of wrapping.
+    match (1..=n).try_fold(1u64, u64::checked_mul) {
+        Some(f) => Ok(f),
+        None => tool_error!("factorial overflows u64 for n={n}"),
+    }
 }
 #[tool]
 /// Return a *fake* weather report for **`location`**.
@@ -131,6 +137,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .result;
     println!("5!     : {fact}");
 
+    let overflow = tools
+        .call(FunctionCall::new("factorial".into(), json!({ "n": 25 })))
+        .await
+        .unwrap_err();
+    println!("25!    : {overflow}");
+
     let meteo = tools
         .call(FunctionCall::new(
             "weather".into(),
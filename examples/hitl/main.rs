@@ -18,7 +18,7 @@ use std::io::{self, BufRead, Write};
 use serde::Deserialize;
 use serde_json::json;
 use tools_core::{validate_tool_attrs, ToolCollection};
-use tools_rs::{tool, FunctionCall};
+use tools_rs::{tool, FunctionCall, ToolError};
 
 // ---------- policy metadata ----------
 
@@ -49,8 +49,9 @@ enum Risk {
 
 #[tool]
 /// Read a file from disk and return its contents.
-async fn read_file(path: String) -> String {
-    format!("(pretend contents of {path})")
+async fn read_file(path: String) -> Result<String, ToolError> {
+    // A missing file fails as `NotFound`, a timeout as `Transient`.
+    std::fs::read_to_string(&path).map_err(ToolError::from_io)
 }
 
 #[tool]
@@ -178,8 +179,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             }
         }
 
-        let response = tools.call(call).await?;
-        println!("       result: {}", response.result);
+        match tools.call(call).await {
+            Ok(response) => println!("       result: {}", response.result),
+            Err(e) => println!("       failed ({}): {e}", e.classification()),
+        }
     }
 
     Ok(())
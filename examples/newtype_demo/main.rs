@@ -170,8 +170,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     };
     
     let booking_result = tools.call(FunctionCall {
+        id: None,
         name: "create_booking".to_string(),
         arguments: json!({ "request": booking_request }),
+        repaired: false,
     }).await?;
     
     println!("✅ Booking result: {}\n", booking_result);
@@ -185,8 +187,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     };
     
     let payment_result = tools.call(FunctionCall {
+        id: None,
         name: "process_payment".to_string(),
         arguments: json!({ "payment": payment_info }),
+        repaired: false,
     }).await?;
     
     println!("✅ Payment result: {}\n", payment_result);
@@ -199,8 +203,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     };
     
     let hotels_result = tools.call(FunctionCall {
+        id: None,
         name: "find_nearby_hotels".to_string(),
         arguments: json!({ "location": location }),
+        repaired: false,
     }).await?;
     
     println!("✅ Nearby hotels: {}\n", hotels_result);
@@ -208,6 +214,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // ───────── Show the difference with unclear parameters ─────────
     println!("⚠️  Compare with unclear parameters (error-prone):");
     let unclear_result = tools.call(FunctionCall {
+        id: None,
         name: "unclear_booking".to_string(),
         arguments: json!({
             "customer": 12345,
@@ -216,6 +223,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             "lat": 40.7128,
             "lng": -74.0060
         }),
+        repaired: false,
     }).await?;
     
     println!("❓ Unclear result: {}\n", unclear_result);
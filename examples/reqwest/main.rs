@@ -1,22 +1,25 @@
 use serde_json::{Value, json};
-use tools_rs::{FunctionCall, collect_tools, tool};
+use tools_rs::{ErrorClass, FunctionCall, Provider, Secrets, ToolError, collect_tools, tool};
 
 #[tool]
 /// Gets the current temperature for given coordinates
-async fn get_weather(lat: f64, lon: f64) -> Result<f64, String> {
+async fn get_weather(lat: f64, lon: f64) -> Result<f64, ToolError> {
     let url = format!(
         "https://api.open-meteo.com/v1/forecast?latitude={}&longitude={}&current=temperature_2m,wind_speed_10m&hourly=temperature_2m,relative_humidity_2m,wind_speed_10m",
         lat, lon
     );
 
-    let response = reqwest::get(&url).await.map_err(|e| e.to_string())?;
+    // The network may come back; a malformed answer won't.
+    let response = reqwest::get(&url)
+        .await
+        .map_err(|e| ToolError::other(e).with_class(ErrorClass::Transient))?;
 
-    let json: Value = response.json().await.map_err(|e| e.to_string())?;
+    let json: Value = response.json().await.map_err(ToolError::other)?;
 
     json.get("current")
         .and_then(|current| current.get("temperature_2m"))
         .and_then(|temp| temp.as_f64())
-        .ok_or("Missing temperature_2m in response".to_string())
+        .ok_or_else(|| ToolError::other("Missing temperature_2m in response"))
 }
 
 #[tool]
@@ -42,7 +45,7 @@ async fn gemini_chat(
         api_key
     );
     let mut history = vec![json!({"role": "user", "parts": [{"text": prompt}]})];
-    let tools_decl = tools.json()?;
+    let tools_decl = tools.declarations_for(Provider::Gemini)?;
 
     loop {
         let response = client
@@ -72,14 +75,16 @@ async fn gemini_chat(
         let mut function_responses: Vec<Value> = vec![];
         for part in parts {
             if let Some(fc) = part.get("functionCall") {
-                let result = tools
-                    .call(FunctionCall {
-                        name: fc["name"].as_str().unwrap().to_string(),
-                        arguments: fc["args"].clone(),
-                    })
-                    .await?;
+                let call =
+                    FunctionCall::new(fc["name"].as_str().unwrap().to_string(), fc["args"].clone());
+                // A failed call goes back to the model with its class, so
+                // it can tell a flaky network from a bad request.
+                let response = match tools.call(call).await {
+                    Ok(result) => json!({ "value": result }),
+                    Err(e) => json!({ "error": e.report() }),
+                };
                 function_responses.push(json!({
-                    "functionResponse": {"name": fc["name"], "response": {"value": result}}
+                    "functionResponse": {"name": fc["name"], "response": response}
                 }));
             } else if let Some(text) = part["text"].as_str() {
                 return Ok(text.to_string());
@@ -94,10 +99,11 @@ async fn gemini_chat(
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let api_key = Secrets::env().require("GEMINI_API_KEY")?;
     let response = gemini_chat(
         "How many letter rs are in the word ratatouille. Also, what's the weather like today in Paris? Could you also send an email to bob (bob@gmail.com) saying how much I liked yesterday's invite?",
         &collect_tools(),
-        &std::env::var("GEMINI_API_KEY")?,
+        api_key.expose(),
     )
     .await?;
 
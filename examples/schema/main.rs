@@ -157,7 +157,7 @@ async fn main() -> Result<(), Box<dyn Error>> {
         .ok_or("function_declarations() did not return a JSON array")?;
 
     // The declarations already come in the correct OpenAI format
-    let tools_field: Vec<JsonValue> = decl_array.iter().cloned().collect();
+    let tools_field: Vec<JsonValue> = decl_array.to_vec();
 
     let chat_request = json!({
         "model": "gpt-4o",
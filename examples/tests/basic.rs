@@ -0,0 +1,13 @@
+//! Declarations of the `basic` example, pinned in `snapshots/basic.json`.
+
+use tools_rs::{ToolCollection, assert_declarations_snapshot};
+
+#[path = "../basic/main.rs"]
+#[allow(dead_code)]
+mod example;
+
+#[test]
+fn declarations_match_snapshot() {
+    let tools: ToolCollection = ToolCollection::collect_tools().unwrap();
+    assert_declarations_snapshot!(tools, "snapshots/basic.json");
+}
@@ -0,0 +1,176 @@
+//! Declarations of the `chatbot` example, pinned in `snapshots/chatbot.json`
+//! and, cut down for later turns, in `snapshots/chatbot.terse.json`.
+
+use std::path::Path;
+
+use tools_rs::{
+    DeclTier, ToolCollection, assert_declarations_snapshot, hash::canonical_json_pretty,
+    testing::assert_snapshot_text,
+};
+
+#[path = "../chatbot/main.rs"]
+#[allow(dead_code)]
+mod example;
+
+#[test]
+fn declarations_match_snapshot() {
+    let tools: ToolCollection = ToolCollection::collect_tools().unwrap();
+    assert_declarations_snapshot!(tools, "snapshots/chatbot.json");
+}
+
+#[test]
+fn both_tiers_match_snapshots() {
+    let tools: ToolCollection = ToolCollection::collect_tools().unwrap();
+    // Returns the size of the tools array as sent.
+    let snapshot = |tier, path: &str| {
+        let decls = tools.json_tier(tier).unwrap();
+        let text = canonical_json_pretty(&decls) + "\n";
+        assert_snapshot_text(&Path::new(env!("CARGO_MANIFEST_DIR")).join(path), &text);
+        decls.to_string().len()
+    };
+    let full = snapshot(DeclTier::Full, "snapshots/chatbot.json");
+    let terse = snapshot(DeclTier::Terse, "snapshots/chatbot.terse.json");
+    // Every turn after the first sends at least a fifth fewer bytes.
+    assert!(
+        terse * 5 <= full * 4,
+        "full {full} bytes, terse {terse} bytes"
+    );
+}
+
+#[test]
+fn mixed_gemini_turn_keeps_text_and_calls() {
+    // Captured from gemini-2.0-flash: commentary, then two calls.
+    let content = serde_json::json!({
+        "role": "model",
+        "parts": [
+            {"text": "Let me check the weather in both cities."},
+            {"functionCall": {"name": "get_weather", "args": {"lat": 48.85, "lon": 2.35}}},
+            {"functionCall": {"name": "get_weather", "args": {"lat": 51.51, "lon": -0.13}}}
+        ]
+    });
+    let turn = example::TurnOutput::from_gemini(&content);
+    assert_eq!(
+        turn.text_parts,
+        ["Let me check the weather in both cities."]
+    );
+    let names: Vec<_> = turn.calls.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, ["get_weather", "get_weather"]);
+    assert_eq!(turn.calls[1].arguments["lon"], -0.13);
+
+    let text_only = serde_json::json!({"parts": [{"text": "Paris is "}, {"text": "warmer."}]});
+    let turn = example::TurnOutput::from_gemini(&text_only);
+    assert!(turn.calls.is_empty());
+    assert_eq!(turn.text_parts.concat(), "Paris is warmer.");
+}
+
+fn count_call(parts: &[serde_json::Value]) -> serde_json::Value {
+    let mut parts = parts.to_vec();
+    parts.push(serde_json::json!({
+        "functionCall": {"name": "count_instance", "args": {"s": "banana", "sub": "an"}}
+    }));
+    serde_json::json!({ "role": "model", "parts": parts })
+}
+
+fn agent_tools() -> ToolCollection {
+    let mut tools: ToolCollection = ToolCollection::collect_tools().unwrap();
+    tools.set_profile(tools_rs::AgentProfile::new("test").allow(["count_instance"]));
+    tools
+}
+
+#[tokio::test]
+async fn repeated_calls_get_an_error_until_the_model_stops() {
+    let tools = agent_tools();
+    let agent = tools.profile("test").unwrap();
+    let limits = example::LoopLimits {
+        max_rounds: 10,
+        repeat_limit: Some(2),
+    };
+    let mut history = vec![];
+    let mut rounds = 0;
+    // Repeats the same call until it is told to stop.
+    let model = async |history: &[serde_json::Value]| {
+        rounds += 1;
+        let told_to_stop = history.last().is_some_and(|turn| {
+            turn["parts"][0]["functionResponse"]["response"]["error"].is_string()
+        });
+        Ok(if told_to_stop {
+            serde_json::json!({"parts": [{"text": "There are 2."}]})
+        } else {
+            count_call(&[])
+        })
+    };
+    let text = example::run_turn(&mut history, &agent, limits, model, |_| {})
+        .await
+        .unwrap();
+
+    assert_eq!(text, "There are 2.");
+    assert_eq!(rounds, 4);
+    let responses: Vec<_> = history
+        .iter()
+        .filter(|turn| turn["role"] == "function")
+        .map(|turn| &turn["parts"][0]["functionResponse"]["response"])
+        .collect();
+    assert_eq!(responses[0]["value"]["result"], 2);
+    assert_eq!(responses[1]["value"]["result"], 2);
+    let error = responses[2]["error"].as_str().unwrap();
+    assert!(error.contains("3 times in a row"), "{error}");
+}
+
+#[tokio::test]
+async fn rounds_are_capped() {
+    let tools = agent_tools();
+    let agent = tools.profile("test").unwrap();
+    let limits = example::LoopLimits {
+        max_rounds: 3,
+        repeat_limit: None,
+    };
+    let mut history = vec![];
+    let mut commentary = vec![];
+    let model = async |_: &[serde_json::Value]| {
+        Ok(count_call(&[
+            serde_json::json!({"text": "Counting again."}),
+        ]))
+    };
+    let err = example::run_turn(&mut history, &agent, limits, model, |text| {
+        commentary.push(text.to_string())
+    })
+    .await
+    .unwrap_err();
+
+    assert!(matches!(
+        err.downcast_ref::<example::AgentError>(),
+        Some(example::AgentError::MaxRoundsExceeded { rounds: 3 })
+    ));
+    assert_eq!(commentary.len(), 3);
+    assert_eq!(history.len(), 6);
+}
+
+#[tokio::test]
+async fn bad_arguments_are_explained_to_the_model() {
+    let tools = agent_tools();
+    let agent = tools.profile("test").unwrap();
+    let mut history = vec![];
+    let mut sent_bad_call = false;
+    let model = async |_: &[serde_json::Value]| {
+        Ok(if std::mem::replace(&mut sent_bad_call, true) {
+            serde_json::json!({"parts": [{"text": "Sorry."}]})
+        } else {
+            serde_json::json!({"parts": [{
+                "functionCall": {"name": "count_instance", "args": {"s": 1, "subb": "an"}}
+            }]})
+        })
+    };
+    example::run_turn(&mut history, &agent, Default::default(), model, |_| {})
+        .await
+        .unwrap();
+
+    let error = &history[1]["parts"][0]["functionResponse"]["response"]["error"];
+    assert_eq!(error["class"], "invalid_input");
+    let explanation = &error["explanation"];
+    assert_eq!(explanation["mismatched"][0]["path"], "$.s");
+    assert_eq!(explanation["unexpected"][0]["did_you_mean"], "sub");
+    assert_eq!(
+        explanation["example"],
+        serde_json::json!({"s": "", "sub": ""})
+    );
+}
@@ -0,0 +1,16 @@
+//! Declarations of the `context` example, pinned in `snapshots/context.json`.
+
+use tools_rs::{ToolCollection, assert_declarations_snapshot};
+
+#[path = "../context/main.rs"]
+#[allow(dead_code)]
+mod example;
+
+#[test]
+fn declarations_match_snapshot() {
+    let tools: ToolCollection = ToolCollection::builder()
+        .with_context(example::Context::build())
+        .collect()
+        .unwrap();
+    assert_declarations_snapshot!(tools, "snapshots/context.json");
+}
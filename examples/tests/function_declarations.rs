@@ -0,0 +1,13 @@
+//! Declarations of the `function_declarations` example, pinned in `snapshots/function_declarations.json`.
+
+use tools_rs::{ToolCollection, assert_declarations_snapshot};
+
+#[path = "../function_declarations/main.rs"]
+#[allow(dead_code)]
+mod example;
+
+#[test]
+fn declarations_match_snapshot() {
+    let tools: ToolCollection = ToolCollection::collect_tools().unwrap();
+    assert_declarations_snapshot!(tools, "snapshots/function_declarations.json");
+}
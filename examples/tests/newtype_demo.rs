@@ -0,0 +1,13 @@
+//! Declarations of the `newtype_demo` example, pinned in `snapshots/newtype_demo.json`.
+
+use tools_rs::{ToolCollection, assert_declarations_snapshot};
+
+#[path = "../newtype_demo/main.rs"]
+#[allow(dead_code)]
+mod example;
+
+#[test]
+fn declarations_match_snapshot() {
+    let tools: ToolCollection = ToolCollection::collect_tools().unwrap();
+    assert_declarations_snapshot!(tools, "snapshots/newtype_demo.json");
+}
@@ -0,0 +1,29 @@
+//! Every document a fixture tool's schema accepts must decode.
+//!
+//! `cargo +nightly fuzz run schema_vs_serde` from the repository root.
+//! Divergences are minimized and written under
+//! `tests-integration/divergences/<tool>/`, where the
+//! `schemas_accept_only_what_decodes` test replays them.
+
+#![no_main]
+
+use std::sync::{Arc, LazyLock};
+
+use libfuzzer_sys::fuzz_target;
+use tests_integration::{Counter, tools};
+use tools_rs::{ToolCollection, testing::fuzz_one};
+
+static TOOLS: LazyLock<ToolCollection> =
+    LazyLock::new(|| tools(Arc::new(Counter::default())).unwrap());
+
+const CORPUS: &str = concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/../tests-integration/divergences"
+);
+
+fuzz_target!(|input: &[u8]| {
+    if let Some(divergence) = fuzz_one(&TOOLS, input) {
+        let path = divergence.write_to(CORPUS).unwrap();
+        panic!("{divergence}\nwritten to {}", path.display());
+    }
+});
@@ -47,19 +47,241 @@
 //! # Ok(())
 //! # }
 //! ```
+//!
+//! ## Typed Calls
+//!
+//! `#[tool]` emits a [`ToolRef`] named `<NAME>_TOOL` and `register`
+//! returns one. [`ToolCollection::invoke`] takes the parameters as a tuple
+//! and returns the tool's own output type:
+//!
+//! ```rust
+//! use tools_rs::{collect_tools, tool};
+//!
+//! #[tool]
+//! /// Adds two numbers together
+//! async fn add(a: i32, b: i32) -> i32 {
+//!     a + b
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), tools_rs::ToolError> {
+//! let tools = collect_tools();
+//! let sum: i32 = tools.invoke(&ADD_TOOL, (1, 2)).await?;
+//! assert_eq!(sum, 3);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Arguments of the wrong type don't compile:
+//!
+//! ```compile_fail
+//! use tools_rs::{collect_tools, tool};
+//!
+//! #[tool]
+//! /// Adds two numbers together
+//! async fn add(a: i32, b: i32) -> i32 {
+//!     a + b
+//! }
+//!
+//! # async fn run() {
+//! // ERROR: `add` takes `(i32, i32)`
+//! let _ = collect_tools().invoke(&ADD_TOOL, ("one", 2)).await;
+//! # }
+//! ```
+//!
+//! ## Descriptions From Files And Constants
+//!
+//! A tool's description is its doc comment unless
+//! `#[tool(description_from = "prompts/search.txt")]` names a file,
+//! relative to the crate root, or `#[tool(description_const = SEARCH_DESC)]`
+//! names a `&'static str` const. Either attribute takes precedence over the
+//! doc comment. A missing file is a compile error naming the resolved
+//! path:
+//!
+//! ```compile_fail
+//! use tools_rs::tool;
+//!
+//! // ERROR: cannot read `description_from` file `.../prompts/none.txt`
+//! #[tool(description_from = "prompts/none.txt")]
+//! async fn search(query: String) -> String {
+//!     query
+//! }
+//! ```
+//!
+//! `#[tool(name = "search-web.v2", description = "...")]` overrides the
+//! function name and doc comment. Names are limited to 1 to 64 ASCII
+//! letters, digits, `_`, `-` and `.`:
+//!
+//! ```compile_fail
+//! use tools_rs::tool;
+//!
+//! // ERROR: invalid tool name "search web"
+//! #[tool(name = "search web")]
+//! async fn search(query: String) -> String {
+//!     query
+//! }
+//! ```
+//!
+//! ## Enum Schemas
+//!
+//! `#[derive(ToolSchema)]` follows the enum's serde representation.
+//! `#[serde(untagged)]` becomes an `anyOf` of the variant schemas, and a
+//! `#[serde(other)]` variant lets the tag be any string:
+//!
+//! ```rust
+//! use serde::Deserialize;
+//! use tools_rs::ToolSchema;
+//!
+//! #[derive(Deserialize, ToolSchema)]
+//! #[serde(untagged)]
+//! enum UserRef {
+//!     Id(u64),
+//!     Name(String),
+//! }
+//!
+//! assert!(UserRef::schema()["anyOf"].is_array());
+//! ```
+//!
+//! Attribute combinations serde rejects are rejected with serde's message:
+//!
+//! ```compile_fail
+//! use tools_rs::ToolSchema;
+//!
+//! // ERROR: enum cannot be both untagged and internally tagged
+//! #[derive(ToolSchema)]
+//! #[serde(untagged, tag = "kind")]
+//! enum Bad {
+//!     A { x: i32 },
+//! }
+//! ```
+//!
+//! ```compile_fail
+//! use tools_rs::ToolSchema;
+//!
+//! // ERROR: #[serde(other)] cannot appear on untagged enum
+//! #[derive(ToolSchema)]
+//! #[serde(untagged)]
+//! enum Bad {
+//!     A(i32),
+//!     #[serde(other)]
+//!     Unknown,
+//! }
+//! ```
+//!
+//! ```compile_fail
+//! use tools_rs::ToolSchema;
+//!
+//! // ERROR: #[serde(other)] must be on the last variant
+//! #[derive(ToolSchema)]
+//! enum Bad {
+//!     #[serde(other)]
+//!     Unknown,
+//!     A,
+//! }
+//! ```
 
 // Re-export core functionality
 pub use tools_core::{
-    CallId, CollectionBuilder, DeserializationError, FunctionCall, FunctionDecl, FunctionResponse,
-    Language, RawToolDef, ToolCollection, ToolError, ToolMetadata, ToolRegistration, ToolsBuilder,
-    TypeSignature,
+    AgentProfile, BatchMode, BoundConflict, CachePolicy, CacheReport, CallId, CallOutcome, CallSeq, CircuitConfig,
+    CollectionBuilder, CollectionReport, DeclFilter, DeclOverride, DeclOverrides, DeclTier, DeclarationPage, DecodedInput,
+    DeserializationError, EnvSecrets, ErrorClass, ErrorReport, FailureEvent, FailureRecord,
+    FailureSink, FileJournal, Fingerprint, FingerprintTool, FunctionCall, FunctionDecl,
+    FunctionResponse, Intent, Invocation, Invocations, Journal, JournalOutcome, Language, Migration,
+    MigrationMode, MigrationSpec, NameNormalization, NameRegistry, OverriddenView, OverrideReport, Overrides, Patch,
+    ProfileUsage, Profiled,
+    PromptStyle, Provider, QueueReport, QueuedReport, QueuedTools, RawToolDef, RecentCall,
+    RegistrationFinding, ScheduleConfig, ScheduleHandle, ScheduleReport, SchemaDialect, Secret, SecretString, Secrets, SecretsProvider, SharedToolCollection, StaticSecrets, Strictness, TierTracker, ToolChoice,
+    ToolCollection, ToolError, ToolGuard, ToolId, ToolMetadata, ToolOrigin, ToolRef,
+    ToolRegistration, ToolReport, ToolSpec, ToolsBuilder, TypeSignature, UnknownArgs, UsageEvent,
+    UsageOutcome, UsageSink, WarmUpReport,
+    inventory_collections, parse_freeform_call, warm_up_inventory,
 };
 
 // Re-export schema functionality (trait from tools_core)
 pub use tools_core::ToolSchema;
 
-// Re-export macros (both tool attribute and ToolSchema derive)
-pub use tools_macros::{ToolSchema, tool};
+// Re-export the three-state helpers (`Patch`, `double_option`)
+pub use tools_core::patch;
+
+// Re-export circuit breaker clocks and state (`ManualClock`, `CircuitState`)
+pub use tools_core::circuit;
+
+// Re-export deadline scoping (`with_deadline`, `remaining`)
+pub use tools_core::deadline;
+
+// Re-export declaration overrides (`merge_patch`)
+pub use tools_core::decl_overrides;
+
+// Re-export agent profiles (`current`)
+pub use tools_core::profile;
+
+// Re-export panic capture (`message`)
+pub use tools_core::panic;
+
+// Re-export registration check codes (`codes`)
+pub use tools_core::checks;
+
+// Re-export tool_choice adapters (`openai`, `anthropic`, `gemini`)
+pub use tools_core::choice;
+
+// Re-export call ordering keys (`next`, `DEFAULT_RECENT_CALLS`)
+pub use tools_core::sequence;
+
+// Re-export secret redaction (`redact`, `REDACTED`)
+pub use tools_core::secrets;
+
+// Re-export unknown-argument helpers (`suggest`, `unknown_keys`)
+pub use tools_core::unknown;
+
+// Re-export provider schema passes (`transform::apply`, `transform::inline_refs`)
+pub use tools_core::schema;
+
+// Re-export argument migrations (`rename_keys`)
+pub use tools_core::migrate;
+
+// Re-export canonical argument hashing (`canonical_hash`, `canonical_json`, `canonical_json_pretty`)
+pub use tools_core::hash;
+
+// Re-export domain error envelopes (`error_envelope`)
+pub use tools_core::domain;
+
+// Re-export the compile-time tool manifest (`enable`, `load`, `load_compile_time`)
+pub use tools_core::manifest;
+
+// Re-export failure explanations (`explain_failure`, `example`)
+pub use tools_core::diagnostics;
+
+// Re-export result compaction (`compact`)
+pub use tools_core::compact;
+
+// Re-export bare-input wrapping (`bare_type`, `wrap_schema`)
+pub use tools_core::wrap;
+
+// Re-export conversation scoping (`with_conversation`, `conversation`)
+pub use tools_core::conversation;
+
+// Re-export usage export (`JsonlUsageSink` with `usage-jsonl`)
+pub use tools_core::usage;
+
+// Re-export protobuf-message tools (`json_schema`)
+#[cfg(feature = "prost")]
+pub use tools_core::proto;
+
+// Re-export snapshot assertions (`assert_snapshot_text`, `diff`), contract tests
+// and schema/decoding differential checks
+#[cfg(feature = "testing")]
+pub use tools_core::{assert_declarations_snapshot, testing};
+
+// Re-export error shorthands (`tool_error!`, `ensure_tool!`)
+pub use tools_core::{ensure_tool, tool_error};
+
+// Re-export macros (`tool` and `tool_resource` attributes, `ToolSchema` derive)
+pub use tools_macros::{ToolSchema, tool, tool_resource};
+
+// Path `#[tool]` expansions use when the calling crate depends on tools-rs
+// but not on tools_core directly
+#[doc(hidden)]
+pub use tools_core as __core;
 
 /// Convenient imports for common usage patterns.
 ///
@@ -72,7 +294,9 @@ pub mod prelude;
 /// Collect all tools registered via the `#[tool]` macro.
 ///
 /// This function discovers all tools that were registered at compile time
-/// using the `#[tool]` attribute macro.
+/// using the `#[tool]` attribute macro. It panics if two crates define a
+/// tool of the same name; see [`collect_tools_namespaced`]. Each call
+/// builds a new collection; [`global_tools`] shares one.
 ///
 /// # Example
 ///
@@ -84,17 +308,75 @@ pub mod prelude;
 /// ```
 #[inline]
 pub fn collect_tools() -> ToolCollection {
-    // `NoMeta` deserializes from any JSON object, so this never fails in
-    // practice — a panic here means the `#[tool]` macro emitted malformed
-    // JSON, which is a bug in tools-rs itself. Typed collections that can
-    // realistically fail use `ToolCollection::<M>::collect_tools()?`.
-    ToolCollection::collect_tools()
-        .expect("tools-rs internal error: macro emitted malformed meta_json")
+    // `NoMeta` deserializes from any JSON object, so this only fails when
+    // two crates define a `#[tool]` of the same name; the panic names both. Typed
+    // collections use `ToolCollection::<M>::collect_tools()?`.
+    ToolCollection::collect_tools().unwrap_or_else(|e| panic!("{e}"))
+}
+
+/// The process-wide collection of `#[tool]` functions behind
+/// [`call_tool`], [`call_tool_typed`] and [`function_declarations`].
+///
+/// Built by [`collect_tools`] on first use and shared from then on, so
+/// repeated calls don't walk the inventory or rebuild declarations again.
+/// It can't be changed: tools registered by hand, a context or any other
+/// setting need a collection of their own.
+///
+/// ```rust
+/// use tools_rs::global_tools;
+///
+/// let names: Vec<_> = global_tools().descriptions().map(|(name, _)| name).collect();
+/// ```
+pub fn global_tools() -> &'static ToolCollection {
+    static GLOBAL: std::sync::LazyLock<ToolCollection> = std::sync::LazyLock::new(collect_tools);
+    &GLOBAL
+}
+
+/// Like [`collect_tools`], but each tool is named after its defining crate,
+/// e.g. `net_tools.ping` with `separator` `"."`.
+pub fn collect_tools_namespaced(separator: &str) -> Result<ToolCollection, ToolError> {
+    ToolCollection::collect_tools_namespaced(separator)
+}
+
+/// Build a collection holding only the named `#[tool]` functions.
+///
+/// The `#[tool]` inventory is process-global, so every test in a binary
+/// sees every tool. `test_tools!` gives each test its own collection of
+/// exactly the tools it uses. Tests can then `register` extra tools under
+/// any free name without colliding with other tests. It panics if a name
+/// does not match a registered tool.
+///
+/// ```rust
+/// use tools_rs::{test_tools, tool};
+///
+/// #[tool]
+/// /// Adds two numbers.
+/// async fn add(a: i32, b: i32) -> i32 { a + b }
+///
+/// let tools = test_tools![add];
+/// assert_eq!(tools.len(), 1);
+/// ```
+#[macro_export]
+macro_rules! test_tools {
+    ($($name:ident),* $(,)?) => {{
+        let names: &[&str] = &[$(stringify!($name)),*];
+        let tools: $crate::ToolCollection =
+            $crate::ToolCollection::collect_tools_filtered(|n| names.contains(&n))
+                .expect("test_tools!: failed to collect tools");
+        for name in names {
+            assert!(
+                tools.resolve(name).is_some(),
+                "test_tools!: no `#[tool]` named `{}` is registered",
+                name
+            );
+        }
+        tools
+    }};
 }
 
 /// Generate function declarations in JSON format for LLM consumption.
 ///
-/// This is equivalent to `collect_tools().json()` but provides a more
+/// This is equivalent to `global_tools().json()` but provides a more
 /// convenient API for the common use case of generating LLM-compatible
 /// function declarations.
 ///
@@ -109,14 +391,14 @@ pub fn collect_tools() -> ToolCollection {
 /// ```
 #[inline]
 pub fn function_declarations() -> Result<serde_json::Value, ToolError> {
-    collect_tools().json()
+    global_tools().json()
 }
 
 /// Call a tool by name with JSON arguments.
 ///
-/// This is a convenience function that combines tool collection and execution
-/// in a single call. Useful for simple scenarios where you don't need to
-/// manage the tool collection yourself.
+/// This is a convenience function that calls the tool on [`global_tools`].
+/// Useful for simple scenarios where you don't need to manage the tool
+/// collection yourself.
 ///
 /// # Arguments
 ///
@@ -138,9 +420,8 @@ pub async fn call_tool(
     name: &str,
     arguments: serde_json::Value,
 ) -> Result<FunctionResponse, ToolError> {
-    let tools = collect_tools();
     let call = FunctionCall::new(name.to_string(), arguments);
-    tools.call(call).await
+    global_tools().call(call).await
 }
 
 /// Call a tool by name with typed arguments.
@@ -176,6 +457,31 @@ pub async fn call_tool_with<T: serde::Serialize>(
     call_tool(name, arguments).await
 }
 
+/// Call a tool by name with JSON arguments and decode its result.
+///
+/// Like [`call_tool`], but the result comes back as `O` instead of JSON.
+/// A result that doesn't decode fails with [`ToolError::Deserialize`]
+/// naming the tool and `O`; see [`ToolCollection::call_typed`].
+///
+/// # Example
+///
+/// ```rust
+/// use tools_rs::call_tool_typed;
+/// use serde_json::json;
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let sum: i64 = call_tool_typed("add", json!({"a": 1, "b": 2})).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn call_tool_typed<O: serde::de::DeserializeOwned>(
+    name: &str,
+    arguments: serde_json::Value,
+) -> Result<O, ToolError> {
+    let call = FunctionCall::new(name.to_string(), arguments);
+    global_tools().call_typed(call).await
+}
+
 /// Call a tool by name with JSON arguments on a given collection.
 ///
 /// # Example
@@ -233,7 +539,7 @@ pub async fn call_tool_with_args<T: serde::Serialize>(
 /// let names = list_tool_names(&tools);
 /// println!("Available tools: {:?}", names);
 /// ```
-pub fn list_tool_names(collection: &ToolCollection) -> Vec<&'static str> {
+pub fn list_tool_names(collection: &ToolCollection) -> Vec<&str> {
     collection.descriptions().map(|(name, _)| name).collect()
 }
 
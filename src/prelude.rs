@@ -9,18 +9,18 @@
 
 // Core functionality
 pub use crate::{
-    call_tool, call_tool_by_name, call_tool_with, call_tool_with_args, collect_tools,
-    function_declarations, list_tool_names,
+    call_tool, call_tool_by_name, call_tool_typed, call_tool_with, call_tool_with_args,
+    collect_tools, function_declarations, global_tools, list_tool_names,
 };
 
 // Essential types
 pub use crate::{
     CallId, CollectionBuilder, FunctionCall, FunctionDecl, FunctionResponse, Language,
-    ToolCollection, ToolError, ToolMetadata, ToolSchema, ToolsBuilder,
+    ToolCollection, ToolError, ToolMetadata, ToolRef, ToolSchema, ToolsBuilder,
 };
 
 // Macros
-pub use crate::tool;
+pub use crate::{ensure_tool, tool, tool_error, tool_resource};
 
 // Commonly used external types
 pub use serde_json::{Value, json};
@@ -0,0 +1,5 @@
+//! Turns on the `#[tool]` manifest for this crate only.
+
+fn main() {
+    tools_core::manifest::enable();
+}
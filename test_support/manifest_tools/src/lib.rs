@@ -0,0 +1,21 @@
+//! Test fixture for the compile-time manifest; see `tests/manifest.rs`.
+
+use tools_macros::tool;
+
+#[tool(category = "math")]
+/// Adds two integers.
+pub async fn add(a: i64, b: i64) -> i64 {
+    a + b
+}
+
+#[tool]
+/// Looks up a word.
+pub async fn define(word: String, limit: Option<u32>) -> Vec<String> {
+    vec![word; limit.unwrap_or(1) as usize]
+}
+
+#[tool]
+/// Liveness check.
+pub async fn ping() -> String {
+    "pong".to_string()
+}
@@ -0,0 +1,72 @@
+//! The fixture's build script turns the manifest on, so compiling the
+//! library recorded each of its tools.
+
+use std::{fs, path::Path};
+
+use serde_json::json;
+use tools_core::manifest::{self, FILE_NAME, ManifestParam};
+
+fn path() -> &'static Path {
+    Path::new(concat!(env!("OUT_DIR"), "/tools-rs-manifest.jsonl"))
+}
+
+#[test]
+fn lists_every_tool() {
+    assert!(path().ends_with(FILE_NAME));
+    let tools = manifest::load(path()).unwrap();
+    let names: Vec<_> = tools.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names, ["add", "define", "ping"]);
+    assert!(tools.iter().all(|t| t.crate_name == "manifest_tools"));
+}
+
+#[test]
+fn entries_describe_the_signature() {
+    let tools = manifest::load(path()).unwrap();
+    let add = &tools[0];
+    assert_eq!(add.doc, "Adds two integers.");
+    assert_eq!(add.schema_fn, "<__TOOL_INPUT_add as ToolSchema>::schema");
+    assert_eq!(add.meta, json!({ "category": "math" }));
+    assert_eq!(
+        tools[1].params,
+        [
+            ManifestParam {
+                name: "word".into(),
+                ty: "String".into()
+            },
+            ManifestParam {
+                name: "limit".into(),
+                ty: "Option < u32 >".into()
+            },
+        ]
+    );
+    assert!(tools[2].params.is_empty());
+}
+
+#[test]
+fn load_keeps_the_latest_line_per_tool() {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR"));
+    let copy = dir.join("manifest-dedupe.jsonl");
+    let text = fs::read_to_string(path()).unwrap();
+    let renamed = text.replace("Adds two integers.", "Adds two numbers.");
+    fs::write(&copy, format!("{text}\n{renamed}")).unwrap();
+
+    let tools = manifest::load(&copy).unwrap();
+    assert_eq!(tools.len(), 3);
+    assert_eq!(tools[0].doc, "Adds two numbers.");
+    assert!(
+        manifest::load(&dir.join("missing.jsonl"))
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn load_compile_time_reads_out_dir() {
+    // Cargo sets `OUT_DIR` for build scripts, and for tests it runs.
+    if std::env::var_os("OUT_DIR").is_some() {
+        assert_eq!(
+            manifest::load_compile_time().unwrap(),
+            manifest::load(path()).unwrap()
+        );
+    }
+}
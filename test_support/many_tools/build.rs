@@ -0,0 +1,28 @@
+//! Generates `TOOLS` `#[tool]` functions, the way a crate generated from
+//! an API spec would look.
+
+use std::{env, fmt::Write, fs, path::Path};
+
+const TOOLS: usize = 1000;
+
+/// Parameter lists cycled through by the generated tools.
+const SIGNATURES: [&str; 4] = [
+    "a: i64, b: i64",
+    "name: String, limit: Option<u32>",
+    "tags: Vec<String>, verbose: bool, note: Option<String>",
+    "",
+];
+
+fn main() {
+    let mut out = String::new();
+    for i in 0..TOOLS {
+        let params = SIGNATURES[i % SIGNATURES.len()];
+        writeln!(
+            out,
+            "#[tool]\n/// Generated tool {i}.\npub async fn op_{i:04}({params}) -> usize {{ {i} }}\n"
+        )
+        .unwrap();
+    }
+    let path = Path::new(&env::var("OUT_DIR").unwrap()).join("tools.rs");
+    fs::write(path, out).unwrap();
+}
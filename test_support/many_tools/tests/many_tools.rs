@@ -0,0 +1,68 @@
+//! All 1000 generated tools collect, declare the expected schemas and
+//! run.
+
+use serde_json::json;
+use tools_core::{FunctionCall, NoMeta, ToolCollection};
+
+fn tools() -> ToolCollection {
+    // Link the fixture's inventory submissions.
+    let _ = many_tools::op_0000;
+    ToolCollection::<NoMeta>::collect_tools().unwrap()
+}
+
+#[test]
+fn every_tool_is_collected() {
+    let tools = tools();
+    assert_eq!(tools.len(), 1000);
+    assert_eq!(tools.json().unwrap().as_array().unwrap().len(), 1000);
+}
+
+#[test]
+fn schemas_match_the_signatures() {
+    let tools = tools();
+    let params = |name: &str| tools.get(name).unwrap().decl.parameters.clone();
+    assert_eq!(
+        params("op_0000"),
+        json!({
+            "type": "object",
+            "properties": {
+                "a": { "type": "integer", "minimum": i64::MIN, "maximum": i64::MAX },
+                "b": { "type": "integer", "minimum": i64::MIN, "maximum": i64::MAX }
+            },
+            "required": ["a", "b"]
+        })
+    );
+    assert_eq!(
+        params("op_0001"),
+        json!({
+            "type": "object",
+            "properties": {
+                "name": { "type": "string" },
+                "limit": {
+                    "anyOf": [
+                        { "type": "integer", "minimum": 0, "maximum": u32::MAX },
+                        { "type": "null" }
+                    ]
+                }
+            },
+            "required": ["name"]
+        })
+    );
+    assert_eq!(params("op_0998")["required"], json!(["tags", "verbose"]));
+    assert_eq!(
+        params("op_0999"),
+        json!({ "type": "object", "properties": {}, "required": [] })
+    );
+}
+
+#[tokio::test]
+async fn tools_run() {
+    let out = tools()
+        .call(FunctionCall::new(
+            "op_0500".into(),
+            json!({ "a": 1, "b": 2 }),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(out.result, 500);
+}
@@ -0,0 +1,9 @@
+//! Test fixture for multi-crate inventories; see `tests/namespaced.rs`.
+
+use tools_macros::tool;
+
+#[tool]
+/// Replies from crate `ping_a`.
+pub async fn ping() -> String {
+    "pong from a".to_string()
+}
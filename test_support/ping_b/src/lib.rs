@@ -0,0 +1,19 @@
+//! Test fixture for multi-crate inventories; see `tests/namespaced.rs`.
+
+use tools_macros::tool;
+
+pub mod net {
+    use tools_macros::tool;
+
+    #[tool]
+    /// Replies from crate `ping_b`.
+    pub async fn ping() -> String {
+        "pong from b".to_string()
+    }
+}
+
+#[tool]
+/// Echoes its input.
+pub async fn echo(text: String) -> String {
+    text
+}
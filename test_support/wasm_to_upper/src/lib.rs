@@ -0,0 +1,29 @@
+//! A WASM tool guest for the `wasm-host` feature: uppercases its `text`
+//! argument.
+//!
+//! The host calls `alloc` for a buffer, copies the arguments into it as a
+//! JSON string, and calls `call` with that buffer. `call` returns a
+//! pointer to a little-endian `u32` length followed by the JSON result.
+//! Each call gets a fresh instance, so nothing here is ever freed.
+
+use serde_json::Value;
+
+#[unsafe(no_mangle)]
+pub extern "C" fn alloc(len: usize) -> *mut u8 {
+    Vec::<u8>::with_capacity(len).leak().as_mut_ptr()
+}
+
+/// # Safety
+///
+/// `ptr` must come from [`alloc`] with at least `len` bytes written.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn call(ptr: *const u8, len: usize) -> *const u8 {
+    let input = unsafe { std::slice::from_raw_parts(ptr, len) };
+    let args: Value = serde_json::from_slice(input).unwrap_or_default();
+    let text = args["text"].as_str().unwrap_or_default();
+    let result = serde_json::to_vec(&text.to_uppercase()).expect("a string serializes");
+
+    let mut framed = (result.len() as u32).to_le_bytes().to_vec();
+    framed.extend(result);
+    framed.leak().as_ptr()
+}
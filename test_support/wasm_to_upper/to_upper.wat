@@ -0,0 +1,76 @@
+;; The guest in src/lib.rs, written by hand so the tests can load it
+;; without a wasm32 toolchain. It uppercases ASCII letters in the first
+;; string value of the arguments object, leaving escapes as they are.
+(module
+  (memory (export "memory") 1)
+  (global $heap (mut i32) (i32.const 1024))
+
+  ;; Bump allocator: the instance lives for one call.
+  (func $alloc (export "alloc") (param $len i32) (result i32)
+    (local $ptr i32)
+    (local $pages i32)
+    (local.set $ptr (global.get $heap))
+    (global.set $heap (i32.add (local.get $ptr) (local.get $len)))
+    (local.set $pages
+      (i32.shr_u (i32.add (global.get $heap) (i32.const 65535)) (i32.const 16)))
+    (if (i32.gt_u (local.get $pages) (memory.size))
+      (then
+        (if (i32.eq (memory.grow (i32.sub (local.get $pages) (memory.size))) (i32.const -1))
+          (then unreachable))))
+    (local.get $ptr))
+
+  ;; Advance from $i to the first $byte before $end, or trap.
+  (func $find (param $i i32) (param $end i32) (param $byte i32) (result i32)
+    (block $found
+      (loop $scan
+        (br_if $found (i32.ge_u (local.get $i) (local.get $end)))
+        (if (i32.eq (i32.load8_u (local.get $i)) (local.get $byte))
+          (then (return (local.get $i))))
+        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+        (br $scan)))
+    unreachable)
+
+  (func (export "call") (param $ptr i32) (param $len i32) (result i32)
+    (local $end i32)
+    (local $i i32)
+    (local $out i32)
+    (local $o i32)
+    (local $c i32)
+    (local.set $end (i32.add (local.get $ptr) (local.get $len)))
+    ;; The opening quote of the first value: after the first `:`.
+    (local.set $i
+      (call $find
+        (call $find (local.get $ptr) (local.get $end) (i32.const 58))
+        (local.get $end)
+        (i32.const 34)))
+
+    ;; The result is at most the input plus its length prefix.
+    (local.set $out (call $alloc (i32.add (local.get $len) (i32.const 4))))
+    (local.set $o (i32.add (local.get $out) (i32.const 4)))
+    (i32.store8 (local.get $o) (i32.const 34))
+    (local.set $o (i32.add (local.get $o) (i32.const 1)))
+    (local.set $i (i32.add (local.get $i) (i32.const 1)))
+    (block $done
+      (loop $copy
+        (br_if $done (i32.ge_u (local.get $i) (local.get $end)))
+        (local.set $c (i32.load8_u (local.get $i)))
+        (br_if $done (i32.eq (local.get $c) (i32.const 34)))
+        (if (i32.eq (local.get $c) (i32.const 92))
+          (then
+            ;; A backslash: copy it, and the escaped byte unchanged.
+            (i32.store8 (local.get $o) (local.get $c))
+            (local.set $o (i32.add (local.get $o) (i32.const 1)))
+            (local.set $i (i32.add (local.get $i) (i32.const 1)))
+            (local.set $c (i32.load8_u (local.get $i))))
+          (else
+            (if (i32.lt_u (i32.sub (local.get $c) (i32.const 97)) (i32.const 26))
+              (then (local.set $c (i32.sub (local.get $c) (i32.const 32)))))))
+        (i32.store8 (local.get $o) (local.get $c))
+        (local.set $o (i32.add (local.get $o) (i32.const 1)))
+        (local.set $i (i32.add (local.get $i) (i32.const 1)))
+        (br $copy)))
+    (i32.store8 (local.get $o) (i32.const 34))
+    (local.set $o (i32.add (local.get $o) (i32.const 1)))
+    (i32.store (local.get $out)
+      (i32.sub (local.get $o) (i32.add (local.get $out) (i32.const 4))))
+    (local.get $out)))
@@ -0,0 +1,55 @@
+//! Generates the `billing.v1` messages from `proto/billing.proto`: prost
+//! types, the encoded descriptor set, and the `ReflectMessage` impls
+//! `prost-reflect-build` would derive. The `.proto` is parsed in pure Rust,
+//! so no `protoc` is needed.
+
+use std::{env, fmt::Write, fs, path::Path};
+
+use prost::Message as _;
+use protobuf::Message as _;
+
+const PROTO: &str = "proto/billing.proto";
+
+fn main() {
+    println!("cargo:rerun-if-changed={PROTO}");
+    let out = env::var("OUT_DIR").unwrap();
+    let out = Path::new(&out);
+
+    let descriptors = protobuf_parse::Parser::new()
+        .pure()
+        .include("proto")
+        .input(PROTO)
+        .file_descriptor_set()
+        .unwrap()
+        .write_to_bytes()
+        .unwrap();
+    fs::write(out.join("billing.bin"), &descriptors).unwrap();
+    let descriptors = prost_types::FileDescriptorSet::decode(descriptors.as_slice()).unwrap();
+
+    // Top-level messages only: billing.proto has no nested ones besides
+    // map entries, which get no Rust type.
+    let mut reflect = String::new();
+    for file in &descriptors.file {
+        for message in &file.message_type {
+            let name = message.name();
+            writeln!(
+                reflect,
+                "impl ::prost_reflect::ReflectMessage for {name} {{
+    fn descriptor(&self) -> ::prost_reflect::MessageDescriptor {{
+        crate::proto::DESCRIPTORS
+            .get_message_by_name(\"{}.{name}\")
+            .unwrap()
+    }}
+}}",
+                file.package()
+            )
+            .unwrap();
+        }
+    }
+    fs::write(out.join("billing.reflect.rs"), reflect).unwrap();
+
+    prost_build::Config::new()
+        .out_dir(out)
+        .compile_fds(descriptors)
+        .unwrap();
+}
@@ -0,0 +1,141 @@
+//! A user crate in miniature: `#[tool]` functions of every supported shape,
+//! compiled against the `tools_rs` facade alone, plus the snapshot helper
+//! the tests in `tests/` share.
+//!
+//! New tool shapes get a fixture here and coverage in `tests/`; the
+//! declarations snapshot picks them up automatically.
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicU64, Ordering},
+};
+
+use serde::{Deserialize, Serialize};
+use tools_rs::{ToolCollection, ToolError, ToolSchema, tool, tool_resource};
+
+pub mod proto;
+pub mod snapshot;
+
+// ---------- primitives ----------
+
+/// Adds two integers.
+#[tool(category = "math")]
+pub async fn add(a: i64, b: i64) -> i64 {
+    a + b
+}
+
+/// Whether a word reads the same backwards.
+#[tool]
+pub async fn is_palindrome(word: String) -> bool {
+    word.chars().eq(word.chars().rev())
+}
+
+// ---------- structs ----------
+
+/// A point on the plane.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToolSchema)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// The midpoint of a segment.
+#[tool]
+pub async fn midpoint(from: Point, to: Point) -> Point {
+    Point {
+        x: (from.x + to.x) / 2.0,
+        y: (from.y + to.y) / 2.0,
+    }
+}
+
+// ---------- options ----------
+
+/// Greets someone, formally if a title is given.
+#[tool]
+pub async fn greet(name: String, title: Option<String>) -> String {
+    match title {
+        Some(title) => format!("Good day, {title} {name}."),
+        None => format!("Hi {name}!"),
+    }
+}
+
+// ---------- enums ----------
+
+/// A temperature scale.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToolSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Scale {
+    Celsius,
+    Fahrenheit,
+}
+
+/// Converts a temperature to the other scale.
+#[tool]
+pub async fn convert(degrees: f64, from: Scale) -> f64 {
+    match from {
+        Scale::Celsius => degrees * 9.0 / 5.0 + 32.0,
+        Scale::Fahrenheit => (degrees - 32.0) * 5.0 / 9.0,
+    }
+}
+
+// ---------- results ----------
+
+/// Divides `a` by `b`.
+#[tool]
+pub async fn divide(a: f64, b: f64) -> Result<f64, String> {
+    if b == 0.0 {
+        Err("division by zero".into())
+    } else {
+        Ok(a / b)
+    }
+}
+
+// ---------- zero-arg ----------
+
+/// Liveness check.
+#[tool]
+pub async fn ping() -> String {
+    "pong".into()
+}
+
+// ---------- context ----------
+
+/// Shared state handed to [`tick`] by the collection.
+#[derive(Debug, Default)]
+pub struct Counter {
+    total: AtomicU64,
+}
+
+impl Counter {
+    pub fn total(&self) -> u64 {
+        self.total.load(Ordering::SeqCst)
+    }
+}
+
+/// Advances the shared counter and returns its new value.
+#[tool]
+pub async fn tick(ctx: Counter, step: u64) -> u64 {
+    ctx.total.fetch_add(step, Ordering::SeqCst) + step
+}
+
+// ---------- injected resources ----------
+
+/// Decimal separator used by [`format_number`].
+#[derive(Debug)]
+pub struct Locale {
+    pub decimal: char,
+}
+
+#[tool_resource]
+pub const LOCALE: Locale = Locale { decimal: ',' };
+
+/// Formats a number with two decimals in the configured locale.
+#[tool]
+pub async fn format_number(#[tool(inject)] locale: &Locale, value: f64) -> String {
+    format!("{value:.2}").replace('.', &locale.decimal.to_string())
+}
+
+/// Every fixture tool, with `counter` as the context of [`tick`].
+pub fn tools(counter: Arc<Counter>) -> Result<ToolCollection, ToolError> {
+    ToolCollection::builder().with_context(counter).collect()
+}
@@ -0,0 +1,17 @@
+//! The `billing.v1` messages `tests/proto.rs` registers tools with,
+//! generated by `build.rs` from `proto/billing.proto`.
+
+use std::sync::LazyLock;
+
+use prost_reflect::DescriptorPool;
+
+/// Descriptors of every message in `billing.proto`.
+pub static DESCRIPTORS: LazyLock<DescriptorPool> = LazyLock::new(|| {
+    DescriptorPool::decode(include_bytes!(concat!(env!("OUT_DIR"), "/billing.bin")).as_ref())
+        .unwrap()
+});
+
+pub mod billing {
+    include!(concat!(env!("OUT_DIR"), "/billing.v1.rs"));
+    include!(concat!(env!("OUT_DIR"), "/billing.reflect.rs"));
+}
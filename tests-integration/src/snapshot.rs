@@ -0,0 +1,39 @@
+//! JSON snapshots kept under `snapshots/`.
+//!
+//! Review a diff, then regenerate with
+//! `UPDATE_GOLDEN=1 cargo test -p tests-integration` after an intentional
+//! change.
+
+use std::{fs, path::PathBuf};
+
+use serde_json::Value;
+
+fn path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("snapshots")
+        .join(name)
+        .with_extension("json")
+}
+
+/// Compare `actual` with the snapshot `name`, or rewrite the snapshot when
+/// `UPDATE_GOLDEN` is set.
+#[track_caller]
+pub fn assert_snapshot(name: &str, actual: &Value) {
+    let path = path(name);
+    let rendered = serde_json::to_string_pretty(actual).unwrap() + "\n";
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&path, &rendered).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "no snapshot at {}; run with UPDATE_GOLDEN=1 to create it",
+            path.display()
+        )
+    });
+    // Compare text so a failure shows the differing lines.
+    assert_eq!(
+        rendered, expected,
+        "snapshot `{name}` changed; rerun with UPDATE_GOLDEN=1 if intended"
+    );
+}
@@ -0,0 +1,63 @@
+//! Provider-facing output built from the fixture declarations.
+
+use std::sync::Arc;
+
+use serde_json::{Value, json};
+use tests_integration::{Counter, snapshot::assert_snapshot, tools};
+use tools_rs::{
+    ToolChoice, ToolCollection,
+    choice::{anthropic, gemini, openai},
+    schema::transform,
+};
+
+fn collection() -> ToolCollection {
+    tools(Arc::new(Counter::default())).unwrap()
+}
+
+#[test]
+fn tool_choice_lowering() {
+    let tools = collection();
+    let forced = ToolChoice::specific("midpoint");
+    assert_eq!(
+        openai::tool_choice(&forced, &tools).unwrap(),
+        json!({ "type": "function", "function": { "name": "midpoint" } })
+    );
+    assert_eq!(
+        anthropic::tool_choice(&forced, &tools).unwrap(),
+        json!({ "type": "tool", "name": "midpoint" })
+    );
+    assert_eq!(
+        gemini::tool_config(&forced, &tools).unwrap(),
+        json!({ "functionCallingConfig": { "mode": "ANY", "allowedFunctionNames": ["midpoint"] } })
+    );
+    assert!(openai::tool_choice(&ToolChoice::specific("subtract"), &tools).is_err());
+}
+
+#[test]
+fn gemini_parameters_snapshot() {
+    let tools = collection();
+    let mut lowered: Vec<(String, Value)> = tools
+        .json()
+        .unwrap()
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|decl| {
+            let parameters = transform::apply(
+                &decl["parameters"],
+                &[
+                    &transform::inline_refs,
+                    &transform::flatten_single_element_tuples,
+                    &transform::prefix_items_to_items,
+                    &transform::anyof_null_to_nullable,
+                ],
+            );
+            (decl["name"].as_str().unwrap().to_owned(), parameters)
+        })
+        .collect();
+    lowered.sort_by(|a, b| a.0.cmp(&b.0));
+    assert_snapshot(
+        "gemini_parameters",
+        &json!(lowered.into_iter().collect::<serde_json::Map<_, _>>()),
+    );
+}
@@ -0,0 +1,257 @@
+//! Round trips through `ToolCollection::call` for each fixture shape.
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicU64, Ordering},
+};
+
+use serde_json::{Value, json};
+use tests_integration::{
+    ADD_TOOL, Counter, DIVIDE_TOOL, FORMAT_NUMBER_TOOL, MIDPOINT_TOOL, PING_TOOL, Point, TICK_TOOL,
+    tools,
+};
+use tools_rs::{FunctionCall, MigrationSpec, ToolCollection};
+
+fn collection() -> ToolCollection {
+    tools(Arc::new(Counter::default())).unwrap()
+}
+
+async fn call(tools: &ToolCollection, name: &str, arguments: Value) -> Value {
+    tools
+        .call(FunctionCall::new(name.into(), arguments))
+        .await
+        .unwrap_or_else(|e| panic!("`{name}` failed: {e}"))
+        .result
+}
+
+// ---------- argument shapes ----------
+
+#[tokio::test]
+async fn primitives() {
+    let tools = collection();
+    assert_eq!(call(&tools, "add", json!({ "a": 2, "b": 40 })).await, 42);
+    assert_eq!(
+        call(&tools, "is_palindrome", json!({ "word": "level" })).await,
+        true
+    );
+}
+
+#[tokio::test]
+async fn structs() {
+    let tools = collection();
+    assert_eq!(
+        call(
+            &tools,
+            "midpoint",
+            json!({ "from": { "x": 0.0, "y": 0.0 }, "to": { "x": 2.0, "y": 4.0 } })
+        )
+        .await,
+        json!({ "x": 1.0, "y": 2.0 })
+    );
+}
+
+#[tokio::test]
+async fn options() {
+    let tools = collection();
+    assert_eq!(
+        call(&tools, "greet", json!({ "name": "Ada" })).await,
+        "Hi Ada!"
+    );
+    assert_eq!(
+        call(&tools, "greet", json!({ "name": "Ada", "title": null })).await,
+        "Hi Ada!"
+    );
+    assert_eq!(
+        call(
+            &tools,
+            "greet",
+            json!({ "name": "Lovelace", "title": "Countess" })
+        )
+        .await,
+        "Good day, Countess Lovelace."
+    );
+}
+
+#[tokio::test]
+async fn enums() {
+    let tools = collection();
+    assert_eq!(
+        call(
+            &tools,
+            "convert",
+            json!({ "degrees": 100.0, "from": "celsius" })
+        )
+        .await,
+        212.0
+    );
+}
+
+#[tokio::test]
+async fn results_report_domain_errors() {
+    let tools = collection();
+    let ok = tools
+        .call(FunctionCall::new(
+            "divide".into(),
+            json!({ "a": 1.0, "b": 4.0 }),
+        ))
+        .await
+        .unwrap();
+    assert_eq!((ok.result, ok.is_error), (json!(0.25), false));
+    let err = tools
+        .call(FunctionCall::new(
+            "divide".into(),
+            json!({ "a": 1.0, "b": 0.0 }),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(err.result, json!({ "error": "division by zero" }));
+    assert!(err.is_error);
+    assert_eq!(
+        tools.invoke(&DIVIDE_TOOL, (1.0, 0.0)).await.unwrap(),
+        Err("division by zero".to_string())
+    );
+}
+
+#[tokio::test]
+async fn zero_arg() {
+    let tools = collection();
+    assert_eq!(call(&tools, "ping", json!({})).await, "pong");
+}
+
+// ---------- injection ----------
+
+#[tokio::test]
+async fn context_is_shared_across_calls() {
+    let counter = Arc::new(Counter::default());
+    let tools = tools(counter.clone()).unwrap();
+    assert_eq!(call(&tools, "tick", json!({ "step": 2 })).await, 2);
+    assert_eq!(call(&tools, "tick", json!({ "step": 3 })).await, 5);
+    assert_eq!(counter.total(), 5);
+}
+
+#[tokio::test]
+async fn resources_are_injected() {
+    let tools = collection();
+    assert_eq!(
+        call(&tools, "format_number", json!({ "value": 1234.567 })).await,
+        "1234,57"
+    );
+}
+
+#[tokio::test]
+async fn call_ref_matches_call() {
+    let tools = collection();
+    let call = FunctionCall::new("add".into(), json!({ "a": 1, "b": 2 }));
+    let by_ref = tools.call_ref(&call).await.unwrap().result;
+    assert_eq!(by_ref, tools.call(call).await.unwrap().result);
+}
+
+// ---------- typed handles ----------
+
+#[tokio::test]
+async fn invoke_with_emitted_handles() {
+    let tools = collection();
+    assert_eq!(tools.invoke(&ADD_TOOL, (2, 40)).await.unwrap(), 42);
+    assert_eq!(tools.invoke(&PING_TOOL, ()).await.unwrap(), "pong");
+    assert_eq!(
+        tools.invoke(&DIVIDE_TOOL, (1.0, 0.0)).await.unwrap(),
+        Err("division by zero".to_string())
+    );
+    let origin = Point { x: 0.0, y: 0.0 };
+    let corner = Point { x: 2.0, y: 2.0 };
+    assert_eq!(
+        tools
+            .invoke(&MIDPOINT_TOOL, (origin, corner))
+            .await
+            .unwrap(),
+        Point { x: 1.0, y: 1.0 }
+    );
+    // Context and resources are filled in as usual.
+    assert_eq!(tools.invoke(&TICK_TOOL, (4,)).await.unwrap(), 4);
+    assert_eq!(
+        tools.invoke(&FORMAT_NUMBER_TOOL, (0.5,)).await.unwrap(),
+        "0,50"
+    );
+}
+
+// ---------- queued ----------
+
+#[tokio::test]
+async fn queued_calls_round_trip() {
+    let counter = Arc::new(Counter::default());
+    let queue = tools(counter.clone()).unwrap().queued(2, 16);
+    let pending: Vec<_> = (1..=3)
+        .map(|step| queue.enqueue(FunctionCall::new("tick".into(), json!({ "step": step }))))
+        .collect();
+    for pending in pending {
+        pending.await.unwrap();
+    }
+    queue.shutdown().await;
+    assert_eq!(counter.total(), 6);
+}
+
+// ---------- migrations ----------
+
+#[tokio::test]
+async fn legacy_arguments_are_migrated() {
+    let mut tools = collection();
+    let specs: Vec<MigrationSpec> = serde_json::from_value(json!([
+        { "tool": "greet", "name": "who-to-name", "rename": { "who": "name" } }
+    ]))
+    .unwrap();
+    tools.add_migrations(specs).unwrap();
+    let response = tools
+        .call(FunctionCall::new("greet".into(), json!({ "who": "Ada" })))
+        .await
+        .unwrap();
+    assert_eq!(response.result, "Hi Ada!");
+    assert_eq!(response.migrations, ["who-to-name"]);
+}
+
+// ---------- lazy state ----------
+
+#[tokio::test]
+async fn lazy_state_is_built_on_first_call() {
+    let mut tools = collection();
+    let built = Arc::new(AtomicU64::new(0));
+    let init_counter = built.clone();
+    let scaled = tools
+        .register_lazy(
+            "scaled",
+            "Multiplies by a factor loaded on first use.",
+            move || {
+                let built = init_counter.clone();
+                async move {
+                    built.fetch_add(1, Ordering::SeqCst);
+                    Ok::<_, String>(10u64)
+                }
+            },
+            |factor: Arc<u64>, n: u64| async move { n * *factor },
+            (),
+        )
+        .unwrap();
+    assert_eq!(built.load(Ordering::SeqCst), 0);
+    assert_eq!(tools.invoke(&scaled, 4u64).await.unwrap(), 40);
+    assert_eq!(tools.invoke(&scaled, 5u64).await.unwrap(), 50);
+    assert_eq!(built.load(Ordering::SeqCst), 1);
+}
+
+// ---------- bare inputs ----------
+
+#[tokio::test]
+async fn bare_inputs_are_wrapped() {
+    let mut tools = collection();
+    tools.set_wrap_bare_inputs(true);
+    let sum = tools
+        .register(
+            "sum",
+            "Adds integers.",
+            |xs: Vec<i64>| async move { xs.iter().sum::<i64>() },
+            (),
+        )
+        .unwrap();
+    assert_eq!(call(&tools, "sum", json!({ "items": [1, 2, 3] })).await, 6);
+    assert_eq!(tools.invoke(&sum, vec![4, 5]).await.unwrap(), 9);
+    // `#[tool]` inputs are already objects.
+    assert_eq!(call(&tools, "add", json!({ "a": 1, "b": 2 })).await, 3);
+}
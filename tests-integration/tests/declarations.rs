@@ -0,0 +1,114 @@
+//! What `collect_tools` declares for each fixture tool.
+
+use std::sync::Arc;
+
+use serde_json::{Value, json};
+use tests_integration::{Counter, snapshot::assert_snapshot, tools};
+
+fn declarations() -> Vec<Value> {
+    let mut decls = tools(Arc::new(Counter::default()))
+        .unwrap()
+        .json()
+        .unwrap()
+        .as_array()
+        .unwrap()
+        .clone();
+    decls.sort_by(|a, b| a["name"].as_str().cmp(&b["name"].as_str()));
+    decls
+}
+
+fn declaration(name: &str) -> Value {
+    declarations()
+        .into_iter()
+        .find(|d| d["name"] == name)
+        .unwrap_or_else(|| panic!("`{name}` is not declared"))
+}
+
+#[test]
+fn declarations_snapshot() {
+    assert_snapshot("declarations", &Value::Array(declarations()));
+}
+
+#[test]
+fn every_fixture_is_declared() {
+    let names: Vec<Value> = declarations().iter().map(|d| d["name"].clone()).collect();
+    assert_eq!(
+        names,
+        [
+            "add",
+            "convert",
+            "divide",
+            "format_number",
+            "greet",
+            "is_palindrome",
+            "midpoint",
+            "ping",
+            "tick"
+        ]
+    );
+}
+
+#[test]
+fn doc_comments_become_descriptions() {
+    assert_eq!(declaration("add")["description"], "Adds two integers.");
+}
+
+#[test]
+fn optional_parameters_are_not_required() {
+    assert_eq!(
+        declaration("greet")["parameters"]["required"],
+        json!(["name"])
+    );
+}
+
+#[test]
+fn context_and_resources_are_not_parameters() {
+    let tick = declaration("tick")["parameters"].clone();
+    assert_eq!(tick["properties"].as_object().unwrap().len(), 1);
+    assert_eq!(tick["required"], json!(["step"]));
+
+    let format = declaration("format_number")["parameters"].clone();
+    assert_eq!(format["required"], json!(["value"]));
+}
+
+#[test]
+fn zero_arg_tools_take_an_empty_object() {
+    let ping = declaration("ping")["parameters"].clone();
+    assert_eq!(ping["type"], "object");
+    assert!(ping["properties"].as_object().unwrap().is_empty());
+}
+
+#[test]
+fn canonical_string_is_sorted_pretty_json() {
+    let text = tools(Arc::new(Counter::default()))
+        .unwrap()
+        .to_canonical_string()
+        .unwrap();
+    assert!(text.ends_with("]\n"));
+    let parsed: Value = serde_json::from_str(&text).unwrap();
+    assert_eq!(parsed, Value::Array(declarations()));
+}
+
+#[test]
+fn snapshot_mismatch_shows_a_line_diff() {
+    let dir = std::env::temp_dir().join(format!("tools-rs-snapshot-{}", std::process::id()));
+    let path = dir.join("nested/tools.json");
+    let tools = tools(Arc::new(Counter::default())).unwrap();
+
+    // A missing snapshot is written, then matches.
+    tools_rs::assert_declarations_snapshot!(tools, &path);
+    tools_rs::assert_declarations_snapshot!(tools, &path);
+
+    let edited = std::fs::read_to_string(&path)
+        .unwrap()
+        .replacen("\"add\"", "\"plus\"", 1);
+    std::fs::write(&path, edited).unwrap();
+    let actual = tools.to_canonical_string().unwrap();
+    let message =
+        std::panic::catch_unwind(|| tools_rs::testing::assert_snapshot_text(&path, &actual))
+            .unwrap_err();
+    let message = message.downcast_ref::<String>().unwrap();
+    assert!(message.contains("-     \"name\": \"plus\","), "{message}");
+    assert!(message.contains("+     \"name\": \"add\","), "{message}");
+    std::fs::remove_dir_all(dir).unwrap();
+}
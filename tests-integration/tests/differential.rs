@@ -0,0 +1,14 @@
+//! Schema validation against decoding: every document a fixture tool's
+//! schema accepts must decode.
+
+use std::sync::Arc;
+
+use tests_integration::{Counter, tools};
+use tools_rs::testing::run_acceptance;
+
+#[test]
+fn schemas_accept_only_what_decodes() {
+    let tools = tools(Arc::new(Counter::default())).unwrap();
+    let corpus = concat!(env!("CARGO_MANIFEST_DIR"), "/divergences");
+    run_acceptance(&tools, corpus, 0x7001_5eed, 2_000);
+}
@@ -0,0 +1,68 @@
+//! How failures from the fixture tools are classified.
+
+use std::sync::Arc;
+
+use serde_json::{Value, json};
+use tests_integration::{Counter, tools};
+use tools_rs::{ErrorClass, FunctionCall, ToolCollection, ToolError, UnknownArgs};
+
+fn collection() -> ToolCollection {
+    tools(Arc::new(Counter::default())).unwrap()
+}
+
+async fn fail(tools: &ToolCollection, name: &str, arguments: Value) -> ToolError {
+    tools
+        .call(FunctionCall::new(name.into(), arguments))
+        .await
+        .expect_err("call should fail")
+}
+
+#[tokio::test]
+async fn unknown_tool_is_not_found() {
+    let err = fail(&collection(), "subtract", json!({})).await;
+    assert!(matches!(err, ToolError::FunctionNotFound { .. }));
+    assert_eq!(err.classification(), ErrorClass::NotFound);
+}
+
+#[tokio::test]
+async fn bad_arguments_are_invalid_input() {
+    let tools = collection();
+    for (name, arguments) in [
+        ("add", json!({ "a": "two", "b": 2 })),
+        ("add", json!({ "a": 1 })),
+        ("convert", json!({ "degrees": 1.0, "from": "kelvin" })),
+        (
+            "midpoint",
+            json!({ "from": { "x": 0.0 }, "to": { "x": 1.0, "y": 1.0 } }),
+        ),
+        ("greet", json!("Ada")),
+    ] {
+        let err = fail(&tools, name, arguments.clone()).await;
+        assert_eq!(
+            err.classification(),
+            ErrorClass::InvalidInput,
+            "{name} {arguments}: {err}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn misspelled_arguments_are_invalid_input() {
+    let mut tools = collection();
+    tools.set_unknown_args(UnknownArgs::Reject);
+    let err = fail(&tools, "greet", json!({ "name": "Ada", "tittle": "Dr" })).await;
+    assert_eq!(err.classification(), ErrorClass::InvalidInput);
+    assert!(err.to_string().contains("did you mean `title`?"), "{err}");
+}
+
+#[test]
+fn context_tools_need_a_context() {
+    let collected: Result<ToolCollection, ToolError> = ToolCollection::collect_tools();
+    let Err(err) = collected else {
+        panic!("collected without a context");
+    };
+    assert!(
+        matches!(err, ToolError::MissingCtx { ref tool } if tool == "tick"),
+        "{err}"
+    );
+}
@@ -0,0 +1,39 @@
+//! Failures of fixture tools reported through a `FailureSink`.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use serde_json::json;
+use tests_integration::{Counter, tools};
+use tools_rs::{FailureEvent, FailureSink, FunctionCall};
+
+#[derive(Default)]
+struct Sink(Mutex<Vec<FailureEvent>>);
+
+impl FailureSink for Sink {
+    fn record(&self, event: FailureEvent) {
+        self.0.lock().unwrap().push(event);
+    }
+}
+
+#[tokio::test]
+async fn looping_model_is_reported_once() {
+    let sink = Arc::new(Sink::default());
+    let mut tools = tools(Arc::new(Counter::default())).unwrap();
+    tools.set_failure_sink(sink.clone(), Duration::from_secs(3600));
+    for _ in 0..20 {
+        let call = FunctionCall::new("add".into(), json!({ "a": "one", "b": 2 }));
+        tools.call(call).await.unwrap_err();
+    }
+    tools.flush_failures();
+
+    let events = sink.0.lock().unwrap();
+    assert_eq!(events.len(), 2);
+    assert!(matches!(&events[0], FailureEvent::Failed(record) if record.tool == "add"));
+    assert!(matches!(
+        events[1],
+        FailureEvent::Repeated { count: 20, .. }
+    ));
+}
@@ -0,0 +1,169 @@
+//! Protobuf-message tools through the `prost` bridge, with the messages
+//! `prost-build` generates from `proto/billing.proto` and the descriptors
+//! `prost-reflect` reads them through.
+
+use std::collections::HashMap;
+
+use prost_reflect::ReflectMessage;
+use serde_json::json;
+use tests_integration::proto::{
+    DESCRIPTORS,
+    billing::{CreateInvoiceRequest, Invoice, Status},
+};
+use tools_rs::{FunctionCall, ToolCollection, proto::json_schema};
+
+fn tools() -> ToolCollection {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register_proto::<CreateInvoiceRequest, Invoice, _, _>(
+            "create_invoice",
+            "Create an invoice.",
+            |req| async move {
+                Ok(Invoice {
+                    invoice_id: format!("inv-{}", req.customer_id),
+                    total_cents: req.line_items.iter().map(|i| i.amount_cents).sum(),
+                    status: if req.labels.contains_key("prepaid") {
+                        Status::Paid
+                    } else {
+                        Status::Open
+                    } as i32,
+                })
+            },
+        )
+        .unwrap();
+    tools
+}
+
+// ---------- schema ----------
+
+#[test]
+fn schema_follows_the_json_mapping() {
+    let tools = tools();
+    let decl = &tools.get("create_invoice").unwrap().decl;
+    let props = &decl.parameters["properties"];
+    assert_eq!(props["customerId"], json!({ "type": "string" }));
+    assert_eq!(
+        props["lineItems"]["items"]["properties"]["amountCents"],
+        json!({ "type": "string", "format": "int64", "pattern": "^-?[0-9]+$" })
+    );
+    assert_eq!(
+        props["labels"],
+        json!({ "type": "object", "additionalProperties": { "type": "string" } })
+    );
+    assert_eq!(decl.parameters["required"], json!([]));
+    assert_eq!(
+        decl.returns.as_ref().unwrap()["properties"]["status"]["enum"],
+        json!(["STATUS_UNSPECIFIED", "STATUS_OPEN", "STATUS_PAID"])
+    );
+    assert_eq!(
+        json_schema(&Invoice::default().descriptor())["title"],
+        "billing.v1.Invoice"
+    );
+}
+
+#[test]
+fn every_message_in_the_pool_has_a_schema() {
+    for message in DESCRIPTORS.all_messages().filter(|m| !m.is_map_entry()) {
+        let schema = json_schema(&message);
+        let fields: Vec<_> = message.fields().map(|f| f.json_name().to_owned()).collect();
+        let properties: Vec<_> = schema["properties"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect();
+        assert_eq!(properties.len(), fields.len(), "{}", message.full_name());
+        assert!(fields.iter().all(|f| properties.contains(f)), "{schema}");
+    }
+}
+
+// ---------- decoding ----------
+
+#[tokio::test]
+async fn canonical_json_round_trips() {
+    let out = tools()
+        .call(FunctionCall::new(
+            "create_invoice".into(),
+            json!({
+                "customerId": "c42",
+                "lineItems": [
+                    { "sku": "a", "amountCents": "9007199254740993" },
+                    { "sku": "b", "amountCents": "7" }
+                ]
+            }),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(
+        out.result,
+        json!({ "invoiceId": "inv-c42", "totalCents": "9007199254741000", "status": "STATUS_OPEN" })
+    );
+}
+
+#[tokio::test]
+async fn proto_names_and_numeric_int64_are_accepted() {
+    let out = tools()
+        .call(FunctionCall::new(
+            "create_invoice".into(),
+            json!({
+                "customer_id": "c7",
+                "line_items": [{ "sku": "a", "amount_cents": 250 }],
+                "labels": { "prepaid": "yes" }
+            }),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(out.result["totalCents"], "250");
+    assert_eq!(out.result["status"], "STATUS_PAID");
+}
+
+#[tokio::test]
+async fn decodes_into_the_generated_message() {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register_proto::<CreateInvoiceRequest, CreateInvoiceRequest, _, _>(
+            "echo",
+            "Echo the request.",
+            |req| async move { Ok(req) },
+        )
+        .unwrap();
+    let arguments = json!({
+        "customerId": "c1",
+        "lineItems": [{ "sku": "a", "amountCents": "-3" }],
+        "labels": { "team": "billing" }
+    });
+    let out = tools
+        .call(FunctionCall::new("echo".into(), arguments.clone()))
+        .await
+        .unwrap();
+    assert_eq!(out.result, arguments);
+
+    let expected = CreateInvoiceRequest {
+        customer_id: "c1".into(),
+        line_items: vec![tests_integration::proto::billing::LineItem {
+            sku: "a".into(),
+            amount_cents: -3,
+        }],
+        labels: HashMap::from([("team".into(), "billing".into())]),
+    };
+    let decoded = tools
+        .get("echo")
+        .unwrap()
+        .func
+        .decode(arguments)
+        .unwrap()
+        .downcast::<CreateInvoiceRequest>()
+        .unwrap();
+    assert_eq!(*decoded, expected);
+}
+
+#[tokio::test]
+async fn malformed_int64_is_rejected() {
+    tools()
+        .call(FunctionCall::new(
+            "create_invoice".into(),
+            json!({ "lineItems": [{ "sku": "a", "amountCents": "12abc" }] }),
+        ))
+        .await
+        .unwrap_err();
+}
@@ -0,0 +1,185 @@
+//! Tools taking a bare array, tuple or primitive are flagged at
+//! registration, or wrapped in an object when the collection asks for it.
+
+use serde_json::{Value, json};
+use tools_rs::{
+    FunctionCall, ToolCollection, ToolError,
+    checks::codes,
+    schema::transform::{self, anyof_null_to_nullable, inline_refs, prefix_items_to_items},
+};
+
+fn register(tools: &mut ToolCollection) {
+    tools
+        .register(
+            "sum",
+            "Adds numbers.",
+            |xs: Vec<i64>| async move { xs.iter().sum::<i64>() },
+            (),
+        )
+        .unwrap();
+    tools
+        .register(
+            "span",
+            "Distance between two points on a line.",
+            |(a, b): (f64, f64)| async move { (b - a).abs() },
+            (),
+        )
+        .unwrap();
+    tools
+        .register(
+            "square",
+            "Squares a number.",
+            |n: i64| async move { n * n },
+            (),
+        )
+        .unwrap();
+}
+
+fn wrapped() -> ToolCollection {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools.set_wrap_bare_inputs(true);
+    register(&mut tools);
+    tools
+}
+
+fn parameters(tools: &ToolCollection, name: &str) -> Value {
+    tools.get(name).unwrap().decl.parameters.clone()
+}
+
+async fn call(tools: &ToolCollection, name: &str, arguments: Value) -> Result<Value, ToolError> {
+    let call = FunctionCall::new(name.into(), arguments);
+    Ok(tools.call(call).await?.result)
+}
+
+// ---------- findings ----------
+
+#[test]
+fn bare_inputs_are_flagged_by_default() {
+    let mut tools: ToolCollection = ToolCollection::new();
+    register(&mut tools);
+    let findings = tools.registration_warnings();
+    assert_eq!(findings.len(), 3);
+    assert!(findings.iter().all(|f| f.code == codes::PRIMITIVE_PARAMS));
+    assert!(
+        findings[0].message.contains("set_wrap_bare_inputs"),
+        "{}",
+        findings[0]
+    );
+    assert_eq!(parameters(&tools, "sum")["type"], "array");
+}
+
+// ---------- wrapping ----------
+
+#[test]
+fn schemas_are_wrapped_under_one_parameter() {
+    let tools = wrapped();
+    assert!(tools.registration_warnings().is_empty());
+    let sum = parameters(&tools, "sum");
+    assert_eq!(sum["type"], "object");
+    assert_eq!(sum["required"], json!(["items"]));
+    assert_eq!(sum["properties"]["items"]["type"], "array");
+    assert_eq!(parameters(&tools, "span")["required"], json!(["items"]));
+    assert_eq!(
+        parameters(&tools, "square"),
+        json!({
+            "type": "object",
+            "properties": {
+                "value": { "type": "integer", "minimum": i64::MIN, "maximum": i64::MAX }
+            },
+            "required": ["value"]
+        })
+    );
+}
+
+#[tokio::test]
+async fn calls_use_the_advertised_shape() {
+    let tools = wrapped();
+    assert_eq!(
+        call(&tools, "sum", json!({ "items": [1, 2, 3] }))
+            .await
+            .unwrap(),
+        6
+    );
+    assert_eq!(
+        call(&tools, "span", json!({ "items": [1.5, -2.5] }))
+            .await
+            .unwrap(),
+        4.0
+    );
+    assert_eq!(
+        call(&tools, "square", json!({ "value": 7 })).await.unwrap(),
+        49
+    );
+
+    // The bare shape no longer matches the declaration.
+    let err = call(&tools, "sum", json!([1, 2, 3])).await.unwrap_err();
+    assert!(matches!(err, ToolError::Deserialize(_)), "{err}");
+    let err = call(&tools, "square", json!({ "n": 7 })).await.unwrap_err();
+    assert!(err.to_string().contains("missing field `value`"), "{err}");
+
+    // Borrowed and owned decoding agree.
+    let args = json!({ "items": [4, 5] });
+    let response = tools
+        .call_ref(&FunctionCall::new("sum".into(), args))
+        .await
+        .unwrap();
+    assert_eq!(response.result, 9);
+}
+
+#[tokio::test]
+async fn invoke_wraps_typed_inputs() {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools.set_wrap_bare_inputs(true);
+    let sum = tools
+        .register(
+            "sum",
+            "Adds numbers.",
+            |xs: Vec<i64>| async move { xs.iter().sum::<i64>() },
+            (),
+        )
+        .unwrap();
+    assert_eq!(tools.invoke(&sum, vec![2, 3]).await.unwrap(), 5);
+}
+
+#[tokio::test]
+async fn turning_it_on_wraps_registered_tools() {
+    let mut tools: ToolCollection = ToolCollection::new();
+    register(&mut tools);
+    let before = tools.json().unwrap();
+    tools.set_wrap_bare_inputs(true);
+    assert!(tools.wrap_bare_inputs());
+    assert!(tools.registration_warnings().is_empty());
+    assert_ne!(tools.json().unwrap(), before);
+    assert_eq!(
+        call(&tools, "square", json!({ "value": 3 })).await.unwrap(),
+        9
+    );
+}
+
+// ---------- adapters ----------
+
+#[test]
+fn wrapped_schemas_stay_objects_through_provider_passes() {
+    let tools = wrapped();
+    for name in ["sum", "span", "square"] {
+        let gemini = transform::apply(
+            &parameters(&tools, name),
+            &[
+                &inline_refs,
+                &prefix_items_to_items,
+                &anyof_null_to_nullable,
+            ],
+        );
+        assert_eq!(gemini["type"], "object", "{name}: {gemini}");
+        assert_eq!(gemini["required"].as_array().unwrap().len(), 1);
+    }
+    // Every declaration sent to a model now takes an object.
+    let declarations = tools.json().unwrap();
+    assert!(
+        declarations
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|d| d["parameters"]["type"] == "object")
+    );
+}
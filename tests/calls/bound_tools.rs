@@ -0,0 +1,132 @@
+//! `register_bound`: narrowed schemas and how bound and model-supplied
+//! arguments merge.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tools_rs::{BoundConflict, FunctionCall, ToolCollection, ToolError, ToolSchema};
+
+#[derive(Serialize, Deserialize, ToolSchema)]
+struct Search {
+    query: String,
+    site: Option<String>,
+    max_results: u32,
+}
+
+fn tools() -> ToolCollection {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register(
+            "search_web",
+            "Searches the web.",
+            |s: Search| async move { s },
+            (),
+        )
+        .unwrap();
+    tools
+}
+
+fn bound(conflict: BoundConflict) -> ToolCollection {
+    let mut tools = tools();
+    tools
+        .register_bound_with(
+            "search_docs",
+            "Searches the internal documentation.",
+            "search_web",
+            json!({ "site": "docs.mycorp.com", "max_results": 5 }),
+            conflict,
+        )
+        .unwrap();
+    tools
+}
+
+async fn call(tools: &ToolCollection, arguments: Value) -> Result<Value, ToolError> {
+    let call = FunctionCall::new("search_docs".into(), arguments);
+    tools.call(call).await.map(|r| r.result)
+}
+
+// ---------- schema ----------
+
+#[test]
+fn schema_omits_the_bound_parameters() {
+    let tools = bound(BoundConflict::BoundWins);
+    let decl = &tools.get("search_docs").unwrap().decl;
+
+    assert_eq!(decl.description, "Searches the internal documentation.");
+    let properties = decl.parameters["properties"].as_object().unwrap();
+    assert_eq!(properties.keys().collect::<Vec<_>>(), ["query"]);
+    assert_eq!(decl.parameters["required"], json!(["query"]));
+    // The base tool is untouched.
+    let base = &tools.get("search_web").unwrap().decl.parameters;
+    assert_eq!(base["properties"].as_object().unwrap().len(), 3);
+}
+
+#[test]
+fn binding_an_undeclared_parameter_fails() {
+    let mut tools = tools();
+    let err = tools
+        .register_bound("typo", "", "search_web", json!({ "sites": "x" }))
+        .unwrap_err();
+    assert!(err.to_string().contains("no parameter `sites`"));
+
+    let err = tools
+        .register_bound("missing", "", "search_news", json!({}))
+        .unwrap_err();
+    assert!(matches!(err, ToolError::FunctionNotFound { .. }));
+    assert!(tools.get("typo").is_none());
+}
+
+// ---------- merging ----------
+
+#[tokio::test]
+async fn bound_arguments_are_applied() {
+    let tools = bound(BoundConflict::BoundWins);
+    let out = call(&tools, json!({ "query": "sso" })).await.unwrap();
+    assert_eq!(
+        out,
+        json!({ "query": "sso", "site": "docs.mycorp.com", "max_results": 5 })
+    );
+}
+
+#[tokio::test]
+async fn conflicts_follow_the_configured_precedence() {
+    let arguments = json!({ "query": "sso", "max_results": 50 });
+
+    let out = call(&bound(BoundConflict::BoundWins), arguments.clone())
+        .await
+        .unwrap();
+    assert_eq!(out["max_results"], json!(5));
+
+    let out = call(&bound(BoundConflict::ModelWins), arguments.clone())
+        .await
+        .unwrap();
+    assert_eq!(out["max_results"], json!(50));
+    assert_eq!(out["site"], json!("docs.mycorp.com"));
+
+    let err = call(&bound(BoundConflict::Reject), arguments)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ToolError::Deserialize(_)));
+    assert!(
+        err.to_string()
+            .contains("`max_results` is fixed by the tool")
+    );
+}
+
+#[tokio::test]
+async fn bound_tool_is_a_tool_of_its_own() {
+    let mut tools = bound(BoundConflict::BoundWins);
+    let names: Vec<_> = tools
+        .json()
+        .unwrap()
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| d["name"].clone())
+        .collect();
+    assert!(names.contains(&json!("search_docs")));
+
+    // Removing the base leaves the bound tool working.
+    tools.unregister("search_web").unwrap();
+    let out = call(&tools, json!({ "query": "vpn" })).await.unwrap();
+    assert_eq!(out["query"], json!("vpn"));
+}
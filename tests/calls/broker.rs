@@ -0,0 +1,166 @@
+//! Both halves of the broker in one process: a collection served on a
+//! temporary Unix socket and the proxy collection connected to it.
+
+#![cfg(all(feature = "broker", unix))]
+
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tools_core::broker::{self, BrokerOptions};
+use tools_rs::{ErrorClass, ToolCollection, ToolError, ToolSchema, ToolSpec};
+
+use crate::common::call;
+
+#[derive(Serialize, Deserialize, ToolSchema)]
+struct Add {
+    a: i64,
+    b: i64,
+}
+
+fn served() -> Arc<ToolCollection> {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register(
+            "add",
+            "Adds two numbers.",
+            |x: Add| async move { x.a + x.b },
+            (),
+        )
+        .unwrap();
+    tools
+        .register_spec(
+            ToolSpec::new("flaky", "Fails with a transient error.")
+                .tag("ops")
+                .handler(|_| {
+                    Box::pin(async {
+                        Err(ToolError::classified(ErrorClass::Transient, "backend busy"))
+                    })
+                }),
+        )
+        .unwrap();
+    tools
+        .register_spec(
+            ToolSpec::new("refuse", "Answers with a domain error.").handler(|_| {
+                Box::pin(async { Err(ToolError::Domain(json!({ "code": "NOT_ALLOWED" }))) })
+            }),
+        )
+        .unwrap();
+    tools
+        .register_spec(ToolSpec::new("slow", "Sleeps.").handler(|_| {
+            Box::pin(async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                Ok(Value::Null)
+            })
+        }))
+        .unwrap();
+    Arc::new(tools)
+}
+
+/// A socket path unique to the test.
+fn socket(test: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "tools-rs-broker-{}-{test}.sock",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+// ---------- declarations and calls ----------
+
+#[tokio::test]
+async fn proxy_declares_and_forwards() {
+    let path = socket("forward");
+    let served = served();
+    let server = broker::serve_unix(served.clone(), &path).unwrap();
+
+    let proxy: ToolCollection = ToolCollection::connect_unix(&path).await.unwrap();
+    assert_eq!(proxy.json().unwrap(), served.json().unwrap());
+
+    let out = proxy
+        .call(call("add", json!({ "a": 2, "b": 3 })))
+        .await
+        .unwrap();
+    assert_eq!(out.result, json!(5));
+
+    server.abort();
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn errors_keep_their_classification() {
+    let path = socket("errors");
+    let server = broker::serve_unix(served(), &path).unwrap();
+    let proxy: ToolCollection = ToolCollection::connect_unix(&path).await.unwrap();
+
+    let err = proxy
+        .call(call("add", json!({ "a": "two" })))
+        .await
+        .unwrap_err();
+    assert_eq!(err.classification(), ErrorClass::InvalidInput);
+    let err = proxy.call(call("flaky", json!({}))).await.unwrap_err();
+    assert_eq!(err.classification(), ErrorClass::Transient);
+    assert!(err.is_retryable());
+    assert!(err.to_string().contains("backend busy"));
+
+    let refused = proxy.call(call("refuse", json!({}))).await.unwrap();
+    assert!(refused.is_error);
+    assert_eq!(
+        refused.result,
+        json!({ "error": { "code": "NOT_ALLOWED" } })
+    );
+
+    server.abort();
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn calls_time_out() {
+    let path = socket("timeout");
+    let server = broker::serve_unix(served(), &path).unwrap();
+    let options = BrokerOptions {
+        timeout: Duration::from_millis(50),
+        ..BrokerOptions::default()
+    };
+    let proxy: ToolCollection = ToolCollection::connect_unix_with(&path, options)
+        .await
+        .unwrap();
+
+    // The server's timeout comes back with its class and message.
+    let err = proxy.call(call("slow", json!({}))).await.unwrap_err();
+    assert_eq!(err.classification(), ErrorClass::Transient);
+    assert!(err.to_string().contains("timed out"), "{err}");
+    // The connection is still usable.
+    let out = proxy
+        .call(call("add", json!({ "a": 1, "b": 1 })))
+        .await
+        .unwrap();
+    assert_eq!(out.result, json!(2));
+
+    server.abort();
+    let _ = std::fs::remove_file(&path);
+}
+
+// ---------- reconnection ----------
+
+#[tokio::test]
+async fn proxy_reconnects_after_a_restart() {
+    let path = socket("restart");
+    let server = broker::serve_unix(served(), &path).unwrap();
+    let proxy: ToolCollection = ToolCollection::connect_unix(&path).await.unwrap();
+    let add = || call("add", json!({ "a": 1, "b": 2 }));
+    proxy.call(add()).await.unwrap();
+
+    server.abort();
+    let _ = server.await;
+    std::fs::remove_file(&path).unwrap();
+    let err = proxy.call(add()).await.unwrap_err();
+    assert_eq!(err.classification(), ErrorClass::Transient);
+
+    let server = broker::serve_unix(served(), &path).unwrap();
+    assert_eq!(proxy.call(add()).await.unwrap().result, json!(3));
+
+    server.abort();
+    let _ = std::fs::remove_file(&path);
+}
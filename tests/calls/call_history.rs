@@ -0,0 +1,124 @@
+//! `enable_history` keeps arguments and results of the last calls, bounded
+//! and with secret fields redacted.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tools_rs::{FunctionCall, Secret, ToolCollection, ToolSchema};
+
+use crate::common;
+
+#[derive(Serialize, Deserialize, ToolSchema)]
+struct Account {
+    user: String,
+    token: Secret<String>,
+}
+
+#[derive(Serialize, Deserialize, ToolSchema)]
+struct Login {
+    account: Account,
+    backup: Option<Secret<String>>,
+    note: String,
+}
+
+fn tools() -> ToolCollection {
+    let mut tools = common::tools();
+    tools
+        .register(
+            "login",
+            "Logs in.",
+            |l: Login| async move { format!("{}:{}", l.account.user, l.account.token.expose().len()) },
+            (),
+        )
+        .unwrap();
+    tools
+}
+
+fn double(n: u32) -> FunctionCall {
+    common::call("double", json!(n))
+}
+
+// ---------- capacity ----------
+
+#[tokio::test]
+async fn oldest_calls_are_evicted() {
+    let tools = tools();
+    tools.enable_history(3);
+    for n in 0..10 {
+        tools.call(double(n)).await.unwrap();
+    }
+    let history = tools.recent_calls(usize::MAX);
+    assert_eq!(history.len(), 3);
+    let arguments: Vec<_> = history.iter().map(|c| c.arguments.clone()).collect();
+    assert_eq!(arguments, [Some(json!(7)), Some(json!(8)), Some(json!(9))]);
+    assert_eq!(history[2].result, Some(json!(18)));
+
+    // `n` picks the newest.
+    let last = tools.recent_calls(1);
+    assert_eq!(last, history[2..]);
+    assert!(tools.recent_calls(0).is_empty());
+
+    // Shrinking drops the oldest right away.
+    tools.enable_history(1);
+    assert_eq!(tools.recent_calls(usize::MAX), last);
+}
+
+#[tokio::test]
+async fn payloads_only_while_enabled() {
+    let tools = tools();
+    tools.call(double(1)).await.unwrap();
+    tools.enable_history(8);
+    tools.call(double(2)).await.unwrap();
+    tools.call(double(u32::MAX)).await.unwrap_err();
+    tools.disable_history();
+    tools.call(double(3)).await.unwrap();
+
+    let history = tools.recent_calls(usize::MAX);
+    assert_eq!(history.len(), 4);
+    assert_eq!(history[0].arguments, None);
+    assert_eq!(history[1].arguments, Some(json!(2)));
+    assert_eq!(history[1].result, Some(json!(4)));
+    assert_eq!(history[2].result, None);
+    assert!(history[2].message.is_some());
+    assert_eq!(history[3].arguments, None);
+}
+
+// ---------- redaction ----------
+
+#[tokio::test]
+async fn secret_fields_are_redacted() {
+    let tools = tools();
+    tools.enable_history(8);
+    let arguments = json!({
+        "account": { "user": "ada", "token": "hunter2" },
+        "backup": "correct-horse",
+        "note": "hi"
+    });
+    let response = tools
+        .call(FunctionCall::new("login".into(), arguments))
+        .await
+        .unwrap();
+    // The tool itself sees the real value.
+    assert_eq!(response.result, "ada:7");
+
+    let call = &tools.recent_calls(1)[0];
+    assert_eq!(
+        call.arguments,
+        Some(json!({
+            "account": { "user": "ada", "token": "[REDACTED]" },
+            "backup": "[REDACTED]",
+            "note": "hi"
+        }))
+    );
+    let report = serde_json::to_string(&tools.clone().introspect()).unwrap();
+    assert!(report.contains("\"note\":\"hi\""), "{report}");
+    assert!(!report.contains("hunter2"));
+    assert!(!report.contains("correct-horse"));
+}
+
+#[test]
+fn secrets_are_marked_write_only() {
+    assert_eq!(
+        Secret::<String>::schema(),
+        json!({ "type": "string", "writeOnly": true })
+    );
+}
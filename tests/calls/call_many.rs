@@ -0,0 +1,198 @@
+//! `call_many` in `Independent` and `FailFast` mode, with a mix of fast
+//! successes, slow successes and early failures, and `call_all`.
+
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+use serde_json::json;
+use tools_rs::{BatchMode, CallOutcome, ErrorClass, ToolCollection, ToolError};
+
+use crate::common::call;
+
+/// Slow calls of a second or more that ran to the end. Every such call
+/// in this file is expected to be cancelled.
+static LONG_FINISHED: AtomicUsize = AtomicUsize::new(0);
+
+fn tools() -> ToolCollection {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register("fast", "Answers at once.", |x: i32| async move { x }, ())
+        .unwrap();
+    tools
+        .register(
+            "slow",
+            "Answers after a while.",
+            |ms: u64| async move {
+                tokio::time::sleep(Duration::from_millis(ms)).await;
+                if ms >= 1_000 {
+                    LONG_FINISHED.fetch_add(1, Ordering::SeqCst);
+                }
+                ms
+            },
+            (),
+        )
+        .unwrap();
+    tools
+        .register_raw(
+            "fail",
+            "Fails after 10ms.",
+            json!({ "type": "object" }),
+            |args| {
+                Box::pin(async move {
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                    if args["transient"] == json!(true) {
+                        Err(ToolError::classified(ErrorClass::Transient, "try again"))
+                    } else {
+                        Err(ToolError::Runtime("boom".into()))
+                    }
+                })
+            },
+            (),
+        )
+        .unwrap();
+    tools
+}
+
+fn shapes(outcomes: &[CallOutcome]) -> Vec<&'static str> {
+    outcomes
+        .iter()
+        .map(|o| match o {
+            CallOutcome::Completed(_) => "completed",
+            CallOutcome::Failed(_) => "failed",
+            CallOutcome::Cancelled => "cancelled",
+        })
+        .collect()
+}
+
+// ---------- fail fast ----------
+
+#[tokio::test]
+async fn fail_fast_cancels_running_siblings() {
+    let start = Instant::now();
+    let outcomes = tools()
+        .call_many(
+            [
+                call("slow", json!(2_000)),
+                call("fast", json!(1)),
+                call("fail", json!({})),
+            ],
+            BatchMode::FailFast,
+        )
+        .await;
+
+    assert_eq!(shapes(&outcomes), ["cancelled", "completed", "failed"]);
+    assert!(start.elapsed() < Duration::from_secs(1));
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(LONG_FINISHED.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn fail_fast_keeps_earlier_successes() {
+    let outcomes = tools()
+        .call_many(
+            [
+                call("fast", json!(1)),
+                call("slow", json!(1)),
+                call("fail", json!({})),
+                call("slow", json!(2_000)),
+            ],
+            BatchMode::FailFast,
+        )
+        .await;
+
+    assert_eq!(
+        shapes(&outcomes),
+        ["completed", "completed", "failed", "cancelled"]
+    );
+    let CallOutcome::Completed(first) = &outcomes[0] else {
+        unreachable!()
+    };
+    assert_eq!(first.result, json!(1));
+}
+
+#[tokio::test]
+async fn retryable_failures_do_not_cancel() {
+    let outcomes = tools()
+        .call_many(
+            [
+                call("fail", json!({ "transient": true })),
+                call("slow", json!(50)),
+            ],
+            BatchMode::FailFast,
+        )
+        .await;
+    assert_eq!(shapes(&outcomes), ["failed", "completed"]);
+}
+
+// ---------- independent ----------
+
+#[tokio::test]
+async fn independent_runs_everything() {
+    let outcomes = tools()
+        .call_many(
+            [
+                call("fail", json!({})),
+                call("slow", json!(50)),
+                call("missing", json!({})),
+                call("fast", json!(2)),
+            ],
+            BatchMode::Independent,
+        )
+        .await;
+
+    assert_eq!(
+        shapes(&outcomes),
+        ["failed", "completed", "failed", "completed"]
+    );
+    let results: Vec<_> = outcomes
+        .into_iter()
+        .map(|o| o.into_result().unwrap().map(|r| r.result).ok())
+        .collect();
+    assert_eq!(results, [None, Some(json!(50)), None, Some(json!(2))]);
+}
+
+#[tokio::test]
+async fn empty_batch() {
+    assert!(
+        tools()
+            .call_many(Vec::new(), BatchMode::FailFast)
+            .await
+            .is_empty()
+    );
+}
+
+// ---------- call_all ----------
+
+#[tokio::test]
+async fn call_all_keeps_order_and_ids_when_a_call_fails() {
+    let calls = vec![
+        call("slow", json!(50)),
+        call("missing", json!({})),
+        call("fast", json!(3)),
+    ];
+    let ids: Vec<_> = calls.iter().map(|c| c.id.clone()).collect();
+    let results = tools().call_all(calls, 2).await;
+
+    assert_eq!(results.len(), 3);
+    let first = results[0].as_ref().unwrap();
+    assert_eq!((&first.id, &first.result), (&ids[0], &json!(50)));
+    assert!(matches!(
+        &results[1],
+        Err(ToolError::FunctionNotFound { name }) if name == "missing"
+    ));
+    let third = results[2].as_ref().unwrap();
+    assert_eq!((&third.id, &third.result), (&ids[2], &json!(3)));
+}
+
+#[tokio::test]
+async fn call_all_bounds_concurrency() {
+    let calls = (0..4).map(|_| call("slow", json!(100))).collect();
+    let start = Instant::now();
+    let results = tools().call_all(calls, 2).await;
+
+    assert!(results.iter().all(Result::is_ok));
+    // Two rounds of two.
+    assert!(start.elapsed() >= Duration::from_millis(200));
+}
@@ -0,0 +1,133 @@
+//! Dispatch and completion numbers give concurrent calls one total order,
+//! and the recent-calls history keeps the last few.
+
+use std::{collections::HashSet, sync::Arc};
+
+use serde_json::json;
+use tools_rs::{CallId, ErrorClass, FunctionCall, ToolCollection, ToolSpec};
+
+fn tools() -> ToolCollection {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register_spec(
+            ToolSpec::new("work", "Yields, then echoes.").handler(|args| {
+                Box::pin(async move {
+                    tokio::task::yield_now().await;
+                    Ok(args)
+                })
+            }),
+        )
+        .unwrap();
+    tools
+        .register("strict", "Takes a number.", |n: u32| async move { n }, ())
+        .unwrap();
+    tools
+}
+
+// ---------- ordering ----------
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+async fn sequence_is_unique_and_monotonic_under_load() {
+    const TASKS: usize = 10_000;
+    let tools = Arc::new(tools());
+    let handles: Vec<_> = (0..TASKS)
+        .map(|i| {
+            let tools = tools.clone();
+            tokio::spawn(async move {
+                tools
+                    .call(FunctionCall::new("work".into(), json!(i)))
+                    .await
+                    .unwrap()
+            })
+        })
+        .collect();
+
+    let mut seen = HashSet::with_capacity(2 * TASKS);
+    for handle in handles {
+        let seq = handle.await.unwrap().seq.unwrap();
+        assert!(seq.dispatch < seq.completion, "{seq:?}");
+        assert!(seen.insert(seq.dispatch), "{seq:?}");
+        assert!(seen.insert(seq.completion), "{seq:?}");
+    }
+
+    let recent = tools.recent_calls(usize::MAX);
+    assert_eq!(recent.len(), tools_rs::sequence::DEFAULT_RECENT_CALLS);
+    assert!(
+        recent
+            .windows(2)
+            .all(|w| w[0].seq.completion < w[1].seq.completion)
+    );
+}
+
+#[tokio::test]
+async fn sequential_calls_never_overlap() {
+    let tools = tools();
+    let a = tools
+        .call(FunctionCall::new("work".into(), json!(1)))
+        .await
+        .unwrap()
+        .seq
+        .unwrap();
+    let b = tools
+        .call(FunctionCall::new("work".into(), json!(2)))
+        .await
+        .unwrap()
+        .seq
+        .unwrap();
+    assert!(a.completion < b.dispatch);
+}
+
+// ---------- recent calls ----------
+
+#[tokio::test]
+async fn recent_calls_include_failures() {
+    let tools = tools();
+    let id = CallId::new();
+    tools
+        .call(FunctionCall {
+            id: Some(id.clone()),
+            name: "work".into(),
+            arguments: json!(1),
+            repaired: false,
+        })
+        .await
+        .unwrap();
+    tools
+        .call(FunctionCall::new("strict".into(), json!("x")))
+        .await
+        .unwrap_err();
+
+    let recent = tools.recent_calls(usize::MAX);
+    assert_eq!(recent.len(), 2);
+    assert_eq!(recent[0].tool, "work");
+    assert_eq!(recent[0].call_id, Some(id.to_string()));
+    assert_eq!(recent[0].error, None);
+    assert_eq!(recent[1].tool, "strict");
+    assert_eq!(recent[1].error, Some(ErrorClass::InvalidInput));
+    assert!(recent[1].seq.dispatch < recent[1].seq.completion);
+
+    // Shared with clones, and part of the introspection report.
+    let report = serde_json::to_value(tools.clone().introspect()).unwrap();
+    assert_eq!(report["recent_calls"][1]["error"], "invalid_input");
+}
+
+#[tokio::test]
+async fn capacity_bounds_the_history() {
+    let tools = tools();
+    tools.set_recent_calls_capacity(2);
+    for i in 0..5 {
+        tools
+            .call(FunctionCall::new("work".into(), json!(i)))
+            .await
+            .unwrap();
+    }
+    assert_eq!(tools.recent_calls(usize::MAX).len(), 2);
+
+    tools.set_recent_calls_capacity(0);
+    assert!(tools.recent_calls(usize::MAX).is_empty());
+    tools
+        .call(FunctionCall::new("work".into(), json!(0)))
+        .await
+        .unwrap();
+    assert!(tools.recent_calls(usize::MAX).is_empty());
+}
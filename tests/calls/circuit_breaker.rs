@@ -0,0 +1,283 @@
+//! Per-tool circuit breakers, driven by a mock tool whose failures are
+//! switched on and off and a `ManualClock`.
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use serde_json::{Value, json};
+use tools_rs::{
+    CircuitConfig, ErrorClass, FunctionCall, ToolCollection, ToolError, ToolSpec,
+    circuit::{CircuitState, ManualClock},
+    deadline,
+};
+
+use crate::common;
+
+/// A `weather` tool that fails transiently while `down` is set and counts
+/// how often it actually ran.
+struct Mock {
+    down: Arc<AtomicBool>,
+    runs: Arc<AtomicUsize>,
+    clock: Arc<ManualClock>,
+    tools: ToolCollection,
+}
+
+const CONFIG: CircuitConfig = CircuitConfig {
+    failure_threshold: 3,
+    cooldown: Duration::from_secs(10),
+};
+
+fn mock() -> Mock {
+    let down = Arc::new(AtomicBool::new(true));
+    let runs = Arc::new(AtomicUsize::new(0));
+    let clock = Arc::new(ManualClock::new());
+
+    let (d, r) = (down.clone(), runs.clone());
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register_spec(
+            ToolSpec::new("weather", "Current weather.").handler(move |_| {
+                r.fetch_add(1, Ordering::SeqCst);
+                let down = d.load(Ordering::SeqCst);
+                Box::pin(async move {
+                    if down {
+                        Err(ToolError::classified(ErrorClass::Transient, "timed out"))
+                    } else {
+                        Ok(json!("sunny"))
+                    }
+                })
+            }),
+        )
+        .unwrap();
+    tools
+        .set_circuit_breaker_with_clock("weather", CONFIG, clock.clone())
+        .unwrap();
+
+    Mock {
+        down,
+        runs,
+        clock,
+        tools,
+    }
+}
+
+async fn call(tools: &ToolCollection) -> Result<Value, ToolError> {
+    tools
+        .call(FunctionCall::new("weather".into(), json!({})))
+        .await
+        .map(|r| r.result)
+}
+
+// ---------- opening ----------
+
+#[tokio::test]
+async fn opens_after_threshold_and_short_circuits() {
+    let m = mock();
+    for _ in 0..3 {
+        assert!(call(&m.tools).await.unwrap_err().is_retryable());
+    }
+    assert_eq!(
+        m.tools.circuit("weather").unwrap().state,
+        CircuitState::Open
+    );
+
+    m.clock.advance(Duration::from_secs(4));
+    match call(&m.tools).await {
+        Err(ToolError::CircuitOpen { name, retry_after }) => {
+            assert_eq!(name, "weather");
+            assert_eq!(retry_after, Duration::from_secs(6));
+        }
+        other => panic!("expected CircuitOpen, got {other:?}"),
+    }
+    assert_eq!(m.runs.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn success_resets_the_failure_count() {
+    let m = mock();
+    for _ in 0..2 {
+        call(&m.tools).await.unwrap_err();
+    }
+    m.down.store(false, Ordering::SeqCst);
+    call(&m.tools).await.unwrap();
+    assert_eq!(m.tools.circuit("weather").unwrap().consecutive_failures, 0);
+
+    m.down.store(true, Ordering::SeqCst);
+    for _ in 0..2 {
+        call(&m.tools).await.unwrap_err();
+    }
+    assert_eq!(
+        m.tools.circuit("weather").unwrap().state,
+        CircuitState::Closed
+    );
+}
+
+#[tokio::test]
+async fn caller_errors_do_not_count() {
+    let mut tools = common::tools();
+    tools
+        .register_spec(ToolSpec::new("locked", "Needs a key.").handler(|_| {
+            Box::pin(async { Err(ToolError::classified(ErrorClass::Denied, "no key")) })
+        }))
+        .unwrap();
+    tools.set_circuit_breaker("double", CONFIG).unwrap();
+    tools.set_circuit_breaker("locked", CONFIG).unwrap();
+
+    for _ in 0..5 {
+        tools
+            .call(common::call("double", json!("two")))
+            .await
+            .unwrap_err();
+        tools
+            .call(common::call("locked", json!({})))
+            .await
+            .unwrap_err();
+    }
+    for name in ["double", "locked"] {
+        assert_eq!(tools.circuit(name).unwrap().state, CircuitState::Closed);
+    }
+}
+
+#[tokio::test]
+async fn runtime_errors_count() {
+    let mut tools = common::tools();
+    tools.set_circuit_breaker("fail", CONFIG).unwrap();
+    for _ in 0..CONFIG.failure_threshold {
+        let err = tools
+            .call(common::call("fail", json!({})))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolError::Runtime(_)), "{err}");
+    }
+    let err = tools
+        .call(common::call("fail", json!({})))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ToolError::CircuitOpen { .. }), "{err}");
+}
+
+#[tokio::test]
+async fn timeouts_open_the_breaker() {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register_spec(
+            ToolSpec::new("hang", "Never answers.").handler(|_| Box::pin(std::future::pending())),
+        )
+        .unwrap();
+    tools.set_circuit_breaker("hang", CONFIG).unwrap();
+    tools.set_default_timeout(Duration::from_millis(10));
+
+    for _ in 0..CONFIG.failure_threshold - 1 {
+        let err = tools
+            .call(common::call("hang", json!({})))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ToolError::Timeout { .. }), "{err}");
+    }
+    // A caller's deadline that cuts the tool off counts too.
+    let late = Instant::now() + Duration::from_millis(10);
+    let err = deadline::with_deadline(late, tools.call(common::call("hang", json!({}))))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ToolError::DeadlineExceeded { .. }), "{err}");
+
+    let err = tools
+        .call(common::call("hang", json!({})))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ToolError::CircuitOpen { ref name, .. } if name == "hang"));
+}
+
+#[tokio::test]
+async fn calls_past_their_deadline_do_not_count() {
+    let mut tools = common::tools();
+    tools.set_circuit_breaker("double", CONFIG).unwrap();
+    let past = Instant::now() - Duration::from_millis(1);
+    for _ in 0..5 {
+        let call = tools.call(common::call("double", json!(2)));
+        let err = deadline::with_deadline(past, call).await.unwrap_err();
+        assert!(matches!(err, ToolError::DeadlineExceeded { .. }), "{err}");
+    }
+    assert_eq!(tools.circuit("double").unwrap().consecutive_failures, 0);
+}
+
+// ---------- half-open probe ----------
+
+#[tokio::test]
+async fn probe_success_closes() {
+    let m = mock();
+    for _ in 0..3 {
+        call(&m.tools).await.unwrap_err();
+    }
+    m.clock.advance(CONFIG.cooldown);
+    m.down.store(false, Ordering::SeqCst);
+
+    assert_eq!(call(&m.tools).await.unwrap(), json!("sunny"));
+    assert_eq!(
+        m.tools.circuit("weather").unwrap().state,
+        CircuitState::Closed
+    );
+}
+
+#[tokio::test]
+async fn probe_failure_reopens_for_a_full_cooldown() {
+    let m = mock();
+    for _ in 0..3 {
+        call(&m.tools).await.unwrap_err();
+    }
+    m.clock.advance(CONFIG.cooldown);
+
+    assert!(call(&m.tools).await.unwrap_err().is_retryable());
+    assert_eq!(m.runs.load(Ordering::SeqCst), 4);
+    let report = m.tools.circuit("weather").unwrap();
+    assert_eq!(report.state, CircuitState::Open);
+    assert_eq!(report.retry_after_ms, Some(10_000));
+}
+
+// ---------- sharing and introspection ----------
+
+#[tokio::test]
+async fn clones_share_breaker_state() {
+    let m = mock();
+    let shared = Arc::new(m.tools.clone());
+    for _ in 0..3 {
+        call(&shared).await.unwrap_err();
+    }
+    assert!(matches!(
+        call(&m.tools).await,
+        Err(ToolError::CircuitOpen { .. })
+    ));
+}
+
+#[tokio::test]
+async fn breaker_state_is_introspected() {
+    let m = mock();
+    for _ in 0..3 {
+        call(&m.tools).await.unwrap_err();
+    }
+    let report = serde_json::to_value(m.tools.introspect()).unwrap();
+    assert_eq!(
+        report["tools"][0]["circuit"],
+        json!({
+            "state": "open",
+            "consecutive_failures": 3,
+            "failure_threshold": 3,
+            "cooldown_ms": 10_000,
+            "retry_after_ms": 10_000
+        })
+    );
+}
+
+#[test]
+fn unknown_tool_is_rejected() {
+    let mut tools: ToolCollection = ToolCollection::new();
+    assert!(matches!(
+        tools.set_circuit_breaker("missing", CircuitConfig::default()),
+        Err(ToolError::FunctionNotFound { .. })
+    ));
+}
@@ -0,0 +1,88 @@
+//! `set_compact_results` drops `null` and empty fields from what `call`
+//! returns, while call history keeps the full result.
+
+use serde_json::{Value, json};
+use tools_rs::{FunctionCall, ToolCollection};
+
+fn profile() -> Value {
+    json!({
+        "name": "Ada",
+        "email": null,
+        "tags": [],
+        "address": { "line2": null, "meta": {} },
+        "visits": [null, 3]
+    })
+}
+
+fn tools() -> ToolCollection {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register_raw(
+            "profile",
+            "Looks up a profile.",
+            json!({ "type": "object" }),
+            |_| Box::pin(async { Ok(profile()) }),
+            (),
+        )
+        .unwrap()
+        .register_raw(
+            "flag",
+            "Reads a flag; null means unset.",
+            json!({ "type": "object" }),
+            |_| Box::pin(async { Ok(json!({ "value": null })) }),
+            (),
+        )
+        .unwrap();
+    tools
+}
+
+async fn call(tools: &ToolCollection, name: &str) -> Value {
+    tools
+        .call(FunctionCall::new(name.into(), json!({})))
+        .await
+        .unwrap()
+        .result
+}
+
+#[tokio::test]
+async fn results_are_untouched_by_default() {
+    let tools = tools();
+    assert!(!tools.compact_results());
+    assert_eq!(call(&tools, "profile").await, profile());
+}
+
+#[tokio::test]
+async fn returned_result_is_compacted_and_history_is_not() {
+    let mut tools = tools();
+    tools.set_compact_results(true);
+    tools.enable_history(4);
+
+    let result = call(&tools, "profile").await;
+    assert_eq!(result, json!({ "name": "Ada", "visits": [null, 3] }));
+
+    let recorded = tools.recent_calls(1).pop().unwrap();
+    assert_eq!(recorded.result, Some(profile()));
+}
+
+#[tokio::test]
+async fn exempt_tools_keep_their_nulls() {
+    let mut tools = tools();
+    tools.set_compact_results(true);
+    tools.set_compaction_exempt("flag", true).unwrap();
+
+    assert_eq!(call(&tools, "flag").await, json!({ "value": null }));
+    assert!(call(&tools, "profile").await.get("email").is_none());
+
+    tools.set_compaction_exempt("flag", false).unwrap();
+    assert_eq!(call(&tools, "flag").await, json!({}));
+    assert!(tools.set_compaction_exempt("missing", true).is_err());
+}
+
+#[tokio::test]
+async fn builder_turns_it_on() {
+    let tools: ToolCollection = ToolCollection::builder()
+        .compact_results(true)
+        .collect()
+        .unwrap();
+    assert!(tools.compact_results());
+}
@@ -0,0 +1,39 @@
+//! The facade re-exports `tools_core`'s error types rather than defining
+//! its own, so `?` works across both paths.
+
+use std::any::TypeId;
+
+fn core_call() -> Result<(), tools_core::ToolError> {
+    Err(tools_core::ToolError::Runtime("boom".into()))
+}
+
+fn facade_call() -> Result<(), tools_rs::ToolError> {
+    core_call()?;
+    Ok(())
+}
+
+fn core_decode() -> Result<u32, tools_core::DeserializationError> {
+    serde_json::from_str::<u32>("\"x\"").map_err(tools_core::DeserializationError::from)
+}
+
+fn facade_decode() -> Result<u32, tools_rs::ToolError> {
+    let value: Result<u32, tools_rs::DeserializationError> = core_decode();
+    Ok(value?)
+}
+
+#[test]
+fn error_types_are_shared() {
+    assert_eq!(
+        TypeId::of::<tools_rs::ToolError>(),
+        TypeId::of::<tools_core::ToolError>()
+    );
+    assert_eq!(
+        TypeId::of::<tools_rs::DeserializationError>(),
+        TypeId::of::<tools_core::DeserializationError>()
+    );
+    assert!(matches!(facade_call(), Err(tools_rs::ToolError::Runtime(m)) if m == "boom"));
+    assert!(matches!(
+        facade_decode(),
+        Err(tools_rs::ToolError::Deserialize(_))
+    ));
+}
@@ -0,0 +1,145 @@
+//! Repeated identical failures reach a `FailureSink` once, with a count
+//! when their window closes.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use serde_json::{Value, json};
+use tools_rs::{
+    ErrorClass, FailureEvent, FailureSink, FunctionCall, ToolCollection, circuit::ManualClock,
+};
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+#[derive(Default)]
+struct Sink {
+    events: Mutex<Vec<FailureEvent>>,
+    raw: bool,
+}
+
+impl Sink {
+    fn take(&self) -> Vec<FailureEvent> {
+        std::mem::take(&mut *self.events.lock().unwrap())
+    }
+}
+
+impl FailureSink for Sink {
+    fn record(&self, event: FailureEvent) {
+        self.events.lock().unwrap().push(event);
+    }
+
+    fn collapse(&self) -> bool {
+        !self.raw
+    }
+}
+
+fn tools(sink: Arc<Sink>, clock: Arc<ManualClock>) -> ToolCollection {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register("strict", "Takes a number.", |n: u32| async move { n }, ())
+        .unwrap();
+    tools.set_failure_sink_with_clock(sink, WINDOW, clock);
+    tools
+}
+
+async fn fail(tools: &ToolCollection, arguments: Value) {
+    tools
+        .call(FunctionCall::new("strict".into(), arguments))
+        .await
+        .unwrap_err();
+}
+
+// ---------- collapsing ----------
+
+#[tokio::test]
+async fn repeats_collapse_into_one_summary() {
+    let sink = Arc::new(Sink::default());
+    let clock = Arc::new(ManualClock::new());
+    let tools = tools(sink.clone(), clock.clone());
+    for _ in 0..1000 {
+        fail(&tools, json!("x")).await;
+    }
+    let events = sink.take();
+    assert_eq!(events.len(), 1);
+    let FailureEvent::Failed(first) = &events[0] else {
+        panic!("{events:?}");
+    };
+    assert_eq!(first.tool, "strict");
+    assert_eq!(first.class, ErrorClass::InvalidInput);
+
+    // The window closes on the next failure after it runs out.
+    clock.advance(WINDOW);
+    fail(&tools, json!("x")).await;
+    let events = sink.take();
+    assert_eq!(
+        events[0],
+        FailureEvent::Repeated {
+            first: first.clone(),
+            count: 1000,
+            window_ms: 60_000,
+        }
+    );
+    // A new window, whose first failure is reported again.
+    let FailureEvent::Failed(again) = &events[1] else {
+        panic!("{events:?}");
+    };
+    assert_eq!(again.args_hash, first.args_hash);
+    assert_ne!(again.call_id, first.call_id);
+}
+
+#[tokio::test]
+async fn different_arguments_are_different_failures() {
+    let sink = Arc::new(Sink::default());
+    let tools = tools(sink.clone(), Arc::new(ManualClock::new()));
+    fail(&tools, json!("x")).await;
+    fail(&tools, json!("y")).await;
+    fail(&tools, json!("x")).await;
+    let events = sink.take();
+    assert_eq!(events.len(), 2);
+    assert!(events.iter().all(|e| matches!(e, FailureEvent::Failed(_))));
+
+    // Successes are not reported.
+    tools
+        .call(FunctionCall::new("strict".into(), json!(1)))
+        .await
+        .unwrap();
+    assert!(sink.take().is_empty());
+}
+
+#[tokio::test]
+async fn flush_reports_open_windows() {
+    let sink = Arc::new(Sink::default());
+    let tools = tools(sink.clone(), Arc::new(ManualClock::new()));
+    for _ in 0..3 {
+        fail(&tools, json!("x")).await;
+    }
+    fail(&tools, json!(-1)).await;
+    sink.take();
+
+    tools.flush_failures();
+    let events = sink.take();
+    assert_eq!(events.len(), 1, "single failures need no summary");
+    assert!(matches!(events[0], FailureEvent::Repeated { count: 3, .. }));
+    tools.flush_failures();
+    assert!(sink.take().is_empty());
+}
+
+// ---------- raw sinks ----------
+
+#[tokio::test]
+async fn raw_sinks_see_every_failure() {
+    let sink = Arc::new(Sink {
+        raw: true,
+        ..Sink::default()
+    });
+    let tools = tools(sink.clone(), Arc::new(ManualClock::new()));
+    for _ in 0..5 {
+        fail(&tools, json!("x")).await;
+    }
+    tools.flush_failures();
+    let events = sink.take();
+    assert_eq!(events.len(), 5);
+    assert_eq!(serde_json::to_value(&events[0]).unwrap()["event"], "failed");
+}
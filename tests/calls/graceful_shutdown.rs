@@ -0,0 +1,147 @@
+//! `shutdown_gracefully` on a shared collection: refuse new calls, drain
+//! or cancel running ones, then run cleanups and flush the sinks.
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use serde_json::json;
+use tools_rs::{ErrorClass, FunctionCall, ToolCollection, ToolError, UsageEvent, UsageSink};
+
+/// Sets its flag when dropped, to tell a cancelled tool future apart from
+/// one left running.
+struct DropFlag(Arc<AtomicBool>);
+
+impl Drop for DropFlag {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+#[derive(Default)]
+struct Flushes(AtomicUsize);
+
+impl UsageSink for Flushes {
+    fn record(&self, _: UsageEvent) {}
+
+    fn flush(&self) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+struct Fixture {
+    tools: Arc<ToolCollection>,
+    cleaned: Arc<AtomicBool>,
+    dropped: Arc<AtomicBool>,
+    flushes: Arc<Flushes>,
+}
+
+/// A `sleep` tool taking milliseconds, with a cleanup.
+fn fixture() -> Fixture {
+    let cleaned = Arc::new(AtomicBool::new(false));
+    let dropped = Arc::new(AtomicBool::new(false));
+    let flushes = Arc::new(Flushes::default());
+    let mut tools: ToolCollection = ToolCollection::new();
+    let flag = dropped.clone();
+    let done = cleaned.clone();
+    tools
+        .register_with_cleanup(
+            "sleep",
+            "Sleeps for the given milliseconds.",
+            move |ms: u64| {
+                let guard = DropFlag(flag.clone());
+                async move {
+                    tokio::time::sleep(Duration::from_millis(ms)).await;
+                    std::mem::forget(guard);
+                    ms
+                }
+            },
+            (),
+            move || Box::pin(async move { done.store(true, Ordering::SeqCst) }),
+        )
+        .unwrap();
+    tools.set_usage_sink(flushes.clone());
+    Fixture {
+        tools: Arc::new(tools),
+        cleaned,
+        dropped,
+        flushes,
+    }
+}
+
+fn sleep(ms: u64) -> FunctionCall {
+    FunctionCall::new("sleep".into(), json!(ms))
+}
+
+async fn started(tools: &ToolCollection) {
+    while tools.in_flight() == 0 {
+        tokio::task::yield_now().await;
+    }
+}
+
+#[tokio::test]
+async fn running_calls_drain_within_the_grace_period() {
+    let f = fixture();
+    let tools = f.tools.clone();
+    let running = tokio::spawn(async move { tools.call(sleep(50)).await });
+    started(&f.tools).await;
+
+    let report = f.tools.shutdown_gracefully(Duration::from_secs(5)).await;
+    assert_eq!(report.in_flight, 1);
+    assert_eq!(report.drained, 1);
+    assert_eq!(report.cancelled, 0);
+    assert_eq!(report.cleanups, 1);
+    assert_eq!(running.await.unwrap().unwrap().result, json!(50));
+    assert!(f.cleaned.load(Ordering::SeqCst));
+    assert!(f.flushes.0.load(Ordering::SeqCst) >= 1);
+
+    // New calls, through the collection or a clone, are refused.
+    assert!(f.tools.is_shutting_down());
+    let err = f.tools.call(sleep(0)).await.unwrap_err();
+    assert!(matches!(err, ToolError::ShuttingDown));
+    assert_eq!(err.classification(), ErrorClass::Transient);
+    let clone = (*f.tools).clone();
+    assert!(matches!(
+        clone.call(sleep(0)).await,
+        Err(ToolError::ShuttingDown)
+    ));
+    // Declarations are still there.
+    assert!(f.tools.get("sleep").is_some());
+}
+
+#[tokio::test]
+async fn stragglers_are_cancelled_after_the_grace_period() {
+    let f = fixture();
+    let tools = f.tools.clone();
+    let running = tokio::spawn(async move { tools.call(sleep(60_000)).await });
+    started(&f.tools).await;
+
+    let start = Instant::now();
+    let report = f.tools.shutdown_gracefully(Duration::from_millis(30)).await;
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert_eq!(report.in_flight, 1);
+    assert_eq!(report.drained, 0);
+    assert_eq!(report.cancelled, 1);
+    assert_eq!(f.tools.in_flight(), 0);
+
+    let err = running.await.unwrap().unwrap_err();
+    assert!(matches!(err, ToolError::ShuttingDown));
+    // The tool's future was dropped, not left sleeping.
+    assert!(f.dropped.load(Ordering::SeqCst));
+    assert!(f.cleaned.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn idle_collections_shut_down_at_once() {
+    let f = fixture();
+    let report = f.tools.shutdown_gracefully(Duration::from_secs(60)).await;
+    assert_eq!((report.in_flight, report.cancelled), (0, 0));
+    assert!(report.elapsed_ms < 5_000);
+    // A second shutdown has no cleanups left to run.
+    let again = f.tools.shutdown_gracefully(Duration::from_secs(60)).await;
+    assert_eq!(again.cleanups, 0);
+}
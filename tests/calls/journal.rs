@@ -0,0 +1,208 @@
+//! Write-ahead journaling with a file journal, including a simulated
+//! crash between the intent and the outcome and a torn final write.
+
+use std::{fs, io::Write, path::PathBuf, sync::Arc};
+
+use serde::Deserialize;
+use serde_json::json;
+use tokio::sync::Notify;
+use tools_rs::{
+    CallId, ErrorClass, FileJournal, FunctionCall, Intent, Journal, JournalOutcome, ToolCollection,
+    ToolError, hash::canonical_hash,
+};
+
+/// A fresh journal path under the system temp dir.
+fn journal_path(test: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "tools-rs-journal-{test}-{}.jsonl",
+        std::process::id()
+    ));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct Policy {
+    side_effects: bool,
+}
+
+fn tools(journal: Arc<dyn Journal>) -> ToolCollection<Policy> {
+    let mut tools = ToolCollection::<Policy>::new();
+    tools
+        .register(
+            "send_email",
+            "Sends an email.",
+            |to: String| async move { format!("sent to {to}") },
+            Policy { side_effects: true },
+        )
+        .unwrap();
+    tools
+        .register_raw(
+            "bounce",
+            "Always fails.",
+            json!({ "type": "object" }),
+            |_| Box::pin(async { Err(ToolError::Runtime("mailbox full".into())) }),
+            Policy { side_effects: true },
+        )
+        .unwrap();
+    tools
+        .register(
+            "lookup",
+            "Reads a contact.",
+            |name: String| async move { name },
+            Policy::default(),
+        )
+        .unwrap();
+    assert_eq!(tools.set_journal_where(journal, |p| p.side_effects), 2);
+    tools
+}
+
+// ---------- journaling ----------
+
+#[tokio::test]
+async fn completed_calls_are_not_reported() {
+    let path = journal_path("completed");
+    let journal = Arc::new(FileJournal::open(&path).unwrap());
+    let tools = tools(journal.clone());
+
+    tools
+        .call(FunctionCall::new(
+            "send_email".into(),
+            json!("a@example.com"),
+        ))
+        .await
+        .unwrap();
+    tools
+        .call(FunctionCall::new("bounce".into(), json!({ "_": "x" })))
+        .await
+        .unwrap_err();
+    tools
+        .call(FunctionCall::new("lookup".into(), json!("Ada")))
+        .await
+        .unwrap();
+
+    assert!(tools.recover(journal.as_ref()).unwrap().is_empty());
+    let text = fs::read_to_string(&path).unwrap();
+    assert_eq!(text.lines().count(), 4, "{text}");
+    assert!(text.contains(r#"{"failed":{"class":"internal"}}"#));
+    assert!(!text.contains("lookup"));
+}
+
+#[tokio::test]
+async fn undecodable_calls_leave_no_intent() {
+    let path = journal_path("undecodable");
+    let journal = Arc::new(FileJournal::open(&path).unwrap());
+    let tools = tools(journal.clone());
+
+    tools
+        .call(FunctionCall::new("send_email".into(), json!(42)))
+        .await
+        .unwrap_err();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "");
+}
+
+// ---------- recovery ----------
+
+#[tokio::test]
+async fn crash_between_intent_and_outcome_is_recovered() {
+    let path = journal_path("crash");
+    let started = Arc::new(Notify::new());
+    let call_id = CallId::new();
+    let args = json!("b@example.com");
+
+    {
+        let journal = Arc::new(FileJournal::open(&path).unwrap());
+        let mut tools: ToolCollection = ToolCollection::new();
+        let s = started.clone();
+        tools
+            .register(
+                "send_email",
+                "Sends an email, then hangs before replying.",
+                move |_: String| {
+                    let s = s.clone();
+                    async move {
+                        s.notify_one();
+                        std::future::pending::<String>().await
+                    }
+                },
+                (),
+            )
+            .unwrap();
+        tools.set_journal("send_email", journal).unwrap();
+
+        // The process "crashes" once the tool is running: the call is
+        // dropped and nothing after the intent is written.
+        let call = FunctionCall {
+            id: Some(call_id.clone()),
+            name: "send_email".into(),
+            arguments: args.clone(),
+            repaired: false,
+        };
+        tokio::select! {
+            _ = tools.call(call) => unreachable!(),
+            _ = started.notified() => {}
+        }
+    }
+
+    let journal = FileJournal::open(&path).unwrap();
+    let tools: ToolCollection = ToolCollection::new();
+    let pending = tools.recover(&journal).unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].tool, "send_email");
+    assert_eq!(pending[0].call_id, Some(call_id.to_string()));
+    assert_eq!(pending[0].args_hash, canonical_hash(&args));
+
+    // Reconciling closes it for good, and new intents get fresh numbers.
+    journal
+        .complete(pending[0].seq, JournalOutcome::Reconciled)
+        .unwrap();
+    let next = journal.begin(pending[0].clone()).unwrap();
+    assert!(next > pending[0].seq);
+    journal
+        .complete(
+            next,
+            JournalOutcome::Failed {
+                class: ErrorClass::Transient,
+            },
+        )
+        .unwrap();
+    assert!(
+        FileJournal::open(&path)
+            .unwrap()
+            .incomplete()
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn torn_last_record_is_ignored() {
+    let path = journal_path("torn");
+    let journal = FileJournal::open(&path).unwrap();
+    let intent = Intent {
+        seq: 0,
+        call_id: None,
+        tool: "send_email".into(),
+        args_hash: 7,
+        dispatch_seq: 0,
+    };
+    let seq = journal.begin(intent).unwrap();
+    drop(journal);
+
+    // A crash while writing the outcome leaves half a line behind.
+    let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+    write!(file, r#"{{"complete":{{"seq":{seq},"outc"#).unwrap();
+    drop(file);
+
+    let journal = FileJournal::open(&path).unwrap();
+    assert_eq!(journal.incomplete().unwrap()[0].seq, seq);
+    journal.complete(seq, JournalOutcome::Reconciled).unwrap();
+    assert!(
+        FileJournal::open(&path)
+            .unwrap()
+            .incomplete()
+            .unwrap()
+            .is_empty()
+    );
+}
@@ -0,0 +1,26 @@
+//! Calling tools: arguments, batches, caching, limits, and the records
+//! calls leave behind.
+
+#[path = "../common/mod.rs"]
+mod common;
+
+mod bare_inputs;
+mod bound_tools;
+mod broker;
+mod call_history;
+mod call_many;
+mod call_sequence;
+mod circuit_breaker;
+mod compact_results;
+mod error_paths;
+mod failure_log;
+mod graceful_shutdown;
+mod journal;
+mod migrations;
+mod queued_calls;
+mod result_cache;
+mod schedule;
+mod schema_validation;
+mod std_types;
+mod unknown_args;
+mod usage_events;
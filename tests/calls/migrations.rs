@@ -0,0 +1,200 @@
+//! Migrations let tools accept argument shapes they used to take.
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tools_rs::{
+    FunctionCall, MigrationMode, MigrationSpec, ToolCollection, ToolError, ToolSchema, UnknownArgs,
+};
+
+#[derive(Serialize, Deserialize, ToolSchema)]
+struct Search {
+    query: String,
+    limit: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, ToolSchema)]
+struct Point {
+    lat: f64,
+    lon: f64,
+}
+
+fn tools() -> ToolCollection {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register(
+            "search",
+            "Searches.",
+            |s: Search| async move { format!("{} ({})", s.query, s.limit.unwrap_or(10)) },
+            (),
+        )
+        .unwrap();
+    tools
+        .register(
+            "weather",
+            "Weather at a point.",
+            |p: Point| async move { format!("{:.1},{:.1}", p.lat, p.lon) },
+            (),
+        )
+        .unwrap();
+    tools
+}
+
+/// `q` became `query`.
+fn q_to_query(mut args: Value) -> Result<Value, ToolError> {
+    if let Some(q) = args.as_object_mut().and_then(|a| a.remove("q")) {
+        args["query"] = q;
+    }
+    Ok(args)
+}
+
+/// `"lat,lon"` strings became an object.
+fn split_location(args: Value) -> Result<Value, ToolError> {
+    let Some(location) = args["location"].as_str() else {
+        return Ok(args);
+    };
+    let parts: Vec<f64> = location
+        .split(',')
+        .map(|p| p.trim().parse())
+        .collect::<Result<_, _>>()
+        .map_err(|_| ToolError::Runtime(format!("bad location `{location}`")))?;
+    let [lat, lon] = parts[..] else {
+        return Err(ToolError::Runtime(format!("bad location `{location}`")));
+    };
+    Ok(json!({ "lat": lat, "lon": lon }))
+}
+
+async fn call(tools: &ToolCollection, name: &str, args: Value) -> (Value, Vec<String>) {
+    let response = tools
+        .call(FunctionCall::new(name.into(), args))
+        .await
+        .unwrap();
+    (response.result, response.migrations)
+}
+
+// ---------- migrations ----------
+
+#[tokio::test]
+async fn renamed_parameters_are_migrated() {
+    let mut tools = tools();
+    tools
+        .add_migration("search", "q-to-query", q_to_query)
+        .unwrap();
+    assert_eq!(
+        call(&tools, "search", json!({ "q": "rust", "limit": 3 })).await,
+        (json!("rust (3)"), vec!["q-to-query".to_string()])
+    );
+    // Checked before unknown arguments are.
+    tools.set_unknown_args(UnknownArgs::Reject);
+    assert_eq!(
+        call(&tools, "search", json!({ "q": "rust" })).await.0,
+        "rust (10)"
+    );
+    tools
+        .dry_run(&FunctionCall::new("search".into(), json!({ "q": "rust" })))
+        .unwrap();
+}
+
+#[tokio::test]
+async fn structural_migrations_run_in_order() {
+    let mut tools = tools();
+    tools
+        .add_migration("weather", "location-string", split_location)
+        .unwrap()
+        .add_migration("weather", "never-needed", Ok)
+        .unwrap();
+    assert_eq!(
+        call(&tools, "weather", json!({ "location": "48.86, 2.35" })).await,
+        (json!("48.9,2.4"), vec!["location-string".to_string()])
+    );
+
+    let err = tools
+        .call(FunctionCall::new(
+            "weather".into(),
+            json!({ "location": "paris" }),
+        ))
+        .await
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Runtime error: bad location `paris`");
+}
+
+#[tokio::test]
+async fn current_arguments_skip_migrations() {
+    let runs = Arc::new(AtomicUsize::new(0));
+    let mut tools = tools();
+    let counted = runs.clone();
+    tools
+        .add_migration("search", "q-to-query", move |args| {
+            counted.fetch_add(1, Ordering::Relaxed);
+            q_to_query(args)
+        })
+        .unwrap();
+
+    let current = json!({ "query": "rust" });
+    assert_eq!(
+        call(&tools, "search", current.clone()).await.1,
+        Vec::<String>::new()
+    );
+    assert_eq!(runs.load(Ordering::Relaxed), 0);
+
+    // `Always` runs them anyway, but only reports those that changed something.
+    tools.set_migration_mode(MigrationMode::Always);
+    assert_eq!(
+        call(&tools, "search", current).await.1,
+        Vec::<String>::new()
+    );
+    assert_eq!(runs.load(Ordering::Relaxed), 1);
+}
+
+// ---------- configuration ----------
+
+#[tokio::test]
+async fn renames_from_config() {
+    let specs: Vec<MigrationSpec> = serde_json::from_value(json!([
+        { "tool": "search", "name": "v1-names", "rename": { "q": "query", "n": "limit" } }
+    ]))
+    .unwrap();
+    let mut tools = tools();
+    tools.add_migrations(specs).unwrap();
+    assert_eq!(
+        call(&tools, "search", json!({ "q": "rust", "n": 5 })).await,
+        (json!("rust (5)"), vec!["v1-names".to_string()])
+    );
+
+    let missing = MigrationSpec {
+        tool: "nope".into(),
+        name: "x".into(),
+        rename: Default::default(),
+    };
+    assert!(matches!(
+        tools.add_migrations([missing]),
+        Err(ToolError::FunctionNotFound { .. })
+    ));
+}
+
+#[test]
+fn responses_list_migrations_only_when_present() {
+    let response = tools_rs::FunctionResponse {
+        id: None,
+        name: "search".into(),
+        result: json!(null),
+        stale: false,
+        seq: None,
+        warnings: Vec::new(),
+        migrations: vec!["q-to-query".into()],
+        is_error: false,
+        repaired: false,
+    };
+    let value = serde_json::to_value(&response).unwrap();
+    assert_eq!(value["migrations"], json!(["q-to-query"]));
+    let value = serde_json::to_value(tools_rs::FunctionResponse {
+        migrations: Vec::new(),
+        ..response
+    })
+    .unwrap();
+    assert!(value.get("migrations").is_none());
+}
@@ -0,0 +1,170 @@
+//! `QueuedTools` runs calls in enqueue order with bounded concurrency,
+//! turns away overflow, and drains on shutdown.
+
+use std::{
+    pin::Pin,
+    sync::{Arc, Mutex},
+};
+
+use serde_json::json;
+use tokio::sync::Semaphore;
+use tools_rs::{ErrorClass, FunctionCall, QueuedTools, ToolCollection, ToolError};
+
+type BoxFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// `log` records its argument; `gated` waits for a permit first.
+fn tools(log: &Arc<Mutex<Vec<String>>>, gate: &Arc<Semaphore>) -> ToolCollection {
+    let mut tools: ToolCollection = ToolCollection::new();
+    for name in ["a", "b"] {
+        let log = log.clone();
+        tools
+            .register(
+                name,
+                "Records its argument.",
+                move |n: u32| {
+                    let log = log.clone();
+                    async move {
+                        tokio::task::yield_now().await;
+                        log.lock().unwrap().push(format!("{name}{n}"));
+                        n
+                    }
+                },
+                (),
+            )
+            .unwrap();
+    }
+    let gate = gate.clone();
+    let cleaned = log.clone();
+    tools
+        .register_with_cleanup(
+            "gated",
+            "Waits for the gate.",
+            move |n: u32| {
+                let gate = gate.clone();
+                async move {
+                    gate.acquire().await.unwrap().forget();
+                    n
+                }
+            },
+            (),
+            move || -> BoxFuture {
+                Box::pin(async move { cleaned.lock().unwrap().push("cleanup".into()) })
+            },
+        )
+        .unwrap();
+    tools
+}
+
+fn call(name: &str, n: u32) -> FunctionCall {
+    FunctionCall::new(name.into(), json!(n))
+}
+
+/// Wait until the worker has picked up `n` calls.
+async fn until_in_flight<M>(queue: &QueuedTools<M>, n: u64) {
+    while queue.report().in_flight < n {
+        tokio::task::yield_now().await;
+    }
+}
+
+// ---------- ordering ----------
+
+#[tokio::test]
+async fn one_slot_runs_calls_in_enqueue_order() {
+    let log = Arc::default();
+    let queue = tools(&log, &Arc::new(Semaphore::new(0))).queued(1, 64);
+    let pending: Vec<_> = (0..10)
+        .map(|i| queue.enqueue(call(if i % 3 == 0 { "b" } else { "a" }, i)))
+        .collect();
+    for (i, pending) in pending.into_iter().enumerate() {
+        let response = pending.await;
+        assert_eq!(response.unwrap().result, json!(i));
+    }
+    assert_eq!(
+        *log.lock().unwrap(),
+        ["b0", "a1", "a2", "b3", "a4", "a5", "b6", "a7", "a8", "b9"]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn calls_to_a_tool_start_first_in_first_out() {
+    let log = Arc::default();
+    let queue = tools(&log, &Arc::new(Semaphore::new(0))).queued(4, 256);
+    let pending: Vec<_> = (0..100)
+        .map(|i| queue.enqueue(call(if i % 2 == 0 { "a" } else { "b" }, i)))
+        .collect();
+    let mut last = [0, 0];
+    for (i, pending) in pending.into_iter().enumerate() {
+        let response = pending.await;
+        let dispatch = response.unwrap().seq.unwrap().dispatch;
+        assert!(dispatch > last[i % 2], "call {i} started out of order");
+        last[i % 2] = dispatch;
+    }
+    let report = queue.report();
+    assert_eq!((report.enqueued, report.completed), (100, 100));
+    assert_eq!((report.queued, report.in_flight), (0, 0));
+}
+
+// ---------- back-pressure ----------
+
+#[tokio::test]
+async fn overflow_is_rejected_until_the_queue_drains() {
+    let gate = Arc::new(Semaphore::new(0));
+    let queue = tools(&Arc::default(), &gate).queued(1, 2);
+
+    let running = queue.enqueue(call("gated", 0));
+    until_in_flight(&queue, 1).await;
+    let waiting = [
+        queue.enqueue(call("gated", 1)),
+        queue.enqueue(call("gated", 2)),
+    ];
+    assert_eq!(queue.depth(), 2);
+
+    let err = queue.enqueue(call("gated", 3)).await.unwrap_err();
+    assert!(matches!(err, ToolError::QueueFull { capacity: 2 }), "{err}");
+    assert_eq!(err.classification(), ErrorClass::Transient);
+    assert_eq!(queue.report().rejected, 1);
+
+    gate.add_permits(3);
+    assert_eq!(running.await.unwrap().result, json!(0));
+    for (i, pending) in waiting.into_iter().enumerate() {
+        assert_eq!(pending.await.unwrap().result, json!(i + 1));
+    }
+    assert_eq!(queue.depth(), 0);
+}
+
+// ---------- shutdown ----------
+
+#[tokio::test]
+async fn shutdown_drains_pending_calls_then_cleans_up() {
+    let log: Arc<Mutex<Vec<String>>> = Arc::default();
+    let gate = Arc::new(Semaphore::new(0));
+    let queue = tools(&log, &gate).queued(1, 8);
+
+    let pending: Vec<_> = (0..3).map(|i| queue.enqueue(call("gated", i))).collect();
+    until_in_flight(&queue, 1).await;
+    assert_eq!(queue.depth(), 2);
+
+    let shutdown = queue.shutdown();
+    tokio::pin!(shutdown);
+    // Nothing new is taken once shutdown has started...
+    tokio::select! {
+        biased;
+        () = &mut shutdown => panic!("shutdown did not wait for the gate"),
+        () = std::future::ready(()) => {}
+    }
+    let late = queue.enqueue(call("a", 9)).await.unwrap_err();
+    assert!(matches!(late, ToolError::QueueClosed), "{late}");
+
+    // ...but everything already queued still runs.
+    gate.add_permits(3);
+    shutdown.await;
+    for (i, pending) in pending.into_iter().enumerate() {
+        let response = pending.await;
+        assert_eq!(response.unwrap().result, json!(i));
+    }
+    assert_eq!(*log.lock().unwrap(), ["cleanup"]);
+    assert_eq!(queue.report().completed, 3);
+
+    // A second shutdown has nothing left to wait for.
+    queue.shutdown().await;
+}
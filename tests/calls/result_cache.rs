@@ -0,0 +1,314 @@
+//! Result caching with stale-while-revalidate, driven by a manual clock:
+//! fresh hits, stale hits with one background refresh, and expiry.
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use serde_json::{Value, json};
+use tokio::sync::Semaphore;
+use tools_rs::{
+    CachePolicy, CacheReport, ErrorClass, FunctionCall, FunctionResponse, StaticSecrets,
+    ToolCollection, ToolError, ToolSpec, circuit::ManualClock, secrets,
+};
+
+const TTL: Duration = Duration::from_secs(60);
+const SWR: Duration = Duration::from_secs(600);
+
+/// The remote API behind `rates`: counts runs, can be held at a gate and
+/// can be made to fail.
+struct Upstream {
+    runs: AtomicUsize,
+    gate: Semaphore,
+    down: AtomicBool,
+}
+
+fn setup() -> (ToolCollection, Arc<Upstream>, Arc<ManualClock>) {
+    let upstream = Arc::new(Upstream {
+        runs: AtomicUsize::new(0),
+        gate: Semaphore::new(Semaphore::MAX_PERMITS),
+        down: AtomicBool::new(false),
+    });
+    let clock = Arc::new(ManualClock::new());
+
+    let up = upstream.clone();
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register_spec(
+            ToolSpec::new("rates", "Exchange rates.").handler(move |args| {
+                let up = up.clone();
+                Box::pin(async move {
+                    up.gate.acquire().await.unwrap().forget();
+                    let run = up.runs.fetch_add(1, Ordering::SeqCst) + 1;
+                    if up.down.load(Ordering::SeqCst) {
+                        return Err(ToolError::classified(
+                            ErrorClass::Transient,
+                            "upstream down",
+                        ));
+                    }
+                    Ok(json!({ "base": args["base"], "run": run }))
+                })
+            }),
+        )
+        .unwrap();
+    tools
+        .set_cache_policy_with_clock(
+            "rates",
+            CachePolicy {
+                ttl: TTL,
+                stale_while_revalidate: SWR,
+            },
+            clock.clone(),
+        )
+        .unwrap();
+    (tools, upstream, clock)
+}
+
+async fn rates(tools: &ToolCollection, args: Value) -> FunctionResponse {
+    tools
+        .call(FunctionCall::new("rates".into(), args))
+        .await
+        .unwrap()
+}
+
+async fn usd(tools: &ToolCollection) -> FunctionResponse {
+    rates(tools, json!({ "base": "USD" })).await
+}
+
+/// Let background refreshes run until `runs` upstream calls have
+/// finished.
+async fn wait_for_runs(upstream: &Upstream, runs: usize) {
+    for _ in 0..1_000 {
+        if upstream.runs.load(Ordering::SeqCst) >= runs {
+            // The store follows the run on the same task.
+            tokio::task::yield_now().await;
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+    panic!("refresh never ran");
+}
+
+/// Let background refreshes run until `done` holds.
+async fn wait_until(done: impl Fn() -> bool) {
+    for _ in 0..1_000 {
+        if done() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+    panic!("condition never held");
+}
+
+// ---------- windows ----------
+
+#[tokio::test]
+async fn fresh_hits_skip_the_tool() {
+    let (tools, upstream, clock) = setup();
+    assert_eq!(usd(&tools).await.result["run"], 1);
+
+    clock.advance(TTL - Duration::from_secs(1));
+    let hit = usd(&tools).await;
+    assert_eq!(hit.result["run"], 1);
+    assert!(!hit.stale);
+    assert_eq!(upstream.runs.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn stale_hits_return_at_once_and_refresh_in_the_background() {
+    let (tools, upstream, clock) = setup();
+    usd(&tools).await;
+
+    clock.advance(TTL);
+    let stale = usd(&tools).await;
+    assert_eq!(stale.result["run"], 1);
+    assert!(stale.stale);
+    assert_eq!(serde_json::to_value(&stale).unwrap()["stale"], true);
+
+    wait_for_runs(&upstream, 2).await;
+    let refreshed = usd(&tools).await;
+    assert_eq!(refreshed.result["run"], 2);
+    assert!(!refreshed.stale);
+    assert!(
+        serde_json::to_value(&refreshed)
+            .unwrap()
+            .get("stale")
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn expired_results_block_on_the_tool() {
+    let (tools, upstream, clock) = setup();
+    usd(&tools).await;
+
+    clock.advance(TTL + SWR);
+    let miss = usd(&tools).await;
+    assert_eq!(miss.result["run"], 2);
+    assert!(!miss.stale);
+    assert_eq!(upstream.runs.load(Ordering::SeqCst), 2);
+}
+
+// ---------- refreshes ----------
+
+#[tokio::test]
+async fn concurrent_stale_hits_share_one_refresh() {
+    let (tools, upstream, clock) = setup();
+    usd(&tools).await;
+
+    upstream.gate.forget_permits(Semaphore::MAX_PERMITS);
+    clock.advance(TTL);
+    for _ in 0..5 {
+        assert!(usd(&tools).await.stale);
+        tokio::task::yield_now().await;
+    }
+
+    upstream.gate.add_permits(Semaphore::MAX_PERMITS);
+    wait_for_runs(&upstream, 2).await;
+    assert_eq!(usd(&tools).await.result["run"], 2);
+    assert_eq!(upstream.runs.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn failed_refresh_keeps_the_stale_result() {
+    let (tools, upstream, clock) = setup();
+    usd(&tools).await;
+
+    upstream.down.store(true, Ordering::SeqCst);
+    clock.advance(TTL);
+    assert!(usd(&tools).await.stale);
+    wait_for_runs(&upstream, 2).await;
+
+    // The failed refresh released its claim, so this hit tries again.
+    let stale = usd(&tools).await;
+    assert_eq!(stale.result["run"], 1);
+    assert!(stale.stale);
+    wait_for_runs(&upstream, 3).await;
+}
+
+#[tokio::test]
+async fn refreshes_time_out_like_calls() {
+    let (mut tools, upstream, clock) = setup();
+    tools.set_default_timeout(Duration::from_millis(20));
+    usd(&tools).await;
+
+    upstream.gate.forget_permits(Semaphore::MAX_PERMITS);
+    clock.advance(TTL);
+    assert!(usd(&tools).await.stale);
+    wait_until(|| tools.in_flight() == 1).await;
+    // Cut off by the default timeout, which releases the claim.
+    wait_until(|| tools.in_flight() == 0).await;
+
+    upstream.gate.add_permits(Semaphore::MAX_PERMITS);
+    assert!(usd(&tools).await.stale);
+    wait_for_runs(&upstream, 2).await;
+    assert_eq!(usd(&tools).await.result["run"], 2);
+}
+
+#[tokio::test]
+async fn graceful_shutdown_cancels_running_refreshes() {
+    let (tools, upstream, clock) = setup();
+    usd(&tools).await;
+
+    upstream.gate.forget_permits(Semaphore::MAX_PERMITS);
+    clock.advance(TTL);
+    assert!(usd(&tools).await.stale);
+    wait_until(|| tools.in_flight() == 1).await;
+
+    let report = tools.shutdown_gracefully(Duration::from_millis(10)).await;
+    assert_eq!((report.in_flight, report.cancelled), (1, 1));
+    assert_eq!(tools.in_flight(), 0);
+}
+
+#[tokio::test]
+async fn refreshes_see_the_collections_secrets() {
+    async fn keyed(tools: &ToolCollection) -> FunctionResponse {
+        tools
+            .call(FunctionCall::new("keyed".into(), json!({})))
+            .await
+            .unwrap()
+    }
+
+    let clock = Arc::new(ManualClock::new());
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register_spec(
+            ToolSpec::new("keyed", "Whether a secrets provider is in scope.")
+                .handler(|_| Box::pin(async { Ok(json!(secrets::current().is_some())) })),
+        )
+        .unwrap();
+    tools
+        .set_cache_policy_with_clock(
+            "keyed",
+            CachePolicy {
+                ttl: TTL,
+                stale_while_revalidate: SWR,
+            },
+            clock.clone(),
+        )
+        .unwrap();
+    assert_eq!(keyed(&tools).await.result, false);
+
+    tools.set_secrets(StaticSecrets::new().with("API_KEY", "k"));
+    clock.advance(TTL);
+    assert_eq!(keyed(&tools).await.result, false);
+    for _ in 0..1_000 {
+        if keyed(&tools).await.result == true {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+    panic!("the refresh ran without the secrets");
+}
+
+// ---------- keys ----------
+
+#[tokio::test]
+async fn results_are_keyed_by_canonical_arguments() {
+    let (tools, upstream, _) = setup();
+    rates(&tools, json!({ "base": "USD", "symbols": ["EUR"] })).await;
+    let same: Value = serde_json::from_str(r#"{"symbols":["EUR"],"base":"USD"}"#).unwrap();
+    assert_eq!(rates(&tools, same).await.result["run"], 1);
+    assert_eq!(
+        rates(&tools, json!({ "base": "EUR" })).await.result["run"],
+        2
+    );
+
+    assert_eq!(upstream.runs.load(Ordering::SeqCst), 2);
+    assert_eq!(tools.result_cache("rates").unwrap().len(), 2);
+}
+
+// ---------- stats ----------
+
+#[tokio::test]
+async fn hits_and_misses_are_reported() {
+    let (tools, upstream, clock) = setup();
+    usd(&tools).await;
+    usd(&tools).await;
+    rates(&tools, json!({ "base": "EUR" })).await;
+    clock.advance(TTL);
+    usd(&tools).await;
+    wait_for_runs(&upstream, 3).await;
+
+    let report = tools.introspect();
+    let cache = report.tools[0].cache.unwrap();
+    assert_eq!(
+        cache,
+        CacheReport {
+            ttl_ms: 60_000,
+            stale_while_revalidate_ms: 600_000,
+            entries: 2,
+            hits: 1,
+            stale_hits: 1,
+            misses: 2,
+        }
+    );
+    assert_eq!(cache, tools.result_cache("rates").unwrap().report());
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["tools"][0]["cache"]["misses"], 2);
+}
@@ -0,0 +1,181 @@
+//! Scheduled tools: intervals, overlap, backoff and cancelling, on a
+//! manual clock.
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU32, Ordering},
+    },
+    time::Duration,
+};
+
+use serde_json::json;
+use tokio::sync::Semaphore;
+use tools_rs::{ScheduleConfig, ToolCollection, ToolError, circuit::ManualClock};
+
+const EVERY: Duration = Duration::from_secs(10);
+
+/// Lets the schedule and its runs catch up with the clock.
+async fn settle() {
+    for _ in 0..20 {
+        tokio::task::yield_now().await;
+    }
+}
+
+async fn advance(clock: &ManualClock, by: Duration) {
+    clock.advance(by);
+    settle().await;
+}
+
+#[tokio::test]
+async fn runs_every_interval_until_cancelled() {
+    let calls = Arc::new(AtomicU32::new(0));
+    let mut tools: ToolCollection = ToolCollection::new();
+    let counted = calls.clone();
+    tools
+        .register(
+            "refresh",
+            "Refreshes the cache.",
+            move |_: ()| {
+                let calls = counted.clone();
+                async move { calls.fetch_add(1, Ordering::SeqCst) }
+            },
+            (),
+        )
+        .unwrap();
+    let clock = Arc::new(ManualClock::new());
+    let handle = tools
+        .schedule_with_clock(
+            "refresh",
+            ScheduleConfig::new(EVERY, json!(null)),
+            clock.clone(),
+        )
+        .unwrap();
+    settle().await;
+    assert_eq!(
+        calls.load(Ordering::SeqCst),
+        0,
+        "first run is one interval in"
+    );
+
+    advance(&clock, EVERY).await;
+    advance(&clock, EVERY).await;
+    advance(&clock, EVERY).await;
+    let report = handle.report();
+    assert_eq!((report.runs, report.succeeded), (3, 3));
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+    handle.cancel();
+    advance(&clock, EVERY * 5).await;
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+    assert!(handle.report().cancelled);
+
+    let err = tools.schedule("missing", EVERY, json!(null)).unwrap_err();
+    assert!(matches!(err, ToolError::FunctionNotFound { .. }));
+}
+
+#[tokio::test]
+async fn skips_runs_while_the_last_is_still_going() {
+    let gate = Arc::new(Semaphore::new(0));
+    let mut tools: ToolCollection = ToolCollection::new();
+    let held = gate.clone();
+    tools
+        .register(
+            "rotate",
+            "Rotates the token.",
+            move |_: ()| {
+                let gate = held.clone();
+                async move {
+                    gate.acquire().await.unwrap().forget();
+                    "rotated"
+                }
+            },
+            (),
+        )
+        .unwrap();
+    let clock = Arc::new(ManualClock::new());
+    let handle = tools
+        .schedule_with_clock(
+            "rotate",
+            ScheduleConfig::new(EVERY, json!(null)),
+            clock.clone(),
+        )
+        .unwrap();
+
+    advance(&clock, EVERY).await;
+    assert!(handle.report().running);
+    advance(&clock, EVERY).await;
+    advance(&clock, EVERY).await;
+    let report = handle.report();
+    assert_eq!((report.runs, report.skipped), (1, 2));
+
+    gate.add_permits(1);
+    settle().await;
+    assert!(!handle.report().running);
+    gate.add_permits(1);
+    advance(&clock, EVERY).await;
+    let report = handle.report();
+    assert_eq!((report.runs, report.succeeded, report.skipped), (2, 2, 2));
+}
+
+#[tokio::test]
+async fn failures_back_off_and_a_success_resets() {
+    let failing = Arc::new(AtomicBool::new(true));
+    let mut tools: ToolCollection = ToolCollection::new();
+    let flag = failing.clone();
+    tools
+        .register(
+            "sync",
+            "Syncs upstream.",
+            move |_: ()| {
+                let failing = flag.clone();
+                async move {
+                    if failing.load(Ordering::SeqCst) {
+                        Err("upstream down".to_string())
+                    } else {
+                        Ok("synced")
+                    }
+                }
+            },
+            (),
+        )
+        .unwrap();
+    let clock = Arc::new(ManualClock::new());
+    let config = ScheduleConfig {
+        max_backoff: EVERY * 4,
+        ..ScheduleConfig::new(EVERY, json!(null))
+    };
+    let handle = tools
+        .schedule_with_clock("sync", config, clock.clone())
+        .unwrap();
+    let runs = || handle.report().runs;
+
+    // t=10: fails. The next run waits 2 intervals from its start.
+    advance(&clock, EVERY).await;
+    assert_eq!(handle.report().consecutive_failures, 1);
+    advance(&clock, EVERY).await;
+    assert_eq!(runs(), 1, "t=20 is inside the backoff");
+    advance(&clock, EVERY).await;
+    assert_eq!(runs(), 2, "t=30");
+
+    // Two failures: 4 intervals, which is also the cap.
+    advance(&clock, EVERY * 3).await;
+    assert_eq!(runs(), 2, "t=60");
+    advance(&clock, EVERY).await;
+    assert_eq!(runs(), 3, "t=70");
+    assert_eq!(handle.report().consecutive_failures, 3);
+    advance(&clock, EVERY * 3).await;
+    assert_eq!(runs(), 3, "t=100: capped at 4 intervals, not 8");
+    failing.store(false, Ordering::SeqCst);
+    advance(&clock, EVERY).await;
+    assert_eq!(runs(), 4, "t=110");
+
+    // A success resets the wait to one interval.
+    let report = handle.report();
+    assert_eq!(
+        (report.failed, report.succeeded, report.consecutive_failures),
+        (3, 1, 0)
+    );
+    advance(&clock, EVERY).await;
+    assert_eq!(runs(), 5, "t=120");
+}
@@ -0,0 +1,179 @@
+//! Arguments checked against the declared schema before decoding, when
+//! validation is on.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tools_rs::{ErrorClass, ToolCollection, ToolError, ToolSchema, ToolSpec};
+
+use crate::common::call;
+
+#[derive(Serialize, Deserialize, ToolSchema)]
+struct Stop {
+    name: String,
+    minutes: u32,
+}
+
+#[derive(Serialize, Deserialize, ToolSchema)]
+#[serde(rename_all = "lowercase")]
+enum Mode {
+    Walk,
+    Drive,
+}
+
+#[derive(Serialize, Deserialize, ToolSchema)]
+struct Route {
+    lat: f64,
+    lon: f64,
+    mode: Mode,
+    stops: Vec<Stop>,
+}
+
+fn tools() -> ToolCollection {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register(
+            "route",
+            "Plans a route.",
+            |r: Route| async move { r.stops.len() },
+            (),
+        )
+        .unwrap();
+    tools
+        .register_spec(
+            ToolSpec::new("echo", "Echoes its text.")
+                .params_schema(json!({
+                    "type": "object",
+                    "properties": { "text": { "type": "string" } },
+                    "required": ["text"]
+                }))
+                .handler(|args| Box::pin(async move { Ok(args["text"].clone()) })),
+        )
+        .unwrap();
+    tools
+}
+
+fn errors(err: &ToolError) -> &[String] {
+    match err {
+        ToolError::SchemaValidation { errors, .. } => errors,
+        other => panic!("unexpected error: {other}"),
+    }
+}
+
+#[tokio::test]
+async fn off_by_default() {
+    let tools = tools();
+    assert!(!tools.validation());
+    let err = tools
+        .call(call("route", json!({ "lat": "48.8" })))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ToolError::Deserialize(_)), "{err}");
+}
+
+#[tokio::test]
+async fn every_problem_is_listed_by_pointer() {
+    let mut tools = tools();
+    tools.set_validation(true);
+    let args = json!({
+        "lat": "48.8",
+        "mode": "fly",
+        "stops": [{ "name": "Louvre", "minutes": 30 }, { "minutes": 5 }]
+    });
+    let err = tools.call(call("route", args.clone())).await.unwrap_err();
+    assert_eq!(
+        errors(&err),
+        [
+            "`/lon`: required, expected number",
+            "`/stops/1/name`: required, expected string",
+            "`/lat`: expected number, found string",
+            "`/mode`: expected one of \"walk\", \"drive\", found string",
+        ]
+    );
+    assert_eq!(err.classification(), ErrorClass::InvalidInput);
+    assert!(
+        err.to_string()
+            .starts_with("arguments for tool `route` don't match its schema: `/lon`: required")
+    );
+
+    // `dry_run` reports the same.
+    let dry = tools.dry_run(&call("route", args)).unwrap_err();
+    assert_eq!(errors(&dry), errors(&err));
+
+    let ok = json!({ "lat": 48.8, "lon": 2.3, "mode": "walk", "stops": [] });
+    assert_eq!(tools.call(call("route", ok)).await.unwrap().result, 0);
+}
+
+#[tokio::test]
+async fn handler_tools_are_checked_against_their_schema() {
+    let mut tools = tools();
+    tools.set_validation(true);
+    let err = tools.call(call("echo", json!({}))).await.unwrap_err();
+    assert_eq!(errors(&err), ["`/text`: required, expected string"]);
+    let err = tools
+        .call(call("echo", json!({ "text": 7 })))
+        .await
+        .unwrap_err();
+    assert_eq!(errors(&err), ["`/text`: expected string, found integer"]);
+
+    let out = tools
+        .call(call("echo", json!({ "text": "hi" })))
+        .await
+        .unwrap();
+    assert_eq!(out.result, "hi");
+}
+
+#[test]
+fn builder_turns_it_on() {
+    let tools: ToolCollection = ToolCollection::builder()
+        .validation(true)
+        .collect()
+        .unwrap();
+    assert!(tools.validation());
+}
+
+#[derive(Serialize, Deserialize, ToolSchema)]
+struct Wide {
+    big: i128,
+    long: i64,
+}
+
+#[tokio::test]
+async fn integers_are_checked_against_their_own_range() {
+    let mut tools = tools();
+    tools
+        .register(
+            "wide",
+            "Adds wide integers.",
+            |w: Wide| async move { (w.big + i128::from(w.long)).to_string() },
+            (),
+        )
+        .unwrap();
+    tools.set_validation(true);
+
+    // Past `i64::MAX`, but an `i128`.
+    let out = tools
+        .call(call(
+            "wide",
+            json!({ "big": 10_000_000_000_000_000_000u64, "long": 1 }),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(out.result, "10000000000000000001");
+
+    let err = tools
+        .call(call(
+            "wide",
+            json!({ "big": 1, "long": i64::MAX as u64 + 1 }),
+        ))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        errors(&err),
+        [format!(
+            "`/long`: expected integer from {} to {}, found {}",
+            i64::MIN,
+            i64::MAX,
+            i64::MAX as u64 + 1
+        )]
+    );
+}
@@ -0,0 +1,113 @@
+//! `ToolSchema` for ranges, bounds and non-zero integers, checked by
+//! round-tripping values through registered tools.
+
+use std::{
+    num::{NonZeroI32, NonZeroU8, NonZeroU32},
+    ops::{Bound, Range, RangeFrom, RangeInclusive, RangeTo},
+};
+
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::{Value, json};
+use tools_rs::{FunctionCall, ToolCollection, ToolSchema};
+
+/// Register an identity tool for `T` and return what comes back for
+/// `value`.
+async fn echo<T>(value: Value) -> Result<Value, tools_rs::ToolError>
+where
+    T: Serialize + DeserializeOwned + ToolSchema + Send + 'static,
+{
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register("echo", "Echoes its input.", |x: T| async move { x }, ())
+        .unwrap();
+    Ok(tools
+        .call(FunctionCall::new("echo".into(), value))
+        .await?
+        .result)
+}
+
+// ---------- ranges ----------
+
+#[test]
+fn range_schemas_describe_inclusivity() {
+    let half_open = Range::<u32>::schema();
+    assert_eq!(half_open["required"], json!(["start", "end"]));
+    assert_eq!(half_open["properties"]["start"], u32::schema());
+    assert!(
+        half_open["description"]
+            .as_str()
+            .unwrap()
+            .contains("excludes `end`")
+    );
+
+    let closed = RangeInclusive::<f64>::schema();
+    assert_eq!(closed["properties"]["end"], json!({ "type": "number" }));
+    assert!(
+        closed["description"]
+            .as_str()
+            .unwrap()
+            .contains("includes both")
+    );
+
+    assert_eq!(RangeFrom::<u8>::schema()["required"], json!(["start"]));
+    assert_eq!(RangeTo::<u8>::schema()["required"], json!(["end"]));
+}
+
+#[tokio::test]
+async fn ranges_round_trip() {
+    let v = json!({ "start": 2, "end": 5 });
+    assert_eq!(echo::<Range<u32>>(v.clone()).await.unwrap(), v);
+
+    let v = json!({ "start": 0.5, "end": 1.5 });
+    assert_eq!(echo::<RangeInclusive<f64>>(v.clone()).await.unwrap(), v);
+
+    let v = json!({ "start": 3 });
+    assert_eq!(echo::<RangeFrom<u8>>(v.clone()).await.unwrap(), v);
+
+    let v = json!({ "end": 9 });
+    assert_eq!(echo::<RangeTo<u8>>(v.clone()).await.unwrap(), v);
+}
+
+// ---------- bounds ----------
+
+#[tokio::test]
+async fn bounds_round_trip() {
+    let branches = Bound::<i32>::schema()["oneOf"].as_array().unwrap().len();
+    assert_eq!(branches, 3);
+
+    for v in [
+        json!("Unbounded"),
+        json!({ "Included": 4 }),
+        json!({ "Excluded": -4 }),
+    ] {
+        assert_eq!(echo::<Bound<i32>>(v.clone()).await.unwrap(), v);
+    }
+    // A pair of bounds is the usual way to express an arbitrary range.
+    let v = json!([{ "Included": 1 }, "Unbounded"]);
+    assert_eq!(
+        echo::<(Bound<i32>, Bound<i32>)>(v.clone()).await.unwrap(),
+        v
+    );
+}
+
+// ---------- non-zero integers ----------
+
+#[test]
+fn nonzero_schemas() {
+    assert_eq!(
+        NonZeroU32::schema(),
+        json!({ "type": "integer", "minimum": 1 })
+    );
+    assert_eq!(
+        NonZeroI32::schema(),
+        json!({ "type": "integer", "not": { "const": 0 } })
+    );
+}
+
+#[tokio::test]
+async fn nonzero_round_trip_and_reject_zero() {
+    assert_eq!(echo::<NonZeroU8>(json!(7)).await.unwrap(), json!(7));
+    assert_eq!(echo::<NonZeroI32>(json!(-3)).await.unwrap(), json!(-3));
+    assert!(echo::<NonZeroU32>(json!(0)).await.is_err());
+    assert!(echo::<NonZeroI32>(json!(0)).await.is_err());
+}
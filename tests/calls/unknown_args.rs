@@ -0,0 +1,122 @@
+//! Arguments the schema doesn't declare: ignored by default, or reported
+//! with the accepted names and a nearest-match suggestion.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tools_rs::{
+    ErrorClass, FunctionCall, ToolCollection, ToolError, ToolSchema, ToolSpec, UnknownArgs,
+};
+
+#[derive(Serialize, Deserialize, ToolSchema)]
+struct Location {
+    lat: f64,
+    #[serde(default)]
+    lon: f64,
+}
+
+fn tools(mode: UnknownArgs) -> ToolCollection {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register(
+            "weather",
+            "Forecast for a location.",
+            |l: Location| async move { format!("{},{}", l.lat, l.lon) },
+            (),
+        )
+        .unwrap();
+    tools.set_unknown_args(mode);
+    tools
+}
+
+fn misspelled() -> FunctionCall {
+    FunctionCall::new("weather".into(), json!({ "lattitude": 48.8, "lon": 2.3 }))
+}
+
+#[tokio::test]
+async fn ignored_by_default() {
+    let tools = tools(UnknownArgs::default());
+    // Serde drops the key; `lat` is then missing.
+    let err = tools.call(misspelled()).await.unwrap_err();
+    assert!(matches!(err, ToolError::Deserialize(_)), "{err}");
+}
+
+#[tokio::test]
+async fn rejected_with_accepted_names_and_a_suggestion() {
+    let tools = tools(UnknownArgs::Reject);
+    let err = tools.call(misspelled()).await.unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "tool `weather` got unknown arguments: `lattitude` (did you mean `lat`?); accepted: `lat`, `lon`"
+    );
+    let ToolError::UnknownArguments {
+        tool,
+        unknown,
+        accepted,
+        ..
+    } = &err
+    else {
+        panic!("unexpected error: {err}");
+    };
+    assert_eq!(*tool, "weather");
+    assert_eq!(unknown, &["lattitude"]);
+    assert_eq!(accepted, &["lat", "lon"]);
+    assert_eq!(err.classification(), ErrorClass::InvalidInput);
+
+    // Checked before decoding, by dry runs too.
+    assert!(matches!(
+        tools.dry_run(&misspelled()).unwrap_err(),
+        ToolError::UnknownArguments { .. }
+    ));
+    // Declared keys pass.
+    let ok = FunctionCall::new("weather".into(), json!({ "lat": 48.8 }));
+    assert_eq!(tools.call(ok).await.unwrap().result, "48.8,0");
+}
+
+#[tokio::test]
+async fn warnings_ride_on_the_response() {
+    let tools = tools(UnknownArgs::Warn);
+    let call = FunctionCall::new(
+        "weather".into(),
+        json!({ "lat": 48.8, "long": 2.3, "verbose": true }),
+    );
+    let response = tools.call(call).await.unwrap();
+    assert_eq!(response.result, "48.8,0");
+    assert_eq!(
+        response.warnings,
+        [
+            "unknown argument `long` (did you mean `lon`?) ignored",
+            "unknown argument `verbose` ignored",
+        ]
+    );
+    let json = serde_json::to_value(&response).unwrap();
+    assert_eq!(json["warnings"].as_array().unwrap().len(), 2);
+
+    let clean = tools
+        .call(FunctionCall::new("weather".into(), json!({ "lat": 1 })))
+        .await
+        .unwrap();
+    assert!(
+        serde_json::to_value(&clean)
+            .unwrap()
+            .get("warnings")
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn open_schemas_are_not_checked() {
+    let mut tools = tools(UnknownArgs::Reject);
+    tools
+        .register_spec(
+            ToolSpec::new("tag", "Free-form tags.")
+                .params_schema(json!({
+                    "type": "object",
+                    "properties": { "id": { "type": "integer" } },
+                    "additionalProperties": { "type": "string" }
+                }))
+                .handler(|args| Box::pin(async move { Ok(args) })),
+        )
+        .unwrap();
+    let call = FunctionCall::new("tag".into(), json!({ "id": 1, "color": "red" }));
+    assert!(tools.call(call).await.is_ok());
+}
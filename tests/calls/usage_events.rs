@@ -0,0 +1,193 @@
+//! Usage events: one per call, complete across outcomes, with sizes in
+//! place of payloads.
+
+use std::sync::{
+    Arc, Mutex,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use serde_json::json;
+use tools_rs::{
+    CachePolicy, ErrorClass, ToolCollection, ToolError, ToolSpec, UsageEvent, UsageOutcome,
+    UsageSink, conversation,
+};
+
+use crate::common::call;
+
+#[derive(Default)]
+struct Events(Mutex<Vec<UsageEvent>>);
+
+impl UsageSink for Events {
+    fn record(&self, event: UsageEvent) {
+        self.0.lock().unwrap().push(event);
+    }
+}
+
+impl Events {
+    fn take(&self) -> Vec<UsageEvent> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+fn setup() -> (ToolCollection, Arc<Events>) {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register_spec(
+            ToolSpec::new("echo", "Echoes its text.")
+                .handler(|args| Box::pin(async move { Ok(json!({ "echo": args["text"] })) })),
+        )
+        .unwrap();
+    let runs = Arc::new(AtomicUsize::new(0));
+    tools
+        .register_spec(
+            ToolSpec::new("flaky", "Fails twice, then answers.").handler(move |_| {
+                let run = runs.fetch_add(1, Ordering::SeqCst);
+                Box::pin(async move {
+                    if run < 2 {
+                        Err(ToolError::classified(ErrorClass::Transient, "busy"))
+                    } else {
+                        Ok(json!("done"))
+                    }
+                })
+            }),
+        )
+        .unwrap();
+    tools
+        .register_spec(
+            ToolSpec::new("refuse", "Refuses.")
+                .handler(|_| Box::pin(async { Err(ToolError::Domain(json!({ "code": "NO" }))) })),
+        )
+        .unwrap();
+    tools
+        .set_cache_policy("echo", CachePolicy::default())
+        .unwrap();
+
+    let events = Arc::new(Events::default());
+    tools.set_usage_sink(events.clone());
+    (tools, events)
+}
+
+// ---------- outcomes ----------
+
+#[tokio::test]
+async fn success_and_cache_hit() {
+    let (tools, events) = setup();
+    let args = json!({ "text": "secret words" });
+    conversation::with_conversation("conv-1", async {
+        tools.call(call("echo", args.clone())).await.unwrap();
+        tools.call(call("echo", args.clone())).await.unwrap();
+    })
+    .await;
+
+    let events = events.take();
+    assert_eq!(events.len(), 2);
+    for (event, hit) in events.iter().zip([false, true]) {
+        assert_eq!(event.conversation_id.as_deref(), Some("conv-1"));
+        assert_eq!(event.tool, "echo");
+        assert_eq!(event.outcome, UsageOutcome::Ok);
+        assert_eq!(event.error_class, None);
+        assert_eq!(event.args_bytes, args.to_string().len() as u64);
+        assert_eq!(
+            event.result_bytes,
+            json!({ "echo": "secret words" }).to_string().len() as u64
+        );
+        assert_eq!(event.cache_hit, hit);
+        assert_eq!(event.retries, 0);
+        assert!(event.timestamp > 0);
+    }
+    // Only sizes leave the process.
+    let line = serde_json::to_string(&events).unwrap();
+    assert!(!line.contains("secret"));
+}
+
+#[tokio::test]
+async fn domain_errors_and_unknown_tools() {
+    let (tools, events) = setup();
+    tools.call(call("refuse", json!({}))).await.unwrap();
+    tools.call(call("missing", json!({}))).await.unwrap_err();
+
+    let events = events.take();
+    assert_eq!(events.len(), 1, "unknown tools produce no event");
+    assert_eq!(events[0].conversation_id, None);
+    assert_eq!(events[0].tool, "refuse");
+    assert_eq!(events[0].outcome, UsageOutcome::DomainError);
+    assert_eq!(events[0].error_class, None);
+    assert_eq!(
+        events[0].result_bytes,
+        json!({ "error": { "code": "NO" } }).to_string().len() as u64
+    );
+}
+
+#[tokio::test]
+async fn failed_calls_report_their_class() {
+    let (tools, events) = setup();
+    tools.call(call("flaky", json!({}))).await.unwrap_err();
+
+    let event = &events.take()[0];
+    assert_eq!(event.outcome, UsageOutcome::Error);
+    assert_eq!(event.error_class, Some(ErrorClass::Transient));
+    assert_eq!(event.result_bytes, 0);
+    assert!(!event.cache_hit);
+}
+
+// ---------- retries ----------
+
+#[tokio::test]
+async fn retries_count_preceding_failures() {
+    let (tools, events) = setup();
+    let attempt = || call("flaky", json!({ "id": 7 }));
+    conversation::with_conversation("conv-2", async {
+        tools.call(attempt()).await.unwrap_err();
+        tools.call(attempt()).await.unwrap_err();
+        tools.call(attempt()).await.unwrap();
+        tools.call(attempt()).await.unwrap();
+    })
+    .await;
+
+    let events = events.take();
+    let retries: Vec<u32> = events.iter().map(|e| e.retries).collect();
+    assert_eq!(retries, [0, 1, 2, 0]);
+    assert_eq!(events[2].outcome, UsageOutcome::Ok);
+}
+
+#[tokio::test]
+async fn retries_are_per_conversation() {
+    let (tools, events) = setup();
+    let attempt = || call("flaky", json!({}));
+    conversation::with_conversation("a", tools.call(attempt()))
+        .await
+        .unwrap_err();
+    conversation::with_conversation("b", tools.call(attempt()))
+        .await
+        .unwrap_err();
+
+    let retries: Vec<u32> = events.take().iter().map(|e| e.retries).collect();
+    assert_eq!(retries, [0, 0]);
+}
+
+// ---------- JSONL sink ----------
+
+#[cfg(feature = "usage-jsonl")]
+#[tokio::test]
+async fn jsonl_sink_appends_one_line_per_call() {
+    use tools_rs::usage::JsonlUsageSink;
+
+    let path = std::env::temp_dir().join(format!("tools-rs-usage-{}.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let (mut tools, _) = setup();
+    tools.set_usage_sink(Arc::new(JsonlUsageSink::create(&path).unwrap()));
+    tools
+        .call(call("echo", json!({ "text": "hi" })))
+        .await
+        .unwrap();
+    tools.call(call("flaky", json!({}))).await.unwrap_err();
+
+    let text = std::fs::read_to_string(&path).unwrap();
+    let events: Vec<UsageEvent> = text
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[1].outcome, UsageOutcome::Error);
+    let _ = std::fs::remove_file(&path);
+}
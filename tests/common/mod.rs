@@ -0,0 +1,44 @@
+//! Fixture shared by the themed test binaries. Each binary compiles its
+//! own copy and uses part of it.
+
+#![allow(dead_code)]
+
+use serde_json::{Value, json};
+use tools_rs::{FunctionCall, ToolCollection, ToolError, ToolSpec};
+
+/// A call to `name` with `arguments`.
+pub fn call(name: &str, arguments: Value) -> FunctionCall {
+    FunctionCall::new(name.to_string(), arguments)
+}
+
+/// A handler tool named `name` that takes anything and returns `null`.
+pub fn stub(name: &str) -> ToolSpec {
+    ToolSpec::new(name.to_string(), "A tool.").handler(|_| Box::pin(async { Ok(json!(null)) }))
+}
+
+/// A collection with the tools most tests need:
+///
+/// - `echo` returns its arguments,
+/// - `double` takes a `u32` and doubles it,
+/// - `fail` always fails with a runtime error.
+///
+/// Tests register their own tools on top.
+pub fn tools() -> ToolCollection {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register_spec(
+            ToolSpec::new("echo", "Echoes its arguments.")
+                .handler(|args| Box::pin(async move { Ok(args) })),
+        )
+        .unwrap();
+    tools
+        .register("double", "Doubles.", |n: u32| async move { n * 2 }, ())
+        .unwrap();
+    tools
+        .register_spec(
+            ToolSpec::new("fail", "Always fails.")
+                .handler(|_| Box::pin(async { Err(ToolError::Runtime("boom".into())) })),
+        )
+        .unwrap();
+    tools
+}
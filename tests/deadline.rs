@@ -0,0 +1,175 @@
+//! Deadlines propagating from a caller through a tool into the tools it
+//! calls via the global `call_tool`.
+
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use serde_json::json;
+use tools_rs::{FunctionCall, ToolCollection, ToolError, call_tool, deadline, tool};
+
+/// Tags of `inner` calls that actually ran.
+static INNER_RUNS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+fn inner_ran(tag: &str) -> bool {
+    INNER_RUNS.lock().unwrap().iter().any(|t| t == tag)
+}
+
+/// Records that it ran.
+#[tool]
+async fn inner(tag: String) -> bool {
+    INNER_RUNS.lock().unwrap().push(tag);
+    true
+}
+
+/// Does `work_ms` of blocking work, then calls `inner` and reports how
+/// that went.
+#[tool]
+async fn outer(tag: String, work_ms: u64) -> String {
+    std::thread::sleep(Duration::from_millis(work_ms));
+    match call_tool("inner", json!({ "tag": tag })).await {
+        Ok(_) => "inner ran".to_string(),
+        Err(ToolError::DeadlineExceeded { tool }) => format!("{tool} skipped"),
+        Err(e) => e.to_string(),
+    }
+}
+
+/// Waits far longer than any test deadline.
+#[tool]
+async fn stall() -> bool {
+    tokio::time::sleep(Duration::from_secs(30)).await;
+    true
+}
+
+fn call(name: &str, arguments: serde_json::Value) -> FunctionCall {
+    FunctionCall::new(name.to_string(), arguments)
+}
+
+// ---------- nested calls ----------
+
+#[tokio::test]
+async fn inner_call_never_starts_after_the_deadline() {
+    let tools: ToolCollection = ToolCollection::collect_tools().unwrap();
+    let out = tools
+        .call_with_timeout(
+            call("outer", json!({ "tag": "late", "work_ms": 50 })),
+            Duration::from_millis(10),
+        )
+        .await
+        .unwrap();
+    assert_eq!(out.result, json!("inner skipped"));
+    assert!(!inner_ran("late"));
+}
+
+#[tokio::test]
+async fn inner_call_runs_while_time_remains() {
+    let tools: ToolCollection = ToolCollection::collect_tools().unwrap();
+    let out = tools
+        .call_with_timeout(
+            call("outer", json!({ "tag": "early", "work_ms": 0 })),
+            Duration::from_secs(10),
+        )
+        .await
+        .unwrap();
+    assert_eq!(out.result, json!("inner ran"));
+    assert!(inner_ran("early"));
+}
+
+// ---------- enforcement ----------
+
+#[tokio::test]
+async fn running_tool_is_cut_off() {
+    let tools: ToolCollection = ToolCollection::collect_tools().unwrap();
+    let start = Instant::now();
+    let err = tools
+        .call_with_timeout(call("stall", json!({})), Duration::from_millis(20))
+        .await
+        .unwrap_err();
+    let ToolError::Timeout { name, elapsed } = &err else {
+        panic!("unexpected error: {err}");
+    };
+    assert_eq!(name, "stall");
+    assert!(*elapsed >= Duration::from_millis(20), "{elapsed:?}");
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert!(err.is_retryable());
+}
+
+#[tokio::test]
+async fn expired_deadline_fails_before_running() {
+    let tools: ToolCollection = ToolCollection::collect_tools().unwrap();
+    let past = Instant::now() - Duration::from_millis(1);
+    let err = deadline::with_deadline(past, tools.call(call("inner", json!({ "tag": "expired" }))))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ToolError::DeadlineExceeded { ref tool } if tool == "inner"));
+    assert!(!inner_ran("expired"));
+}
+
+// ---------- scoping ----------
+
+#[tokio::test]
+async fn nested_scopes_only_shorten_the_deadline() {
+    assert!(deadline::current().is_none());
+
+    let near = Instant::now() + Duration::from_secs(1);
+    let far = near + Duration::from_secs(60);
+    deadline::with_deadline(near, async {
+        deadline::with_deadline(far, async {
+            assert_eq!(deadline::current(), Some(near));
+        })
+        .await;
+        let sooner = near - Duration::from_millis(500);
+        deadline::with_deadline(sooner, async {
+            assert_eq!(deadline::current(), Some(sooner));
+        })
+        .await;
+        assert!(deadline::remaining().unwrap() <= Duration::from_secs(1));
+    })
+    .await;
+}
+
+// ---------- default timeout ----------
+
+#[tokio::test]
+async fn default_timeout_cuts_off_slow_calls() {
+    let mut tools: ToolCollection = ToolCollection::collect_tools().unwrap();
+    tools.set_default_timeout(Duration::from_millis(20));
+    assert_eq!(tools.default_timeout(), Some(Duration::from_millis(20)));
+
+    let start = Instant::now();
+    let err = tools.call(call("stall", json!({}))).await.unwrap_err();
+    assert!(matches!(err, ToolError::Timeout { ref name, .. } if name == "stall"));
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    let out = tools
+        .call(call("inner", json!({ "tag": "fast" })))
+        .await
+        .unwrap();
+    assert_eq!(out.result, json!(true));
+}
+
+#[tokio::test]
+async fn default_timeout_only_shortens() {
+    let tools: ToolCollection = ToolCollection::builder()
+        .default_timeout(Duration::from_millis(20))
+        .collect()
+        .unwrap();
+    // A longer per-call timeout doesn't extend the default.
+    let err = tools
+        .call_with_timeout(call("stall", json!({})), Duration::from_secs(10))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ToolError::Timeout { ref name, .. } if name == "stall"));
+
+    let mut tools = tools;
+    tools.clear_default_timeout();
+    let out = tools
+        .call_with_timeout(
+            call("outer", json!({ "tag": "cleared", "work_ms": 30 })),
+            Duration::from_secs(10),
+        )
+        .await
+        .unwrap();
+    assert_eq!(out.result, json!("inner ran"));
+}
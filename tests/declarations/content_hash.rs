@@ -0,0 +1,124 @@
+//! Declaration content hashes and the registry hash: stable across runs,
+//! and changed by exactly the parts the model sees.
+
+use serde_json::{Value, json};
+use tools_rs::{
+    ToolCollection, ToolSpec,
+    hash::{CONTENT_HASH_KEY, canonical_hash},
+};
+
+fn spec(name: &str, description: &str, parameters: Value) -> ToolSpec {
+    ToolSpec::new(name.to_string(), description.to_string())
+        .params_schema(parameters)
+        .handler(|_| Box::pin(async { Ok(Value::Null) }))
+}
+
+fn weather_params() -> Value {
+    json!({
+        "type": "object",
+        "properties": { "city": { "type": "string" } },
+        "required": ["city"]
+    })
+}
+
+fn collection(specs: Vec<ToolSpec>) -> ToolCollection {
+    let mut tools: ToolCollection = ToolCollection::new();
+    for spec in specs {
+        tools.register_spec(spec).unwrap();
+    }
+    tools
+}
+
+fn content_hash(spec: ToolSpec) -> u64 {
+    let tools = collection(vec![spec]);
+    tools.declarations().next().unwrap().content_hash()
+}
+
+// ---------- content hashes ----------
+
+#[test]
+fn content_hash_is_pinned() {
+    // Stored by agent loops across processes and releases.
+    let hash = content_hash(spec("weather", "Gets the weather.", weather_params()));
+    assert_eq!(hash, 0x8e04_80fe_7888_8440);
+    assert_eq!(
+        hash,
+        canonical_hash(&json!({
+            "name": "weather",
+            "description": "Gets the weather.",
+            "parameters": weather_params(),
+        }))
+    );
+}
+
+#[test]
+fn content_hash_covers_name_description_and_parameters() {
+    let base = content_hash(spec("weather", "Gets the weather.", weather_params()));
+    let renamed = content_hash(spec("forecast", "Gets the weather.", weather_params()));
+    let reworded = content_hash(spec("weather", "Gets the forecast.", weather_params()));
+    let mut params = weather_params();
+    params["properties"]["units"] = json!({ "type": "string" });
+    let widened = content_hash(spec("weather", "Gets the weather.", params));
+    for changed in [renamed, reworded, widened] {
+        assert_ne!(changed, base);
+    }
+}
+
+#[test]
+fn content_hash_ignores_key_order_and_tags() {
+    let base = content_hash(spec("weather", "Gets the weather.", weather_params()));
+    let reordered: Value = serde_json::from_str(
+        r#"{ "required": ["city"], "properties": { "city": { "type": "string" } }, "type": "object" }"#,
+    )
+    .unwrap();
+    let tagged = spec("weather", "Gets the weather.", reordered).tag("outdoor");
+    assert_eq!(content_hash(tagged), base);
+}
+
+// ---------- registry hash ----------
+
+#[test]
+fn registry_hash_ignores_registration_order() {
+    let a = || spec("a", "First.", json!({ "type": "object" }));
+    let b = || spec("b", "Second.", json!({ "type": "object" }));
+    assert_eq!(
+        collection(vec![a(), b()]).registry_hash(),
+        collection(vec![b(), a()]).registry_hash()
+    );
+}
+
+#[test]
+fn registry_hash_tracks_the_declared_set() {
+    let mut tools = collection(vec![spec("weather", "Gets the weather.", weather_params())]);
+    let before = tools.registry_hash();
+
+    tools
+        .register_spec(spec("time", "Gets the time.", json!({ "type": "object" })))
+        .unwrap();
+    let added = tools.registry_hash();
+    assert_ne!(added, before);
+
+    tools.set_hidden("time", true).unwrap();
+    assert_eq!(tools.registry_hash(), before);
+
+    tools.unregister("weather").unwrap();
+    tools
+        .register_spec(spec("weather", "Gets the forecast.", weather_params()))
+        .unwrap();
+    assert_ne!(tools.registry_hash(), before);
+}
+
+// ---------- embedding ----------
+
+#[test]
+fn json_embeds_hashes_on_request() {
+    let mut tools = collection(vec![spec("weather", "Gets the weather.", weather_params())]);
+    assert!(tools.json().unwrap()[0].get(CONTENT_HASH_KEY).is_none());
+
+    tools.set_content_hashes(true);
+    let expected = format!(
+        "{:016x}",
+        tools.declarations().next().unwrap().content_hash()
+    );
+    assert_eq!(tools.json().unwrap()[0][CONTENT_HASH_KEY], json!(expected));
+}
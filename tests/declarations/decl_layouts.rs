@@ -0,0 +1,190 @@
+//! Flat and OpenAI-nested declaration layouts: strict parsing, and
+//! round trips in both directions over generated declarations (a small
+//! seeded generator stands in for a property-testing crate).
+
+use serde_json::{Value, json};
+use tools_rs::{Fingerprint, FunctionDecl, ToolCollection, ToolError, ToolSpec};
+
+/// xorshift64*, seeded per test so failures reproduce.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+}
+
+fn schema(rng: &mut Rng) -> Value {
+    let properties: serde_json::Map<String, Value> = (0..rng.below(4))
+        .map(|i| {
+            let ty = ["string", "integer", "boolean"][rng.below(3) as usize];
+            (format!("p{i}"), json!({ "type": ty }))
+        })
+        .collect();
+    json!({ "type": "object", "properties": properties })
+}
+
+/// A flat declaration, with `returns` and `tags` each present or absent.
+fn flat(rng: &mut Rng) -> Value {
+    let mut decl = json!({
+        "name": format!("tool_{}", rng.below(100)),
+        "description": format!("Does thing {}.\n\"quoted\" é", rng.below(100)),
+        "parameters": schema(rng),
+    });
+    if rng.below(2) == 0 {
+        decl["returns"] = schema(rng);
+    }
+    if rng.below(2) == 0 {
+        decl["tags"] = (0..=rng.below(2)).map(|i| json!(format!("t{i}"))).collect();
+    }
+    decl
+}
+
+fn nested(flat: &Value) -> Value {
+    json!({ "type": "function", "function": flat })
+}
+
+// ---------- round trips ----------
+
+#[test]
+fn flat_round_trips() {
+    let mut rng = Rng(0x5eed_0001);
+    for _ in 0..500 {
+        let value = flat(&mut rng);
+        let decl = FunctionDecl::from_flat(&value).unwrap();
+        assert_eq!(decl.to_flat(), value);
+        let nested = decl.to_openai();
+        assert_eq!(FunctionDecl::from_openai(&nested).unwrap(), decl);
+    }
+}
+
+#[test]
+fn openai_round_trips() {
+    let mut rng = Rng(0x5eed_0002);
+    for _ in 0..500 {
+        let value = nested(&flat(&mut rng));
+        let decl = FunctionDecl::from_openai(&value).unwrap();
+        assert_eq!(decl.to_openai(), value);
+        let flat = decl.to_flat();
+        assert_eq!(FunctionDecl::from_flat(&flat).unwrap(), decl);
+    }
+}
+
+#[test]
+fn returns_survive_both_layouts() {
+    let value = json!({
+        "name": "sum",
+        "description": "Adds.",
+        "parameters": { "type": "object" },
+        "returns": { "type": "integer" }
+    });
+    let decl = FunctionDecl::from_flat(&value).unwrap();
+    assert_eq!(decl.returns, Some(json!({ "type": "integer" })));
+    assert_eq!(
+        decl.to_openai()["function"]["returns"],
+        json!({ "type": "integer" })
+    );
+    let bare = FunctionDecl::from_flat(&json!({
+        "name": "sum", "description": "Adds.", "parameters": { "type": "object" }
+    }))
+    .unwrap()
+    .to_openai();
+    assert!(bare["function"].get("returns").is_none());
+}
+
+#[test]
+fn collection_output_parses_as_flat() {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register_spec(
+            ToolSpec::new("echo", "Echoes.")
+                .params_schema(json!({ "type": "object" }))
+                .returns_schema(json!({ "type": "object" }))
+                .tag("io")
+                .handler(|args| Box::pin(async move { Ok(args) })),
+        )
+        .unwrap();
+    let json = tools.json().unwrap();
+    let parsed = FunctionDecl::from_any_layout(&json[0]).unwrap();
+    assert_eq!(&parsed, tools.declarations().next().unwrap());
+}
+
+// ---------- strict parsing ----------
+
+type Parse = for<'a> fn(&'a Value) -> Result<FunctionDecl<'a>, ToolError>;
+
+fn error(parse: Parse, value: Value) -> String {
+    parse(&value).unwrap_err().to_string()
+}
+
+#[test]
+fn missing_keys_are_named() {
+    let flat: Parse = |v| FunctionDecl::from_flat(v);
+    let openai: Parse = |v| FunctionDecl::from_openai(v);
+    let any: Parse = |v| FunctionDecl::from_any_layout(v);
+    let cases = [
+        (
+            error(flat, json!({ "name": "a", "parameters": {} })),
+            "declaration is missing `description`",
+        ),
+        (
+            error(
+                flat,
+                json!({ "name": 1, "description": "", "parameters": {} }),
+            ),
+            "declaration `name` is not a string",
+        ),
+        (
+            error(openai, json!({ "function": {} })),
+            "declaration is missing `type`",
+        ),
+        (
+            error(
+                openai,
+                json!({ "type": "function", "function": { "name": "a", "description": "" } }),
+            ),
+            "declaration is missing `function.parameters`",
+        ),
+        (
+            error(
+                any,
+                json!({
+                    "type": "function",
+                    "function": { "name": "a", "description": "", "parameters": {}, "tags": [1] }
+                }),
+            ),
+            "declaration `function.tags` is not an array of strings",
+        ),
+        (error(flat, json!([])), "declaration is not an object"),
+    ];
+    for (error, message) in cases {
+        assert_eq!(error, format!("Runtime error: {message}"));
+    }
+}
+
+// ---------- fingerprints ----------
+
+#[test]
+fn fingerprints_load_either_layout() {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register_spec(
+            ToolSpec::new("echo", "Echoes.")
+                .params_schema(json!({ "type": "object", "required": ["x"] }))
+                .handler(|args| Box::pin(async move { Ok(args) })),
+        )
+        .unwrap();
+    let mut doc = serde_json::to_value(tools.fingerprint()).unwrap();
+    let flat_tool = doc["tools"][0].take();
+    doc["tools"][0] = nested(&flat_tool);
+
+    let fingerprint: Fingerprint = serde_json::from_value(doc).unwrap();
+    assert_eq!(fingerprint, tools.fingerprint());
+}
@@ -0,0 +1,98 @@
+//! Reading single declarations, serializing a named subset, and the
+//! order declarations come in.
+
+use serde_json::{Value, json};
+use tools_rs::{ToolCollection, ToolError, ToolSpec};
+
+use crate::common;
+
+fn tools() -> ToolCollection {
+    let mut tools: ToolCollection = ToolCollection::new();
+    for name in ["search", "fetch", "delete", "debug"] {
+        tools.register_spec(common::stub(name)).unwrap();
+    }
+    tools.set_hidden("debug", true).unwrap();
+    tools
+}
+
+fn names(value: &Value) -> Vec<&str> {
+    value
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| d["name"].as_str().unwrap())
+        .collect()
+}
+
+#[test]
+fn single_declarations() {
+    let mut tools = tools();
+    assert_eq!(tools.get_declaration("fetch").unwrap().name, "fetch");
+    assert!(tools.get_declaration("missing").is_none());
+    // Hidden tools aren't declared, unless asked for.
+    assert!(tools.get_declaration("debug").is_none());
+    tools.set_declare_hidden(true);
+    assert!(tools.get_declaration("debug").is_some());
+
+    let listed: Vec<&str> = tools.declarations().map(|d| &*d.name).collect();
+    assert_eq!(listed, ["debug", "delete", "fetch", "search"]);
+}
+
+#[test]
+fn filtered_json_has_only_the_named_tools() {
+    let tools = tools();
+    let subset = tools.json_filtered(&["delete", "search"]).unwrap();
+    assert_eq!(names(&subset), ["delete", "search"]);
+
+    // Each entry is the same as in the full `json()`.
+    let full = tools.json().unwrap();
+    let search = full
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|d| d["name"] == "search");
+    assert_eq!(Some(&subset[1]), search);
+
+    assert_eq!(tools.json_filtered(&[]).unwrap(), json!([]));
+}
+
+#[test]
+fn unknown_and_hidden_names_fail() {
+    let tools = tools();
+    for name in ["serch", "debug"] {
+        let err = tools.json_filtered(&["search", name]).unwrap_err();
+        assert!(
+            matches!(&err, ToolError::FunctionNotFound { name: got } if got == name),
+            "{err}"
+        );
+    }
+}
+
+#[test]
+fn filtered_json_follows_collection_settings() {
+    let mut tools = tools();
+    tools.set_content_hashes(true);
+    let subset = tools.json_filtered(&["fetch"]).unwrap();
+    let full = tools.json().unwrap();
+    assert_eq!(subset[0], full[1]);
+    assert_eq!(full[1]["name"], "fetch");
+    assert!(subset[0]["x-content-hash"].is_string());
+}
+
+#[test]
+fn json_is_sorted_by_name_whatever_the_registration_order() {
+    let mut shuffled: ToolCollection = ToolCollection::new();
+    for name in ["mango", "apple", "zucchini", "banana", "kiwi"] {
+        shuffled
+            .register_spec(
+                ToolSpec::new(name, "A fruit.").handler(|_| Box::pin(async { Ok(json!(null)) })),
+            )
+            .unwrap();
+    }
+    let sorted = ["apple", "banana", "kiwi", "mango", "zucchini"];
+    assert_eq!(names(&shuffled.json().unwrap()), sorted);
+    let described: Vec<&str> = shuffled.descriptions().map(|(name, _)| name).collect();
+    assert_eq!(described, sorted);
+    // Registration order is still what `iter` gives.
+    assert_eq!(shuffled.iter().next().unwrap().0, "mango");
+}
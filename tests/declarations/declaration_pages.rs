@@ -0,0 +1,139 @@
+//! Walking a 500-tool collection with `declarations_page`, with and
+//! without filters and while the collection changes underneath.
+
+use serde_json::json;
+use tools_rs::{DeclFilter, ToolCollection, ToolSpec};
+
+const TOOLS: usize = 500;
+
+/// Tool `i` is `group{i % 5}.tool{i:03}`, tagged `even` when `i` is
+/// even. Registered in a scrambled order so paging can't lean on it.
+fn tools() -> ToolCollection {
+    let mut tools: ToolCollection = ToolCollection::new();
+    for n in 0..TOOLS {
+        let i = n * 7 % TOOLS;
+        let mut spec = ToolSpec::new(format!("group{}.tool{i:03}", i % 5), "A tool.")
+            .handler(|_| Box::pin(async { Ok(json!(null)) }));
+        if i.is_multiple_of(2) {
+            spec = spec.tag("even");
+        }
+        tools.register_spec(spec).unwrap();
+    }
+    tools
+}
+
+fn walk(tools: &ToolCollection, limit: usize, filter: &DeclFilter) -> Vec<Vec<String>> {
+    let mut pages = Vec::new();
+    let mut cursor = None;
+    loop {
+        let page = tools.declarations_page(cursor.as_deref(), limit, filter);
+        pages.push(
+            page.declarations
+                .iter()
+                .map(|d| d.name.to_string())
+                .collect(),
+        );
+        match page.next_cursor {
+            Some(next) => cursor = Some(next),
+            None => return pages,
+        }
+    }
+}
+
+fn all_names(tools: &ToolCollection, filter: &DeclFilter) -> Vec<String> {
+    let mut names: Vec<String> = tools
+        .iter()
+        .filter(|(_, e)| filter.matches(&e.decl))
+        .map(|(n, _)| n.to_string())
+        .collect();
+    names.sort();
+    names
+}
+
+// ---------- walking ----------
+
+#[test]
+fn pages_cover_every_tool_once_in_name_order() {
+    let tools = tools();
+    let pages = walk(&tools, 37, &DeclFilter::default());
+
+    assert_eq!(pages.len(), TOOLS.div_ceil(37));
+    assert!(pages[..pages.len() - 1].iter().all(|p| p.len() == 37));
+    assert_eq!(pages.concat(), all_names(&tools, &DeclFilter::default()));
+}
+
+#[test]
+fn exact_multiple_has_no_empty_trailing_page() {
+    let tools = tools();
+    let pages = walk(&tools, 100, &DeclFilter::default());
+    assert_eq!(pages.len(), 5);
+    assert!(pages.iter().all(|p| p.len() == 100));
+}
+
+#[test]
+fn zero_limit_still_makes_progress() {
+    let mut tools: ToolCollection = ToolCollection::new();
+    for name in ["a", "b"] {
+        tools
+            .register_spec(
+                ToolSpec::new(name, "A tool.").handler(|_| Box::pin(async { Ok(json!(null)) })),
+            )
+            .unwrap();
+    }
+    assert_eq!(
+        walk(&tools, 0, &DeclFilter::default()),
+        [vec!["a".to_string()], vec!["b".to_string()]]
+    );
+}
+
+// ---------- filters ----------
+
+#[test]
+fn filters_apply_before_paging() {
+    let tools = tools();
+    let filter = DeclFilter::default().tag("even").name_prefix("group2.");
+    let pages = walk(&tools, 7, &filter);
+    let names = pages.concat();
+
+    assert_eq!(names.len(), TOOLS / 10);
+    assert!(pages[..pages.len() - 1].iter().all(|p| p.len() == 7));
+    assert!(names.iter().all(|n| n.starts_with("group2.")));
+    assert_eq!(names, all_names(&tools, &filter));
+}
+
+// ---------- stability ----------
+
+#[test]
+fn changes_between_pages_cause_no_gaps_or_duplicates() {
+    let mut tools = tools();
+    let filter = DeclFilter::default();
+    let before = all_names(&tools, &filter);
+
+    let first = tools.declarations_page(None, 50, &filter);
+    let mut seen: Vec<String> = first
+        .declarations
+        .iter()
+        .map(|d| d.name.to_string())
+        .collect();
+    let mut cursor = first.next_cursor;
+
+    // Remove one tool already returned and one not yet reached, and add
+    // one that sorts before the cursor.
+    tools.unregister(&before[10]).unwrap();
+    tools.unregister(&before[300]).unwrap();
+    tools
+        .register_spec(
+            ToolSpec::new("aaa.new", "A tool.").handler(|_| Box::pin(async { Ok(json!(null)) })),
+        )
+        .unwrap();
+
+    while let Some(c) = cursor {
+        let page = tools.declarations_page(Some(&c), 50, &filter);
+        seen.extend(page.declarations.iter().map(|d| d.name.to_string()));
+        cursor = page.next_cursor;
+    }
+
+    let mut expected = before.clone();
+    expected.remove(300);
+    assert_eq!(seen, expected);
+}
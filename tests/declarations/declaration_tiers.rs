@@ -0,0 +1,94 @@
+//! Full and terse declarations, and the tier chosen for each turn.
+
+use serde_json::json;
+use tools_rs::{AgentProfile, DeclTier, FunctionDecl, TierTracker, ToolCollection, ToolSpec};
+
+fn tools() -> ToolCollection {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register_spec(
+            ToolSpec::new(
+                "create_issue",
+                "Opens an issue.\nThe body is rendered as Markdown.",
+            )
+            .params_schema(json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "title": "CreateIssue",
+                "type": "object",
+                "properties": {
+                    "title": { "type": "string", "description": "One-line summary." },
+                    "description": { "type": "string", "description": "The body." },
+                    "labels": {
+                        "type": "array",
+                        "items": { "type": "string", "enum": ["bug", "idea"], "examples": ["bug"] },
+                        "default": []
+                    }
+                },
+                "required": ["title"]
+            }))
+            .handler(|_| Box::pin(async { Ok(json!(1)) })),
+        )
+        .unwrap();
+    tools
+}
+
+#[test]
+fn terse_keeps_names_types_and_required() {
+    let tools = tools();
+    let terse = tools.json_tier(DeclTier::Terse).unwrap();
+    assert_eq!(
+        terse,
+        json!([{
+            "name": "create_issue",
+            "description": "Opens an issue.",
+            "parameters": {
+                "type": "object",
+                "properties": {
+                    "title": { "type": "string" },
+                    "description": { "type": "string" },
+                    "labels": { "type": "array", "items": { "type": "string", "enum": ["bug", "idea"] } }
+                },
+                "required": ["title"]
+            }
+        }])
+    );
+    assert_eq!(
+        tools.json_tier(DeclTier::Full).unwrap(),
+        tools.json().unwrap()
+    );
+
+    let decl = FunctionDecl::new("x", "", json!({}));
+    assert_eq!(decl.to_terse(), decl);
+}
+
+#[test]
+fn full_on_the_first_turn_and_after_changes() {
+    let mut tools = tools();
+    let mut tiers = TierTracker::new();
+    assert_eq!(tiers.tier(tools.registry_hash()), DeclTier::Full);
+    assert_eq!(tiers.tier(tools.registry_hash()), DeclTier::Terse);
+    assert_eq!(tiers.tier(tools.registry_hash()), DeclTier::Terse);
+
+    tools
+        .register("ping", "Pings.", |_: ()| async { "pong" }, ())
+        .unwrap();
+    assert_eq!(tiers.tier(tools.registry_hash()), DeclTier::Full);
+    assert_eq!(tiers.tier(tools.registry_hash()), DeclTier::Terse);
+
+    tiers.reset();
+    assert_eq!(tiers.tier(tools.registry_hash()), DeclTier::Full);
+}
+
+#[test]
+fn profile_views_have_tiers_too() {
+    let mut tools = tools();
+    tools
+        .register("ping", "Pings.", |_: ()| async { "pong" }, ())
+        .unwrap();
+    tools.set_profile(AgentProfile::new("pinger").allow(["ping"]));
+    let view = tools.profile("pinger").unwrap();
+    assert_ne!(view.registry_hash(), tools.registry_hash());
+    let terse = view.json_tier(DeclTier::Terse).unwrap();
+    assert_eq!(terse.as_array().unwrap().len(), 1);
+    assert_eq!(terse[0]["name"], "ping");
+}
@@ -0,0 +1,144 @@
+//! `explain_failure` on representative bad calls.
+//!
+//! `tests/fixtures/diagnostics/failures.expected.json` holds the golden
+//! explanations, keyed by case. Regenerate it with
+//! `UPDATE_GOLDEN=1 cargo test --test diagnostics` after an intentional
+//! change.
+
+use std::{fs, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use tools_rs::{
+    ErrorClass, FunctionCall, ToolCollection, ToolSchema, UnknownArgs, diagnostics::explain_failure,
+};
+
+#[derive(Serialize, Deserialize, ToolSchema)]
+#[serde(rename_all = "snake_case")]
+enum Units {
+    Celsius,
+    Fahrenheit,
+}
+
+#[derive(Serialize, Deserialize, ToolSchema)]
+struct Stop {
+    lat: f64,
+    lon: f64,
+}
+
+#[derive(Serialize, Deserialize, ToolSchema)]
+struct Route {
+    name: String,
+    stops: Vec<Stop>,
+    units: Units,
+    days: u32,
+    note: Option<String>,
+}
+
+fn tools() -> ToolCollection {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register(
+            "plan",
+            "Plans a route.",
+            |r: Route| async move { r.stops.len() },
+            (),
+        )
+        .unwrap();
+    tools
+}
+
+async fn explain(tools: &ToolCollection, arguments: Value) -> Value {
+    let call = FunctionCall::new("plan".into(), arguments);
+    let err = tools.call(call.clone()).await.unwrap_err();
+    let decl = &tools.get("plan").unwrap().decl;
+    let mut explanation = explain_failure(decl, &call.arguments, &err);
+    // serde's wording is not ours to pin.
+    explanation["error"] = json!("...");
+    explanation
+}
+
+fn example_route() -> Value {
+    json!({ "name": "", "stops": [], "units": "celsius", "days": 0 })
+}
+
+fn valid() -> Value {
+    json!({
+        "name": "coast",
+        "stops": [{ "lat": 43.3, "lon": -1.9 }],
+        "units": "celsius",
+        "days": 3,
+        "note": null
+    })
+}
+
+#[tokio::test]
+async fn explanations_match_golden() {
+    let mut tools = tools();
+    let mut cases = Map::new();
+
+    let mut missing = valid();
+    missing.as_object_mut().unwrap().remove("stops");
+    missing.as_object_mut().unwrap().remove("days");
+    cases.insert("missing_fields".into(), explain(&tools, missing).await);
+
+    let mut mismatched = valid();
+    mismatched["stops"][0]["lat"] = json!("43.3");
+    mismatched["days"] = json!(2.5);
+    mismatched["units"] = json!("kelvin");
+    cases.insert("type_mismatches".into(), explain(&tools, mismatched).await);
+
+    cases.insert("not_an_object".into(), explain(&tools, json!([1, 2])).await);
+
+    tools.set_unknown_args(UnknownArgs::Reject);
+    let mut unexpected = valid();
+    unexpected["stopps"] = unexpected["stops"].take();
+    unexpected.as_object_mut().unwrap().remove("stops");
+    cases.insert("unexpected_field".into(), explain(&tools, unexpected).await);
+
+    let actual = Value::Object(cases);
+    let golden = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/diagnostics/failures.expected.json");
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(
+            &golden,
+            serde_json::to_string_pretty(&actual).unwrap() + "\n",
+        )
+        .unwrap();
+    }
+    let expected: Value = serde_json::from_str(&fs::read_to_string(golden).unwrap()).unwrap();
+    assert_eq!(actual, expected);
+}
+
+#[tokio::test]
+async fn only_input_errors_are_explained() {
+    let mut tools = tools();
+    tools
+        .register_raw(
+            "down",
+            "Always fails.",
+            json!({ "type": "object" }),
+            |_| Box::pin(async { Err(tools_rs::ToolError::Runtime("backend down".into())) }),
+            (),
+        )
+        .unwrap();
+
+    let down = FunctionCall::new("down".into(), json!({}));
+    let err = tools.call(down.clone()).await.unwrap_err();
+    let report = tools.report_failure(&down, &err);
+    assert_eq!(report.class, ErrorClass::Internal);
+    assert!(report.explanation.is_none());
+
+    let bad = FunctionCall::new("plan".into(), json!({}));
+    let err = tools.call(bad.clone()).await.unwrap_err();
+    let report = tools.report_failure(&bad, &err);
+    let explanation = report.explanation.unwrap();
+    let missing: Vec<_> = explanation["missing"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| m["path"].as_str().unwrap())
+        .collect();
+    assert_eq!(missing, ["$.name", "$.stops", "$.units", "$.days"]);
+    assert_eq!(explanation["example"], example_route());
+}
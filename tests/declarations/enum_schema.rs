@@ -0,0 +1,265 @@
+//! `#[derive(ToolSchema)]` on enums, across serde's representations.
+//!
+//! Every schema branch is exercised by a value that deserializes through a
+//! registered tool and comes back unchanged.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tools_core::schema::transform::fold_enum_descriptions;
+use tools_rs::{FunctionCall, ToolCollection, ToolSchema};
+
+#[derive(Debug, Serialize, Deserialize, ToolSchema)]
+struct User {
+    id: u64,
+    name: String,
+}
+
+/// Either a bare id or a full object.
+#[derive(Debug, Serialize, Deserialize, ToolSchema)]
+#[serde(untagged)]
+enum UserRef {
+    Id(u64),
+    Full(User),
+    Nothing,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToolSchema)]
+#[serde(rename_all = "snake_case")]
+enum Color {
+    Red,
+    DarkBlue,
+    #[serde(rename = "rgb")]
+    Rgb(u8, u8, u8),
+}
+
+#[derive(Debug, Serialize, Deserialize, ToolSchema)]
+#[serde(rename_all = "lowercase")]
+enum Level {
+    Low,
+    High,
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToolSchema)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+enum Shape {
+    Circle {
+        radius: f64,
+    },
+    Square {
+        side: f64,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToolSchema)]
+#[serde(tag = "t", content = "c")]
+enum Event {
+    Ping,
+    Move(i32),
+}
+
+#[derive(Debug, Serialize, Deserialize, ToolSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+enum Priority {
+    #[schema(variant_description = "Can wait until next week.")]
+    Low,
+    NeedsTriage,
+    #[schema(variant_description = "Page the on-call engineer.")]
+    #[serde(rename = "P0")]
+    Critical,
+    #[schema(variant_description = "Free-form escalation note.")]
+    Escalate(String),
+}
+
+/// Register an identity tool for `T` and check that `value` survives it.
+async fn round_trip<T>(value: Value) -> Value
+where
+    T: Serialize + for<'de> Deserialize<'de> + ToolSchema + Send + 'static,
+{
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register("echo", "Echoes its input.", |x: T| async move { x }, ())
+        .unwrap();
+    tools
+        .call(FunctionCall::new("echo".into(), value))
+        .await
+        .unwrap()
+        .result
+}
+
+// ---------- untagged ----------
+
+#[test]
+fn untagged_is_any_of_without_discriminator() {
+    assert_eq!(
+        UserRef::schema(),
+        json!({
+            "anyOf": [
+                u64::schema(),
+                User::schema(),
+                { "type": "null" }
+            ]
+        })
+    );
+}
+
+#[tokio::test]
+async fn untagged_branches_round_trip() {
+    for value in [json!(7), json!({ "id": 7, "name": "Ada" }), Value::Null] {
+        assert_eq!(round_trip::<UserRef>(value.clone()).await, value);
+    }
+}
+
+// ---------- externally tagged ----------
+
+#[test]
+fn external_units_become_string_enum() {
+    assert_eq!(
+        Color::schema(),
+        json!({
+            "oneOf": [
+                { "type": "string", "enum": ["red", "dark_blue"] },
+                {
+                    "type": "object",
+                    "properties": {
+                        "rgb": {
+                            "type": "array",
+                            "prefixItems": [u8::schema(), u8::schema(), u8::schema()],
+                            "minItems": 3,
+                            "maxItems": 3
+                        }
+                    },
+                    "required": ["rgb"],
+                    "additionalProperties": false
+                }
+            ]
+        })
+    );
+}
+
+#[tokio::test]
+async fn external_branches_round_trip() {
+    for value in [
+        json!("red"),
+        json!("dark_blue"),
+        json!({ "rgb": [1, 2, 3] }),
+    ] {
+        assert_eq!(round_trip::<Color>(value.clone()).await, value);
+    }
+}
+
+// ---------- #[serde(other)] ----------
+
+#[test]
+fn other_relaxes_string_enum() {
+    assert_eq!(
+        Level::schema(),
+        json!({ "type": "string", "examples": ["low", "high"] })
+    );
+}
+
+#[tokio::test]
+async fn other_accepts_unknown_names() {
+    assert_eq!(round_trip::<Level>(json!("high")).await, json!("high"));
+    assert_eq!(
+        round_trip::<Level>(json!("extreme")).await,
+        json!("unknown")
+    );
+}
+
+#[test]
+fn internal_other_accepts_any_tag() {
+    let schema = Shape::schema();
+    let branches = schema["anyOf"].as_array().unwrap();
+    assert_eq!(branches.len(), 3);
+    assert_eq!(
+        branches[0]["properties"]["kind"],
+        json!({ "const": "circle" })
+    );
+    assert_eq!(branches[0]["required"], json!(["kind", "radius"]));
+    assert_eq!(
+        branches[2]["properties"]["kind"],
+        json!({ "type": "string" })
+    );
+}
+
+#[tokio::test]
+async fn internal_branches_round_trip() {
+    let circle = json!({ "kind": "circle", "radius": 1.5 });
+    assert_eq!(round_trip::<Shape>(circle.clone()).await, circle);
+    assert_eq!(
+        round_trip::<Shape>(json!({ "kind": "hexagon" })).await,
+        json!({ "kind": "other" })
+    );
+}
+
+// ---------- adjacently tagged ----------
+
+#[tokio::test]
+async fn adjacent_branches_round_trip() {
+    let schema = Event::schema();
+    assert_eq!(schema["oneOf"][1]["required"], json!(["t", "c"]));
+
+    for value in [json!({ "t": "Ping" }), json!({ "t": "Move", "c": -3 })] {
+        assert_eq!(round_trip::<Event>(value.clone()).await, value);
+    }
+}
+
+// ---------- variant descriptions ----------
+
+#[test]
+fn variant_descriptions_parallel_the_wire_names() {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register(
+            "file_ticket",
+            "Files a ticket.",
+            |p: Priority| async move { p },
+            (),
+        )
+        .unwrap();
+    let params = &tools.get("file_ticket").unwrap().decl.parameters;
+    assert_eq!(
+        params["oneOf"][0],
+        json!({
+            "type": "string",
+            "enum": ["LOW", "NEEDS_TRIAGE", "P0"],
+            "x-enum-descriptions": ["Can wait until next week.", "", "Page the on-call engineer."]
+        })
+    );
+    assert_eq!(
+        params["oneOf"][1]["description"],
+        "Free-form escalation note."
+    );
+
+    let folded = fold_enum_descriptions(params);
+    assert_eq!(
+        folded["oneOf"][0]["description"],
+        "\"LOW\": Can wait until next week.\n\"P0\": Page the on-call engineer."
+    );
+    assert!(folded["oneOf"][0].get("x-enum-descriptions").is_none());
+}
+
+#[test]
+fn undescribed_enums_have_no_description_array() {
+    assert!(
+        Color::schema()["oneOf"][0]
+            .get("x-enum-descriptions")
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn renamed_variants_round_trip() {
+    for value in [
+        json!("LOW"),
+        json!("NEEDS_TRIAGE"),
+        json!("P0"),
+        json!({ "ESCALATE": "db down" }),
+    ] {
+        assert_eq!(round_trip::<Priority>(value.clone()).await, value);
+    }
+}
@@ -0,0 +1,124 @@
+//! A collection's fingerprint, loaded into a stub collection, routes and
+//! validates calls the same way the original did.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tools_rs::{Fingerprint, Invocation, NameNormalization, ToolCollection, ToolSchema};
+
+use crate::common::call;
+
+#[derive(Debug, Serialize, Deserialize, ToolSchema)]
+struct Transfer {
+    from: String,
+    to: String,
+    cents: u64,
+}
+
+fn original() -> ToolCollection {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register(
+            "transfer_funds",
+            "Moves money between accounts.",
+            |t: Transfer| async move { format!("moved {} from {} to {}", t.cents, t.from, t.to) },
+            (),
+        )
+        .unwrap();
+    tools
+        .register("ping", "Liveness check.", |(): ()| async { "pong" }, ())
+        .unwrap();
+    tools
+        .set_name_normalization(NameNormalization::IgnoreCaseAndSeparators)
+        .unwrap();
+    tools.add_alias("pay", "transfer_funds").unwrap();
+    tools
+}
+
+/// Round-trip through JSON, as a bug report would.
+fn stub(tools: &ToolCollection) -> (ToolCollection, tools_rs::Invocations) {
+    let doc = serde_json::to_string(&tools.fingerprint()).unwrap();
+    let fingerprint: Fingerprint = serde_json::from_str(&doc).unwrap();
+    ToolCollection::from_fingerprint(fingerprint).unwrap()
+}
+
+// ---------- document ----------
+
+#[test]
+fn fingerprint_captures_routing_settings() {
+    let fingerprint = original().fingerprint();
+    assert_eq!(fingerprint.format, 1);
+    assert_eq!(
+        fingerprint.normalization,
+        NameNormalization::IgnoreCaseAndSeparators
+    );
+    assert_eq!(fingerprint.aliases["pay"], "transfer_funds");
+    let names: Vec<&str> = fingerprint.tools.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names, ["transfer_funds", "ping"]);
+
+    let value = serde_json::to_value(&fingerprint).unwrap();
+    assert_eq!(value["normalization"], "ignore_case_and_separators");
+    assert_eq!(
+        value["tools"][0]["parameters"]["required"],
+        json!(["from", "to", "cents"])
+    );
+
+    // The stub fingerprints the same.
+    let (stub, _) = stub(&original());
+    assert_eq!(stub.fingerprint(), fingerprint);
+}
+
+// ---------- reproduction ----------
+
+#[tokio::test]
+async fn failures_reproduce_with_the_same_classification() {
+    let tools = original();
+    let (stub, invocations) = stub(&tools);
+
+    for failing in [
+        call("wire_money", json!({})),
+        call("TransferFunds", json!({ "from": "a", "to": "b" })),
+        call("pay", json!("all of it")),
+    ] {
+        let expected = tools.call(failing.clone()).await.unwrap_err();
+        let got = stub.call(failing.clone()).await.unwrap_err();
+        assert_eq!(
+            got.classification(),
+            expected.classification(),
+            "{}: {expected} vs {got}",
+            failing.name
+        );
+    }
+    // Nothing reached a tool.
+    assert!(invocations.snapshot().is_empty());
+}
+
+#[tokio::test]
+async fn stub_tools_echo_and_record() {
+    let (stub, invocations) = stub(&original());
+    let args = json!({ "from": "a", "to": "b", "cents": 5 });
+    let out = stub.call(call("Pay", args.clone())).await.unwrap();
+    assert_eq!(out.result, args);
+    stub.call(call("ping", json!(null))).await.unwrap();
+
+    assert_eq!(
+        invocations.take(),
+        [
+            Invocation {
+                tool: "transfer_funds".into(),
+                arguments: args,
+            },
+            Invocation {
+                tool: "ping".into(),
+                arguments: json!(null),
+            },
+        ]
+    );
+    assert!(invocations.snapshot().is_empty());
+}
+
+#[test]
+fn newer_formats_are_refused() {
+    let mut fingerprint = original().fingerprint();
+    fingerprint.format += 1;
+    assert!(ToolCollection::from_fingerprint(fingerprint).is_err());
+}
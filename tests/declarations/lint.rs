@@ -0,0 +1,102 @@
+//! Fixture-driven tests for `ToolCollection::lint`.
+//!
+//! Each `tests/fixtures/lint/<case>.json` holds a tool declaration
+//! (`description`, `parameters`), whether it is `destructive`, and the
+//! `[code, path]` pairs the lint must report for it.
+
+use std::{fs, path::Path};
+
+use serde_json::Value;
+use tools_core::{
+    ToolCollection,
+    lint::{LintConfig, Severity, codes},
+};
+
+fn load_fixtures() -> ToolCollection<bool> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/lint");
+    let mut tools = ToolCollection::<bool>::new();
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        let fixture: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        // Tool names and descriptions are `&'static str`; leaking a few
+        // fixture strings in a test binary is fine.
+        let name: &'static str = Box::leak(
+            path.file_stem()
+                .unwrap()
+                .to_string_lossy()
+                .into_owned()
+                .into_boxed_str(),
+        );
+        let desc: &'static str = Box::leak(
+            fixture["description"]
+                .as_str()
+                .unwrap()
+                .to_string()
+                .into_boxed_str(),
+        );
+        tools
+            .register_raw(
+                name,
+                desc,
+                fixture["parameters"].clone(),
+                |_| Box::pin(async { Ok(Value::Null) }),
+                fixture["destructive"].as_bool().unwrap(),
+            )
+            .unwrap();
+    }
+    tools
+}
+
+fn expected(case: &str) -> Vec<(String, String)> {
+    let path =
+        Path::new(env!("CARGO_MANIFEST_DIR")).join(format!("tests/fixtures/lint/{case}.json"));
+    let fixture: Value = serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+    let mut pairs: Vec<(String, String)> = fixture["expected"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| (p[0].as_str().unwrap().into(), p[1].as_str().unwrap().into()))
+        .collect();
+    pairs.sort();
+    pairs
+}
+
+#[test]
+fn fixtures_produce_expected_findings() {
+    let tools = load_fixtures();
+    let findings = tools.lint_with(&LintConfig::default().destructive(|d| *d));
+
+    let names: Vec<&str> = tools.iter().map(|(name, _)| name).collect();
+    assert!(names.len() >= 6, "fixture corpus went missing");
+
+    for case in names {
+        let mut got: Vec<(String, String)> = findings
+            .iter()
+            .filter(|f| f.tool == case)
+            .map(|f| (f.code.to_string(), f.path.clone()))
+            .collect();
+        got.sort();
+        assert_eq!(got, expected(case), "fixture `{case}`");
+    }
+}
+
+#[test]
+fn missing_tool_description_is_an_error() {
+    let tools = load_fixtures();
+    let finding = tools
+        .lint()
+        .into_iter()
+        .find(|f| f.code == codes::MISSING_TOOL_DESCRIPTION)
+        .unwrap();
+    assert_eq!(finding.tool, "sloppy");
+    assert_eq!(finding.severity, Severity::Error);
+}
+
+#[test]
+fn thresholds_are_configurable() {
+    let tools = load_fixtures();
+    let config = LintConfig::default().max_depth(10).max_enum_variants(100);
+    let findings = tools.lint_with(&config);
+    assert!(!findings.iter().any(|f| f.code == codes::DEEP_NESTING));
+    assert!(!findings.iter().any(|f| f.code == codes::LARGE_ENUM));
+}
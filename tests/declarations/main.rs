@@ -0,0 +1,19 @@
+//! What the model is shown: declarations, their schemas and layouts,
+//! and the checks and hashes over them.
+
+#[path = "../common/mod.rs"]
+mod common;
+
+mod content_hash;
+mod decl_layouts;
+mod declaration_access;
+mod declaration_pages;
+mod declaration_tiers;
+mod diagnostics;
+mod enum_schema;
+mod fingerprint;
+mod lint;
+mod schema_dialects;
+mod schema_proxy;
+mod serde_rename;
+mod tool_choice;
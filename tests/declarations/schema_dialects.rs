@@ -0,0 +1,155 @@
+//! Each declaration records the JSON Schema dialect of its parameters,
+//! and exports convert from it.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tools_rs::{SchemaDialect, ToolCollection, ToolSchema, ToolSpec};
+
+#[derive(Serialize, Deserialize, ToolSchema)]
+struct Search {
+    query: String,
+    limit: Option<u32>,
+    range: (u32, u32),
+}
+
+/// What schemars 0.8 emits for a struct with an optional nested field.
+fn schemars_schema() -> Value {
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "Lookup",
+        "type": "object",
+        "properties": {
+            "id": { "type": "string" },
+            "filter": { "anyOf": [{ "$ref": "#/definitions/Filter" }, { "type": "null" }] }
+        },
+        "required": ["id"],
+        "definitions": {
+            "Filter": { "type": "object", "properties": { "tag": { "type": "string" } } }
+        }
+    })
+}
+
+fn tools() -> ToolCollection {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register(
+            "search",
+            "Searches.",
+            |s: Search| async move { s.query },
+            (),
+        )
+        .unwrap();
+    tools
+        .register_raw(
+            "lookup",
+            "Looks up a record.",
+            schemars_schema(),
+            |args| Box::pin(async move { Ok(args) }),
+            (),
+        )
+        .unwrap();
+    tools
+        .register_spec(
+            ToolSpec::new("legacy", "Already converted for Gemini.")
+                .params_schema(json!({
+                    "type": "object",
+                    "properties": { "note": { "type": "string", "nullable": true } }
+                }))
+                .schema_dialect(SchemaDialect::OpenApi30)
+                .handler(|args| Box::pin(async move { Ok(args) })),
+        )
+        .unwrap();
+    tools
+}
+
+fn dialect(tools: &ToolCollection, name: &str) -> SchemaDialect {
+    tools.get(name).unwrap().decl.dialect
+}
+
+#[test]
+fn dialect_is_set_at_registration() {
+    let tools = tools();
+    assert_eq!(dialect(&tools, "search"), SchemaDialect::Draft2020_12);
+    assert_eq!(dialect(&tools, "lookup"), SchemaDialect::Draft07);
+    assert_eq!(dialect(&tools, "legacy"), SchemaDialect::OpenApi30);
+
+    let spec = ToolSpec::new("x", "y").params_schema(schemars_schema());
+    assert_eq!(spec.dialect(), SchemaDialect::Draft07);
+}
+
+#[test]
+fn declarations_are_unchanged_by_default() {
+    let tools = tools();
+    let json = tools.json().unwrap();
+    for decl in json.as_array().unwrap() {
+        assert!(decl.get("dialect").is_none());
+    }
+    assert!(json[2]["parameters"].get("$schema").is_none());
+    assert_eq!(json[1]["parameters"], schemars_schema());
+}
+
+#[test]
+fn json_can_carry_schema_uris() {
+    let mut tools = tools();
+    tools.json().unwrap();
+    tools.set_schema_uris(true);
+    let json = tools.json().unwrap();
+    // Sorted by name: `legacy`, `lookup`, `search`.
+    assert!(json[0]["parameters"].get("$schema").is_none());
+    assert_eq!(
+        json[1]["parameters"]["$schema"],
+        "http://json-schema.org/draft-07/schema#"
+    );
+    assert_eq!(
+        json[2]["parameters"]["$schema"],
+        "https://json-schema.org/draft/2020-12/schema"
+    );
+    // The stored declarations are not touched.
+    assert!(
+        tools
+            .get("search")
+            .unwrap()
+            .decl
+            .parameters
+            .get("$schema")
+            .is_none()
+    );
+}
+
+#[test]
+fn exports_for_two_providers() {
+    let tools = tools();
+    let export = |target: SchemaDialect| -> Vec<Value> {
+        tools
+            .iter()
+            .filter_map(|(_, e)| e.decl.parameters_as(target))
+            .map(|p| p.into_owned())
+            .collect()
+    };
+
+    // A JSON Schema consumer takes each schema in its own dialect.
+    let as_is: Vec<Value> = tools
+        .iter()
+        .map(|(_, e)| e.decl.parameters_as(e.decl.dialect).unwrap().into_owned())
+        .collect();
+    assert_eq!(as_is[1], schemars_schema());
+
+    // Gemini gets the OpenAPI 3.0 subset from every dialect.
+    let gemini = export(SchemaDialect::OpenApi30);
+    assert_eq!(gemini.len(), 3);
+    let search = &gemini[0];
+    assert_eq!(search["properties"]["limit"]["nullable"], true);
+    assert!(search["properties"]["range"].get("prefixItems").is_none());
+    let lookup = &gemini[1];
+    assert!(lookup.get("$schema").is_none());
+    assert!(lookup.get("definitions").is_none());
+    assert_eq!(
+        lookup["properties"]["filter"]["properties"]["tag"]["type"],
+        "string"
+    );
+    assert_eq!(lookup["properties"]["filter"]["nullable"], true);
+    assert_eq!(gemini[2], tools.get("legacy").unwrap().decl.parameters);
+
+    // There is no way back up from the subset.
+    assert_eq!(export(SchemaDialect::Draft2020_12).len(), 1);
+}
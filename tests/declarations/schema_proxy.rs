@@ -0,0 +1,112 @@
+//! `#[schema(as = "Type")]`: describe a field by another type's schema when
+//! its serde representation differs from the Rust type.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tools_rs::{FunctionCall, ToolCollection, ToolSchema};
+
+/// humantime-style durations: "90s", "5m", "2h".
+mod duration_str {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+
+    pub fn serialize<S: Serializer>(d: &Duration, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(&format!("{}s", d.as_secs()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Duration, D::Error> {
+        let raw = String::deserialize(d)?;
+        let (num, unit) = raw.split_at(raw.len().saturating_sub(1));
+        let n: u64 = num.parse().map_err(D::Error::custom)?;
+        let secs = match unit {
+            "s" => n,
+            "m" => n * 60,
+            "h" => n * 3600,
+            other => return Err(D::Error::custom(format!("unknown unit `{other}`"))),
+        };
+        Ok(Duration::from_secs(secs))
+    }
+}
+
+/// Plain milliseconds.
+#[derive(Debug, Serialize, Deserialize, ToolSchema)]
+struct Millis(u64);
+
+#[derive(Debug, Serialize, Deserialize, ToolSchema)]
+struct Schedule {
+    #[serde(with = "duration_str")]
+    #[schema(as = "String")]
+    every: Duration,
+    #[schema(as = "std::vec::Vec<String>")]
+    labels: Vec<Label>,
+    retries: u8,
+}
+
+/// Serialized as a plain string, but has no `ToolSchema` of its own.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+struct Label(String);
+
+#[derive(Debug, Serialize, Deserialize, ToolSchema)]
+#[serde(untagged)]
+enum Timeout {
+    Human(
+        #[serde(with = "duration_str")]
+        #[schema(as = "String")]
+        Duration,
+    ),
+    Millis(Millis),
+}
+
+#[test]
+fn proxy_schema_replaces_field_type() {
+    let schema = Schedule::schema();
+    assert_eq!(schema["properties"]["every"], json!({ "type": "string" }));
+    assert_eq!(
+        schema["properties"]["labels"],
+        json!({ "type": "array", "items": { "type": "string" } })
+    );
+    assert_eq!(
+        schema["properties"]["retries"],
+        json!({ "type": "integer", "minimum": 0, "maximum": 255 })
+    );
+    assert_eq!(schema["required"], json!(["every", "labels", "retries"]));
+}
+
+#[test]
+fn proxy_applies_to_enum_variant_fields() {
+    assert_eq!(Timeout::schema()["anyOf"][0], json!({ "type": "string" }));
+}
+
+#[tokio::test]
+async fn custom_deserialization_still_applies() {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register(
+            "schedule",
+            "Schedules a job.",
+            |s: Schedule| async move { (s.every.as_secs(), s.labels.len(), s.retries) },
+            (),
+        )
+        .unwrap();
+
+    let out = tools
+        .call(FunctionCall::new(
+            "schedule".into(),
+            json!({ "every": "5m", "labels": ["a", "b"], "retries": 3 }),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(out.result, json!([300, 2, 3]));
+
+    let err = tools
+        .call(FunctionCall::new(
+            "schedule".into(),
+            json!({ "every": "5 fortnights", "labels": [], "retries": 0 }),
+        ))
+        .await;
+    assert!(err.is_err());
+}
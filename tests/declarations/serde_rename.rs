@@ -0,0 +1,105 @@
+//! Derived schemas list fields under the names serde reads, so the
+//! advertised parameters are the ones the deserializer accepts.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tools_rs::{FunctionCall, ToolCollection, ToolSchema};
+
+#[derive(Debug, Serialize, Deserialize, ToolSchema)]
+#[serde(rename_all = "camelCase")]
+struct Search {
+    search_query: String,
+    #[serde(rename = "max")]
+    max_results: Option<u32>,
+    include_archived_items: bool,
+}
+
+#[derive(Serialize, Deserialize, ToolSchema)]
+#[serde(rename_all = "SCREAMING-KEBAB-CASE")]
+struct Headers {
+    content_type: String,
+    r#type: String,
+}
+
+#[derive(Serialize, Deserialize, ToolSchema)]
+#[serde(tag = "op", rename_all = "lowercase", rename_all_fields = "PascalCase")]
+enum Edit {
+    Insert {
+        at_line: u32,
+        new_text: String,
+    },
+    #[serde(rename_all = "kebab-case")]
+    Delete {
+        from_line: u32,
+        to_line: u32,
+    },
+}
+
+fn keys(schema: &Value) -> Vec<&str> {
+    let mut keys: Vec<&str> = schema["properties"]
+        .as_object()
+        .unwrap()
+        .keys()
+        .map(String::as_str)
+        .collect();
+    keys.sort_unstable();
+    keys
+}
+
+// ---------- schemas ----------
+
+#[test]
+fn container_rule_and_field_rename() {
+    let schema = Search::schema();
+    assert_eq!(
+        keys(&schema),
+        ["includeArchivedItems", "max", "searchQuery"]
+    );
+    assert_eq!(
+        schema["required"],
+        json!(["searchQuery", "includeArchivedItems"])
+    );
+}
+
+#[test]
+fn raw_identifiers_lose_their_prefix() {
+    assert_eq!(keys(&Headers::schema()), ["CONTENT-TYPE", "TYPE"]);
+}
+
+#[test]
+fn struct_variant_fields_are_renamed() {
+    let branches = Edit::schema()["oneOf"].as_array().unwrap().clone();
+    assert_eq!(keys(&branches[0]), ["AtLine", "NewText", "op"]);
+    assert_eq!(keys(&branches[1]), ["from-line", "op", "to-line"]);
+    assert_eq!(
+        branches[1]["required"],
+        json!(["op", "from-line", "to-line"])
+    );
+}
+
+// ---------- calls ----------
+
+#[tokio::test]
+async fn renamed_arguments_deserialize() {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register(
+            "search",
+            "Searches.",
+            |s: Search| async move {
+                format!(
+                    "{}:{:?}:{}",
+                    s.search_query, s.max_results, s.include_archived_items
+                )
+            },
+            (),
+        )
+        .unwrap();
+
+    let args = json!({ "searchQuery": "rust", "max": 3, "includeArchivedItems": false });
+    let out = tools
+        .call(FunctionCall::new("search".into(), args))
+        .await
+        .unwrap();
+    assert_eq!(out.result, json!("rust:Some(3):false"));
+}
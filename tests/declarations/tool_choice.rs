@@ -0,0 +1,122 @@
+//! `ToolChoice` lowered by each provider adapter, and checked against the
+//! declarations being sent.
+
+use serde_json::{Value, json};
+use tools_rs::{
+    AgentProfile, ToolChoice, ToolCollection, ToolError, ToolSpec,
+    choice::{anthropic, gemini, openai},
+};
+
+fn tools() -> ToolCollection {
+    let mut tools: ToolCollection = ToolCollection::new();
+    for name in ["search_web", "run_tests"] {
+        tools
+            .register_spec(
+                ToolSpec::new(name, "Test tool.").handler(|args| Box::pin(async move { Ok(args) })),
+            )
+            .unwrap();
+    }
+    tools.set_profile(AgentProfile::new("researcher").allow(["search_web"]));
+    tools
+}
+
+fn variants() -> [ToolChoice; 4] {
+    [
+        ToolChoice::Auto,
+        ToolChoice::None,
+        ToolChoice::Required,
+        ToolChoice::specific("search_web"),
+    ]
+}
+
+fn lowered(lower: fn(&ToolChoice, &ToolCollection) -> Result<Value, ToolError>) -> Vec<Value> {
+    let tools = tools();
+    variants()
+        .iter()
+        .map(|choice| lower(choice, &tools).unwrap())
+        .collect()
+}
+
+// ---------- adapters ----------
+
+#[test]
+fn openai_lowering() {
+    assert_eq!(
+        lowered(openai::tool_choice),
+        [
+            json!("auto"),
+            json!("none"),
+            json!("required"),
+            json!({ "type": "function", "function": { "name": "search_web" } }),
+        ]
+    );
+    assert_eq!(
+        openai::force_tool("search_web"),
+        lowered(openai::tool_choice)[3]
+    );
+}
+
+#[test]
+fn anthropic_lowering() {
+    assert_eq!(
+        lowered(anthropic::tool_choice),
+        [
+            json!({ "type": "auto" }),
+            json!({ "type": "none" }),
+            json!({ "type": "any" }),
+            json!({ "type": "tool", "name": "search_web" }),
+        ]
+    );
+    assert_eq!(
+        anthropic::force_tool("search_web"),
+        lowered(anthropic::tool_choice)[3]
+    );
+}
+
+#[test]
+fn gemini_lowering() {
+    let modes: Vec<Value> = lowered(gemini::tool_config)
+        .into_iter()
+        .map(|config| config["functionCallingConfig"].clone())
+        .collect();
+    assert_eq!(
+        modes,
+        [
+            json!({ "mode": "AUTO" }),
+            json!({ "mode": "NONE" }),
+            json!({ "mode": "ANY" }),
+            json!({ "mode": "ANY", "allowedFunctionNames": ["search_web"] }),
+        ]
+    );
+}
+
+// ---------- validation ----------
+
+#[test]
+fn forced_tools_must_be_declared() {
+    let tools = tools();
+    let ghost = ToolChoice::specific("delete_everything");
+    assert!(matches!(
+        openai::tool_choice(&ghost, &tools).unwrap_err(),
+        ToolError::FunctionNotFound { name } if name == "delete_everything"
+    ));
+
+    // A profile only declares its own tools.
+    let researcher = tools.profile("researcher").unwrap();
+    let outside = ToolChoice::specific("run_tests");
+    assert!(anthropic::tool_choice(&outside, &researcher).is_err());
+    assert!(openai::tool_choice(&outside, &tools).is_ok());
+    assert!(gemini::tool_config(&ToolChoice::specific("search_web"), &researcher).is_ok());
+    // Nothing to check for the other variants.
+    assert!(anthropic::tool_choice(&ToolChoice::Required, &researcher).is_ok());
+}
+
+#[test]
+fn choices_serialize() {
+    assert_eq!(ToolChoice::default(), ToolChoice::Auto);
+    assert_eq!(
+        serde_json::to_value(ToolChoice::specific("search_web")).unwrap(),
+        json!({ "specific": "search_web" })
+    );
+    assert_eq!(serde_json::to_value(ToolChoice::None).unwrap(), "none");
+}
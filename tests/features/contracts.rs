@@ -0,0 +1,172 @@
+//! Contract tests: calls recorded during development, replayed against
+//! the current registry.
+
+#![cfg(feature = "testing")]
+
+use std::{
+    fs,
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use serde::Serialize;
+use serde_json::json;
+use tools_rs::{
+    ErrorClass, FunctionCall, ToolCollection, ToolSchema,
+    testing::{ContractOutcome, ContractRecorder, check_contracts, load_contracts, run_contracts},
+};
+
+/// A fresh contract file path under the system temp dir.
+fn contract_path(test: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "tools-rs-contracts-{test}-{}.jsonl",
+        std::process::id()
+    ));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+#[derive(Serialize, ToolSchema)]
+struct Quote {
+    total: f64,
+    quoted_at: u64,
+}
+
+/// `quote` returns a price and a timestamp that changes on every call.
+fn tools(rate: f64) -> ToolCollection {
+    let clock = Arc::new(AtomicU64::new(1_000));
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register(
+            "quote",
+            "Prices an order.",
+            move |qty: u32| {
+                let at = clock.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    Quote {
+                        total: f64::from(qty) * rate,
+                        quoted_at: at,
+                    }
+                }
+            },
+            (),
+        )
+        .unwrap();
+    tools.add_alias("price", "quote").unwrap();
+    tools
+}
+
+async fn record(path: &PathBuf) {
+    let mut tools = tools(2.5);
+    let recorder = ContractRecorder::attach(&mut tools, path).unwrap();
+    tools
+        .call(FunctionCall::new("quote".into(), json!(4)))
+        .await
+        .unwrap();
+    tools
+        .call(FunctionCall::new("price".into(), json!(10)))
+        .await
+        .unwrap();
+    tools
+        .call(FunctionCall::new("quote".into(), json!("lots")))
+        .await
+        .unwrap_err();
+    assert_eq!((recorder.recorded(), recorder.dropped()), (3, 0));
+}
+
+#[tokio::test]
+async fn calls_are_recorded_under_a_versioned_header() {
+    let path = contract_path("record");
+    record(&path).await;
+
+    let text = fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines[0], r#"{"format":"tools-rs/contracts","version":1}"#);
+    assert_eq!(
+        lines[1],
+        r#"{"arguments":4,"result":{"quoted_at":1000,"total":10},"tool":"quote"}"#
+    );
+
+    let contracts = load_contracts(&path).unwrap();
+    assert_eq!(contracts.len(), 3);
+    // Aliases are recorded under the tool's name.
+    assert_eq!((contracts[1].0, contracts[1].1.tool.as_str()), (3, "quote"));
+    assert!(matches!(
+        &contracts[2].1.outcome,
+        ContractOutcome::Error {
+            class: ErrorClass::InvalidInput,
+            ..
+        }
+    ));
+
+    // Attaching again appends below the same header.
+    record(&path).await;
+    assert_eq!(load_contracts(&path).unwrap().len(), 6);
+    let text = fs::read_to_string(&path).unwrap();
+    assert_eq!(text.matches("tools-rs/contracts").count(), 1);
+}
+
+#[tokio::test]
+async fn unchanged_tools_replay_cleanly() {
+    let path = contract_path("replay");
+    record(&path).await;
+
+    let tools = tools(2.5);
+    // Move the clock on, so the timestamps differ on replay.
+    tools
+        .call(FunctionCall::new("quote".into(), json!(1)))
+        .await
+        .unwrap();
+    let mismatches = check_contracts(&tools, &path, &[]).await.unwrap();
+    assert_eq!(mismatches.len(), 2);
+    assert!(
+        check_contracts(&tools, &path, &["/quoted_at"])
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    run_contracts(&tools, &path, &["/quoted_at"]).await;
+}
+
+#[tokio::test]
+async fn changed_results_come_with_a_diff() {
+    let path = contract_path("mismatch");
+    record(&path).await;
+
+    let tools = tools(3.0);
+    let mismatches = check_contracts(&tools, &path, &["/quoted_at"])
+        .await
+        .unwrap();
+    assert_eq!(mismatches.len(), 2);
+    let first = &mismatches[0];
+    assert_eq!((first.line, first.tool.as_str()), (2, "quote"));
+    assert_eq!(first.arguments, json!(4));
+    assert!(first.diff.contains("-     \"total\": 10"), "{}", first.diff);
+    assert!(first.diff.contains("+     \"total\": 12"), "{}", first.diff);
+    assert!(first.to_string().starts_with("line 2: `quote` with 4\n"));
+
+    let replay = tokio::spawn(async move {
+        run_contracts(&tools, &path, &["/quoted_at"]).await;
+    });
+    let panic = replay.await.unwrap_err().into_panic();
+    let message = panic.downcast_ref::<String>().unwrap();
+    assert!(message.starts_with("2 of the contracts in "), "{message}");
+}
+
+#[test]
+fn foreign_files_are_refused() {
+    let path = contract_path("foreign");
+    fs::write(&path, "{\"tool\":\"quote\"}\n").unwrap();
+    let err = load_contracts(&path).unwrap_err();
+    assert!(err.to_string().contains("not a contract file"), "{err}");
+
+    let mut tools = tools(1.0);
+    assert!(ContractRecorder::attach(&mut tools, &path).is_err());
+
+    fs::write(&path, "{\"format\":\"tools-rs/contracts\",\"version\":9}\n").unwrap();
+    let err = load_contracts(&path).unwrap_err();
+    assert!(err.to_string().contains("version 9"), "{err}");
+}
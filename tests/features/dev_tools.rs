@@ -0,0 +1,153 @@
+//! The hidden `__describe_tool` / `__list_tools` developer tools, called
+//! through the normal dispatch path.
+
+#![cfg(feature = "dev-tools")]
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tools_rs::{AgentProfile, ErrorClass, ToolCollection, ToolError, ToolSchema};
+
+use crate::common::call;
+
+#[derive(Serialize, Deserialize, ToolSchema)]
+struct Weather {
+    city: String,
+    days: Option<u32>,
+}
+
+fn tools() -> ToolCollection {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register(
+            "get_weather",
+            "Gets the forecast. Temperatures are in Celsius.",
+            |w: Weather| async move { w.city },
+            (),
+        )
+        .unwrap();
+    tools
+        .register(
+            "delete_account",
+            "Deletes the user's account",
+            |_: ()| async {},
+            (),
+        )
+        .unwrap();
+    tools.register_dev_tools().unwrap();
+    tools
+}
+
+fn names(json: &Value) -> Vec<&str> {
+    json.as_array()
+        .unwrap()
+        .iter()
+        .map(|d| d["name"].as_str().unwrap())
+        .collect()
+}
+
+// ---------- declarations ----------
+
+#[test]
+fn dev_tools_are_not_declared_by_default() {
+    let mut tools = tools();
+    assert_eq!(tools.len(), 4);
+    assert_eq!(
+        names(&tools.json().unwrap()),
+        ["delete_account", "get_weather"]
+    );
+    assert!(!tools.to_canonical_string().unwrap().contains("__"));
+
+    tools.set_declare_hidden(true);
+    assert_eq!(
+        names(&tools.json().unwrap()),
+        [
+            "__describe_tool",
+            "__list_tools",
+            "delete_account",
+            "get_weather"
+        ]
+    );
+}
+
+// ---------- calls ----------
+
+#[tokio::test]
+async fn list_tools_gives_names_and_summaries() {
+    let tools = tools();
+    let out = tools
+        .call(call("__list_tools", json!({})))
+        .await
+        .unwrap()
+        .result;
+    assert_eq!(
+        out["tools"][0],
+        json!({ "name": "get_weather", "summary": "Gets the forecast." })
+    );
+    assert_eq!(out["tools"][1]["summary"], "Deletes the user's account");
+    assert_eq!(out["tools"].as_array().unwrap().len(), 4);
+}
+
+#[tokio::test]
+async fn describe_tool_returns_the_declaration() {
+    let tools = tools();
+    let out = tools
+        .call(call("__describe_tool", json!({ "name": "get_weather" })))
+        .await
+        .unwrap()
+        .result;
+    let decl = &tools.get("get_weather").unwrap().decl;
+    assert_eq!(out["description"], *decl.description);
+    assert_eq!(out["parameters"], decl.parameters);
+    assert_eq!(out["dialect"], "draft2020_12");
+
+    let err = tools
+        .call(call("__describe_tool", json!({ "name": "get_wether" })))
+        .await
+        .unwrap_err();
+    assert_eq!(err.classification(), ErrorClass::InvalidInput);
+    assert!(err.to_string().contains("did you mean `get_weather`"));
+}
+
+// ---------- profiles ----------
+
+#[tokio::test]
+async fn profiles_reach_dev_tools_only_when_allowed() {
+    let mut tools = tools();
+    tools.set_profile(AgentProfile::new("everything"));
+    tools.set_profile(AgentProfile::new("support").allow([
+        "get_weather",
+        "__describe_tool",
+        "__list_tools",
+    ]));
+
+    let everything = tools.profile("everything").unwrap();
+    assert_eq!(
+        names(&everything.json().unwrap()),
+        ["delete_account", "get_weather"]
+    );
+    let err = everything
+        .call(call("__list_tools", json!({})))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ToolError::FunctionNotFound { .. }));
+
+    let support = tools.profile("support").unwrap();
+    assert_eq!(names(&support.json().unwrap()), ["get_weather"]);
+    let listed = support
+        .call(call("__list_tools", json!({})))
+        .await
+        .unwrap()
+        .result;
+    let listed: Vec<&str> = listed["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(listed, ["get_weather", "__describe_tool", "__list_tools"]);
+    let err = support
+        .call(call("__describe_tool", json!({ "name": "delete_account" })))
+        .await
+        .unwrap_err();
+    assert_eq!(err.classification(), ErrorClass::InvalidInput);
+}
@@ -0,0 +1,179 @@
+//! GraphQL import against a small schema with a stub endpoint.
+
+#![cfg(feature = "graphql")]
+
+use std::sync::{Arc, Mutex};
+
+use serde::Deserialize;
+use serde_json::{Value, json};
+use tools_core::graphql::{self, Auth, HttpRequest, HttpSend};
+use tools_rs::{ToolCollection, ToolError};
+
+use crate::common::call;
+
+const ENDPOINT: &str = "https://api.example.com/graphql";
+
+const SDL: &str = r#"
+    schema { query: RootQuery mutation: RootMutation }
+
+    enum Status { OPEN CLOSED }
+
+    input NewTodo {
+        title: String!
+        tags: [String!]
+        status: Status = OPEN
+    }
+
+    type Todo {
+        id: ID!
+        title: String!
+        status: Status!
+    }
+
+    type RootQuery {
+        "Fetch one todo."
+        todo(id: ID!): Todo
+        todos(status: Status): [Todo!]!
+    }
+
+    type RootMutation {
+        addTodo(input: NewTodo!): Todo!
+    }
+
+    extend type RootQuery {
+        count: Int!
+    }
+"#;
+
+/// Records every request and answers with `reply(query)`.
+fn stub(seen: Arc<Mutex<Vec<HttpRequest>>>, reply: fn(&str) -> Value) -> Arc<HttpSend> {
+    Arc::new(move |req: HttpRequest| {
+        let query = req.body.as_ref().unwrap()["query"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        seen.lock().unwrap().push(req);
+        Box::pin(async move { Ok(reply(&query)) })
+    })
+}
+
+// ---------- import ----------
+
+#[test]
+fn custom_roots_and_extensions() {
+    let specs = graphql::import(SDL, ENDPOINT, &["todo", "count", "addTodo"], Auth::None).unwrap();
+    let names: Vec<&str> = specs.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, ["todo", "count", "addTodo"]);
+
+    assert_eq!(
+        specs[0].document,
+        "query todo($id: ID!) { todo(id: $id) { id title status } }"
+    );
+    assert_eq!(specs[1].document, "query count { count }");
+    assert_eq!(
+        specs[2].document,
+        "mutation addTodo($input: NewTodo!) { addTodo(input: $input) { id title status } }"
+    );
+
+    let input = &specs[2].parameters["properties"]["input"];
+    assert_eq!(input["required"], json!(["title"]));
+    assert_eq!(
+        input["properties"]["status"],
+        json!({ "type": "string", "enum": ["OPEN", "CLOSED"] })
+    );
+    assert_eq!(
+        input["properties"]["tags"],
+        json!({ "type": "array", "items": { "type": "string" } })
+    );
+}
+
+#[test]
+fn unknown_allow_list_entry() {
+    let err = graphql::import(SDL, ENDPOINT, &["RootQuery.addTodo"], Auth::None).unwrap_err();
+    assert_eq!(
+        err,
+        graphql::ImportError::UnknownField("RootQuery.addTodo".into())
+    );
+}
+
+// ---------- execution ----------
+
+#[tokio::test]
+async fn call_sends_variables_and_returns_field() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let send = stub(
+        seen.clone(),
+        |_| json!({ "data": { "todo": { "id": "7", "title": "Ship it", "status": "OPEN" } } }),
+    );
+
+    let specs = graphql::import(SDL, ENDPOINT, &["todo"], Auth::Bearer("secret".into())).unwrap();
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools.register_graphql(specs, send).unwrap();
+
+    let out = tools
+        .call(call("todo", json!({ "id": "7" })))
+        .await
+        .unwrap();
+    assert_eq!(out.result["title"], "Ship it");
+
+    let seen = seen.lock().unwrap();
+    assert_eq!(seen[0].url, ENDPOINT);
+    assert_eq!(
+        seen[0].body.as_ref().unwrap()["variables"],
+        json!({ "id": "7" })
+    );
+    assert!(
+        seen[0]
+            .headers
+            .contains(&("Authorization".into(), "Bearer secret".into()))
+    );
+}
+
+#[tokio::test]
+async fn graphql_errors_become_remote_errors() {
+    let send = stub(Arc::default(), |_| {
+        json!({
+            "data": null,
+            "errors": [
+                { "message": "title too long", "path": ["addTodo"] },
+                { "message": "rate limited" }
+            ]
+        })
+    });
+
+    let specs = graphql::import(SDL, ENDPOINT, &["addTodo"], Auth::None).unwrap();
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools.register_graphql(specs, send).unwrap();
+
+    let err = tools
+        .call(call("addTodo", json!({ "input": { "title": "x" } })))
+        .await
+        .unwrap_err();
+    match err {
+        ToolError::Remote { message, details } => {
+            assert_eq!(message, "title too long; rate limited");
+            assert_eq!(details["errors"][0]["path"], json!(["addTodo"]));
+        }
+        other => panic!("unexpected error: {other}"),
+    }
+}
+
+#[tokio::test]
+async fn metadata_names_operation_and_field() {
+    #[derive(Debug, Deserialize)]
+    struct Op {
+        operation: String,
+        field: String,
+    }
+
+    let specs = graphql::import(SDL, ENDPOINT, &["todos", "addTodo"], Auth::None).unwrap();
+    let mut tools: ToolCollection<Op> = ToolCollection::new();
+    tools
+        .register_graphql(specs, stub(Arc::default(), |_| json!({ "data": {} })))
+        .unwrap();
+
+    let add = tools.meta("addTodo").unwrap();
+    assert_eq!(add.operation, "mutation");
+    assert_eq!(add.field, "addTodo");
+    assert_eq!(tools.meta("todos").unwrap().operation, "query");
+}
@@ -0,0 +1,86 @@
+//! Fixture-driven tests for the `json-repair` feature.
+//!
+//! Every `tests/fixtures/json_repair/<case>.txt` is a model reply;
+//! `<case>.expected.json` is the `{"tool", "arguments"}` object it must
+//! parse to. The `well_formed` case must parse without repair.
+#![cfg(feature = "json-repair")]
+
+use std::{fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tools_core::repair::parse_freeform_call_repaired;
+use tools_rs::{ToolCollection, ToolSchema, parse_freeform_call};
+
+#[derive(Serialize, Deserialize, ToolSchema)]
+struct Echo {
+    text: String,
+}
+
+fn fixtures() -> Vec<(String, String, Value)> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/json_repair");
+    let mut cases: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "txt"))
+        .map(|p| {
+            let name = p.file_stem().unwrap().to_string_lossy().into_owned();
+            let input = fs::read_to_string(&p).unwrap();
+            let expected = fs::read_to_string(dir.join(format!("{name}.expected.json"))).unwrap();
+            (name, input, serde_json::from_str(&expected).unwrap())
+        })
+        .collect();
+    cases.sort_by(|a, b| a.0.cmp(&b.0));
+    cases
+}
+
+#[test]
+fn corpus_parses_to_expected_calls() {
+    let cases = fixtures();
+    assert!(cases.len() >= 5, "fixture corpus went missing");
+
+    for (name, input, expected) in cases {
+        let parsed = parse_freeform_call_repaired(&input)
+            .unwrap_or_else(|| panic!("{name}: no call extracted"));
+        assert_eq!(parsed.value.name, expected["tool"], "{name}: tool");
+        assert_eq!(
+            parsed.value.arguments, expected["arguments"],
+            "{name}: arguments"
+        );
+        assert_eq!(parsed.applied, name != "well_formed", "{name}: repair flag");
+        assert_eq!(parsed.value.repaired, parsed.applied, "{name}: call flag");
+    }
+}
+
+#[test]
+fn parse_freeform_call_uses_repair_when_enabled() {
+    let call = parse_freeform_call("{'tool': 'ping',}").unwrap();
+    assert_eq!(call.name, "ping");
+    assert!(call.repaired);
+}
+
+#[tokio::test]
+async fn responses_say_the_call_was_repaired() {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register("echo", "Echoes.", |e: Echo| async move { e.text }, ())
+        .unwrap();
+
+    let call = parse_freeform_call("{tool: 'echo', arguments: {text: 'hi'},}").unwrap();
+    let response = tools.call_ref(&call).await.unwrap();
+    assert!(response.repaired);
+    assert_eq!(response.result, json!("hi"));
+    let value = serde_json::to_value(&response).unwrap();
+    assert_eq!(value["repaired"], true);
+
+    let call = parse_freeform_call(r#"{"tool": "echo", "arguments": {"text": "hi"}}"#).unwrap();
+    assert!(!call.repaired);
+    let response = tools.call(call).await.unwrap();
+    assert!(!response.repaired);
+    assert!(
+        serde_json::to_value(&response)
+            .unwrap()
+            .get("repaired")
+            .is_none()
+    );
+}
@@ -0,0 +1,12 @@
+//! Optional features, each compiled in with its Cargo feature.
+
+#[path = "../common/mod.rs"]
+mod common;
+
+mod contracts;
+mod dev_tools;
+mod graphql_import;
+mod json_repair;
+mod memory_tools;
+mod openapi_import;
+mod wasm_tools;
@@ -0,0 +1,145 @@
+//! The memory tool kit: namespaces per conversation, the in-memory store
+//! and the declarations shown to the model.
+
+#![cfg(feature = "memory-tools")]
+
+use std::sync::Arc;
+
+use serde_json::{Value, json};
+use tools_core::memory::{self, InMemoryStore, KvStore, MemoryToolSet};
+use tools_rs::{ErrorClass, FunctionCall, ToolCollection, ToolError};
+
+fn tools() -> (ToolCollection, Arc<InMemoryStore>) {
+    let store = Arc::new(InMemoryStore::default());
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register_memory(&MemoryToolSet::new(store.clone()))
+        .unwrap();
+    (tools, store)
+}
+
+async fn call_in(
+    tools: &ToolCollection,
+    conversation: &str,
+    name: &str,
+    arguments: Value,
+) -> Result<Value, ToolError> {
+    let call = FunctionCall::new(name.to_string(), arguments);
+    memory::with_conversation(conversation, tools.call(call))
+        .await
+        .map(|r| r.result)
+}
+
+// ---------- namespaces ----------
+
+#[tokio::test]
+async fn conversations_do_not_see_each_other() {
+    let (tools, _) = tools();
+    call_in(
+        &tools,
+        "a",
+        "remember",
+        json!({ "key": "name", "value": "Ada" }),
+    )
+    .await
+    .unwrap();
+    call_in(
+        &tools,
+        "b",
+        "remember",
+        json!({ "key": "name", "value": "Bob" }),
+    )
+    .await
+    .unwrap();
+
+    let a = call_in(&tools, "a", "recall", json!({ "key": "name" }))
+        .await
+        .unwrap();
+    assert_eq!(a, json!({ "key": "name", "found": true, "value": "Ada" }));
+
+    call_in(&tools, "b", "forget", json!({ "key": "name" }))
+        .await
+        .unwrap();
+    let b = call_in(&tools, "b", "recall", json!({ "key": "name" }))
+        .await
+        .unwrap();
+    assert_eq!(b, json!({ "key": "name", "found": false }));
+    let a = call_in(&tools, "a", "list_memories", json!({}))
+        .await
+        .unwrap();
+    assert_eq!(a, json!({ "keys": ["name"] }));
+}
+
+#[tokio::test]
+async fn calls_outside_a_conversation_fail() {
+    let (tools, _) = tools();
+    let err = tools
+        .call(FunctionCall::new("recall".into(), json!({ "key": "name" })))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ToolError::Runtime(m) if m.contains("with_conversation")));
+}
+
+// ---------- tools ----------
+
+#[tokio::test]
+async fn remember_overwrites_and_forget_reports() {
+    let (tools, store) = tools();
+    for value in [json!("km"), json!({ "system": "metric" })] {
+        call_in(
+            &tools,
+            "c",
+            "remember",
+            json!({ "key": "units", "value": value }),
+        )
+        .await
+        .unwrap();
+    }
+    assert_eq!(
+        store.get("c", "units").await.unwrap(),
+        Some(json!({ "system": "metric" }))
+    );
+
+    let first = call_in(&tools, "c", "forget", json!({ "key": "units" }))
+        .await
+        .unwrap();
+    let second = call_in(&tools, "c", "forget", json!({ "key": "units" }))
+        .await
+        .unwrap();
+    assert_eq!(first["deleted"], json!(true));
+    assert_eq!(second["deleted"], json!(false));
+}
+
+#[tokio::test]
+async fn empty_key_is_invalid_input() {
+    let (tools, _) = tools();
+    let err = call_in(&tools, "c", "recall", json!({ "key": "" }))
+        .await
+        .unwrap_err();
+    assert_eq!(err.classification(), ErrorClass::InvalidInput);
+}
+
+#[test]
+fn declarations_describe_every_parameter() {
+    let (tools, _) = tools();
+    let decls = tools.json().unwrap();
+    let mut names: Vec<&str> = decls
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| d["name"].as_str().unwrap())
+        .collect();
+    names.sort();
+    assert_eq!(names, ["forget", "list_memories", "recall", "remember"]);
+
+    for decl in decls.as_array().unwrap() {
+        assert!(!decl["description"].as_str().unwrap().is_empty());
+        for param in decl["parameters"]["properties"]
+            .as_object()
+            .unwrap()
+            .values()
+        {
+            assert!(param["description"].is_string(), "{decl}");
+        }
+    }
+}
@@ -0,0 +1,166 @@
+//! OpenAPI import against the Swagger Petstore (OpenAPI 3.0) spec.
+//!
+//! `tests/fixtures/openapi/petstore.expected.json` is the golden output of
+//! `openapi::import`. Regenerate it with `UPDATE_GOLDEN=1 cargo test
+//! --features openapi --test openapi_import` after an intentional change.
+
+#![cfg(feature = "openapi")]
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use serde::Deserialize;
+use serde_json::{Value, json};
+use tools_core::openapi::{self, Auth, HttpRequest, HttpSend, Method};
+use tools_rs::{FunctionCall, ToolCollection};
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/openapi")
+        .join(name)
+}
+
+fn petstore() -> Value {
+    serde_json::from_str(&fs::read_to_string(fixture("petstore.json")).unwrap()).unwrap()
+}
+
+const BASE: &str = "https://petstore3.swagger.io/api/v3";
+
+/// Echoes the request back instead of sending it.
+fn echo() -> Arc<HttpSend> {
+    Arc::new(|req: HttpRequest| {
+        Box::pin(async move {
+            Ok(json!({
+                "method": req.method,
+                "url": req.url,
+                "headers": req.headers,
+                "body": req.body,
+            }))
+        })
+    })
+}
+
+// ---------- golden ----------
+
+#[test]
+fn petstore_matches_golden() {
+    let specs = openapi::import(&petstore(), BASE, Auth::None).unwrap();
+    let actual = serde_json::to_value(&specs).unwrap();
+
+    let golden = fixture("petstore.expected.json");
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(
+            &golden,
+            serde_json::to_string_pretty(&actual).unwrap() + "\n",
+        )
+        .unwrap();
+    }
+    let expected: Value = serde_json::from_str(&fs::read_to_string(golden).unwrap()).unwrap();
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn petstore_operations_and_bodies() {
+    let specs = openapi::import(&petstore(), BASE, Auth::None).unwrap();
+    let names: Vec<&str> = specs.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(specs.len(), 19);
+    assert!(names.contains(&"getPetById"));
+
+    let find = |name: &str| specs.iter().find(|s| s.name == name).unwrap();
+
+    // JSON is picked out of json/xml/form content types.
+    let add = find("addPet");
+    assert_eq!(
+        add.request.body.as_ref().unwrap().content_type,
+        "application/json"
+    );
+    assert_eq!(add.parameters["required"], json!(["body"]));
+    assert_eq!(
+        add.parameters["properties"]["body"]["properties"]["category"]["properties"]["name"]["type"],
+        "string"
+    );
+
+    // Optional octet-stream body: imported without a body.
+    let upload = find("uploadFile");
+    assert!(upload.request.body.is_none());
+    assert_eq!(upload.parameters["required"], json!(["petId"]));
+
+    // Optional query and header parameters stay optional.
+    let delete = find("deletePet");
+    assert_eq!(delete.parameters["required"], json!(["petId"]));
+    assert_eq!(delete.request.method, Method::Delete);
+}
+
+// ---------- dispatch ----------
+
+#[tokio::test]
+async fn imported_tools_build_requests() {
+    let specs = openapi::import(
+        &petstore(),
+        BASE,
+        Auth::Header {
+            name: "api_key".into(),
+            value: "special-key".into(),
+        },
+    )
+    .unwrap();
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools.register_openapi(specs, echo()).unwrap();
+
+    let resp = tools
+        .call(FunctionCall::new(
+            "findPetsByTags".into(),
+            json!({ "tags": ["tag1", "tag 2"] }),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.result["method"], "GET");
+    assert_eq!(
+        resp.result["url"],
+        format!("{BASE}/pet/findByTags?tags=tag1&tags=tag%202")
+    );
+    assert_eq!(resp.result["headers"], json!([["api_key", "special-key"]]));
+
+    let resp = tools
+        .call(FunctionCall::new(
+            "updateUser".into(),
+            json!({ "username": "theUser", "body": { "email": "a@b.c" } }),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.result["method"], "PUT");
+    assert_eq!(resp.result["url"], format!("{BASE}/user/theUser"));
+    assert_eq!(resp.result["body"], json!({ "email": "a@b.c" }));
+
+    let err = tools
+        .call(FunctionCall::new("getPetById".into(), json!({})))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("petId"));
+}
+
+#[derive(Debug, Deserialize)]
+struct HttpMeta {
+    method: String,
+}
+
+#[test]
+fn typed_metadata_sees_method_and_path() {
+    let specs = openapi::import(&petstore(), BASE, Auth::None).unwrap();
+    let mut tools = ToolCollection::<HttpMeta>::new();
+    tools.register_openapi(specs, echo()).unwrap();
+
+    let destructive: Vec<&str> = {
+        let mut v: Vec<&str> = tools
+            .iter()
+            .filter(|(_, e)| e.meta.method == "DELETE")
+            .map(|(n, _)| n)
+            .collect();
+        v.sort();
+        v
+    };
+    assert_eq!(destructive, ["deleteOrder", "deletePet", "deleteUser"]);
+}
@@ -0,0 +1,191 @@
+//! Manifest-declared WASM tools run on wasmtime: the sample `to_upper`
+//! guest, and small guests that trap, spin or grow past their limits.
+
+#![cfg(feature = "wasm-host")]
+
+use std::time::{Duration, Instant};
+
+use serde_json::json;
+use tools_core::wasm::{WasmLimits, WasmManifest};
+use tools_rs::{FunctionCall, ToolCollection, ToolError};
+
+const MANIFEST: &str = include_str!("../../test_support/wasm_to_upper/manifest.json");
+const TO_UPPER: &str = include_str!("../../test_support/wasm_to_upper/to_upper.wat");
+
+/// A guest exporting the expected functions, whose `call` runs `body` and
+/// returns `result`, a pointer into memory.
+fn guest(memory_pages: u32, body: &str, result: &str) -> String {
+    format!(
+        r#"(module
+             (memory (export "memory") {memory_pages})
+             (data (i32.const 16) "\08\00\00\00not json")
+             (func (export "alloc") (param i32) (result i32) (i32.const 1024))
+             (func (export "call") (param i32 i32) (result i32) {body} {result}))"#
+    )
+}
+
+fn register(module: &str, limits: WasmLimits) -> ToolCollection {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register_wasm(WasmManifest::from_json(MANIFEST).unwrap(), module, limits)
+        .unwrap();
+    tools
+}
+
+async fn call(tools: &ToolCollection, text: &str) -> Result<serde_json::Value, ToolError> {
+    tools
+        .call(FunctionCall::new(
+            "to_upper".into(),
+            json!({ "text": text }),
+        ))
+        .await
+        .map(|r| r.result)
+}
+
+fn runtime_message(err: ToolError) -> String {
+    match err {
+        ToolError::Runtime(msg) => msg,
+        other => panic!("expected Runtime, got {other:?}"),
+    }
+}
+
+// ---------- calls ----------
+
+#[tokio::test]
+async fn calls_the_guest_with_json_arguments() {
+    let tools = register(TO_UPPER, WasmLimits::default());
+    assert_eq!(call(&tools, "hello").await.unwrap(), json!("HELLO"));
+    assert_eq!(
+        call(&tools, "line\n\"quoted\" é").await.unwrap(),
+        json!("LINE\n\"QUOTED\" é")
+    );
+}
+
+#[tokio::test]
+async fn each_call_gets_a_fresh_instance() {
+    // The guest's allocator never frees; a shared instance would
+    // outgrow its one-page memory cap on the second call.
+    let tools = register(
+        TO_UPPER,
+        WasmLimits {
+            max_memory_bytes: 64 * 1024,
+            ..WasmLimits::default()
+        },
+    );
+    let text = "x".repeat(20_000);
+    for _ in 0..5 {
+        assert_eq!(
+            call(&tools, &text).await.unwrap(),
+            json!(text.to_uppercase())
+        );
+    }
+}
+
+#[test]
+fn declaration_comes_from_the_manifest() {
+    let tools = register(TO_UPPER, WasmLimits::default());
+    let decl = &tools.json().unwrap()[0];
+    assert_eq!(decl["description"], "Uppercase a string.");
+    assert_eq!(
+        decl["parameters"]["properties"]["text"]["description"],
+        "Text to convert."
+    );
+    assert_eq!(decl["returns"], json!({ "type": "string" }));
+    assert_eq!(decl["tags"], json!(["text"]));
+}
+
+#[test]
+fn malformed_manifest_is_rejected() {
+    let err = WasmManifest::from_json(r#"{ "name": "x" }"#).unwrap_err();
+    assert!(runtime_message(err).contains("manifest"));
+}
+
+#[test]
+fn invalid_modules_are_rejected_at_registration() {
+    let mut tools: ToolCollection = ToolCollection::new();
+    let err = tools
+        .register_wasm(
+            WasmManifest::from_json(MANIFEST).unwrap(),
+            b"\0asm garbage",
+            WasmLimits::default(),
+        )
+        .unwrap_err();
+    assert!(runtime_message(err).starts_with("WASM tool `to_upper` module failed to load"));
+    assert!(tools.get("to_upper").is_none());
+}
+
+// ---------- failures ----------
+
+#[tokio::test]
+async fn traps_and_bad_output_become_runtime_errors() {
+    let trap = register(&guest(1, "unreachable", ""), WasmLimits::default());
+    let msg = runtime_message(call(&trap, "hi").await.unwrap_err());
+    assert!(msg.starts_with("WASM tool `to_upper` trapped:"), "{msg}");
+
+    let garbage = register(&guest(1, "", "(i32.const 16)"), WasmLimits::default());
+    let msg = runtime_message(call(&garbage, "hi").await.unwrap_err());
+    assert!(msg.contains("invalid JSON"), "{msg}");
+
+    let imports = r#"(module (import "env" "f" (func)) (memory (export "memory") 1))"#;
+    let msg = runtime_message(
+        call(&register(imports, WasmLimits::default()), "hi")
+            .await
+            .unwrap_err(),
+    );
+    assert!(msg.contains("module failed to load"), "{msg}");
+}
+
+#[tokio::test]
+async fn fuel_runs_out_in_an_endless_loop() {
+    let spin = guest(1, "(loop $spin (br $spin))", "(i32.const 0)");
+    let limits = WasmLimits {
+        fuel: 10_000,
+        ..WasmLimits::default()
+    };
+    let msg = runtime_message(call(&register(&spin, limits), "hi").await.unwrap_err());
+    assert_eq!(msg, "WASM tool `to_upper` ran out of fuel");
+
+    // The sample guest fits in the same budget.
+    assert_eq!(
+        call(&register(TO_UPPER, limits), "hi").await.unwrap(),
+        json!("HI")
+    );
+}
+
+#[tokio::test]
+async fn memory_is_capped() {
+    // 1000 pages is 64 MiB, past the default 16 MiB.
+    let grow = guest(1, "(drop (memory.grow (i32.const 1000)))", "(i32.const 0)");
+    let msg = runtime_message(
+        call(&register(&grow, WasmLimits::default()), "hi")
+            .await
+            .unwrap_err(),
+    );
+    assert_eq!(msg, "WASM tool `to_upper` exceeded its memory limit");
+
+    // So is the memory a module starts with.
+    let large = guest(300, "", "(i32.const 0)");
+    let msg = runtime_message(
+        call(&register(&large, WasmLimits::default()), "hi")
+            .await
+            .unwrap_err(),
+    );
+    assert_eq!(msg, "WASM tool `to_upper` exceeded its memory limit");
+}
+
+#[tokio::test]
+async fn wall_clock_limit_interrupts_the_guest() {
+    let spin = guest(1, "(loop $spin (br $spin))", "(i32.const 0)");
+    let limits = WasmLimits {
+        fuel: u64::MAX,
+        timeout: Duration::from_millis(50),
+        ..WasmLimits::default()
+    };
+    let tools = register(&spin, limits);
+    let started = Instant::now();
+    // The call only returns once the guest has stopped and its thread
+    // is joined.
+    let msg = runtime_message(call(&tools, "hi").await.unwrap_err());
+    assert_eq!(msg, "WASM tool `to_upper` timed out after 50ms");
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
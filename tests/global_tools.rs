@@ -0,0 +1,44 @@
+//! `call_tool` and friends share one collection instead of collecting the
+//! inventory on every call.
+//!
+//! The inventory counter is process-wide, so this file holds a single test.
+
+use serde_json::json;
+use tools_rs::{
+    call_tool, call_tool_typed, collect_tools, function_declarations, global_tools,
+    inventory_collections, tool,
+};
+
+#[tool]
+/// Adds two integers.
+async fn add(a: i64, b: i64) -> i64 {
+    a + b
+}
+
+#[tokio::test]
+async fn repeated_calls_collect_the_inventory_once() {
+    const CALLS: u32 = 200;
+    call_tool("add", json!({ "a": 0, "b": 0 })).await.unwrap();
+    let collected = inventory_collections();
+
+    for i in 0..CALLS {
+        let out = call_tool("add", json!({ "a": i, "b": 1 })).await.unwrap();
+        assert_eq!(out.result, json!(i + 1));
+    }
+    let sum: i64 = call_tool_typed("add", json!({ "a": 2, "b": 3 }))
+        .await
+        .unwrap();
+    assert_eq!(sum, 5);
+    function_declarations().unwrap();
+    assert_eq!(inventory_collections(), collected);
+    assert!(std::ptr::eq(global_tools(), global_tools()));
+
+    // Each collection of your own walks the inventory again.
+    for i in 0..CALLS {
+        let tools = collect_tools();
+        tools_rs::call_tool_by_name(&tools, "add", json!({ "a": i, "b": 1 }))
+            .await
+            .unwrap();
+    }
+    assert_eq!(inventory_collections(), collected + u64::from(CALLS));
+}
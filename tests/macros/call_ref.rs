@@ -0,0 +1,64 @@
+//! `call_ref` behaves like `call` while leaving the `FunctionCall` with
+//! the caller.
+
+use serde_json::json;
+use tools_rs::{FunctionCall, ToolCollection, ToolError, ToolSpec, test_tools, tool};
+
+#[tool]
+/// Adds two numbers.
+async fn sum(a: i64, b: i64) -> i64 {
+    a + b
+}
+
+fn tools() -> ToolCollection {
+    let mut tools = test_tools![sum];
+    tools
+        .register("square", "Squares.", |n: i64| async move { n * n }, ())
+        .unwrap();
+    tools
+        .register_spec(
+            ToolSpec::new("echo", "Echoes.").handler(|args| Box::pin(async move { Ok(args) })),
+        )
+        .unwrap();
+    tools
+}
+
+#[tokio::test]
+async fn matches_call_for_every_kind_of_tool() {
+    let tools = tools();
+    let calls = [
+        FunctionCall::new("sum".into(), json!({ "a": 2, "b": 3 })),
+        FunctionCall::new("square".into(), json!(4)),
+        FunctionCall::new("echo".into(), json!({ "big": [1, 2, 3] })),
+    ];
+    for call in &calls {
+        let by_ref = tools.call_ref(call).await.unwrap();
+        let by_value = tools.call(call.clone()).await.unwrap();
+        assert_eq!(by_ref.result, by_value.result);
+        assert_eq!(by_ref.id, call.id);
+        assert_eq!(by_ref.name, call.name);
+    }
+    // The caller still has its calls.
+    assert_eq!(calls[2].arguments, json!({ "big": [1, 2, 3] }));
+}
+
+#[tokio::test]
+async fn rejects_the_same_arguments() {
+    let tools = tools();
+    for call in [
+        FunctionCall::new("sum".into(), json!({ "a": "two", "b": 3 })),
+        FunctionCall::new("sum".into(), json!({ "a": 2 })),
+        FunctionCall::new("square".into(), json!(null)),
+    ] {
+        let by_ref = tools.call_ref(&call).await.unwrap_err();
+        let by_value = tools.call(call.clone()).await.unwrap_err();
+        assert!(matches!(by_ref, ToolError::Deserialize(_)), "{by_ref}");
+        assert_eq!(by_ref.to_string(), by_value.to_string());
+    }
+
+    let missing = FunctionCall::new("nope".into(), json!({}));
+    assert!(matches!(
+        tools.call_ref(&missing).await.unwrap_err(),
+        ToolError::FunctionNotFound { name } if name == "nope"
+    ));
+}
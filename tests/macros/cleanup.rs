@@ -0,0 +1,215 @@
+//! Cleanup hooks run exactly once per tool, across `unregister`, `clear`,
+//! `shutdown` and clones.
+
+use std::{future::Future, pin::Pin, sync::Arc};
+
+use serde_json::{Value, json};
+use tools_rs::{FunctionCall, ToolCollection, ToolSpec, test_tools, tool};
+
+type BoxFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// Records the names of tools whose cleanup ran.
+#[derive(Clone, Default)]
+struct Counter(Arc<std::sync::Mutex<Vec<&'static str>>>);
+
+impl Counter {
+    fn hook(&self, name: &'static str) -> impl FnOnce() -> BoxFuture + Send + 'static {
+        let log = self.0.clone();
+        move || {
+            Box::pin(async move {
+                tokio::task::yield_now().await;
+                log.lock().unwrap().push(name);
+            })
+        }
+    }
+
+    fn runs(&self, name: &str) -> usize {
+        self.0
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|n| **n == name)
+            .count()
+    }
+
+    fn order(&self) -> Vec<&'static str> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+fn collection(counter: &Counter, names: &[&'static str]) -> ToolCollection {
+    let mut tools: ToolCollection = ToolCollection::new();
+    for &name in names {
+        tools
+            .register_with_cleanup(
+                name,
+                "Echoes.",
+                |x: i32| async move { x },
+                (),
+                counter.hook(name),
+            )
+            .unwrap();
+    }
+    tools
+}
+
+// ---------- unregister ----------
+
+#[tokio::test]
+async fn unregister_then_shutdown_runs_once() {
+    let counter = Counter::default();
+    let mut tools = collection(&counter, &["a", "b"]);
+
+    tools.unregister("a").unwrap();
+    tools.shutdown().await;
+    tools.shutdown().await;
+
+    assert_eq!(counter.runs("a"), 1);
+    assert_eq!(counter.runs("b"), 1);
+    assert!(tools.is_empty());
+}
+
+#[tokio::test]
+async fn unregister_starts_cleanup_on_the_runtime() {
+    let counter = Counter::default();
+    let mut tools = collection(&counter, &["a"]);
+    let cleanup = tools.get("a").unwrap().cleanup.clone().unwrap();
+
+    tools.unregister("a").unwrap();
+    assert!(cleanup.has_run());
+    for _ in 0..10 {
+        tokio::task::yield_now().await;
+    }
+    assert_eq!(counter.runs("a"), 1);
+}
+
+#[test]
+fn outside_a_runtime_cleanups_wait_for_shutdown() {
+    let counter = Counter::default();
+    let mut tools = collection(&counter, &["a", "b", "c"]);
+
+    tools.unregister("b").unwrap();
+    tools.unregister("a").unwrap();
+    assert_eq!(counter.runs("a"), 0);
+
+    tokio::runtime::Runtime::new()
+        .unwrap()
+        .block_on(tools.shutdown());
+    // Queued cleanups run in removal order. `c` was spawned by `shutdown`
+    // itself and may interleave with them.
+    let order = counter.order();
+    assert_eq!(order.len(), 3);
+    let pos = |name| order.iter().position(|n| *n == name).unwrap();
+    assert!(pos("b") < pos("a"));
+}
+
+// ---------- clear ----------
+
+#[tokio::test]
+async fn clear_runs_every_cleanup() {
+    let counter = Counter::default();
+    let mut tools = collection(&counter, &["a", "b"]);
+
+    tools.clear();
+    assert!(tools.is_empty());
+    assert!(tools.resolve("a").is_none());
+    tools.shutdown().await;
+    assert_eq!(counter.order().len(), 2);
+}
+
+/// Has no cleanup.
+#[tool]
+async fn heartbeat() -> String {
+    "alive".into()
+}
+
+#[tokio::test]
+async fn spec_cleanup_and_plain_tools_mix() {
+    let counter = Counter::default();
+    let mut tools = test_tools![heartbeat];
+    tools
+        .register_spec(
+            ToolSpec::new("session", "Holds a session.")
+                .handler(|_| Box::pin(async { Ok(Value::Null) }))
+                .cleanup(counter.hook("session")),
+        )
+        .unwrap();
+
+    let out = tools
+        .call(FunctionCall::new("heartbeat".into(), json!({})))
+        .await
+        .unwrap();
+    assert_eq!(out.result, json!("alive"));
+
+    tools.shutdown().await;
+    assert_eq!(counter.order(), ["session"]);
+}
+
+// ---------- clones ----------
+
+#[tokio::test]
+async fn clones_share_a_single_cleanup() {
+    let counter = Counter::default();
+    let mut tools = collection(&counter, &["a"]);
+    let mut clone = tools.clone();
+
+    clone.unregister("a").unwrap();
+    tools.unregister("a").unwrap();
+    clone.shutdown().await;
+    tools.shutdown().await;
+
+    assert_eq!(counter.runs("a"), 1);
+}
+
+// ---------- running calls ----------
+
+#[tokio::test]
+async fn cleanup_waits_for_running_calls() {
+    let log = Counter::default();
+    let gate = Arc::new(tokio::sync::Semaphore::new(0));
+    let started = Arc::new(tokio::sync::Semaphore::new(0));
+    let mut tools: ToolCollection = ToolCollection::new();
+    let hook = log.hook("cleanup");
+    {
+        let (log, gate, started) = (log.clone(), gate.clone(), started.clone());
+        tools
+            .register_with_cleanup(
+                "slow",
+                "Waits for the test.",
+                move |x: i32| {
+                    let (log, gate, started) = (log.clone(), gate.clone(), started.clone());
+                    async move {
+                        started.add_permits(1);
+                        gate.acquire().await.unwrap().forget();
+                        log.0.lock().unwrap().push("returned");
+                        x
+                    }
+                },
+                (),
+                hook,
+            )
+            .unwrap();
+    }
+    let cleanup = tools.get("slow").unwrap().cleanup.clone().unwrap();
+
+    let worker = tools.clone();
+    let running = tokio::spawn(async move {
+        worker
+            .call(FunctionCall::new("slow".into(), json!(7)))
+            .await
+    });
+    started.acquire().await.unwrap().forget();
+
+    // Removed while the call is still running on it.
+    tools.unregister("slow").unwrap();
+    for _ in 0..10 {
+        tokio::task::yield_now().await;
+    }
+    assert!(!cleanup.has_run());
+    assert!(log.order().is_empty());
+
+    gate.add_permits(1);
+    assert_eq!(running.await.unwrap().unwrap().result, json!(7));
+    tools.shutdown().await;
+    assert_eq!(log.order(), ["returned", "cleanup"]);
+}
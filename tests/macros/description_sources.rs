@@ -0,0 +1,77 @@
+//! Descriptions taken from a file, a constant or the attribute instead of
+//! the doc comment, and names taken from the attribute.
+
+use serde_json::json;
+use tools_rs::{FunctionCall, ToolCollection, tool};
+
+const LOOKUP_DESC: &str = "Look up a user by id.";
+
+/// Ignored: `description_from` takes precedence.
+#[tool(description_from = "tests/fixtures/prompts/search.txt")]
+async fn search_articles(query: String, limit: u32) -> Vec<String> {
+    vec![query; limit as usize]
+}
+
+#[tool(description_const = LOOKUP_DESC, audited)]
+async fn lookup_user(id: u64) -> u64 {
+    id
+}
+
+/// Searches the web for pages matching `query`, following the ranking
+/// rules of the legacy search backend.
+#[tool(name = "search-web.v2", description = "Searches the web", legacy)]
+async fn search_web_v2(query: String) -> String {
+    query
+}
+
+fn tools() -> ToolCollection {
+    ToolCollection::collect_tools_filtered(|name| {
+        matches!(name, "search_articles" | "lookup_user" | "search-web.v2")
+    })
+    .unwrap()
+}
+
+fn description(name: &str) -> String {
+    tools().get(name).unwrap().decl.description.to_string()
+}
+
+#[test]
+fn description_from_reads_the_file() {
+    assert_eq!(
+        description("search_articles"),
+        "Search the knowledge base for articles matching a query.\n\
+         Return at most `limit` results, best match first."
+    );
+}
+
+#[test]
+fn description_const_uses_the_constant() {
+    assert_eq!(description("lookup_user"), LOOKUP_DESC);
+    // The declaration sent to models carries it too.
+    let tools = tools();
+    let json = tools.json().unwrap();
+    let lookup = json
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|d| d["name"] == "lookup_user")
+        .unwrap();
+    assert_eq!(lookup["description"], LOOKUP_DESC);
+}
+
+#[tokio::test]
+async fn name_and_description_override_the_function() {
+    let tools = tools();
+    assert!(tools.get("search_web_v2").is_none());
+    assert_eq!(description("search-web.v2"), "Searches the web");
+
+    let out = tools
+        .call(FunctionCall::new(
+            "search-web.v2".into(),
+            json!({ "query": "rust" }),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(out.result, json!("rust"));
+    assert_eq!(SEARCH_WEB_V2_TOOL.name(), "search-web.v2");
+}
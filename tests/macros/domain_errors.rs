@@ -0,0 +1,185 @@
+//! Tools returning `Result` report `Err` to the model as a domain error:
+//! an `{"error": ..}` result with `is_error` set, not a failed call.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tools_rs::{
+    CircuitConfig, FunctionCall, ToolCollection, ToolError, ToolSchema, ToolSpec,
+    circuit::CircuitState, test_tools, tool,
+};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, ToolSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum OrderError {
+    OutOfStock,
+    PaymentDeclined { reason: String },
+}
+
+/// Places an order.
+#[tool]
+async fn place_order(sku: String, card: String) -> Result<String, OrderError> {
+    match (sku.as_str(), card.as_str()) {
+        ("sold-out", _) => Err(OrderError::OutOfStock),
+        (_, "expired") => Err(OrderError::PaymentDeclined {
+            reason: "card expired".into(),
+        }),
+        _ => Ok(format!("order for {sku}")),
+    }
+}
+
+fn order(sku: &str, card: &str) -> FunctionCall {
+    FunctionCall::new("place_order".into(), json!({ "sku": sku, "card": card }))
+}
+
+// ---------- #[tool] ----------
+
+#[tokio::test]
+async fn ok_values_are_sent_unwrapped() {
+    let tools = test_tools![place_order];
+    let response = tools.call(order("mug", "visa")).await.unwrap();
+    assert_eq!(response.result, "order for mug");
+    assert!(!response.is_error);
+    assert!(
+        serde_json::to_value(&response)
+            .unwrap()
+            .get("is_error")
+            .is_none()
+    );
+}
+
+#[tokio::test]
+async fn errors_are_enveloped() {
+    let tools = test_tools![place_order];
+    let response = tools.call(order("sold-out", "visa")).await.unwrap();
+    assert_eq!(
+        response.result,
+        json!({ "error": { "kind": "out_of_stock" } })
+    );
+    assert!(response.is_error);
+
+    let response = tools.call(order("mug", "expired")).await.unwrap();
+    assert_eq!(
+        serde_json::to_value(&response).unwrap()["result"],
+        json!({ "error": { "kind": "payment_declined", "reason": "card expired" } })
+    );
+    assert_eq!(serde_json::to_value(&response).unwrap()["is_error"], true);
+}
+
+#[test]
+fn declaration_includes_both_arms() {
+    let tools = test_tools![place_order];
+    let returns = tools
+        .get("place_order")
+        .unwrap()
+        .decl
+        .returns
+        .clone()
+        .unwrap();
+    assert_eq!(returns["oneOf"][0], json!({ "type": "string" }));
+    assert_eq!(returns["oneOf"][1]["required"], json!(["error"]));
+    assert_eq!(
+        returns["oneOf"][1]["properties"]["error"],
+        OrderError::schema()
+    );
+}
+
+#[tokio::test]
+async fn invoke_decodes_the_result() {
+    let tools = test_tools![place_order];
+    assert_eq!(
+        tools
+            .invoke(&PLACE_ORDER_TOOL, ("mug".to_string(), "visa".to_string()))
+            .await
+            .unwrap(),
+        Ok("order for mug".to_string())
+    );
+    assert_eq!(
+        tools
+            .invoke(
+                &PLACE_ORDER_TOOL,
+                ("sold-out".to_string(), "visa".to_string())
+            )
+            .await
+            .unwrap(),
+        Err(OrderError::OutOfStock)
+    );
+}
+
+// ---------- register ----------
+
+#[tokio::test]
+async fn registered_closures_report_domain_errors() {
+    let mut tools: ToolCollection = ToolCollection::new();
+    let stock = tools
+        .register(
+            "stock",
+            "Units in stock.",
+            |sku: String| async move {
+                match sku.as_str() {
+                    "mug" => Ok(3u32),
+                    _ => Err(OrderError::OutOfStock),
+                }
+            },
+            (),
+        )
+        .unwrap();
+    let returns = tools.get("stock").unwrap().decl.returns.clone();
+    assert!(returns.is_some_and(|r| r["oneOf"].is_array()));
+
+    let response = tools
+        .call(FunctionCall::new("stock".into(), json!("teapot")))
+        .await
+        .unwrap();
+    assert!(response.is_error);
+    assert_eq!(
+        response.result,
+        json!({ "error": { "kind": "out_of_stock" } })
+    );
+    assert_eq!(tools.invoke(&stock, "mug").await.unwrap(), Ok(3));
+}
+
+#[tokio::test]
+async fn raw_handlers_use_tool_error_domain() {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register_spec(
+            ToolSpec::new("refund", "Refunds an order.")
+                .handler(|_| Box::pin(async { Err(ToolError::domain(OrderError::OutOfStock)) })),
+        )
+        .unwrap();
+    let response = tools
+        .call(FunctionCall::new("refund".into(), json!({})))
+        .await
+        .unwrap();
+    assert!(response.is_error);
+    assert_eq!(response.result["error"]["kind"], "out_of_stock");
+}
+
+// ---------- not failures ----------
+
+#[tokio::test]
+async fn domain_errors_are_successful_calls() {
+    let mut tools = test_tools![place_order];
+    let config = CircuitConfig {
+        failure_threshold: 1,
+        cooldown: Duration::from_secs(60),
+    };
+    tools.set_circuit_breaker("place_order", config).unwrap();
+    for _ in 0..3 {
+        assert!(
+            tools
+                .call(order("sold-out", "visa"))
+                .await
+                .unwrap()
+                .is_error
+        );
+    }
+    assert_eq!(
+        tools.circuit("place_order").unwrap().state,
+        CircuitState::Closed
+    );
+    let recent = tools.recent_calls(usize::MAX);
+    assert!(recent.iter().all(|call| call.error.is_none()));
+}
@@ -0,0 +1,96 @@
+//! `tool_error!` and `ensure_tool!`: early returns with a
+//! `ToolError::Runtime`.
+
+use serde_json::json;
+use tools_rs::{FunctionCall, ToolError, ensure_tool, test_tools, tool, tool_error};
+
+/// Multiplies the numbers from 1 to `n`.
+#[tool]
+async fn checked_factorial(n: u64) -> Result<u64, ToolError> {
+    match (1..=n).try_fold(1u64, u64::checked_mul) {
+        Some(f) => Ok(f),
+        None => tool_error!("factorial overflows u64 for n={n}"),
+    }
+}
+
+/// Picks the `index`th letter of the alphabet.
+#[tool]
+async fn letter(index: usize) -> Result<String, ToolError> {
+    ensure_tool!(
+        index < 26,
+        "index {index} is out of range, expected 0 to 25"
+    );
+    Ok(((b'a' + index as u8) as char).to_string())
+}
+
+fn runtime_message(err: ToolError) -> String {
+    match err {
+        ToolError::Runtime(message) => message,
+        other => panic!("expected a runtime error, got {other:?}"),
+    }
+}
+
+// ---------- tool_error! ----------
+
+#[tokio::test]
+async fn tool_error_fails_the_call_with_the_formatted_message() {
+    let tools = test_tools![checked_factorial, letter];
+    let call = |n: u64| FunctionCall::new("checked_factorial".into(), json!({ "n": n }));
+
+    assert_eq!(
+        tools.call(call(20)).await.unwrap().result,
+        json!(2_432_902_008_176_640_000u64)
+    );
+    let err = tools.call(call(21)).await.unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Runtime error: factorial overflows u64 for n=21"
+    );
+    assert_eq!(runtime_message(err), "factorial overflows u64 for n=21");
+}
+
+#[test]
+fn tool_error_converts_into_the_callers_error_type() {
+    #[derive(Debug)]
+    struct Wrapped(ToolError);
+    impl From<ToolError> for Wrapped {
+        fn from(err: ToolError) -> Self {
+            Self(err)
+        }
+    }
+    fn fail(code: u32) -> Result<(), Wrapped> {
+        tool_error!("failed with code {}", code)
+    }
+
+    let Wrapped(err) = fail(7).unwrap_err();
+    assert_eq!(runtime_message(err), "failed with code 7");
+}
+
+// ---------- ensure_tool! ----------
+
+#[tokio::test]
+async fn ensure_tool_returns_early_when_the_condition_fails() {
+    let tools = test_tools![checked_factorial, letter];
+    let call = |index: usize| FunctionCall::new("letter".into(), json!({ "index": index }));
+
+    assert_eq!(tools.call(call(2)).await.unwrap().result, json!("c"));
+    let err = tools.call(call(26)).await.unwrap_err();
+    assert_eq!(
+        runtime_message(err),
+        "index 26 is out of range, expected 0 to 25"
+    );
+}
+
+#[test]
+fn ensure_tool_without_a_message_names_the_condition() {
+    fn positive(x: i64) -> Result<i64, ToolError> {
+        ensure_tool!(x > 0);
+        Ok(x)
+    }
+
+    assert_eq!(positive(3).unwrap(), 3);
+    assert_eq!(
+        runtime_message(positive(-1).unwrap_err()),
+        "condition failed: `x > 0`"
+    );
+}
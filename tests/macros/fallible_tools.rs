@@ -0,0 +1,123 @@
+//! Tools whose `Err` fails the call: `register_fallible` and `#[tool]`
+//! functions returning `Result<T, ToolError>`.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tools_rs::{FunctionCall, ToolCollection, ToolError, ToolSchema, test_tools, tool};
+
+#[derive(Serialize, Deserialize, ToolSchema)]
+struct Division {
+    dividend: f64,
+    divisor: f64,
+}
+
+fn divide(dividend: f64, divisor: f64) -> FunctionCall {
+    FunctionCall::new(
+        "divide".into(),
+        json!({ "dividend": dividend, "divisor": divisor }),
+    )
+}
+
+fn registered() -> ToolCollection {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register_fallible(
+            "divide",
+            "Divides two numbers.",
+            |d: Division| async move {
+                if d.divisor == 0.0 {
+                    return Err("division by zero".to_string());
+                }
+                Ok(d.dividend / d.divisor)
+            },
+            (),
+        )
+        .unwrap();
+    tools
+}
+
+// ---------- register_fallible ----------
+
+#[tokio::test]
+async fn err_fails_the_call() {
+    let tools = registered();
+    let err = tools.call(divide(1.0, 0.0)).await.unwrap_err();
+    assert!(matches!(&err, ToolError::Runtime(message) if message == "division by zero"));
+
+    let response = tools.call(divide(6.0, 4.0)).await.unwrap();
+    assert_eq!(response.result, json!(1.5));
+    assert!(!response.is_error);
+}
+
+#[test]
+fn returns_describes_the_ok_type() {
+    let tools = registered();
+    let returns = tools.get("divide").unwrap().decl.returns.clone();
+    assert_eq!(returns, Some(json!({ "type": "number" })));
+}
+
+#[tokio::test]
+async fn invoke_decodes_the_ok_type() {
+    let mut tools: ToolCollection = ToolCollection::new();
+    let divide = tools
+        .register_fallible(
+            "divide",
+            "Divides two numbers.",
+            |d: Division| async move {
+                if d.divisor == 0.0 {
+                    return Err(ToolError::Runtime("division by zero".into()));
+                }
+                Ok(d.dividend / d.divisor)
+            },
+            (),
+        )
+        .unwrap();
+    let quotient: f64 = tools
+        .invoke(
+            &divide,
+            Division {
+                dividend: 1.0,
+                divisor: 4.0,
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(quotient, 0.25);
+    let err = tools
+        .invoke(
+            &divide,
+            Division {
+                dividend: 1.0,
+                divisor: 0.0,
+            },
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ToolError::Runtime(_)));
+}
+
+// ---------- #[tool] ----------
+
+/// Takes the square root of a non-negative number.
+#[tool]
+async fn square_root(x: f64) -> Result<f64, ToolError> {
+    if x < 0.0 {
+        return Err("negative input".into());
+    }
+    Ok(x.sqrt())
+}
+
+#[tokio::test]
+async fn tool_functions_returning_tool_error_fail_the_call() {
+    let tools = test_tools![square_root];
+    let call = |x: f64| FunctionCall::new("square_root".into(), json!({ "x": x }));
+
+    let err = tools.call(call(-1.0)).await.unwrap_err();
+    assert!(matches!(&err, ToolError::Runtime(message) if message == "negative input"));
+    assert_eq!(tools.call(call(9.0)).await.unwrap().result, json!(3.0));
+
+    let returns = tools.get("square_root").unwrap().decl.returns.clone();
+    assert_eq!(returns, Some(json!({ "type": "number" })));
+    let root: f64 = tools.invoke(&SQUARE_ROOT_TOOL, (16.0,)).await.unwrap();
+    assert_eq!(root, 4.0);
+}
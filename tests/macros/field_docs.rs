@@ -0,0 +1,108 @@
+//! Doc comments on fields and `#[tool]` parameters become the
+//! `description` of their property schemas.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tools_rs::{FunctionCall, ToolSchema, test_tools, tool};
+
+#[derive(Serialize, Deserialize, ToolSchema)]
+struct Person {
+    /// Full legal name
+    name: String,
+    /// Age in years.
+    ///
+    /// Rounded down.
+    age: Option<u8>,
+    nickname: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToolSchema)]
+#[serde(tag = "kind")]
+enum Contact {
+    Email {
+        /// Address including the domain.
+        address: String,
+    },
+    Phone {
+        number: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, ToolSchema)]
+struct Pair(
+    /// Left-hand side.
+    i32,
+    i32,
+);
+
+/// Book a table.
+#[tool]
+async fn book_table(
+    /// Guests, including the one booking.
+    party_size: u8,
+    /// Name the table is held under
+    name: String,
+    notes: Option<String>,
+) -> String {
+    format!("{name}:{party_size}:{notes:?}")
+}
+
+// ---------- derived schemas ----------
+
+#[test]
+fn field_docs_describe_properties() {
+    let schema = Person::schema();
+    assert_eq!(
+        schema["properties"]["name"],
+        json!({ "type": "string", "description": "Full legal name" })
+    );
+    assert_eq!(
+        schema["properties"]["age"]["description"],
+        "Age in years.\n\nRounded down."
+    );
+    assert!(
+        schema["properties"]["nickname"]
+            .get("description")
+            .is_none()
+    );
+    // Built once, like every derived schema.
+    assert_eq!(Person::schema(), schema);
+}
+
+#[test]
+fn variant_and_tuple_fields_are_described() {
+    let branch = &Contact::schema()["oneOf"][0];
+    assert_eq!(
+        branch["properties"]["address"]["description"],
+        "Address including the domain."
+    );
+    let pair = Pair::schema();
+    assert_eq!(pair["prefixItems"][0]["description"], "Left-hand side.");
+    assert!(pair["prefixItems"][1].get("description").is_none());
+}
+
+// ---------- #[tool] parameters ----------
+
+#[tokio::test]
+async fn parameter_docs_describe_properties() {
+    let tools = test_tools![book_table];
+    let params = &tools.get("book_table").unwrap().decl.parameters;
+    assert_eq!(
+        params["properties"]["party_size"]["description"],
+        "Guests, including the one booking."
+    );
+    assert_eq!(
+        params["properties"]["name"]["description"],
+        "Name the table is held under"
+    );
+    assert!(params["properties"]["notes"].get("description").is_none());
+
+    let out = tools
+        .call(FunctionCall::new(
+            "book_table".into(),
+            json!({ "party_size": 2, "name": "Ada" }),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(out.result, json!("Ada:2:None"));
+}
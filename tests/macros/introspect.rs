@@ -0,0 +1,94 @@
+//! `ToolCollection::introspect` and the JSON shape dashboards rely on.
+//!
+//! `tests/fixtures/introspect/report.expected.json` is the golden report.
+//! Build-dependent fields (`version`, `features`, `capacity`) are replaced
+//! with placeholders before comparing. Regenerate it with
+//! `UPDATE_GOLDEN=1 cargo test --test introspect` after an intentional
+//! change, and bump `REPORT_FORMAT` if a field was renamed or removed.
+
+use std::{fs, path::PathBuf};
+
+use serde_json::{Value, json};
+use tools_core::introspect::REPORT_FORMAT;
+use tools_rs::{ToolCollection, ToolSpec, tool};
+
+/// Multiplies two numbers.
+#[tool]
+async fn multiply(a: i32, b: i32) -> i32 {
+    a * b
+}
+
+/// Deletes a file.
+#[tool(requires_approval = true)]
+async fn delete_file(path: String) -> String {
+    format!("deleted {path}")
+}
+
+fn collection() -> ToolCollection<Value> {
+    let mut tools =
+        ToolCollection::<Value>::collect_tools_filtered(|n| n == "multiply" || n == "delete_file")
+            .unwrap();
+    tools
+        .register_spec(
+            ToolSpec::new("echo", "Echoes its input.")
+                .params_schema(
+                    json!({ "type": "object", "properties": { "text": { "type": "string" } } }),
+                )
+                .returns_schema(json!({ "type": "object" }))
+                .tags(["debug", "io"])
+                .handler(|args| Box::pin(async move { Ok(args) }))
+                .with_meta(json!({ "owner": "platform" })),
+        )
+        .unwrap();
+    tools
+}
+
+fn normalized(tools: &ToolCollection<Value>) -> Value {
+    let mut report = serde_json::to_value(tools.introspect()).unwrap();
+    report["version"] = json!("<version>");
+    report["features"] = json!("<features>");
+    report["capacity"] = json!("<capacity>");
+    report
+}
+
+// ---------- snapshot ----------
+
+#[test]
+fn report_matches_golden() {
+    let tools = collection();
+    tools.warm_up().unwrap();
+    let actual = normalized(&tools);
+
+    let golden = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/introspect/report.expected.json");
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(
+            &golden,
+            serde_json::to_string_pretty(&actual).unwrap() + "\n",
+        )
+        .unwrap();
+    }
+    let expected: Value = serde_json::from_str(&fs::read_to_string(golden).unwrap()).unwrap();
+    assert_eq!(actual, expected);
+}
+
+// ---------- build-dependent fields ----------
+
+#[test]
+fn version_and_features_reflect_the_build() {
+    let tools = collection();
+    let report = tools.introspect();
+    assert_eq!(report.format, REPORT_FORMAT);
+    assert!(!report.version.is_empty());
+    assert_eq!(
+        report.features.contains(&"openapi"),
+        cfg!(feature = "openapi")
+    );
+    assert!(report.capacity >= report.tool_count);
+}
+
+#[test]
+fn cold_collection_reports_cold_cache() {
+    let tools = collection();
+    assert_eq!(normalized(&tools)["schema_cache_warm"], json!(false));
+}
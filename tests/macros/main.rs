@@ -0,0 +1,23 @@
+//! `#[tool]` functions. Every test in this binary shares one inventory,
+//! so tests collect only their own tools, by name.
+
+#[path = "../common/mod.rs"]
+mod common;
+
+mod call_ref;
+mod cleanup;
+mod description_sources;
+mod domain_errors;
+mod error_macros;
+mod fallible_tools;
+mod field_docs;
+mod introspect;
+mod opaque_schema;
+mod openai_calls;
+mod panics;
+mod patch;
+mod provider_formats;
+mod registration_checks;
+mod resources;
+mod secrets;
+mod tool_ref;
@@ -0,0 +1,112 @@
+//! `#[tool(schema(param = "opaque"))]` and `#[tool(no_schema)]` for
+//! parameter types that do not implement `ToolSchema`.
+
+use serde_json::{Value, json};
+use tools_rs::{FunctionCall, ToolCollection, test_tools, tool};
+
+/// Stands in for a third-party crate: serde support, no `ToolSchema`.
+mod geo {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct Point {
+        pub x: f64,
+        pub y: f64,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct Rect {
+        pub min: Point,
+        pub max: Point,
+    }
+}
+
+#[tool(schema(origin = "opaque"), requires_approval = true)]
+/// Moves a point.
+async fn translate(origin: geo::Point, dx: f64, dy: f64) -> geo::Point {
+    geo::Point {
+        x: origin.x + dx,
+        y: origin.y + dy,
+    }
+}
+
+#[tool(no_schema)]
+/// Area of a rectangle.
+async fn area(rect: geo::Rect, scale: Option<f64>) -> f64 {
+    let s = scale.unwrap_or(1.0);
+    (rect.max.x - rect.min.x) * (rect.max.y - rect.min.y) * s * s
+}
+
+// ---------- declarations ----------
+
+#[test]
+fn opaque_param_is_marked_unvalidated() {
+    let tools = test_tools![translate];
+    let params = &tools.json().unwrap()[0]["parameters"];
+
+    assert_eq!(
+        params["properties"]["origin"],
+        json!({
+            "type": "object",
+            "description": std::any::type_name::<geo::Point>(),
+            "x-unvalidated": true
+        })
+    );
+    // Other parameters keep their real schema.
+    assert_eq!(params["properties"]["dx"], json!({ "type": "number" }));
+    assert_eq!(params["required"], json!(["origin", "dx", "dy"]));
+}
+
+#[test]
+fn schema_attrs_are_not_metadata() {
+    let tools =
+        ToolCollection::<Value>::collect_tools_filtered(|n| n == "translate" || n == "area")
+            .unwrap();
+    assert_eq!(
+        tools.meta("translate").unwrap(),
+        &json!({ "requires_approval": true })
+    );
+    assert_eq!(tools.meta("area").unwrap(), &json!({}));
+}
+
+#[test]
+fn no_schema_covers_every_param() {
+    let tools = test_tools![area];
+    let params = &tools.json().unwrap()[0]["parameters"];
+    for name in ["rect", "scale"] {
+        assert_eq!(params["properties"][name]["x-unvalidated"], json!(true));
+    }
+    // Optional parameters stay optional.
+    assert_eq!(params["required"], json!(["rect"]));
+}
+
+// ---------- execution ----------
+
+#[tokio::test]
+async fn opaque_params_still_deserialize_with_serde() {
+    let tools = test_tools![translate, area];
+
+    let moved = tools
+        .call(FunctionCall::new(
+            "translate".into(),
+            json!({ "origin": { "x": 1.0, "y": 2.0 }, "dx": 0.5, "dy": -1.0 }),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(moved.result, json!({ "x": 1.5, "y": 1.0 }));
+
+    let rect = json!({ "min": { "x": 0.0, "y": 0.0 }, "max": { "x": 2.0, "y": 3.0 } });
+    let out = tools
+        .call(FunctionCall::new("area".into(), json!({ "rect": rect })))
+        .await
+        .unwrap();
+    assert_eq!(out.result, json!(6.0));
+
+    let bad = tools
+        .call(FunctionCall::new(
+            "area".into(),
+            json!({ "rect": { "min": 1 } }),
+        ))
+        .await;
+    assert!(bad.is_err());
+}
@@ -0,0 +1,85 @@
+//! OpenAI tool calls, whose `arguments` arrive as a string holding JSON.
+
+use serde_json::{Value, json};
+use tools_rs::{FunctionCall, ToolCollection, ToolError, test_tools, tool};
+
+/// Adds two numbers.
+#[tool]
+async fn plus(a: i64, b: i64) -> i64 {
+    a + b
+}
+
+fn tools() -> ToolCollection {
+    test_tools![plus]
+}
+
+#[tokio::test]
+async fn stringified_arguments_are_parsed() {
+    let call: FunctionCall =
+        serde_json::from_str(r#"{"name":"plus","arguments":"{\"a\":1,\"b\":2}"}"#).unwrap();
+    assert_eq!(call.arguments, json!({ "a": 1, "b": 2 }));
+    let response = tools().call(call).await.unwrap();
+    assert_eq!(response.result, json!(3));
+
+    // Objects are taken as they are.
+    let call: FunctionCall =
+        serde_json::from_value(json!({ "name": "plus", "arguments": { "a": 2, "b": 2 } })).unwrap();
+    assert_eq!(call.arguments, json!({ "a": 2, "b": 2 }));
+}
+
+#[tokio::test]
+async fn from_openai_keeps_the_provider_id() {
+    let tool_call = json!({
+        "id": "call_abc123",
+        "type": "function",
+        "function": { "name": "plus", "arguments": "{\"a\":40,\"b\":2}" }
+    });
+    let call = FunctionCall::from_openai(&tool_call).unwrap();
+    assert_eq!(
+        call.id.clone().map(String::from).as_deref(),
+        Some("call_abc123")
+    );
+    let response = tools().call(call).await.unwrap();
+    assert_eq!(response.result, json!(42));
+    assert_eq!(
+        response.id.map(String::from).as_deref(),
+        Some("call_abc123")
+    );
+
+    // The Responses API's flat item, with no arguments at all.
+    let call = FunctionCall::from_openai(&json!({
+        "type": "function_call",
+        "call_id": "call_xyz",
+        "name": "now",
+    }))
+    .unwrap();
+    assert_eq!(call.name, "now");
+    assert_eq!(call.arguments, json!({}));
+    assert_eq!(call.id.map(String::from).as_deref(), Some("call_xyz"));
+}
+
+#[test]
+fn malformed_arguments_quote_the_string() {
+    let tool_call = json!({
+        "id": "call_1",
+        "function": { "name": "plus", "arguments": "{\"a\":1," }
+    });
+    let err = FunctionCall::from_openai(&tool_call).unwrap_err();
+    assert!(matches!(err, ToolError::Deserialize(_)), "{err:?}");
+    assert!(err.to_string().contains(r#""{\"a\":1,""#), "{err}");
+
+    let err = serde_json::from_value::<FunctionCall>(json!({
+        "name": "plus",
+        "arguments": "{\"a\":1,"
+    }))
+    .unwrap_err();
+    assert!(err.to_string().contains("not valid JSON"), "{err}");
+}
+
+#[test]
+fn string_arguments_round_trip() {
+    let call = FunctionCall::new("echo".into(), Value::String("hi".into()));
+    let text = serde_json::to_string(&call).unwrap();
+    let back: FunctionCall = serde_json::from_str(&text).unwrap();
+    assert_eq!(back, call);
+}
@@ -0,0 +1,160 @@
+//! Panicking tools, whether registered closures, specs or `#[tool]`
+//! functions, fail their call with `ToolError::Panicked`, and the
+//! backtrace never reaches the LLM-facing error report.
+
+use std::backtrace::{Backtrace, BacktraceStatus};
+
+use serde_json::json;
+use tools_rs::{ErrorClass, FunctionCall, ToolCollection, ToolError, ToolSpec, test_tools, tool};
+
+fn tools() -> ToolCollection {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register_spec(ToolSpec::new("explode", "Panics.").handler(|args| {
+            Box::pin(async move {
+                tokio::task::yield_now().await;
+                if args["formatted"] == true {
+                    panic!("bad row {}", 7);
+                }
+                panic!("index out of range")
+            })
+        }))
+        .unwrap();
+    tools
+        .register("ok", "Returns its input.", |x: i32| async move { x }, ())
+        .unwrap();
+    tools
+}
+
+fn panicked(tool: &'static str, backtrace: Option<&str>) -> ToolError {
+    ToolError::Panicked {
+        tool: tool.into(),
+        message: "index out of range".into(),
+        backtrace: backtrace.map(str::to_string),
+    }
+}
+
+// ---------- capture ----------
+
+#[tokio::test]
+async fn panics_become_errors() {
+    let tools = tools();
+    for (args, message) in [
+        (json!({}), "index out of range"),
+        (json!({ "formatted": true }), "bad row 7"),
+    ] {
+        let err = tools
+            .call(FunctionCall::new("explode".into(), args))
+            .await
+            .unwrap_err();
+        let ToolError::Panicked {
+            tool,
+            message: got,
+            backtrace,
+        } = &err
+        else {
+            panic!("unexpected error: {err}");
+        };
+        assert_eq!(*tool, "explode");
+        assert_eq!(got, message);
+        // Captured only when the environment enables backtraces.
+        let enabled = Backtrace::capture().status() == BacktraceStatus::Captured;
+        assert_eq!(backtrace.is_some(), enabled);
+        if let Some(backtrace) = backtrace {
+            // Taken at the panic site, not where it was caught.
+            assert!(backtrace.contains("panics::tools"), "{backtrace}");
+        }
+        assert_eq!(err.classification(), ErrorClass::Internal);
+    }
+
+    // The collection keeps working.
+    let out = tools
+        .call(FunctionCall::new("ok".into(), json!(3)))
+        .await
+        .unwrap();
+    assert_eq!(out.result, 3);
+}
+
+/// Applies an arithmetic operation to two numbers.
+#[tool]
+async fn calculate(a: f64, b: f64, op: String) -> f64 {
+    match op.as_str() {
+        "add" => a + b,
+        "mul" => a * b,
+        other => panic!("unsupported operation `{other}`"),
+    }
+}
+
+#[tokio::test]
+async fn registered_closures_and_tool_functions_are_caught() {
+    let mut tools = test_tools![calculate];
+    tools
+        .register(
+            "halve",
+            "Halves an even number.",
+            |x: i32| async move {
+                assert!(x % 2 == 0, "odd input {x}");
+                x / 2
+            },
+            (),
+        )
+        .unwrap();
+
+    let err = tools
+        .call(FunctionCall::new("halve".into(), json!(3)))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, ToolError::Panicked { tool, message, .. }
+            if tool == "halve" && message == "odd input 3"),
+        "{err}"
+    );
+
+    let calc =
+        |op: &str| FunctionCall::new("calculate".into(), json!({ "a": 6.0, "b": 2.0, "op": op }));
+    let err = tools.call(calc("pow")).await.unwrap_err();
+    assert!(
+        matches!(&err, ToolError::Panicked { tool, message, .. }
+            if tool == "calculate" && message == "unsupported operation `pow`"),
+        "{err}"
+    );
+
+    // Both tools keep working after panicking.
+    let out = tools
+        .call(FunctionCall::new("halve".into(), json!(4)))
+        .await
+        .unwrap();
+    assert_eq!(out.result, 2);
+    assert_eq!(tools.call(calc("mul")).await.unwrap().result, json!(12.0));
+}
+
+// ---------- redaction ----------
+
+#[test]
+fn reports_leave_the_backtrace_out() {
+    let err = panicked("explode", Some("0: tools_core::secret_internals"));
+    assert_eq!(
+        err.to_string(),
+        "tool `explode` panicked: index out of range"
+    );
+    let report = serde_json::to_value(err.report()).unwrap();
+    assert_eq!(
+        report,
+        json!({
+            "class": "internal",
+            "retryable": false,
+            "message": "tool `explode` panicked: index out of range"
+        })
+    );
+    assert!(!report.to_string().contains("secret_internals"));
+}
+
+#[test]
+fn trusted_reports_include_the_backtrace() {
+    let err = panicked("explode", Some("0: tools_core::secret_internals"));
+    let report = serde_json::to_value(err.report_trusted()).unwrap();
+    assert_eq!(report["backtrace"], "0: tools_core::secret_internals");
+
+    let without = serde_json::to_value(panicked("explode", None).report_trusted()).unwrap();
+    assert!(without.get("backtrace").is_none());
+}
@@ -0,0 +1,113 @@
+//! Absent / `null` / value through `#[tool]` parameters and derived structs.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tools_rs::{FunctionCall, Patch, ToolSchema, test_tools, tool};
+
+fn state<T: std::fmt::Debug>(patch: Patch<T>) -> String {
+    match patch {
+        Patch::Missing => "missing".to_string(),
+        Patch::Null => "null".to_string(),
+        Patch::Value(v) => format!("value {v:?}"),
+    }
+}
+
+/// Updates a user.
+#[tool]
+async fn update_user(id: u32, nickname: Patch<String>, age: Option<Option<u32>>) -> Value {
+    json!({
+        "id": id,
+        "nickname": state(nickname),
+        "age": state(Patch::from(age)),
+    })
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, ToolSchema)]
+struct Settings {
+    theme: String,
+    #[serde(default, skip_serializing_if = "Patch::is_missing")]
+    accent: Patch<String>,
+    #[serde(
+        default,
+        deserialize_with = "tools_rs::patch::double_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    font_size: Option<Option<u8>>,
+}
+
+/// Updates settings.
+#[tool]
+async fn update_settings(settings: Settings) -> Settings {
+    settings
+}
+
+async fn call(name: &str, arguments: Value) -> Value {
+    let tools = test_tools![update_user, update_settings];
+    tools
+        .call(FunctionCall::new(name.to_string(), arguments))
+        .await
+        .unwrap()
+        .result
+}
+
+// ---------- schema ----------
+
+#[test]
+fn three_state_params_are_nullable_and_optional() {
+    let tools = test_tools![update_user];
+    let decl = tools.json().unwrap();
+    let params = &decl[0]["parameters"];
+    assert_eq!(params["required"], json!(["id"]));
+
+    let nullable_string = json!({ "anyOf": [{ "type": "string" }, { "type": "null" }] });
+    assert_eq!(params["properties"]["nickname"], nullable_string);
+    // Option<Option<T>> is not nested: the schema is the same as Option<T>.
+    assert_eq!(params["properties"]["age"], Option::<u32>::schema());
+}
+
+#[test]
+fn derived_struct_treats_patch_fields_as_optional() {
+    let schema = Settings::schema();
+    assert_eq!(schema["required"], json!(["theme"]));
+    assert_eq!(schema["properties"]["font_size"], Option::<u8>::schema());
+}
+
+// ---------- round trips ----------
+
+#[tokio::test]
+async fn tool_params_round_trip_all_three_states() {
+    let absent = call("update_user", json!({ "id": 1 })).await;
+    assert_eq!(
+        absent,
+        json!({ "id": 1, "nickname": "missing", "age": "missing" })
+    );
+
+    let null = call(
+        "update_user",
+        json!({ "id": 1, "nickname": null, "age": null }),
+    )
+    .await;
+    assert_eq!(null, json!({ "id": 1, "nickname": "null", "age": "null" }));
+
+    let value = call(
+        "update_user",
+        json!({ "id": 1, "nickname": "ada", "age": 36 }),
+    )
+    .await;
+    assert_eq!(
+        value,
+        json!({ "id": 1, "nickname": "value \"ada\"", "age": "value 36" })
+    );
+}
+
+#[tokio::test]
+async fn derived_struct_round_trips_all_three_states() {
+    for settings in [
+        json!({ "theme": "dark" }),
+        json!({ "theme": "dark", "accent": null, "font_size": null }),
+        json!({ "theme": "dark", "accent": "teal", "font_size": 14 }),
+    ] {
+        let out = call("update_settings", json!({ "settings": settings })).await;
+        assert_eq!(out, settings);
+    }
+}
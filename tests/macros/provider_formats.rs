@@ -0,0 +1,112 @@
+//! `declarations_for`: the tools array in each provider's own shape.
+
+use serde_json::{Value, json};
+use tools_rs::{AgentProfile, DeclTier, Provider, ToolCollection, tool};
+
+/// Looks up a city's forecast.
+#[tool]
+async fn forecast(city: String, days: Option<u8>) -> String {
+    format!("{city}: sunny for {} days", days.unwrap_or(1))
+}
+
+/// Returns the current time.
+#[tool]
+async fn now() -> u64 {
+    0
+}
+
+fn tools() -> ToolCollection {
+    ToolCollection::collect_tools_filtered(|name| matches!(name, "forecast" | "now")).unwrap()
+}
+
+fn parameters(tools: &ToolCollection, name: &str) -> Value {
+    tools.get(name).unwrap().decl.parameters.clone()
+}
+
+#[test]
+fn openai_nests_under_function() {
+    let tools = tools();
+    let decls = tools.declarations_for(Provider::OpenAi).unwrap();
+    assert_eq!(
+        decls[0],
+        json!({
+            "type": "function",
+            "function": {
+                "name": "forecast",
+                "description": "Looks up a city's forecast.",
+                "parameters": parameters(&tools, "forecast"),
+            }
+        })
+    );
+    assert_eq!(decls.as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn openai_strict_closes_every_object() {
+    let tools = tools();
+    let decls = tools.declarations_for(Provider::OpenAiStrict).unwrap();
+    let function = &decls[0]["function"];
+    assert_eq!(function["strict"], true);
+    let params = &function["parameters"];
+    assert_eq!(params["additionalProperties"], false);
+    assert_eq!(params["required"], json!(["city", "days"]));
+    // `days` was optional, and already nullable.
+    assert_eq!(
+        params["properties"]["days"],
+        parameters(&tools, "forecast")["properties"]["days"]
+    );
+}
+
+#[test]
+fn gemini_is_a_bare_list_in_the_openapi_subset() {
+    let tools = tools();
+    let decls = tools.declarations_for(Provider::Gemini).unwrap();
+    let forecast = &decls[0];
+    assert_eq!(forecast["name"], "forecast");
+    assert_eq!(forecast["description"], "Looks up a city's forecast.");
+    assert!(forecast.get("function").is_none());
+    let days = &forecast["parameters"]["properties"]["days"];
+    assert_eq!(days["nullable"], true);
+    assert!(days.get("anyOf").is_none(), "{days}");
+    // No empty object schema for a tool without parameters.
+    assert_eq!(
+        decls[1],
+        json!({ "name": "now", "description": "Returns the current time." })
+    );
+}
+
+#[test]
+fn anthropic_uses_input_schema() {
+    let tools = tools();
+    let decls = tools.declarations_for(Provider::Anthropic).unwrap();
+    assert_eq!(
+        decls[0],
+        json!({
+            "name": "forecast",
+            "description": "Looks up a city's forecast.",
+            "input_schema": parameters(&tools, "forecast"),
+        })
+    );
+}
+
+#[test]
+fn views_and_tiers() {
+    let mut tools = tools();
+    tools.set_profile(AgentProfile::new("clock").allow(["now"]));
+    let view = tools.profile("clock").unwrap();
+    let decls = view.declarations_for(Provider::Anthropic).unwrap();
+    assert_eq!(decls.as_array().unwrap().len(), 1);
+    assert_eq!(decls[0]["name"], "now");
+
+    let terse = tools
+        .declarations_for_tier(Provider::OpenAi, DeclTier::Terse)
+        .unwrap();
+    let expected = tools.json_tier(DeclTier::Terse).unwrap();
+    assert_eq!(
+        terse[0]["function"]["parameters"],
+        expected[0]["parameters"]
+    );
+
+    // `json()` keeps its flat layout.
+    assert_eq!(tools.json().unwrap()[0]["name"], "forecast");
+}
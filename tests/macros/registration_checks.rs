@@ -0,0 +1,297 @@
+//! Declaration mistakes caught at registration instead of at the first
+//! call: warnings by default, errors in strict mode.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tools_core::ToolFunc;
+use tools_rs::{
+    DecodedInput, DeserializationError, ErrorClass, ToolCollection, ToolError, ToolSchema,
+    ToolSpec,
+    checks::{Strictness, codes},
+    test_tools, tool,
+};
+
+#[derive(Debug, Serialize, Deserialize, ToolSchema)]
+#[serde(deny_unknown_fields)]
+struct Lookup {
+    id: u32,
+}
+
+/// A `#[tool]` with a clean declaration, so strict collection succeeds.
+#[tool]
+/// Subtracts `b` from `a`.
+async fn subtract(a: i64, b: i64) -> i64 {
+    a - b
+}
+
+// No doc comment: `#[tool]` warns (on nightly) and
+// `Strictness::RequireDescriptions` rejects it.
+#[tool]
+async fn shrug(x: i64) -> i64 {
+    x
+}
+
+fn raw(name: &'static str, parameters: Value) -> ToolSpec {
+    ToolSpec::new(name, "Test tool.")
+        .params_schema(parameters)
+        .handler(|args| Box::pin(async move { Ok(args) }))
+}
+
+/// `lookup` decoding into `Lookup`, with `examples` spliced into its
+/// derived schema.
+fn lookup_with_examples(examples: Value) -> ToolSpec {
+    let mut schema = Lookup::schema();
+    schema["examples"] = examples;
+    let decode = |raw: Value| {
+        let input: Lookup = serde_json::from_value(raw).map_err(DeserializationError::from)?;
+        Ok(Box::new(input) as DecodedInput)
+    };
+    ToolSpec::new("lookup", "Looks up a record.")
+        .params_schema(schema)
+        .func(ToolFunc::new(decode, |_, _| {
+            Box::pin(async { Ok(json!(null)) })
+        }))
+}
+
+/// Codes of the warnings left by registering `spec`.
+fn codes_for(spec: ToolSpec) -> Vec<&'static str> {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools.register_spec(spec).unwrap();
+    tools
+        .registration_warnings()
+        .iter()
+        .map(|f| f.code)
+        .collect()
+}
+
+// ---------- checks ----------
+
+#[test]
+fn clean_declarations_have_no_findings() {
+    assert!(codes_for(lookup_with_examples(json!([{ "id": 7 }]))).is_empty());
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register("echo", "Echoes.", |l: Lookup| async move { l.id }, ())
+        .unwrap();
+    assert!(tools.registration_warnings().is_empty());
+}
+
+#[test]
+fn malformed_schemas() {
+    assert_eq!(
+        codes_for(raw("a", json!("object"))),
+        [codes::INVALID_SCHEMA]
+    );
+    assert_eq!(
+        codes_for(raw("b", json!({ "type": "object", "properties": [] }))),
+        [codes::INVALID_SCHEMA]
+    );
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register_spec(raw(
+            "c",
+            json!({ "type": "object", "properties": { "x": {} }, "required": ["x", "y"] }),
+        ))
+        .unwrap();
+    let finding = &tools.registration_warnings()[0];
+    assert_eq!(finding.code, codes::INVALID_SCHEMA);
+    assert_eq!(finding.path, "/required/1");
+    assert_eq!(
+        finding.to_string(),
+        "[invalid-schema] tool `c` at /required/1: required argument `y` is not in `properties`"
+    );
+}
+
+#[test]
+fn primitive_arguments() {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register(
+            "create_user",
+            "Creates a user.",
+            |id: u32| async move { id },
+            (),
+        )
+        .unwrap();
+    let codes: Vec<_> = tools
+        .registration_warnings()
+        .iter()
+        .map(|f| f.code)
+        .collect();
+    assert_eq!(codes, [codes::PRIMITIVE_PARAMS]);
+}
+
+#[test]
+fn examples_run_through_the_decoder() {
+    // The derived decoder rejects a wrong type and an unknown field.
+    assert_eq!(
+        codes_for(lookup_with_examples(json!([{ "id": "seven" }]))),
+        [codes::EXAMPLE_REJECTED]
+    );
+    assert_eq!(
+        codes_for(lookup_with_examples(json!([{ "id": 7, "verbose": true }]))),
+        [codes::EXAMPLE_REJECTED, codes::EXAMPLE_UNKNOWN_ARG]
+    );
+    // A zero-field wrapper with a non-empty example.
+    assert_eq!(
+        codes_for(raw(
+            "noop",
+            json!({ "type": "object", "properties": {}, "examples": [{ "x": 1 }] })
+        )),
+        [codes::EXAMPLE_UNKNOWN_ARG]
+    );
+}
+
+#[test]
+fn defaults_must_fit_their_field() {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "limit": { "type": "integer", "default": "ten" },
+            "unit": { "type": "string", "enum": ["c", "f"], "default": "k" },
+            "page": { "type": ["integer", "null"], "default": null }
+        }
+    });
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools.register_spec(raw("search", schema)).unwrap();
+    let paths: Vec<(&str, &str)> = tools
+        .registration_warnings()
+        .iter()
+        .map(|f| (f.code, f.path.as_str()))
+        .collect();
+    assert_eq!(
+        paths,
+        [
+            (codes::DEFAULT_MISMATCH, "/properties/limit/default"),
+            (codes::DEFAULT_MISMATCH, "/properties/unit/default"),
+        ]
+    );
+}
+
+// ---------- strict mode ----------
+
+#[test]
+fn strict_mode_rejects_with_the_tool_name() {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools.set_strict_registration(true).unwrap();
+    let err = tools
+        .register_spec(raw("bad", json!({ "type": "object", "required": ["x"] })))
+        .unwrap_err();
+    let ToolError::RegistrationRejected { tool, findings, .. } = &err else {
+        panic!("unexpected error: {err}");
+    };
+    assert_eq!(tool, "bad");
+    assert_eq!(findings.len(), 1);
+    assert_eq!(
+        err.to_string(),
+        "tool `bad` failed registration checks: required argument `x` is not in `properties`"
+    );
+    assert_eq!(err.classification(), ErrorClass::Internal);
+    assert!(tools.is_empty());
+}
+
+#[test]
+fn warnings_follow_their_tool() {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register_spec(raw("bad", json!({ "type": "object", "required": ["x"] })))
+        .unwrap();
+    // Strict mode can't be turned on over existing warnings.
+    assert!(matches!(
+        tools.set_strict_registration(true).unwrap_err(),
+        ToolError::RegistrationRejected { .. }
+    ));
+
+    tools.unregister("bad").unwrap();
+    assert!(tools.registration_warnings().is_empty());
+    tools.set_strict_registration(true).unwrap();
+}
+
+#[test]
+fn strict_collection_of_tool_functions() {
+    let mut tools = test_tools![subtract];
+    tools.set_strict_registration(true).unwrap();
+    assert!(tools.registration_warnings().is_empty());
+}
+
+// ---------- required descriptions ----------
+
+fn undocumented() -> ToolSpec {
+    ToolSpec::new("vague", "  ")
+        .params_schema(json!({
+            "type": "object",
+            "properties": {
+                "q": { "type": "string" },
+                "n": { "type": "integer", "description": "How many." }
+            }
+        }))
+        .handler(|args| Box::pin(async move { Ok(args) }))
+}
+
+#[test]
+fn required_descriptions_list_each_gap() {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .set_registration_strictness(Strictness::RequireDescriptions)
+        .unwrap();
+    let err = tools.register_spec(undocumented()).unwrap_err();
+    let ToolError::RegistrationRejected { tool, findings, .. } = &err else {
+        panic!("unexpected error: {err}");
+    };
+    assert_eq!(tool, "vague");
+    let gaps: Vec<_> = findings.iter().map(|f| (f.code, f.path.as_str())).collect();
+    assert_eq!(
+        gaps,
+        [
+            (codes::MISSING_DESCRIPTION, ""),
+            (codes::MISSING_PARAM_DESCRIPTION, "/properties/q"),
+        ]
+    );
+    assert_eq!(
+        err.to_string(),
+        "tool `vague` failed registration checks: tool has no description; \
+         parameter `q` has no description"
+    );
+
+    // Derived schemas without field docs fall short too.
+    let err = tools
+        .register("echo", "Echoes.", |l: Lookup| async move { l.id }, ())
+        .unwrap_err();
+    assert!(matches!(err, ToolError::RegistrationRejected { .. }));
+    assert!(tools.is_empty());
+
+    tools
+        .register_spec(raw("clear", json!({ "type": "object" })))
+        .unwrap();
+}
+
+#[test]
+fn descriptions_are_optional_by_default() {
+    let mut tools: ToolCollection = ToolCollection::new();
+    assert_eq!(tools.registration_strictness(), Strictness::Lenient);
+    tools.register_spec(undocumented()).unwrap();
+    assert!(tools.registration_warnings().is_empty());
+
+    // Can't be required over tools that lack them.
+    assert!(matches!(
+        tools
+            .set_registration_strictness(Strictness::RequireDescriptions)
+            .unwrap_err(),
+        ToolError::RegistrationRejected { .. }
+    ));
+    assert_eq!(tools.registration_strictness(), Strictness::Lenient);
+    tools.unregister("vague").unwrap();
+    tools
+        .set_registration_strictness(Strictness::RequireDescriptions)
+        .unwrap();
+}
+
+#[test]
+fn tool_functions_without_docs() {
+    let mut tools = test_tools![shrug];
+    assert_eq!(tools.get("shrug").unwrap().decl.description, "");
+    assert!(matches!(
+        tools.set_registration_strictness(Strictness::RequireDescriptions),
+        Err(ToolError::RegistrationRejected { .. })
+    ));
+}
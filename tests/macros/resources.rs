@@ -0,0 +1,125 @@
+//! `#[tool_resource]` statics injected into `#[tool]` functions by type.
+
+use std::sync::{
+    LazyLock,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use serde_json::json;
+use tools_rs::{ToolError, test_tools, tool, tool_resource};
+
+use crate::common::call;
+
+struct Config {
+    greeting: &'static str,
+}
+
+struct Client {
+    base_url: String,
+}
+
+static CLIENT_INITS: AtomicUsize = AtomicUsize::new(0);
+
+#[tool_resource]
+static CONFIG: Config = Config { greeting: "Hello" };
+
+#[tool_resource]
+static CLIENT: LazyLock<Client> = LazyLock::new(|| {
+    CLIENT_INITS.fetch_add(1, Ordering::SeqCst);
+    Client {
+        base_url: "https://api.example.com".to_string(),
+    }
+});
+
+#[tool_resource]
+const RETRIES: u8 = 3;
+
+/// Greets someone through the configured client.
+#[tool]
+async fn greet(
+    #[tool(inject)] config: &Config,
+    name: String,
+    #[tool(inject)] client: &Client,
+) -> String {
+    format!("{}, {name}! (via {})", config.greeting, client.base_url)
+}
+
+/// Reports the retry budget.
+#[tool]
+async fn retries(#[tool(inject)] retries: &u8) -> u8 {
+    *retries
+}
+
+struct Unregistered;
+
+/// Needs a resource nobody registered.
+#[tool]
+async fn orphan(#[tool(inject)] _missing: &Unregistered) -> bool {
+    true
+}
+
+// ---------- injection ----------
+
+#[tokio::test]
+async fn tool_receives_both_resources() {
+    let tools = test_tools![greet];
+    let out = tools
+        .call(call("greet", json!({ "name": "Ada" })))
+        .await
+        .unwrap();
+    assert_eq!(
+        out.result,
+        json!("Hello, Ada! (via https://api.example.com)")
+    );
+    assert_eq!(CLIENT_INITS.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn const_resources_are_injected() {
+    let tools = test_tools![retries];
+    let out = tools.call(call("retries", json!({}))).await.unwrap();
+    assert_eq!(out.result, json!(3));
+}
+
+#[test]
+fn injected_parameters_are_not_in_the_schema() {
+    let tools = test_tools![greet];
+    let decl = &tools.get("greet").unwrap().decl;
+    assert_eq!(
+        decl.parameters["properties"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .collect::<Vec<_>>(),
+        ["name"]
+    );
+    assert_eq!(decl.parameters["required"], json!(["name"]));
+}
+
+// ---------- lookup ----------
+
+#[test]
+fn resources_are_found_by_type() {
+    assert_eq!(
+        tools_core::resources::get::<Config>().unwrap().greeting,
+        "Hello"
+    );
+    assert!(tools_core::resources::get::<Unregistered>().is_none());
+
+    let mut names: Vec<_> = tools_core::resources::registered().collect();
+    names.sort();
+    assert_eq!(names, ["Client", "Config", "u8"]);
+}
+
+#[tokio::test]
+async fn missing_resource_fails_the_call() {
+    let tools = test_tools![orphan];
+    let err = tools.call(call("orphan", json!({}))).await.unwrap_err();
+    match err {
+        ToolError::MissingResource { tool, resource } => {
+            assert_eq!(tool, "orphan");
+            assert_eq!(resource, "Unregistered");
+        }
+        other => panic!("unexpected error: {other}"),
+    }
+}
@@ -0,0 +1,86 @@
+//! Tests for `Secrets` injected through the shared context, or set on the
+//! collection for tools without one.
+//!
+//! Lives in its own binary because every `ctx` tool in an inventory must
+//! share one context type.
+
+use std::sync::Arc;
+
+use tools_core::{NoMeta, Secrets, StaticSecrets, ToolCollection};
+use tools_rs::{FunctionCall, secrets, tool};
+
+#[tool]
+/// Reports which API key the tool would use.
+async fn whoami(ctx: Secrets, service: String) -> String {
+    match ctx.get(&format!("{}_API_KEY", service.to_uppercase())) {
+        Some(key) => format!("{service}: {}", key.expose()),
+        None => format!("{service}: no key"),
+    }
+}
+
+fn tools_with(secrets: StaticSecrets) -> ToolCollection {
+    ToolCollection::<NoMeta>::builder()
+        .with_context(Arc::new(Secrets::new(secrets)))
+        .collect()
+        .expect("collect with secrets")
+}
+
+#[tokio::test]
+async fn tool_reads_secret_from_context() {
+    let tools = tools_with(StaticSecrets::new().with("WEATHER_API_KEY", "test-key"));
+
+    let resp = tools
+        .call(FunctionCall::new(
+            "whoami".into(),
+            serde_json::json!({ "service": "weather" }),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.result, serde_json::json!("weather: test-key"));
+}
+
+#[tokio::test]
+async fn provider_is_swappable() {
+    let tools = tools_with(StaticSecrets::new());
+
+    let resp = tools
+        .call(FunctionCall::new(
+            "whoami".into(),
+            serde_json::json!({ "service": "weather" }),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.result, serde_json::json!("weather: no key"));
+}
+
+#[tokio::test]
+async fn collection_provider_reaches_tools_without_a_secrets_context() {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register(
+            "api_key",
+            "Returns the API key it would use.",
+            |_: ()| async {
+                let secrets = secrets::current().ok_or("no secrets provider")?;
+                let key = secrets.require("API_KEY").map_err(|e| e.to_string())?;
+                Ok::<_, String>(key.into_inner())
+            },
+            (),
+        )
+        .unwrap();
+    let call = || FunctionCall::new("api_key".into(), serde_json::json!(null));
+
+    let resp = tools.call(call()).await.unwrap();
+    assert!(resp.is_error);
+    assert!(secrets::current().is_none());
+
+    tools.set_secrets(StaticSecrets::new().with("API_KEY", "abc"));
+    let resp = tools.call(call()).await.unwrap();
+    assert_eq!(resp.result, serde_json::json!("abc"));
+    assert!(tools.secrets().unwrap().get("API_KEY").is_some());
+    // Only for the duration of the call.
+    assert!(secrets::current().is_none());
+
+    tools.clear_secrets();
+    assert!(tools.call(call()).await.unwrap().is_error);
+}
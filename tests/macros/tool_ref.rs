@@ -0,0 +1,108 @@
+//! Typed handles from `register` and `#[tool]` call tools through
+//! `invoke` with typed input and output.
+
+use serde::{Deserialize, Serialize};
+use tools_rs::{Patch, ToolCollection, ToolError, ToolRef, ToolSchema, test_tools, tool};
+
+#[tool]
+/// Adds two numbers.
+async fn add(a: i32, b: i32) -> i32 {
+    a + b
+}
+
+#[tool]
+/// Liveness check.
+async fn ping() -> String {
+    "pong".into()
+}
+
+#[tool]
+/// Does nothing.
+async fn noop(_flag: bool) {}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, ToolSchema)]
+struct Range {
+    start: u32,
+    end: u32,
+}
+
+#[tool]
+/// Length of a range, clamped to `limit` if given.
+async fn span(range: Range, limit: Option<u32>) -> u32 {
+    let len = range.end - range.start;
+    limit.map_or(len, |limit| len.min(limit))
+}
+
+#[tool]
+/// What the caller said about the nickname.
+async fn nickname(nick: Patch<String>, note: Option<Option<String>>) -> String {
+    format!("{nick:?} {note:?}")
+}
+
+// ---------- #[tool] handles ----------
+
+#[tokio::test]
+async fn macro_handles_round_trip() {
+    let tools = test_tools![add, ping, noop, span];
+    assert_eq!(ADD_TOOL.name(), "add");
+    assert_eq!(tools.invoke(&ADD_TOOL, (40, 2)).await.unwrap(), 42);
+    assert_eq!(tools.invoke(&PING_TOOL, ()).await.unwrap(), "pong");
+    tools.invoke(&NOOP_TOOL, (true,)).await.unwrap();
+
+    let range = Range { start: 2, end: 10 };
+    assert_eq!(tools.invoke(&SPAN_TOOL, (range, Some(5))).await.unwrap(), 5);
+    let range = Range { start: 2, end: 10 };
+    assert_eq!(tools.invoke(&SPAN_TOOL, (range, None)).await.unwrap(), 8);
+}
+
+#[tokio::test]
+async fn absent_arguments_stay_absent() {
+    let tools = test_tools![nickname];
+    assert_eq!(
+        tools
+            .invoke(&NICKNAME_TOOL, (Patch::Missing, None))
+            .await
+            .unwrap(),
+        "Missing None"
+    );
+    assert_eq!(
+        tools
+            .invoke(&NICKNAME_TOOL, (Patch::Null, Some(None)))
+            .await
+            .unwrap(),
+        "Null Some(None)"
+    );
+}
+
+// ---------- registered handles ----------
+
+#[tokio::test]
+async fn register_returns_a_handle() {
+    let mut tools: ToolCollection = ToolCollection::new();
+    let square = tools
+        .register("square", "Squares.", |n: i64| async move { n * n }, ())
+        .unwrap();
+    assert_eq!(square.name(), "square");
+    assert_eq!(tools.invoke(&square, 9).await.unwrap(), 81);
+}
+
+#[tokio::test]
+async fn mismatched_handles_fail_at_runtime() {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register("square", "Squares.", |n: i64| async move { n * n }, ())
+        .unwrap();
+
+    // Built by hand, so nothing ties it to the registered types.
+    let wrong: ToolRef<i64, String> = ToolRef::new("square");
+    assert!(matches!(
+        tools.invoke(&wrong, 3).await,
+        Err(ToolError::Serialization(_))
+    ));
+
+    let missing: ToolRef<i64, i64> = ToolRef::new("cube");
+    assert!(matches!(
+        tools.invoke(&missing, 3).await,
+        Err(ToolError::FunctionNotFound { .. })
+    ));
+}
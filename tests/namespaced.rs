@@ -0,0 +1,91 @@
+//! Inventories spanning several crates: `ping_a` and `ping_b` (under
+//! `test_support/`) each define a `ping` tool.
+
+use serde_json::json;
+use tools_rs::{FunctionCall, ToolCollection, ToolError, ToolOrigin, collect_tools_namespaced};
+
+// Link the fixture crates so their tools are in the inventory.
+use ping_a as _;
+use ping_b as _;
+
+// ---------- duplicates ----------
+
+#[test]
+fn flat_collection_reports_both_definitions() {
+    let collected: Result<ToolCollection, _> = ToolCollection::collect_tools();
+    let Err(err) = collected else {
+        panic!("duplicate `ping` was accepted");
+    };
+    let ToolError::DuplicateTool {
+        name,
+        first,
+        second,
+    } = &err
+    else {
+        panic!("unexpected error: {err}");
+    };
+    assert_eq!(*name, "ping");
+    let mut crates = [first.crate_name, second.crate_name];
+    crates.sort();
+    assert_eq!(crates, ["ping_a", "ping_b"]);
+
+    let message = err.to_string();
+    assert!(message.contains("`ping_a` in crate `ping_a`"), "{message}");
+    assert!(
+        message.contains("`ping_b::net` in crate `ping_b`"),
+        "{message}"
+    );
+}
+
+#[test]
+fn filtered_collection_skips_the_clash() {
+    let tools: ToolCollection = ToolCollection::collect_tools_filtered(|n| n == "echo").unwrap();
+    assert_eq!(
+        tools.get("echo").unwrap().origin,
+        Some(ToolOrigin::from_module_path("ping_b"))
+    );
+}
+
+// ---------- namespaced ----------
+
+#[tokio::test]
+async fn namespaced_tools_are_callable() {
+    let tools = collect_tools_namespaced("__").unwrap();
+    let mut names: Vec<&str> = tools.iter().map(|(name, _)| name).collect();
+    names.sort();
+    assert_eq!(names, ["ping_a__ping", "ping_b__echo", "ping_b__ping"]);
+
+    for (name, reply) in [
+        ("ping_a__ping", "pong from a"),
+        ("ping_b__ping", "pong from b"),
+    ] {
+        let out = tools
+            .call(FunctionCall::new(name.into(), json!({})))
+            .await
+            .unwrap();
+        assert_eq!(out.result, reply);
+    }
+}
+
+#[test]
+fn separator_is_configurable() {
+    let tools = collect_tools_namespaced(".").unwrap();
+    assert!(tools.get("ping_a.ping").is_some());
+    assert!(tools.get("ping_a__ping").is_none());
+}
+
+#[test]
+fn introspection_shows_the_origin() {
+    let tools = collect_tools_namespaced("__").unwrap();
+    let report = serde_json::to_value(tools.introspect()).unwrap();
+    let ping_b = report["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|t| t["name"] == "ping_b__ping")
+        .unwrap();
+    assert_eq!(
+        ping_b["origin"],
+        json!({ "crate_name": "ping_b", "module_path": "ping_b::net" })
+    );
+}
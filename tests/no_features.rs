@@ -73,3 +73,40 @@ fn test_schema_traits_available() {
     let _int_schema = i32::schema();
     let _bool_schema = bool::schema();
 }
+
+#[test]
+fn test_vec_of_struct_has_real_item_schema() {
+    // Container schemas delegate to the element's `ToolSchema` impl rather
+    // than falling back to a type-name description.
+    let schema = <Vec<TestInput>>::schema();
+    assert_eq!(schema["type"], "array");
+    assert_eq!(schema["items"], TestInput::schema());
+    assert_eq!(schema["items"]["properties"]["value"]["type"], "integer");
+    assert_eq!(schema["items"]["required"], serde_json::json!(["value"]));
+}
+
+#[test]
+fn test_tuple_schema_is_positional_not_generic_array() {
+    let schema = <(TestInput, String)>::schema();
+    assert_eq!(schema["type"], "array");
+    assert_eq!(schema["prefixItems"][0], TestInput::schema());
+    assert_eq!(schema["prefixItems"][1]["type"], "string");
+    assert_eq!(schema["minItems"], 2);
+    assert_eq!(schema["maxItems"], 2);
+}
+
+#[tokio::test]
+async fn test_vec_of_struct_tool_parameter_schema() {
+    let mut tools: tools_rs::ToolCollection = tools_rs::ToolCollection::new();
+    tools
+        .register(
+            "sum_values",
+            "Sums a list of inputs",
+            |inputs: Vec<TestInput>| async move { inputs.iter().map(|i| i.value).sum::<i32>() },
+            (),
+        )
+        .unwrap();
+
+    let params = &tools.get("sum_values").unwrap().decl.parameters;
+    assert_eq!(params["items"]["properties"]["value"]["type"], "integer");
+}
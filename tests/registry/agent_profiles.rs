@@ -0,0 +1,171 @@
+//! Two agents, a researcher and a coder, sharing one collection through
+//! agent profiles: isolated views, isolated budgets and attribution.
+
+use std::time::Duration;
+
+use serde_json::json;
+use tools_rs::{
+    AgentProfile, DeclFilter, ErrorClass, FunctionCall, ToolCollection, ToolError, ToolSpec,
+    profile,
+};
+
+fn tools() -> ToolCollection {
+    let mut tools: ToolCollection = ToolCollection::new();
+    for (name, tag) in [
+        ("search_web", "research"),
+        ("get_weather", "research"),
+        ("run_tests", "code"),
+        ("count_instance", "text"),
+    ] {
+        tools
+            .register_spec(
+                ToolSpec::new(name, "Test tool.")
+                    .tag(tag)
+                    .handler(|_| Box::pin(async { Ok(json!(profile::current().as_deref())) })),
+            )
+            .unwrap();
+    }
+    tools
+        .register_spec(ToolSpec::new("slow", "Sleeps.").handler(|_| {
+            Box::pin(async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                Ok(json!(null))
+            })
+        }))
+        .unwrap();
+
+    tools.set_profile(
+        AgentProfile::new("researcher")
+            .filter(DeclFilter::default().tag("research"))
+            .budget(2),
+    );
+    tools.set_profile(
+        AgentProfile::new("coder")
+            .allow(["run_tests", "count_instance", "slow"])
+            .budget(5)
+            .timeout(Duration::from_millis(20)),
+    );
+    tools
+}
+
+fn call(name: &str) -> FunctionCall {
+    FunctionCall::new(name.into(), json!({}))
+}
+
+// ---------- views ----------
+
+#[test]
+fn each_profile_declares_only_its_tools() {
+    let tools = tools();
+    let names = |profile: &str| -> Vec<String> {
+        tools
+            .profile(profile)
+            .unwrap()
+            .declarations()
+            .iter()
+            .map(|d| d.name.to_string())
+            .collect()
+    };
+    assert_eq!(names("researcher"), ["get_weather", "search_web"]);
+    assert_eq!(names("coder"), ["count_instance", "run_tests", "slow"]);
+    assert_eq!(
+        tools.profile("coder").unwrap().json().unwrap()[0]["name"],
+        "count_instance"
+    );
+    assert!(tools.profile("nobody").is_none());
+    assert_eq!(
+        tools.profile_names().collect::<Vec<_>>(),
+        ["coder", "researcher"]
+    );
+}
+
+#[tokio::test]
+async fn tools_outside_the_view_are_not_found() {
+    let tools = tools();
+    let researcher = tools.profile("researcher").unwrap();
+    let err = researcher.call(call("run_tests")).await.unwrap_err();
+    assert!(matches!(err, ToolError::FunctionNotFound { .. }));
+    // Rejected calls are not charged.
+    assert_eq!(researcher.usage().calls, 0);
+}
+
+// ---------- budgets ----------
+
+#[tokio::test]
+async fn budgets_are_per_profile() {
+    let tools = tools();
+    let researcher = tools.profile("researcher").unwrap();
+    researcher.call(call("search_web")).await.unwrap();
+    researcher.call(call("get_weather")).await.unwrap();
+
+    let err = researcher.call(call("search_web")).await.unwrap_err();
+    assert!(matches!(
+        &err,
+        ToolError::BudgetExhausted { profile, budget: 2 } if profile == "researcher"
+    ));
+    assert_eq!(err.classification(), ErrorClass::Exhausted);
+
+    // The coder's budget is untouched, and so is the collection itself.
+    let coder = tools.profile("coder").unwrap();
+    coder.call(call("count_instance")).await.unwrap();
+    assert_eq!(coder.usage().remaining, Some(4));
+    tools.call(call("search_web")).await.unwrap();
+}
+
+#[tokio::test]
+async fn clones_share_budgets() {
+    let tools = tools();
+    let clone = tools.clone();
+    tools
+        .profile("researcher")
+        .unwrap()
+        .call(call("search_web"))
+        .await
+        .unwrap();
+    assert_eq!(
+        clone.profile("researcher").unwrap().usage().remaining,
+        Some(1)
+    );
+}
+
+#[tokio::test]
+async fn set_profile_resets_usage() {
+    let mut tools = tools();
+    tools
+        .profile("researcher")
+        .unwrap()
+        .call(call("search_web"))
+        .await
+        .unwrap();
+    tools.set_profile(AgentProfile::new("researcher").budget(2));
+    assert_eq!(tools.profile("researcher").unwrap().usage().calls, 0);
+    assert_eq!(tools.remove_profile("researcher").unwrap().budget, Some(2));
+}
+
+// ---------- attribution and timeouts ----------
+
+#[tokio::test]
+async fn calls_see_their_profile() {
+    let tools = tools();
+    let out = tools
+        .profile("coder")
+        .unwrap()
+        .call(call("count_instance"))
+        .await
+        .unwrap();
+    assert_eq!(out.result, "coder");
+    assert_eq!(
+        tools.call(call("count_instance")).await.unwrap().result,
+        json!(null)
+    );
+}
+
+#[tokio::test]
+async fn profile_timeout_bounds_each_call() {
+    let tools = tools();
+    let coder = tools.profile("coder").unwrap();
+    let err = coder.call(call("slow")).await.unwrap_err();
+    assert!(matches!(err, ToolError::Timeout { ref name, .. } if name == "slow"));
+    assert_eq!(coder.usage().calls, 1);
+    assert_eq!(coder.usage().failures, 1);
+}
@@ -0,0 +1,203 @@
+//! `with_overrides`: declarations patched for one request, with dispatch
+//! and the collection left as registered.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use tools_rs::{
+    DeclOverrides, FunctionCall, ToolCollection, ToolError, ToolSchema, ToolSpec,
+    decl_overrides::merge_patch,
+};
+
+#[derive(Serialize, Deserialize, ToolSchema)]
+struct Query {
+    /// What to search for.
+    text: String,
+    /// Results to return.
+    limit: Option<u32>,
+}
+
+fn tools() -> ToolCollection {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register(
+            "search_web",
+            "Searches the web.",
+            |q: Query| async move { format!("{} ({})", q.text, q.limit.unwrap_or(10)) },
+            (),
+        )
+        .unwrap();
+    tools
+        .register("ping", "Pings.", |_: ()| async { "pong" }, ())
+        .unwrap();
+    tools
+}
+
+fn find<'a>(declarations: &'a Value, name: &str) -> &'a Value {
+    declarations
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|d| d["name"] == name)
+        .unwrap()
+}
+
+#[tokio::test]
+async fn patches_only_the_view() {
+    let tools = tools();
+    let before = tools.json().unwrap();
+    let view = tools
+        .with_overrides(
+            DeclOverrides::new()
+                .description("search_web", "Search the web. Prefer recent sources.")
+                .parameters(
+                    "search_web",
+                    json!({
+                        "properties": {
+                            "limit": { "maximum": 5, "description": null },
+                            "text": { "minLength": 3 }
+                        }
+                    }),
+                ),
+        )
+        .unwrap();
+
+    let patched = view.json().unwrap();
+    let search = find(&patched, "search_web");
+    assert_eq!(
+        search["description"],
+        "Search the web. Prefer recent sources."
+    );
+    let limit = &search["parameters"]["properties"]["limit"];
+    assert_eq!(limit["maximum"], 5);
+    assert!(limit.get("description").is_none(), "{limit}");
+    let text = &search["parameters"]["properties"]["text"];
+    assert_eq!(text["minLength"], 3);
+    assert_eq!(text["description"], "What to search for.");
+    assert_eq!(find(&patched, "ping"), find(&before, "ping"));
+    assert_eq!(
+        view.get_declaration("search_web").unwrap().parameters,
+        search["parameters"]
+    );
+
+    // The base collection declares what was registered.
+    assert_eq!(tools.json().unwrap(), before);
+    assert_eq!(
+        tools.get_declaration("search_web").unwrap().description,
+        "Searches the web."
+    );
+
+    // Dispatch goes by the registered schema, not the patch.
+    let response = view
+        .call(FunctionCall::new(
+            "search_web".into(),
+            json!({ "text": "ai", "limit": 50 }),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.result, json!("ai (50)"));
+}
+
+#[test]
+fn the_patched_schema_passes_registration_checks() {
+    let tools = tools();
+    let view = tools
+        .with_overrides(DeclOverrides::new().parameters(
+            "search_web",
+            json!({ "properties": { "limit": { "minimum": 1, "default": 5 } } }),
+        ))
+        .unwrap();
+    let declaration = serde_json::to_value(view.get_declaration("search_web").unwrap()).unwrap();
+
+    let mut strict: ToolCollection = ToolCollection::new();
+    strict.set_strict_registration(true).unwrap();
+    strict
+        .register_spec(
+            ToolSpec::from_declaration(&declaration)
+                .unwrap()
+                .handler(|args| Box::pin(async move { Ok(args) })),
+        )
+        .unwrap();
+}
+
+#[test]
+fn invalid_overrides_are_rejected_when_the_view_is_made() {
+    let tools = tools();
+    let err = tools
+        .with_overrides(DeclOverrides::new().parameters(
+            "search_web",
+            json!({ "properties": { "limit": { "type": "integer", "default": "five" } } }),
+        ))
+        .err()
+        .unwrap();
+    let ToolError::RegistrationRejected { tool, findings, .. } = &err else {
+        panic!("{err:?}");
+    };
+    assert_eq!(tool, "search_web");
+    assert_eq!(findings[0].path, "/properties/limit/default");
+
+    let err = tools
+        .with_overrides(
+            DeclOverrides::new().parameters("search_web", json!({ "required": ["query"] })),
+        )
+        .err()
+        .unwrap();
+    assert!(
+        matches!(err, ToolError::RegistrationRejected { .. }),
+        "{err:?}"
+    );
+
+    let err = tools
+        .with_overrides(DeclOverrides::new().description("serach_web", "Typo."))
+        .err()
+        .unwrap();
+    assert!(
+        matches!(&err, ToolError::FunctionNotFound { name } if name == "serach_web"),
+        "{err:?}"
+    );
+
+    // Overrides can come from a file.
+    let typo = serde_json::from_value::<DeclOverrides>(
+        json!({ "tools": { "ping": { "desc": "Pings." } } }),
+    );
+    assert!(typo.is_err());
+}
+
+#[test]
+fn merge_patch_follows_rfc_7386() {
+    // From the examples in RFC 7386, section 3.
+    let cases = [
+        (json!({"a": "b"}), json!({"a": "c"}), json!({"a": "c"})),
+        (
+            json!({"a": "b"}),
+            json!({"b": "c"}),
+            json!({"a": "b", "b": "c"}),
+        ),
+        (json!({"a": "b"}), json!({"a": null}), json!({})),
+        (
+            json!({"a": [{"b": "c"}]}),
+            json!({"a": [1]}),
+            json!({"a": [1]}),
+        ),
+        (json!(["a", "b"]), json!({"a": "c"}), json!({"a": "c"})),
+        (json!({"a": "foo"}), json!("bar"), json!("bar")),
+        (
+            json!({"e": null}),
+            json!({"a": 1}),
+            json!({"e": null, "a": 1}),
+        ),
+        (
+            json!([1, 2]),
+            json!({"a": "b", "c": null}),
+            json!({"a": "b"}),
+        ),
+        (
+            json!({}),
+            json!({"a": {"bb": {"ccc": null}}}),
+            json!({"a": {"bb": {}}}),
+        ),
+    ];
+    for (mut target, patch, expected) in cases {
+        merge_patch(&mut target, &patch);
+        assert_eq!(target, expected, "patch {patch}");
+    }
+}
@@ -0,0 +1,133 @@
+//! Tools defined entirely at runtime: a stored declaration plus a handler
+//! over JSON, with no `ToolSchema` type behind them.
+
+use serde_json::{Value, json};
+use tools_rs::{FunctionCall, SchemaDialect, ToolCollection, ToolError, ToolSpec, UnknownArgs};
+
+/// A definition as it might be loaded from a database row.
+fn stored() -> Value {
+    json!({
+        "type": "function",
+        "function": {
+            "name": "scale",
+            "description": "Multiplies a value by a factor.",
+            "parameters": {
+                "type": "object",
+                "properties": {
+                    "value": { "type": "number", "description": "Number to scale." },
+                    "factor": { "type": "number", "description": "Multiplier." },
+                    "label": {
+                        "anyOf": [{ "type": "string" }, { "type": "null" }],
+                        "description": "Optional label for the result."
+                    }
+                },
+                "required": ["value", "factor"]
+            },
+            "tags": ["math"]
+        }
+    })
+}
+
+/// The "body" of the stored tool, evaluated over the raw arguments.
+fn scale(args: Value) -> Result<Value, ToolError> {
+    let number = |key: &str| {
+        args[key]
+            .as_f64()
+            .ok_or_else(|| ToolError::Runtime(format!("`{key}` is not a number")))
+    };
+    let product = number("value")? * number("factor")?;
+    Ok(match args["label"].as_str() {
+        Some(label) => json!({ label: product }),
+        None => json!(product),
+    })
+}
+
+fn tools() -> ToolCollection {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register_spec(
+            ToolSpec::from_declaration(&stored())
+                .unwrap()
+                .handler(|args| Box::pin(async move { scale(args) })),
+        )
+        .unwrap();
+    tools
+}
+
+fn call(args: Value) -> FunctionCall {
+    FunctionCall::new("scale".into(), args)
+}
+
+#[tokio::test]
+async fn registered_from_a_declaration() {
+    let tools = tools();
+    let entry = tools.get("scale").unwrap();
+    assert!(entry.external_schema);
+    assert_eq!(entry.decl.tags, ["math"]);
+    assert_eq!(entry.decl.parameters, stored()["function"]["parameters"]);
+
+    let out = tools
+        .call(call(json!({ "value": 2.5, "factor": 4 })))
+        .await
+        .unwrap();
+    assert_eq!(out.result, json!(10.0));
+    let out = tools
+        .call(call(json!({ "value": 1, "factor": 3, "label": "total" })))
+        .await
+        .unwrap();
+    assert_eq!(out.result, json!({ "total": 3.0 }));
+
+    let err = tools
+        .call(call(json!({ "value": "two", "factor": 3 })))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ToolError::Runtime(m) if m == "`value` is not a number"));
+}
+
+#[tokio::test]
+async fn the_given_schema_is_checked_against() {
+    let mut tools = tools();
+    tools.set_unknown_args(UnknownArgs::Reject);
+    let err = tools
+        .call(call(json!({ "value": 1, "factr": 3 })))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("did you mean `factor`?"), "{err}");
+}
+
+#[test]
+fn exported_for_two_providers() {
+    let tools = tools();
+    let decl = &tools.get("scale").unwrap().decl;
+
+    // OpenAI: the stored definition round-trips as it was.
+    assert_eq!(decl.to_openai(), stored());
+
+    // Gemini: the OpenAPI 3.0 subset, with the optional label nullable.
+    let gemini = decl.parameters_as(SchemaDialect::OpenApi30).unwrap();
+    let label = &gemini["properties"]["label"];
+    assert_eq!(label["type"], "string");
+    assert_eq!(label["nullable"], true);
+    assert!(label.get("anyOf").is_none());
+}
+
+#[test]
+fn malformed_declarations_are_rejected() {
+    let Err(err) = ToolSpec::from_declaration(&json!({ "name": "scale", "description": "" }))
+    else {
+        panic!("expected an error");
+    };
+    assert_eq!(
+        err.to_string(),
+        "Runtime error: declaration is missing `parameters`"
+    );
+}
+
+#[test]
+fn typed_tools_have_derived_schemas() {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register("double", "Doubles.", |x: i32| async move { x * 2 }, ())
+        .unwrap();
+    assert!(!tools.get("double").unwrap().external_schema);
+}
@@ -0,0 +1,196 @@
+//! Per-deployment settings from environment variables, layered over a
+//! config file and the settings made in code.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use serde_json::json;
+use tools_rs::{
+    FunctionCall, OverrideReport, Overrides, ToolCollection, ToolError, UsageEvent, UsageSink,
+};
+
+fn tools() -> ToolCollection {
+    let mut tools: ToolCollection = ToolCollection::new();
+    for name in ["send_email", "search_web", "debug_dump", "debug_trace"] {
+        tools
+            .register(name, "Does a thing.", |_: ()| async { "ok" }, ())
+            .unwrap();
+    }
+    tools
+        .register(
+            "stall",
+            "Takes a while.",
+            |_: ()| async {
+                tokio::time::sleep(Duration::from_millis(200)).await;
+                "done"
+            },
+            (),
+        )
+        .unwrap();
+    tools
+}
+
+fn env(vars: &[(&str, &str)]) -> HashMap<String, String> {
+    vars.iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+fn names(tools: &ToolCollection) -> Vec<&str> {
+    tools.iter().map(|(name, _)| name).collect()
+}
+
+#[test]
+fn parses_the_variables_under_the_prefix() {
+    let vars = env(&[
+        ("TOOLS_DISABLE", " send_email, debug_* ,"),
+        ("TOOLS_TIMEOUT_MS__SEARCH_WEB", "3000"),
+        ("TOOLS_NAMESPACE", "staging"),
+        ("TOOLS_DISABEL", "oops"),
+        ("OTHER_DISABLE", "search_web"),
+        ("PATH", "/usr/bin"),
+    ]);
+    let overrides = Overrides::from_vars("TOOLS_", &vars).unwrap();
+    assert_eq!(
+        overrides,
+        Overrides {
+            disable: Some(vec!["send_email".into(), "debug_*".into()]),
+            timeout_ms: [("search_web".to_string(), 3000)].into(),
+            namespace: Some("staging".into()),
+            unrecognized: vec!["TOOLS_DISABEL".into()],
+        }
+    );
+
+    let err = Overrides::from_vars("TOOLS", env(&[("TOOLS_TIMEOUT_MS__STALL", "3s")])).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("TOOLS_TIMEOUT_MS__STALL: `3s` is not a whole number"),
+        "{err}"
+    );
+}
+
+#[test]
+fn applies_globs_and_reports_what_matched_nothing() {
+    let mut tools = tools();
+    let vars = env(&[
+        ("TOOLS_DISABLE", "debug_*,admin_*"),
+        ("TOOLS_TIMEOUT_MS__SEARCH_WEB", "3000"),
+        ("TOOLS_TIMEOUT_MS__DEBUG_DUMP", "10"),
+        ("TOOLS_NAMESPACE", "staging"),
+    ]);
+    let report = tools.apply_overrides(&Overrides::from_vars("TOOLS", vars).unwrap());
+    assert_eq!(
+        report,
+        OverrideReport {
+            disabled: vec!["debug_dump".into(), "debug_trace".into()],
+            timeouts: vec![("search_web".into(), Duration::from_millis(3000))],
+            namespace: Some("staging".into()),
+            unmatched_disable: vec!["admin_*".into()],
+            // Disabled before timeouts are applied.
+            unmatched_timeouts: vec!["debug_dump".into()],
+            unrecognized: vec![],
+        }
+    );
+    assert!(!report.is_clean());
+    assert_eq!(names(&tools), ["send_email", "search_web", "stall"]);
+    assert_eq!(
+        tools.get("search_web").unwrap().timeout,
+        Some(Duration::from_secs(3))
+    );
+    assert_eq!(tools.namespace(), Some("staging"));
+
+    // Nothing set, nothing changed.
+    let report = tools.apply_overrides(&Overrides::default());
+    assert!(report.is_clean());
+    assert_eq!(tools.namespace(), Some("staging"));
+}
+
+#[test]
+fn env_over_config_file_over_code() {
+    let mut tools = tools();
+    // Code.
+    tools
+        .set_timeout("search_web", Duration::from_millis(1000))
+        .unwrap();
+    tools
+        .set_timeout("send_email", Duration::from_millis(1000))
+        .unwrap();
+    tools.set_namespace(Some("dev".into()));
+    // Config file.
+    let file: Overrides = serde_json::from_value(json!({
+        "disable": ["debug_*"],
+        "timeout_ms": { "search_web": 2000, "stall": 500 },
+        "namespace": "prod"
+    }))
+    .unwrap();
+    // Environment: re-enables everything, and wins for `search_web`.
+    let vars = env(&[
+        ("TOOLS_DISABLE", ""),
+        ("TOOLS_TIMEOUT_MS__SEARCH_WEB", "3000"),
+    ]);
+    let layered = Overrides::from_vars("TOOLS", vars).unwrap().over(file);
+    let report = tools.apply_overrides(&layered);
+
+    assert!(report.disabled.is_empty());
+    assert_eq!(names(&tools).len(), 5);
+    let timeout = |name| tools.get(name).unwrap().timeout.unwrap().as_millis();
+    assert_eq!(timeout("search_web"), 3000);
+    assert_eq!(timeout("stall"), 500);
+    assert_eq!(timeout("send_email"), 1000);
+    assert_eq!(tools.namespace(), Some("prod"));
+
+    // An empty namespace removes it.
+    tools.apply_overrides(&Overrides::from_vars("TOOLS", env(&[("TOOLS_NAMESPACE", "")])).unwrap());
+    assert_eq!(tools.namespace(), None);
+
+    // Unknown keys in a config file are errors.
+    let typo = serde_json::from_value::<Overrides>(json!({ "disabled": ["x"] }));
+    assert!(typo.is_err());
+}
+
+#[tokio::test]
+async fn tool_timeout_replaces_the_default() {
+    let mut tools = tools();
+    tools.set_default_timeout(Duration::from_millis(20));
+    let call = || FunctionCall::new("stall".into(), json!(null));
+    let err = tools.call(call()).await.unwrap_err();
+    assert!(matches!(err, ToolError::Timeout { ref name, .. } if name == "stall"));
+
+    let vars = env(&[("TOOLS_TIMEOUT_MS__STALL", "5000")]);
+    tools.apply_overrides(&Overrides::from_vars("TOOLS", vars).unwrap());
+    assert_eq!(tools.call(call()).await.unwrap().result, json!("done"));
+
+    tools.clear_timeout("stall").unwrap();
+    assert!(tools.call(call()).await.is_err());
+}
+
+#[derive(Default)]
+struct Events(Mutex<Vec<UsageEvent>>);
+
+impl UsageSink for Events {
+    fn record(&self, event: UsageEvent) {
+        self.0.lock().unwrap().push(event);
+    }
+}
+
+#[tokio::test]
+async fn usage_events_carry_the_namespace() {
+    let mut tools = tools();
+    let events = Arc::new(Events::default());
+    tools.set_usage_sink(events.clone());
+    let call = || FunctionCall::new("send_email".into(), json!(null));
+    tools.call(call()).await.unwrap();
+    tools.apply_overrides(
+        &Overrides::from_vars("TOOLS", env(&[("TOOLS_NAMESPACE", "staging")])).unwrap(),
+    );
+    tools.call(call()).await.unwrap();
+
+    let events = events.0.lock().unwrap();
+    assert_eq!(events[0].namespace, None);
+    assert_eq!(events[1].namespace.as_deref(), Some("staging"));
+    let line = serde_json::to_value(&events[0]).unwrap();
+    assert!(line.get("namespace").is_none(), "{line}");
+}
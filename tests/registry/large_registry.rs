@@ -0,0 +1,103 @@
+//! Large generated registries: pre-sizing, shrinking, and a guard against
+//! super-linear construction.
+
+use serde_json::json;
+use std::time::{Duration, Instant};
+use tools_rs::{FunctionCall, ToolCollection};
+
+fn names(n: usize) -> Vec<&'static str> {
+    (0..n)
+        .map(|i| &*Box::leak(format!("api_operation_{i}").into_boxed_str()))
+        .collect()
+}
+
+fn build(names: &[&'static str], presized: bool) -> (ToolCollection, Duration) {
+    let start = Instant::now();
+    let mut tools = if presized {
+        ToolCollection::with_capacity(names.len())
+    } else {
+        ToolCollection::new()
+    };
+    for &name in names {
+        tools
+            .register_raw(
+                name,
+                "Generated operation.",
+                json!({ "type": "object", "properties": {} }),
+                |v| Box::pin(async move { Ok(v) }),
+                (),
+            )
+            .unwrap();
+    }
+    (tools, start.elapsed())
+}
+
+// ---------- capacity ----------
+
+#[test]
+fn with_capacity_does_not_grow_while_registering() {
+    let names = names(5_000);
+    let mut tools: ToolCollection = ToolCollection::with_capacity(names.len());
+    let initial = tools.capacity();
+    assert!(initial >= 5_000);
+
+    let (filled, _) = build(&names, true);
+    assert_eq!(filled.len(), 5_000);
+    assert_eq!(filled.capacity(), initial);
+
+    tools.shrink_to_fit();
+    assert_eq!(tools.capacity(), 0);
+}
+
+#[test]
+fn shrink_to_fit_keeps_live_ids() {
+    let names = names(100);
+    let (mut tools, _) = build(&names, false);
+    let keep = tools.resolve(names[10]).unwrap();
+    for name in &names[50..] {
+        tools.unregister(name).unwrap();
+    }
+
+    tools.shrink_to_fit();
+    assert_eq!(tools.len(), 50);
+    assert!(tools.capacity() < 100);
+    assert_eq!(tools.resolve(names[10]), Some(keep));
+    assert_eq!(tools.get_id(keep).unwrap().decl.name, names[10]);
+}
+
+// ---------- construction cost ----------
+
+#[test]
+fn construction_scales_roughly_linearly() {
+    let small = names(500);
+    let large = names(5_000);
+
+    // Take the best of a few runs to keep scheduler noise out of the ratio.
+    let best = |names: &[&'static str]| {
+        (0..3)
+            .map(|_| build(names, true).1)
+            .min()
+            .unwrap()
+            .max(Duration::from_micros(1))
+    };
+    let ratio = best(&large).as_secs_f64() / best(&small).as_secs_f64();
+
+    // 10x the tools: linear is ~10x, quadratic would be ~100x.
+    assert!(ratio < 40.0, "5k/500 construction ratio was {ratio:.1}");
+}
+
+#[tokio::test]
+async fn calls_work_across_a_large_registry() {
+    let names = names(5_000);
+    let (mut tools, _) = build(&names, true);
+    tools.shrink_to_fit();
+
+    let resp = tools
+        .call(FunctionCall::new(
+            names[4_321].to_string(),
+            json!({ "ok": true }),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(resp.result, json!({ "ok": true }));
+}
@@ -0,0 +1,117 @@
+//! `register_lazy` builds a tool's state on its first call, once, and
+//! reruns a failed initialization on the next call.
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use serde_json::json;
+use tools_rs::{ErrorClass, FunctionCall, ToolCollection, ToolError};
+
+/// A client that is expensive to construct.
+struct Client {
+    prefix: String,
+}
+
+/// Registers `lookup`, whose client construction is counted in `inits`
+/// and fails while `failures` is above zero.
+fn tools(inits: Arc<AtomicUsize>, failures: Arc<AtomicUsize>) -> ToolCollection {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register_lazy(
+            "lookup",
+            "Looks a key up.",
+            move || {
+                let inits = inits.clone();
+                let failures = failures.clone();
+                async move {
+                    inits.fetch_add(1, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                    if failures
+                        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                        .is_ok()
+                    {
+                        return Err("connection refused");
+                    }
+                    Ok(Client {
+                        prefix: "value of".into(),
+                    })
+                }
+            },
+            |client: Arc<Client>, key: String| async move { format!("{} {key}", client.prefix) },
+            (),
+        )
+        .unwrap();
+    tools
+}
+
+fn lookup(key: &str) -> FunctionCall {
+    FunctionCall::new("lookup".into(), json!(key))
+}
+
+// ---------- initialization ----------
+
+#[tokio::test]
+async fn init_waits_for_the_first_call() {
+    let inits = Arc::new(AtomicUsize::new(0));
+    let tools = tools(inits.clone(), Arc::default());
+    assert_eq!(inits.load(Ordering::SeqCst), 0);
+
+    let response = tools.call(lookup("a")).await.unwrap();
+    assert_eq!(response.result, "value of a");
+    tools.call(lookup("b")).await.unwrap();
+    assert_eq!(inits.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+async fn concurrent_first_calls_share_one_init() {
+    let inits = Arc::new(AtomicUsize::new(0));
+    let tools = Arc::new(tools(inits.clone(), Arc::default()));
+    let handles: Vec<_> = (0..64)
+        .map(|i| {
+            let tools = tools.clone();
+            tokio::spawn(async move { tools.call(lookup(&i.to_string())).await })
+        })
+        .collect();
+    for (i, handle) in handles.into_iter().enumerate() {
+        let response = handle.await.unwrap().unwrap();
+        assert_eq!(response.result, format!("value of {i}"));
+    }
+    assert_eq!(inits.load(Ordering::SeqCst), 1);
+}
+
+// ---------- failures ----------
+
+#[tokio::test]
+async fn failed_init_is_transient_and_retried() {
+    let inits = Arc::new(AtomicUsize::new(0));
+    let tools = tools(inits.clone(), Arc::new(AtomicUsize::new(1)));
+
+    let err = tools.call(lookup("a")).await.unwrap_err();
+    assert!(matches!(err, ToolError::RuntimeClassified { .. }));
+    assert_eq!(err.classification(), ErrorClass::Transient);
+    assert!(err.to_string().contains("connection refused"), "{err}");
+
+    assert_eq!(tools.call(lookup("a")).await.unwrap().result, "value of a");
+    tools.call(lookup("b")).await.unwrap();
+    assert_eq!(inits.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn handles_invoke_lazy_tools() {
+    let mut tools: ToolCollection = ToolCollection::new();
+    let double = tools
+        .register_lazy(
+            "double",
+            "Doubles a number.",
+            || async { Ok::<_, String>(2) },
+            |factor: Arc<i64>, n: i64| async move { n * *factor },
+            (),
+        )
+        .unwrap();
+    assert_eq!(tools.invoke(&double, 21).await.unwrap(), 42);
+}
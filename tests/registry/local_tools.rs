@@ -0,0 +1,103 @@
+//! `register_local` accepts closures that are not `Sync` and runs their
+//! calls one at a time.
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use serde_json::json;
+use tools_rs::{FunctionCall, ToolCollection};
+
+/// Registers `count`, which tallies words in a `RefCell` and tracks how
+/// many of its calls are running at once in `running`/`peak`.
+fn tools(running: Arc<AtomicUsize>, peak: Arc<AtomicUsize>) -> ToolCollection {
+    let counts: RefCell<HashMap<String, u32>> = RefCell::default();
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register_local(
+            "count",
+            "Counts how often a word was seen.",
+            move |word: String| {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                let mut counts = counts.borrow_mut();
+                let seen = counts.entry(word).or_default();
+                *seen += 1;
+                let seen = *seen;
+                let running = running.clone();
+                async move {
+                    tokio::time::sleep(Duration::from_millis(1)).await;
+                    running.fetch_sub(1, Ordering::SeqCst);
+                    seen
+                }
+            },
+            (),
+        )
+        .unwrap();
+    tools
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn concurrent_calls_run_one_at_a_time() {
+    const CALLS: usize = 40;
+    let running = Arc::new(AtomicUsize::new(0));
+    let peak = Arc::new(AtomicUsize::new(0));
+    let tools = Arc::new(tools(running.clone(), peak.clone()));
+
+    let handles: Vec<_> = (0..CALLS)
+        .map(|i| {
+            let tools = tools.clone();
+            let word = if i % 2 == 0 { "even" } else { "odd" };
+            tokio::spawn(async move {
+                tools
+                    .call(FunctionCall::new("count".into(), json!(word)))
+                    .await
+                    .unwrap()
+                    .result
+            })
+        })
+        .collect();
+    let mut results = Vec::new();
+    for handle in handles {
+        results.push(handle.await.unwrap().as_u64().unwrap());
+    }
+
+    // Every call saw a distinct count: no update was lost.
+    results.sort_unstable();
+    let expected: Vec<u64> = (1..=CALLS as u64 / 2).flat_map(|n| [n, n]).collect();
+    assert_eq!(results, expected);
+    // A call's future finished before the next one started.
+    assert_eq!(peak.load(Ordering::SeqCst), 1);
+
+    let queue = tools.queue("count").unwrap();
+    assert_eq!(queue.calls, CALLS as u64);
+    assert_eq!(queue.waiting, 0);
+    assert!(queue.max_wait_us > 0);
+    assert!(queue.total_wait_us >= queue.max_wait_us);
+}
+
+#[tokio::test]
+async fn queue_stats_are_reported() {
+    let tools = tools(Arc::default(), Arc::default());
+    tools
+        .call(FunctionCall::new("count".into(), json!("a")))
+        .await
+        .unwrap();
+    let report = serde_json::to_value(tools.introspect()).unwrap();
+    assert_eq!(report["tools"][0]["queue"]["calls"], 1);
+    assert_eq!(report["tools"][0]["queue"]["waiting"], 0);
+
+    // Bad arguments are rejected before queueing.
+    tools
+        .call(FunctionCall::new("count".into(), json!(1)))
+        .await
+        .unwrap_err();
+    assert_eq!(tools.queue("count").unwrap().calls, 1);
+    assert_eq!(tools.queue("missing"), None);
+}
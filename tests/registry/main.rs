@@ -0,0 +1,17 @@
+//! Building and changing a collection: names, runtime and scoped tools,
+//! sharing, profiles and per-deployment overrides.
+
+#[path = "../common/mod.rs"]
+mod common;
+
+mod agent_profiles;
+mod decl_overrides;
+mod dynamic_tools;
+mod env_overrides;
+mod large_registry;
+mod lazy_tools;
+mod local_tools;
+mod name_registry;
+mod runtime_names;
+mod scoped_tools;
+mod shared_collection;
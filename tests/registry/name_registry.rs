@@ -0,0 +1,223 @@
+//! Tool names and aliases never become ambiguous: registrations, aliases
+//! and normalization changes that would let one name reach two tools are
+//! rejected.
+
+use std::{borrow::Cow, collections::HashMap};
+
+use serde_json::json;
+use tools_rs::{FunctionCall, NameNormalization, ToolCollection, ToolError, ToolSpec};
+
+fn spec(name: &str) -> ToolSpec {
+    let echo = name.to_string();
+    ToolSpec::new(name.to_string(), "Test tool.").handler(move |_| {
+        let echo = echo.clone();
+        Box::pin(async move { Ok(json!(echo)) })
+    })
+}
+
+fn tools(normalization: NameNormalization) -> ToolCollection {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools.set_name_normalization(normalization).unwrap();
+    tools.register_spec(spec("get_weather")).unwrap();
+    tools.register_spec(spec("search_web")).unwrap();
+    tools
+}
+
+fn conflict(err: ToolError) -> (String, String, Cow<'static, str>) {
+    match err {
+        ToolError::NameConflict {
+            name,
+            existing,
+            tool,
+            ..
+        } => (name, existing, tool),
+        err => panic!("unexpected error: {err}"),
+    }
+}
+
+// ---------- lookup ----------
+
+#[tokio::test]
+async fn normalized_names_and_aliases_resolve() {
+    let exact = tools(NameNormalization::Exact);
+    assert!(exact.resolve("getWeather").is_none());
+
+    let mut tools = tools(NameNormalization::IgnoreCaseAndSeparators);
+    tools.add_alias("weather", "get_weather").unwrap();
+
+    let id = tools.resolve("get_weather").unwrap();
+    for name in ["getWeather", "Get-Weather", "weather", "WEATHER"] {
+        assert_eq!(tools.resolve(name), Some(id), "{name}");
+    }
+    let out = tools
+        .call(FunctionCall::new("GetWeather".into(), json!({})))
+        .await
+        .unwrap();
+    assert_eq!(out.result, "get_weather");
+}
+
+// ---------- conflicts ----------
+
+#[test]
+fn registrations_colliding_after_normalization_are_rejected() {
+    let mut tools = tools(NameNormalization::IgnoreCaseAndSeparators);
+    let err = tools.register_spec(spec("GetWeather")).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "name `GetWeather` conflicts with `get_weather` (tool `get_weather`) under ignore case and separators matching"
+    );
+    assert_eq!(tools.len(), 2);
+
+    // Re-registering under the exact name is still a plain duplicate.
+    assert!(matches!(
+        tools.register_spec(spec("get_weather")).unwrap_err(),
+        ToolError::AlreadyRegistered { .. }
+    ));
+}
+
+#[test]
+fn aliases_cannot_shadow_other_tools() {
+    let mut tools = tools(NameNormalization::IgnoreCase);
+    tools.add_alias("web", "search_web").unwrap();
+
+    let err = tools.add_alias("WEB", "get_weather").unwrap_err();
+    assert_eq!(
+        conflict(err),
+        ("WEB".into(), "web".into(), "search_web".into())
+    );
+    let err = tools.add_alias("Search_Web", "get_weather").unwrap_err();
+    assert_eq!(
+        conflict(err),
+        (
+            "Search_Web".into(),
+            "search_web".into(),
+            "search_web".into()
+        )
+    );
+    // A tool may have several spellings of the same alias.
+    tools.add_alias("Web", "search_web").unwrap();
+
+    // Nor can new tools take an alias.
+    let err = tools.register_spec(spec("Web")).unwrap_err();
+    assert_eq!(conflict(err).2, "search_web");
+
+    assert!(matches!(
+        tools.add_alias("x", "ghost").unwrap_err(),
+        ToolError::FunctionNotFound { .. }
+    ));
+}
+
+#[test]
+fn aliases_go_with_their_tool() {
+    let mut tools = tools(NameNormalization::Exact);
+    tools.add_alias("web", "search_web").unwrap();
+    tools.unregister("search_web").unwrap();
+    assert!(tools.resolve("web").is_none());
+    tools.register_spec(spec("web")).unwrap();
+
+    tools.add_alias("weather", "get_weather").unwrap();
+    tools.remove_alias("weather").unwrap();
+    assert!(tools.resolve("weather").is_none());
+    assert!(tools.remove_alias("weather").is_err());
+}
+
+#[test]
+fn loosening_normalization_revalidates() {
+    let mut tools = tools(NameNormalization::Exact);
+    tools.register_spec(spec("getWeather")).unwrap();
+
+    let err = tools
+        .set_name_normalization(NameNormalization::IgnoreCaseAndSeparators)
+        .unwrap_err();
+    assert!(matches!(
+        &err,
+        ToolError::NameConflict {
+            normalization: NameNormalization::IgnoreCaseAndSeparators,
+            ..
+        }
+    ));
+    // The old setting is kept.
+    assert_eq!(tools.names().normalization(), NameNormalization::Exact);
+    assert!(tools.resolve("GET_WEATHER").is_none());
+
+    // Case alone is not enough to collide these two.
+    tools
+        .set_name_normalization(NameNormalization::IgnoreCase)
+        .unwrap();
+    assert_eq!(tools.resolve("GETWEATHER"), tools.resolve("getWeather"));
+}
+
+// ---------- properties ----------
+
+/// Small deterministic PRNG so failures reproduce.
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Short names over an alphabet rich in near-collisions.
+    fn name(&mut self) -> String {
+        const ALPHABET: &[char] = &['a', 'A', 'b', 'B', '_', '-'];
+        let len = 1 + self.next() % 4;
+        (0..len)
+            .map(|_| ALPHABET[(self.next() % ALPHABET.len() as u64) as usize])
+            .collect()
+    }
+}
+
+#[test]
+fn no_name_ever_reaches_two_tools() {
+    let mut rng = XorShift(0x9E37_79B9_7F4A_7C15);
+    for normalization in [
+        NameNormalization::Exact,
+        NameNormalization::IgnoreCase,
+        NameNormalization::IgnoreCaseAndSeparators,
+    ] {
+        for _ in 0..200 {
+            let mut tools: ToolCollection = ToolCollection::new();
+            tools.set_name_normalization(normalization).unwrap();
+            // Normalized key → canonical name of the tool holding it.
+            let mut model: HashMap<String, String> = HashMap::new();
+
+            for _ in 0..12 {
+                let name = rng.name();
+                let key = normalization.normalize(&name).into_owned();
+                let alias = rng.next() % 3 == 2;
+                let result = match (alias, model.values().next().cloned()) {
+                    (true, Some(target)) => tools
+                        .add_alias(&name, &target)
+                        .map(|_| (key.clone(), target)),
+                    _ => tools
+                        .register_spec(spec(&name))
+                        .map(|_| (key.clone(), name.clone())),
+                };
+                match (model.get(&key), result) {
+                    (None, Ok((key, owner))) => {
+                        model.insert(key, owner);
+                    }
+                    (Some(owner), Ok((_, target))) => assert_eq!(owner, &target, "{name}"),
+                    (Some(owner), Err(ToolError::NameConflict { tool, .. })) => {
+                        assert_eq!(*owner, tool, "{name}")
+                    }
+                    (Some(_), Err(ToolError::AlreadyRegistered { .. })) => {}
+                    (held, Err(err)) => panic!("{name} rejected ({held:?}): {err}"),
+                }
+            }
+
+            for (key, owner) in &model {
+                assert_eq!(tools.resolve(key), tools.resolve(owner), "{key}");
+            }
+            assert_eq!(tools.len(), {
+                let mut owners: Vec<_> = model.values().collect();
+                owners.sort();
+                owners.dedup();
+                owners.len()
+            });
+        }
+    }
+}
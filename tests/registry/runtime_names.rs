@@ -0,0 +1,130 @@
+//! Tools whose names are only known at runtime, such as names read from a
+//! config file.
+
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tools_rs::{FunctionCall, ToolCollection, ToolError, ToolSchema};
+
+fn from_config() -> Vec<String> {
+    "lookup_user,lookup_order"
+        .split(',')
+        .map(str::to_string)
+        .collect()
+}
+
+#[tokio::test]
+async fn registers_and_calls_owned_names() {
+    let mut tools: ToolCollection = ToolCollection::new();
+    for name in from_config() {
+        let description = format!("Looks up a {}.", name.trim_start_matches("lookup_"));
+        tools
+            .register(name.clone(), description, |id: u32| async move { id }, ())
+            .unwrap();
+    }
+
+    let name = String::from("lookup_order");
+    assert_eq!(
+        tools.get(&name).unwrap().decl.description,
+        "Looks up a order."
+    );
+    let response = tools
+        .call(FunctionCall::new(name.clone(), json!(7)))
+        .await
+        .unwrap();
+    assert_eq!(response.result, json!(7));
+    let declarations = tools.json().unwrap().to_string();
+    assert!(declarations.contains("\"lookup_user\""), "{declarations}");
+
+    let err = tools
+        .register(name.clone(), "Again.", |_: ()| async {}, ())
+        .unwrap_err();
+    assert!(
+        matches!(&err, ToolError::AlreadyRegistered { name, .. } if name == "lookup_order"),
+        "{err:?}"
+    );
+
+    tools.unregister(&name).unwrap();
+    assert!(tools.get(&name).is_none());
+}
+
+#[test]
+fn static_names_are_borrowed_and_runtime_names_owned() {
+    const NAME: &str = "ping";
+    let mut tools: ToolCollection = ToolCollection::new();
+    let ping = tools
+        .register(NAME, "Pings.", |_: ()| async {}, ())
+        .unwrap();
+    assert!(std::ptr::eq(ping.name(), NAME));
+    assert!(matches!(
+        tools.get(NAME).unwrap().decl.name,
+        Cow::Borrowed(_)
+    ));
+
+    // The entry owns a runtime name, and unregistering drops it.
+    for _ in 0..3 {
+        let pong = tools
+            .register(
+                String::from("pong"),
+                "Pongs.".to_string(),
+                |_: ()| async {},
+                (),
+            )
+            .unwrap();
+        assert_eq!(pong.name(), "pong");
+        let decl = &tools.get("pong").unwrap().decl;
+        assert!(matches!(decl.name, Cow::Owned(_)));
+        assert!(matches!(decl.description, Cow::Owned(_)));
+        tools.unregister("pong").unwrap();
+    }
+}
+
+#[test]
+fn borrowed_static_names_still_work() {
+    const NAMES: [&str; 2] = ["one", "two"];
+    let mut tools: ToolCollection = ToolCollection::new();
+    for name in NAMES {
+        tools
+            .register(name, "Does it.", |_: ()| async {}, ())
+            .unwrap();
+    }
+    let names: Vec<_> = tools.iter().map(|(name, _)| name).collect();
+    assert_eq!(names, NAMES);
+}
+
+#[derive(Serialize, Deserialize, ToolSchema)]
+struct Scale {
+    x: i64,
+    by: i64,
+}
+
+#[tokio::test]
+async fn bound_and_cleanup_registrations_take_runtime_names() {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register_with_cleanup(
+            String::from("scale"),
+            String::from("Scales."),
+            |s: Scale| async move { s.x * s.by },
+            (),
+            || Box::pin(async {}),
+        )
+        .unwrap();
+    let name = String::from("scale_by_ten");
+    tools
+        .register_bound(
+            name.clone(),
+            format!("{name}."),
+            "scale",
+            json!({ "by": 10 }),
+        )
+        .unwrap();
+
+    let out = tools
+        .call(FunctionCall::new(name.clone(), json!({ "x": 4 })))
+        .await
+        .unwrap();
+    assert_eq!(out.result, json!(40));
+    assert!(matches!(tools.get(&name).unwrap().decl.name, Cow::Owned(_)));
+}
@@ -0,0 +1,180 @@
+//! `register_scoped` and the `ToolGuard` it returns.
+
+use std::sync::{Arc, Mutex};
+
+use serde_json::{Value, json};
+use tokio::sync::Semaphore;
+use tools_rs::{SharedToolCollection, ToolCollection, ToolError, ToolSpec};
+
+use crate::common::call;
+
+fn shared() -> SharedToolCollection {
+    SharedToolCollection::new(ToolCollection::new())
+}
+
+// ---------- drop and keep ----------
+
+#[tokio::test]
+async fn dropping_the_guard_unregisters() {
+    let tools = shared();
+    {
+        let guard = tools
+            .register_scoped("cart", "Session cart.", |n: i32| async move { n + 1 }, ())
+            .unwrap();
+        assert_eq!(guard.name(), "cart");
+        let out = guard.call(call("cart", json!(1))).await.unwrap();
+        assert_eq!(out.result, json!(2));
+    }
+    assert!(tools.snapshot().resolve("cart").is_none());
+    assert!(matches!(
+        tools.call(call("cart", json!(1))).await,
+        Err(ToolError::FunctionNotFound { .. })
+    ));
+}
+
+#[test]
+fn keep_disarms_the_guard() {
+    let tools = shared();
+    let guard = tools
+        .register_scoped("cart", "Session cart.", |n: i32| async move { n }, ())
+        .unwrap();
+    let id = guard.id();
+    guard.keep();
+    assert_eq!(tools.snapshot().resolve("cart"), Some(id));
+}
+
+#[test]
+fn replacement_through_the_guard_survives_drop() {
+    let tools = shared();
+    {
+        let guard = tools
+            .register_scoped("cart", "Session cart.", |n: i32| async move { n }, ())
+            .unwrap();
+        guard.unregister("cart").unwrap();
+        guard
+            .register("cart", "Permanent cart.", |n: i32| async move { n }, ())
+            .unwrap();
+    }
+    assert_eq!(
+        tools.snapshot().get("cart").unwrap().decl.description,
+        "Permanent cart."
+    );
+}
+
+#[test]
+fn name_collision_is_rejected() {
+    let tools = shared();
+    tools
+        .register("cart", "Global cart.", |n: i32| async move { n }, ())
+        .unwrap();
+    assert!(matches!(
+        tools.register_scoped("cart", "Session cart.", |n: i32| async move { n }, ()),
+        Err(ToolError::AlreadyRegistered { .. })
+    ));
+    assert!(tools.snapshot().resolve("cart").is_some());
+}
+
+// ---------- in-flight calls ----------
+
+#[tokio::test]
+async fn in_flight_calls_outlive_the_guard() {
+    let gate = Arc::new(Semaphore::new(0));
+    let started = Arc::new(Semaphore::new(0));
+    let tools = shared();
+    let guard = {
+        let (gate, started) = (gate.clone(), started.clone());
+        tools
+            .register_scoped(
+                "slow",
+                "Waits for the test.",
+                move |n: i32| {
+                    let (gate, started) = (gate.clone(), started.clone());
+                    async move {
+                        started.add_permits(1);
+                        gate.acquire().await.unwrap().forget();
+                        n * 10
+                    }
+                },
+                (),
+            )
+            .unwrap()
+    };
+
+    let calls: Vec<_> = (0..8)
+        .map(|n| {
+            let worker = tools.clone();
+            tokio::spawn(async move { worker.call(call("slow", json!(n))).await })
+        })
+        .collect();
+    started.acquire_many(8).await.unwrap().forget();
+
+    // Removed while all eight are running on it.
+    drop(guard);
+    assert!(tools.snapshot().resolve("slow").is_none());
+    assert!(matches!(
+        tools.call(call("slow", json!(1))).await,
+        Err(ToolError::FunctionNotFound { .. })
+    ));
+
+    gate.add_permits(8);
+    for (n, task) in calls.into_iter().enumerate() {
+        let out = task.await.unwrap().unwrap();
+        assert_eq!(out.result, json!(n * 10));
+    }
+}
+
+#[tokio::test]
+async fn cleanup_starts_after_in_flight_calls_return() {
+    let gate = Arc::new(Semaphore::new(0));
+    let started = Arc::new(Semaphore::new(0));
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let tools = shared();
+    let (in_call, in_cleanup) = (log.clone(), log.clone());
+    let (gate_in, started_in) = (gate.clone(), started.clone());
+    tools
+        .register_spec(
+            ToolSpec::new("slow", "Waits for the test.")
+                .handler(move |n: Value| {
+                    let (gate, started, log) =
+                        (gate_in.clone(), started_in.clone(), in_call.clone());
+                    Box::pin(async move {
+                        started.add_permits(1);
+                        gate.acquire().await.unwrap().forget();
+                        log.lock().unwrap().push("returned");
+                        Ok(n)
+                    })
+                })
+                .cleanup(move || {
+                    Box::pin(async move { in_cleanup.lock().unwrap().push("cleanup") })
+                }),
+        )
+        .unwrap();
+
+    let calls: Vec<_> = (0..2)
+        .map(|n| {
+            let worker = tools.clone();
+            tokio::spawn(async move { worker.call(call("slow", json!(n))).await })
+        })
+        .collect();
+    started.acquire_many(2).await.unwrap().forget();
+
+    tools.unregister("slow").unwrap();
+    gate.add_permits(1);
+    while log.lock().unwrap().is_empty() {
+        tokio::task::yield_now().await;
+    }
+    for _ in 0..10 {
+        tokio::task::yield_now().await;
+    }
+    // One call is still running.
+    assert_eq!(*log.lock().unwrap(), ["returned"]);
+
+    gate.add_permits(1);
+    for task in calls {
+        task.await.unwrap().unwrap();
+    }
+    while log.lock().unwrap().len() < 3 {
+        tokio::task::yield_now().await;
+    }
+    assert_eq!(*log.lock().unwrap(), ["returned", "returned", "cleanup"]);
+}
@@ -0,0 +1,137 @@
+//! `SharedToolCollection`: calling and changing one collection from many
+//! tasks.
+
+use std::{sync::Arc, time::Duration};
+
+use serde_json::json;
+use tokio::sync::Semaphore;
+use tools_rs::{FunctionCall, SharedToolCollection, ToolCollection, ToolError};
+
+fn call(name: &str) -> FunctionCall {
+    FunctionCall::new(name.into(), json!(null))
+}
+
+fn shared() -> SharedToolCollection {
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register("fast", "Answers at once.", |_: ()| async { "fast" }, ())
+        .unwrap();
+    tools
+        .register(
+            "slow",
+            "Takes a moment.",
+            |_: ()| async {
+                tokio::time::sleep(Duration::from_millis(2)).await;
+                "slow"
+            },
+            (),
+        )
+        .unwrap();
+    SharedToolCollection::from(tools)
+}
+
+#[test]
+fn is_a_clone_send_sync_handle() {
+    fn assert_handle<T: Clone + Send + Sync + 'static>() {}
+    assert_handle::<SharedToolCollection>();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn unregistering_while_calls_run_neither_deadlocks_nor_loses_calls() {
+    let tools = shared();
+    let work = async {
+        let callers: Vec<_> = (0..8)
+            .map(|i| {
+                let tools = tools.clone();
+                tokio::spawn(async move {
+                    for _ in 0..200 {
+                        let name = if i % 2 == 0 { "fast" } else { "slow" };
+                        match tools.call(call(name)).await {
+                            Ok(response) => assert_eq!(response.result, json!(name)),
+                            Err(ToolError::FunctionNotFound { name: missing }) => {
+                                assert_eq!(missing, "fast")
+                            }
+                            Err(e) => panic!("{e:?}"),
+                        }
+                    }
+                })
+            })
+            .collect();
+        let remover = {
+            let tools = tools.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                tools.unregister("fast").unwrap();
+            })
+        };
+        remover.await.unwrap();
+        for caller in callers {
+            caller.await.unwrap();
+        }
+    };
+    tokio::time::timeout(Duration::from_secs(30), work)
+        .await
+        .expect("calls and unregister deadlocked");
+
+    let err = tools.call(call("fast")).await.unwrap_err();
+    assert!(
+        matches!(&err, ToolError::FunctionNotFound { name } if name == "fast"),
+        "{err:?}"
+    );
+    assert_eq!(
+        tools.call(call("slow")).await.unwrap().result,
+        json!("slow")
+    );
+    let declarations = tools.json().unwrap().to_string();
+    assert!(declarations.contains("\"slow\""), "{declarations}");
+    assert!(!declarations.contains("\"fast\""), "{declarations}");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn a_long_call_does_not_block_writers() {
+    let tools = shared();
+    let (started, gate) = (Arc::new(Semaphore::new(0)), Arc::new(Semaphore::new(0)));
+    {
+        let (started, gate) = (started.clone(), gate.clone());
+        tools
+            .register(
+                "gated",
+                "Waits for the test.",
+                move |_: ()| {
+                    let (started, gate) = (started.clone(), gate.clone());
+                    async move {
+                        started.add_permits(1);
+                        let _permit = gate.acquire().await.unwrap();
+                        "through"
+                    }
+                },
+                (),
+            )
+            .unwrap();
+    }
+    let in_flight = tokio::spawn({
+        let tools = tools.clone();
+        async move { tools.call(call("gated")).await }
+    });
+    started.acquire().await.unwrap().forget();
+
+    // The tool is still running; changing the collection doesn't wait for it.
+    tools.unregister("gated").unwrap();
+    tools
+        .register("later", "Added afterwards.", |_: ()| async { 1 }, ())
+        .unwrap();
+    assert!(matches!(
+        tools.call(call("gated")).await,
+        Err(ToolError::FunctionNotFound { .. })
+    ));
+
+    // The call that started before the removal still finishes.
+    gate.add_permits(1);
+    let response = tokio::time::timeout(Duration::from_secs(5), in_flight)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert_eq!(response.result, json!("through"));
+    assert_eq!(tools.call(call("later")).await.unwrap().result, json!(1));
+}
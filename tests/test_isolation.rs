@@ -0,0 +1,118 @@
+//! Per-test collections built from the global `#[tool]` inventory.
+//!
+//! Every test in this binary sees the same inventory; the tests below use
+//! overlapping tools and register manual tools under the same names without
+//! interfering with each other.
+
+use serde::Deserialize;
+use serde_json::json;
+use tools_rs::{FunctionCall, ToolCollection, ToolError, test_tools, tool};
+
+/// Adds two numbers.
+#[tool]
+async fn add(a: i32, b: i32) -> i32 {
+    a + b
+}
+
+/// Greets someone.
+#[tool]
+async fn greet(name: String) -> String {
+    format!("Hello, {name}!")
+}
+
+/// Shouts.
+#[tool(dangerous = true)]
+async fn shout(text: String) -> String {
+    text.to_uppercase()
+}
+
+fn call(name: &str, arguments: serde_json::Value) -> FunctionCall {
+    FunctionCall::new(name.to_string(), arguments)
+}
+
+// ---------- overlapping tests ----------
+
+#[tokio::test]
+async fn first_user_of_add() {
+    let mut tools = test_tools![add, greet];
+    assert_eq!(tools.len(), 2);
+    assert!(tools.resolve("shout").is_none());
+
+    tools
+        .register("stub", "First stub.", |x: i32| async move { x }, ())
+        .unwrap();
+    let out = tools
+        .call(call("add", json!({ "a": 2, "b": 3 })))
+        .await
+        .unwrap();
+    assert_eq!(out.result, json!(5));
+}
+
+#[tokio::test]
+async fn second_user_of_add() {
+    let mut tools = test_tools![add, shout];
+    assert_eq!(tools.len(), 2);
+
+    // Same manual name as the other test: collections are independent.
+    tools
+        .register("stub", "Second stub.", |x: i32| async move { -x }, ())
+        .unwrap();
+    let out = tools.call(call("stub", json!(4))).await.unwrap();
+    assert_eq!(out.result, json!(-4));
+}
+
+#[test]
+fn empty_list_collects_nothing() {
+    let tools = test_tools![];
+    assert!(tools.is_empty());
+}
+
+#[test]
+#[should_panic(expected = "no `#[tool]` named `missing`")]
+fn unknown_name_panics() {
+    let _ = test_tools![add, missing];
+}
+
+// ---------- filtered collection ----------
+
+#[test]
+fn filter_skips_metadata_of_excluded_tools() {
+    #[derive(Debug, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Strict {}
+
+    // `shout` carries `dangerous = true`, which `Strict` rejects.
+    assert!(matches!(
+        ToolCollection::<Strict>::collect_tools(),
+        Err(ToolError::BadMeta { ref tool, .. }) if tool == "shout"
+    ));
+    let tools = ToolCollection::<Strict>::collect_tools_filtered(|n| n != "shout").unwrap();
+    assert_eq!(tools.len(), 2);
+}
+
+// ---------- collisions ----------
+
+#[test]
+fn already_registered_names_both_descriptions() {
+    let mut tools = test_tools![add];
+    let Err(err) = tools.register(
+        "add",
+        "Manual add.",
+        |p: (i32, i32)| async move { p.0 + p.1 },
+        (),
+    ) else {
+        panic!("duplicate name was accepted");
+    };
+    match err {
+        ToolError::AlreadyRegistered {
+            name,
+            existing,
+            attempted,
+        } => {
+            assert_eq!(name, "add");
+            assert_eq!(existing, "Adds two numbers.");
+            assert_eq!(attempted, "Manual add.");
+        }
+        other => panic!("unexpected error: {other}"),
+    }
+}
@@ -0,0 +1,103 @@
+//! `call_typed` and `call_tool_typed`: results decoded into Rust types.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tools_rs::{FunctionCall, ToolError, ToolSchema, call_tool_typed, test_tools, tool};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, ToolSchema)]
+struct Forecast {
+    city: String,
+    high: i32,
+}
+
+#[tool]
+/// Forecast for a city.
+async fn forecast(city: String) -> Forecast {
+    Forecast { city, high: 21 }
+}
+
+#[tool]
+/// Takes money out, if there is enough.
+async fn withdraw(amount: u32) -> Result<u32, String> {
+    if amount > 100 {
+        Err("insufficient funds".into())
+    } else {
+        Ok(100 - amount)
+    }
+}
+
+fn call(name: &str, arguments: serde_json::Value) -> FunctionCall {
+    FunctionCall::new(name.into(), arguments)
+}
+
+#[tokio::test]
+async fn decodes_into_the_output_type() {
+    let tools = test_tools![forecast, withdraw];
+    let out: Forecast = tools
+        .call_typed(call("forecast", json!({ "city": "Oslo" })))
+        .await
+        .unwrap();
+    assert_eq!(
+        out,
+        Forecast {
+            city: "Oslo".into(),
+            high: 21
+        }
+    );
+
+    let left: u32 = tools
+        .call_typed(call("withdraw", json!({ "amount": 30 })))
+        .await
+        .unwrap();
+    assert_eq!(left, 70);
+
+    let facade: Forecast = call_tool_typed("forecast", json!({ "city": "Rome" }))
+        .await
+        .unwrap();
+    assert_eq!(facade.city, "Rome");
+}
+
+#[tokio::test]
+async fn domain_errors_decode_into_a_result_or_fail() {
+    let tools = test_tools![withdraw];
+    let out: Result<u32, String> = tools
+        .call_typed(call("withdraw", json!({ "amount": 500 })))
+        .await
+        .unwrap();
+    assert_eq!(out, Err("insufficient funds".into()));
+
+    let err = tools
+        .call_typed::<u32>(call("withdraw", json!({ "amount": 500 })))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, ToolError::Domain(e) if e == "insufficient funds"),
+        "{err:?}"
+    );
+}
+
+#[tokio::test]
+async fn a_wrong_type_names_the_tool_and_the_type() {
+    let tools = test_tools![forecast];
+    let err = tools
+        .call_typed::<Vec<String>>(call("forecast", json!({ "city": "Oslo" })))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ToolError::Deserialize(_)), "{err:?}");
+    let message = err.to_string();
+    assert!(
+        message.contains("result of tool `forecast` is not a `"),
+        "{message}"
+    );
+    assert!(
+        message.contains(std::any::type_name::<Vec<String>>()),
+        "{message}"
+    );
+
+    // Calls that fail keep their own error.
+    let err = tools
+        .call_typed::<Forecast>(call("forecast", json!({})))
+        .await
+        .unwrap_err();
+    assert!(!err.to_string().contains("result of tool"), "{err}");
+}
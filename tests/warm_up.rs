@@ -0,0 +1,79 @@
+//! Warm-up: schemas and the serialized declaration cache are built before
+//! the first request.
+
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use tools_rs::{ToolSchema, collect_tools, tool, warm_up_inventory};
+
+#[derive(Serialize, Deserialize, ToolSchema)]
+struct Address {
+    street: String,
+    city: String,
+    zip: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, ToolSchema)]
+struct Customer {
+    id: u64,
+    name: String,
+    addresses: Vec<Address>,
+    tags: std::collections::HashMap<String, String>,
+}
+
+#[tool]
+/// Stores a customer record.
+async fn save_customer(customer: Customer) -> u64 {
+    customer.id
+}
+
+#[tool]
+/// Looks up a customer's primary city.
+async fn primary_city(customer: Customer, fallback: Address) -> String {
+    customer
+        .addresses
+        .into_iter()
+        .next()
+        .unwrap_or(fallback)
+        .city
+}
+
+// ---------- inventory ----------
+
+#[test]
+fn inventory_warm_up_counts_registrations() {
+    let report = warm_up_inventory();
+    assert_eq!(report.tools, 2);
+}
+
+// ---------- collection ----------
+
+#[test]
+fn first_json_after_warm_up_is_cached() {
+    let tools = collect_tools();
+    let report = tools.warm_up().expect("warm up");
+    assert_eq!(report.tools, 2);
+    assert!(tools.is_warm());
+
+    // The Lazy statics and the serialized declarations are already built, so
+    // the first request-path call is a clone.
+    let start = Instant::now();
+    let json = tools.json().unwrap();
+    assert!(start.elapsed() < Duration::from_millis(50));
+    assert_eq!(json.as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn warm_output_matches_cold_output() {
+    let cold = collect_tools().json().unwrap();
+    let warm = collect_tools();
+    warm.warm_up().unwrap();
+    assert_eq!(warm.json().unwrap(), cold);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn async_warm_up_spreads_across_blocking_pool() {
+    let tools = collect_tools();
+    let report = tools.warm_up_async(1).await.expect("warm up");
+    assert_eq!(report.tools, 2);
+    assert!(tools.is_warm());
+}
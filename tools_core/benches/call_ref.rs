@@ -0,0 +1,53 @@
+//! `call` with a clone of the caller's `FunctionCall` vs. `call_ref`, on a
+//! 1 MB argument payload.
+//!
+//! `cargo bench -p tools_core --bench call_ref`
+
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use serde_json::{Value, json};
+use tools_core::{FunctionCall, ToolCollection, ToolSpec};
+
+/// About 1 MB: 10 000 strings of 100 bytes.
+fn payload() -> Value {
+    json!(
+        (0..10_000)
+            .map(|i| format!("{i:0>100}"))
+            .collect::<Vec<_>>()
+    )
+}
+
+fn call_vs_call_ref(c: &mut Criterion) {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register(
+            "typed",
+            "Counts lines.",
+            |lines: Vec<String>| async move { lines.len() },
+            (),
+        )
+        .unwrap();
+    tools
+        .register_spec(ToolSpec::new("raw", "Counts lines.").handler(|args| {
+            Box::pin(async move { Ok(json!(args.as_array().map_or(0, Vec::len))) })
+        }))
+        .unwrap();
+
+    let mut group = c.benchmark_group("call_1mb");
+    group.sample_size(20);
+    for tool in ["typed", "raw"] {
+        let call = FunctionCall::new(tool.to_string(), payload());
+        group.bench_function(format!("{tool}/call_clone"), |b| {
+            b.iter(|| rt.block_on(tools.call(black_box(call.clone()))).unwrap())
+        });
+        group.bench_function(format!("{tool}/call_ref"), |b| {
+            b.iter(|| rt.block_on(tools.call_ref(black_box(&call))).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, call_vs_call_ref);
+criterion_main!(benches);
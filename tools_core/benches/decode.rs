@@ -0,0 +1,80 @@
+//! Invalid-argument throughput: decoding before boxing the tool future vs.
+//! deserializing inside it (the previous single-stage wrapper).
+//!
+//! `cargo bench -p tools_core --bench decode`
+
+use std::{any::Any, sync::Arc};
+
+use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use futures::{FutureExt, future::BoxFuture};
+use serde_json::{Value, json};
+use tools_core::{DeserializationError, FunctionCall, ToolCollection, ToolError};
+
+type LegacyFunc = dyn Fn(Value, Option<Arc<dyn Any + Send + Sync>>) -> BoxFuture<'static, Result<Value, ToolError>>
+    + Send
+    + Sync;
+
+/// The single-stage wrapper `register` used to build.
+fn legacy() -> Arc<LegacyFunc> {
+    let func = Arc::new(|p: (i64, i64)| async move { p.0 + p.1 });
+    Arc::new(move |raw, _ctx| {
+        let func = func.clone();
+        async move {
+            let input: (i64, i64) =
+                serde_json::from_value(raw).map_err(DeserializationError::from)?;
+            let output = func(input).await;
+            serde_json::to_value(output).map_err(|e| ToolError::Runtime(e.to_string()))
+        }
+        .boxed()
+    })
+}
+
+fn invalid_arguments(c: &mut Criterion) {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let mut tools: ToolCollection = ToolCollection::new();
+    tools
+        .register("add", "Adds.", |p: (i64, i64)| async move { p.0 + p.1 }, ())
+        .unwrap();
+    let legacy = legacy();
+    let bad = json!(["one", "two"]);
+
+    let func = tools.get("add").unwrap().func.clone();
+    let call = FunctionCall {
+        id: None,
+        name: "add".to_string(),
+        arguments: bad.clone(),
+        repaired: false,
+    };
+
+    let mut group = c.benchmark_group("invalid_arguments");
+    group.bench_function("legacy_boxed", |b| {
+        b.iter(|| {
+            rt.block_on(legacy(black_box(bad.clone()), None))
+                .unwrap_err()
+        })
+    });
+    group.bench_function("two_stage", |b| {
+        b.iter(|| {
+            rt.block_on(async {
+                let input = func.decode(black_box(bad.clone()))?;
+                func.run(input, None).await
+            })
+            .unwrap_err()
+        })
+    });
+    group.bench_function("call", |b| {
+        b.iter(|| {
+            rt.block_on(tools.call(black_box(call.clone())))
+                .unwrap_err()
+        })
+    });
+    group.bench_function("dry_run", |b| {
+        b.iter(|| tools.dry_run(black_box(&call)).unwrap_err())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, invalid_arguments);
+criterion_main!(benches);
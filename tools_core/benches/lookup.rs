@@ -0,0 +1,111 @@
+//! Name resolution vs. id dispatch, and building large collections.
+//!
+//! `cargo bench -p tools_core --bench lookup`; compare against
+//! `--no-default-features` to see the effect of the `fast-hash` feature.
+
+use std::collections::HashMap;
+
+use criterion::{BenchmarkId, Criterion, black_box, criterion_group, criterion_main};
+use serde_json::json;
+use tools_core::{FunctionCall, ToolCollection};
+
+const SIZES: [usize; 3] = [8, 64, 512];
+
+fn names(n: usize) -> Vec<&'static str> {
+    (0..n)
+        .map(|i| &*Box::leak(format!("tool_number_{i}").into_boxed_str()))
+        .collect()
+}
+
+fn collection(names: &[&'static str]) -> ToolCollection {
+    let mut tools = ToolCollection::new();
+    for &name in names {
+        tools
+            .register(name, "Echo.", |x: i64| async move { x }, ())
+            .unwrap();
+    }
+    tools
+}
+
+fn resolve(c: &mut Criterion) {
+    let mut group = c.benchmark_group("resolve");
+    for n in SIZES {
+        let names = names(n);
+        let tools = collection(&names);
+        let sip: HashMap<&'static str, usize> =
+            names.iter().enumerate().map(|(i, n)| (*n, i)).collect();
+        let lookups: Vec<String> = names.iter().map(|n| n.to_string()).collect();
+
+        group.bench_with_input(BenchmarkId::new("std_siphash", n), &lookups, |b, l| {
+            b.iter(|| {
+                for name in l {
+                    black_box(sip.get(name.as_str()));
+                }
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("collection", n), &lookups, |b, l| {
+            b.iter(|| {
+                for name in l {
+                    black_box(tools.resolve(name));
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn dispatch(c: &mut Criterion) {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let names = names(64);
+    let tools = collection(&names);
+    let target = names[37];
+    let id = tools.resolve(target).unwrap();
+
+    let mut group = c.benchmark_group("dispatch");
+    group.bench_function("call_by_name", |b| {
+        b.iter(|| {
+            let call = FunctionCall::new(target.to_string(), json!(7));
+            rt.block_on(tools.call(call)).unwrap()
+        })
+    });
+    group.bench_function("call_by_id", |b| {
+        b.iter(|| {
+            let call = FunctionCall::new(target.to_string(), json!(7));
+            rt.block_on(tools.call_id(id, call)).unwrap()
+        })
+    });
+    group.finish();
+}
+
+fn build(c: &mut Criterion) {
+    let names = names(5_000);
+    let mut group = c.benchmark_group("build_5k");
+    group.bench_function("new", |b| {
+        b.iter(|| {
+            let mut tools: ToolCollection = ToolCollection::new();
+            for &name in &names {
+                tools
+                    .register(name, "Echo.", |x: i64| async move { x }, ())
+                    .unwrap();
+            }
+            tools
+        })
+    });
+    group.bench_function("with_capacity", |b| {
+        b.iter(|| {
+            let mut tools: ToolCollection = ToolCollection::with_capacity(names.len());
+            for &name in &names {
+                tools
+                    .register(name, "Echo.", |x: i64| async move { x }, ())
+                    .unwrap();
+            }
+            tools
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, resolve, dispatch, build);
+criterion_main!(benches);
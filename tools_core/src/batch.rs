@@ -0,0 +1,116 @@
+//! Running several calls at once.
+//!
+//! [`ToolCollection::call_many`] runs a batch concurrently on the calling
+//! task and reports one [`CallOutcome`] per call, in input order. With
+//! [`BatchMode::FailFast`] the first non-retryable error (see
+//! [`ToolError::is_retryable`]) drops every call still running, so their
+//! futures are cancelled at their next `.await`; successes that finished
+//! before that are still returned.
+//!
+//! [`ToolCollection::call_all`] is the plain variant for an assistant
+//! message with several tool calls: every call runs, at most
+//! `max_concurrency` at a time, and each result stays at its call's
+//! index. A successful response carries its call's
+//! [`id`][FunctionCall::id]; a failed call is matched back by position.
+
+use futures::stream::{self, FuturesUnordered, StreamExt};
+
+use crate::{FunctionCall, FunctionResponse, ToolCollection, ToolError};
+
+/// How [`ToolCollection::call_many`] reacts to a failed call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BatchMode {
+    /// Every call runs to completion regardless of the others.
+    #[default]
+    Independent,
+    /// The first non-retryable error cancels all calls still running.
+    /// Retryable errors are reported without cancelling anything.
+    FailFast,
+}
+
+/// What happened to one call of a batch.
+#[derive(Debug)]
+pub enum CallOutcome {
+    Completed(FunctionResponse),
+    Failed(ToolError),
+    /// Dropped before finishing because a sibling failed in
+    /// [`BatchMode::FailFast`].
+    Cancelled,
+}
+
+impl CallOutcome {
+    pub fn is_completed(&self) -> bool {
+        matches!(self, Self::Completed(_))
+    }
+
+    pub fn is_failed(&self) -> bool {
+        matches!(self, Self::Failed(_))
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self, Self::Cancelled)
+    }
+
+    /// The outcome as a `Result`; cancellation has no response.
+    pub fn into_result(self) -> Option<Result<FunctionResponse, ToolError>> {
+        match self {
+            Self::Completed(response) => Some(Ok(response)),
+            Self::Failed(err) => Some(Err(err)),
+            Self::Cancelled => None,
+        }
+    }
+}
+
+impl<M> ToolCollection<M> {
+    /// Run `calls` concurrently and return their outcomes in the same
+    /// order. See [`batch`][crate::batch].
+    pub async fn call_many(
+        &self,
+        calls: impl IntoIterator<Item = FunctionCall>,
+        mode: BatchMode,
+    ) -> Vec<CallOutcome> {
+        let mut running: FuturesUnordered<_> = calls
+            .into_iter()
+            .enumerate()
+            .map(|(i, call)| async move { (i, self.call(call).await) })
+            .collect();
+        let mut outcomes: Vec<CallOutcome> = std::iter::repeat_with(|| CallOutcome::Cancelled)
+            .take(running.len())
+            .collect();
+
+        while let Some((i, result)) = running.next().await {
+            let fatal = matches!(&result, Err(e) if !e.is_retryable());
+            outcomes[i] = match result {
+                Ok(response) => CallOutcome::Completed(response),
+                Err(err) => CallOutcome::Failed(err),
+            };
+            if fatal && mode == BatchMode::FailFast {
+                // Dropping the set cancels the calls still in it.
+                break;
+            }
+        }
+        outcomes
+    }
+
+    /// Run every call in `calls`, at most `max_concurrency` at a time
+    /// (`0` counts as `1`), and return their results in input order. See
+    /// [`batch`][crate::batch].
+    pub async fn call_all(
+        &self,
+        calls: Vec<FunctionCall>,
+        max_concurrency: usize,
+    ) -> Vec<Result<FunctionResponse, ToolError>> {
+        let mut results: Vec<_> = std::iter::repeat_with(|| None).take(calls.len()).collect();
+        // Unordered, so a slow call doesn't hold back the ones after it.
+        let mut running = stream::iter(calls.into_iter().enumerate())
+            .map(|(i, call)| async move { (i, self.call(call).await) })
+            .buffer_unordered(max_concurrency.max(1));
+        while let Some((i, result)) = running.next().await {
+            results[i] = Some(result);
+        }
+        results
+            .into_iter()
+            .map(|result| result.expect("every call finishes"))
+            .collect()
+    }
+}
@@ -0,0 +1,154 @@
+//! Narrowed variants of a tool with some arguments fixed.
+//!
+//! [`ToolCollection::register_bound`] registers a new tool that calls an
+//! existing one with part of its arguments already applied. The bound
+//! parameters are left out of the new tool's schema, and the arguments
+//! the model sends are merged with the bound ones before the base tool
+//! decodes them:
+//!
+//! ```ignore
+//! tools.register_bound(
+//!     "search_docs",
+//!     "Searches the internal documentation.",
+//!     "search_web",
+//!     json!({ "site": "docs.mycorp.com", "max_results": 5 }),
+//! )?;
+//! // search_docs {"query": "sso"} runs search_web {"query": "sso", "site": .., "max_results": 5}
+//! ```
+//!
+//! The bound tool is a tool of its own: it has its own name, circuit
+//! breaker, cache, journal and usage events, and starts with a copy of
+//! the base tool's metadata, returns schema and tags. It keeps calling
+//! the handler the base tool had when it was bound, even if the base is
+//! later replaced or removed.
+//!
+//! [`BoundConflict`] decides what happens when the model sends a bound
+//! argument anyway; by default the bound value wins.
+
+use std::borrow::Cow;
+
+use serde::de::Error as _;
+use serde_json::{Map, Value};
+
+use crate::{DeserializationError, ToolCollection, ToolError, ToolFunc, ToolId, spec::ToolSpec};
+
+/// What a bound tool does with a model-supplied argument that is also
+/// bound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BoundConflict {
+    /// The bound value replaces the model's.
+    #[default]
+    BoundWins,
+    /// The model's value replaces the bound one, which acts as a default.
+    ModelWins,
+    /// The call fails as invalid input.
+    Reject,
+}
+
+fn invalid(message: String) -> ToolError {
+    DeserializationError::from(serde_json::Error::custom(message)).into()
+}
+
+/// `arguments` merged with `bound` under `conflict`.
+fn merge(
+    arguments: Value,
+    bound: &Map<String, Value>,
+    conflict: BoundConflict,
+) -> Result<Value, ToolError> {
+    let mut arguments = match arguments {
+        Value::Object(map) => map,
+        Value::Null => Map::new(),
+        other => return Err(invalid(format!("expected an object, found {other}"))),
+    };
+    for (key, value) in bound {
+        match (conflict, arguments.contains_key(key)) {
+            (BoundConflict::Reject, true) => {
+                return Err(invalid(format!("`{key}` is fixed by the tool")));
+            }
+            (BoundConflict::ModelWins, true) => {}
+            _ => {
+                arguments.insert(key.clone(), value.clone());
+            }
+        }
+    }
+    Ok(Value::Object(arguments))
+}
+
+/// `parameters` without the bound properties. Errors name a bound key
+/// the schema doesn't declare, when it declares any.
+fn narrow(base: &str, parameters: &Value, bound: &Map<String, Value>) -> Result<Value, ToolError> {
+    let mut parameters = parameters.clone();
+    if let Some(properties) = parameters
+        .get_mut("properties")
+        .and_then(Value::as_object_mut)
+    {
+        for key in bound.keys() {
+            if properties.remove(key).is_none() {
+                return Err(ToolError::Runtime(format!(
+                    "tool `{base}` has no parameter `{key}` to bind"
+                )));
+            }
+        }
+    }
+    if let Some(required) = parameters.get_mut("required").and_then(Value::as_array_mut) {
+        required.retain(|name| !name.as_str().is_some_and(|name| bound.contains_key(name)));
+    }
+    Ok(parameters)
+}
+
+impl<M: Clone> ToolCollection<M> {
+    /// Register `name`, a variant of the tool `base` with the arguments in
+    /// `bound_args` fixed. Bound values win over the model's. See
+    /// [`bound`][crate::bound].
+    pub fn register_bound(
+        &mut self,
+        name: impl Into<Cow<'static, str>>,
+        desc: impl Into<Cow<'static, str>>,
+        base: &str,
+        bound_args: Value,
+    ) -> Result<ToolId, ToolError> {
+        self.register_bound_with(name, desc, base, bound_args, BoundConflict::default())
+    }
+
+    /// [`register_bound`][Self::register_bound] with the given
+    /// [`BoundConflict`] handling.
+    ///
+    /// Fails with [`ToolError::FunctionNotFound`] if `base` isn't
+    /// registered, and with [`ToolError::Runtime`] if `bound_args` isn't
+    /// an object or names a parameter `base` doesn't declare.
+    pub fn register_bound_with(
+        &mut self,
+        name: impl Into<Cow<'static, str>>,
+        desc: impl Into<Cow<'static, str>>,
+        base: &str,
+        bound_args: Value,
+        conflict: BoundConflict,
+    ) -> Result<ToolId, ToolError> {
+        let entry = self.get(base).ok_or_else(|| ToolError::FunctionNotFound {
+            name: base.to_string().into(),
+        })?;
+        let Value::Object(bound) = bound_args else {
+            return Err(ToolError::Runtime(format!(
+                "arguments bound to `{base}` must be an object"
+            )));
+        };
+        let parameters = narrow(&entry.decl.name, &entry.decl.parameters, &bound)?;
+
+        let decode = entry.func.clone();
+        let run = entry.func.clone();
+        let func = ToolFunc::new(
+            move |arguments: Value| decode.decode(merge(arguments, &bound, conflict)?),
+            move |input, ctx| run.run(input, ctx),
+        );
+        let mut spec = ToolSpec::new(name, desc)
+            .params_schema(parameters)
+            .schema_dialect(entry.decl.dialect)
+            .tags(entry.decl.tags.iter().cloned())
+            .func(func)
+            .with_meta(entry.meta.clone());
+        if let Some(returns) = &entry.decl.returns {
+            spec = spec.returns_schema(returns.clone());
+        }
+        self.register_spec(spec)
+    }
+}
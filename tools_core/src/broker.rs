@@ -0,0 +1,451 @@
+//! Serving a collection to another process over a local socket.
+//!
+//! Enabled with the `broker` feature. The process that runs the tools
+//! serves its collection; the orchestrator connects and gets a proxy
+//! collection with the same declarations, whose calls are forwarded:
+//!
+//! ```ignore
+//! // Sandboxed tool process.
+//! let server = broker::serve_unix(Arc::new(tools), "/run/agent/tools.sock")?;
+//!
+//! // Orchestrator.
+//! let tools: ToolCollection = ToolCollection::connect_unix("/run/agent/tools.sock").await?;
+//! send(tools.json()?);
+//! tools.call(call).await?;
+//! ```
+//!
+//! The declarations are fetched once, when connecting. Each request and
+//! reply is one JSON document preceded by its length as a big-endian
+//! `u32`: `{"op": "list"}` or `{"op": "call", "call": ..., "timeout_ms": ...}`,
+//! answered by `{"ok": ...}` or `{"error": <ErrorReport>}`. A failed call
+//! comes back as [`ToolError::RuntimeClassified`] with the remote error's
+//! class and message; domain errors come back as domain errors.
+//!
+//! The proxy keeps idle connections for reuse and opens a new one when a
+//! reused connection turns out to be closed, e.g. after the serving
+//! process restarted. The enclosing [`deadline`] travels with the call.
+//!
+//! To stop serving, call
+//! [`shutdown_gracefully`][ToolCollection::shutdown_gracefully] on the
+//! served collection, then abort the server task. Calls that arrive in
+//! between are answered with a transient error, so the proxy's caller
+//! may retry them elsewhere.
+//! On Windows, [`serve_pipe`] and
+//! [`ToolCollection::connect_pipe`] do the same over a named pipe.
+
+use std::{
+    io,
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
+};
+
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    task::{JoinHandle, JoinSet},
+};
+
+use crate::{
+    ErrorClass, ErrorReport, FunctionCall, FunctionResponse, SchemaDialect, ToolCollection,
+    ToolError, ToolSpec, deadline,
+};
+
+/// Frames larger than this are refused.
+const MAX_FRAME: usize = 16 * 1024 * 1024;
+
+/// How much longer than the call's timeout the proxy waits for a reply,
+/// so the server's own deadline error can arrive first.
+const REPLY_GRACE: Duration = Duration::from_millis(250);
+
+// ============================================================================
+// PROTOCOL
+// ============================================================================
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum Request {
+    List,
+    Call {
+        call: FunctionCall,
+        /// Time left on the caller's deadline.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        timeout_ms: Option<u64>,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Reply {
+    Ok(Value),
+    Error(ErrorReport),
+}
+
+/// A declaration as listed by the server.
+#[derive(Serialize, Deserialize)]
+struct RemoteDecl {
+    name: String,
+    description: String,
+    parameters: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    returns: Option<Value>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(default)]
+    dialect: SchemaDialect,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    hidden: bool,
+}
+
+async fn read_frame<T: serde::de::DeserializeOwned>(
+    conn: &mut (impl AsyncRead + Unpin),
+) -> io::Result<T> {
+    let len = conn.read_u32().await? as usize;
+    if len > MAX_FRAME {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("frame of {len} bytes exceeds the {MAX_FRAME} byte limit"),
+        ));
+    }
+    let mut buf = vec![0; len];
+    conn.read_exact(&mut buf).await?;
+    Ok(serde_json::from_slice(&buf)?)
+}
+
+async fn write_frame(
+    conn: &mut (impl AsyncWrite + Unpin),
+    value: &impl Serialize,
+) -> io::Result<()> {
+    let buf = serde_json::to_vec(value)?;
+    if buf.len() > MAX_FRAME {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "frame of {} bytes exceeds the {MAX_FRAME} byte limit",
+                buf.len()
+            ),
+        ));
+    }
+    conn.write_u32(buf.len() as u32).await?;
+    conn.write_all(&buf).await?;
+    conn.flush().await
+}
+
+// ============================================================================
+// SERVER
+// ============================================================================
+
+/// Answer requests on one connection until the client closes it.
+async fn serve_connection<M>(
+    tools: Arc<ToolCollection<M>>,
+    mut conn: impl AsyncRead + AsyncWrite + Unpin,
+) {
+    while let Ok(request) = read_frame::<Request>(&mut conn).await {
+        let reply = match request {
+            Request::List => list(&tools),
+            Request::Call { call, timeout_ms } => {
+                let outcome = match timeout_ms {
+                    Some(ms) => {
+                        tools
+                            .call_with_timeout(call, Duration::from_millis(ms))
+                            .await
+                    }
+                    None => tools.call(call).await,
+                };
+                match outcome.and_then(|r| Ok(serde_json::to_value(r)?)) {
+                    Ok(response) => Reply::Ok(response),
+                    Err(e) => Reply::Error(e.report()),
+                }
+            }
+        };
+        if write_frame(&mut conn, &reply).await.is_err() {
+            return;
+        }
+    }
+}
+
+fn list<M>(tools: &ToolCollection<M>) -> Reply {
+    let decls: Vec<RemoteDecl> = tools
+        .iter()
+        .map(|(_, e)| RemoteDecl {
+            name: e.decl.name.to_string(),
+            description: e.decl.description.to_string(),
+            parameters: e.decl.parameters.clone(),
+            returns: e.decl.returns.clone(),
+            tags: e.decl.tags.clone(),
+            dialect: e.decl.dialect,
+            hidden: e.hidden,
+        })
+        .collect();
+    match serde_json::to_value(decls) {
+        Ok(decls) => Reply::Ok(decls),
+        Err(e) => Reply::Error(ToolError::from(e).report()),
+    }
+}
+
+/// Serve `tools` on a Unix domain socket at `path`. Binding fails if
+/// `path` exists; remove a stale socket first. Each connection is served
+/// on its own task; aborting the returned handle stops accepting and
+/// closes every connection.
+#[cfg(unix)]
+pub fn serve_unix<M: Send + Sync + 'static>(
+    tools: Arc<ToolCollection<M>>,
+    path: impl AsRef<std::path::Path>,
+) -> io::Result<JoinHandle<()>> {
+    let listener = tokio::net::UnixListener::bind(path)?;
+    Ok(tokio::spawn(async move {
+        let mut connections = JoinSet::new();
+        while let Ok((conn, _)) = listener.accept().await {
+            connections.spawn(serve_connection(tools.clone(), conn));
+            // Reap finished connections so the set doesn't grow.
+            while connections.try_join_next().is_some() {}
+        }
+    }))
+}
+
+/// Serve `tools` on the named pipe `name`, such as
+/// `\\.\pipe\agent-tools`. Like [`serve_unix`] otherwise.
+#[cfg(windows)]
+pub fn serve_pipe<M: Send + Sync + 'static>(
+    tools: Arc<ToolCollection<M>>,
+    name: impl Into<String>,
+) -> io::Result<JoinHandle<()>> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    let name = name.into();
+    let mut server = ServerOptions::new()
+        .first_pipe_instance(true)
+        .create(&name)?;
+    Ok(tokio::spawn(async move {
+        let mut connections = JoinSet::new();
+        while server.connect().await.is_ok() {
+            let Ok(next) = ServerOptions::new().create(&name) else {
+                return;
+            };
+            let conn = std::mem::replace(&mut server, next);
+            connections.spawn(serve_connection(tools.clone(), conn));
+            while connections.try_join_next().is_some() {}
+        }
+    }))
+}
+
+// ============================================================================
+// CLIENT
+// ============================================================================
+
+/// Options for a proxy collection; see
+/// [`ToolCollection::connect_unix_with`].
+#[derive(Debug, Clone)]
+pub struct BrokerOptions {
+    /// Longest a forwarded call may take, or less if the caller's deadline
+    /// is earlier.
+    pub timeout: Duration,
+    /// Idle connections kept for reuse.
+    pub max_idle: usize,
+}
+
+impl Default for BrokerOptions {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30),
+            max_idle: 8,
+        }
+    }
+}
+
+trait Conn: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Conn for T {}
+
+type Connect = Box<dyn Fn() -> BoxFuture<'static, io::Result<Box<dyn Conn>>> + Send + Sync>;
+
+/// Connections to one server, shared by every tool of a proxy
+/// collection.
+struct Client {
+    connect: Connect,
+    idle: Mutex<Vec<Box<dyn Conn>>>,
+    options: BrokerOptions,
+}
+
+impl Client {
+    async fn open(&self) -> Result<Box<dyn Conn>, ToolError> {
+        (self.connect)().await.map_err(|e| {
+            ToolError::classified(ErrorClass::Transient, format!("broker unreachable: {e}"))
+        })
+    }
+
+    /// Send `request` and wait for the reply, within `timeout`. A reused
+    /// connection that fails while sending is replaced once; the server
+    /// cannot have seen the request.
+    async fn round_trip(&self, request: &Request, timeout: Duration) -> Result<Reply, ToolError> {
+        let exchange = async {
+            let reused = self
+                .idle
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .pop();
+            let mut conn = match reused {
+                Some(mut conn) => match write_frame(&mut conn, request).await {
+                    Ok(()) => conn,
+                    Err(_) => self.send_fresh(request).await?,
+                },
+                None => self.send_fresh(request).await?,
+            };
+            let reply = read_frame::<Reply>(&mut conn).await.map_err(lost)?;
+            let mut idle = self.idle.lock().unwrap_or_else(PoisonError::into_inner);
+            if idle.len() < self.options.max_idle {
+                idle.push(conn);
+            }
+            Ok(reply)
+        };
+        // A connection cut off mid-exchange is dropped, not reused.
+        tokio::time::timeout(timeout, exchange)
+            .await
+            .unwrap_or_else(|_| {
+                Err(ToolError::classified(
+                    ErrorClass::Exhausted,
+                    format!("broker did not answer within {timeout:?}"),
+                ))
+            })
+    }
+
+    async fn send_fresh(&self, request: &Request) -> Result<Box<dyn Conn>, ToolError> {
+        let mut conn = self.open().await?;
+        write_frame(&mut conn, request).await.map_err(lost)?;
+        Ok(conn)
+    }
+
+    async fn call(&self, name: String, arguments: Value) -> Result<Value, ToolError> {
+        let timeout = deadline::remaining()
+            .map_or(self.options.timeout, |left| left.min(self.options.timeout));
+        let request = Request::Call {
+            call: FunctionCall::new(name, arguments),
+            timeout_ms: Some(timeout.as_millis().try_into().unwrap_or(u64::MAX)),
+        };
+        let reply = self.round_trip(&request, timeout + REPLY_GRACE).await?;
+        let mut response: FunctionResponse = match reply {
+            Reply::Ok(response) => serde_json::from_value(response)?,
+            Reply::Error(report) => return Err(remote_error(report)),
+        };
+        if response.is_error {
+            return Err(ToolError::Domain(response.result["error"].take()));
+        }
+        Ok(response.result)
+    }
+}
+
+fn lost(e: io::Error) -> ToolError {
+    ToolError::classified(
+        ErrorClass::Transient,
+        format!("broker connection lost: {e}"),
+    )
+}
+
+fn remote_error(report: ErrorReport) -> ToolError {
+    ToolError::RuntimeClassified {
+        class: report.class,
+        message: report.message,
+    }
+}
+
+impl<M: Default> ToolCollection<M> {
+    /// A proxy for the collection served at `path` by [`serve_unix`],
+    /// with default [`BrokerOptions`].
+    #[cfg(unix)]
+    pub async fn connect_unix(path: impl AsRef<std::path::Path>) -> Result<Self, ToolError> {
+        Self::connect_unix_with(path, BrokerOptions::default()).await
+    }
+
+    /// A proxy for the collection served at `path` by [`serve_unix`]. Each
+    /// tool's metadata is `M::default()`.
+    #[cfg(unix)]
+    pub async fn connect_unix_with(
+        path: impl AsRef<std::path::Path>,
+        options: BrokerOptions,
+    ) -> Result<Self, ToolError> {
+        let path = path.as_ref().to_path_buf();
+        let connect: Connect = Box::new(move || {
+            let path = path.clone();
+            Box::pin(async move {
+                let conn = tokio::net::UnixStream::connect(path).await?;
+                Ok(Box::new(conn) as Box<dyn Conn>)
+            })
+        });
+        Self::connect_with(connect, options).await
+    }
+
+    /// A proxy for the collection served on the named pipe `name` by
+    /// [`serve_pipe`], with default [`BrokerOptions`].
+    #[cfg(windows)]
+    pub async fn connect_pipe(name: impl Into<String>) -> Result<Self, ToolError> {
+        Self::connect_pipe_with(name, BrokerOptions::default()).await
+    }
+
+    /// A proxy for the collection served on the named pipe `name` by
+    /// [`serve_pipe`].
+    #[cfg(windows)]
+    pub async fn connect_pipe_with(
+        name: impl Into<String>,
+        options: BrokerOptions,
+    ) -> Result<Self, ToolError> {
+        use tokio::net::windows::named_pipe::ClientOptions;
+
+        const ERROR_PIPE_BUSY: i32 = 231;
+        let name = name.into();
+        let connect: Connect = Box::new(move || {
+            let name = name.clone();
+            Box::pin(async move {
+                loop {
+                    match ClientOptions::new().open(&name) {
+                        Ok(conn) => return Ok(Box::new(conn) as Box<dyn Conn>),
+                        Err(e) if e.raw_os_error() == Some(ERROR_PIPE_BUSY) => {
+                            tokio::time::sleep(Duration::from_millis(20)).await
+                        }
+                        Err(e) => return Err(e),
+                    }
+                }
+            })
+        });
+        Self::connect_with(connect, options).await
+    }
+
+    async fn connect_with(connect: Connect, options: BrokerOptions) -> Result<Self, ToolError> {
+        let client = Arc::new(Client {
+            connect,
+            idle: Mutex::new(Vec::new()),
+            options,
+        });
+        let decls: Vec<RemoteDecl> = match client
+            .round_trip(&Request::List, client.options.timeout)
+            .await?
+        {
+            Reply::Ok(decls) => serde_json::from_value(decls)?,
+            Reply::Error(report) => return Err(remote_error(report)),
+        };
+
+        let mut tools = Self::new();
+        for decl in decls {
+            let name = decl.name.clone();
+            let client = client.clone();
+            let mut spec = ToolSpec::new(decl.name, decl.description)
+                .params_schema(decl.parameters)
+                .schema_dialect(decl.dialect)
+                .tags(decl.tags)
+                .handler(move |args| {
+                    let client = client.clone();
+                    let name = name.clone();
+                    Box::pin(async move { client.call(name, args).await })
+                });
+            if let Some(returns) = decl.returns {
+                spec = spec.returns_schema(returns);
+            }
+            let id = tools.register_spec(spec.with_meta(M::default()))?;
+            if let Some(entry) = tools.entries[id.index()].as_mut() {
+                entry.hidden = decl.hidden;
+            }
+        }
+        tools.json_cache.take();
+        Ok(tools)
+    }
+}
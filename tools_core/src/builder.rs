@@ -24,7 +24,7 @@ use std::{
 use serde::de::DeserializeOwned;
 
 use crate::{NoMeta, ToolCollection, ToolError, collect_inventory_inner};
-use crate::ffi::{Language, leak_string, load_language};
+use crate::ffi::{Language, load_language};
 
 // ============================================================================
 // TYPESTATE MARKERS
@@ -261,15 +261,7 @@ impl<M: DeserializeOwned> ToolsBuilder<Scripted, M> {
         for path in &self.inner.script_paths {
             let defs = load_language(lang, path)?;
             for def in defs {
-                let name = leak_string(def.name);
-                let desc = leak_string(def.description);
-                let meta: M =
-                    serde_json::from_value(def.meta).map_err(|e| ToolError::BadMeta {
-                        tool: name,
-                        error: e.to_string(),
-                    })?;
-                let func = def.func;
-                collection.register_raw(name, desc, def.parameters, move |v| func(v), meta)?;
+                collection.register_spec(def.into_spec()?)?;
             }
         }
 
@@ -0,0 +1,234 @@
+//! Per-tool result caches with stale-while-revalidate.
+//!
+//! Results are keyed by the [canonical hash][crate::hash] of the call's
+//! arguments. For a cached result of age `a`:
+//!
+//! - `a < ttl`: returned at once;
+//! - `ttl <= a < ttl + stale_while_revalidate`: returned at once with
+//!   [`FunctionResponse::stale`][crate::FunctionResponse::stale] set, and one background task refreshes
+//!   it. Further hits while that refresh runs don't start another;
+//! - older, or nothing cached: the call runs as usual and a successful
+//!   result is stored.
+//!
+//! ```ignore
+//! tools.set_cache_policy("get_exchange_rates", CachePolicy {
+//!     ttl: Duration::from_secs(60),
+//!     stale_while_revalidate: Duration::from_secs(600),
+//! })?;
+//! ```
+//!
+//! Refreshes are spawned on the runtime the call runs on, and skipped
+//! outside one. Each runs like a call of its own: under the tool's or the
+//! collection's default timeout, with the collection's secrets, and
+//! counted by [`shutdown_gracefully`][ToolCollection::shutdown_gracefully],
+//! but not under the caller's deadline. A failed refresh keeps the stale
+//! result until it ages out. Like breakers, the cache lives behind an `Arc` in the tool's
+//! [`ToolEntry`][crate::ToolEntry], so clones of the collection share it.
+
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, Mutex, MutexGuard, PoisonError,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    ToolCollection, ToolError,
+    circuit::{Clock, SystemClock},
+};
+
+/// How long a tool's results are served from its cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CachePolicy {
+    /// Age up to which a result is fresh.
+    pub ttl: Duration,
+    /// How long past `ttl` a result is still served, stale, while it is
+    /// refreshed in the background. Zero disables stale serving.
+    pub stale_while_revalidate: Duration,
+}
+
+impl Default for CachePolicy {
+    fn default() -> Self {
+        Self {
+            ttl: Duration::from_secs(60),
+            stale_while_revalidate: Duration::ZERO,
+        }
+    }
+}
+
+struct Slot {
+    value: Value,
+    stored: Instant,
+    refreshing: bool,
+}
+
+/// What a lookup found.
+pub(crate) enum Lookup {
+    Fresh(Value),
+    /// `refresh` is true for exactly one caller per stale period; that
+    /// caller must refresh the slot and then [`store`][ResultCache::store]
+    /// or [`release`][ResultCache::release] it.
+    Stale {
+        value: Value,
+        refresh: bool,
+    },
+    Miss,
+}
+
+/// Cache statistics as reported by [`ResultCache::report`] and
+/// [`ToolCollection::introspect`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct CacheReport {
+    pub ttl_ms: u64,
+    pub stale_while_revalidate_ms: u64,
+    /// Cached results, expired ones included until they are looked up
+    /// again.
+    pub entries: usize,
+    /// Calls answered with a fresh result.
+    pub hits: u64,
+    /// Calls answered with a stale result while it was refreshed.
+    pub stale_hits: u64,
+    /// Calls that found nothing usable and ran the tool.
+    pub misses: u64,
+}
+
+/// A tool's cached results. Attach one with
+/// [`ToolCollection::set_cache_policy`].
+pub struct ResultCache {
+    policy: CachePolicy,
+    clock: Arc<dyn Clock>,
+    slots: Mutex<HashMap<u64, Slot>>,
+    hits: AtomicU64,
+    stale_hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl ResultCache {
+    pub fn new(policy: CachePolicy, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            policy,
+            clock,
+            slots: Mutex::new(HashMap::new()),
+            hits: AtomicU64::new(0),
+            stale_hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    pub fn policy(&self) -> CachePolicy {
+        self.policy
+    }
+
+    /// Number of cached results, expired ones included until they are
+    /// looked up again.
+    pub fn len(&self) -> usize {
+        self.slots().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn report(&self) -> CacheReport {
+        CacheReport {
+            ttl_ms: self.policy.ttl.as_millis() as u64,
+            stale_while_revalidate_ms: self.policy.stale_while_revalidate.as_millis() as u64,
+            entries: self.len(),
+            hits: self.hits.load(Ordering::Relaxed),
+            stale_hits: self.stale_hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    fn slots(&self) -> MutexGuard<'_, HashMap<u64, Slot>> {
+        self.slots.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub(crate) fn lookup(&self, key: u64) -> Lookup {
+        let lookup = self.find(key);
+        let counter = match lookup {
+            Lookup::Fresh(_) => &self.hits,
+            Lookup::Stale { .. } => &self.stale_hits,
+            Lookup::Miss => &self.misses,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        lookup
+    }
+
+    fn find(&self, key: u64) -> Lookup {
+        let now = self.clock.now();
+        let mut slots = self.slots();
+        let Some(slot) = slots.get_mut(&key) else {
+            return Lookup::Miss;
+        };
+        let age = now.saturating_duration_since(slot.stored);
+        if age < self.policy.ttl {
+            return Lookup::Fresh(slot.value.clone());
+        }
+        if age < self.policy.ttl + self.policy.stale_while_revalidate {
+            let refresh = !slot.refreshing;
+            slot.refreshing = true;
+            return Lookup::Stale {
+                value: slot.value.clone(),
+                refresh,
+            };
+        }
+        slots.remove(&key);
+        Lookup::Miss
+    }
+
+    pub(crate) fn store(&self, key: u64, value: Value) {
+        let stored = self.clock.now();
+        self.slots().insert(
+            key,
+            Slot {
+                value,
+                stored,
+                refreshing: false,
+            },
+        );
+    }
+
+    /// Give up a refresh claimed through [`Lookup::Stale`], keeping the
+    /// stale value.
+    pub(crate) fn release(&self, key: u64) {
+        if let Some(slot) = self.slots().get_mut(&key) {
+            slot.refreshing = false;
+        }
+    }
+}
+
+impl<M> ToolCollection<M> {
+    /// Cache the results of the tool `name`, replacing any existing cache.
+    pub fn set_cache_policy(&mut self, name: &str, policy: CachePolicy) -> Result<(), ToolError> {
+        self.set_cache_policy_with_clock(name, policy, Arc::new(SystemClock))
+    }
+
+    /// [`set_cache_policy`][Self::set_cache_policy] with a custom
+    /// [`Clock`].
+    pub fn set_cache_policy_with_clock(
+        &mut self,
+        name: &str,
+        policy: CachePolicy,
+        clock: Arc<dyn Clock>,
+    ) -> Result<(), ToolError> {
+        self.entry_mut(name)?.cache = Some(Arc::new(ResultCache::new(policy, clock)));
+        Ok(())
+    }
+
+    /// Stop caching the tool's results and drop what was cached.
+    pub fn clear_cache_policy(&mut self, name: &str) -> Result<(), ToolError> {
+        self.entry_mut(name)?.cache = None;
+        Ok(())
+    }
+
+    /// The tool's result cache, if it has one.
+    pub fn result_cache(&self, name: &str) -> Option<&ResultCache> {
+        self.get(name)?.cache.as_deref()
+    }
+}
@@ -0,0 +1,391 @@
+//! Consistency checks run when a tool is registered.
+//!
+//! Some mistakes in a declaration are visible before the first call: a
+//! malformed parameters schema, an example the tool's own decoder
+//! rejects, a default of the wrong type. Every registration —
+//! [`register_spec`][ToolCollection::register_spec], `register` and
+//! `#[tool]` functions gathered by `collect_tools` — runs these cheap
+//! checks and reports findings with the tool's name.
+//!
+//! By default findings are kept as warnings, readable from
+//! [`ToolCollection::registration_warnings`]. In strict mode they fail the
+//! registration with [`ToolError::RegistrationRejected`]:
+//!
+//! ```ignore
+//! let mut tools = ToolCollection::new();
+//! tools.set_strict_registration(true)?;
+//! // or, for `#[tool]` functions:
+//! let tools = ToolCollection::<NoMeta>::builder().strict_registration(true).collect()?;
+//! ```
+//!
+//! [`Strictness::RequireDescriptions`] additionally rejects tools with a
+//! blank description or undescribed top-level parameters, whether or not
+//! strict mode is on.
+
+use core::fmt;
+
+use serde_json::Value;
+
+use crate::{
+    ToolCollection, ToolError, ToolFunc,
+    lint::{escape, top_level_params},
+};
+
+/// Stable finding codes.
+pub mod codes {
+    /// The parameters schema is malformed.
+    pub const INVALID_SCHEMA: &str = "invalid-schema";
+    /// The arguments are a bare primitive rather than an object of named
+    /// arguments.
+    pub const PRIMITIVE_PARAMS: &str = "primitive-params";
+    /// A schema example is rejected by the tool's decoder.
+    pub const EXAMPLE_REJECTED: &str = "example-rejected";
+    /// A schema example passes arguments the schema doesn't declare.
+    pub const EXAMPLE_UNKNOWN_ARG: &str = "example-unknown-arg";
+    /// A property's `default` doesn't match its type or enum.
+    pub const DEFAULT_MISMATCH: &str = "default-mismatch";
+    /// The tool's description is blank. Only under
+    /// [`Strictness::RequireDescriptions`][super::Strictness].
+    pub const MISSING_DESCRIPTION: &str = "missing-description";
+    /// A top-level parameter has no description. Only under
+    /// [`Strictness::RequireDescriptions`][super::Strictness].
+    pub const MISSING_PARAM_DESCRIPTION: &str = "missing-param-description";
+}
+
+/// What a tool must document to be registered. See
+/// [`ToolCollection::set_registration_strictness`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Strictness {
+    /// No requirements on descriptions.
+    #[default]
+    Lenient,
+    /// A non-blank tool description, and one on every top-level
+    /// parameter.
+    RequireDescriptions,
+}
+
+/// One registration check result. `path` is a JSON pointer into the
+/// tool's `parameters` schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegistrationFinding {
+    pub code: &'static str,
+    pub tool: String,
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for RegistrationFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] tool `{}`", self.code, self.tool)?;
+        if !self.path.is_empty() {
+            write!(f, " at {}", self.path)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+struct Findings<'a> {
+    tool: &'a str,
+    out: Vec<RegistrationFinding>,
+}
+
+impl Findings<'_> {
+    fn push(&mut self, code: &'static str, path: impl Into<String>, message: impl Into<String>) {
+        self.out.push(RegistrationFinding {
+            code,
+            tool: self.tool.to_string(),
+            path: path.into(),
+            message: message.into(),
+        });
+    }
+}
+
+/// Run every check on a tool about to be registered.
+pub(crate) fn check(tool: &str, parameters: &Value, func: &ToolFunc) -> Vec<RegistrationFinding> {
+    let mut findings = Findings {
+        tool,
+        out: Vec::new(),
+    };
+    if schema(parameters, &mut findings) {
+        examples(parameters, func, &mut findings);
+        defaults(parameters, &mut findings);
+    }
+    findings.out
+}
+
+/// Blank tool and top-level parameter descriptions.
+fn descriptions(description: &str, parameters: &Value, out: &mut Findings<'_>) {
+    if description.trim().is_empty() {
+        out.push(codes::MISSING_DESCRIPTION, "", "tool has no description");
+    }
+    for (name, prop) in top_level_params(parameters) {
+        let described = prop["description"]
+            .as_str()
+            .is_some_and(|d| !d.trim().is_empty());
+        if !described {
+            out.push(
+                codes::MISSING_PARAM_DESCRIPTION,
+                format!("/properties/{}", escape(name)),
+                format!("parameter `{name}` has no description"),
+            );
+        }
+    }
+}
+
+/// Shape of the top level. Returns whether the schema is sound enough
+/// for the other checks.
+fn schema(parameters: &Value, out: &mut Findings<'_>) -> bool {
+    if !parameters.is_object() {
+        out.push(
+            codes::INVALID_SCHEMA,
+            "",
+            "parameters schema is not an object",
+        );
+        return false;
+    }
+    match &parameters["type"] {
+        Value::Null | Value::Array(_) => {}
+        Value::String(ty) if ty == "object" => {}
+        Value::String(ty) => out.push(
+            codes::PRIMITIVE_PARAMS,
+            "/type",
+            format!(
+                "arguments are a bare {ty}; models call tools with an object of named arguments. \
+                 Take a struct with a named field, or turn on `set_wrap_bare_inputs`"
+            ),
+        ),
+        other => out.push(
+            codes::INVALID_SCHEMA,
+            "/type",
+            format!("`type` must be a string or array, found {other}"),
+        ),
+    }
+    let properties = &parameters["properties"];
+    if !properties.is_null() && !properties.is_object() {
+        out.push(
+            codes::INVALID_SCHEMA,
+            "/properties",
+            "`properties` is not an object",
+        );
+        return false;
+    }
+    match &parameters["required"] {
+        Value::Null => {}
+        Value::Array(required) => {
+            for (i, name) in required.iter().enumerate() {
+                match name.as_str() {
+                    Some(name) if properties.get(name).is_none() => out.push(
+                        codes::INVALID_SCHEMA,
+                        format!("/required/{i}"),
+                        format!("required argument `{name}` is not in `properties`"),
+                    ),
+                    Some(_) => {}
+                    None => out.push(
+                        codes::INVALID_SCHEMA,
+                        format!("/required/{i}"),
+                        "`required` entries must be strings",
+                    ),
+                }
+            }
+        }
+        _ => out.push(
+            codes::INVALID_SCHEMA,
+            "/required",
+            "`required` is not an array",
+        ),
+    }
+    true
+}
+
+/// Top-level `examples` must decode and name only declared arguments.
+fn examples(parameters: &Value, func: &ToolFunc, out: &mut Findings<'_>) {
+    let Some(examples) = parameters["examples"].as_array() else {
+        return;
+    };
+    let objects = parameters["type"] == "object";
+    for (i, example) in examples.iter().enumerate() {
+        if let Err(e) = func.decode(example.clone()) {
+            out.push(
+                codes::EXAMPLE_REJECTED,
+                format!("/examples/{i}"),
+                format!("example does not decode: {e}"),
+            );
+        }
+        if !objects {
+            continue;
+        }
+        for arg in example.as_object().into_iter().flat_map(|o| o.keys()) {
+            if parameters["properties"].get(arg).is_none() {
+                out.push(
+                    codes::EXAMPLE_UNKNOWN_ARG,
+                    format!("/examples/{i}"),
+                    format!("example passes `{arg}`, which the schema does not declare"),
+                );
+            }
+        }
+    }
+}
+
+/// Top-level `default`s must fit their property's `type` and `enum`.
+fn defaults(parameters: &Value, out: &mut Findings<'_>) {
+    for (name, prop) in top_level_params(parameters) {
+        let Some(default) = prop.get("default") else {
+            continue;
+        };
+        let path = format!("/properties/{}/default", escape(name));
+        if !fits_type(default, &prop["type"]) {
+            out.push(
+                codes::DEFAULT_MISMATCH,
+                path,
+                format!("default {default} is not of type {}", prop["type"]),
+            );
+        } else if let Some(allowed) = prop["enum"].as_array() {
+            if !allowed.contains(default) {
+                out.push(
+                    codes::DEFAULT_MISMATCH,
+                    path,
+                    format!("default {default} is not one of the allowed values"),
+                );
+            }
+        }
+    }
+}
+
+fn fits_type(value: &Value, ty: &Value) -> bool {
+    match ty {
+        Value::String(ty) => match ty.as_str() {
+            "null" => value.is_null(),
+            "boolean" => value.is_boolean(),
+            "integer" => value.is_i64() || value.is_u64(),
+            "number" => value.is_number(),
+            "string" => value.is_string(),
+            "array" => value.is_array(),
+            "object" => value.is_object(),
+            _ => true,
+        },
+        Value::Array(types) => types.iter().any(|ty| fits_type(value, ty)),
+        _ => true,
+    }
+}
+
+impl<M> ToolCollection<M> {
+    /// Findings of registrations made in non-strict mode, for tools still
+    /// registered. See [`checks`][crate::checks].
+    pub fn registration_warnings(&self) -> &[RegistrationFinding] {
+        &self.registration_warnings
+    }
+
+    /// Make registration findings errors (`true`) or warnings (`false`,
+    /// the default). Turning strict mode on fails with
+    /// [`ToolError::RegistrationRejected`] if registered tools already
+    /// have warnings.
+    pub fn set_strict_registration(&mut self, strict: bool) -> Result<(), ToolError> {
+        if strict {
+            if let Some(first) = self.registration_warnings.first() {
+                let tool = first.tool.clone();
+                let findings = self
+                    .registration_warnings
+                    .iter()
+                    .filter(|f| f.tool == tool)
+                    .cloned()
+                    .collect();
+                return Err(rejected(tool, findings));
+            }
+        }
+        self.strict_registration = strict;
+        Ok(())
+    }
+
+    /// Set what tools must document to be registered. Under
+    /// [`Strictness::RequireDescriptions`], a blank description or an
+    /// undescribed top-level parameter fails the registration with
+    /// [`ToolError::RegistrationRejected`] listing each one. Fails the
+    /// same way, leaving the setting unchanged, if a registered tool
+    /// falls short.
+    pub fn set_registration_strictness(&mut self, strictness: Strictness) -> Result<(), ToolError> {
+        if strictness == Strictness::RequireDescriptions {
+            for (name, entry) in self.iter() {
+                let mut findings = Findings {
+                    tool: name,
+                    out: Vec::new(),
+                };
+                descriptions(
+                    &entry.decl.description,
+                    &entry.decl.parameters,
+                    &mut findings,
+                );
+                if !findings.out.is_empty() {
+                    return Err(rejected(name.to_string(), findings.out));
+                }
+            }
+        }
+        self.registration_strictness = strictness;
+        Ok(())
+    }
+
+    pub fn registration_strictness(&self) -> Strictness {
+        self.registration_strictness
+    }
+
+    /// Run the checks for a tool about to be registered under `name`.
+    /// The findings are returned for [`record_warnings`], or are an error
+    /// in strict mode or when required descriptions are missing.
+    ///
+    /// [`record_warnings`]: Self::record_warnings
+    pub(crate) fn check_registration(
+        &self,
+        name: &str,
+        description: &str,
+        parameters: &Value,
+        func: &ToolFunc,
+    ) -> Result<Vec<RegistrationFinding>, ToolError> {
+        let mut findings = Findings {
+            tool: name,
+            out: Vec::new(),
+        };
+        if self.registration_strictness == Strictness::RequireDescriptions {
+            descriptions(description, parameters, &mut findings);
+        }
+        let undocumented = !findings.out.is_empty();
+        findings.out.extend(check(name, parameters, func));
+        if undocumented || (self.strict_registration && !findings.out.is_empty()) {
+            return Err(rejected(name.to_string(), findings.out));
+        }
+        Ok(findings.out)
+    }
+
+    /// Replace the warnings kept for `name`.
+    pub(crate) fn record_warnings(&mut self, name: &str, findings: Vec<RegistrationFinding>) {
+        self.registration_warnings.retain(|f| f.tool != name);
+        self.registration_warnings.extend(findings);
+    }
+}
+
+pub(crate) fn rejected(tool: String, findings: Vec<RegistrationFinding>) -> ToolError {
+    let summary = findings
+        .iter()
+        .map(|f| f.message.as_str())
+        .collect::<Vec<_>>()
+        .join("; ");
+    ToolError::RegistrationRejected {
+        tool,
+        findings,
+        summary,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn types_and_unions() {
+        assert!(fits_type(&json!(3), &json!("integer")));
+        assert!(!fits_type(&json!(3.5), &json!("integer")));
+        assert!(fits_type(&json!(3.5), &json!("number")));
+        assert!(fits_type(&json!(null), &json!(["string", "null"])));
+        assert!(!fits_type(&json!(1), &json!(["string", "null"])));
+        assert!(fits_type(&json!(1), &Value::Null));
+    }
+}
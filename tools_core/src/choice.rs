@@ -0,0 +1,133 @@
+//! Steering which tool the model calls next.
+//!
+//! Providers let a request require a tool call, forbid one, or force a
+//! specific tool, each with its own JSON. [`ToolChoice`] says what you
+//! want once; the per-provider modules lower it, checking that a forced
+//! tool is actually among the declarations being sent:
+//!
+//! ```ignore
+//! let researcher = tools.profile("researcher").unwrap();
+//! let body = json!({
+//!     "model": "claude-sonnet-4-5",
+//!     "tools": researcher.json()?,
+//!     "tool_choice": anthropic::tool_choice(&ToolChoice::specific("search_web"), &researcher)?,
+//! });
+//! ```
+//!
+//! [`openai::force_tool`] and [`anthropic::force_tool`] build the forcing
+//! JSON directly, without a collection to check against.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::{ToolCollection, ToolError, profile::Profiled};
+
+/// Whether and which tool the model should call on the next turn.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolChoice {
+    /// The model decides.
+    #[default]
+    Auto,
+    /// No tool calls.
+    None,
+    /// Some tool call, of the model's choosing.
+    Required,
+    /// A call to the named tool.
+    Specific(String),
+}
+
+impl ToolChoice {
+    pub fn specific(name: impl Into<String>) -> Self {
+        Self::Specific(name.into())
+    }
+
+    /// Check that a forced tool is declared by `tools`.
+    pub fn check(&self, tools: &impl Declares) -> Result<(), ToolError> {
+        match self {
+            Self::Specific(name) if !tools.declares(name) => Err(ToolError::FunctionNotFound {
+                name: name.clone().into(),
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A set of declarations sent to a model: a whole collection or a
+/// [`Profiled`] view of one.
+pub trait Declares {
+    /// Whether a tool of exactly this name is declared.
+    fn declares(&self, name: &str) -> bool;
+}
+
+impl<M> Declares for ToolCollection<M> {
+    fn declares(&self, name: &str) -> bool {
+        self.names().canonical(name).is_some()
+    }
+}
+
+impl<M> Declares for Profiled<'_, M> {
+    fn declares(&self, name: &str) -> bool {
+        self.declarations().iter().any(|d| d.name == name)
+    }
+}
+
+/// OpenAI Chat Completions `tool_choice`.
+pub mod openai {
+    use super::*;
+
+    /// `tool_choice` forcing a call to `name`.
+    pub fn force_tool(name: &str) -> Value {
+        json!({ "type": "function", "function": { "name": name } })
+    }
+
+    /// `tool_choice` for `choice`, checked against `tools`.
+    pub fn tool_choice(choice: &ToolChoice, tools: &impl Declares) -> Result<Value, ToolError> {
+        choice.check(tools)?;
+        Ok(match choice {
+            ToolChoice::Auto => json!("auto"),
+            ToolChoice::None => json!("none"),
+            ToolChoice::Required => json!("required"),
+            ToolChoice::Specific(name) => force_tool(name),
+        })
+    }
+}
+
+/// Anthropic Messages `tool_choice`.
+pub mod anthropic {
+    use super::*;
+
+    /// `tool_choice` forcing a call to `name`.
+    pub fn force_tool(name: &str) -> Value {
+        json!({ "type": "tool", "name": name })
+    }
+
+    /// `tool_choice` for `choice`, checked against `tools`.
+    pub fn tool_choice(choice: &ToolChoice, tools: &impl Declares) -> Result<Value, ToolError> {
+        choice.check(tools)?;
+        Ok(match choice {
+            ToolChoice::Auto => json!({ "type": "auto" }),
+            ToolChoice::None => json!({ "type": "none" }),
+            ToolChoice::Required => json!({ "type": "any" }),
+            ToolChoice::Specific(name) => force_tool(name),
+        })
+    }
+}
+
+/// Gemini `toolConfig`.
+pub mod gemini {
+    use super::*;
+
+    /// `toolConfig` for `choice`, checked against `tools`. Gemini has no
+    /// single-tool mode; a forced tool is `ANY` restricted to that name.
+    pub fn tool_config(choice: &ToolChoice, tools: &impl Declares) -> Result<Value, ToolError> {
+        choice.check(tools)?;
+        let config = match choice {
+            ToolChoice::Auto => json!({ "mode": "AUTO" }),
+            ToolChoice::None => json!({ "mode": "NONE" }),
+            ToolChoice::Required => json!({ "mode": "ANY" }),
+            ToolChoice::Specific(name) => json!({ "mode": "ANY", "allowedFunctionNames": [name] }),
+        };
+        Ok(json!({ "functionCallingConfig": config }))
+    }
+}
@@ -0,0 +1,307 @@
+//! Per-tool circuit breakers.
+//!
+//! A tool whose dependency is down keeps failing, and every failed call
+//! costs a full timeout. After `failure_threshold` consecutive failures
+//! the breaker opens and calls fail fast with [`ToolError::CircuitOpen`].
+//! Once `cooldown` has elapsed, one probe call is let through: success
+//! closes the breaker, another failure opens it again.
+//!
+//! Failures are the errors that say something about the tool rather than
+//! the call: [`Transient`][ErrorClass::Transient] and
+//! [`Internal`][ErrorClass::Internal] errors, which include timeouts,
+//! plain [`Runtime`][ToolError::Runtime] errors and panics, and a
+//! deadline that cut the tool off while it ran. Invalid arguments,
+//! denials and other errors the caller can fix count as answers: they
+//! reset the count like a success.
+//!
+//! ```ignore
+//! tools.set_circuit_breaker("get_weather", CircuitConfig {
+//!     failure_threshold: 3,
+//!     cooldown: Duration::from_secs(30),
+//! })?;
+//! ```
+//!
+//! Breaker state lives behind an `Arc` in the tool's [`ToolEntry`], so
+//! clones of the collection share it.
+
+use std::{
+    borrow::Cow,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+
+use futures::future::BoxFuture;
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::oneshot;
+
+use crate::{ErrorClass, ToolCollection, ToolEntry, ToolError};
+
+/// When a breaker opens and for how long.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitConfig {
+    /// Consecutive failures that open the breaker.
+    pub failure_threshold: u32,
+    /// How long the breaker stays open before a probe call is allowed.
+    pub cooldown: Duration,
+}
+
+impl Default for CircuitConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            cooldown: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Source of the current time for breakers, caches and
+/// [schedules][crate::schedule]. Swap in a [`ManualClock`] to test
+/// cooldowns and intervals without sleeping.
+pub trait Clock: Send + Sync + 'static {
+    fn now(&self) -> Instant;
+
+    /// Wait until [`now`][Self::now] reaches `deadline`. A tokio sleep by
+    /// default.
+    fn sleep_until(&self, deadline: Instant) -> BoxFuture<'static, ()> {
+        Box::pin(tokio::time::sleep_until(deadline.into()))
+    }
+}
+
+/// [`Clock`] backed by [`Instant::now`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// [`Clock`] that only moves when [`advance`][Self::advance] is called.
+#[derive(Debug)]
+pub struct ManualClock {
+    start: Instant,
+    offset: Mutex<Duration>,
+    /// Pending [`sleep_until`][Clock::sleep_until]s, woken by `advance`.
+    sleepers: Mutex<Vec<(Instant, oneshot::Sender<()>)>>,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            offset: Mutex::new(Duration::ZERO),
+            sleepers: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Move the clock forward, waking every sleep that is now over.
+    pub fn advance(&self, by: Duration) {
+        let now = {
+            let mut offset = lock(&self.offset);
+            *offset += by;
+            self.start + *offset
+        };
+        lock(&self.sleepers).retain_mut(|(deadline, _)| *deadline > now);
+        // Dropping a sender wakes its sleeper.
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.start + *lock(&self.offset)
+    }
+
+    fn sleep_until(&self, deadline: Instant) -> BoxFuture<'static, ()> {
+        let mut sleepers = lock(&self.sleepers);
+        if deadline <= self.now() {
+            return Box::pin(async {});
+        }
+        let (wake, woken) = oneshot::channel();
+        sleepers.push((deadline, wake));
+        Box::pin(async move {
+            let _ = woken.await;
+        })
+    }
+}
+
+/// Whether a breaker lets calls through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Calls run normally.
+    Closed,
+    /// Calls fail with [`ToolError::CircuitOpen`].
+    Open,
+    /// The cooldown elapsed and a probe call is in flight.
+    HalfOpen,
+}
+
+/// Breaker state as reported by [`ToolCollection::circuit`] and
+/// [`ToolCollection::introspect`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct CircuitReport {
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    pub failure_threshold: u32,
+    pub cooldown_ms: u64,
+    /// Time until the next probe is allowed, while open.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_after_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum State {
+    Closed {
+        failures: u32,
+    },
+    Open {
+        until: Instant,
+    },
+    /// `since` lets another probe through if the first one never
+    /// reported back (e.g. its future was dropped).
+    HalfOpen {
+        since: Instant,
+    },
+}
+
+/// A tool's breaker. Attach one with
+/// [`ToolCollection::set_circuit_breaker`].
+pub struct Circuit {
+    config: CircuitConfig,
+    clock: Arc<dyn Clock>,
+    state: Mutex<State>,
+}
+
+fn lock<T>(m: &Mutex<T>) -> MutexGuard<'_, T> {
+    m.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn millis(d: Duration) -> u64 {
+    d.as_millis().try_into().unwrap_or(u64::MAX)
+}
+
+impl Circuit {
+    pub fn new(config: CircuitConfig, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            config,
+            clock,
+            state: Mutex::new(State::Closed { failures: 0 }),
+        }
+    }
+
+    /// Admit a call to `tool`, or fail with [`ToolError::CircuitOpen`].
+    pub(crate) fn acquire(&self, tool: &str) -> Result<(), ToolError> {
+        let now = self.clock.now();
+        let mut state = lock(&self.state);
+        let retry_at = match *state {
+            State::Closed { .. } => return Ok(()),
+            State::Open { until } => until,
+            State::HalfOpen { since } => since + self.config.cooldown,
+        };
+        if now >= retry_at {
+            *state = State::HalfOpen { since: now };
+            return Ok(());
+        }
+        Err(ToolError::CircuitOpen {
+            name: tool.to_owned().into(),
+            retry_after: retry_at - now,
+        })
+    }
+
+    /// Update the breaker with the outcome of an admitted call. See the
+    /// [module docs][self] for which errors count as failures.
+    pub(crate) fn record(&self, outcome: &Result<Value, ToolError>) {
+        let failed = matches!(outcome, Err(e) if counts_as_failure(e));
+        let mut state = lock(&self.state);
+        *state = match (*state, failed) {
+            (_, false) => State::Closed { failures: 0 },
+            (State::Closed { failures }, true) if failures + 1 < self.config.failure_threshold => {
+                State::Closed {
+                    failures: failures + 1,
+                }
+            }
+            (_, true) => State::Open {
+                until: self.clock.now() + self.config.cooldown,
+            },
+        };
+    }
+
+    pub fn report(&self) -> CircuitReport {
+        let now = self.clock.now();
+        let (state, consecutive_failures, retry_after) = match *lock(&self.state) {
+            State::Closed { failures } => (CircuitState::Closed, failures, None),
+            State::Open { until } => (
+                CircuitState::Open,
+                self.config.failure_threshold,
+                Some(until.saturating_duration_since(now)),
+            ),
+            State::HalfOpen { .. } => (CircuitState::HalfOpen, self.config.failure_threshold, None),
+        };
+        CircuitReport {
+            state,
+            consecutive_failures,
+            failure_threshold: self.config.failure_threshold,
+            cooldown_ms: millis(self.config.cooldown),
+            retry_after_ms: retry_after.map(millis),
+        }
+    }
+}
+
+fn counts_as_failure(error: &ToolError) -> bool {
+    matches!(error, ToolError::DeadlineExceeded { .. })
+        || matches!(
+            error.classification(),
+            ErrorClass::Transient | ErrorClass::Internal
+        )
+}
+
+impl<M> ToolCollection<M> {
+    /// Attach a circuit breaker to the tool `name`, replacing any existing
+    /// one.
+    pub fn set_circuit_breaker(
+        &mut self,
+        name: &str,
+        config: CircuitConfig,
+    ) -> Result<(), ToolError> {
+        self.set_circuit_breaker_with_clock(name, config, Arc::new(SystemClock))
+    }
+
+    /// [`set_circuit_breaker`][Self::set_circuit_breaker] with a custom
+    /// [`Clock`].
+    pub fn set_circuit_breaker_with_clock(
+        &mut self,
+        name: &str,
+        config: CircuitConfig,
+        clock: Arc<dyn Clock>,
+    ) -> Result<(), ToolError> {
+        self.entry_mut(name)?.circuit = Some(Arc::new(Circuit::new(config, clock)));
+        Ok(())
+    }
+
+    /// Remove the tool's circuit breaker, if any.
+    pub fn clear_circuit_breaker(&mut self, name: &str) -> Result<(), ToolError> {
+        self.entry_mut(name)?.circuit = None;
+        Ok(())
+    }
+
+    /// State of the tool's circuit breaker, if it has one.
+    pub fn circuit(&self, name: &str) -> Option<CircuitReport> {
+        self.get(name)?.circuit.as_ref().map(|c| c.report())
+    }
+
+    pub(crate) fn entry_mut(&mut self, name: &str) -> Result<&mut ToolEntry<M>, ToolError> {
+        self.resolve(name)
+            .and_then(|id| self.entries.get_mut(id.index())?.as_mut())
+            .ok_or_else(|| ToolError::FunctionNotFound {
+                name: Cow::Owned(name.to_string()),
+            })
+    }
+}
@@ -0,0 +1,257 @@
+//! Async teardown for stateful tools.
+//!
+//! A tool that owns a browser session or a connection pool can register a
+//! cleanup alongside it, with [`ToolCollection::register_with_cleanup`] or
+//! [`ToolSpec::cleanup`][crate::ToolSpec::cleanup]. The cleanup runs at
+//! most once, when the tool leaves the collection:
+//!
+//! - [`unregister`][ToolCollection::unregister] and
+//!   [`clear`][ToolCollection::clear] start it on the current tokio
+//!   runtime as soon as no call is running on the tool. Outside a runtime
+//!   it is queued instead.
+//! - [`shutdown`][ToolCollection::shutdown] removes every remaining tool
+//!   and awaits all cleanups, including those started or queued earlier.
+//!   Queued cleanups run one after another in removal order.
+//! - [`shutdown_gracefully`][ToolCollection::shutdown_gracefully] does the
+//!   same for a shared collection once its calls are drained, leaving the
+//!   tools registered.
+//!
+//! `Drop` can't be async, so a collection dropped without `shutdown` only
+//! runs the cleanups already spawned. Clones of a collection share their
+//! tools' cleanups: whichever clone removes the tool first runs it, after
+//! the calls still running on the tool through any clone, or through an
+//! older snapshot of a [`SharedToolCollection`][crate::SharedToolCollection],
+//! have returned. `shutdown` waits for those calls as well.
+//!
+//! ```ignore
+//! tools.register_with_cleanup(
+//!     "browse",
+//!     "Opens a page in the shared browser session.",
+//!     move |url: String| { let s = session.clone(); async move { s.open(&url).await } },
+//!     (),
+//!     move || Box::pin(async move { browser.close().await }),
+//! )?;
+//! // ...
+//! tools.shutdown().await;
+//! ```
+
+use std::{
+    borrow::Cow,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+use futures::future::BoxFuture;
+use serde::{Serialize, de::DeserializeOwned};
+use tokio::{sync::oneshot, task::JoinHandle};
+
+use crate::{MetaArg, ToolCollection, ToolEntry, ToolError, ToolSchema};
+
+pub(crate) type CleanupFn = Box<dyn FnOnce() -> BoxFuture<'static, ()> + Send>;
+
+/// A tool's teardown. Shared by clones of its [`ToolEntry`]; taken by
+/// whichever runs it first.
+pub struct Cleanup(Mutex<State>);
+
+struct State {
+    f: Option<CleanupFn>,
+    /// Calls running on the tool, through any clone of its entry.
+    calls: usize,
+    /// Set when the tool was removed while calls were running. The last
+    /// of them starts the cleanup and sends it here.
+    deferred: Option<oneshot::Sender<BoxFuture<'static, ()>>>,
+}
+
+impl Cleanup {
+    pub(crate) fn new(f: CleanupFn) -> Self {
+        Self(Mutex::new(State {
+            f: Some(f),
+            calls: 0,
+            deferred: None,
+        }))
+    }
+
+    /// Whether the cleanup has already been started.
+    pub fn has_run(&self) -> bool {
+        self.state().f.is_none()
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn take(&self) -> Option<BoxFuture<'static, ()>> {
+        let f = self.state().f.take()?;
+        Some(f())
+    }
+
+    /// Hold the cleanup back until the returned guard drops.
+    pub(crate) fn enter(self: &Arc<Self>) -> Running {
+        self.state().calls += 1;
+        Running(self.clone())
+    }
+
+    /// Start the cleanup of a removed tool, or leave it to the last call
+    /// still running on it.
+    fn retire(&self) -> Option<Pending> {
+        let mut state = self.state();
+        if state.calls > 0 {
+            if state.f.is_none() || state.deferred.is_some() {
+                return None;
+            }
+            let (send, receive) = oneshot::channel();
+            state.deferred = Some(send);
+            return Some(Pending::Deferred(receive));
+        }
+        let f = state.f.take()?;
+        drop(state);
+        Some(start(f()))
+    }
+}
+
+/// A call running on a tool with a [`Cleanup`]. The last one to drop
+/// after the tool was removed starts the cleanup.
+pub(crate) struct Running(Arc<Cleanup>);
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        let mut state = self.0.state();
+        state.calls -= 1;
+        if state.calls > 0 {
+            return;
+        }
+        let Some(send) = state.deferred.take() else {
+            return;
+        };
+        let Some(f) = state.f.take() else {
+            return;
+        };
+        drop(state);
+        let fut = match start(f()) {
+            Pending::Spawned(handle) => Box::pin(async move {
+                let _ = handle.await;
+            }),
+            Pending::Queued(fut) => fut,
+            Pending::Deferred(_) => unreachable!("`start` never defers"),
+        };
+        // Nobody to await it if the collection was dropped; a spawned
+        // cleanup still runs.
+        let _ = send.send(fut);
+    }
+}
+
+/// Spawn `fut` on the current runtime, or queue it outside one.
+fn start(fut: BoxFuture<'static, ()>) -> Pending {
+    match tokio::runtime::Handle::try_current() {
+        Ok(rt) => Pending::Spawned(rt.spawn(fut)),
+        Err(_) => Pending::Queued(fut),
+    }
+}
+
+/// A cleanup that has left its entry but has not been awaited yet.
+pub(crate) enum Pending {
+    Spawned(JoinHandle<()>),
+    Queued(BoxFuture<'static, ()>),
+    /// Started by the last call running on the tool when it was removed.
+    Deferred(oneshot::Receiver<BoxFuture<'static, ()>>),
+}
+
+impl<M> ToolCollection<M> {
+    /// [`register`][Self::register] with a cleanup that runs when the tool
+    /// is removed. See the [module docs][crate::cleanup].
+    pub fn register_with_cleanup<A, I, O, F, Fut, C>(
+        &mut self,
+        name: impl Into<Cow<'static, str>>,
+        desc: impl Into<Cow<'static, str>>,
+        func: F,
+        meta: A,
+        cleanup: C,
+    ) -> Result<&mut Self, ToolError>
+    where
+        A: MetaArg<M>,
+        I: 'static + DeserializeOwned + Serialize + Send + ToolSchema,
+        O: 'static + Serialize + Send + ToolSchema,
+        F: Fn(I) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = O> + Send + 'static,
+        C: FnOnce() -> BoxFuture<'static, ()> + Send + 'static,
+    {
+        let spec = Self::typed_spec(name, desc, func, meta)?.cleanup(cleanup);
+        self.register_spec(spec)?;
+        Ok(self)
+    }
+
+    /// Remove every tool, starting their cleanups like
+    /// [`unregister`][Self::unregister]. Ids handed out earlier stay dead.
+    pub fn clear(&mut self) {
+        self.names.clear();
+        self.registration_warnings.clear();
+        self.json_cache.take();
+        let removed: Vec<ToolEntry<M>> = self.entries.iter_mut().filter_map(Option::take).collect();
+        for entry in removed {
+            self.retire(entry);
+        }
+    }
+
+    /// Remove every tool and wait for all cleanups to finish, including
+    /// those started by earlier `unregister` or `clear` calls.
+    pub async fn shutdown(&mut self) {
+        self.clear();
+        self.await_pending().await;
+    }
+
+    /// Start the cleanup of every registered tool, leaving the tools in
+    /// place, and await them with the pending ones. Returns how many were
+    /// awaited.
+    pub(crate) async fn run_cleanups(&self) -> usize {
+        let started: Vec<_> = self
+            .entries
+            .iter()
+            .flatten()
+            .filter_map(|entry| entry.cleanup.as_ref()?.take())
+            .collect();
+        let count = started.len() + self.await_pending().await;
+        for fut in started {
+            fut.await;
+        }
+        count
+    }
+
+    /// Await the cleanups of removed tools. Returns how many there were.
+    async fn await_pending(&self) -> usize {
+        let pending = std::mem::take(
+            &mut *self
+                .pending_cleanups
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner),
+        );
+        let count = pending.len();
+        for cleanup in pending {
+            match cleanup {
+                // A panicking cleanup must not stop the others.
+                Pending::Spawned(handle) => {
+                    let _ = handle.await;
+                }
+                Pending::Queued(fut) => fut.await,
+                Pending::Deferred(started) => {
+                    if let Ok(fut) = started.await {
+                        fut.await;
+                    }
+                }
+            }
+        }
+        count
+    }
+
+    /// Start the cleanup of an entry that just left the collection, once
+    /// no call is running on it.
+    pub(crate) fn retire(&mut self, entry: ToolEntry<M>) {
+        let Some(started) = entry.cleanup.as_ref().and_then(|c| c.retire()) else {
+            return;
+        };
+        let pending = self
+            .pending_cleanups
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner);
+        pending.retain(|p| !matches!(p, Pending::Spawned(h) if h.is_finished()));
+        pending.push(started);
+    }
+}
@@ -0,0 +1,127 @@
+//! Dropping `null` and empty fields from results before the model sees
+//! them.
+//!
+//! Serialized outputs are full of `"middle_name": null` and `"tags": []`,
+//! which cost tokens and say nothing. `#[serde(skip_serializing_if)]`
+//! fixes that one field at a time; with compaction on, the collection
+//! does it for every result instead:
+//!
+//! ```ignore
+//! tools.set_compact_results(true);
+//! // {"name": "Ada", "email": null, "tags": [], "address": {"line2": null}}
+//! // is returned as
+//! // {"name": "Ada"}
+//! tools.set_compaction_exempt("lookup_flag", true)?; // here `null` means "unset"
+//! ```
+//!
+//! Object fields that are `null`, `[]` or `{}` are removed, after their
+//! own contents are compacted. Array items are compacted but never
+//! removed, so positions keep their meaning. The top-level result is left
+//! in place even when it ends up empty. Arrays and objects nested
+//! [`MAX_DEPTH`] levels deep are kept as they are.
+//!
+//! Only the returned [`FunctionResponse`][crate::FunctionResponse] is
+//! compacted. [`recent_calls`][ToolCollection::recent_calls], the result
+//! cache and journals see the full result.
+
+use serde_json::Value;
+
+use crate::{ToolCollection, ToolError};
+
+/// Nesting below which [`compact`] stops descending.
+pub const MAX_DEPTH: usize = 64;
+
+/// Remove `null` and empty object fields from `value`, in place.
+pub fn compact(value: &mut Value) {
+    compact_at(value, 0);
+}
+
+/// Compact `value` at `depth`; true if it is now `null` or empty.
+fn compact_at(value: &mut Value, depth: usize) -> bool {
+    match value {
+        Value::Null => true,
+        Value::Array(_) | Value::Object(_) if depth >= MAX_DEPTH => false,
+        Value::Array(items) => {
+            for item in items.iter_mut() {
+                compact_at(item, depth + 1);
+            }
+            items.is_empty()
+        }
+        Value::Object(map) => {
+            map.retain(|_, field| !compact_at(field, depth + 1));
+            map.is_empty()
+        }
+        Value::Bool(_) | Value::Number(_) | Value::String(_) => false,
+    }
+}
+
+impl<M> ToolCollection<M> {
+    /// Compact every tool's results before returning them; see
+    /// [`compact`][crate::compact].
+    pub fn set_compact_results(&mut self, compact: bool) {
+        self.compact_results = compact;
+    }
+
+    pub fn compact_results(&self) -> bool {
+        self.compact_results
+    }
+
+    /// Return the tool's results untouched even while compaction is on,
+    /// for tools where `null` or an empty list means something.
+    pub fn set_compaction_exempt(&mut self, name: &str, exempt: bool) -> Result<(), ToolError> {
+        self.entry_mut(name)?.compaction_exempt = exempt;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn drops_empty_fields_bottom_up() {
+        let mut v = json!({
+            "name": "Ada",
+            "email": null,
+            "tags": [],
+            "address": { "line2": null, "extra": {} },
+            "scores": [null, {}, { "a": null, "b": 0 }],
+            "flags": [false, ""]
+        });
+        compact(&mut v);
+        assert_eq!(
+            v,
+            json!({
+                "name": "Ada",
+                "scores": [null, {}, { "b": 0 }],
+                "flags": [false, ""]
+            })
+        );
+    }
+
+    #[test]
+    fn top_level_is_kept() {
+        let mut v = json!({ "a": null });
+        compact(&mut v);
+        assert_eq!(v, json!({}));
+        let mut v = Value::Null;
+        compact(&mut v);
+        assert_eq!(v, Value::Null);
+    }
+
+    #[test]
+    fn stops_at_max_depth() {
+        let mut deep = json!({ "leaf": null });
+        for _ in 0..MAX_DEPTH {
+            deep = json!({ "next": deep, "gone": null });
+        }
+        compact(&mut deep);
+        let mut v = &deep;
+        for _ in 0..MAX_DEPTH {
+            assert!(v.get("gone").is_none());
+            v = &v["next"];
+        }
+        assert_eq!(v, &json!({ "leaf": null }));
+    }
+}
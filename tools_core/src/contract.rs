@@ -0,0 +1,365 @@
+//! Contract tests recorded from real calls (`testing` feature).
+//!
+//! During a dev session, attach a [`ContractRecorder`] and use the tools as
+//! usual; every call is appended to a JSONL file as its tool, arguments and
+//! result. Check the file in, and a test replays each call against the
+//! current registry:
+//!
+//! ```ignore
+//! // dev session
+//! ContractRecorder::attach(&mut tools, "contracts/weather.jsonl")?;
+//!
+//! // tests/contracts.rs
+//! #[tokio::test]
+//! async fn weather_contracts_hold() {
+//!     let tools = collect_tools();
+//!     run_contracts(&tools, "contracts/weather.jsonl", &["/fetched_at"]).await;
+//! }
+//! ```
+//!
+//! The first line is a header naming the format and its version; each
+//! following line is one call in [canonical JSON][crate::hash::canonical_json]:
+//!
+//! ```text
+//! {"format":"tools-rs/contracts","version":1}
+//! {"arguments":{"city":"Paris"},"result":{"temp":21},"tool":"weather"}
+//! {"arguments":{},"error":{"class":"invalid_input","message":"..."},"tool":"weather"}
+//! ```
+//!
+//! Tools are recorded under their registered name, with the arguments as
+//! sent and the result as handed back. A replayed call must give the same
+//! result, or fail with the same class and message. Ignore paths are JSON
+//! pointers into the result, where `*` matches any key or index; they are
+//! removed from both sides before comparing.
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex, PoisonError,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    ErrorClass, FunctionCall, FunctionResponse, ToolCollection, ToolError, hash, testing::diff,
+};
+
+/// Value of the header's `format` key.
+pub const CONTRACT_FORMAT: &str = "tools-rs/contracts";
+
+/// Current value of the header's `version` key.
+pub const CONTRACT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct Header {
+    format: String,
+    version: u32,
+}
+
+/// One recorded call.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Contract {
+    pub tool: String,
+    pub arguments: Value,
+    #[serde(flatten)]
+    pub outcome: ContractOutcome,
+}
+
+/// What a recorded call gave back.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContractOutcome {
+    Result(Value),
+    Error { class: ErrorClass, message: String },
+}
+
+impl ContractOutcome {
+    fn of(result: &Result<FunctionResponse, ToolError>) -> Self {
+        match result {
+            Ok(response) => Self::Result(response.result.clone()),
+            Err(e) => Self::Error {
+                class: e.classification(),
+                message: e.to_string(),
+            },
+        }
+    }
+}
+
+/// Appends every call made through a collection to a contract file; see
+/// the [module docs][self].
+pub struct ContractRecorder {
+    path: PathBuf,
+    file: Mutex<File>,
+    recorded: AtomicU64,
+    dropped: AtomicU64,
+}
+
+impl ContractRecorder {
+    /// Record calls through `tools` and its later clones at `path`,
+    /// appending to the file if it already holds contracts.
+    pub fn attach<M>(
+        tools: &mut ToolCollection<M>,
+        path: impl AsRef<Path>,
+    ) -> Result<Arc<Self>, ToolError> {
+        let path = path.as_ref();
+        let existing = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(io_error("read", path, e)),
+        };
+        let mut file = OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(path)
+            .map_err(|e| io_error("open", path, e))?;
+        match existing.lines().next() {
+            Some(header) => check_header(path, header)?,
+            None => {
+                let header = serde_json::to_value(Header {
+                    format: CONTRACT_FORMAT.into(),
+                    version: CONTRACT_VERSION,
+                })?;
+                writeln!(file, "{}", hash::canonical_json(&header))
+                    .map_err(|e| io_error("write", path, e))?;
+            }
+        }
+        if !existing.is_empty() && !existing.ends_with('\n') {
+            // Start the next record on a fresh line.
+            file.write_all(b"\n")
+                .map_err(|e| io_error("write", path, e))?;
+        }
+        let recorder = Arc::new(Self {
+            path: path.to_path_buf(),
+            file: Mutex::new(file),
+            recorded: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+        });
+        tools.contracts = Some(recorder.clone());
+        Ok(recorder)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Calls written since [`attach`][Self::attach].
+    pub fn recorded(&self) -> u64 {
+        self.recorded.load(Ordering::Relaxed)
+    }
+
+    /// Calls that could not be written and were dropped.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn record(
+        &self,
+        tool: &str,
+        arguments: Value,
+        result: &Result<FunctionResponse, ToolError>,
+    ) {
+        let contract = Contract {
+            tool: tool.to_string(),
+            arguments,
+            outcome: ContractOutcome::of(result),
+        };
+        let written = serde_json::to_value(&contract).is_ok_and(|value| {
+            let mut file = self.file.lock().unwrap_or_else(PoisonError::into_inner);
+            writeln!(file, "{}", hash::canonical_json(&value)).is_ok()
+        });
+        let counter = if written {
+            &self.recorded
+        } else {
+            &self.dropped
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl<M> ToolCollection<M> {
+    /// Stop recording contracts.
+    pub fn detach_contract_recorder(&mut self) {
+        self.contracts = None;
+    }
+}
+
+/// A replayed call whose outcome changed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContractMismatch {
+    /// Line of the call in the contract file, counting from 1.
+    pub line: usize,
+    pub tool: String,
+    pub arguments: Value,
+    /// Line diff of the recorded outcome (`-`) against the replayed one
+    /// (`+`), after removing ignored paths.
+    pub diff: String,
+}
+
+impl std::fmt::Display for ContractMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "line {}: `{}` with {}",
+            self.line,
+            self.tool,
+            hash::canonical_json(&self.arguments)
+        )?;
+        f.write_str(&self.diff)
+    }
+}
+
+/// Load the contracts at `path`, with the line each one is on.
+pub fn load_contracts(path: impl AsRef<Path>) -> Result<Vec<(usize, Contract)>, ToolError> {
+    let path = path.as_ref();
+    let text = fs::read_to_string(path).map_err(|e| io_error("read", path, e))?;
+    let mut lines = text.lines().enumerate();
+    let Some((_, header)) = lines.next() else {
+        return Err(ToolError::Runtime(format!(
+            "contract file {} is empty",
+            path.display()
+        )));
+    };
+    check_header(path, header)?;
+    lines
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line).map(|c| (i + 1, c)).map_err(|e| {
+                ToolError::Runtime(format!(
+                    "contract file {} line {}: {e}",
+                    path.display(),
+                    i + 1
+                ))
+            })
+        })
+        .collect()
+}
+
+/// Replay every call at `path` against `tools` and list those whose
+/// outcome changed. `ignore` holds JSON pointers into results to leave
+/// out of the comparison.
+pub async fn check_contracts<M>(
+    tools: &ToolCollection<M>,
+    path: impl AsRef<Path>,
+    ignore: &[&str],
+) -> Result<Vec<ContractMismatch>, ToolError> {
+    let mut mismatches = Vec::new();
+    for (line, contract) in load_contracts(path)? {
+        let call = FunctionCall::new(contract.tool.clone(), contract.arguments.clone());
+        let replayed = ContractOutcome::of(&tools.call_ref(&call).await);
+        // Compared in canonical form, where `10` and `10.0` are the same.
+        let expected = hash::canonical_json_pretty(&comparable(contract.outcome, ignore));
+        let actual = hash::canonical_json_pretty(&comparable(replayed, ignore));
+        if expected != actual {
+            mismatches.push(ContractMismatch {
+                line,
+                tool: contract.tool,
+                arguments: contract.arguments,
+                diff: diff(&expected, &actual),
+            });
+        }
+    }
+    Ok(mismatches)
+}
+
+/// [`check_contracts`], panicking with every mismatch's diff.
+pub async fn run_contracts<M>(tools: &ToolCollection<M>, path: impl AsRef<Path>, ignore: &[&str]) {
+    let path = path.as_ref();
+    let mismatches = check_contracts(tools, path, ignore)
+        .await
+        .unwrap_or_else(|e| panic!("{e}"));
+    if !mismatches.is_empty() {
+        let report: Vec<String> = mismatches.iter().map(ToString::to_string).collect();
+        panic!(
+            "{} of the contracts in {} changed\n{}",
+            mismatches.len(),
+            path.display(),
+            report.join("\n")
+        );
+    }
+}
+
+fn check_header(path: &Path, line: &str) -> Result<(), ToolError> {
+    match serde_json::from_str::<Header>(line) {
+        Ok(h) if h.format == CONTRACT_FORMAT && h.version == CONTRACT_VERSION => Ok(()),
+        Ok(h) if h.format == CONTRACT_FORMAT => Err(ToolError::Runtime(format!(
+            "contract file {} has version {}; this build reads version {CONTRACT_VERSION}",
+            path.display(),
+            h.version
+        ))),
+        _ => Err(ToolError::Runtime(format!(
+            "{} is not a contract file: its first line is not a `{CONTRACT_FORMAT}` header",
+            path.display()
+        ))),
+    }
+}
+
+fn io_error(action: &str, path: &Path, e: std::io::Error) -> ToolError {
+    ToolError::Runtime(format!(
+        "contract file {} {action} failed: {e}",
+        path.display()
+    ))
+}
+
+/// `outcome` as a value to compare, with the `ignore` paths removed from
+/// a result.
+fn comparable(outcome: ContractOutcome, ignore: &[&str]) -> Value {
+    let mut outcome = outcome;
+    if let ContractOutcome::Result(result) = &mut outcome {
+        for pointer in ignore {
+            let segments: Vec<String> = pointer
+                .split('/')
+                .skip(1)
+                .map(|s| s.replace("~1", "/").replace("~0", "~"))
+                .collect();
+            remove(result, &segments);
+        }
+    }
+    serde_json::to_value(outcome).unwrap_or(Value::Null)
+}
+
+fn remove(value: &mut Value, segments: &[String]) {
+    let Some((first, rest)) = segments.split_first() else {
+        // The empty pointer names the whole value.
+        *value = Value::Null;
+        return;
+    };
+    match value {
+        Value::Object(map) if rest.is_empty() => {
+            if first == "*" {
+                map.clear();
+            } else {
+                map.remove(first);
+            }
+        }
+        Value::Object(map) if first == "*" => map.values_mut().for_each(|v| remove(v, rest)),
+        Value::Object(map) => {
+            if let Some(v) = map.get_mut(first) {
+                remove(v, rest);
+            }
+        }
+        Value::Array(items) => {
+            let selected: Vec<usize> = match first.as_str() {
+                "*" => (0..items.len()).collect(),
+                index => index
+                    .parse()
+                    .into_iter()
+                    .filter(|&i| i < items.len())
+                    .collect(),
+            };
+            for i in selected {
+                if rest.is_empty() {
+                    // Blank rather than remove, so later indices still line up.
+                    items[i] = Value::Null;
+                } else {
+                    remove(&mut items[i], rest);
+                }
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,25 @@
+//! The conversation a call belongs to.
+//!
+//! Set with [`with_conversation`] around calls; read by the memory tools
+//! (namespace) and the [`usage`][crate::usage] events (`conversation_id`).
+//!
+//! ```ignore
+//! let out = conversation::with_conversation(conversation_id, tools.call(call)).await?;
+//! ```
+
+use std::future::Future;
+
+tokio::task_local! {
+    static CONVERSATION: String;
+}
+
+/// Run `fut` with `conversation_id` as the conversation of every call made
+/// inside it.
+pub async fn with_conversation<F: Future>(conversation_id: impl Into<String>, fut: F) -> F::Output {
+    CONVERSATION.scope(conversation_id.into(), fut).await
+}
+
+/// The conversation id of the current task, if any.
+pub fn conversation() -> Option<String> {
+    CONVERSATION.try_with(Clone::clone).ok()
+}
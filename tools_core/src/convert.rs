@@ -0,0 +1,263 @@
+//! Conversions between [`ToolError`] and the error types of the
+//! application embedding the collection.
+//!
+//! - [`ToolError::from_io`] (also `From<io::Error>`) classifies an I/O
+//!   error by its kind: `NotFound` is [`NotFound`][ErrorClass::NotFound],
+//!   timeouts and dropped connections are
+//!   [`Transient`][ErrorClass::Transient], and so on.
+//! - [`ToolError::other`] wraps any message, and
+//!   [`with_class`][ToolError::with_class] gives it a class.
+//! - `From<ToolError> for io::Error` picks the matching kind and keeps the
+//!   `ToolError` as the inner error; `from_io` unwraps it again.
+//! - [`ErrorClass::of`] finds the class anywhere in an error's source
+//!   chain, so it survives `anyhow`/`eyre` context and `Box<dyn Error>`:
+//!
+//! ```ignore
+//! let err = anyhow::Error::from(tool_error).context("refreshing the cache");
+//! assert_eq!(ErrorClass::of(err.as_ref()), Some(ErrorClass::Transient));
+//! ```
+//!
+//! With the `anyhow` or `eyre` feature, a report converts back into a
+//! `ToolError`, so `?` works on them inside a tool returning
+//! `Result<T, ToolError>`. A bare `ToolError` comes back as it was; with
+//! context around it, or for any other error, the result is
+//! [`RuntimeClassified`][ToolError::RuntimeClassified] with the class from
+//! [`ErrorClass::of`] (`Internal` if none) and the whole chain as message.
+
+use std::{
+    error::Error,
+    fmt::Display,
+    io::{self, ErrorKind},
+};
+
+use crate::{ErrorClass, ToolError};
+
+impl ToolError {
+    /// An I/O error, classified by its [kind][io::Error::kind]. An
+    /// `io::Error` made from a `ToolError` gives that error back.
+    pub fn from_io(e: io::Error) -> Self {
+        if e.get_ref().is_some_and(|inner| inner.is::<ToolError>()) {
+            let inner = e.into_inner().expect("checked above");
+            return *inner.downcast::<ToolError>().expect("checked above");
+        }
+        Self::classified(io_class(e.kind()), e.to_string())
+    }
+
+    /// A [`Runtime`][Self::Runtime] error with `message`, for failures that
+    /// fit no other variant. Chain [`with_class`][Self::with_class] to
+    /// classify it.
+    pub fn other(message: impl Display) -> Self {
+        Self::Runtime(message.to_string())
+    }
+
+    /// This error as [`RuntimeClassified`][Self::RuntimeClassified] with
+    /// `class`, keeping its message.
+    pub fn with_class(self, class: ErrorClass) -> Self {
+        match self {
+            Self::Runtime(message) | Self::RuntimeClassified { message, .. } => {
+                Self::classified(class, message)
+            }
+            other => Self::classified(class, other.to_string()),
+        }
+    }
+}
+
+impl From<io::Error> for ToolError {
+    fn from(e: io::Error) -> Self {
+        Self::from_io(e)
+    }
+}
+
+impl From<ToolError> for io::Error {
+    fn from(e: ToolError) -> Self {
+        let kind = match e.classification() {
+            ErrorClass::NotFound => ErrorKind::NotFound,
+            ErrorClass::Denied => ErrorKind::PermissionDenied,
+            ErrorClass::InvalidInput => ErrorKind::InvalidInput,
+            ErrorClass::Transient | ErrorClass::Internal | ErrorClass::Exhausted => {
+                ErrorKind::Other
+            }
+        };
+        io::Error::new(kind, e)
+    }
+}
+
+fn io_class(kind: ErrorKind) -> ErrorClass {
+    match kind {
+        ErrorKind::NotFound => ErrorClass::NotFound,
+        ErrorKind::PermissionDenied => ErrorClass::Denied,
+        ErrorKind::InvalidInput | ErrorKind::InvalidData => ErrorClass::InvalidInput,
+        ErrorKind::TimedOut
+        | ErrorKind::Interrupted
+        | ErrorKind::WouldBlock
+        | ErrorKind::ConnectionRefused
+        | ErrorKind::ConnectionReset
+        | ErrorKind::ConnectionAborted
+        | ErrorKind::BrokenPipe => ErrorClass::Transient,
+        _ => ErrorClass::Internal,
+    }
+}
+
+impl ErrorClass {
+    /// The class of the first [`ToolError`] or `io::Error` in `err`'s
+    /// source chain, starting with `err` itself. `None` if there is
+    /// neither.
+    pub fn of(err: &(dyn Error + 'static)) -> Option<ErrorClass> {
+        let mut next = Some(err);
+        while let Some(err) = next {
+            if let Some(e) = err.downcast_ref::<ToolError>() {
+                return Some(e.classification());
+            }
+            if let Some(e) = err.downcast_ref::<io::Error>() {
+                // `source` skips the inner error of an `io::Error`.
+                return match e
+                    .get_ref()
+                    .and_then(|inner| inner.downcast_ref::<ToolError>())
+                {
+                    Some(inner) => Some(inner.classification()),
+                    None => Some(io_class(e.kind())),
+                };
+            }
+            next = err.source();
+        }
+        None
+    }
+}
+
+#[cfg(feature = "anyhow")]
+impl From<anyhow::Error> for ToolError {
+    fn from(e: anyhow::Error) -> Self {
+        let bare = e.chain().next().is_some_and(|top| top.is::<ToolError>());
+        let class = ErrorClass::of(e.as_ref()).unwrap_or(ErrorClass::Internal);
+        let message = format!("{e:#}");
+        match e.downcast::<ToolError>() {
+            Ok(e) if bare => e,
+            _ => Self::classified(class, message),
+        }
+    }
+}
+
+#[cfg(feature = "eyre")]
+impl From<eyre::Report> for ToolError {
+    fn from(e: eyre::Report) -> Self {
+        let bare = e.chain().next().is_some_and(|top| top.is::<ToolError>());
+        let class = ErrorClass::of(e.as_ref()).unwrap_or(ErrorClass::Internal);
+        let message = format!("{e:#}");
+        match e.downcast::<ToolError>() {
+            Ok(e) if bare => e,
+            _ => Self::classified(class, message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn io_kinds_map_to_classes() {
+        let cases = [
+            (ErrorKind::NotFound, ErrorClass::NotFound),
+            (ErrorKind::TimedOut, ErrorClass::Transient),
+            (ErrorKind::ConnectionReset, ErrorClass::Transient),
+            (ErrorKind::PermissionDenied, ErrorClass::Denied),
+            (ErrorKind::InvalidData, ErrorClass::InvalidInput),
+            (ErrorKind::Other, ErrorClass::Internal),
+        ];
+        for (kind, class) in cases {
+            let err = ToolError::from_io(io::Error::new(kind, "disk says no"));
+            assert_eq!(err.classification(), class, "{kind:?}");
+            assert!(err.to_string().contains("disk says no"), "{err}");
+        }
+
+        fn read(path: &str) -> Result<String, ToolError> {
+            Ok(std::fs::read_to_string(path)?)
+        }
+        let err = read("/definitely/not/here").unwrap_err();
+        assert_eq!(err.classification(), ErrorClass::NotFound);
+    }
+
+    #[test]
+    fn io_round_trip_keeps_the_tool_error() {
+        let original = ToolError::classified(ErrorClass::Exhausted, "quota used up");
+        let io_err = io::Error::from(original);
+        assert_eq!(io_err.kind(), ErrorKind::Other);
+        assert_eq!(ErrorClass::of(&io_err), Some(ErrorClass::Exhausted));
+        let back = ToolError::from(io_err);
+        assert!(
+            matches!(&back, ToolError::RuntimeClassified { class: ErrorClass::Exhausted, message } if message == "quota used up"),
+            "{back:?}"
+        );
+
+        let not_found = io::Error::from(ToolError::FunctionNotFound {
+            name: "missing".into(),
+        });
+        assert_eq!(not_found.kind(), ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn other_with_an_optional_class() {
+        let err = ToolError::other(format_args!("upstream returned {}", 503));
+        assert!(matches!(&err, ToolError::Runtime(m) if m == "upstream returned 503"));
+        assert_eq!(err.classification(), ErrorClass::Internal);
+
+        let err = err.with_class(ErrorClass::Transient);
+        assert!(err.is_retryable());
+        assert_eq!(err.to_string(), "transient error: upstream returned 503");
+
+        let err = ToolError::QueueClosed.with_class(ErrorClass::Transient);
+        assert_eq!(err.to_string(), "transient error: call queue is shut down");
+    }
+
+    #[test]
+    fn class_survives_context() {
+        let err = anyhow::Error::from(ToolError::classified(ErrorClass::Denied, "no token"))
+            .context("syncing calendar")
+            .context("nightly job");
+        assert_eq!(ErrorClass::of(err.as_ref()), Some(ErrorClass::Denied));
+
+        let err = eyre::Report::from(io::Error::from(ErrorKind::TimedOut)).wrap_err("fetching");
+        assert_eq!(ErrorClass::of(err.as_ref()), Some(ErrorClass::Transient));
+
+        let err = anyhow::anyhow!("plain");
+        assert_eq!(ErrorClass::of(err.as_ref()), None);
+    }
+
+    #[cfg(feature = "anyhow")]
+    #[test]
+    fn anyhow_converts_back() {
+        let bare = anyhow::Error::from(ToolError::MissingSecret {
+            key: "TOKEN".into(),
+        });
+        assert!(matches!(
+            ToolError::from(bare),
+            ToolError::MissingSecret { key } if key == "TOKEN"
+        ));
+
+        let wrapped =
+            anyhow::Error::from(io::Error::from(ErrorKind::NotFound)).context("loading config");
+        let err = ToolError::from(wrapped);
+        assert_eq!(err.classification(), ErrorClass::NotFound);
+        assert!(
+            err.to_string()
+                .starts_with("not found error: loading config: "),
+            "{err}"
+        );
+
+        let err = ToolError::from(anyhow::anyhow!("plain"));
+        assert_eq!(err.classification(), ErrorClass::Internal);
+    }
+
+    #[cfg(feature = "eyre")]
+    #[test]
+    fn eyre_converts_back() {
+        let wrapped = eyre::Report::from(ToolError::classified(ErrorClass::Transient, "busy"))
+            .wrap_err("retrying");
+        let err = ToolError::from(wrapped);
+        assert!(err.is_retryable());
+        assert_eq!(
+            err.to_string(),
+            "transient error: retrying: transient error: busy"
+        );
+    }
+}
@@ -0,0 +1,165 @@
+//! Deadlines that follow a call into the tools it calls.
+//!
+//! A deadline is an absolute [`Instant`] stored in a tokio task-local.
+//! Every call made while one is set, through any collection, including
+//! the global `call_tool`, gets at most the remaining time. A call that
+//! starts after the deadline fails with [`ToolError::DeadlineExceeded`]
+//! without running the tool. Nested scopes can only shorten the deadline,
+//! so an inner timeout is effectively `min(own, remaining)`.
+//!
+//! A tool cut off while running fails with [`ToolError::Timeout`] when
+//! the deadline came from a timeout ([`with_timeout`],
+//! [`call_with_timeout`][ToolCollection::call_with_timeout], a default or
+//! per-tool timeout), and with `DeadlineExceeded` when it came from
+//! [`with_deadline`].
+//!
+//! ```ignore
+//! let deadline = Instant::now() + Duration::from_secs(5);
+//! let out = deadline::with_deadline(deadline, tools.call(call)).await?;
+//! // or, per call:
+//! let out = tools.call_with_timeout(call, Duration::from_secs(5)).await?;
+//! // or for every call:
+//! tools.set_default_timeout(Duration::from_secs(30));
+//! // or for every call to one tool, instead of the default:
+//! tools.set_timeout("search_web", Duration::from_secs(3))?;
+//! ```
+//!
+//! Task-locals don't cross `tokio::spawn`. A tool that spawns work must
+//! wrap it in [`with_deadline`] itself, using [`current`].
+
+use std::{
+    future::Future,
+    time::{Duration, Instant},
+};
+
+use crate::{FunctionCall, FunctionResponse, ToolCollection, ToolError};
+
+/// The deadline in force, and when the timeout that set it started, if
+/// a timeout did.
+#[derive(Clone, Copy)]
+struct Scope {
+    deadline: Instant,
+    timeout_since: Option<Instant>,
+}
+
+tokio::task_local! {
+    static DEADLINE: Scope;
+}
+
+/// Run `fut` under `scope`, or the enclosing scope if its deadline is no
+/// later.
+async fn scoped<F: Future>(scope: Scope, fut: F) -> F::Output {
+    let effective = match DEADLINE.try_with(|outer| *outer) {
+        Ok(outer) if outer.deadline <= scope.deadline => outer,
+        _ => scope,
+    };
+    DEADLINE.scope(effective, fut).await
+}
+
+/// Run `fut` with `deadline`, or the enclosing deadline if that is
+/// earlier.
+pub async fn with_deadline<F: Future>(deadline: Instant, fut: F) -> F::Output {
+    let scope = Scope {
+        deadline,
+        timeout_since: None,
+    };
+    scoped(scope, fut).await
+}
+
+/// [`with_deadline`] `timeout` from now. A tool it cuts off fails with
+/// [`ToolError::Timeout`].
+pub async fn with_timeout<F: Future>(timeout: Duration, fut: F) -> F::Output {
+    let now = Instant::now();
+    let scope = Scope {
+        deadline: now + timeout,
+        timeout_since: Some(now),
+    };
+    scoped(scope, fut).await
+}
+
+/// The deadline of the current task, if any.
+pub fn current() -> Option<Instant> {
+    DEADLINE.try_with(|scope| scope.deadline).ok()
+}
+
+/// Time left until the current deadline; zero once it has passed.
+pub fn remaining() -> Option<Duration> {
+    current().map(|d| d.saturating_duration_since(Instant::now()))
+}
+
+/// Fail if the current deadline has already passed.
+pub(crate) fn check(tool: &str) -> Result<(), ToolError> {
+    match current() {
+        Some(deadline) if Instant::now() >= deadline => Err(ToolError::DeadlineExceeded {
+            tool: tool.to_owned().into(),
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Cut `run` off at the deadline. Call [`check`] first so it doesn't
+/// start when no time remains.
+pub(crate) async fn enforce<T, Fut>(tool: &str, run: impl FnOnce() -> Fut) -> Result<T, ToolError>
+where
+    Fut: Future<Output = Result<T, ToolError>>,
+{
+    let Ok(scope) = DEADLINE.try_with(|scope| *scope) else {
+        return run().await;
+    };
+    tokio::time::timeout_at(scope.deadline.into(), run())
+        .await
+        .unwrap_or_else(|_| {
+            Err(match scope.timeout_since {
+                Some(since) => ToolError::Timeout {
+                    name: tool.to_owned().into(),
+                    elapsed: since.elapsed(),
+                },
+                None => ToolError::DeadlineExceeded {
+                    tool: tool.to_owned().into(),
+                },
+            })
+        })
+}
+
+impl<M> ToolCollection<M> {
+    /// [`call`][Self::call] with a timeout, shortened to the enclosing
+    /// deadline if that is earlier. Nested calls made by the tool share it.
+    pub async fn call_with_timeout(
+        &self,
+        call: FunctionCall,
+        timeout: Duration,
+    ) -> Result<FunctionResponse, ToolError> {
+        with_timeout(timeout, self.call(call)).await
+    }
+
+    /// Give every call at most `timeout`, as if each ran under
+    /// [`with_timeout`]. Like any nested scope it only shortens: an
+    /// earlier enclosing deadline or a shorter
+    /// [`call_with_timeout`][Self::call_with_timeout] still wins.
+    pub fn set_default_timeout(&mut self, timeout: Duration) {
+        self.default_timeout = Some(timeout);
+    }
+
+    /// Stop applying the default timeout.
+    pub fn clear_default_timeout(&mut self) {
+        self.default_timeout = None;
+    }
+
+    pub fn default_timeout(&self) -> Option<Duration> {
+        self.default_timeout
+    }
+
+    /// Give every call to `name` at most `timeout`, in place of the
+    /// [default timeout][Self::set_default_timeout], longer or shorter.
+    /// Enclosing deadlines still win.
+    pub fn set_timeout(&mut self, name: &str, timeout: Duration) -> Result<(), ToolError> {
+        self.entry_mut(name)?.timeout = Some(timeout);
+        Ok(())
+    }
+
+    /// Go back to the default timeout for `name`.
+    pub fn clear_timeout(&mut self, name: &str) -> Result<(), ToolError> {
+        self.entry_mut(name)?.timeout = None;
+        Ok(())
+    }
+}
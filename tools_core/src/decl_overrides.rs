@@ -0,0 +1,197 @@
+//! Changing what one request declares, without touching the registration.
+//!
+//! To try another phrasing of a tool's description on some prompts, or to
+//! narrow a parameter for one request, build [`DeclOverrides`] and export
+//! through the view [`ToolCollection::with_overrides`] returns:
+//!
+//! ```ignore
+//! let view = tools.with_overrides(
+//!     DeclOverrides::new()
+//!         .description("search_web", "Search the web. Prefer recent sources.")
+//!         .parameters("search_web", json!({ "properties": { "limit": { "maximum": 5 } } })),
+//! )?;
+//! send(view.json()?);
+//! ```
+//!
+//! A parameters patch is a JSON merge patch (RFC 7386) applied to the
+//! stored schema: objects merge key by key, `null` removes a key, and any
+//! other value replaces what was there. Only declarations change. Calls
+//! through the view go to the collection as they are, checked against
+//! the registered schema, and the collection itself is never modified.
+//!
+//! Overrides are checked when the view is made. One naming an unknown
+//! tool fails with [`ToolError::FunctionNotFound`]. A patch whose result
+//! fails the [registration checks][crate::checks] that the stored schema
+//! passes fails with [`ToolError::RegistrationRejected`].
+
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{FunctionCall, FunctionDecl, FunctionResponse, ToolCollection, ToolError, checks};
+
+/// Replacement descriptions and schema patches, by tool name.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeclOverrides {
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tools: BTreeMap<String, DeclOverride>,
+}
+
+/// What to change in one tool's declaration.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeclOverride {
+    /// Replaces the description.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Merge patch for the parameter schema.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<Value>,
+}
+
+impl DeclOverrides {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declare `tool` with `description`.
+    pub fn description(mut self, tool: impl Into<String>, description: impl Into<String>) -> Self {
+        self.tools.entry(tool.into()).or_default().description = Some(description.into());
+        self
+    }
+
+    /// Merge `patch` into `tool`'s parameter schema.
+    pub fn parameters(mut self, tool: impl Into<String>, patch: Value) -> Self {
+        self.tools.entry(tool.into()).or_default().parameters = Some(patch);
+        self
+    }
+}
+
+/// Apply `patch` to `target` as a JSON merge patch (RFC 7386).
+pub fn merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    let Value::Object(target) = target else {
+        unreachable!()
+    };
+    for (key, value) in patch {
+        if value.is_null() {
+            target.remove(key);
+        } else {
+            merge_patch(target.entry(key.clone()).or_insert(Value::Null), value);
+        }
+    }
+}
+
+/// The overridden parts of one declaration.
+#[derive(Debug)]
+struct Patched {
+    description: Option<String>,
+    parameters: Option<Value>,
+}
+
+/// A collection whose declarations carry a request's [`DeclOverrides`];
+/// see [`decl_overrides`][crate::decl_overrides].
+pub struct OverriddenView<'a, M> {
+    tools: &'a ToolCollection<M>,
+    patched: HashMap<&'a str, Patched>,
+}
+
+impl<M> ToolCollection<M> {
+    /// A view declaring this collection's tools with `overrides` applied.
+    /// Fails if an override names an unknown tool or its schema patch
+    /// doesn't pass the registration checks.
+    pub fn with_overrides(
+        &self,
+        overrides: DeclOverrides,
+    ) -> Result<OverriddenView<'_, M>, ToolError> {
+        let mut patched = HashMap::with_capacity(overrides.tools.len());
+        for (name, change) in overrides.tools {
+            let entry = self
+                .get(&name)
+                .ok_or(ToolError::FunctionNotFound { name: name.into() })?;
+            let parameters = match change.parameters {
+                Some(patch) => {
+                    let mut schema = entry.decl.parameters.clone();
+                    merge_patch(&mut schema, &patch);
+                    let tool = &*entry.decl.name;
+                    let before = checks::check(tool, &entry.decl.parameters, &entry.func);
+                    let introduced: Vec<_> = checks::check(tool, &schema, &entry.func)
+                        .into_iter()
+                        .filter(|finding| !before.contains(finding))
+                        .collect();
+                    if !introduced.is_empty() {
+                        return Err(checks::rejected(tool.to_string(), introduced));
+                    }
+                    Some(schema)
+                }
+                None => None,
+            };
+            patched.insert(
+                &*entry.decl.name,
+                Patched {
+                    description: change.description,
+                    parameters,
+                },
+            );
+        }
+        Ok(OverriddenView {
+            tools: self,
+            patched,
+        })
+    }
+}
+
+impl<M> OverriddenView<'_, M> {
+    /// The collection's [`declarations`][ToolCollection::declarations]
+    /// with the overrides applied.
+    pub fn declarations(&self) -> Vec<FunctionDecl<'_>> {
+        self.tools
+            .declarations()
+            .map(|decl| self.apply(decl))
+            .collect()
+    }
+
+    pub fn get_declaration(&self, name: &str) -> Option<FunctionDecl<'_>> {
+        self.tools
+            .get_declaration(name)
+            .map(|decl| self.apply(decl))
+    }
+
+    /// [`ToolCollection::json`] with the overrides applied.
+    pub fn json(&self) -> Result<Value, ToolError> {
+        let decls = self.declarations();
+        let decls: Vec<&FunctionDecl> = decls.iter().collect();
+        Ok(self.tools.render(&decls)?)
+    }
+
+    /// Call a tool on the collection. Overrides don't apply: arguments
+    /// are checked against the registered schema.
+    pub async fn call(&self, call: FunctionCall) -> Result<FunctionResponse, ToolError> {
+        self.tools.call(call).await
+    }
+
+    fn apply<'a>(&'a self, decl: &'a FunctionDecl<'static>) -> FunctionDecl<'a> {
+        let Some(patched) = self.patched.get(&*decl.name) else {
+            return decl.clone();
+        };
+        FunctionDecl {
+            description: Cow::Borrowed(patched.description.as_deref().unwrap_or(&decl.description)),
+            parameters: patched
+                .parameters
+                .clone()
+                .unwrap_or_else(|| decl.parameters.clone()),
+            ..decl.clone()
+        }
+    }
+}
@@ -0,0 +1,150 @@
+//! `__describe_tool` / `__list_tools`: interrogating a deployed
+//! collection through its own tool channel.
+//!
+//! Enabled with the `dev-tools` feature. The tools are registered
+//! [hidden][ToolCollection::set_hidden], so they stay out of the
+//! declarations sent to models unless
+//! [`set_declare_hidden`][ToolCollection::set_declare_hidden] is on:
+//!
+//! ```ignore
+//! tools.register_dev_tools()?;
+//!
+//! let listed = tools.call(FunctionCall::new("__list_tools".into(), json!({}))).await?;
+//! let decl = tools
+//!     .call(FunctionCall::new("__describe_tool".into(), json!({ "name": "get_weather" })))
+//!     .await?;
+//! ```
+//!
+//! Both answer from the tools the caller could call itself. Through a
+//! [`Profiled`][crate::Profiled] view that is the profile's tools, and the
+//! dev tools are only reachable when the profile names them in its
+//! [`allow`][crate::AgentProfile::allow] list.
+
+use std::{future::Future, sync::Arc};
+
+use serde_json::{Value, json};
+
+use crate::{
+    ErrorClass, FunctionDecl, ToolCollection, ToolEntry, ToolError, ToolSpec, profile, unknown,
+};
+
+pub const DESCRIBE_TOOL: &str = "__describe_tool";
+pub const LIST_TOOLS: &str = "__list_tools";
+
+tokio::task_local! {
+    /// Declarations the current dev tool call may report on.
+    static VIEW: Arc<Vec<FunctionDecl<'static>>>;
+}
+
+/// Run `fut`, a call to `entry`, with the declarations its caller can
+/// reach in scope if `entry` is a dev tool.
+pub(crate) async fn with_view<M, F: Future>(
+    tools: &ToolCollection<M>,
+    entry: &ToolEntry<M>,
+    fut: F,
+) -> F::Output {
+    if ![DESCRIBE_TOOL, LIST_TOOLS].contains(&&*entry.decl.name) {
+        return fut.await;
+    }
+    let profile = profile::current().and_then(|name| tools.profiles.get(&*name));
+    let decls = tools
+        .iter()
+        .filter(|(_, e)| profile.is_none_or(|p| p.sees(e)))
+        .map(|(_, e)| e.decl.clone())
+        .collect();
+    VIEW.scope(Arc::new(decls), fut).await
+}
+
+fn view() -> Result<Arc<Vec<FunctionDecl<'static>>>, ToolError> {
+    VIEW.try_with(Arc::clone).map_err(|_| {
+        ToolError::Runtime("dev tools only answer calls made through their collection".into())
+    })
+}
+
+/// The first sentence or line of `description`.
+fn summary(description: &str) -> &str {
+    let line = description.trim().lines().next().unwrap_or_default();
+    match line.find(". ") {
+        Some(end) => &line[..=end],
+        None => line,
+    }
+}
+
+fn describe(args: Value) -> Result<Value, ToolError> {
+    let Some(name) = args["name"].as_str() else {
+        return Err(ToolError::classified(
+            ErrorClass::InvalidInput,
+            "`name` must be a string",
+        ));
+    };
+    let view = view()?;
+    let Some(decl) = view.iter().find(|d| d.name == name) else {
+        let mut message = format!("no tool named `{name}`");
+        if let Some(close) = unknown::suggest(name, view.iter().map(|d| &*d.name)) {
+            message.push_str(&format!("; did you mean `{close}`?"));
+        }
+        return Err(ToolError::classified(ErrorClass::InvalidInput, message));
+    };
+    let mut described = serde_json::to_value(decl)?;
+    described["dialect"] = serde_json::to_value(decl.dialect)?;
+    Ok(described)
+}
+
+fn list() -> Result<Value, ToolError> {
+    let tools: Vec<Value> = view()?
+        .iter()
+        .map(|d| json!({ "name": d.name, "summary": summary(&d.description) }))
+        .collect();
+    Ok(json!({ "tools": tools }))
+}
+
+impl<M: Default> ToolCollection<M> {
+    /// Register `__describe_tool` and `__list_tools`, hidden. Their
+    /// metadata is `M::default()`.
+    pub fn register_dev_tools(&mut self) -> Result<&mut Self, ToolError> {
+        let specs = [
+            ToolSpec::new(
+                DESCRIBE_TOOL,
+                "Return the full declaration of a tool: description, parameter schema, \
+                 result schema and tags.",
+            )
+            .params_schema(json!({
+                "type": "object",
+                "properties": { "name": { "type": "string", "description": "The tool's name." } },
+                "required": ["name"]
+            }))
+            .handler(|args| Box::pin(async move { describe(args) })),
+            ToolSpec::new(
+                LIST_TOOLS,
+                "List the callable tools by name, each with the first sentence of its \
+                 description.",
+            )
+            .params_schema(json!({ "type": "object", "properties": {}, "required": [] }))
+            .handler(|_| Box::pin(async { list() })),
+        ];
+        for spec in specs {
+            let id = self.register_spec(spec.with_meta(M::default()))?;
+            if let Some(entry) = self.entries[id.index()].as_mut() {
+                entry.hidden = true;
+            }
+        }
+        self.json_cache.take();
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summaries_stop_at_the_first_sentence() {
+        assert_eq!(
+            summary("Gets the weather. Uses metric units."),
+            "Gets the weather."
+        );
+        assert_eq!(summary("  Adds numbers\nSecond line."), "Adds numbers");
+        assert_eq!(summary("Version 1.2 of the API"), "Version 1.2 of the API");
+        assert_eq!(summary(""), "");
+    }
+}
@@ -0,0 +1,428 @@
+//! Explaining rejected arguments to the model.
+//!
+//! A serde message like "invalid type: string \"48.8\", expected f64 at
+//! line 1 column 12" rarely gets a model to fix its call. For a call that
+//! failed on its input, [`explain_failure`] compares the arguments with the
+//! tool's parameter schema and lists what to change, with example values
+//! generated from the schema:
+//!
+//! ```ignore
+//! let err = tools.call(call.clone()).await.unwrap_err();
+//! let decl = &tools.get(&call.name).unwrap().decl;
+//! let explanation = explain_failure(decl, &call.arguments, &err);
+//! // {
+//! //   "tool": "weather",
+//! //   "error": "Deserialization error: ...",
+//! //   "missing": [{"path": "$.lat", "expected": "number", "example": 0.0}],
+//! //   "unexpected": [{"path": "$.lattitude", "did_you_mean": "lat"}],
+//! //   "mismatched": [{"path": "$.units", "expected": "one of \"c\", \"f\"", "found": "number", "example": "c"}],
+//! //   "example": {"lat": 0.0, "lon": 0.0}
+//! // }
+//! ```
+//!
+//! The value is meant to be sent to the model as it is.
+//! [`ToolCollection::report_failure`] attaches it to the [`ErrorReport`]
+//! of input errors. Schemas are walked as the derive emits them: `$ref`
+//! is not followed, and `anyOf`/`oneOf` are explained by their first
+//! branch of the right type.
+
+use std::cmp::Ordering;
+
+use serde_json::{Map, Value, json};
+
+use crate::{
+    ErrorClass, ErrorReport, FunctionCall, FunctionDecl, ToolCollection, ToolError, lint::escape,
+    unknown,
+};
+
+/// Explain why `args` failed for `decl` with `err`. Errors that aren't
+/// about the input ([`ErrorClass::InvalidInput`]) only get the tool name
+/// and message; domain errors are the tool's own answer and are not
+/// explained either.
+pub fn explain_failure(decl: &FunctionDecl, args: &Value, err: &ToolError) -> Value {
+    let mut out = Map::new();
+    out.insert("tool".into(), decl.name.as_ref().into());
+    out.insert("error".into(), err.to_string().into());
+    if err.classification() != ErrorClass::InvalidInput || matches!(err, ToolError::Domain(_)) {
+        return Value::Object(out);
+    }
+
+    let mut findings = Findings::default();
+    walk(&decl.parameters, args, "$", &mut findings);
+    for (key, list) in [
+        ("missing", findings.missing),
+        ("unexpected", findings.unexpected),
+        ("mismatched", findings.mismatched),
+    ] {
+        if !list.is_empty() {
+            out.insert(key.into(), Value::Array(list));
+        }
+    }
+    out.insert("example".into(), example(&decl.parameters));
+    Value::Object(out)
+}
+
+/// A minimal value valid against `schema`: its `const`, first `enum`
+/// value, `default` or first of `examples` if it has one, else an object
+/// of the required properties, an array of `minItems` items, `0`, `""`
+/// or `false`. `null` when the schema says nothing.
+pub fn example(schema: &Value) -> Value {
+    if let Some(value) = schema.get("const") {
+        return value.clone();
+    }
+    if let Some(first) = schema["enum"].get(0) {
+        return first.clone();
+    }
+    if let Some(value) = schema.get("default") {
+        return value.clone();
+    }
+    if let Some(first) = schema["examples"].get(0) {
+        return first.clone();
+    }
+    if let Some(branch) = branches(schema).find(|b| b["type"] != "null") {
+        return example(branch);
+    }
+    match primary_type(schema) {
+        Some("object") => {
+            let properties = &schema["properties"];
+            let required = schema["required"].as_array().into_iter().flatten();
+            Value::Object(
+                required
+                    .filter_map(Value::as_str)
+                    .map(|name| (name.to_string(), example(&properties[name])))
+                    .collect(),
+            )
+        }
+        Some("array") => {
+            let len = schema["minItems"].as_u64().unwrap_or(0) as usize;
+            (0..len)
+                .map(|i| match schema["prefixItems"].get(i) {
+                    Some(item) => example(item),
+                    None => example(&schema["items"]),
+                })
+                .collect()
+        }
+        Some("integer") => schema.get("minimum").cloned().unwrap_or(json!(0)),
+        Some("number") => schema.get("minimum").cloned().unwrap_or(json!(0.0)),
+        Some("string") => json!(""),
+        Some("boolean") => json!(false),
+        _ => Value::Null,
+    }
+}
+
+#[derive(Default)]
+struct Findings {
+    /// Write paths as JSON pointers (`/a/0`) instead of `$.a[0]`.
+    pointers: bool,
+    missing: Vec<Value>,
+    unexpected: Vec<Value>,
+    mismatched: Vec<Value>,
+}
+
+impl Findings {
+    fn key(&self, path: &str, key: &str) -> String {
+        if self.pointers {
+            format!("{path}/{}", escape(key))
+        } else {
+            format!("{path}.{key}")
+        }
+    }
+
+    fn index(&self, path: &str, i: usize) -> String {
+        if self.pointers {
+            format!("{path}/{i}")
+        } else {
+            format!("{path}[{i}]")
+        }
+    }
+}
+
+/// Where `args` break `schema`: missing required properties, then values
+/// of the wrong type, `enum` or `const`, one line each with its JSON
+/// pointer.
+/// Undeclared properties are left to [`UnknownArgs`][crate::UnknownArgs].
+pub(crate) fn violations(schema: &Value, args: &Value) -> Vec<String> {
+    let mut findings = Findings {
+        pointers: true,
+        ..Findings::default()
+    };
+    walk(schema, args, "", &mut findings);
+    let at = |finding: &Value| match finding["path"].as_str() {
+        Some("") | None => "arguments".to_string(),
+        Some(path) => format!("`{path}`"),
+    };
+    let missing = findings.missing.iter().map(|f| {
+        format!(
+            "{}: required, expected {}",
+            at(f),
+            f["expected"].as_str().unwrap_or("any")
+        )
+    });
+    let mismatched = findings.mismatched.iter().map(|f| {
+        format!(
+            "{}: expected {}, found {}",
+            at(f),
+            f["expected"].as_str().unwrap_or("any"),
+            f["found"].as_str().unwrap_or("any")
+        )
+    });
+    missing.chain(mismatched).collect()
+}
+
+/// Compare `value` at `path` with `schema`, recording what doesn't fit.
+fn walk(schema: &Value, value: &Value, path: &str, findings: &mut Findings) {
+    let mut alternatives = branches(schema).peekable();
+    if alternatives.peek().is_some() {
+        match alternatives.find(|branch| fits(branch, value)) {
+            Some(branch) => walk(branch, value, path, findings),
+            None => findings.mismatched.push(mismatch(schema, value, path)),
+        }
+        return;
+    }
+    if !fits(schema, value) {
+        findings.mismatched.push(mismatch(schema, value, path));
+        return;
+    }
+    match value {
+        Value::Object(map) => {
+            let properties = &schema["properties"];
+            for name in schema["required"].as_array().into_iter().flatten() {
+                let Some(name) = name.as_str() else { continue };
+                if !map.contains_key(name) {
+                    findings.missing.push(json!({
+                        "path": findings.key(path, name),
+                        "expected": expected(&properties[name]),
+                        "example": example(&properties[name]),
+                    }));
+                }
+            }
+            let accepted: Vec<&str> = properties
+                .as_object()
+                .into_iter()
+                .flat_map(|p| p.keys().map(String::as_str))
+                .collect();
+            for key in unknown::unknown_keys(schema, value) {
+                let mut entry = json!({ "path": findings.key(path, key) });
+                if let Some(name) = unknown::suggest(key, accepted.iter().copied()) {
+                    entry["did_you_mean"] = name.into();
+                }
+                findings.unexpected.push(entry);
+            }
+            for (key, item) in map {
+                if let Some(property) = properties.get(key) {
+                    let path = findings.key(path, key);
+                    walk(property, item, &path, findings);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                let item_schema = match schema["prefixItems"].get(i) {
+                    Some(item_schema) => item_schema,
+                    None => &schema["items"],
+                };
+                if item_schema.is_object() {
+                    let path = findings.index(path, i);
+                    walk(item_schema, item, &path, findings);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn mismatch(schema: &Value, value: &Value, path: &str) -> Value {
+    let found = if has_schema_type(schema, value) && !in_range(schema, value) {
+        value.to_string()
+    } else {
+        type_of(value).to_string()
+    };
+    json!({
+        "path": path,
+        "expected": expected(schema),
+        "found": found,
+        "example": example(schema),
+    })
+}
+
+/// The `anyOf` or `oneOf` alternatives of `schema`, if any.
+fn branches(schema: &Value) -> impl Iterator<Item = &Value> {
+    ["anyOf", "oneOf"]
+        .into_iter()
+        .filter_map(|key| schema[key].as_array())
+        .flatten()
+}
+
+/// The schema's `type`, or the first non-`null` one of a type list.
+fn primary_type(schema: &Value) -> Option<&str> {
+    match &schema["type"] {
+        Value::String(ty) => Some(ty),
+        Value::Array(types) => types
+            .iter()
+            .filter_map(Value::as_str)
+            .find(|t| *t != "null"),
+        _ => None,
+    }
+}
+
+/// Whether `value` has a type, `enum` value or `const` the schema allows.
+/// Only the top level is checked.
+fn fits(schema: &Value, value: &Value) -> bool {
+    if let Some(allowed) = schema["enum"].as_array() {
+        return allowed.contains(value);
+    }
+    if let Some(constant) = schema.get("const") {
+        return constant == value;
+    }
+    if value.is_null() && schema["nullable"] == true {
+        return true;
+    }
+    has_schema_type(schema, value) && in_range(schema, value)
+}
+
+fn has_schema_type(schema: &Value, value: &Value) -> bool {
+    match &schema["type"] {
+        Value::String(ty) => has_type(value, ty),
+        Value::Array(types) => types
+            .iter()
+            .filter_map(Value::as_str)
+            .any(|ty| has_type(value, ty)),
+        _ => true,
+    }
+}
+
+/// Whether a number is within the schema's `minimum` and `maximum`, if
+/// it declares them.
+fn in_range(schema: &Value, value: &Value) -> bool {
+    compare(value, &schema["minimum"]) != Some(Ordering::Less)
+        && compare(value, &schema["maximum"]) != Some(Ordering::Greater)
+}
+
+/// `value` against `bound`, exactly when both are integers, so that
+/// `i64::MAX + 1` is out of an `i64`'s range.
+fn compare(value: &Value, bound: &Value) -> Option<Ordering> {
+    let int = |v: &Value| v.as_i64().map(i128::from).or(v.as_u64().map(i128::from));
+    match (int(value), int(bound)) {
+        (Some(value), Some(bound)) => Some(value.cmp(&bound)),
+        _ => value.as_f64()?.partial_cmp(&bound.as_f64()?),
+    }
+}
+
+fn has_type(value: &Value, ty: &str) -> bool {
+    match ty {
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        ty => type_of(value) == ty,
+    }
+}
+
+fn type_of(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// What `schema` accepts, in a few words.
+fn expected(schema: &Value) -> String {
+    if let Some(allowed) = schema["enum"].as_array() {
+        let listed: Vec<String> = allowed.iter().map(Value::to_string).collect();
+        return format!("one of {}", listed.join(", "));
+    }
+    if let Some(constant) = schema.get("const") {
+        return constant.to_string();
+    }
+    let alternatives: Vec<String> = branches(schema).map(expected).collect();
+    if !alternatives.is_empty() {
+        return alternatives.join(" or ");
+    }
+    let ty = match &schema["type"] {
+        Value::String(ty) => ty.clone(),
+        Value::Array(types) => {
+            let types: Vec<&str> = types.iter().filter_map(Value::as_str).collect();
+            types.join(" or ")
+        }
+        _ => "any".into(),
+    };
+    match (schema.get("minimum"), schema.get("maximum")) {
+        (Some(min), Some(max)) => format!("{ty} from {min} to {max}"),
+        (Some(min), None) => format!("{ty} of at least {min}"),
+        (None, Some(max)) => format!("{ty} of at most {max}"),
+        (None, None) => ty,
+    }
+}
+
+impl<M> ToolCollection<M> {
+    /// [`ToolError::report`] for a failed `call`, with an
+    /// [`explain_failure`] explanation when the tool exists and the error
+    /// is about its input.
+    pub fn report_failure(&self, call: &FunctionCall, err: &ToolError) -> ErrorReport {
+        let mut report = err.report();
+        if report.class == ErrorClass::InvalidInput && !matches!(err, ToolError::Domain(_)) {
+            if let Some(entry) = self.get(&call.name) {
+                report.explanation = Some(explain_failure(&entry.decl, &call.arguments, err));
+            }
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn examples_follow_the_schema() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "city": { "type": "string" },
+                "days": { "type": "integer", "minimum": 1 },
+                "units": { "type": "string", "enum": ["c", "f"] },
+                "at": { "type": "array", "prefixItems": [{ "type": "number" }, { "type": "number" }], "minItems": 2 },
+                "note": { "anyOf": [{ "type": "null" }, { "type": "string" }] },
+                "verbose": { "type": "boolean" }
+            },
+            "required": ["city", "days", "units", "at"]
+        });
+        assert_eq!(
+            example(&schema),
+            json!({ "city": "", "days": 1, "units": "c", "at": [0.0, 0.0] })
+        );
+        assert_eq!(example(&schema["properties"]["note"]), json!(""));
+        assert_eq!(example(&json!({})), Value::Null);
+    }
+
+    #[test]
+    fn integers_and_floats_are_told_apart() {
+        assert!(fits(&json!({ "type": "number" }), &json!(1)));
+        assert!(!fits(&json!({ "type": "integer" }), &json!(1.5)));
+        assert!(fits(&json!({ "type": ["integer", "null"] }), &Value::Null));
+        assert_eq!(
+            expected(&json!({ "type": ["integer", "null"] })),
+            "integer or null"
+        );
+    }
+
+    #[test]
+    fn integers_stay_in_range() {
+        let byte = json!({ "type": "integer", "minimum": 0, "maximum": 255 });
+        assert!(fits(&byte, &json!(255)));
+        assert!(!fits(&byte, &json!(256)));
+        assert!(!fits(&byte, &json!(-1)));
+        assert_eq!(expected(&byte), "integer from 0 to 255");
+        assert_eq!(mismatch(&byte, &json!(256), "/n")["found"], "256");
+
+        // Bounds past 2^53 are compared exactly.
+        let long = json!({ "type": "integer", "minimum": i64::MIN, "maximum": i64::MAX });
+        assert!(fits(&long, &json!(i64::MAX)));
+        assert!(!fits(&long, &json!(i64::MAX as u64 + 1)));
+        assert!(fits(&json!({ "type": "integer" }), &json!(u64::MAX)));
+        assert!(fits(&json!({ "type": "number" }), &json!(u64::MAX)));
+    }
+}
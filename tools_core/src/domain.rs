@@ -0,0 +1,118 @@
+//! Domain errors a tool reports to the model.
+//!
+//! A tool returning `Result<T, E>` with `E: Serialize + ToolSchema`, from
+//! `#[tool]` or [`register`][crate::ToolCollection::register], reports
+//! `Err` as data the model can branch on rather than as a failed call:
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize, ToolSchema)]
+//! #[serde(tag = "kind", rename_all = "snake_case")]
+//! enum OrderError {
+//!     OutOfStock,
+//!     PaymentDeclined { reason: String },
+//! }
+//!
+//! #[tool]
+//! /// Places an order.
+//! async fn order(sku: String) -> Result<OrderId, OrderError> { ... }
+//!
+//! // Ok:  result = "ord_42"
+//! // Err: result = { "error": { "kind": "out_of_stock" } }, is_error = true
+//! ```
+//!
+//! `Ok` values are sent as they are. `Err` values are wrapped in an
+//! `{"error": ...}` envelope and the response has
+//! [`is_error`][crate::FunctionResponse::is_error] set. Either way the call
+//! succeeded: there is no [`ToolError`], circuit breakers count it as a
+//! success and result caches skip it. The tool's declaration gets a
+//! `returns` schema of `oneOf [T, envelope of E]`.
+//!
+//! Handlers registered with a raw `Value` signature report one by
+//! returning [`ToolError::domain`].
+//!
+//! A tool whose `Err` should fail the call instead returns
+//! `Result<T, ToolError>` from `#[tool]`, or is registered with
+//! [`register_fallible`][crate::ToolCollection::register_fallible].
+
+use serde::Serialize;
+use serde_json::{Value, json};
+
+use crate::{ToolError, ToolSchema, ToolSpec};
+
+/// The `{"error": ...}` wrapper for a domain error, given its schema.
+pub fn error_envelope(schema: Value) -> Value {
+    json!({
+        "type": "object",
+        "properties": { "error": schema },
+        "required": ["error"]
+    })
+}
+
+/// The schema of a `Result` *returned by a tool*: the `Ok` value, or the
+/// error in its envelope. It does not describe serde's
+/// `{"Ok": ..} / {"Err": ..}` form.
+impl<T: ToolSchema, E: ToolSchema> ToolSchema for Result<T, E> {
+    const IS_RESULT: bool = true;
+
+    fn schema() -> Value {
+        json!({ "oneOf": [T::schema(), error_envelope(E::schema())] })
+    }
+}
+
+impl ToolError {
+    /// A domain error for a raw handler to return; see
+    /// [`domain`][crate::domain].
+    pub fn domain(error: impl Serialize) -> Self {
+        match serde_json::to_value(error) {
+            Ok(error) => Self::Domain(error),
+            Err(e) => Self::Serialization(e),
+        }
+    }
+}
+
+/// Split a serialized `Result` into the tool's value or its domain error.
+#[doc(hidden)]
+pub fn from_result(value: Value) -> Result<Value, ToolError> {
+    let Value::Object(mut object) = value else {
+        return Ok(value);
+    };
+    if object.len() == 1 {
+        if let Some(ok) = object.remove("Ok") {
+            return Ok(ok);
+        }
+        if let Some(err) = object.remove("Err") {
+            return Err(ToolError::Domain(err));
+        }
+    }
+    Ok(Value::Object(object))
+}
+
+/// Serialize a typed tool's output, splitting out a domain error if `O`
+/// is a `Result`.
+pub(crate) fn encode<O: Serialize + ToolSchema>(output: O) -> Result<Value, ToolError> {
+    let value = serde_json::to_value(output).map_err(|e| ToolError::Runtime(e.to_string()))?;
+    if O::IS_RESULT {
+        from_result(value)
+    } else {
+        Ok(value)
+    }
+}
+
+/// Declare `O`'s schema as `spec`'s result if `O` is a `Result`.
+pub(crate) fn declare_returns<O: ToolSchema, M>(spec: ToolSpec<M>) -> ToolSpec<M> {
+    if O::IS_RESULT {
+        spec.returns_schema(O::schema())
+    } else {
+        spec
+    }
+}
+
+/// The result of a call as `serde`'s `Result` form, so a typed caller
+/// can decode it back into `Result<T, E>`.
+pub(crate) fn to_result(result: Value, is_error: bool) -> Value {
+    if is_error {
+        json!({ "Err": result.get("error").cloned().unwrap_or(Value::Null) })
+    } else {
+        json!({ "Ok": result })
+    }
+}
@@ -0,0 +1,223 @@
+//! Reporting failed calls without flooding the log.
+//!
+//! A model stuck calling a broken tool produces the same failure over and
+//! over. A [`FailureSink`] attached with
+//! [`set_failure_sink`][ToolCollection::set_failure_sink] sees the first
+//! failure of each (tool, [`ErrorClass`], argument hash) at once. Repeats
+//! within `window` of it are only counted, and one
+//! [`FailureEvent::Repeated`] with the total is reported when the window
+//! closes:
+//!
+//! ```ignore
+//! struct Log;
+//! impl FailureSink for Log {
+//!     fn record(&self, event: FailureEvent) {
+//!         tracing::warn!(event = %serde_json::to_string(&event).unwrap(), "tool failure");
+//!     }
+//! }
+//! tools.set_failure_sink(Arc::new(Log), Duration::from_secs(60));
+//! ```
+//!
+//! Windows close when a later failure is reported or on
+//! [`flush_failures`][ToolCollection::flush_failures]; call that on
+//! shutdown. A sink whose [`collapse`][FailureSink::collapse] returns
+//! `false`, such as a raw audit trail, gets every failure as it happens.
+//! Arguments are hashed with [`hash::canonical_hash`] only while a sink
+//! is attached.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    CallId, ErrorClass, ToolCollection, ToolError,
+    circuit::{Clock, SystemClock},
+    hash,
+};
+
+/// One failed call.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FailureRecord {
+    pub tool: String,
+    pub class: ErrorClass,
+    /// [`hash::canonical_hash`] of the arguments.
+    pub args_hash: u64,
+    pub message: String,
+    pub call_id: Option<String>,
+}
+
+/// What a [`FailureSink`] is told.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum FailureEvent {
+    /// A failure not seen within the current window.
+    Failed(FailureRecord),
+    /// A window closed on `count` identical failures, `first` included.
+    Repeated {
+        first: FailureRecord,
+        count: u64,
+        window_ms: u64,
+    },
+}
+
+/// Receives failed calls; see [`failures`][crate::failures].
+pub trait FailureSink: Send + Sync {
+    fn record(&self, event: FailureEvent);
+
+    /// Whether repeats are collapsed. `false` reports each failure as
+    /// [`FailureEvent::Failed`].
+    fn collapse(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct Key {
+    tool: String,
+    class: ErrorClass,
+    args_hash: u64,
+}
+
+struct Window {
+    first: FailureRecord,
+    opened: Instant,
+    count: u64,
+}
+
+/// A sink and the windows of failures it has been told about.
+pub(crate) struct FailureLog {
+    sink: Arc<dyn FailureSink>,
+    window: Duration,
+    clock: Arc<dyn Clock>,
+    open: Mutex<HashMap<Key, Window>>,
+}
+
+impl FailureLog {
+    pub(crate) fn args_hash(&self, arguments: &Value) -> u64 {
+        hash::canonical_hash(arguments)
+    }
+
+    /// Report a failed call to `tool`.
+    pub(crate) fn record(
+        &self,
+        tool: &str,
+        call_id: Option<&CallId>,
+        args_hash: u64,
+        error: &ToolError,
+    ) {
+        let record = FailureRecord {
+            tool: tool.to_string(),
+            class: error.classification(),
+            args_hash,
+            message: error.to_string(),
+            call_id: call_id.map(|id| id.to_string()),
+        };
+        if !self.sink.collapse() {
+            self.sink.record(FailureEvent::Failed(record));
+            return;
+        }
+        let key = Key {
+            tool: record.tool.clone(),
+            class: record.class,
+            args_hash,
+        };
+        let now = self.clock.now();
+        let mut events = Vec::new();
+        {
+            let mut open = self.open.lock().unwrap_or_else(PoisonError::into_inner);
+            self.close(&mut open, Some(now), &mut events);
+            match open.get_mut(&key) {
+                Some(window) => window.count += 1,
+                None => {
+                    events.push(FailureEvent::Failed(record.clone()));
+                    open.insert(
+                        key,
+                        Window {
+                            first: record,
+                            opened: now,
+                            count: 1,
+                        },
+                    );
+                }
+            }
+        }
+        // Outside the lock, so a sink may call back into the collection.
+        events.into_iter().for_each(|e| self.sink.record(e));
+    }
+
+    /// Close every open window.
+    pub(crate) fn flush(&self) {
+        let mut events = Vec::new();
+        {
+            let mut open = self.open.lock().unwrap_or_else(PoisonError::into_inner);
+            self.close(&mut open, None, &mut events);
+        }
+        events.into_iter().for_each(|e| self.sink.record(e));
+    }
+
+    /// Remove windows that have run out by `now` (all of them for `None`),
+    /// queueing a summary for those with repeats.
+    fn close(
+        &self,
+        open: &mut HashMap<Key, Window>,
+        now: Option<Instant>,
+        events: &mut Vec<FailureEvent>,
+    ) {
+        let expired: Vec<Key> = open
+            .iter()
+            .filter(|(_, w)| now.is_none_or(|now| now.duration_since(w.opened) >= self.window))
+            .map(|(key, _)| key.clone())
+            .collect();
+        let mut closed: Vec<Window> = expired.iter().filter_map(|key| open.remove(key)).collect();
+        closed.sort_by_key(|w| w.opened);
+        let window_ms = self.window.as_millis().try_into().unwrap_or(u64::MAX);
+        events.extend(
+            closed
+                .into_iter()
+                .filter(|w| w.count > 1)
+                .map(|w| FailureEvent::Repeated {
+                    first: w.first,
+                    count: w.count,
+                    window_ms,
+                }),
+        );
+    }
+}
+
+impl<M> ToolCollection<M> {
+    /// Report failed calls to `sink`, collapsing identical ones within
+    /// `window`; see [`failures`][crate::failures]. Replaces any previous
+    /// sink, after flushing it. Shared with clones made afterwards.
+    pub fn set_failure_sink(&mut self, sink: Arc<dyn FailureSink>, window: Duration) {
+        self.set_failure_sink_with_clock(sink, window, Arc::new(SystemClock));
+    }
+
+    /// [`set_failure_sink`][Self::set_failure_sink] with a custom
+    /// [`Clock`].
+    pub fn set_failure_sink_with_clock(
+        &mut self,
+        sink: Arc<dyn FailureSink>,
+        window: Duration,
+        clock: Arc<dyn Clock>,
+    ) {
+        self.flush_failures();
+        self.failures = Some(Arc::new(FailureLog {
+            sink,
+            window,
+            clock,
+            open: Mutex::default(),
+        }));
+    }
+
+    /// Report the counts of all open windows now.
+    pub fn flush_failures(&self) {
+        if let Some(log) = &self.failures {
+            log.flush();
+        }
+    }
+}
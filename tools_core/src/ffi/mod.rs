@@ -49,10 +49,8 @@ impl Language {
 
 /// A tool definition produced by an FFI adapter's `load()` function.
 ///
-/// `name` and `description` are owned [`String`]s here; they are leaked
-/// to `&'static str` at registration time so that
-/// [`register_raw`][crate::ToolCollection::register_raw]'s signature
-/// stays unchanged.
+/// `name` and `description` are owned [`String`]s here; the registered
+/// tool's entry keeps them and frees them when it is unregistered.
 pub struct RawToolDef {
     pub name: String,
     pub description: String,
@@ -95,14 +93,3 @@ pub(crate) fn load_language(
         ))),
     }
 }
-
-// ============================================================================
-// HELPERS
-// ============================================================================
-
-/// Leak a [`String`] to `&'static str`. Used at the FFI boundary so
-/// that tool names and descriptions from scripts become static
-/// references compatible with the rest of the API.
-pub(crate) fn leak_string(s: String) -> &'static str {
-    Box::leak(s.into_boxed_str())
-}
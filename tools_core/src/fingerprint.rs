@@ -0,0 +1,237 @@
+//! Reproducing a collection's dispatch behavior offline.
+//!
+//! [`ToolCollection::fingerprint`] captures everything that decides how a
+//! call is routed and validated — declarations, aliases, name
+//! normalization, crate version and features — as one serializable
+//! document. Attach it to a bug report; [`ToolCollection::from_fingerprint`]
+//! loads it into a stub collection whose tools decide the same way but do
+//! nothing: each echoes its arguments back and records the call.
+//!
+//! ```ignore
+//! let doc = serde_json::to_string(&tools.fingerprint())?;
+//! // ...later, elsewhere
+//! let (stub, calls) = ToolCollection::from_fingerprint(serde_json::from_str(&doc)?)?;
+//! let err = stub.call(reported_call).await.unwrap_err();
+//! assert_eq!(calls.take().len(), 0);
+//! ```
+//!
+//! Stub tools check the arguments against the top level of the declared
+//! schema: an object carrying every `required` property. That reproduces
+//! the [`InvalidInput`][crate::ErrorClass::InvalidInput] failures of typed
+//! tools for missing arguments; checks a tool makes beyond its schema are
+//! not reproduced.
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+use serde::{Deserialize, Serialize, de::Error as _};
+use serde_json::Value;
+
+use crate::{
+    DecodedInput, FunctionDecl, NameNormalization, ToolCollection, ToolError, ToolFunc, ToolSpec,
+    downcast_input, introspect,
+};
+
+/// Current value of [`Fingerprint::format`].
+pub const FINGERPRINT_FORMAT: u32 = 1;
+
+/// A collection's routing and validation settings, from
+/// [`ToolCollection::fingerprint`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fingerprint {
+    /// Version of this document's JSON shape.
+    pub format: u32,
+    /// Version of `tools_core` that produced it.
+    pub version: String,
+    /// Cargo features `tools_core` was built with.
+    pub features: Vec<String>,
+    pub normalization: NameNormalization,
+    /// Alias → name of the tool it points to.
+    pub aliases: BTreeMap<String, String>,
+    /// Declarations in registration order.
+    pub tools: Vec<FingerprintTool>,
+}
+
+/// One tool's declaration in a [`Fingerprint`]. Written flat; read from
+/// either [layout][crate::layout].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "Value")]
+pub struct FingerprintTool {
+    pub name: String,
+    pub description: String,
+    pub parameters: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub returns: Option<Value>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl TryFrom<Value> for FingerprintTool {
+    type Error = ToolError;
+
+    fn try_from(value: Value) -> Result<Self, ToolError> {
+        let decl = FunctionDecl::from_any_layout(&value)?;
+        Ok(Self {
+            name: decl.name.to_string(),
+            description: decl.description.to_string(),
+            parameters: decl.parameters,
+            returns: decl.returns,
+            tags: decl.tags,
+        })
+    }
+}
+
+/// A call that reached a stub tool.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Invocation {
+    pub tool: String,
+    pub arguments: Value,
+}
+
+/// Calls recorded by the tools of a stub collection. Shared by the stub
+/// and its clones.
+#[derive(Debug, Clone, Default)]
+pub struct Invocations(Arc<Mutex<Vec<Invocation>>>);
+
+impl Invocations {
+    /// Recorded calls so far, oldest first.
+    pub fn snapshot(&self) -> Vec<Invocation> {
+        self.0.lock().unwrap().clone()
+    }
+
+    /// Recorded calls so far, clearing the record.
+    pub fn take(&self) -> Vec<Invocation> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+/// Top-level checks of `arguments` against `schema`, failing like a
+/// derived `Deserialize` impl would.
+fn check_arguments(schema: &Value, arguments: &Value) -> Result<(), ToolError> {
+    if schema["type"] != "object" {
+        return Ok(());
+    }
+    let Some(args) = arguments.as_object() else {
+        return Err(ToolError::Deserialize(
+            serde_json::Error::custom(format!("invalid type: {arguments}, expected an object"))
+                .into(),
+        ));
+    };
+    let required = schema["required"].as_array().into_iter().flatten();
+    for field in required.filter_map(Value::as_str) {
+        if !args.contains_key(field) {
+            return Err(ToolError::Deserialize(
+                serde_json::Error::custom(format!("missing field `{field}`")).into(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn echo(tool: String, schema: Value, invocations: Invocations) -> ToolFunc {
+    ToolFunc::new(
+        move |raw: Value| {
+            check_arguments(&schema, &raw)?;
+            Ok(Box::new(raw) as DecodedInput)
+        },
+        move |input, _ctx| {
+            let tool = tool.clone();
+            let invocations = invocations.clone();
+            Box::pin(async move {
+                let arguments = downcast_input::<Value>(input)?;
+                invocations.0.lock().unwrap().push(Invocation {
+                    tool,
+                    arguments: arguments.clone(),
+                });
+                Ok(arguments)
+            })
+        },
+    )
+}
+
+impl<M> ToolCollection<M> {
+    /// Capture how this collection routes and validates calls. See
+    /// [`fingerprint`][crate::fingerprint].
+    pub fn fingerprint(&self) -> Fingerprint {
+        let tools = self
+            .iter()
+            .map(|(_, e)| FingerprintTool {
+                name: e.decl.name.to_string(),
+                description: e.decl.description.to_string(),
+                parameters: e.decl.parameters.clone(),
+                returns: e.decl.returns.clone(),
+                tags: e.decl.tags.clone(),
+            })
+            .collect();
+        let aliases = self
+            .names
+            .aliases()
+            .filter_map(|(alias, id)| {
+                Some((alias.to_string(), self.get_id(id)?.decl.name.to_string()))
+            })
+            .collect();
+        Fingerprint {
+            format: FINGERPRINT_FORMAT,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            features: introspect::features()
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            normalization: self.names.normalization(),
+            aliases,
+            tools,
+        }
+    }
+}
+
+impl ToolCollection {
+    /// A stub collection that routes and validates calls like the one
+    /// `fingerprint` was taken from. Its tools echo their arguments and
+    /// record each call in the returned [`Invocations`].
+    pub fn from_fingerprint(fingerprint: Fingerprint) -> Result<(Self, Invocations), ToolError> {
+        if fingerprint.format > FINGERPRINT_FORMAT {
+            return Err(ToolError::Runtime(format!(
+                "fingerprint format {} is newer than the supported {FINGERPRINT_FORMAT}",
+                fingerprint.format
+            )));
+        }
+        let invocations = Invocations::default();
+        let mut tools = ToolCollection::with_capacity(fingerprint.tools.len());
+        tools.set_name_normalization(fingerprint.normalization)?;
+        for tool in fingerprint.tools {
+            let mut spec = ToolSpec::new(tool.name.clone(), tool.description)
+                .params_schema(tool.parameters.clone())
+                .tags(tool.tags)
+                .func(echo(tool.name, tool.parameters, invocations.clone()));
+            if let Some(returns) = tool.returns {
+                spec = spec.returns_schema(returns);
+            }
+            tools.register_spec(spec)?;
+        }
+        for (alias, name) in &fingerprint.aliases {
+            tools.add_alias(alias, name)?;
+        }
+        Ok((tools, invocations))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn required_arguments_are_checked() {
+        let schema = json!({ "type": "object", "required": ["a"] });
+        assert!(check_arguments(&schema, &json!({ "a": 1 })).is_ok());
+        let err = check_arguments(&schema, &json!({})).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Deserialization error: Failed to deserialize JSON: missing field `a`"
+        );
+        assert!(check_arguments(&schema, &json!([1])).is_err());
+        assert!(check_arguments(&json!({ "type": "string" }), &json!(1)).is_ok());
+    }
+}
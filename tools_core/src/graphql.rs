@@ -0,0 +1,700 @@
+//! Expose GraphQL queries and mutations as remote tools.
+//!
+//! Enabled with the `graphql` feature. [`import`] parses an SDL schema and
+//! produces one [`ToolSpec`] per allowed root field:
+//!
+//! - Field arguments become the tool's parameters. Scalars, enums, input
+//!   objects, lists and non-null types are mapped to JSON Schema.
+//! - A call builds a query document with one variable per argument and
+//!   POSTs `{"query": ..., "variables": ...}` to the endpoint.
+//!
+//! The selection set is generated from the return type. It includes every
+//! scalar and enum field, nested objects up to two levels deep, and
+//! `__typename` for unions. Fields that need arguments are skipped.
+//!
+//! A response with a non-empty `errors` array fails with
+//! [`ToolError::Remote`], even when partial `data` is present. The full
+//! response is kept in `details`.
+//!
+//! ```ignore
+//! let specs = graphql::import(SDL, "https://api.internal/graphql", &["user", "createUser"], Auth::None)?;
+//! tools.register_graphql(specs, send)?;
+//! ```
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use graphql_parser::schema::{
+    Definition, Document, Field, InputValue, Type, TypeDefinition, TypeExtension, parse_schema,
+};
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::{Map, Value, json};
+
+pub use crate::http::{Auth, HttpRequest, HttpSend, Method};
+use crate::{ToolCollection, ToolError, ffi::RawToolDef};
+
+/// How deep the generated selection set follows object-typed fields.
+const MAX_SELECTION_DEPTH: usize = 2;
+
+// ============================================================================
+// ERRORS
+// ============================================================================
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum ImportError {
+    #[error("failed to parse schema: {0}")]
+    Parse(String),
+
+    #[error("allow-list entry `{0}` is not a query or mutation field")]
+    UnknownField(String),
+
+    #[error(
+        "`{0}` is both a query and a mutation field; qualify it as `Query.{0}` or `Mutation.{0}`"
+    )]
+    Ambiguous(String),
+
+    #[error("unknown type `{0}`")]
+    UnknownType(String),
+
+    #[error("argument `{argument}` of `{field}` has output type `{ty}`")]
+    NotAnInputType {
+        field: String,
+        argument: String,
+        ty: String,
+    },
+}
+
+// ============================================================================
+// TOOL SPEC
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OperationKind {
+    Query,
+    Mutation,
+}
+
+impl OperationKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Query => "query",
+            Self::Mutation => "mutation",
+        }
+    }
+}
+
+/// One imported root field: the tool declaration plus the query document
+/// that executes it.
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolSpec {
+    pub name: String,
+    pub description: String,
+    pub parameters: Value,
+    pub operation: OperationKind,
+    /// Root field the tool selects; its value is the tool's result.
+    pub field: String,
+    /// Query document sent with every call.
+    pub document: String,
+    pub endpoint: String,
+    #[serde(skip)]
+    pub auth: Auth,
+}
+
+impl ToolSpec {
+    /// The POST request for a call with `args` as variables.
+    pub fn request(&self, args: &Value) -> Result<HttpRequest, ToolError> {
+        let variables = match args {
+            Value::Object(_) => args.clone(),
+            Value::Null => json!({}),
+            other => {
+                return Err(ToolError::Runtime(format!(
+                    "expected an arguments object, got `{other}`"
+                )));
+            }
+        };
+        let mut headers = vec![("Content-Type".to_string(), "application/json".to_string())];
+        self.auth.apply(&mut headers);
+        Ok(HttpRequest {
+            method: Method::Post,
+            url: self.endpoint.clone(),
+            headers,
+            body: Some(json!({ "query": self.document, "variables": variables })),
+        })
+    }
+
+    /// Pull this tool's field out of a GraphQL response body.
+    pub fn extract(&self, response: Value) -> Result<Value, ToolError> {
+        if let Some(errors) = response["errors"].as_array().filter(|e| !e.is_empty()) {
+            let message = errors
+                .iter()
+                .map(|e| e["message"].as_str().unwrap_or("unknown error"))
+                .collect::<Vec<_>>()
+                .join("; ");
+            return Err(ToolError::Remote {
+                message,
+                details: response,
+            });
+        }
+        match response.get("data") {
+            Some(Value::Object(data)) => Ok(data.get(&self.field).cloned().unwrap_or(Value::Null)),
+            _ => Err(ToolError::Remote {
+                message: "response has neither `data` nor `errors`".to_string(),
+                details: response,
+            }),
+        }
+    }
+
+    /// Turn the spec into a [`RawToolDef`] that sends through `send`.
+    /// `meta` is `{"operation": "query" | "mutation", "field": ...}`.
+    pub fn into_raw(self, send: Arc<HttpSend>) -> RawToolDef {
+        let meta = json!({ "operation": self.operation, "field": self.field });
+        let name = self.name.clone();
+        let description = self.description.clone();
+        let parameters = self.parameters.clone();
+        let spec = Arc::new(self);
+        RawToolDef {
+            name,
+            description,
+            parameters,
+            meta,
+            func: Box::new(move |args| {
+                let spec = spec.clone();
+                let send = send.clone();
+                Box::pin(async move {
+                    let response = send(spec.request(&args)?).await?;
+                    spec.extract(response)
+                })
+            }),
+        }
+    }
+}
+
+impl<M: DeserializeOwned> ToolCollection<M> {
+    /// Register every imported field as a remote tool that dispatches
+    /// through `send`. Each tool's metadata is deserialized from
+    /// `{"operation": ..., "field": ...}`.
+    pub fn register_graphql(
+        &mut self,
+        specs: Vec<ToolSpec>,
+        send: Arc<HttpSend>,
+    ) -> Result<&mut Self, ToolError> {
+        for spec in specs {
+            self.register_spec(spec.into_raw(send.clone()).into_spec()?)?;
+        }
+        Ok(self)
+    }
+}
+
+// ============================================================================
+// IMPORT
+// ============================================================================
+
+/// Build one [`ToolSpec`] per entry of `allow`. Entries are root field
+/// names (`user`), qualified when a name exists on both roots
+/// (`Mutation.user`).
+pub fn import(
+    sdl: &str,
+    endpoint: &str,
+    allow: &[&str],
+    auth: Auth,
+) -> Result<Vec<ToolSpec>, ImportError> {
+    let doc = parse_schema::<&str>(sdl).map_err(|e| ImportError::Parse(e.to_string()))?;
+    let schema = Schema::index(&doc);
+
+    let mut specs = Vec::with_capacity(allow.len());
+    for entry in allow {
+        let (kind, field) = schema.find_root_field(entry)?;
+        specs.push(schema.tool(kind, field, endpoint, &auth)?);
+    }
+    Ok(specs)
+}
+
+enum Kind<'d> {
+    Scalar(Option<&'d str>),
+    Enum(Option<&'d str>, Vec<&'d str>),
+    Input(Option<&'d str>, Vec<&'d InputValue<'d, &'d str>>),
+    Object(Vec<&'d Field<'d, &'d str>>),
+    Union,
+}
+
+struct Schema<'d> {
+    types: HashMap<&'d str, Kind<'d>>,
+    query: &'d str,
+    mutation: &'d str,
+}
+
+impl<'d> Schema<'d> {
+    fn index(doc: &'d Document<'d, &'d str>) -> Self {
+        let mut types = HashMap::new();
+        let mut query = "Query";
+        let mut mutation = "Mutation";
+
+        for def in &doc.definitions {
+            match def {
+                Definition::SchemaDefinition(s) => {
+                    query = s.query.unwrap_or(query);
+                    mutation = s.mutation.unwrap_or(mutation);
+                }
+                Definition::TypeDefinition(t) => {
+                    let (name, kind) = match t {
+                        TypeDefinition::Scalar(s) => {
+                            (s.name, Kind::Scalar(s.description.as_deref()))
+                        }
+                        TypeDefinition::Enum(e) => (
+                            e.name,
+                            Kind::Enum(
+                                e.description.as_deref(),
+                                e.values.iter().map(|v| v.name).collect(),
+                            ),
+                        ),
+                        TypeDefinition::InputObject(i) => (
+                            i.name,
+                            Kind::Input(i.description.as_deref(), i.fields.iter().collect()),
+                        ),
+                        TypeDefinition::Object(o) => {
+                            (o.name, Kind::Object(o.fields.iter().collect()))
+                        }
+                        TypeDefinition::Interface(i) => {
+                            (i.name, Kind::Object(i.fields.iter().collect()))
+                        }
+                        TypeDefinition::Union(u) => (u.name, Kind::Union),
+                    };
+                    types.insert(name, kind);
+                }
+                Definition::TypeExtension(_) | Definition::DirectiveDefinition(_) => {}
+            }
+        }
+
+        // Extensions add to types defined anywhere in the document.
+        for def in &doc.definitions {
+            let Definition::TypeExtension(ext) = def else {
+                continue;
+            };
+            match ext {
+                TypeExtension::Object(o) => {
+                    if let Some(Kind::Object(fields)) = types.get_mut(o.name) {
+                        fields.extend(o.fields.iter());
+                    }
+                }
+                TypeExtension::Enum(e) => {
+                    if let Some(Kind::Enum(_, values)) = types.get_mut(e.name) {
+                        values.extend(e.values.iter().map(|v| v.name));
+                    }
+                }
+                TypeExtension::InputObject(i) => {
+                    if let Some(Kind::Input(_, fields)) = types.get_mut(i.name) {
+                        fields.extend(i.fields.iter());
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Self {
+            types,
+            query,
+            mutation,
+        }
+    }
+
+    fn root_fields(&self, kind: OperationKind) -> &[&'d Field<'d, &'d str>] {
+        let root = match kind {
+            OperationKind::Query => self.query,
+            OperationKind::Mutation => self.mutation,
+        };
+        match self.types.get(root) {
+            Some(Kind::Object(fields)) => fields,
+            _ => &[],
+        }
+    }
+
+    fn find_root_field(
+        &self,
+        entry: &str,
+    ) -> Result<(OperationKind, &'d Field<'d, &'d str>), ImportError> {
+        let lookup = |kind: OperationKind, name: &str| {
+            self.root_fields(kind)
+                .iter()
+                .find(|f| f.name == name)
+                .map(|f| (kind, *f))
+        };
+        let unknown = || ImportError::UnknownField(entry.to_string());
+
+        match entry.split_once('.') {
+            Some((root, name)) if root == self.query => {
+                lookup(OperationKind::Query, name).ok_or_else(unknown)
+            }
+            Some((root, name)) if root == self.mutation => {
+                lookup(OperationKind::Mutation, name).ok_or_else(unknown)
+            }
+            Some(_) => Err(unknown()),
+            None => match (
+                lookup(OperationKind::Query, entry),
+                lookup(OperationKind::Mutation, entry),
+            ) {
+                (Some(_), Some(_)) => Err(ImportError::Ambiguous(entry.to_string())),
+                (Some(found), None) | (None, Some(found)) => Ok(found),
+                (None, None) => Err(unknown()),
+            },
+        }
+    }
+
+    fn tool(
+        &self,
+        kind: OperationKind,
+        field: &Field<'d, &'d str>,
+        endpoint: &str,
+        auth: &Auth,
+    ) -> Result<ToolSpec, ImportError> {
+        let mut properties = Map::new();
+        let mut required = Vec::new();
+        let mut variables = Vec::new();
+        let mut arguments = Vec::new();
+
+        for arg in &field.arguments {
+            let mut schema = self
+                .input_schema(&arg.value_type, &[])
+                .map_err(|e| match e {
+                    ImportError::NotAnInputType { ty, .. } => ImportError::NotAnInputType {
+                        field: field.name.to_string(),
+                        argument: arg.name.to_string(),
+                        ty,
+                    },
+                    other => other,
+                })?;
+            if let (Some(desc), Value::Object(obj)) = (&arg.description, &mut schema) {
+                obj.insert("description".to_string(), json!(desc));
+            }
+            properties.insert(arg.name.to_string(), schema);
+
+            // A non-null argument with a default may be omitted; declare its
+            // variable as nullable so the server applies the default.
+            let var_type = match (&arg.value_type, &arg.default_value) {
+                (Type::NonNullType(inner), Some(_)) => type_ref(inner),
+                (ty, None) => {
+                    if matches!(ty, Type::NonNullType(_)) {
+                        required.push(json!(arg.name));
+                    }
+                    type_ref(ty)
+                }
+                (ty, Some(_)) => type_ref(ty),
+            };
+            variables.push(format!("${}: {}", arg.name, var_type));
+            arguments.push(format!("{0}: ${0}", arg.name));
+        }
+
+        let mut document = format!("{} {}", kind.as_str(), field.name);
+        if !variables.is_empty() {
+            document.push_str(&format!("({})", variables.join(", ")));
+        }
+        document.push_str(&format!(" {{ {}", field.name));
+        if !arguments.is_empty() {
+            document.push_str(&format!("({})", arguments.join(", ")));
+        }
+        if let Some(selection) = self.selection(named(&field.field_type), 0, &[]) {
+            document.push(' ');
+            document.push_str(&selection);
+        }
+        document.push_str(" }");
+
+        let description = field
+            .description
+            .clone()
+            .unwrap_or_else(|| format!("GraphQL {} `{}`.", kind.as_str(), field.name));
+
+        Ok(ToolSpec {
+            name: field.name.to_string(),
+            description,
+            parameters: json!({
+                "type": "object",
+                "properties": properties,
+                "required": required,
+            }),
+            operation: kind,
+            field: field.name.to_string(),
+            document,
+            endpoint: endpoint.to_string(),
+            auth: auth.clone(),
+        })
+    }
+
+    /// JSON Schema for an input type. `stack` holds the input objects being
+    /// expanded; a recursive reference becomes `{}`.
+    fn input_schema(&self, ty: &Type<'d, &'d str>, stack: &[&str]) -> Result<Value, ImportError> {
+        let name = match ty {
+            Type::NonNullType(inner) => return self.input_schema(inner, stack),
+            Type::ListType(inner) => {
+                return Ok(json!({ "type": "array", "items": self.input_schema(inner, stack)? }));
+            }
+            Type::NamedType(name) => *name,
+        };
+
+        let builtin = match name {
+            "Int" => Some(json!({ "type": "integer" })),
+            "Float" => Some(json!({ "type": "number" })),
+            "String" | "ID" => Some(json!({ "type": "string" })),
+            "Boolean" => Some(json!({ "type": "boolean" })),
+            _ => None,
+        };
+        if let Some(schema) = builtin {
+            return Ok(schema);
+        }
+
+        let mut schema = match self.types.get(name) {
+            None => return Err(ImportError::UnknownType(name.to_string())),
+            Some(Kind::Scalar(desc)) => json!({
+                "description": desc.map_or_else(|| format!("Custom scalar `{name}`."), str::to_string),
+            }),
+            Some(Kind::Enum(desc, values)) => {
+                let mut schema = json!({ "type": "string", "enum": values });
+                if let Some(desc) = desc {
+                    schema["description"] = json!(desc);
+                }
+                schema
+            }
+            Some(Kind::Input(_, _)) if stack.contains(&name) => json!({}),
+            Some(Kind::Input(desc, fields)) => {
+                let mut nested = stack.to_vec();
+                nested.push(name);
+                let mut properties = Map::new();
+                let mut required = Vec::new();
+                for f in fields {
+                    let mut field_schema = self.input_schema(&f.value_type, &nested)?;
+                    if let (Some(d), Value::Object(obj)) = (&f.description, &mut field_schema) {
+                        obj.insert("description".to_string(), json!(d));
+                    }
+                    properties.insert(f.name.to_string(), field_schema);
+                    if matches!(f.value_type, Type::NonNullType(_)) && f.default_value.is_none() {
+                        required.push(json!(f.name));
+                    }
+                }
+                let mut schema = json!({
+                    "type": "object",
+                    "properties": properties,
+                    "required": required,
+                });
+                if let Some(desc) = desc {
+                    schema["description"] = json!(desc);
+                }
+                schema
+            }
+            Some(Kind::Object(_) | Kind::Union) => {
+                return Err(ImportError::NotAnInputType {
+                    field: String::new(),
+                    argument: String::new(),
+                    ty: name.to_string(),
+                });
+            }
+        };
+        if let Value::Object(obj) = &mut schema {
+            obj.retain(|_, v| !v.is_null());
+        }
+        Ok(schema)
+    }
+
+    /// `{ a b nested { c } }` for an output type, or `None` for leaf types.
+    fn selection(&self, name: &str, depth: usize, stack: &[&str]) -> Option<String> {
+        let fields = match self.types.get(name)? {
+            Kind::Object(fields) => fields,
+            Kind::Union => return Some("{ __typename }".to_string()),
+            _ => return None,
+        };
+        let mut nested = stack.to_vec();
+        nested.push(name);
+
+        let mut parts = Vec::new();
+        for f in fields {
+            // Fields with required arguments cannot be selected blindly.
+            let needs_args = f
+                .arguments
+                .iter()
+                .any(|a| matches!(a.value_type, Type::NonNullType(_)) && a.default_value.is_none());
+            if needs_args {
+                continue;
+            }
+            let target = named(&f.field_type);
+            match self.types.get(target) {
+                Some(Kind::Object(_) | Kind::Union) => {
+                    if depth + 1 >= MAX_SELECTION_DEPTH || nested.contains(&target) {
+                        continue;
+                    }
+                    if let Some(sub) = self.selection(target, depth + 1, &nested) {
+                        parts.push(format!("{} {}", f.name, sub));
+                    }
+                }
+                _ => parts.push(f.name.to_string()),
+            }
+        }
+        if parts.is_empty() {
+            parts.push("__typename".to_string());
+        }
+        Some(format!("{{ {} }}", parts.join(" ")))
+    }
+}
+
+/// Innermost named type: `[User!]!` → `User`.
+fn named<'a>(ty: &Type<'a, &'a str>) -> &'a str {
+    match ty {
+        Type::NamedType(name) => name,
+        Type::ListType(inner) | Type::NonNullType(inner) => named(inner),
+    }
+}
+
+/// SDL spelling of a type reference, for variable declarations.
+fn type_ref<'a>(ty: &Type<'a, &'a str>) -> String {
+    match ty {
+        Type::NamedType(name) => name.to_string(),
+        Type::ListType(inner) => format!("[{}]", type_ref(inner)),
+        Type::NonNullType(inner) => format!("{}!", type_ref(inner)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SDL: &str = r#"
+        "A user account."
+        type User {
+            id: ID!
+            name: String
+            role: Role!
+            friends(first: Int!): [User!]!
+            best: User
+            pet: Pet
+        }
+
+        union Pet = Cat | Dog
+        type Cat { lives: Int }
+        type Dog { good: Boolean }
+
+        enum Role { ADMIN MEMBER }
+
+        input UserFilter {
+            "Substring match on the name."
+            name: String
+            roles: [Role!]
+            and: UserFilter
+            limit: Int! = 10
+        }
+
+        scalar DateTime
+
+        type Query {
+            "Look up one user."
+            user(id: ID!): User
+            users(filter: UserFilter, first: Int! = 20): [User!]!
+            now: DateTime!
+        }
+
+        type Mutation {
+            rename(id: ID!, name: String!): User
+            user(id: ID!): User
+        }
+    "#;
+
+    #[test]
+    fn maps_arguments_to_json_schema() {
+        let specs = import(SDL, "http://x/graphql", &["users"], Auth::None).unwrap();
+        let users = &specs[0];
+        assert_eq!(users.operation, OperationKind::Query);
+        // `first` has a default, so it is optional.
+        assert_eq!(users.parameters["required"], json!([]));
+
+        let filter = &users.parameters["properties"]["filter"];
+        assert_eq!(filter["type"], "object");
+        assert_eq!(filter["required"], json!([]));
+        assert_eq!(
+            filter["properties"]["name"]["description"],
+            "Substring match on the name."
+        );
+        assert_eq!(
+            filter["properties"]["roles"],
+            json!({ "type": "array", "items": { "type": "string", "enum": ["ADMIN", "MEMBER"] } })
+        );
+        assert_eq!(filter["properties"]["and"], json!({}));
+    }
+
+    #[test]
+    fn builds_document_with_variables_and_selection() {
+        let specs = import(
+            SDL,
+            "http://x/graphql",
+            &["Query.user", "users"],
+            Auth::None,
+        )
+        .unwrap();
+        // `best: User` is a cycle back to `User` and is left out.
+        assert_eq!(
+            specs[0].document,
+            "query user($id: ID!) { user(id: $id) { id name role pet { __typename } } }"
+        );
+        assert_eq!(specs[0].description, "Look up one user.");
+        assert_eq!(specs[0].parameters["required"], json!(["id"]));
+        assert!(
+            specs[1]
+                .document
+                .starts_with("query users($filter: UserFilter, $first: Int) ")
+        );
+    }
+
+    #[test]
+    fn scalar_return_has_no_selection() {
+        let specs = import(SDL, "http://x/graphql", &["now"], Auth::None).unwrap();
+        assert_eq!(specs[0].document, "query now { now }");
+    }
+
+    #[test]
+    fn allow_list_errors() {
+        let err = import(SDL, "x", &["user"], Auth::None).unwrap_err();
+        assert_eq!(err, ImportError::Ambiguous("user".into()));
+        let err = import(SDL, "x", &["nope"], Auth::None).unwrap_err();
+        assert_eq!(err, ImportError::UnknownField("nope".into()));
+        let err = import("type Query {", "x", &[], Auth::None).unwrap_err();
+        assert!(matches!(err, ImportError::Parse(_)));
+    }
+
+    #[test]
+    fn request_and_extraction() {
+        let specs = import(
+            SDL,
+            "http://x/graphql",
+            &["rename"],
+            Auth::Bearer("t".into()),
+        )
+        .unwrap();
+        let spec = &specs[0];
+        assert_eq!(spec.operation, OperationKind::Mutation);
+
+        let req = spec.request(&json!({ "id": "1", "name": "Ada" })).unwrap();
+        assert_eq!(req.method, Method::Post);
+        assert_eq!(
+            req.body.as_ref().unwrap()["variables"],
+            json!({ "id": "1", "name": "Ada" })
+        );
+        assert!(
+            req.headers
+                .contains(&("Authorization".into(), "Bearer t".into()))
+        );
+
+        let ok = spec
+            .extract(json!({ "data": { "rename": { "id": "1" } } }))
+            .unwrap();
+        assert_eq!(ok, json!({ "id": "1" }));
+
+        let err = spec
+            .extract(json!({
+                "data": { "rename": null },
+                "errors": [{ "message": "not allowed", "path": ["rename"] }]
+            }))
+            .unwrap_err();
+        match err {
+            ToolError::Remote { message, details } => {
+                assert_eq!(message, "not allowed");
+                assert_eq!(details["errors"][0]["path"], json!(["rename"]));
+            }
+            other => panic!("unexpected error: {other}"),
+        }
+    }
+}
@@ -0,0 +1,381 @@
+//! Stable hashes of JSON arguments.
+//!
+//! Two argument objects that mean the same thing should hash the same,
+//! whatever order their keys arrived in or however their numbers were
+//! spelled. [`canonical_json`] writes a [`Value`] in this canonical form:
+//!
+//! - no whitespace between tokens;
+//! - object keys sorted by their UTF-8 bytes;
+//! - array order kept;
+//! - strings escaped as `serde_json` writes them: `"`, `\` and control
+//!   characters escaped, everything else verbatim;
+//! - integers written exactly, as `serde_json` parsed them;
+//! - a float that is integral and within ±2^53 written as that integer,
+//!   so `1.0` and `1` are the same value and `-0.0` is `0`;
+//! - any other float in Rust's shortest round-trip exponent form, e.g.
+//!   `1.5e0`, `1e-7`, `1.152921504606847e18`.
+//!
+//! [`canonical_json_pretty`] is the same form with indentation, for
+//! golden files.
+//!
+//! [`canonical_hash`] is 64-bit FNV-1a over those bytes. It is fixed by
+//! this crate, unlike `std`'s hashers, so it can be stored and compared
+//! across processes and releases. It is not collision resistant; for
+//! audit trails, feed [`canonical_json`] to a cryptographic hash.
+//!
+//! The same hash fingerprints declarations. Provider-side prompt caches
+//! key on the exact tool block, so an agent loop that compares
+//! [`registry_hash`][ToolCollection::registry_hash] between turns knows
+//! when to send the declarations afresh:
+//!
+//! ```ignore
+//! let hash = tools.registry_hash();
+//! if last_sent != Some(hash) {
+//!     request.tools = tools.json()?;
+//!     last_sent = Some(hash);
+//! }
+//! ```
+
+use std::fmt::Write;
+
+use serde_json::{Number, Value, json};
+
+use crate::{FunctionDecl, ToolCollection};
+
+/// Key [`ToolCollection::json`] writes each declaration's
+/// [`content_hash`][FunctionDecl::content_hash] under, as 16 hex digits,
+/// once [`set_content_hashes`][ToolCollection::set_content_hashes] is on.
+pub const CONTENT_HASH_KEY: &str = "x-content-hash";
+
+/// Largest integer every `f64` below it represents exactly.
+const EXACT_F64: f64 = 9_007_199_254_740_992.0; // 2^53
+
+/// `value` in the canonical form described in the [module docs][self].
+pub fn canonical_json(value: &Value) -> String {
+    let mut out = String::new();
+    write_value(&mut out, value, None);
+    out
+}
+
+/// [`canonical_json`] laid out for reading: two-space indentation, one
+/// array item or object entry per line, `": "` after keys and empty
+/// containers as `[]` and `{}`. For golden files and documentation.
+pub fn canonical_json_pretty(value: &Value) -> String {
+    let mut out = String::new();
+    write_value(&mut out, value, Some(0));
+    out
+}
+
+/// 64-bit FNV-1a of [`canonical_json`].
+pub fn canonical_hash(value: &Value) -> u64 {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    canonical_json(value).bytes().fold(OFFSET, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+    })
+}
+
+/// Write `value`, compact for `indent: None`, else pretty at that depth.
+fn write_value(out: &mut String, value: &Value, indent: Option<usize>) {
+    match value {
+        Value::Null | Value::Bool(_) | Value::String(_) => out.push_str(&value.to_string()),
+        Value::Number(n) => write_number(out, n),
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                separate(out, i, indent);
+                write_value(out, item, indent.map(|depth| depth + 1));
+            }
+            close(out, items.is_empty(), indent, ']');
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+            out.push('{');
+            for (i, (key, item)) in entries.iter().enumerate() {
+                separate(out, i, indent);
+                out.push_str(&Value::String((*key).clone()).to_string());
+                out.push_str(if indent.is_some() { ": " } else { ":" });
+                write_value(out, item, indent.map(|depth| depth + 1));
+            }
+            close(out, entries.is_empty(), indent, '}');
+        }
+    }
+}
+
+/// Before the `i`th item of a container at `indent`.
+fn separate(out: &mut String, i: usize, indent: Option<usize>) {
+    if i > 0 {
+        out.push(',');
+    }
+    if let Some(depth) = indent {
+        newline(out, depth + 1);
+    }
+}
+
+fn close(out: &mut String, empty: bool, indent: Option<usize>, bracket: char) {
+    if let (Some(depth), false) = (indent, empty) {
+        newline(out, depth);
+    }
+    out.push(bracket);
+}
+
+fn newline(out: &mut String, depth: usize) {
+    out.push('\n');
+    out.extend(std::iter::repeat_n("  ", depth));
+}
+
+fn write_number(out: &mut String, n: &Number) {
+    if n.is_i64() || n.is_u64() {
+        out.push_str(&n.to_string());
+        return;
+    }
+    // Non-finite floats can't appear in a `Value`.
+    let f = n.as_f64().unwrap_or_default();
+    if f.fract() == 0.0 && f.abs() <= EXACT_F64 {
+        let _ = write!(out, "{}", f as i64);
+    } else {
+        let _ = write!(out, "{f:e}");
+    }
+}
+
+// ============================================================================
+// DECLARATION HASHES
+// ============================================================================
+
+impl FunctionDecl<'_> {
+    /// [`canonical_hash`] of `{"name", "description", "parameters"}`.
+    /// Tags, the result schema and the dialect don't count.
+    pub fn content_hash(&self) -> u64 {
+        canonical_hash(&json!({
+            "name": self.name,
+            "description": self.description,
+            "parameters": self.parameters,
+        }))
+    }
+}
+
+/// [`canonical_hash`] of the sorted content hashes of `decls`.
+pub(crate) fn combined_hash<'a>(decls: impl Iterator<Item = &'a FunctionDecl<'static>>) -> u64 {
+    let mut hashes: Vec<u64> = decls.map(FunctionDecl::content_hash).collect();
+    hashes.sort_unstable();
+    canonical_hash(&json!(hashes))
+}
+
+impl<M> ToolCollection<M> {
+    /// [`canonical_hash`] of the sorted
+    /// [`content_hash`][FunctionDecl::content_hash]es of the
+    /// [declared][Self::declarations] tools. Registration order doesn't
+    /// count; adding, removing, hiding or changing a tool does.
+    pub fn registry_hash(&self) -> u64 {
+        combined_hash(self.declarations())
+    }
+
+    /// Write each declaration's content hash under [`CONTENT_HASH_KEY`]
+    /// in [`json`][Self::json]. Off by default; a provider may reject
+    /// the unknown key.
+    pub fn set_content_hashes(&mut self, on: bool) {
+        if self.content_hashes != on {
+            self.content_hashes = on;
+            self.json_cache.take();
+        }
+    }
+
+    pub fn content_hashes(&self) -> bool {
+        self.content_hashes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Fixed cases for each rule of the canonical form, and randomized
+    // checks over generated values (a small seeded generator stands in
+    // for a property-testing crate).
+
+    use super::*;
+
+    /// xorshift64*, seeded per test so failures reproduce.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 >> 12;
+            self.0 ^= self.0 << 25;
+            self.0 ^= self.0 >> 27;
+            self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+        }
+
+        fn below(&mut self, n: u64) -> u64 {
+            self.next() % n
+        }
+    }
+
+    fn value(rng: &mut Rng, depth: u32) -> Value {
+        let kinds = if depth == 0 { 5 } else { 7 };
+        match rng.below(kinds) {
+            0 => Value::Null,
+            1 => json!(rng.below(2) == 0),
+            2 => json!(rng.below(2_000) as i64 - 1_000),
+            3 => json!((rng.below(20_000) as f64 - 10_000.0) / 8.0),
+            4 => json!(format!("s{}\n\"é", rng.below(50))),
+            5 => (0..rng.below(4)).map(|_| value(rng, depth - 1)).collect(),
+            _ => (0..rng.below(5))
+                .map(|_| (format!("k{}", rng.below(8)), value(rng, depth - 1)))
+                .collect::<serde_json::Map<_, _>>()
+                .into(),
+        }
+    }
+
+    /// JSON text for `value` with every object's keys in a random order and
+    /// random whitespace between tokens.
+    fn scrambled(rng: &mut Rng, value: &Value) -> String {
+        let ws = |rng: &mut Rng| [" ", "", "\n  ", "\t"][rng.below(4) as usize];
+        match value {
+            Value::Array(items) => {
+                let parts: Vec<String> = items.iter().map(|v| scrambled(rng, v)).collect();
+                format!("[{}{}]", parts.join(","), ws(rng))
+            }
+            Value::Object(map) => {
+                let mut entries: Vec<_> = map.iter().collect();
+                for i in (1..entries.len()).rev() {
+                    entries.swap(i, rng.below(i as u64 + 1) as usize);
+                }
+                let parts: Vec<String> = entries
+                    .into_iter()
+                    .map(|(k, v)| format!("{}{}:{}", json!(k), ws(rng), scrambled(rng, v)))
+                    .collect();
+                format!("{{{}{}}}", ws(rng), parts.join(","))
+            }
+            other => format!("{}{other}", ws(rng)),
+        }
+    }
+
+    /// `value` with one leaf changed to something different.
+    fn mutated(rng: &mut Rng, value: &Value) -> Value {
+        match value {
+            Value::Array(items) if !items.is_empty() => {
+                let mut items = items.clone();
+                let i = rng.below(items.len() as u64) as usize;
+                items[i] = mutated(rng, &items[i]);
+                Value::Array(items)
+            }
+            Value::Object(map) if !map.is_empty() => {
+                let mut map = map.clone();
+                let key = map
+                    .keys()
+                    .nth(rng.below(map.len() as u64) as usize)
+                    .unwrap()
+                    .clone();
+                let changed = mutated(rng, &map[&key]);
+                map.insert(key, changed);
+                Value::Object(map)
+            }
+            Value::String(s) => json!(format!("{s}!")),
+            Value::Number(n) => json!(n.as_f64().unwrap() + 0.5),
+            Value::Bool(b) => json!(!b),
+            _ => json!([value]),
+        }
+    }
+
+    // ---------- canonical form ----------
+
+    #[test]
+    fn objects_sort_keys_and_drop_whitespace() {
+        let v: Value =
+            serde_json::from_str(r#"{ "b": [1, {"z": 0, "a": null}], "a": "x" }"#).unwrap();
+        assert_eq!(canonical_json(&v), r#"{"a":"x","b":[1,{"a":null,"z":0}]}"#);
+    }
+
+    #[test]
+    fn numbers_have_one_spelling() {
+        let cases = [
+            (json!(1), "1"),
+            (json!(1.0), "1"),
+            (json!(-0.0), "0"),
+            (json!(0.0), "0"),
+            (json!(1.5), "1.5e0"),
+            (json!(0.0000001), "1e-7"),
+            (json!(9_007_199_254_740_992.0), "9007199254740992"),
+            (json!(1e300), "1e300"),
+            (json!(u64::MAX), "18446744073709551615"),
+            (json!(i64::MIN), "-9223372036854775808"),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(canonical_json(&value), expected, "{value}");
+        }
+        assert_eq!(
+            canonical_hash(&json!({ "n": 2 })),
+            canonical_hash(&json!({ "n": 2.0 }))
+        );
+    }
+
+    #[test]
+    fn strings_are_escaped_like_serde_json() {
+        assert_eq!(
+            canonical_json(&json!("a\"\\\n\u{1}é")),
+            r#""a\"\\\n\u0001é""#
+        );
+    }
+
+    #[test]
+    fn hash_is_pinned() {
+        // Stored hashes must stay valid across releases.
+        let v = json!({ "b": 1.5, "a": [true, null] });
+        assert_eq!(canonical_json(&v), r#"{"a":[true,null],"b":1.5e0}"#);
+        assert_eq!(canonical_hash(&v), 0x88ae_5456_3d07_60ba);
+    }
+
+    #[test]
+    fn pretty_form_indents_the_same_tokens() {
+        let v = json!({ "b": [1.0, {}], "a": [], "c": { "z": 1.5, "y": null } });
+        let expected = r#"{
+  "a": [],
+  "b": [
+    1,
+    {}
+  ],
+  "c": {
+    "y": null,
+    "z": 1.5e0
+  }
+}"#;
+        assert_eq!(canonical_json_pretty(&v), expected);
+        let stripped: String = expected.split_whitespace().collect();
+        assert_eq!(canonical_json(&v), stripped);
+    }
+
+    // ---------- properties ----------
+
+    #[test]
+    fn key_order_and_whitespace_never_change_the_hash() {
+        let mut rng = Rng(0x5eed_0001);
+        for _ in 0..500 {
+            let v = value(&mut rng, 4);
+            let text = scrambled(&mut rng, &v);
+            let reparsed: Value = serde_json::from_str(&text).unwrap();
+            assert_eq!(canonical_json(&reparsed), canonical_json(&v), "{text}");
+            assert_eq!(canonical_hash(&reparsed), canonical_hash(&v));
+        }
+    }
+
+    #[test]
+    fn different_values_hash_differently() {
+        let mut rng = Rng(0x5eed_0002);
+        for _ in 0..500 {
+            let v = value(&mut rng, 4);
+            let w = mutated(&mut rng, &v);
+            assert_ne!(canonical_json(&v), canonical_json(&w));
+            assert_ne!(canonical_hash(&v), canonical_hash(&w), "{v} vs {w}");
+        }
+    }
+
+    #[test]
+    fn canonical_form_round_trips() {
+        let mut rng = Rng(0x5eed_0003);
+        for _ in 0..500 {
+            let v = value(&mut rng, 4);
+            let reparsed: Value = serde_json::from_str(&canonical_json(&v)).unwrap();
+            assert_eq!(canonical_json(&reparsed), canonical_json(&v));
+        }
+    }
+}
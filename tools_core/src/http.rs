@@ -0,0 +1,99 @@
+//! HTTP request types shared by the remote-tool importers ([`openapi`],
+//! [`graphql`]).
+//!
+//! Importers only build [`HttpRequest`]s; the caller supplies an
+//! [`HttpSend`] function backed by whatever client the application
+//! already uses.
+//!
+//! [`openapi`]: crate::openapi
+//! [`graphql`]: crate::graphql
+
+use core::fmt;
+
+use futures::future::BoxFuture;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{ToolError, secrets::SecretString};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Method {
+    Get,
+    Put,
+    Post,
+    Delete,
+    Patch,
+    Head,
+    Options,
+}
+
+impl Method {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Get => "GET",
+            Self::Put => "PUT",
+            Self::Post => "POST",
+            Self::Delete => "DELETE",
+            Self::Patch => "PATCH",
+            Self::Head => "HEAD",
+            Self::Options => "OPTIONS",
+        }
+    }
+}
+
+impl fmt::Display for Method {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Credentials attached to every request built by an importer.
+#[derive(Debug, Clone, Default)]
+pub enum Auth {
+    #[default]
+    None,
+    /// `Authorization: Bearer <token>`.
+    Bearer(SecretString),
+    /// A custom header such as `X-API-Key`.
+    Header { name: String, value: SecretString },
+}
+
+impl Auth {
+    pub(crate) fn apply(&self, headers: &mut Vec<(String, String)>) {
+        match self {
+            Auth::None => {}
+            Auth::Bearer(token) => headers.push((
+                "Authorization".to_string(),
+                format!("Bearer {}", token.expose()),
+            )),
+            Auth::Header { name, value } => headers.push((name.clone(), value.expose().clone())),
+        }
+    }
+}
+
+/// A concrete request, ready to hand to an HTTP client.
+#[derive(Clone, PartialEq)]
+pub struct HttpRequest {
+    pub method: Method,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<Value>,
+}
+
+impl fmt::Debug for HttpRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Header values may carry credentials.
+        let headers: Vec<&str> = self.headers.iter().map(|(k, _)| k.as_str()).collect();
+        f.debug_struct("HttpRequest")
+            .field("method", &self.method)
+            .field("url", &self.url)
+            .field("headers", &headers)
+            .field("body", &self.body)
+            .finish()
+    }
+}
+
+/// Sends an [`HttpRequest`] and returns the decoded response body.
+pub type HttpSend =
+    dyn Fn(HttpRequest) -> BoxFuture<'static, Result<Value, ToolError>> + Send + Sync;
@@ -0,0 +1,191 @@
+//! A serializable snapshot of a collection, for dashboards and admin
+//! endpoints.
+//!
+//! ```ignore
+//! let report = tools.introspect();
+//! let body = serde_json::to_string(&report)?;
+//! ```
+//!
+//! The JSON shape is versioned by [`CollectionReport::format`]. Fields are
+//! only ever added within one format; renames and removals bump it.
+
+use serde::Serialize;
+
+use crate::{
+    QueueReport, RecentCall, ToolCollection, ToolOrigin, cache::CacheReport, circuit::CircuitReport,
+};
+
+/// Current value of [`CollectionReport::format`].
+pub const REPORT_FORMAT: u32 = 1;
+
+/// Everything a collection knows about itself, as returned by
+/// [`ToolCollection::introspect`].
+#[derive(Debug, Clone, Serialize)]
+pub struct CollectionReport<'a, M> {
+    /// Version of this report's JSON shape.
+    pub format: u32,
+    /// Version of `tools_core` that produced the report.
+    pub version: &'static str,
+    /// Cargo features `tools_core` was built with.
+    pub features: Vec<&'static str>,
+    pub tool_count: usize,
+    pub capacity: usize,
+    /// Whether a shared context was attached with the builder.
+    pub has_context: bool,
+    /// Whether the serialized declarations are cached; see
+    /// [`ToolCollection::warm_up`].
+    pub schema_cache_warm: bool,
+    pub tools: Vec<ToolReport<'a, M>>,
+    /// The last finished calls; see [`ToolCollection::recent_calls`].
+    pub recent_calls: Vec<RecentCall>,
+}
+
+/// One registered tool in a [`CollectionReport`].
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolReport<'a, M> {
+    /// Index of the tool's [`ToolId`][crate::ToolId].
+    pub id: usize,
+    pub name: &'a str,
+    pub description: &'a str,
+    /// Top-level argument names from the parameter schema.
+    pub parameters: Vec<&'a str>,
+    pub has_returns: bool,
+    /// Whether the parameter schema was given rather than derived; see
+    /// [`ToolSpec::handler`][crate::ToolSpec::handler].
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub external_schema: bool,
+    pub tags: &'a [String],
+    pub meta: &'a M,
+    /// Defining crate and module, for `#[tool]` functions.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub origin: Option<ToolOrigin>,
+    /// Breaker state, for tools with a circuit breaker.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub circuit: Option<CircuitReport>,
+    /// Hits and misses, for tools with a result cache; see
+    /// [`set_cache_policy`][ToolCollection::set_cache_policy].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache: Option<CacheReport>,
+    /// Wait times, for tools registered with
+    /// [`register_local`][ToolCollection::register_local].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub queue: Option<QueueReport>,
+}
+
+/// Every Cargo feature other than `default`, with whether it is on.
+const FEATURES: [(&str, bool); 16] = [
+    ("anyhow", cfg!(feature = "anyhow")),
+    ("broker", cfg!(feature = "broker")),
+    ("dev-tools", cfg!(feature = "dev-tools")),
+    ("eyre", cfg!(feature = "eyre")),
+    ("fast-hash", cfg!(feature = "fast-hash")),
+    ("graphql", cfg!(feature = "graphql")),
+    ("js", cfg!(feature = "js")),
+    ("json-repair", cfg!(feature = "json-repair")),
+    ("lua", cfg!(feature = "lua")),
+    ("memory-tools", cfg!(feature = "memory-tools")),
+    ("openapi", cfg!(feature = "openapi")),
+    ("prost", cfg!(feature = "prost")),
+    ("python", cfg!(feature = "python")),
+    ("testing", cfg!(feature = "testing")),
+    ("usage-jsonl", cfg!(feature = "usage-jsonl")),
+    ("wasm-host", cfg!(feature = "wasm-host")),
+];
+
+pub(crate) fn features() -> Vec<&'static str> {
+    FEATURES
+        .into_iter()
+        .filter_map(|(name, on)| on.then_some(name))
+        .collect()
+}
+
+impl<M: Serialize> ToolCollection<M> {
+    /// Snapshot the collection's tools, metadata and cache state. Borrows
+    /// from the collection; serialize it before registering more tools.
+    pub fn introspect(&self) -> CollectionReport<'_, M> {
+        let tools: Vec<ToolReport<'_, M>> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(id, slot)| slot.as_ref().map(|e| (id, e)))
+            .map(|(id, e)| ToolReport {
+                id,
+                name: &e.decl.name,
+                description: &e.decl.description,
+                parameters: e.decl.parameters["properties"]
+                    .as_object()
+                    .map(|props| props.keys().map(String::as_str).collect())
+                    .unwrap_or_default(),
+                has_returns: e.decl.returns.is_some(),
+                external_schema: e.external_schema,
+                tags: &e.decl.tags,
+                meta: &e.meta,
+                origin: e.origin,
+                circuit: e.circuit.as_ref().map(|c| c.report()),
+                cache: e.cache.as_ref().map(|c| c.report()),
+                queue: e.queue.as_ref().map(|q| q.report()),
+            })
+            .collect();
+
+        CollectionReport {
+            format: REPORT_FORMAT,
+            version: env!("CARGO_PKG_VERSION"),
+            features: features(),
+            tool_count: tools.len(),
+            capacity: self.capacity(),
+            has_context: self.ctx.is_some(),
+            schema_cache_warm: self.is_warm(),
+            tools,
+            recent_calls: self.recent_calls(usize::MAX),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ToolSpec;
+    use serde_json::json;
+
+    #[test]
+    fn unregistered_slots_keep_their_ids_out() {
+        let mut tools: ToolCollection = ToolCollection::new();
+        for name in ["a", "b", "c"] {
+            tools
+                .register_spec(
+                    ToolSpec::new(name, "Test.").handler(|_| Box::pin(async { Ok(json!(null)) })),
+                )
+                .unwrap();
+        }
+        tools.unregister("b").unwrap();
+
+        let report = tools.introspect();
+        assert_eq!(report.tool_count, 2);
+        let ids: Vec<usize> = report.tools.iter().map(|t| t.id).collect();
+        assert_eq!(ids, [0, 2]);
+    }
+
+    #[test]
+    fn warm_state_is_reported() {
+        let tools: ToolCollection = ToolCollection::new();
+        assert!(!tools.introspect().schema_cache_warm);
+        tools.json().unwrap();
+        assert!(tools.introspect().schema_cache_warm);
+    }
+
+    #[test]
+    fn every_cargo_feature_is_listed() {
+        let manifest = include_str!("../Cargo.toml");
+        let section = manifest.split("[features]").nth(1).unwrap();
+        let mut declared: Vec<&str> = section
+            .lines()
+            .take_while(|line| !line.starts_with('['))
+            .filter_map(|line| line.split_once('='))
+            .map(|(name, _)| name.trim())
+            .filter(|name| *name != "default")
+            .collect();
+        declared.sort_unstable();
+        let listed: Vec<&str> = FEATURES.iter().map(|(name, _)| *name).collect();
+        assert_eq!(listed, declared);
+    }
+}
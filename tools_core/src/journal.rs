@@ -0,0 +1,247 @@
+//! Write-ahead journaling of side-effectful calls.
+//!
+//! A process that crashes after `send_email` ran but before the reply was
+//! persisted can't tell whether the email went out. For tools with a
+//! journal attached, [`ToolCollection::call`] durably records an
+//! [`Intent`] before running the tool and its [`JournalOutcome`] after.
+//! On startup, [`ToolCollection::recover`] lists the intents with no
+//! outcome so the application can reconcile them, e.g. by checking the
+//! mail server, and then close them with [`JournalOutcome::Reconciled`].
+//!
+//! ```ignore
+//! let journal = Arc::new(FileJournal::open("calls.journal")?);
+//! for pending in tools.recover(journal.as_ref())? {
+//!     reconcile(&pending).await?;
+//!     journal.complete(pending.seq, JournalOutcome::Reconciled)?;
+//! }
+//! tools.set_journal_where(journal, |m: &Policy| m.side_effects);
+//! ```
+//!
+//! If the intent can't be written the tool does not run and the call
+//! fails. If the outcome can't be written the call still returns its
+//! result; the intent is then reported by `recover`, which errs on the
+//! safe side. Calls rejected while decoding, calls answered from a
+//! result cache and background cache refreshes are not journaled.
+
+use std::{
+    collections::BTreeMap,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::Path,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{CallId, ErrorClass, ToolCollection, ToolError, hash};
+
+/// A call about to run, as written before the tool starts.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Intent {
+    /// Assigned by the journal; pairs the intent with its outcome.
+    pub seq: u64,
+    pub call_id: Option<String>,
+    pub tool: String,
+    /// [`hash::canonical_hash`] of the arguments.
+    pub args_hash: u64,
+    /// Process-wide dispatch number of the call; see [`sequence`]. `0` in
+    /// journals written before it was recorded.
+    ///
+    /// [`sequence`]: crate::sequence
+    #[serde(default)]
+    pub dispatch_seq: u64,
+}
+
+/// How a journaled call ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JournalOutcome {
+    Succeeded,
+    Failed {
+        class: ErrorClass,
+    },
+    /// Closed by the application after [`ToolCollection::recover`].
+    Reconciled,
+}
+
+/// Durable storage for intents and outcomes. Both writes must be durable
+/// when they return; they are made on the calling task.
+pub trait Journal: Send + Sync {
+    /// Record `intent` with a fresh sequence number, which is returned.
+    /// `intent.seq` is ignored.
+    fn begin(&self, intent: Intent) -> Result<u64, ToolError>;
+
+    fn complete(&self, seq: u64, outcome: JournalOutcome) -> Result<(), ToolError>;
+
+    /// Intents with no outcome, oldest first.
+    fn incomplete(&self) -> Result<Vec<Intent>, ToolError>;
+}
+
+// ============================================================================
+// FILE JOURNAL
+// ============================================================================
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Record {
+    Begin(Intent),
+    Complete { seq: u64, outcome: JournalOutcome },
+}
+
+struct FileState {
+    file: File,
+    next_seq: u64,
+    open: BTreeMap<u64, Intent>,
+}
+
+/// [`Journal`] appending JSON lines to one file, with an `fsync` after
+/// every record. A torn last line, left by a crash mid-write, is ignored
+/// on open.
+pub struct FileJournal {
+    state: Mutex<FileState>,
+}
+
+fn io_error(action: &str, e: impl std::fmt::Display) -> ToolError {
+    ToolError::Runtime(format!("journal {action} failed: {e}"))
+}
+
+impl FileJournal {
+    /// Open or create the journal at `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ToolError> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)
+            .map_err(|e| io_error("open", e))?;
+
+        let mut next_seq = 0;
+        let mut open = BTreeMap::new();
+        let mut torn = false;
+        for line in BufReader::new(&file).lines() {
+            let line = line.map_err(|e| io_error("read", e))?;
+            match serde_json::from_str(&line) {
+                Ok(Record::Begin(intent)) => {
+                    next_seq = next_seq.max(intent.seq + 1);
+                    open.insert(intent.seq, intent);
+                }
+                Ok(Record::Complete { seq, .. }) => {
+                    open.remove(&seq);
+                }
+                Err(_) => torn = true,
+            }
+        }
+        if torn {
+            // Start the next record on a fresh line.
+            file.write_all(b"\n").map_err(|e| io_error("write", e))?;
+        }
+        Ok(Self {
+            state: Mutex::new(FileState {
+                file,
+                next_seq,
+                open,
+            }),
+        })
+    }
+
+    fn append(state: &mut FileState, record: &Record) -> Result<(), ToolError> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        state
+            .file
+            .write_all(&line)
+            .and_then(|()| state.file.sync_data())
+            .map_err(|e| io_error("write", e))
+    }
+}
+
+impl Journal for FileJournal {
+    fn begin(&self, mut intent: Intent) -> Result<u64, ToolError> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        intent.seq = state.next_seq;
+        Self::append(&mut state, &Record::Begin(intent.clone()))?;
+        state.next_seq += 1;
+        state.open.insert(intent.seq, intent);
+        Ok(state.next_seq - 1)
+    }
+
+    fn complete(&self, seq: u64, outcome: JournalOutcome) -> Result<(), ToolError> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        Self::append(&mut state, &Record::Complete { seq, outcome })?;
+        state.open.remove(&seq);
+        Ok(())
+    }
+
+    fn incomplete(&self) -> Result<Vec<Intent>, ToolError> {
+        let state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        Ok(state.open.values().cloned().collect())
+    }
+}
+
+// ============================================================================
+// COLLECTION
+// ============================================================================
+
+/// The intent for a call of `tool`; the journal assigns `seq`.
+pub(crate) fn intent(
+    call_id: Option<&CallId>,
+    tool: &str,
+    arguments: &Value,
+    dispatch_seq: u64,
+) -> Intent {
+    Intent {
+        seq: 0,
+        call_id: call_id.map(CallId::to_string),
+        tool: tool.to_string(),
+        args_hash: hash::canonical_hash(arguments),
+        dispatch_seq,
+    }
+}
+
+/// Write the outcome of a call. A failed write is dropped: the intent stays
+/// open and [`ToolCollection::recover`] reports it.
+pub(crate) fn complete(journal: &dyn Journal, seq: u64, outcome: &Result<Value, ToolError>) {
+    let outcome = match outcome {
+        Ok(_) => JournalOutcome::Succeeded,
+        Err(e) => JournalOutcome::Failed {
+            class: e.classification(),
+        },
+    };
+    let _ = journal.complete(seq, outcome);
+}
+
+impl<M> ToolCollection<M> {
+    /// Journal every call of the tool `name`, replacing any existing
+    /// journal.
+    pub fn set_journal(&mut self, name: &str, journal: Arc<dyn Journal>) -> Result<(), ToolError> {
+        self.entry_mut(name)?.journal = Some(journal);
+        Ok(())
+    }
+
+    /// Attach `journal` to every tool whose metadata passes
+    /// `side_effectful`. Returns how many tools matched.
+    pub fn set_journal_where(
+        &mut self,
+        journal: Arc<dyn Journal>,
+        side_effectful: impl Fn(&M) -> bool,
+    ) -> usize {
+        self.entries
+            .iter_mut()
+            .flatten()
+            .filter(|e| side_effectful(&e.meta))
+            .map(|e| e.journal = Some(journal.clone()))
+            .count()
+    }
+
+    pub fn clear_journal(&mut self, name: &str) -> Result<(), ToolError> {
+        self.entry_mut(name)?.journal = None;
+        Ok(())
+    }
+
+    /// Calls in `journal` that started but never recorded an outcome,
+    /// oldest first. Call this on startup, before serving new calls.
+    pub fn recover(&self, journal: &dyn Journal) -> Result<Vec<Intent>, ToolError> {
+        journal.incomplete()
+    }
+}
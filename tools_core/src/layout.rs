@@ -0,0 +1,129 @@
+//! The two JSON layouts a [`FunctionDecl`] is stored in.
+//!
+//! - flat, as [`ToolCollection::json`][crate::ToolCollection::json] and
+//!   older releases write it: `{"name", "description", "parameters",
+//!   "returns"?, "tags"?}`;
+//! - OpenAI-nested: `{"type": "function", "function": {...}}` with the
+//!   same keys inside `function`.
+//!
+//! Both convert losslessly into each other. OpenAI has no result schema or
+//! tags; when present they travel inside `function` under the same keys.
+//!
+//! ```ignore
+//! let decl = FunctionDecl::from_any_layout(&stored)?;
+//! let request_tool = decl.to_openai();
+//! ```
+//!
+//! Parsing is strict about the keys a declaration needs: a missing or
+//! mistyped one fails with [`ToolError::Runtime`] naming it, e.g.
+//! ``declaration is missing `function.parameters` ``. Unknown keys, such
+//! as vendor extensions, are ignored.
+
+use serde_json::{Map, Value, json};
+
+use crate::{FunctionDecl, SchemaDialect, ToolError};
+
+fn invalid(message: String) -> ToolError {
+    ToolError::Runtime(format!("declaration {message}"))
+}
+
+/// The object `value` at `path`, or an error naming `path`.
+fn object<'a>(value: &'a Value, path: &str) -> Result<&'a Map<String, Value>, ToolError> {
+    value.as_object().ok_or_else(|| {
+        if path.is_empty() {
+            invalid("is not an object".into())
+        } else {
+            invalid(format!("`{path}` is not an object"))
+        }
+    })
+}
+
+/// Read the flat keys from `fields`; `prefix` is how errors spell their
+/// parent.
+fn from_fields<'a>(
+    fields: &'a Map<String, Value>,
+    prefix: &str,
+) -> Result<FunctionDecl<'a>, ToolError> {
+    let required = |key: &str| {
+        fields
+            .get(key)
+            .ok_or_else(|| invalid(format!("is missing `{prefix}{key}`")))
+    };
+    let string = |key: &str| {
+        required(key)?
+            .as_str()
+            .ok_or_else(|| invalid(format!("`{prefix}{key}` is not a string")))
+    };
+    let name = string("name")?;
+    let description = string("description")?;
+    let parameters = required("parameters")?.clone();
+    let returns = fields.get("returns").filter(|r| !r.is_null()).cloned();
+    let tags = match fields.get("tags") {
+        None | Some(Value::Null) => Vec::new(),
+        Some(tags) => tags
+            .as_array()
+            .and_then(|tags| {
+                tags.iter()
+                    .map(|t| t.as_str().map(String::from))
+                    .collect::<Option<Vec<_>>>()
+            })
+            .ok_or_else(|| invalid(format!("`{prefix}tags` is not an array of strings")))?,
+    };
+    let mut decl = FunctionDecl::new(name, description, parameters);
+    decl.dialect = SchemaDialect::from_schema(&decl.parameters).unwrap_or_default();
+    decl.returns = returns;
+    decl.tags = tags;
+    Ok(decl)
+}
+
+impl<'a> FunctionDecl<'a> {
+    /// Parse the flat layout, borrowing the strings from `value`.
+    pub fn from_flat(value: &'a Value) -> Result<Self, ToolError> {
+        from_fields(object(value, "")?, "")
+    }
+
+    /// Parse the OpenAI-nested layout, borrowing the strings from `value`.
+    pub fn from_openai(value: &'a Value) -> Result<Self, ToolError> {
+        let outer = object(value, "")?;
+        match outer.get("type") {
+            Some(Value::String(kind)) if kind == "function" => {}
+            Some(_) => return Err(invalid("`type` is not \"function\"".into())),
+            None => return Err(invalid("is missing `type`".into())),
+        }
+        let function = outer
+            .get("function")
+            .ok_or_else(|| invalid("is missing `function`".into()))?;
+        from_fields(object(function, "function")?, "function.")
+    }
+
+    /// Either layout: nested when `value` has a `function` key, else flat.
+    pub fn from_any_layout(value: &'a Value) -> Result<Self, ToolError> {
+        if value.get("function").is_some() {
+            Self::from_openai(value)
+        } else {
+            Self::from_flat(value)
+        }
+    }
+
+    /// The flat layout; `returns` and `tags` only when set.
+    pub fn to_flat(&self) -> Value {
+        let mut out = json!({
+            "name": self.name,
+            "description": self.description,
+            "parameters": self.parameters,
+        });
+        if let Some(returns) = &self.returns {
+            out["returns"] = returns.clone();
+        }
+        if !self.tags.is_empty() {
+            out["tags"] = json!(self.tags);
+        }
+        out
+    }
+
+    /// The OpenAI-nested layout, with [`to_flat`][Self::to_flat] as
+    /// `function`.
+    pub fn to_openai(&self) -> Value {
+        json!({ "type": "function", "function": self.to_flat() })
+    }
+}
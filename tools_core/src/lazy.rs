@@ -0,0 +1,119 @@
+//! Tools whose state is built on first use.
+//!
+//! A tool backed by a client that takes a network round trip to construct
+//! shouldn't pay for it at registration, nor build it again per call.
+//! [`register_lazy`][ToolCollection::register_lazy] runs `init` when the
+//! tool is first called and hands every call the same state:
+//!
+//! ```ignore
+//! tools.register_lazy(
+//!     "query",
+//!     "Runs a read-only SQL query.",
+//!     || async { Pool::connect(&url).await },
+//!     |pool: Arc<Pool>, sql: String| async move { pool.fetch_all(&sql).await },
+//!     (),
+//! )?;
+//! ```
+//!
+//! Concurrent first calls wait for a single `init`. If it fails, those
+//! calls fail with a [`Transient`][crate::ErrorClass::Transient] error and
+//! the next call runs `init` again; once it succeeds it never runs again.
+//! That is why `init` is `Fn` rather than `FnOnce`.
+
+use std::{borrow::Cow, fmt::Display, future::Future, sync::Arc};
+
+use futures::FutureExt;
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::Value;
+use tokio::sync::OnceCell;
+
+use crate::{
+    DecodedInput, DeserializationError, ErrorClass, MetaArg, ToolCollection, ToolError, ToolFunc,
+    ToolRef, ToolSchema, ToolSpec, decode_typed, domain, downcast_input, schema_value,
+};
+
+/// A tool's state and the function that builds it.
+struct LazyState<S, Init> {
+    cell: OnceCell<Arc<S>>,
+    init: Init,
+}
+
+impl<S, E, Init, InitFut> LazyState<S, Init>
+where
+    Init: Fn() -> InitFut,
+    InitFut: Future<Output = Result<S, E>>,
+    E: Display,
+{
+    /// The state, built by the first call to get here.
+    async fn get(&self, tool: &str) -> Result<Arc<S>, ToolError> {
+        self.cell
+            .get_or_try_init(|| async { (self.init)().await.map(Arc::new) })
+            .await
+            .cloned()
+            .map_err(|e| {
+                ToolError::classified(
+                    ErrorClass::Transient,
+                    format!("initializing tool `{tool}` failed: {e}"),
+                )
+            })
+    }
+}
+
+impl<M> ToolCollection<M> {
+    /// Like [`register`][Self::register] for a tool needing state that
+    /// `init` builds on the first call; see [`lazy`][crate::lazy].
+    pub fn register_lazy<A, S, E, I, O, Init, InitFut, F, Fut>(
+        &mut self,
+        name: impl Into<Cow<'static, str>>,
+        desc: impl Into<Cow<'static, str>>,
+        init: Init,
+        func: F,
+        meta: A,
+    ) -> Result<ToolRef<I, O>, ToolError>
+    where
+        A: MetaArg<M>,
+        S: Send + Sync + 'static,
+        E: Display,
+        I: 'static + DeserializeOwned + Serialize + Send + ToolSchema,
+        O: 'static + Serialize + Send + ToolSchema,
+        Init: Fn() -> InitFut + Send + Sync + 'static,
+        InitFut: Future<Output = Result<S, E>> + Send + 'static,
+        F: Fn(Arc<S>, I) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = O> + Send + 'static,
+    {
+        let name = name.into();
+        let tool: Arc<str> = name.as_ref().into();
+        let state = Arc::new(LazyState {
+            cell: OnceCell::new(),
+            init,
+        });
+        let func = Arc::new(func);
+        let boxed = ToolFunc::new(
+            |raw: Value| -> Result<DecodedInput, ToolError> {
+                let input: I = serde_json::from_value(raw).map_err(DeserializationError::from)?;
+                Ok(Box::new(input))
+            },
+            move |input, _ctx| {
+                let state = state.clone();
+                let func = func.clone();
+                let tool = tool.clone();
+                async move {
+                    let input: I = downcast_input(input)?;
+                    let handle = state.get(&tool).await?;
+                    let output: O = (func)(handle, input).await;
+                    domain::encode(output)
+                }
+                .boxed()
+            },
+        )
+        .with_decode_ref(decode_typed::<I>);
+
+        self.register_spec(domain::declare_returns::<O, M>(
+            ToolSpec::new(name.clone(), desc.into())
+                .params_schema(schema_value::<I>()?)
+                .func(boxed)
+                .with_meta(meta.into_meta()),
+        ))?;
+        Ok(ToolRef::named(name))
+    }
+}
@@ -1,33 +1,166 @@
 #![deny(unsafe_code)]
 
+pub mod batch;
+pub mod bound;
+#[cfg(feature = "broker")]
+pub mod broker;
 pub mod builder;
+pub mod cache;
+pub mod checks;
+pub mod choice;
+pub mod circuit;
+pub mod cleanup;
+pub mod compact;
+#[cfg(feature = "testing")]
+pub mod contract;
+pub mod conversation;
+pub mod convert;
+pub mod deadline;
+pub mod decl_overrides;
+#[cfg(feature = "dev-tools")]
+pub mod devtools;
+pub mod diagnostics;
+#[cfg(feature = "testing")]
+pub mod differential;
+pub mod domain;
+pub mod failures;
 pub mod ffi;
-
+pub mod fingerprint;
+pub mod hash;
+#[cfg(feature = "graphql")]
+pub mod graphql;
+#[cfg(any(feature = "openapi", feature = "graphql"))]
+pub mod http;
+pub mod introspect;
+pub mod journal;
+pub mod layout;
+pub mod lazy;
+pub mod lint;
+pub mod local;
+pub mod manifest;
+#[cfg(feature = "memory-tools")]
+pub mod memory;
+pub mod migrate;
+pub mod names;
+#[cfg(feature = "openapi")]
+pub mod openapi;
+pub mod overrides;
+pub mod page;
+pub mod panic;
+pub mod patch;
+pub mod profile;
+pub mod prompt;
+#[cfg(feature = "prost")]
+pub mod proto;
+pub mod provider;
+pub mod queued;
+#[cfg(feature = "json-repair")]
+pub mod repair;
+pub mod resources;
+pub mod schedule;
+pub mod schema;
+pub mod scoped;
+pub mod secrets;
+pub mod sequence;
+pub mod shared;
+pub mod shutdown;
+pub mod spec;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod tier;
+pub mod tool_ref;
+pub mod unknown;
+pub mod usage;
+pub mod validate;
+pub mod warmup;
+#[cfg(feature = "wasm-host")]
+pub mod wasm;
+pub mod wrap;
+
+pub use batch::{BatchMode, CallOutcome};
+pub use bound::BoundConflict;
 pub use builder::ToolsBuilder;
+pub use cache::{CachePolicy, CacheReport};
+pub use checks::{RegistrationFinding, Strictness};
+pub use choice::ToolChoice;
+pub use circuit::{CircuitConfig, CircuitReport, CircuitState};
+pub use decl_overrides::{DeclOverride, DeclOverrides, OverriddenView};
+pub use failures::{FailureEvent, FailureRecord, FailureSink};
 pub use ffi::{Language, RawToolDef};
+pub use fingerprint::{Fingerprint, FingerprintTool, Invocation, Invocations};
+pub use introspect::{CollectionReport, ToolReport};
+pub use journal::{FileJournal, Intent, Journal, JournalOutcome};
+pub use local::QueueReport;
+pub use migrate::{Migration, MigrationMode, MigrationSpec};
+pub use names::{NameNormalization, NameRegistry};
+pub use overrides::{OverrideReport, Overrides};
+pub use page::{DeclFilter, DeclarationPage};
+pub use profile::{AgentProfile, ProfileUsage, Profiled};
+pub use patch::Patch;
+pub use prompt::{PromptStyle, parse_freeform_call};
+pub use provider::Provider;
+pub use queued::{QueuedReport, QueuedTools};
+pub use schedule::{ScheduleConfig, ScheduleHandle, ScheduleReport};
+pub use schema::SchemaDialect;
+pub use scoped::ToolGuard;
+pub use unknown::UnknownArgs;
+pub use usage::{UsageEvent, UsageOutcome, UsageSink};
+pub use secrets::{EnvSecrets, Secret, SecretString, Secrets, SecretsProvider, StaticSecrets};
+pub use sequence::{CallSeq, RecentCall};
+pub use shared::SharedToolCollection;
+pub use shutdown::ShutdownReport;
+pub use spec::ToolSpec;
+pub use tier::{DeclTier, TierTracker};
+pub use tool_ref::ToolRef;
+pub use warmup::{WarmUpReport, warm_up_inventory, warm_up_inventory_async};
 
 use core::fmt;
 use std::{
     any::{Any, TypeId},
     borrow::Cow,
-    collections::HashMap,
-    sync::Arc,
+    collections::{BTreeMap, HashMap},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
 };
 
 use futures::{FutureExt, future::BoxFuture};
-use once_cell::sync::Lazy;
+use once_cell::sync::{Lazy, OnceCell};
 use serde::{Deserialize, Deserializer, Serialize, Serializer, de::DeserializeOwned};
-use serde_json::{self, Value, to_string_pretty};
+use serde_json::{Value, to_string_pretty};
+
+use crate::{
+    cache::{Lookup, ResultCache},
+    circuit::Circuit,
+    cleanup::{Cleanup, Pending},
+    failures::FailureLog,
+    local::CallQueue,
+    profile::ProfileState,
+    sequence::RecentCalls,
+    shutdown::Lifecycle,
+    usage::UsageLog,
+};
 
 // Re-export once_cell
 pub use once_cell;
 
+// Dependencies of `#[tool]` expansions, so user crates don't need them
+#[doc(hidden)]
+pub use {inventory, serde, serde_json};
+
 // ============================================================================
 // TOOL SCHEMA TRAIT AND IMPLEMENTATIONS
 // ============================================================================
 
 /// Trait for types that can generate a JSON Schema representation of themselves.
 pub trait ToolSchema {
+    /// Whether this is `Result<T, E>`, whose `Err` a tool returns as a
+    /// domain error; see [`domain`].
+    #[doc(hidden)]
+    const IS_RESULT: bool = false;
+
     fn schema() -> Value;
 }
 
@@ -44,21 +177,38 @@ macro_rules! prim {
 }
 
 prim!(bool, "boolean");
-prim!(i8, "integer");
-prim!(i16, "integer");
-prim!(i32, "integer");
-prim!(i64, "integer");
 prim!(i128, "integer");
-prim!(isize, "integer");
-prim!(u8, "integer");
-prim!(u16, "integer");
-prim!(u32, "integer");
-prim!(u64, "integer");
-prim!(u128, "integer");
-prim!(usize, "integer");
 prim!(f32, "number");
 prim!(f64, "number");
 
+// Integers that validation would otherwise let through out of range:
+// those up to 64 bits declare their bounds, wide unsigned ones start at
+// 0. `i128` stays a bare integer, which validation doesn't bound.
+macro_rules! bounded_int {
+    (narrow: $($ty:ty),+) => {$(
+        impl ToolSchema for $ty {
+            fn schema() -> Value {
+                static SCHEMA: Lazy<Value> = Lazy::new(|| {
+                    serde_json::json!({ "type": "integer", "minimum": <$ty>::MIN, "maximum": <$ty>::MAX })
+                });
+                SCHEMA.clone()
+            }
+        }
+    )+};
+    (unsigned: $($ty:ty),+) => {$(
+        impl ToolSchema for $ty {
+            fn schema() -> Value {
+                static SCHEMA: Lazy<Value> =
+                    Lazy::new(|| serde_json::json!({ "type": "integer", "minimum": 0 }));
+                SCHEMA.clone()
+            }
+        }
+    )+};
+}
+
+bounded_int!(narrow: i8, i16, i32, i64, isize, u8, u16, u32);
+bounded_int!(unsigned: u64, u128, usize);
+
 impl ToolSchema for &'_ str {
     fn schema() -> Value {
         static SCHEMA: Lazy<Value> = Lazy::new(|| serde_json::json!({ "type": "string" }));
@@ -122,6 +272,104 @@ impl<T: ToolSchema> ToolSchema for HashMap<String, T> {
     }
 }
 
+// Ranges use serde's `{start, end}` struct representation. The
+// descriptions spell out inclusivity since the shape alone cannot.
+impl<T: ToolSchema> ToolSchema for std::ops::Range<T> {
+    fn schema() -> Value {
+        serde_json::json!({
+            "type": "object",
+            "description": "Half-open range: includes `start`, excludes `end`.",
+            "properties": { "start": T::schema(), "end": T::schema() },
+            "required": ["start", "end"]
+        })
+    }
+}
+
+impl<T: ToolSchema> ToolSchema for std::ops::RangeInclusive<T> {
+    fn schema() -> Value {
+        serde_json::json!({
+            "type": "object",
+            "description": "Closed range: includes both `start` and `end`.",
+            "properties": { "start": T::schema(), "end": T::schema() },
+            "required": ["start", "end"]
+        })
+    }
+}
+
+impl<T: ToolSchema> ToolSchema for std::ops::RangeFrom<T> {
+    fn schema() -> Value {
+        serde_json::json!({
+            "type": "object",
+            "description": "Range with no upper bound: includes `start`.",
+            "properties": { "start": T::schema() },
+            "required": ["start"]
+        })
+    }
+}
+
+impl<T: ToolSchema> ToolSchema for std::ops::RangeTo<T> {
+    fn schema() -> Value {
+        serde_json::json!({
+            "type": "object",
+            "description": "Range with no lower bound: excludes `end`.",
+            "properties": { "end": T::schema() },
+            "required": ["end"]
+        })
+    }
+}
+
+impl<T: ToolSchema> ToolSchema for std::ops::Bound<T> {
+    fn schema() -> Value {
+        serde_json::json!({
+            "oneOf": [
+                { "type": "string", "enum": ["Unbounded"] },
+                {
+                    "type": "object",
+                    "description": "Bound that includes its value.",
+                    "properties": { "Included": T::schema() },
+                    "required": ["Included"],
+                    "additionalProperties": false
+                },
+                {
+                    "type": "object",
+                    "description": "Bound that excludes its value.",
+                    "properties": { "Excluded": T::schema() },
+                    "required": ["Excluded"],
+                    "additionalProperties": false
+                }
+            ]
+        })
+    }
+}
+
+// Non-zero integers: unsigned ones start at 1, signed ones exclude 0.
+macro_rules! nonzero {
+    (unsigned: $($ty:ty),+) => {$(
+        impl ToolSchema for $ty {
+            fn schema() -> Value {
+                static SCHEMA: Lazy<Value> =
+                    Lazy::new(|| serde_json::json!({ "type": "integer", "minimum": 1 }));
+                SCHEMA.clone()
+            }
+        }
+    )+};
+    (signed: $($ty:ty),+) => {$(
+        impl ToolSchema for $ty {
+            fn schema() -> Value {
+                static SCHEMA: Lazy<Value> = Lazy::new(|| {
+                    serde_json::json!({ "type": "integer", "not": { "const": 0 } })
+                });
+                SCHEMA.clone()
+            }
+        }
+    )+};
+}
+
+nonzero!(unsigned: std::num::NonZeroU8, std::num::NonZeroU16, std::num::NonZeroU32,
+    std::num::NonZeroU64, std::num::NonZeroU128, std::num::NonZeroUsize);
+nonzero!(signed: std::num::NonZeroI8, std::num::NonZeroI16, std::num::NonZeroI32,
+    std::num::NonZeroI64, std::num::NonZeroI128, std::num::NonZeroIsize);
+
 // Tuple implementations
 macro_rules! impl_tuples {
     ($($len:expr => ($($n:tt $name:ident)+))+) => {
@@ -186,12 +434,63 @@ pub enum ToolError {
     #[error("Tool function '{name}' not found")]
     FunctionNotFound { name: Cow<'static, str> },
 
-    #[error("Tool function '{name}' is already registered")]
-    AlreadyRegistered { name: &'static str },
+    /// `existing` and `attempted` are the descriptions of the registered
+    /// tool and of the one that collided with it.
+    #[error(
+        "Tool function '{name}' is already registered (existing: {existing:?}, attempted: {attempted:?})"
+    )]
+    AlreadyRegistered {
+        name: Cow<'static, str>,
+        existing: Cow<'static, str>,
+        attempted: Cow<'static, str>,
+    },
+
+    /// `#[tool]` functions from two crates, collected into one
+    /// collection, share a name.
+    #[error("Tool function '{name}' is defined in two crates: {first} and {second}")]
+    DuplicateTool {
+        name: Cow<'static, str>,
+        first: ToolOrigin,
+        second: ToolOrigin,
+    },
+
+    /// `name`, as a tool name or alias, would be indistinguishable from
+    /// `existing`, a name of the tool `tool`, under `normalization`. See
+    /// [`names`].
+    #[error(
+        "name `{name}` conflicts with `{existing}` (tool `{tool}`) under {normalization} matching"
+    )]
+    NameConflict {
+        name: String,
+        existing: String,
+        tool: Cow<'static, str>,
+        normalization: NameNormalization,
+    },
 
     #[error("Deserialization error: {0}")]
     Deserialize(#[from] DeserializationError),
 
+    /// The call had arguments the tool's schema doesn't declare, under
+    /// [`UnknownArgs::Reject`]. `summary` lists them with suggestions and
+    /// the accepted names. See [`unknown`].
+    #[error("tool `{tool}` got unknown arguments: {summary}")]
+    UnknownArguments {
+        tool: Cow<'static, str>,
+        unknown: Vec<String>,
+        accepted: Vec<String>,
+        summary: String,
+    },
+
+    /// The arguments don't match the tool's parameter schema, with
+    /// validation on. `errors` has one entry per problem, starting with
+    /// its JSON pointer. See [`validate`].
+    #[error("arguments for tool `{tool}` don't match its schema: {summary}")]
+    SchemaValidation {
+        tool: Cow<'static, str>,
+        errors: Vec<String>,
+        summary: String,
+    },
+
     #[error("JSON serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
 
@@ -200,7 +499,7 @@ pub enum ToolError {
 
     #[error("tool `{tool}` has attributes that do not match the metadata schema: {error}")]
     BadMeta {
-        tool: &'static str,
+        tool: Cow<'static, str>,
         error: String,
     },
 
@@ -210,15 +509,250 @@ pub enum ToolError {
         summary: String,
     },
 
+    /// Registration checks found problems with the tool's declaration
+    /// while strict registration was on. See [`checks`].
+    #[error("tool `{tool}` failed registration checks: {summary}")]
+    RegistrationRejected {
+        tool: String,
+        findings: Vec<RegistrationFinding>,
+        summary: String,
+    },
+
     #[error("tool `{tool}` requires context but none was provided")]
-    MissingCtx { tool: &'static str },
+    MissingCtx { tool: Cow<'static, str> },
+
+    #[error("tool `{tool}` has no handler")]
+    MissingHandler { tool: Cow<'static, str> },
 
     #[error("tool `{tool}` expects context type `{expected}` but collection has `{got}`")]
     CtxTypeMismatch {
-        tool: &'static str,
+        tool: Cow<'static, str>,
         expected: String,
         got: String,
     },
+
+    #[error(
+        "tool `{tool}` injects `{resource}`, but no single `#[tool_resource]` of that type is registered"
+    )]
+    MissingResource {
+        tool: Cow<'static, str>,
+        resource: &'static str,
+    },
+
+    /// The caller's deadline (see [`deadline`]) passed before or while
+    /// the tool ran.
+    #[error("tool `{tool}` did not finish before the caller's deadline")]
+    DeadlineExceeded { tool: Cow<'static, str> },
+
+    /// A timeout cut the running tool off after `elapsed`: the call's
+    /// own, the collection's default, the tool's, or a profile's. The
+    /// tool's future was dropped. See [`deadline`].
+    #[error("tool `{name}` timed out after {elapsed:?}")]
+    Timeout {
+        name: Cow<'static, str>,
+        elapsed: Duration,
+    },
+
+    #[error("secret `{key}` is not set")]
+    MissingSecret { key: String },
+
+    /// A remote service answered with an error payload (e.g. GraphQL
+    /// `errors`). `details` holds the payload verbatim.
+    #[error("remote error: {message}")]
+    Remote { message: String, details: Value },
+
+    /// A tool failure with an explicit [`ErrorClass`], so retry and
+    /// circuit-breaker logic can tell transient failures from permanent
+    /// ones. Build it with [`ToolError::classified`].
+    #[error("{class} error: {message}")]
+    RuntimeClassified { class: ErrorClass, message: String },
+
+    /// The tool's circuit breaker is open after repeated failures; the
+    /// call was not attempted. See [`circuit`].
+    #[error("tool `{name}` is failing; circuit open for another {retry_after:?}")]
+    CircuitOpen {
+        name: Cow<'static, str>,
+        retry_after: Duration,
+    },
+
+    /// The agent profile the call was made through has used up its
+    /// budget; the call was not attempted. See [`profile`].
+    #[error("profile `{profile}` has used its budget of {budget} calls")]
+    BudgetExhausted { profile: String, budget: u64 },
+
+    /// A domain error from a tool returning `Result`. Dispatch turns it
+    /// into a successful response with
+    /// [`is_error`][FunctionResponse::is_error] set; see [`domain`].
+    #[error("tool returned an error: {0}")]
+    Domain(Value),
+
+    /// A [`QueuedTools`] already holds `capacity` waiting calls; the call
+    /// was not queued. See [`queued`].
+    #[error("call queue is full ({capacity} calls waiting)")]
+    QueueFull { capacity: usize },
+
+    /// The [`QueuedTools`] was shut down before the call could be queued.
+    #[error("call queue is shut down")]
+    QueueClosed,
+
+    /// The collection is shutting down: the call arrived after shutdown
+    /// started, or was cancelled when the grace period ran out. See
+    /// [`shutdown`].
+    #[error("the tool collection is shutting down")]
+    ShuttingDown,
+
+    /// The tool panicked. `backtrace` is set when backtraces are enabled;
+    /// it is not part of the message. See [`panic`].
+    #[error("tool `{tool}` panicked: {message}")]
+    Panicked {
+        tool: Cow<'static, str>,
+        message: String,
+        backtrace: Option<String>,
+    },
+}
+
+/// Coarse category of a [`ToolError`], as returned by
+/// [`ToolError::classification`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorClass {
+    /// The arguments were malformed or rejected. Retrying the same call
+    /// fails the same way.
+    InvalidInput,
+    /// The tool (or something it looked up) does not exist.
+    NotFound,
+    /// A temporary failure such as a timeout or an unavailable upstream.
+    /// Worth retrying with backoff.
+    Transient,
+    /// A bug or misconfiguration on the serving side.
+    Internal,
+    /// The caller is not allowed to do this, or credentials are missing.
+    Denied,
+    /// A quota or rate limit is used up. Retry only once it resets.
+    Exhausted,
+}
+
+impl ErrorClass {
+    /// Only [`Transient`][Self::Transient] errors are retried blindly.
+    pub fn is_retryable(self) -> bool {
+        matches!(self, Self::Transient)
+    }
+}
+
+impl fmt::Display for ErrorClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::InvalidInput => "invalid input",
+            Self::NotFound => "not found",
+            Self::Transient => "transient",
+            Self::Internal => "internal",
+            Self::Denied => "denied",
+            Self::Exhausted => "exhausted",
+        })
+    }
+}
+
+impl ToolError {
+    /// Shorthand for [`ToolError::RuntimeClassified`].
+    pub fn classified(class: ErrorClass, message: impl Into<String>) -> Self {
+        Self::RuntimeClassified {
+            class,
+            message: message.into(),
+        }
+    }
+
+    /// Category of this error:
+    ///
+    /// | Variant | Class |
+    /// |---|---|
+    /// | `FunctionNotFound` | `NotFound` |
+    /// | `Deserialize`, `UnknownArguments`, `SchemaValidation`, `Domain` | `InvalidInput` |
+    /// | `MissingSecret` | `Denied` |
+    /// | `QueueFull`, `ShuttingDown`, `Timeout` | `Transient` |
+    /// | `CircuitOpen`, `DeadlineExceeded`, `BudgetExhausted` | `Exhausted` |
+    /// | `RuntimeClassified { class, .. }` | `class` |
+    /// | `Runtime`, `Remote`, `Panicked` | `Internal` |
+    /// | registration and context errors, `Serialization`, `QueueClosed` | `Internal` |
+    ///
+    /// Plain `Runtime` and `Remote` errors carry no hint of their cause and
+    /// are treated as permanent; return
+    /// [`RuntimeClassified`][Self::RuntimeClassified] to mark a failure
+    /// transient.
+    pub fn classification(&self) -> ErrorClass {
+        match self {
+            Self::FunctionNotFound { .. } => ErrorClass::NotFound,
+            Self::Deserialize(_)
+            | Self::UnknownArguments { .. }
+            | Self::SchemaValidation { .. }
+            | Self::Domain(_) => ErrorClass::InvalidInput,
+            Self::MissingSecret { .. } => ErrorClass::Denied,
+            Self::QueueFull { .. } | Self::ShuttingDown | Self::Timeout { .. } => {
+                ErrorClass::Transient
+            }
+            Self::CircuitOpen { .. }
+            | Self::DeadlineExceeded { .. }
+            | Self::BudgetExhausted { .. } => ErrorClass::Exhausted,
+            Self::RuntimeClassified { class, .. } => *class,
+            Self::Runtime(_) | Self::Remote { .. } | Self::Panicked { .. } => ErrorClass::Internal,
+            Self::AlreadyRegistered { .. }
+            | Self::DuplicateTool { .. }
+            | Self::NameConflict { .. }
+            | Self::Serialization(_)
+            | Self::BadMeta { .. }
+            | Self::MetaValidation { .. }
+            | Self::RegistrationRejected { .. }
+            | Self::MissingCtx { .. }
+            | Self::MissingHandler { .. }
+            | Self::MissingResource { .. }
+            | Self::CtxTypeMismatch { .. }
+            | Self::QueueClosed => ErrorClass::Internal,
+        }
+    }
+
+    /// Whether retrying the same call may succeed. See
+    /// [`ErrorClass::is_retryable`].
+    pub fn is_retryable(&self) -> bool {
+        self.classification().is_retryable()
+    }
+
+    /// The error as shown to the model. Backtraces are left out.
+    pub fn report(&self) -> ErrorReport {
+        ErrorReport {
+            class: self.classification(),
+            retryable: self.is_retryable(),
+            message: self.to_string(),
+            backtrace: None,
+            explanation: None,
+        }
+    }
+
+    /// [`report`][Self::report] including the backtrace of a panic, for
+    /// trusted consumers such as operators and internal logs.
+    pub fn report_trusted(&self) -> ErrorReport {
+        let backtrace = match self {
+            Self::Panicked { backtrace, .. } => backtrace.clone(),
+            _ => None,
+        };
+        ErrorReport {
+            backtrace,
+            ..self.report()
+        }
+    }
+}
+
+/// A [`ToolError`] in serializable form, from [`ToolError::report`] or
+/// [`ToolError::report_trusted`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorReport {
+    pub class: ErrorClass,
+    pub retryable: bool,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backtrace: Option<String>,
+    /// What was wrong with the arguments, from
+    /// [`ToolCollection::report_failure`]; see [`diagnostics`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<Value>,
 }
 
 /// Specific deserialization errors
@@ -235,16 +769,81 @@ impl From<serde_json::Error> for DeserializationError {
     }
 }
 
+/// A plain message is a [`ToolError::Runtime`], so a fallible tool can
+/// fail with `Err("...".into())`; see
+/// [`register_fallible`][ToolCollection::register_fallible].
+impl From<String> for ToolError {
+    fn from(message: String) -> Self {
+        Self::Runtime(message)
+    }
+}
+
+impl From<&str> for ToolError {
+    fn from(message: &str) -> Self {
+        Self::Runtime(message.to_string())
+    }
+}
+
+/// Return early with a [`ToolError::Runtime`] built from a `format!`
+/// message. Works in any function whose error type is `From<ToolError>`:
+///
+/// ```ignore
+/// #[tool]
+/// async fn factorial(n: u64) -> Result<u64, ToolError> {
+///     match (1..=n).try_fold(1u64, u64::checked_mul) {
+///         Some(f) => Ok(f),
+///         None => tool_error!("factorial overflows u64 for n={n}"),
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! tool_error {
+    ($($arg:tt)+) => {
+        return ::core::result::Result::Err(::core::convert::From::from(
+            $crate::ToolError::Runtime(::std::format!($($arg)+)),
+        ))
+    };
+}
+
+/// Return early with a [`ToolError::Runtime`] unless `cond` holds. The
+/// message is a `format!` string, or names the condition when left out.
+///
+/// ```ignore
+/// ensure_tool!(n <= 20, "factorial overflows u64 for n={n}");
+/// ```
+#[macro_export]
+macro_rules! ensure_tool {
+    ($cond:expr $(,)?) => {
+        if !$cond {
+            $crate::tool_error!("condition failed: `{}`", ::core::stringify!($cond));
+        }
+    };
+    ($cond:expr, $($arg:tt)+) => {
+        if !$cond {
+            $crate::tool_error!($($arg)+);
+        }
+    };
+}
+
 // ============================================================================
 // CORE MODELS
 // ============================================================================
 
 /// Represents a function call with name and arguments
+///
+/// `arguments` deserializes from JSON or, as OpenAI sends it, from a string
+/// holding JSON. A string argument serializes JSON-encoded, so it reads
+/// back unchanged.
 #[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
 pub struct FunctionCall {
     pub id: Option<CallId>,
     pub name: String,
+    #[serde(with = "encoded_arguments")]
     pub arguments: Value,
+    /// [`parse_freeform_call`] had to repair the model output to read it
+    /// as JSON, with the `json-repair` feature.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub repaired: bool,
 }
 
 impl FunctionCall {
@@ -253,6 +852,69 @@ impl FunctionCall {
             id: Some(CallId::new()),
             name,
             arguments,
+            repaired: false,
+        }
+    }
+
+    /// Build a call from one entry of an OpenAI `tool_calls` array,
+    /// `{"id", "type": "function", "function": {"name", "arguments"}}`,
+    /// keeping the provider's `id` so the response can answer it. The
+    /// flat `{"call_id" or "id", "name", "arguments"}` item of the
+    /// Responses API is accepted too. Arguments that aren't valid JSON
+    /// fail with [`ToolError::Deserialize`] quoting them.
+    pub fn from_openai(tool_call: &Value) -> Result<FunctionCall, ToolError> {
+        let function = tool_call.get("function").unwrap_or(tool_call);
+        let invalid = |message: String| {
+            ToolError::Deserialize(<serde_json::Error as serde::de::Error>::custom(message).into())
+        };
+        let name = function["name"]
+            .as_str()
+            .ok_or_else(|| invalid("tool call has no `name`".into()))?;
+        let arguments = match &function["arguments"] {
+            Value::Null => Value::Object(Default::default()),
+            Value::String(encoded) => encoded_arguments::parse(encoded).map_err(invalid)?,
+            arguments => arguments.clone(),
+        };
+        let id = tool_call
+            .get("call_id")
+            .or_else(|| tool_call.get("id"))
+            .and_then(Value::as_str)
+            .map(|id| CallId::from(id.to_string()));
+        Ok(FunctionCall {
+            id,
+            name: name.to_string(),
+            arguments,
+            repaired: false,
+        })
+    }
+}
+
+/// `arguments` as JSON or as a string holding JSON; see [`FunctionCall`].
+mod encoded_arguments {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error as _};
+    use serde_json::Value;
+
+    pub(crate) fn parse(encoded: &str) -> Result<Value, String> {
+        serde_json::from_str(encoded)
+            .map_err(|e| format!("tool call arguments are not valid JSON ({e}): {encoded:?}"))
+    }
+
+    pub(super) fn serialize<S: Serializer>(
+        arguments: &Value,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match arguments {
+            Value::String(_) => arguments.to_string().serialize(serializer),
+            _ => arguments.serialize(serializer),
+        }
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Value, D::Error> {
+        match Value::deserialize(deserializer)? {
+            Value::String(encoded) => parse(&encoded).map_err(D::Error::custom),
+            arguments => Ok(arguments),
         }
     }
 }
@@ -277,9 +939,16 @@ impl<'de> Deserialize<'de> for CallId {
     where
         D: Deserializer<'de>,
     {
+        // UUIDs are normalized; other ids, such as OpenAI's `call_...`,
+        // are kept as the provider sent them.
         let s = String::deserialize(deserializer)?;
-        let uuid = uuid::Uuid::parse_str(&s).map_err(serde::de::Error::custom)?;
-        Ok(CallId(uuid.to_string()))
+        if s.is_empty() {
+            return Err(serde::de::Error::custom("call id is empty"));
+        }
+        match uuid::Uuid::parse_str(&s) {
+            Ok(uuid) => Ok(CallId(uuid.to_string())),
+            Err(_) => Ok(CallId(s)),
+        }
     }
 }
 
@@ -316,6 +985,28 @@ pub struct FunctionResponse {
     pub id: Option<CallId>,
     pub name: String,
     pub result: Value,
+    /// Served from the tool's result cache past its TTL; see [`cache`].
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub stale: bool,
+    /// Dispatch and completion order of the call; see [`sequence`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seq: Option<CallSeq>,
+    /// Problems with the call that didn't stop it, such as ignored
+    /// arguments; see [`unknown`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    /// Migrations that rewrote the arguments, in the order they ran; see
+    /// [`migrate`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub migrations: Vec<String>,
+    /// `result` is a domain error in an `{"error": ...}` envelope; see
+    /// [`domain`].
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_error: bool,
+    /// The call's arguments were repaired before dispatch; carried over
+    /// from [`FunctionCall::repaired`].
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub repaired: bool,
 }
 
 impl fmt::Display for FunctionResponse {
@@ -339,12 +1030,107 @@ impl fmt::Display for FunctionResponse {
     }
 }
 
-/// Function signature for tools
-pub type ToolFunc = dyn Fn(Value, Option<Arc<dyn Any + Send + Sync>>)
-    -> BoxFuture<'static, Result<Value, ToolError>>
+/// Tool arguments that passed the decode stage, type-erased. The matching
+/// run stage downcasts it back to the concrete input type.
+pub type DecodedInput = Box<dyn Any + Send>;
+
+/// First stage of a tool: deserialize JSON arguments. Runs synchronously,
+/// so invalid arguments fail before any future is allocated.
+pub type DecodeFn = dyn Fn(Value) -> Result<DecodedInput, ToolError> + Send + Sync;
+
+/// [`DecodeFn`] reading borrowed arguments, for
+/// [`ToolCollection::call_ref`]. Typed tools deserialize straight from the
+/// `&Value` without cloning it.
+pub type DecodeRefFn = dyn Fn(&Value) -> Result<DecodedInput, ToolError> + Send + Sync;
+
+/// Second stage of a tool: execute with decoded arguments and the shared
+/// context.
+pub type RunFn = dyn Fn(
+        DecodedInput,
+        Option<Arc<dyn Any + Send + Sync>>,
+    ) -> BoxFuture<'static, Result<Value, ToolError>>
     + Send
     + Sync;
 
+/// Callable tool, split into a synchronous [`decode`][Self::decode] stage
+/// and an async [`run`][Self::run] stage.
+#[derive(Clone)]
+pub struct ToolFunc {
+    decode: Arc<DecodeFn>,
+    decode_ref: Option<Arc<DecodeRefFn>>,
+    run: Arc<RunFn>,
+}
+
+impl ToolFunc {
+    pub fn new(
+        decode: impl Fn(Value) -> Result<DecodedInput, ToolError> + Send + Sync + 'static,
+        run: impl Fn(
+            DecodedInput,
+            Option<Arc<dyn Any + Send + Sync>>,
+        ) -> BoxFuture<'static, Result<Value, ToolError>>
+        + Send
+        + Sync
+        + 'static,
+    ) -> Self {
+        Self {
+            decode: Arc::new(decode),
+            decode_ref: None,
+            run: Arc::new(run),
+        }
+    }
+
+    /// Add a decode stage for borrowed arguments. It must accept and reject
+    /// the same arguments as the owned one.
+    pub fn with_decode_ref(
+        mut self,
+        decode_ref: impl Fn(&Value) -> Result<DecodedInput, ToolError> + Send + Sync + 'static,
+    ) -> Self {
+        self.decode_ref = Some(Arc::new(decode_ref));
+        self
+    }
+
+    pub fn decode(&self, arguments: Value) -> Result<DecodedInput, ToolError> {
+        (self.decode)(arguments)
+    }
+
+    /// Decode borrowed arguments, cloning them only if the tool has no
+    /// borrowed decode stage.
+    pub fn decode_ref(&self, arguments: &Value) -> Result<DecodedInput, ToolError> {
+        match &self.decode_ref {
+            Some(decode_ref) => decode_ref(arguments),
+            None => (self.decode)(arguments.clone()),
+        }
+    }
+
+    pub fn run(
+        &self,
+        input: DecodedInput,
+        ctx: Option<Arc<dyn Any + Send + Sync>>,
+    ) -> BoxFuture<'static, Result<Value, ToolError>> {
+        (self.run)(input, ctx)
+    }
+}
+
+/// Borrowed decode stage for a typed input. Used by `register` and
+/// `#[tool]`-generated registrations.
+#[doc(hidden)]
+pub fn decode_typed<I: DeserializeOwned + Send + 'static>(
+    arguments: &Value,
+) -> Result<DecodedInput, ToolError> {
+    let input = I::deserialize(arguments).map_err(DeserializationError::from)?;
+    Ok(Box::new(input))
+}
+
+/// Downcast a [`DecodedInput`] produced by the same tool's decode stage.
+/// Used by `register` and `#[tool]`-generated run stages.
+#[doc(hidden)]
+pub fn downcast_input<T: 'static>(input: DecodedInput) -> Result<T, ToolError> {
+    input
+        .downcast::<T>()
+        .map(|b| *b)
+        .map_err(|_| ToolError::Runtime("decoded input has unexpected type".to_string()))
+}
+
 /// Metadata about a tool function
 #[derive(Debug, Clone)]
 pub struct ToolMetadata {
@@ -390,11 +1176,19 @@ impl MetaArg<NoMeta> for () {
 pub struct ToolRegistration {
     pub name: &'static str,
     pub doc: &'static str,
-    pub f: fn(
-        Value,
+    /// Deserialize JSON arguments into the tool's input struct.
+    pub decode: fn(Value) -> Result<DecodedInput, ToolError>,
+    /// Like `decode`, from borrowed arguments.
+    pub decode_ref: fn(&Value) -> Result<DecodedInput, ToolError>,
+    /// Run the tool on the output of `decode`.
+    #[allow(clippy::type_complexity)]
+    pub run: fn(
+        DecodedInput,
         Option<Arc<dyn Any + Send + Sync>>,
     ) -> BoxFuture<'static, Result<Value, ToolError>>,
     pub param_schema: fn() -> Value,
+    /// Schema of the result, for tools returning `Result`; see [`domain`].
+    pub returns_schema: Option<fn() -> Value>,
     /// JSON object literal of the attributes declared in `#[tool(...)]`.
     /// `"{}"` when no attributes were given. Deserialized into the
     /// collection's `M` at [`ToolCollection::collect_tools`] time.
@@ -407,6 +1201,39 @@ pub struct ToolRegistration {
     /// Human-readable name of the expected context type, for error
     /// messages. Empty string when `needs_ctx` is `false`.
     pub ctx_type_name: &'static str,
+    /// `module_path!()` at the `#[tool]` function.
+    pub module_path: &'static str,
+}
+
+impl ToolRegistration {
+    pub fn origin(&self) -> ToolOrigin {
+        ToolOrigin::from_module_path(self.module_path)
+    }
+}
+
+/// Where a `#[tool]` function is defined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ToolOrigin {
+    /// Name of the defining crate, as it appears in paths (`my_tools`, not
+    /// `my-tools`).
+    pub crate_name: &'static str,
+    pub module_path: &'static str,
+}
+
+impl ToolOrigin {
+    /// The origin of an item at `module_path`, e.g. `my_tools::net`.
+    pub fn from_module_path(module_path: &'static str) -> Self {
+        Self {
+            crate_name: module_path.split("::").next().unwrap_or(module_path),
+            module_path,
+        }
+    }
+}
+
+impl fmt::Display for ToolOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` in crate `{}`", self.module_path, self.crate_name)
+    }
 }
 
 /// Per-tool attribute validation error. Reported by
@@ -440,18 +1267,34 @@ pub struct Tool {
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, serde::Deserialize)]
 pub struct FunctionDecl<'a> {
     #[serde(borrow)]
-    pub name: &'a str,
+    pub name: Cow<'a, str>,
     #[serde(borrow)]
-    pub description: &'a str,
+    pub description: Cow<'a, str>,
     pub parameters: Value,
+    /// Result schema, when the tool was registered with one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub returns: Option<Value>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Dialect of `parameters` and `returns`; see [`schema::dialect`].
+    /// Not serialized: providers only see the schemas.
+    #[serde(skip)]
+    pub dialect: SchemaDialect,
 }
 
 impl<'a> FunctionDecl<'a> {
-    pub fn new(name: &'a str, description: &'a str, parameters: Value) -> Self {
+    pub fn new(
+        name: impl Into<Cow<'a, str>>,
+        description: impl Into<Cow<'a, str>>,
+        parameters: Value,
+    ) -> Self {
         Self {
-            name,
-            description,
+            name: name.into(),
+            description: description.into(),
             parameters,
+            returns: None,
+            tags: Vec::new(),
+            dialect: SchemaDialect::default(),
         }
     }
 }
@@ -464,12 +1307,85 @@ fn schema_value<T: ToolSchema>() -> Result<Value, ToolError> {
     Ok(T::schema())
 }
 
+/// Run a decoded call through the tool's breaker, if any, and the current
+/// deadline. A call that starts too late never reaches the breaker.
+async fn invoke(
+    tool: &str,
+    func: &ToolFunc,
+    circuit: Option<&Circuit>,
+    input: DecodedInput,
+    ctx: Option<Arc<dyn Any + Send + Sync>>,
+) -> Result<Value, ToolError> {
+    deadline::check(tool)?;
+    let run = || panic::catch(tool, func.run(input, ctx));
+    let Some(circuit) = circuit else {
+        return deadline::enforce(tool, run).await;
+    };
+    circuit.acquire(tool)?;
+    let outcome = deadline::enforce(tool, run).await;
+    circuit.record(&outcome);
+    outcome
+}
+
+/// Run a call under `timeout`, with the collection's `secrets` in scope,
+/// until it finishes or the collection's shutdown cancels it.
+async fn supervise<T>(
+    lifecycle: &Lifecycle,
+    secrets: Option<&Secrets>,
+    timeout: Option<Duration>,
+    run: impl Future<Output = Result<T, ToolError>>,
+) -> Result<T, ToolError> {
+    let run = async {
+        match timeout {
+            Some(timeout) => deadline::with_timeout(timeout, run).await,
+            None => run.await,
+        }
+    };
+    let run = async {
+        tokio::select! {
+            result = run => result,
+            () = lifecycle.cancelled() => Err(ToolError::ShuttingDown),
+        }
+    };
+    secrets::scope(secrets, run).await
+}
+
 /// One entry in a [`ToolCollection`]: callable function, schema, and the
 /// metadata typed against the collection's `M` parameter.
 pub struct ToolEntry<M> {
-    pub func: Arc<ToolFunc>,
+    pub func: ToolFunc,
     pub decl: FunctionDecl<'static>,
     pub meta: M,
+    /// Shared with clones of the entry; see
+    /// [`ToolCollection::set_circuit_breaker`].
+    pub circuit: Option<Arc<Circuit>>,
+    /// Runs once when the tool is removed; see [`cleanup`].
+    pub cleanup: Option<Arc<Cleanup>>,
+    /// Shared with clones of the entry; see
+    /// [`ToolCollection::set_cache_policy`].
+    pub cache: Option<Arc<ResultCache>>,
+    /// Records each call before and after it runs; see [`journal`].
+    pub journal: Option<Arc<dyn Journal>>,
+    /// Where the tool was defined, for `#[tool]` functions.
+    pub origin: Option<ToolOrigin>,
+    /// Wait-time counters, for tools registered with
+    /// [`ToolCollection::register_local`].
+    pub queue: Option<Arc<CallQueue>>,
+    /// Rewrites of older argument shapes; see [`migrate`].
+    pub migrations: Vec<Arc<Migration>>,
+    /// The parameter a bare input is wrapped under; see [`wrap`].
+    pub wrapped: Option<&'static str>,
+    /// Results are returned uncompacted; see [`compact`].
+    pub compaction_exempt: bool,
+    /// Left out of declarations; see
+    /// [`ToolCollection::set_hidden`].
+    pub hidden: bool,
+    /// The parameter schema was given with the tool rather than derived
+    /// from an input type; see [`ToolSpec::handler`].
+    pub external_schema: bool,
+    /// Replaces the collection's default timeout; see
+    /// [`ToolCollection::set_timeout`].
+    pub timeout: Option<Duration>,
 }
 
 impl<M: Clone> Clone for ToolEntry<M> {
@@ -478,6 +1394,18 @@ impl<M: Clone> Clone for ToolEntry<M> {
             func: self.func.clone(),
             decl: self.decl.clone(),
             meta: self.meta.clone(),
+            circuit: self.circuit.clone(),
+            cleanup: self.cleanup.clone(),
+            cache: self.cache.clone(),
+            journal: self.journal.clone(),
+            origin: self.origin,
+            queue: self.queue.clone(),
+            migrations: self.migrations.clone(),
+            wrapped: self.wrapped,
+            compaction_exempt: self.compaction_exempt,
+            hidden: self.hidden,
+            external_schema: self.external_schema,
+            timeout: self.timeout,
         }
     }
 }
@@ -493,15 +1421,110 @@ impl<M: Clone> Clone for ToolEntry<M> {
 /// if tools.meta("delete_file").unwrap().requires_approval { ... }
 /// ```
 pub struct ToolCollection<M = NoMeta> {
-    entries: HashMap<&'static str, ToolEntry<M>>,
+    /// Names and aliases → slot in `entries`. Resolved once per call;
+    /// everything after that indexes by [`ToolId`]. See [`names`].
+    names: NameRegistry,
+    /// Indexed by [`ToolId`]. Unregistered slots are left `None` so ids
+    /// handed out earlier never point at a different tool.
+    entries: Vec<Option<ToolEntry<M>>>,
     ctx: Option<Arc<dyn Any + Send + Sync>>,
+    /// Serialized output of [`json`][Self::json], filled on first use or by
+    /// [`warm_up`][Self::warm_up]. Cleared whenever the entries change.
+    json_cache: OnceCell<Value>,
+    /// Cleanups of removed tools, awaited by [`shutdown`][Self::shutdown].
+    pending_cleanups: Mutex<Vec<Pending>>,
+    /// Shared with clones, budgets included; see [`profile`].
+    profiles: BTreeMap<String, Arc<ProfileState>>,
+    /// Whether registration findings are errors; see [`checks`].
+    strict_registration: bool,
+    /// See [`set_registration_strictness`][Self::set_registration_strictness].
+    registration_strictness: Strictness,
+    registration_warnings: Vec<RegistrationFinding>,
+    /// Shared with clones; see [`recent_calls`][Self::recent_calls].
+    recent: Arc<RecentCalls>,
+    /// Shared with clones; see [`shutdown`].
+    lifecycle: Arc<Lifecycle>,
+    /// See [`unknown`].
+    unknown_args: UnknownArgs,
+    /// See [`migrate`].
+    migration_mode: MigrationMode,
+    /// See [`wrap`].
+    wrap_bare_inputs: bool,
+    /// See [`compact`].
+    compact_results: bool,
+    /// See [`validate`].
+    validation: bool,
+    /// See [`schema::dialect`].
+    schema_uris: bool,
+    /// See [`hash`].
+    content_hashes: bool,
+    /// See [`set_declare_hidden`][Self::set_declare_hidden].
+    declare_hidden: bool,
+    /// Shared with clones; see [`failures`].
+    failures: Option<Arc<FailureLog>>,
+    /// Shared with clones; see [`usage`].
+    usage: Option<Arc<UsageLog>>,
+    /// See [`set_default_timeout`][Self::set_default_timeout].
+    default_timeout: Option<Duration>,
+    /// See [`set_namespace`][Self::set_namespace].
+    namespace: Option<String>,
+    /// See [`set_secrets`][Self::set_secrets].
+    secrets: Option<Secrets>,
+    /// Shared with clones; see [`contract`].
+    #[cfg(feature = "testing")]
+    contracts: Option<Arc<contract::ContractRecorder>>,
+}
+
+#[cfg(feature = "fast-hash")]
+pub(crate) type NameMap = HashMap<Cow<'static, str>, ToolId, rustc_hash::FxBuildHasher>;
+#[cfg(not(feature = "fast-hash"))]
+pub(crate) type NameMap = HashMap<Cow<'static, str>, ToolId>;
+
+/// Compact handle to a tool in one [`ToolCollection`], obtained from
+/// [`ToolCollection::resolve`]. Resolve a name once, then call by id to
+/// skip the name lookup on every dispatch.
+///
+/// Ids are only meaningful for the collection that issued them. After
+/// [`unregister`][ToolCollection::unregister] the old id stays dead; a
+/// re-registered tool of the same name gets a fresh id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ToolId(u32);
+
+impl ToolId {
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
 }
 
 impl<M> Default for ToolCollection<M> {
     fn default() -> Self {
         Self {
-            entries: HashMap::new(),
+            names: NameRegistry::default(),
+            entries: Vec::new(),
             ctx: None,
+            json_cache: OnceCell::new(),
+            pending_cleanups: Mutex::new(Vec::new()),
+            profiles: BTreeMap::new(),
+            strict_registration: false,
+            registration_strictness: Strictness::default(),
+            registration_warnings: Vec::new(),
+            recent: Arc::default(),
+            lifecycle: Arc::default(),
+            unknown_args: UnknownArgs::default(),
+            migration_mode: MigrationMode::default(),
+            wrap_bare_inputs: false,
+            compact_results: false,
+            validation: false,
+            schema_uris: false,
+            content_hashes: false,
+            declare_hidden: false,
+            failures: None,
+            usage: None,
+            default_timeout: None,
+            namespace: None,
+            secrets: None,
+            #[cfg(feature = "testing")]
+            contracts: None,
         }
     }
 }
@@ -509,8 +1532,34 @@ impl<M> Default for ToolCollection<M> {
 impl<M: Clone> Clone for ToolCollection<M> {
     fn clone(&self) -> Self {
         Self {
+            names: self.names.clone(),
             entries: self.entries.clone(),
             ctx: self.ctx.clone(),
+            json_cache: self.json_cache.clone(),
+            // Pending cleanups belong to the collection that removed the
+            // tools.
+            pending_cleanups: Mutex::new(Vec::new()),
+            profiles: self.profiles.clone(),
+            strict_registration: self.strict_registration,
+            registration_strictness: self.registration_strictness,
+            registration_warnings: self.registration_warnings.clone(),
+            recent: self.recent.clone(),
+            lifecycle: self.lifecycle.clone(),
+            unknown_args: self.unknown_args,
+            migration_mode: self.migration_mode,
+            wrap_bare_inputs: self.wrap_bare_inputs,
+            compact_results: self.compact_results,
+            validation: self.validation,
+            schema_uris: self.schema_uris,
+            content_hashes: self.content_hashes,
+            declare_hidden: self.declare_hidden,
+            failures: self.failures.clone(),
+            usage: self.usage.clone(),
+            default_timeout: self.default_timeout,
+            namespace: self.namespace.clone(),
+            secrets: self.secrets.clone(),
+            #[cfg(feature = "testing")]
+            contracts: self.contracts.clone(),
         }
     }
 }
@@ -520,6 +1569,31 @@ impl<M> ToolCollection<M> {
         Self::default()
     }
 
+    /// Empty collection with room for `capacity` tools before any
+    /// reallocation. Useful when registering thousands of generated tools.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            names: NameRegistry::with_capacity(capacity),
+            entries: Vec::with_capacity(capacity),
+            ..Self::default()
+        }
+    }
+
+    /// Number of tools the collection can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.names.capacity().min(self.entries.capacity())
+    }
+
+    /// Release spare capacity once registration is finished. Trailing
+    /// unregistered slots are dropped; ids of live tools are unchanged.
+    pub fn shrink_to_fit(&mut self) {
+        while matches!(self.entries.last(), Some(None)) {
+            self.entries.pop();
+        }
+        self.entries.shrink_to_fit();
+        self.names.shrink_to_fit();
+    }
+
     /// Create a [`CollectionBuilder`] for constructing a collection with
     /// shared context and/or custom configuration.
     pub fn builder() -> CollectionBuilder<M> {
@@ -527,6 +1601,13 @@ impl<M> ToolCollection<M> {
             ctx: None,
             ctx_type_id: None,
             ctx_type_name: "",
+            strict_registration: false,
+            registration_strictness: Strictness::default(),
+            unknown_args: UnknownArgs::default(),
+            wrap_bare_inputs: false,
+            compact_results: false,
+            validation: false,
+            default_timeout: None,
             _meta: std::marker::PhantomData,
         }
     }
@@ -543,42 +1624,35 @@ impl<M> ToolCollection<M> {
     /// typed collections.
     pub fn register_raw<A: MetaArg<M>>(
         &mut self,
-        name: &'static str,
-        description: &'static str,
+        name: impl Into<Cow<'static, str>>,
+        description: impl Into<Cow<'static, str>>,
         parameters: Value,
         func: impl Fn(Value) -> BoxFuture<'static, Result<Value, ToolError>> + Send + Sync + 'static,
         meta: A,
     ) -> Result<&mut Self, ToolError> {
-        if self.entries.contains_key(name) {
-            return Err(ToolError::AlreadyRegistered { name });
-        }
-
-        let boxed: Arc<ToolFunc> = Arc::new(
-            move |raw: Value, _ctx: Option<Arc<dyn Any + Send + Sync>>| func(raw),
-        );
-
-        self.entries.insert(
-            name,
-            ToolEntry {
-                func: boxed,
-                decl: FunctionDecl::new(name, description, parameters),
-                meta: meta.into_meta(),
-            },
-        );
-
+        self.register_spec(
+            ToolSpec::new(name, description)
+                .params_schema(parameters)
+                .handler(func)
+                .with_meta(meta.into_meta()),
+        )?;
         Ok(self)
     }
 
     /// Register a tool programmatically. Pass `()` as `meta` for
     /// `ToolCollection<NoMeta>`; pass an `M` for typed collections.
-    /// Passing `()` to a typed collection is a compile error.
+    /// Passing `()` to a typed collection is a compile error. The returned
+    /// [`ToolRef`] calls the tool through [`invoke`][Self::invoke].
+    ///
+    /// `name` and `desc` are stored without copying when they are
+    /// `&'static str`; a `String` built at runtime is owned by the entry.
     pub fn register<A, I, O, F, Fut>(
         &mut self,
-        name: &'static str,
-        desc: &'static str,
+        name: impl Into<Cow<'static, str>>,
+        desc: impl Into<Cow<'static, str>>,
         func: F,
         meta: A,
-    ) -> Result<&mut Self, ToolError>
+    ) -> Result<ToolRef<I, O>, ToolError>
     where
         A: MetaArg<M>,
         I: 'static + DeserializeOwned + Serialize + Send + ToolSchema,
@@ -586,83 +1660,539 @@ impl<M> ToolCollection<M> {
         F: Fn(I) -> Fut + Send + Sync + 'static,
         Fut: std::future::Future<Output = O> + Send + 'static,
     {
-        if self.entries.contains_key(name) {
-            return Err(ToolError::AlreadyRegistered { name });
-        }
+        let name = name.into();
+        self.register_spec(Self::typed_spec(name.clone(), desc, func, meta)?)?;
+        Ok(ToolRef::named(name))
+    }
+
+    /// Like [`register`][Self::register] for a closure returning
+    /// `Result<O, E>`, where `Err` means the call failed: it comes back
+    /// from [`call`][Self::call] as the `ToolError` `E` converts into, such
+    /// as [`ToolError::Runtime`] for a `String`. The declaration's
+    /// `returns` schema is `O`'s.
+    ///
+    /// To report an error the model should act on instead, return a
+    /// `Result` from [`register`][Self::register]; see [`domain`].
+    pub fn register_fallible<A, I, O, E, F, Fut>(
+        &mut self,
+        name: impl Into<Cow<'static, str>>,
+        desc: impl Into<Cow<'static, str>>,
+        func: F,
+        meta: A,
+    ) -> Result<ToolRef<I, O>, ToolError>
+    where
+        A: MetaArg<M>,
+        I: 'static + DeserializeOwned + Serialize + Send + ToolSchema,
+        O: 'static + Serialize + Send + ToolSchema,
+        E: 'static + Into<ToolError> + Send,
+        F: Fn(I) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Result<O, E>> + Send + 'static,
+    {
+        let name = name.into();
+        let spec = Self::spec_from_fn(name.clone(), desc, func, meta, |output: Result<O, E>| {
+            let output = output.map_err(Into::into)?;
+            serde_json::to_value(output).map_err(|e| ToolError::Runtime(e.to_string()))
+        })?;
+        self.register_spec(spec.returns_schema(schema_value::<O>()?))?;
+        Ok(ToolRef::named(name))
+    }
+
+    /// The [`ToolSpec`] [`register`][Self::register] builds from a typed
+    /// closure.
+    pub(crate) fn typed_spec<A, I, O, F, Fut>(
+        name: impl Into<Cow<'static, str>>,
+        desc: impl Into<Cow<'static, str>>,
+        func: F,
+        meta: A,
+    ) -> Result<ToolSpec<M>, ToolError>
+    where
+        A: MetaArg<M>,
+        I: 'static + DeserializeOwned + Serialize + Send + ToolSchema,
+        O: 'static + Serialize + Send + ToolSchema,
+        F: Fn(I) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = O> + Send + 'static,
+    {
+        Ok(domain::declare_returns::<O, M>(Self::spec_from_fn(
+            name,
+            desc,
+            func,
+            meta,
+            domain::encode::<O>,
+        )?))
+    }
 
+    /// A [`ToolSpec`] for a typed closure whose output `encode` turns into
+    /// the call's result.
+    fn spec_from_fn<A, I, R, F, Fut>(
+        name: impl Into<Cow<'static, str>>,
+        desc: impl Into<Cow<'static, str>>,
+        func: F,
+        meta: A,
+        encode: fn(R) -> Result<Value, ToolError>,
+    ) -> Result<ToolSpec<M>, ToolError>
+    where
+        A: MetaArg<M>,
+        I: 'static + DeserializeOwned + Serialize + Send + ToolSchema,
+        R: 'static + Send,
+        F: Fn(I) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = R> + Send + 'static,
+    {
         let func_arc: Arc<F> = Arc::new(func);
-        let boxed: Arc<ToolFunc> = Arc::new(
-            move |raw: Value,
-                  _ctx: Option<Arc<dyn Any + Send + Sync>>|
-                  -> BoxFuture<'static, Result<Value, ToolError>> {
+        let boxed = ToolFunc::new(
+            |raw: Value| -> Result<DecodedInput, ToolError> {
+                let input: I = serde_json::from_value(raw).map_err(DeserializationError::from)?;
+                Ok(Box::new(input))
+            },
+            move |input, _ctx| -> BoxFuture<'static, Result<Value, ToolError>> {
                 let func = func_arc.clone();
                 async move {
-                    let input: I =
-                        serde_json::from_value(raw).map_err(DeserializationError::from)?;
-                    let output: O = (func)(input).await;
-                    serde_json::to_value(output).map_err(|e| ToolError::Runtime(e.to_string()))
+                    let input: I = downcast_input(input)?;
+                    encode((func)(input).await)
                 }
                 .boxed()
             },
-        );
+        )
+        .with_decode_ref(decode_typed::<I>);
 
-        self.entries.insert(
-            name,
-            ToolEntry {
-                func: boxed,
-                decl: FunctionDecl::new(name, desc, schema_value::<I>()?),
-                meta: meta.into_meta(),
-            },
-        );
+        Ok(ToolSpec::new(name, desc)
+            .params_schema(schema_value::<I>()?)
+            .func(boxed)
+            .with_meta(meta.into_meta()))
+    }
 
-        Ok(self)
+    /// Add `entry` under its declared name, replacing any previous entry
+    /// of that name in place (same id). Fails if the name conflicts with
+    /// another tool's name or alias; see [`names`].
+    fn insert(&mut self, entry: ToolEntry<M>) -> Result<ToolId, ToolError> {
+        let name = entry.decl.name.clone();
+        self.check_name(&name)?;
+        self.json_cache.take();
+        if let Some(id) = self.names.canonical(&name) {
+            if let Some(old) = self.entries[id.index()].replace(entry) {
+                self.retire(old);
+            }
+            return Ok(id);
+        }
+        let id = ToolId(u32::try_from(self.entries.len()).expect("more than u32::MAX tools"));
+        self.entries.push(Some(entry));
+        self.names.insert(name, id);
+        Ok(id)
+    }
+
+    /// Look up the [`ToolId`] for `name`, a tool name or alias, matched
+    /// under the collection's [`NameNormalization`].
+    pub fn resolve(&self, name: &str) -> Option<ToolId> {
+        self.names.resolve(name)
     }
 
     pub async fn call(&self, call: FunctionCall) -> Result<FunctionResponse, ToolError> {
-        let FunctionCall {
-            id,
+        let Some(id) = self.resolve(&call.name) else {
+            return Err(ToolError::FunctionNotFound {
+                name: Cow::Owned(call.name),
+            });
+        };
+        self.call_id(id, call).await
+    }
+
+    /// Like [`call`][Self::call] but leaves `call` with the caller, e.g. to
+    /// keep it in a conversation history. Typed tools decode straight from
+    /// the borrowed arguments; only tools registered with a raw `Value`
+    /// handler clone them.
+    pub async fn call_ref(&self, call: &FunctionCall) -> Result<FunctionResponse, ToolError> {
+        let Some(id) = self.resolve(&call.name) else {
+            return Err(ToolError::FunctionNotFound {
+                name: Cow::Owned(call.name.clone()),
+            });
+        };
+        let response = self
+            .start(
+                id,
+                call.id.clone(),
+                call.name.clone(),
+                Cow::Borrowed(&call.arguments),
+            )
+            .await?;
+        Ok(FunctionResponse {
+            repaired: call.repaired,
+            ..response
+        })
+    }
+
+    /// Like [`call`][Self::call] but dispatches on an already resolved id;
+    /// `call.name` is only echoed back in the response.
+    pub async fn call_id(
+        &self,
+        id: ToolId,
+        call: FunctionCall,
+    ) -> Result<FunctionResponse, ToolError> {
+        let response = self
+            .start(id, call.id, call.name, Cow::Owned(call.arguments))
+            .await?;
+        Ok(FunctionResponse {
+            repaired: call.repaired,
+            ..response
+        })
+    }
+
+    /// Number and record a call to the tool at `id`.
+    async fn start(
+        &self,
+        id: ToolId,
+        call_id: Option<CallId>,
+        name: String,
+        arguments: Cow<'_, Value>,
+    ) -> Result<FunctionResponse, ToolError> {
+        let _in_flight = self.lifecycle.enter()?;
+        let Some(entry) = self.get_id(id) else {
+            return Err(ToolError::FunctionNotFound {
+                name: Cow::Owned(name),
+            });
+        };
+        // Keeps the tool's cleanup waiting if it is removed meanwhile.
+        let _running = entry.cleanup.as_ref().map(Cleanup::enter);
+        let started = Instant::now();
+        let dispatch = sequence::next();
+        let recorded_id = call_id.clone();
+        let recorded_args = self.recent.arguments(&entry.decl, &arguments);
+        let args_hash = self.failures.as_ref().map(|log| log.args_hash(&arguments));
+        let usage = self.usage.as_ref().map(|log| (log, log.start(&arguments)));
+        #[cfg(feature = "testing")]
+        let contract_args = self
+            .contracts
+            .as_ref()
+            .map(|_| arguments.clone().into_owned());
+        let mut cache_hit = false;
+        let run = self.dispatch(entry, dispatch, call_id, name, arguments, &mut cache_hit);
+        let run = supervise(
+            &self.lifecycle,
+            self.secrets.as_ref(),
+            entry.timeout.or(self.default_timeout),
+            run,
+        );
+        #[cfg(feature = "dev-tools")]
+        let run = devtools::with_view(self, entry, run);
+        let mut result = run.await;
+        self.recent.record(
+            &entry.decl,
+            recorded_id.as_ref(),
+            dispatch,
+            started,
+            recorded_args,
+            &result,
+        );
+        if let (Some(log), Some(args_hash), Err(e)) = (&self.failures, args_hash, &result) {
+            log.record(&entry.decl.name, recorded_id.as_ref(), args_hash, e);
+        }
+        if let Ok(response) = &mut result {
+            if self.compact_results && !entry.compaction_exempt {
+                compact::compact(&mut response.result);
+            }
+        }
+        if let Some((log, usage)) = usage {
+            log.finish(
+                usage,
+                &entry.decl.name,
+                self.namespace.as_deref(),
+                started.elapsed(),
+                &result,
+                cache_hit,
+            );
+        }
+        #[cfg(feature = "testing")]
+        if let (Some(recorder), Some(arguments)) = (&self.contracts, contract_args) {
+            recorder.record(&entry.decl.name, arguments, &result);
+        }
+        result
+    }
+
+    /// Run a call that resolved to `entry`, dispatched at `dispatch`.
+    /// `cache_hit` is set when the result cache answers it.
+    async fn dispatch(
+        &self,
+        entry: &ToolEntry<M>,
+        dispatch: u64,
+        call_id: Option<CallId>,
+        name: String,
+        arguments: Cow<'_, Value>,
+        cache_hit: &mut bool,
+    ) -> Result<FunctionResponse, ToolError> {
+        let tool = &entry.decl.name;
+        let (arguments, migrations) = self.migrate(entry, arguments)?;
+        let warnings = self.check_unknown_args(entry, &arguments)?;
+        self.check_schema(entry, &arguments)?;
+        let intent = entry
+            .journal
+            .as_ref()
+            .map(|j| (j, journal::intent(call_id.as_ref(), tool, &arguments, dispatch)));
+        let respond = |result, stale, is_error| FunctionResponse {
+            id: call_id,
             name,
-            arguments,
-        } = call;
+            result,
+            stale,
+            is_error,
+            seq: Some(CallSeq {
+                dispatch,
+                completion: sequence::next(),
+            }),
+            warnings,
+            migrations,
+            repaired: false,
+        };
+        let cached = entry
+            .cache
+            .as_ref()
+            .map(|cache| (cache, hash::canonical_hash(&arguments)));
+        if let Some((cache, key)) = cached {
+            match cache.lookup(key) {
+                Lookup::Fresh(result) => {
+                    *cache_hit = true;
+                    return Ok(respond(result, false, false));
+                }
+                Lookup::Stale { value, refresh } => {
+                    *cache_hit = true;
+                    if refresh {
+                        self.spawn_refresh(entry, cache.clone(), key, arguments.into_owned());
+                    }
+                    return Ok(respond(value, true, false));
+                }
+                Lookup::Miss => {}
+            }
+        }
+
+        // Decoding is synchronous: bad arguments return here, before the
+        // tool's future is created.
+        let input = match arguments {
+            Cow::Owned(arguments) => entry.func.decode(arguments),
+            Cow::Borrowed(arguments) => entry.func.decode_ref(arguments),
+        }?;
+        let journal = intent
+            .map(|(journal, intent)| journal.begin(intent).map(|seq| (journal, seq)))
+            .transpose()?;
+        let circuit = entry.circuit.as_deref();
+        let outcome = invoke(tool, &entry.func, circuit, input, self.ctx.clone()).await;
+        let is_error = matches!(outcome, Err(ToolError::Domain(_)));
+        let outcome = match outcome {
+            Err(ToolError::Domain(error)) => Ok(serde_json::json!({ "error": error })),
+            outcome => outcome,
+        };
+        if let Some((journal, seq)) = journal {
+            journal::complete(journal.as_ref(), seq, &outcome);
+        }
+        let result = outcome?;
+        if let Some((cache, key)) = cached.filter(|_| !is_error) {
+            cache.store(key, result.clone());
+        }
+        Ok(respond(result, false, is_error))
+    }
+
+    /// Re-run a stale cached call on the current runtime and store the
+    /// result, or keep the stale one if it fails. The refresh runs like a
+    /// call of its own; outside a runtime it is skipped.
+    fn spawn_refresh(
+        &self,
+        entry: &ToolEntry<M>,
+        cache: Arc<ResultCache>,
+        key: u64,
+        arguments: Value,
+    ) {
+        let Ok(rt) = tokio::runtime::Handle::try_current() else {
+            cache.release(key);
+            return;
+        };
+        let tool = entry.decl.name.clone();
+        let func = entry.func.clone();
+        let circuit = entry.circuit.clone();
+        let running = entry.cleanup.as_ref().map(Cleanup::enter);
+        let timeout = entry.timeout.or(self.default_timeout);
+        let lifecycle = self.lifecycle.clone();
+        let secrets = self.secrets.clone();
+        let ctx = self.ctx.clone();
+        rt.spawn(async move {
+            let _running = running;
+            let refresh = async {
+                let _in_flight = lifecycle.enter()?;
+                let input = func.decode(arguments)?;
+                let run = invoke(&tool, &func, circuit.as_deref(), input, ctx);
+                supervise(&lifecycle, secrets.as_ref(), timeout, run).await
+            };
+            match refresh.await {
+                Ok(result) => cache.store(key, result),
+                Err(_) => cache.release(key),
+            }
+        });
+    }
+
+    /// Resolve and decode `call` without running the tool. Reports the
+    /// same [`FunctionNotFound`][ToolError::FunctionNotFound],
+    /// [`UnknownArguments`][ToolError::UnknownArguments],
+    /// [`SchemaValidation`][ToolError::SchemaValidation] and
+    /// [`Deserialize`][ToolError::Deserialize] errors [`call`][Self::call]
+    /// would.
+    pub fn dry_run(&self, call: &FunctionCall) -> Result<(), ToolError> {
         let entry = self
-            .entries
-            .get(name.as_str())
-            .ok_or(ToolError::FunctionNotFound {
-                name: Cow::Owned(name.clone()),
+            .get(&call.name)
+            .ok_or_else(|| ToolError::FunctionNotFound {
+                name: Cow::Owned(call.name.clone()),
             })?;
-
-        let result = (entry.func)(arguments, self.ctx.clone()).await?;
-        Ok(FunctionResponse { id, name, result })
+        let (arguments, _) = self.migrate(entry, Cow::Borrowed(&call.arguments))?;
+        self.check_unknown_args(entry, &arguments)?;
+        self.check_schema(entry, &arguments)?;
+        entry.func.decode_ref(&arguments).map(drop)
     }
 
     pub fn unregister(&mut self, name: &str) -> Result<(), ToolError> {
-        if self.entries.remove(name).is_none() {
+        let Some(id) = self.names.remove(name) else {
             return Err(ToolError::FunctionNotFound {
                 name: Cow::Owned(name.to_string()),
             });
+        };
+        self.json_cache.take();
+        self.record_warnings(name, Vec::new());
+        if let Some(entry) = self.entries[id.index()].take() {
+            self.retire(entry);
         }
         Ok(())
     }
 
     pub fn get(&self, name: &str) -> Option<&ToolEntry<M>> {
-        self.entries.get(name)
+        self.get_id(self.resolve(name)?)
+    }
+
+    pub fn get_id(&self, id: ToolId) -> Option<&ToolEntry<M>> {
+        self.entries.get(id.index())?.as_ref()
     }
 
     pub fn meta(&self, name: &str) -> Option<&M> {
-        self.entries.get(name).map(|e| &e.meta)
+        self.get(name).map(|e| &e.meta)
+    }
+
+    /// Number of registered tools.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
     }
 
-    pub fn iter(&self) -> impl Iterator<Item = (&'static str, &ToolEntry<M>)> + '_ {
-        self.entries.iter().map(|(k, v)| (*k, v))
+    /// Registered tools in registration order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &ToolEntry<M>)> + '_ {
+        self.entries.iter().flatten().map(|e| (&*e.decl.name, e))
     }
 
-    pub fn descriptions(&self) -> impl Iterator<Item = (&'static str, &'static str)> + '_ {
-        self.entries.iter().map(|(k, v)| (*k, v.decl.description))
+    /// Name and description of every registered tool, sorted by name.
+    pub fn descriptions(&self) -> impl Iterator<Item = (&str, &str)> + '_ {
+        let mut all: Vec<_> = self
+            .iter()
+            .map(|(k, v)| (k, &*v.decl.description))
+            .collect();
+        all.sort_unstable_by_key(|&(name, _)| name);
+        all.into_iter()
+    }
+
+    /// Declarations of the tools sent to models, sorted by name so that
+    /// prompts and snapshots don't depend on registration order: every
+    /// tool but the [hidden][Self::set_hidden] ones, unless
+    /// [`set_declare_hidden`][Self::set_declare_hidden] is on.
+    pub fn declarations(&self) -> impl Iterator<Item = &FunctionDecl<'static>> + '_ {
+        let mut decls: Vec<_> = self
+            .iter()
+            .filter(|(_, e)| !e.hidden || self.declare_hidden)
+            .map(|(_, e)| &e.decl)
+            .collect();
+        decls.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+        decls.into_iter()
+    }
+
+    /// Leave the tool out of [`declarations`][Self::declarations] and
+    /// [`json`][Self::json]. It can still be called.
+    pub fn set_hidden(&mut self, name: &str, hidden: bool) -> Result<(), ToolError> {
+        self.entry_mut(name)?.hidden = hidden;
+        self.json_cache.take();
+        Ok(())
+    }
+
+    /// Declare hidden tools too. Off by default.
+    pub fn set_declare_hidden(&mut self, on: bool) {
+        if self.declare_hidden != on {
+            self.declare_hidden = on;
+            self.json_cache.take();
+        }
+    }
+
+    pub fn declare_hidden(&self) -> bool {
+        self.declare_hidden
+    }
+
+    /// Declaration of the tool `name` (or an alias of it), if it is
+    /// among [`declarations`][Self::declarations].
+    pub fn get_declaration(&self, name: &str) -> Option<&FunctionDecl<'static>> {
+        self.get(name)
+            .filter(|e| !e.hidden || self.declare_hidden)
+            .map(|e| &e.decl)
     }
 
     pub fn json(&self) -> Result<Value, ToolError> {
-        let list: Vec<&FunctionDecl> = self.entries.values().map(|e| &e.decl).collect();
-        Ok(serde_json::to_value(list)?)
+        self.json_cache
+            .get_or_try_init(|| {
+                let decls: Vec<&FunctionDecl> = self.declarations().collect();
+                self.render(&decls)
+            })
+            .cloned()
+            .map_err(ToolError::from)
+    }
+
+    /// [`json`][Self::json] for only the named tools, in the order given.
+    /// Fails with [`ToolError::FunctionNotFound`] on the first name that
+    /// [`get_declaration`][Self::get_declaration] doesn't find. Not
+    /// cached.
+    pub fn json_filtered(&self, names: &[&str]) -> Result<Value, ToolError> {
+        let decls = names
+            .iter()
+            .map(|&name| {
+                self.get_declaration(name)
+                    .ok_or_else(|| ToolError::FunctionNotFound {
+                        name: Cow::Owned(name.to_string()),
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(self.render(&decls)?)
+    }
+
+    /// Serialize `decls` the way [`json`][Self::json] does, with `$schema`
+    /// URIs and content hashes when those are on.
+    fn render(&self, decls: &[&FunctionDecl]) -> Result<Value, serde_json::Error> {
+        let mut value = if self.schema_uris {
+            let annotated: Vec<FunctionDecl> = decls
+                .iter()
+                .map(|d| FunctionDecl {
+                    parameters: d.annotated_parameters().into_owned(),
+                    ..(*d).clone()
+                })
+                .collect();
+            serde_json::to_value(annotated)?
+        } else {
+            serde_json::to_value(decls)?
+        };
+        if self.content_hashes {
+            if let Value::Array(items) = &mut value {
+                for (decl, item) in decls.iter().zip(items) {
+                    let hash = format!("{:016x}", decl.content_hash());
+                    item[hash::CONTENT_HASH_KEY] = Value::from(hash);
+                }
+            }
+        }
+        Ok(value)
+    }
+
+    /// [`declarations`][Self::declarations] rendered with
+    /// [`hash::canonical_json_pretty`], ending in a newline: the same tools
+    /// always give the same bytes, whatever order they were registered in.
+    /// Meant for checked-in snapshots; see `assert_declarations_snapshot!`.
+    pub fn to_canonical_string(&self) -> Result<String, ToolError> {
+        let decls: Vec<&FunctionDecl> = self.declarations().collect();
+        let value = serde_json::to_value(decls)?;
+        Ok(hash::canonical_json_pretty(&value) + "\n")
     }
 }
 
@@ -674,6 +2204,31 @@ impl<M: DeserializeOwned> ToolCollection<M> {
     pub fn collect_tools() -> Result<Self, ToolError> {
         collect_inventory_inner(None, None, "")
     }
+
+    /// Like [`collect_tools`][Self::collect_tools] but names each tool
+    /// after its defining crate: `ping` in crate `net_tools` becomes
+    /// `net_tools.ping` with `separator` `"."`. Use this when several crates
+    /// in a workspace define `#[tool]` functions of the same name.
+    ///
+    /// ```ignore
+    /// let tools = ToolCollection::<NoMeta>::collect_tools_namespaced("__")?;
+    /// tools.call(FunctionCall::new("net_tools__ping".into(), json!({}))).await?;
+    /// ```
+    pub fn collect_tools_namespaced(separator: &str) -> Result<Self, ToolError> {
+        collect_inventory_filtered(None, None, "", Some(separator), &|_| true)
+    }
+
+    /// Like [`collect_tools`][Self::collect_tools] but keeps only the
+    /// inventory tools whose name passes `filter`. Excluded tools are not
+    /// validated, so a test can build a collection of exactly the tools it
+    /// uses without tripping over unrelated `ctx` tools or metadata.
+    ///
+    /// ```ignore
+    /// let tools = ToolCollection::<NoMeta>::collect_tools_filtered(|n| n == "add")?;
+    /// ```
+    pub fn collect_tools_filtered(filter: impl Fn(&str) -> bool) -> Result<Self, ToolError> {
+        collect_inventory_filtered(None, None, "", None, &filter)
+    }
 }
 
 /// Validate every registered tool's `#[tool(...)]` attributes against `M`,
@@ -749,17 +2304,55 @@ pub(crate) fn collect_inventory_inner<M: DeserializeOwned>(
     ctx_type_id: Option<TypeId>,
     ctx_type_name: &str,
 ) -> Result<ToolCollection<M>, ToolError> {
-    let mut entries = HashMap::new();
+    collect_inventory_filtered(ctx, ctx_type_id, ctx_type_name, None, &|_| true)
+}
 
-    for reg in inventory::iter::<ToolRegistration> {
+/// Collections built from the `#[tool]` inventory so far.
+static INVENTORY_COLLECTIONS: AtomicU64 = AtomicU64::new(0);
+
+/// How many collections this process has built from the `#[tool]`
+/// inventory, through `collect_tools` and its variants or a builder's
+/// `collect`. Each one walks the inventory and builds every declaration
+/// again. Only meant for tests.
+#[doc(hidden)]
+pub fn inventory_collections() -> u64 {
+    INVENTORY_COLLECTIONS.load(Ordering::Relaxed)
+}
+
+/// [`collect_inventory_inner`] restricted to registrations whose name
+/// passes `filter`. With a `separator`, tools are named
+/// `{crate}{separator}{name}`; `filter` still sees the bare name.
+pub(crate) fn collect_inventory_filtered<M: DeserializeOwned>(
+    ctx: Option<Arc<dyn Any + Send + Sync>>,
+    ctx_type_id: Option<TypeId>,
+    ctx_type_name: &str,
+    separator: Option<&str>,
+    filter: &dyn Fn(&str) -> bool,
+) -> Result<ToolCollection<M>, ToolError> {
+    INVENTORY_COLLECTIONS.fetch_add(1, Ordering::Relaxed);
+    // Inventory order follows link order, which changes between builds;
+    // sorting keeps ids and declaration order stable.
+    let mut selected: Vec<&ToolRegistration> = inventory::iter::<ToolRegistration>
+        .into_iter()
+        .filter(|reg| filter(reg.name))
+        .collect();
+    selected.sort_by_key(|reg| (reg.name, reg.module_path));
+    let mut tools = ToolCollection {
+        ctx,
+        ..ToolCollection::with_capacity(selected.len())
+    };
+
+    for reg in selected {
         if reg.needs_ctx {
             let Some(provided_id) = ctx_type_id else {
-                return Err(ToolError::MissingCtx { tool: reg.name });
+                return Err(ToolError::MissingCtx {
+                    tool: reg.name.into(),
+                });
             };
             let expected_id = (reg.ctx_type_id.unwrap())();
             if expected_id != provided_id {
                 return Err(ToolError::CtxTypeMismatch {
-                    tool: reg.name,
+                    tool: reg.name.into(),
                     expected: reg.ctx_type_name.to_string(),
                     got: ctx_type_name.to_string(),
                 });
@@ -767,21 +2360,42 @@ pub(crate) fn collect_inventory_inner<M: DeserializeOwned>(
         }
 
         let meta: M = serde_json::from_str(reg.meta_json).map_err(|e| ToolError::BadMeta {
-            tool: reg.name,
+            tool: reg.name.into(),
             error: e.to_string(),
         })?;
 
-        entries.insert(
-            reg.name,
-            ToolEntry {
-                func: Arc::new(reg.f),
-                decl: FunctionDecl::new(reg.name, reg.doc, (reg.param_schema)()),
-                meta,
-            },
-        );
+        let origin = reg.origin();
+        let name: Cow<'static, str> = match separator {
+            Some(sep) => format!("{}{sep}{}", origin.crate_name, reg.name).into(),
+            None => reg.name.into(),
+        };
+        // Within one crate the last `#[tool]` of a name wins, as it always
+        // has; across crates the clash is almost certainly unintended.
+        if let Some(existing) = tools.get(&name) {
+            match existing.origin {
+                Some(first) if first.crate_name != origin.crate_name => {
+                    return Err(ToolError::DuplicateTool {
+                        name: existing.decl.name.clone(),
+                        first,
+                        second: origin,
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        let mut spec = ToolSpec::new(name, reg.doc)
+            .params_schema((reg.param_schema)())
+            .func(ToolFunc::new(reg.decode, reg.run).with_decode_ref(reg.decode_ref))
+            .origin(origin)
+            .with_meta(meta);
+        if let Some(returns) = reg.returns_schema {
+            spec = spec.returns_schema(returns());
+        }
+        tools.insert_spec(spec)?;
     }
 
-    Ok(ToolCollection { entries, ctx })
+    Ok(tools)
 }
 
 // ============================================================================
@@ -801,6 +2415,13 @@ pub struct CollectionBuilder<M = NoMeta> {
     ctx: Option<Arc<dyn Any + Send + Sync>>,
     ctx_type_id: Option<TypeId>,
     ctx_type_name: &'static str,
+    strict_registration: bool,
+    registration_strictness: Strictness,
+    unknown_args: UnknownArgs,
+    wrap_bare_inputs: bool,
+    compact_results: bool,
+    validation: bool,
+    default_timeout: Option<Duration>,
     _meta: std::marker::PhantomData<M>,
 }
 
@@ -815,6 +2436,52 @@ impl<M> CollectionBuilder<M> {
         self.ctx = Some(ctx);
         self
     }
+
+    /// Fail [`collect()`][Self::collect] if any tool has registration
+    /// findings, instead of keeping them as warnings. See [`checks`].
+    pub fn strict_registration(mut self, strict: bool) -> Self {
+        self.strict_registration = strict;
+        self
+    }
+
+    /// Fail [`collect()`][Self::collect] if any tool falls short of
+    /// `strictness`. See [`ToolCollection::set_registration_strictness`].
+    pub fn registration_strictness(mut self, strictness: Strictness) -> Self {
+        self.registration_strictness = strictness;
+        self
+    }
+
+    /// What calls do with undeclared arguments. See [`unknown`].
+    pub fn unknown_args(mut self, mode: UnknownArgs) -> Self {
+        self.unknown_args = mode;
+        self
+    }
+
+    /// Wrap bare array and primitive inputs in an object. See [`wrap`].
+    pub fn wrap_bare_inputs(mut self, wrap: bool) -> Self {
+        self.wrap_bare_inputs = wrap;
+        self
+    }
+
+    /// Drop `null` and empty fields from results. See [`compact`].
+    pub fn compact_results(mut self, compact: bool) -> Self {
+        self.compact_results = compact;
+        self
+    }
+
+    /// Check arguments against each tool's schema first. See
+    /// [`validate`].
+    pub fn validation(mut self, on: bool) -> Self {
+        self.validation = on;
+        self
+    }
+
+    /// Give every call at most `timeout`. See
+    /// [`ToolCollection::set_default_timeout`].
+    pub fn default_timeout(mut self, timeout: Duration) -> Self {
+        self.default_timeout = Some(timeout);
+        self
+    }
 }
 
 impl<M: DeserializeOwned> CollectionBuilder<M> {
@@ -823,8 +2490,20 @@ impl<M: DeserializeOwned> CollectionBuilder<M> {
     /// - Every tool's `meta_json` deserializes into `M`.
     /// - Every `needs_ctx` tool's expected `TypeId` matches the builder's.
     /// - No `needs_ctx` tool exists when no context was provided.
+    /// - With [`strict_registration`][Self::strict_registration], no tool
+    ///   has registration findings.
+    /// - With [`registration_strictness`][Self::registration_strictness],
+    ///   every tool is documented as required.
     pub fn collect(self) -> Result<ToolCollection<M>, ToolError> {
-        collect_inventory_inner(self.ctx, self.ctx_type_id, self.ctx_type_name)
+        let mut tools = collect_inventory_inner(self.ctx, self.ctx_type_id, self.ctx_type_name)?;
+        tools.set_wrap_bare_inputs(self.wrap_bare_inputs);
+        tools.set_strict_registration(self.strict_registration)?;
+        tools.set_registration_strictness(self.registration_strictness)?;
+        tools.set_unknown_args(self.unknown_args);
+        tools.set_compact_results(self.compact_results);
+        tools.set_validation(self.validation);
+        tools.default_timeout = self.default_timeout;
+        Ok(tools)
     }
 }
 
@@ -997,6 +2676,7 @@ mod tool_tests {
     fn noop() {}
     // async fn async_foo() {}
 
+    #[allow(dead_code)]
     #[derive(Debug, PartialEq, Serialize, Deserialize)]
     struct SomeArgs {
         a: i32,
@@ -1029,12 +2709,7 @@ mod tool_tests {
             )
             .unwrap();
         collection
-            .register(
-                "noop",
-                "Does nothing",
-                |_t: ()| async move { noop() },
-                (),
-            )
+            .register("noop", "Does nothing", |_t: ()| async move { noop() }, ())
             .unwrap();
         // Complex args test commented out due to ToolSchema derive requirement
         // collection
@@ -1151,13 +2826,124 @@ mod tool_tests {
 
         assert!(matches!(err, ToolError::Deserialize(_)));
     }
+
+    #[test]
+    fn test_dry_run_decodes_without_running() {
+        let ran = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let flag = ran.clone();
+        let mut col: ToolCollection = ToolCollection::default();
+        col.register(
+            "touch",
+            "Sets a flag",
+            move |_: i32| {
+                let flag = flag.clone();
+                async move { flag.store(true, std::sync::atomic::Ordering::SeqCst) }
+            },
+            (),
+        )
+        .unwrap();
+
+        col.dry_run(&fc("touch", json!(1))).unwrap();
+        assert!(!ran.load(std::sync::atomic::Ordering::SeqCst));
+
+        let err = col.dry_run(&fc("touch", json!("nope"))).unwrap_err();
+        assert!(matches!(err, ToolError::Deserialize(_)));
+        let err = col.dry_run(&fc("ghost", json!(1))).unwrap_err();
+        assert!(matches!(err, ToolError::FunctionNotFound { .. }));
+    }
+
+    #[test]
+    fn test_decode_failure_is_synchronous() {
+        let mut col: ToolCollection = ToolCollection::default();
+        col.register("neg", "Negates", |x: i32| async move { -x }, ())
+            .unwrap();
+        let func = &col.get("neg").unwrap().func;
+        assert!(func.decode(json!("x")).is_err());
+        let input = func.decode(json!(3)).unwrap();
+        let out = futures::executor::block_on(func.run(input, None)).unwrap();
+        assert_eq!(out, json!(-3));
+    }
+
+    #[test]
+    fn test_stale_hit_outside_a_runtime_skips_the_refresh() {
+        let runs = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let clock = Arc::new(circuit::ManualClock::new());
+        let mut col: ToolCollection = ToolCollection::default();
+        let counter = runs.clone();
+        col.register(
+            "count",
+            "Counts its runs",
+            move |_: ()| {
+                let runs = counter.clone();
+                async move { runs.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1 }
+            },
+            (),
+        )
+        .unwrap();
+        let ttl = Duration::from_secs(60);
+        col.set_cache_policy_with_clock(
+            "count",
+            CachePolicy {
+                ttl,
+                stale_while_revalidate: ttl,
+            },
+            clock.clone(),
+        )
+        .unwrap();
+
+        let call = || futures::executor::block_on(col.call(fc("count", json!(null)))).unwrap();
+        assert_eq!(call().result, json!(1));
+        clock.advance(ttl);
+        for _ in 0..2 {
+            let stale = call();
+            assert!(stale.stale);
+            assert_eq!(stale.result, json!(1));
+        }
+        assert_eq!(runs.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_call_by_resolved_id() {
+        let mut col: ToolCollection = ToolCollection::default();
+        col.register("neg", "Negates", |x: i32| async move { -x }, ())
+            .unwrap();
+        col.register("double", "Doubles", |x: i32| async move { x * 2 }, ())
+            .unwrap();
+
+        let id = col.resolve("double").unwrap();
+        assert_eq!(col.get_id(id).unwrap().decl.name, "double");
+        let resp = col.call_id(id, fc("double", json!(21))).await.unwrap();
+        assert_eq!(resp.result, json!(42));
+        assert_eq!(resp.name, "double");
+        assert!(col.resolve("ghost").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_unregistered_id_stays_dead() {
+        let mut col: ToolCollection = ToolCollection::default();
+        col.register("neg", "Negates", |x: i32| async move { -x }, ())
+            .unwrap();
+        let old = col.resolve("neg").unwrap();
+
+        col.unregister("neg").unwrap();
+        assert!(col.is_empty());
+        let err = col.call_id(old, fc("neg", json!(1))).await.unwrap_err();
+        assert!(matches!(err, ToolError::FunctionNotFound { .. }));
+
+        col.register("neg", "Negates again", |x: i32| async move { -x }, ())
+            .unwrap();
+        let new = col.resolve("neg").unwrap();
+        assert_ne!(old, new);
+        assert_eq!(col.len(), 1);
+        assert_eq!(col.iter().count(), 1);
+        assert!(col.get_id(old).is_none());
+    }
 }
 
 // Performance tests for schema caching (primitive types only)
 #[cfg(test)]
 mod performance_tests {
     use super::*;
-    use std::time::Instant;
 
     #[test]
     fn test_schema_caching_primitives() {
@@ -1307,3 +3093,238 @@ mod performance_tests {
         assert!(bool_duration.as_millis() < 100);
     }
 }
+
+#[cfg(test)]
+mod error_tests {
+    // `ToolError::classification` and `is_retryable` for every variant.
+
+    use super::*;
+    use serde_json::json;
+
+    fn json_error() -> serde_json::Error {
+        serde_json::from_str::<i32>("not a number").unwrap_err()
+    }
+
+    /// One instance of every variant, with its expected class.
+    fn table() -> Vec<(ToolError, ErrorClass)> {
+        vec![
+            (
+                ToolError::FunctionNotFound {
+                    name: Cow::Borrowed("missing"),
+                },
+                ErrorClass::NotFound,
+            ),
+            (
+                ToolError::AlreadyRegistered {
+                    name: "add".into(),
+                    existing: "Adds.".into(),
+                    attempted: "Adds again.".into(),
+                },
+                ErrorClass::Internal,
+            ),
+            (
+                ToolError::DuplicateTool {
+                    name: "add".into(),
+                    first: ToolOrigin::from_module_path("a::math"),
+                    second: ToolOrigin::from_module_path("b::math"),
+                },
+                ErrorClass::Internal,
+            ),
+            (
+                ToolError::NameConflict {
+                    name: "Add".into(),
+                    existing: "add".into(),
+                    tool: "add".into(),
+                    normalization: NameNormalization::IgnoreCase,
+                },
+                ErrorClass::Internal,
+            ),
+            (
+                ToolError::Deserialize(DeserializationError::from(json_error())),
+                ErrorClass::InvalidInput,
+            ),
+            (
+                ToolError::UnknownArguments {
+                    tool: "add".into(),
+                    unknown: vec!["c".into()],
+                    accepted: vec!["a".into(), "b".into()],
+                    summary: "`c`".into(),
+                },
+                ErrorClass::InvalidInput,
+            ),
+            (
+                ToolError::SchemaValidation {
+                    tool: "add".into(),
+                    errors: vec!["/a: expected integer".into()],
+                    summary: "/a: expected integer".into(),
+                },
+                ErrorClass::InvalidInput,
+            ),
+            (ToolError::Serialization(json_error()), ErrorClass::Internal),
+            (ToolError::Runtime("boom".into()), ErrorClass::Internal),
+            (
+                ToolError::BadMeta {
+                    tool: "add".into(),
+                    error: "unknown field".into(),
+                },
+                ErrorClass::Internal,
+            ),
+            (
+                ToolError::MetaValidation {
+                    errors: vec![MetaValidationError {
+                        tool: Cow::Borrowed("add"),
+                        error: "unknown field".into(),
+                    }],
+                    summary: "add".into(),
+                },
+                ErrorClass::Internal,
+            ),
+            (
+                ToolError::RegistrationRejected {
+                    tool: "add".into(),
+                    findings: Vec::new(),
+                    summary: "tool has no description".into(),
+                },
+                ErrorClass::Internal,
+            ),
+            (
+                ToolError::MissingCtx { tool: "add".into() },
+                ErrorClass::Internal,
+            ),
+            (
+                ToolError::MissingHandler { tool: "add".into() },
+                ErrorClass::Internal,
+            ),
+            (
+                ToolError::CtxTypeMismatch {
+                    tool: "add".into(),
+                    expected: "A".into(),
+                    got: "B".into(),
+                },
+                ErrorClass::Internal,
+            ),
+            (
+                ToolError::MissingResource {
+                    tool: "add".into(),
+                    resource: "Client",
+                },
+                ErrorClass::Internal,
+            ),
+            (
+                ToolError::MissingSecret {
+                    key: "API_KEY".into(),
+                },
+                ErrorClass::Denied,
+            ),
+            (
+                ToolError::CircuitOpen {
+                    name: "add".into(),
+                    retry_after: Duration::from_secs(1),
+                },
+                ErrorClass::Exhausted,
+            ),
+            (
+                ToolError::DeadlineExceeded { tool: "add".into() },
+                ErrorClass::Exhausted,
+            ),
+            (
+                ToolError::Timeout {
+                    name: "add".into(),
+                    elapsed: Duration::from_secs(10),
+                },
+                ErrorClass::Transient,
+            ),
+            (
+                ToolError::Remote {
+                    message: "bad query".into(),
+                    details: json!([]),
+                },
+                ErrorClass::Internal,
+            ),
+            (
+                ToolError::BudgetExhausted {
+                    profile: "researcher".into(),
+                    budget: 2,
+                },
+                ErrorClass::Exhausted,
+            ),
+            (
+                ToolError::Domain(json!({ "kind": "out_of_stock" })),
+                ErrorClass::InvalidInput,
+            ),
+            (ToolError::QueueFull { capacity: 8 }, ErrorClass::Transient),
+            (ToolError::QueueClosed, ErrorClass::Internal),
+            (ToolError::ShuttingDown, ErrorClass::Transient),
+            (
+                ToolError::Panicked {
+                    tool: "add".into(),
+                    message: "overflow".into(),
+                    backtrace: None,
+                },
+                ErrorClass::Internal,
+            ),
+        ]
+    }
+
+    const ALL_CLASSES: [ErrorClass; 6] = [
+        ErrorClass::InvalidInput,
+        ErrorClass::NotFound,
+        ErrorClass::Transient,
+        ErrorClass::Internal,
+        ErrorClass::Denied,
+        ErrorClass::Exhausted,
+    ];
+
+    // ---------- fixed mappings ----------
+
+    #[test]
+    fn every_variant_has_its_documented_class() {
+        for (err, class) in table() {
+            assert_eq!(err.classification(), class, "{err}");
+            assert_eq!(err.is_retryable(), class == ErrorClass::Transient, "{err}");
+        }
+    }
+
+    // ---------- classified runtime errors ----------
+
+    #[test]
+    fn classified_errors_keep_their_class() {
+        for class in ALL_CLASSES {
+            let err = ToolError::classified(class, "upstream said no");
+            assert_eq!(err.classification(), class);
+            assert_eq!(err.is_retryable(), class == ErrorClass::Transient);
+        }
+    }
+
+    #[test]
+    fn classified_message_names_the_class() {
+        let err = ToolError::classified(ErrorClass::Exhausted, "quota used up");
+        assert_eq!(err.to_string(), "exhausted error: quota used up");
+        assert_eq!(
+            serde_json::to_value(ErrorClass::InvalidInput).unwrap(),
+            json!("invalid_input")
+        );
+    }
+
+    // ---------- errors from a collection ----------
+
+    #[tokio::test]
+    async fn bad_arguments_are_not_retryable() {
+        let mut tools: ToolCollection = ToolCollection::new();
+        tools
+            .register("double", "Doubles.", |x: i32| async move { x * 2 }, ())
+            .unwrap();
+
+        let err = tools
+            .call(FunctionCall::new("double".into(), json!("two")))
+            .await
+            .unwrap_err();
+        assert_eq!(err.classification(), ErrorClass::InvalidInput);
+
+        let err = tools
+            .call(FunctionCall::new("triple".into(), json!(2)))
+            .await
+            .unwrap_err();
+        assert_eq!(err.classification(), ErrorClass::NotFound);
+    }
+}
@@ -0,0 +1,442 @@
+//! Advisory lint over tool declarations.
+//!
+//! [`ToolCollection::lint`] flags declarations that tend to confuse
+//! models: positional (tuple) parameters, deep nesting, missing
+//! descriptions, one-letter parameter names, huge enums and unbounded
+//! strings on destructive tools. Nothing here affects registration or
+//! calls — run it in a test to enforce team conventions:
+//!
+//! ```ignore
+//! #[test]
+//! fn tools_pass_lint() {
+//!     let tools = ToolCollection::<Policy>::collect_tools().unwrap();
+//!     let config = LintConfig::default()
+//!         .destructive(|m: &Policy| m.requires_approval)
+//!         .allow(codes::MISSING_PARAM_DESCRIPTION);
+//!     let findings = tools.lint_with(&config);
+//!     assert!(findings.is_empty(), "{findings:#?}");
+//! }
+//! ```
+
+use core::fmt;
+
+use serde_json::Value;
+
+use crate::{ToolCollection, ToolEntry};
+
+/// Stable finding codes. Use these with [`LintConfig::allow`].
+pub mod codes {
+    pub const POSITIONAL_PARAMS: &str = "positional-params";
+    pub const DEEP_NESTING: &str = "deep-nesting";
+    pub const MISSING_TOOL_DESCRIPTION: &str = "missing-tool-description";
+    pub const MISSING_PARAM_DESCRIPTION: &str = "missing-param-description";
+    pub const SINGLE_LETTER_PARAM: &str = "single-letter-param";
+    pub const LARGE_ENUM: &str = "large-enum";
+    pub const UNBOUNDED_STRING: &str = "unbounded-string";
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+/// One lint result. `path` is a JSON pointer into the tool's
+/// `parameters` schema (`""` for tool-level findings).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintFinding {
+    pub code: &'static str,
+    pub severity: Severity,
+    pub tool: String,
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for LintFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}[{}] tool `{}`", self.severity, self.code, self.tool)?;
+        if !self.path.is_empty() {
+            write!(f, " at {}", self.path)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// Thresholds and toggles for [`ToolCollection::lint_with`].
+pub struct LintConfig<M> {
+    /// Object nesting depth (the parameters object itself is level 1)
+    /// beyond which [`codes::DEEP_NESTING`] fires. Default 4.
+    pub max_depth: usize,
+    /// Enum size beyond which [`codes::LARGE_ENUM`] fires. Default 50.
+    pub max_enum_variants: usize,
+    /// Decides which tools count as destructive for
+    /// [`codes::UNBOUNDED_STRING`]. Default: none.
+    pub is_destructive: fn(&M) -> bool,
+    allowed: Vec<&'static str>,
+}
+
+impl<M> Default for LintConfig<M> {
+    fn default() -> Self {
+        Self {
+            max_depth: 4,
+            max_enum_variants: 50,
+            is_destructive: |_| false,
+            allowed: Vec::new(),
+        }
+    }
+}
+
+impl<M> LintConfig<M> {
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    pub fn max_enum_variants(mut self, n: usize) -> Self {
+        self.max_enum_variants = n;
+        self
+    }
+
+    pub fn destructive(mut self, pred: fn(&M) -> bool) -> Self {
+        self.is_destructive = pred;
+        self
+    }
+
+    /// Silence a rule by code.
+    pub fn allow(mut self, code: &'static str) -> Self {
+        self.allowed.push(code);
+        self
+    }
+}
+
+// ============================================================================
+// RULES ENGINE
+// ============================================================================
+
+/// What a rule sees for one tool.
+struct Subject<'a, M> {
+    name: &'a str,
+    entry: &'a ToolEntry<M>,
+}
+
+impl<M> Subject<'_, M> {
+    fn finding(
+        &self,
+        code: &'static str,
+        severity: Severity,
+        path: String,
+        message: String,
+    ) -> LintFinding {
+        LintFinding {
+            code,
+            severity,
+            tool: self.name.to_string(),
+            path,
+            message,
+        }
+    }
+}
+
+type Rule<M> = fn(&Subject<'_, M>, &LintConfig<M>, &mut Vec<LintFinding>);
+
+fn rules<M>() -> [(&'static str, Rule<M>); 7] {
+    [
+        (codes::POSITIONAL_PARAMS, positional_params),
+        (codes::DEEP_NESTING, deep_nesting),
+        (codes::MISSING_TOOL_DESCRIPTION, missing_tool_description),
+        (codes::MISSING_PARAM_DESCRIPTION, missing_param_description),
+        (codes::SINGLE_LETTER_PARAM, single_letter_param),
+        (codes::LARGE_ENUM, large_enum),
+        (codes::UNBOUNDED_STRING, unbounded_string),
+    ]
+}
+
+impl<M> ToolCollection<M> {
+    /// Lint every declaration with the default [`LintConfig`].
+    pub fn lint(&self) -> Vec<LintFinding> {
+        self.lint_with(&LintConfig::default())
+    }
+
+    /// Lint every declaration. Findings are sorted by tool, path and code.
+    pub fn lint_with(&self, config: &LintConfig<M>) -> Vec<LintFinding> {
+        let mut findings = Vec::new();
+        for (name, entry) in self.iter() {
+            let subject = Subject { name, entry };
+            for (code, rule) in rules::<M>() {
+                if !config.allowed.contains(&code) {
+                    rule(&subject, config, &mut findings);
+                }
+            }
+        }
+        findings.sort_by(|a, b| (&a.tool, &a.path, a.code).cmp(&(&b.tool, &b.path, b.code)));
+        findings
+    }
+}
+
+/// Visit every subschema of `schema` with its JSON pointer and object
+/// nesting depth.
+fn walk(schema: &Value, path: &str, depth: usize, visit: &mut dyn FnMut(&Value, &str, usize)) {
+    let depth = if schema["type"] == "object" {
+        depth + 1
+    } else {
+        depth
+    };
+    visit(schema, path, depth);
+
+    let Value::Object(obj) = schema else {
+        return;
+    };
+    for (key, value) in obj {
+        match (key.as_str(), value) {
+            ("properties" | "$defs" | "definitions", Value::Object(entries)) => {
+                for (name, sub) in entries {
+                    walk(sub, &format!("{path}/{key}/{}", escape(name)), depth, visit);
+                }
+            }
+            ("items" | "additionalProperties" | "not", Value::Object(_)) => {
+                walk(value, &format!("{path}/{key}"), depth, visit);
+            }
+            ("prefixItems" | "items" | "anyOf" | "oneOf" | "allOf", Value::Array(items)) => {
+                for (i, sub) in items.iter().enumerate() {
+                    walk(sub, &format!("{path}/{key}/{i}"), depth, visit);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// JSON-pointer escaping for a property name.
+pub(crate) fn escape(name: &str) -> String {
+    name.replace('~', "~0").replace('/', "~1")
+}
+
+pub(crate) fn top_level_params(schema: &Value) -> impl Iterator<Item = (&String, &Value)> {
+    schema["properties"]
+        .as_object()
+        .into_iter()
+        .flat_map(|p| p.iter())
+}
+
+// ---------- rules ----------
+
+fn positional_params<M>(s: &Subject<'_, M>, _: &LintConfig<M>, out: &mut Vec<LintFinding>) {
+    walk(&s.entry.decl.parameters, "", 0, &mut |node, path, _| {
+        if node.get("prefixItems").is_some() {
+            out.push(s.finding(
+                codes::POSITIONAL_PARAMS,
+                Severity::Warning,
+                path.to_string(),
+                "positional (tuple) arguments; models handle named fields far better".into(),
+            ));
+        }
+    });
+}
+
+fn deep_nesting<M>(s: &Subject<'_, M>, cfg: &LintConfig<M>, out: &mut Vec<LintFinding>) {
+    walk(&s.entry.decl.parameters, "", 0, &mut |node, path, depth| {
+        // Report only where the limit is first crossed on each branch.
+        if node["type"] == "object" && depth == cfg.max_depth + 1 {
+            out.push(s.finding(
+                codes::DEEP_NESTING,
+                Severity::Warning,
+                path.to_string(),
+                format!("object nested {depth} levels deep (max {})", cfg.max_depth),
+            ));
+        }
+    });
+}
+
+fn missing_tool_description<M>(s: &Subject<'_, M>, _: &LintConfig<M>, out: &mut Vec<LintFinding>) {
+    if s.entry.decl.description.trim().is_empty() {
+        out.push(s.finding(
+            codes::MISSING_TOOL_DESCRIPTION,
+            Severity::Error,
+            String::new(),
+            "tool has no description".into(),
+        ));
+    }
+}
+
+fn missing_param_description<M>(s: &Subject<'_, M>, _: &LintConfig<M>, out: &mut Vec<LintFinding>) {
+    for (name, schema) in top_level_params(&s.entry.decl.parameters) {
+        let described = schema
+            .get("description")
+            .and_then(Value::as_str)
+            .is_some_and(|d| !d.trim().is_empty());
+        if !described {
+            out.push(s.finding(
+                codes::MISSING_PARAM_DESCRIPTION,
+                Severity::Info,
+                format!("/properties/{}", escape(name)),
+                format!("parameter `{name}` has no description"),
+            ));
+        }
+    }
+}
+
+fn single_letter_param<M>(s: &Subject<'_, M>, _: &LintConfig<M>, out: &mut Vec<LintFinding>) {
+    for (name, _) in top_level_params(&s.entry.decl.parameters) {
+        if name.chars().count() == 1 {
+            out.push(s.finding(
+                codes::SINGLE_LETTER_PARAM,
+                Severity::Warning,
+                format!("/properties/{}", escape(name)),
+                format!("parameter name `{name}` carries no meaning for the model"),
+            ));
+        }
+    }
+}
+
+fn large_enum<M>(s: &Subject<'_, M>, cfg: &LintConfig<M>, out: &mut Vec<LintFinding>) {
+    walk(&s.entry.decl.parameters, "", 0, &mut |node, path, _| {
+        if let Some(values) = node.get("enum").and_then(Value::as_array) {
+            if values.len() > cfg.max_enum_variants {
+                out.push(s.finding(
+                    codes::LARGE_ENUM,
+                    Severity::Warning,
+                    path.to_string(),
+                    format!(
+                        "enum with {} values (max {})",
+                        values.len(),
+                        cfg.max_enum_variants
+                    ),
+                ));
+            }
+        }
+    });
+}
+
+fn unbounded_string<M>(s: &Subject<'_, M>, cfg: &LintConfig<M>, out: &mut Vec<LintFinding>) {
+    if !(cfg.is_destructive)(&s.entry.meta) {
+        return;
+    }
+    walk(&s.entry.decl.parameters, "", 0, &mut |node, path, _| {
+        let bounded = ["maxLength", "enum", "const", "pattern"]
+            .iter()
+            .any(|k| node.get(k).is_some());
+        if node["type"] == "string" && !bounded {
+            out.push(s.finding(
+                codes::UNBOUNDED_STRING,
+                Severity::Warning,
+                path.to_string(),
+                "unbounded string on a destructive tool; add maxLength, pattern or enum".into(),
+            ));
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn raw(
+        tools: &mut ToolCollection<bool>,
+        name: &'static str,
+        desc: &'static str,
+        params: Value,
+        destructive: bool,
+    ) {
+        tools
+            .register_raw(
+                name,
+                desc,
+                params,
+                |_| Box::pin(async { Ok(Value::Null) }),
+                destructive,
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn clean_tool_has_no_findings() {
+        let mut tools = ToolCollection::<bool>::new();
+        raw(
+            &mut tools,
+            "search",
+            "Search documents",
+            json!({
+                "type": "object",
+                "properties": { "query": { "type": "string", "description": "Search terms" } },
+                "required": ["query"]
+            }),
+            false,
+        );
+        assert_eq!(tools.lint(), vec![]);
+    }
+
+    #[test]
+    fn tuple_params_and_missing_description() {
+        let mut tools = ToolCollection::<bool>::new();
+        raw(
+            &mut tools,
+            "add",
+            "",
+            json!({ "type": "array", "prefixItems": [{ "type": "integer" }, { "type": "integer" }] }),
+            false,
+        );
+        let codes: Vec<&str> = tools.lint().iter().map(|f| f.code).collect();
+        assert_eq!(
+            codes,
+            vec![codes::MISSING_TOOL_DESCRIPTION, codes::POSITIONAL_PARAMS]
+        );
+    }
+
+    #[test]
+    fn allow_silences_rule() {
+        let mut tools = ToolCollection::<bool>::new();
+        raw(
+            &mut tools,
+            "f",
+            "Does f",
+            json!({ "type": "object", "properties": { "x": { "type": "integer" } } }),
+            false,
+        );
+        let all = tools.lint();
+        assert!(all.iter().any(|f| f.code == codes::SINGLE_LETTER_PARAM));
+        let config = LintConfig::default()
+            .allow(codes::SINGLE_LETTER_PARAM)
+            .allow(codes::MISSING_PARAM_DESCRIPTION);
+        assert_eq!(tools.lint_with(&config), vec![]);
+    }
+
+    #[test]
+    fn unbounded_string_needs_destructive_tag() {
+        let mut tools = ToolCollection::<bool>::new();
+        let params = json!({
+            "type": "object",
+            "properties": { "path": { "type": "string", "description": "File to delete" } }
+        });
+        raw(
+            &mut tools,
+            "delete_file",
+            "Deletes a file",
+            params.clone(),
+            true,
+        );
+        raw(&mut tools, "read_file", "Reads a file", params, false);
+
+        assert_eq!(tools.lint(), vec![], "no destructive predicate by default");
+
+        let findings = tools.lint_with(&LintConfig::default().destructive(|d| *d));
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].tool, "delete_file");
+        assert_eq!(findings[0].path, "/properties/path");
+        assert_eq!(
+            findings[0].to_string(),
+            "warning[unbounded-string] tool `delete_file` at /properties/path: unbounded string on a destructive tool; add maxLength, pattern or enum"
+        );
+    }
+}
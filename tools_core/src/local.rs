@@ -0,0 +1,156 @@
+//! Tools whose closures are not `Sync`.
+//!
+//! [`ToolCollection::register`] needs `F: Sync` because concurrent calls
+//! share the closure. A closure holding a `RefCell` cache or a client that
+//! is `Send` but not `Sync` can still be registered with
+//! [`register_local`][ToolCollection::register_local], which keeps it
+//! behind an async mutex. The closure may even be `FnMut`:
+//!
+//! ```ignore
+//! let seen = RefCell::new(HashSet::new());
+//! tools.register_local(
+//!     "first_visit",
+//!     "Whether a URL is new.",
+//!     move |url: String| {
+//!         let new = seen.borrow_mut().insert(url);
+//!         async move { new }
+//!     },
+//!     (),
+//! )?;
+//! ```
+//!
+//! The mutex is held until the call's future completes, so calls to the
+//! tool run one at a time and throughput is bounded by a single call's
+//! latency; other tools are unaffected. The time calls spend waiting for
+//! their turn is reported by [`ToolCollection::queue`] and in
+//! [`introspect`][ToolCollection::introspect].
+//!
+//! The closure and its future must still be `Send`. For a resource that
+//! is not, run it on a thread of its own and register a closure that
+//! sends it requests over a channel.
+
+use std::{
+    borrow::Cow,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Instant,
+};
+
+use futures::FutureExt;
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::Value;
+use tokio::sync::{Mutex, OwnedMutexGuard};
+
+use crate::{
+    DecodedInput, DeserializationError, MetaArg, ToolCollection, ToolError, ToolFunc, ToolSchema,
+    ToolSpec, decode_typed, domain, downcast_input, schema_value,
+};
+
+/// Queue statistics for a tool registered with
+/// [`register_local`][ToolCollection::register_local].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct QueueReport {
+    /// Calls that have started running.
+    pub calls: u64,
+    /// Calls currently waiting for their turn.
+    pub waiting: u64,
+    /// Time calls spent waiting, summed.
+    pub total_wait_us: u64,
+    /// Longest time a single call waited.
+    pub max_wait_us: u64,
+}
+
+/// Wait-time counters shared by a local tool's handler and its
+/// [`ToolEntry`][crate::ToolEntry].
+#[derive(Debug, Default)]
+pub struct CallQueue {
+    calls: AtomicU64,
+    waiting: AtomicU64,
+    total_wait_us: AtomicU64,
+    max_wait_us: AtomicU64,
+}
+
+impl CallQueue {
+    /// Wait for `func`'s lock, counting the wait.
+    async fn lock<F>(&self, func: Arc<Mutex<F>>) -> OwnedMutexGuard<F> {
+        let start = Instant::now();
+        self.waiting.fetch_add(1, Ordering::Relaxed);
+        let guard = func.lock_owned().await;
+        self.waiting.fetch_sub(1, Ordering::Relaxed);
+        let waited = start.elapsed().as_micros().try_into().unwrap_or(u64::MAX);
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        self.total_wait_us.fetch_add(waited, Ordering::Relaxed);
+        self.max_wait_us.fetch_max(waited, Ordering::Relaxed);
+        guard
+    }
+
+    pub fn report(&self) -> QueueReport {
+        QueueReport {
+            calls: self.calls.load(Ordering::Relaxed),
+            waiting: self.waiting.load(Ordering::Relaxed),
+            total_wait_us: self.total_wait_us.load(Ordering::Relaxed),
+            max_wait_us: self.max_wait_us.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl<M> ToolCollection<M> {
+    /// Like [`register`][Self::register] for a closure that is not `Sync`
+    /// (or is only `FnMut`). Calls to the tool run one at a time; see
+    /// [`local`][crate::local].
+    pub fn register_local<A, I, O, F, Fut>(
+        &mut self,
+        name: impl Into<Cow<'static, str>>,
+        desc: impl Into<Cow<'static, str>>,
+        func: F,
+        meta: A,
+    ) -> Result<&mut Self, ToolError>
+    where
+        A: MetaArg<M>,
+        I: 'static + DeserializeOwned + Serialize + Send + ToolSchema,
+        O: 'static + Serialize + Send + ToolSchema,
+        F: FnMut(I) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = O> + Send + 'static,
+    {
+        let name = name.into();
+        let queue = Arc::new(CallQueue::default());
+        let func = Arc::new(Mutex::new(func));
+        let handler_queue = queue.clone();
+        let boxed = ToolFunc::new(
+            |raw: Value| -> Result<DecodedInput, ToolError> {
+                let input: I = serde_json::from_value(raw).map_err(DeserializationError::from)?;
+                Ok(Box::new(input))
+            },
+            move |input, _ctx| {
+                let queue = handler_queue.clone();
+                let func = func.clone();
+                async move {
+                    let input: I = downcast_input(input)?;
+                    let mut func = queue.lock(func).await;
+                    let output: O = (func)(input).await;
+                    domain::encode(output)
+                }
+                .boxed()
+            },
+        )
+        .with_decode_ref(decode_typed::<I>);
+
+        let spec = domain::declare_returns::<O, M>(
+            ToolSpec::new(name.clone(), desc.into())
+                .params_schema(schema_value::<I>()?)
+                .func(boxed)
+                .with_meta(meta.into_meta()),
+        );
+        self.register_spec(spec)?;
+        self.entry_mut(&name)?.queue = Some(queue);
+        Ok(self)
+    }
+
+    /// Queue statistics for a tool registered with
+    /// [`register_local`][Self::register_local].
+    pub fn queue(&self, name: &str) -> Option<QueueReport> {
+        self.get(name)?.queue.as_ref().map(|q| q.report())
+    }
+}
@@ -0,0 +1,102 @@
+//! Every `#[tool]` a crate defines, known at build time.
+//!
+//! When turned on, the `#[tool]` macro appends each tool's name, doc,
+//! parameters and schema function to `$OUT_DIR/tools-rs-manifest.jsonl`
+//! as the crate compiles. Turn it on for one crate from its build script:
+//!
+//! ```ignore
+//! // build.rs
+//! fn main() {
+//!     tools_rs::manifest::enable();
+//! }
+//! ```
+//!
+//! or for every crate in the build with the `manifest-gen` feature. Build
+//! scripts and tests run by cargo read the file with
+//! [`load_compile_time`]; xtask tooling passes its path to [`load`]:
+//!
+//! ```ignore
+//! let path = std::path::Path::new(env!("OUT_DIR")).join(tools_rs::manifest::FILE_NAME);
+//! let tools = tools_rs::manifest::load(&path)?;
+//! ```
+//!
+//! Schemas are only known at run time, so the manifest names the function
+//! producing each one rather than including it. Expansions append under a
+//! lock file; incremental builds only expand changed code, so the file
+//! keeps lines from earlier builds and [`load`] keeps the latest per tool.
+//! A tool deleted from the source stays listed until `OUT_DIR` is cleaned.
+
+use std::{collections::BTreeMap, env, fs, io::ErrorKind, path::Path};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::ToolError;
+
+/// The manifest's file name inside `OUT_DIR`.
+pub const FILE_NAME: &str = "tools-rs-manifest.jsonl";
+
+/// Environment variable that turns the manifest on for a crate's
+/// compilation.
+pub const ENV_VAR: &str = "TOOLS_RS_MANIFEST";
+
+/// One `#[tool]` function.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// Package defining the tool.
+    #[serde(rename = "crate")]
+    pub crate_name: String,
+    pub name: String,
+    pub doc: String,
+    /// The expression producing the parameter schema, e.g.
+    /// `<__TOOL_INPUT_add as ToolSchema>::schema`.
+    pub schema_fn: String,
+    /// JSON parameters in declaration order.
+    pub params: Vec<ManifestParam>,
+    /// `#[tool(key = value)]` metadata.
+    #[serde(default)]
+    pub meta: Value,
+}
+
+/// A parameter and its Rust type as written.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestParam {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: String,
+}
+
+/// From a build script: record a manifest while compiling this crate.
+pub fn enable() {
+    println!("cargo:rustc-env={ENV_VAR}=1");
+}
+
+/// Read the manifest at `path`, one entry per tool sorted by crate and
+/// name. A missing file is an empty manifest.
+pub fn load(path: &Path) -> Result<Vec<ManifestEntry>, ToolError> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(ToolError::Runtime(format!(
+                "cannot read tool manifest {}: {e}",
+                path.display()
+            )));
+        }
+    };
+    let mut tools = BTreeMap::new();
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        let entry: ManifestEntry = serde_json::from_str(line)
+            .map_err(|e| ToolError::Runtime(format!("invalid tool manifest line: {e}")))?;
+        tools.insert((entry.crate_name.clone(), entry.name.clone()), entry);
+    }
+    Ok(tools.into_values().collect())
+}
+
+/// [`load`] the manifest in `$OUT_DIR`, as cargo sets it for build
+/// scripts and tests.
+pub fn load_compile_time() -> Result<Vec<ManifestEntry>, ToolError> {
+    let out_dir = env::var_os("OUT_DIR")
+        .ok_or_else(|| ToolError::Runtime("OUT_DIR is not set".to_string()))?;
+    load(&Path::new(&out_dir).join(FILE_NAME))
+}
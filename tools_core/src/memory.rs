@@ -0,0 +1,260 @@
+//! `remember` / `recall` / `forget` / `list_memories` tools, scoped per
+//! conversation.
+//!
+//! Enabled with the `memory-tools` feature. One registration serves every
+//! conversation: each call reads its namespace from the conversation id
+//! set with [`with_conversation`] around the call.
+//!
+//! ```ignore
+//! let memory = MemoryToolSet::new(Arc::new(InMemoryStore::default()));
+//! tools.register_memory(&memory)?;
+//!
+//! let out = memory::with_conversation(conversation_id, tools.call(call)).await?;
+//! ```
+//!
+//! Storage goes through the [`KvStore`] trait; [`InMemoryStore`] keeps
+//! everything in process.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use futures::future::BoxFuture;
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+
+use crate::{ErrorClass, ToolCollection, ToolError, ffi::RawToolDef};
+
+// ============================================================================
+// STORE
+// ============================================================================
+
+/// Namespaced key-value storage behind the memory tools.
+pub trait KvStore: Send + Sync {
+    fn get<'a>(
+        &'a self,
+        namespace: &'a str,
+        key: &'a str,
+    ) -> BoxFuture<'a, Result<Option<Value>, ToolError>>;
+
+    fn set<'a>(
+        &'a self,
+        namespace: &'a str,
+        key: &'a str,
+        value: Value,
+    ) -> BoxFuture<'a, Result<(), ToolError>>;
+
+    /// Returns whether the key existed.
+    fn delete<'a>(
+        &'a self,
+        namespace: &'a str,
+        key: &'a str,
+    ) -> BoxFuture<'a, Result<bool, ToolError>>;
+
+    /// Keys of `namespace`, sorted.
+    fn list<'a>(&'a self, namespace: &'a str) -> BoxFuture<'a, Result<Vec<String>, ToolError>>;
+}
+
+/// [`KvStore`] held in process memory. Lost on restart.
+#[derive(Debug, Default)]
+pub struct InMemoryStore {
+    namespaces: Mutex<HashMap<String, BTreeMap<String, Value>>>,
+}
+
+impl InMemoryStore {
+    fn with<T>(&self, f: impl FnOnce(&mut HashMap<String, BTreeMap<String, Value>>) -> T) -> T {
+        f(&mut self
+            .namespaces
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+impl KvStore for InMemoryStore {
+    fn get<'a>(
+        &'a self,
+        namespace: &'a str,
+        key: &'a str,
+    ) -> BoxFuture<'a, Result<Option<Value>, ToolError>> {
+        let value = self.with(|ns| ns.get(namespace)?.get(key).cloned());
+        Box::pin(async move { Ok(value) })
+    }
+
+    fn set<'a>(
+        &'a self,
+        namespace: &'a str,
+        key: &'a str,
+        value: Value,
+    ) -> BoxFuture<'a, Result<(), ToolError>> {
+        self.with(|ns| {
+            ns.entry(namespace.to_string())
+                .or_default()
+                .insert(key.to_string(), value)
+        });
+        Box::pin(async { Ok(()) })
+    }
+
+    fn delete<'a>(
+        &'a self,
+        namespace: &'a str,
+        key: &'a str,
+    ) -> BoxFuture<'a, Result<bool, ToolError>> {
+        let existed = self.with(|ns| {
+            ns.get_mut(namespace)
+                .is_some_and(|keys| keys.remove(key).is_some())
+        });
+        Box::pin(async move { Ok(existed) })
+    }
+
+    fn list<'a>(&'a self, namespace: &'a str) -> BoxFuture<'a, Result<Vec<String>, ToolError>> {
+        let keys = self.with(|ns| {
+            ns.get(namespace)
+                .map(|keys| keys.keys().cloned().collect())
+                .unwrap_or_default()
+        });
+        Box::pin(async move { Ok(keys) })
+    }
+}
+
+// ============================================================================
+// CONVERSATION SCOPE
+// ============================================================================
+
+pub use crate::conversation::{conversation, with_conversation};
+
+// ============================================================================
+// TOOLS
+// ============================================================================
+
+const KEY_DESCRIPTION: &str = "Short, stable name for the fact, such as `user_name` or \
+                               `preferred_units`. Use the same key to update or look up a fact.";
+
+/// Body of one memory tool: store, namespace, arguments.
+type Handler = fn(Arc<dyn KvStore>, String, Value) -> BoxFuture<'static, Result<Value, ToolError>>;
+
+/// The memory tools over one [`KvStore`].
+#[derive(Clone)]
+pub struct MemoryToolSet {
+    store: Arc<dyn KvStore>,
+}
+
+impl MemoryToolSet {
+    pub fn new(store: Arc<dyn KvStore>) -> Self {
+        Self { store }
+    }
+
+    /// Definitions of the four tools, ready for
+    /// [`RawToolDef::into_spec`].
+    pub fn defs(&self) -> Vec<RawToolDef> {
+        let key_only = json!({
+            "type": "object",
+            "properties": { "key": { "type": "string", "description": KEY_DESCRIPTION } },
+            "required": ["key"]
+        });
+        vec![
+            self.def(
+                "remember",
+                "Save a fact about the user or the task so you can use it later in this \
+                 conversation. Saving under an existing key replaces the old value.",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "key": { "type": "string", "description": KEY_DESCRIPTION },
+                        "value": {
+                            "description": "The fact to save: a string, number or small JSON object."
+                        }
+                    },
+                    "required": ["key", "value"]
+                }),
+                |store, ns, args| {
+                    Box::pin(async move {
+                        let key = key_arg(&args)?;
+                        store.set(&ns, key, args["value"].clone()).await?;
+                        Ok(json!({ "key": key, "stored": true }))
+                    })
+                },
+            ),
+            self.def(
+                "recall",
+                "Look up a fact saved earlier in this conversation with `remember`. Returns \
+                 `found: false` if nothing is saved under the key.",
+                key_only.clone(),
+                |store, ns, args| {
+                    Box::pin(async move {
+                        let key = key_arg(&args)?;
+                        Ok(match store.get(&ns, key).await? {
+                            Some(value) => json!({ "key": key, "found": true, "value": value }),
+                            None => json!({ "key": key, "found": false }),
+                        })
+                    })
+                },
+            ),
+            self.def(
+                "forget",
+                "Delete a saved fact, e.g. when the user asks you to forget it or it is no \
+                 longer true.",
+                key_only,
+                |store, ns, args| {
+                    Box::pin(async move {
+                        let key = key_arg(&args)?;
+                        let deleted = store.delete(&ns, key).await?;
+                        Ok(json!({ "key": key, "deleted": deleted }))
+                    })
+                },
+            ),
+            self.def(
+                "list_memories",
+                "List the keys of all facts saved in this conversation. Use `recall` to read \
+                 one.",
+                json!({ "type": "object", "properties": {}, "required": [] }),
+                |store, ns, _args| {
+                    Box::pin(async move { Ok(json!({ "keys": store.list(&ns).await? })) })
+                },
+            ),
+        ]
+    }
+
+    fn def(&self, name: &str, description: &str, parameters: Value, run: Handler) -> RawToolDef {
+        let store = self.store.clone();
+        RawToolDef {
+            name: name.to_string(),
+            description: description.to_string(),
+            parameters,
+            meta: json!({}),
+            func: Box::new(move |args| {
+                let Some(ns) = conversation() else {
+                    return Box::pin(async {
+                        Err(ToolError::Runtime(
+                            "memory tools need a conversation id; wrap the call in \
+                             `memory::with_conversation`"
+                                .to_string(),
+                        ))
+                    });
+                };
+                run(store.clone(), ns, args)
+            }),
+        }
+    }
+}
+
+fn key_arg(args: &Value) -> Result<&str, ToolError> {
+    match args["key"].as_str() {
+        Some(key) if !key.is_empty() => Ok(key),
+        _ => Err(ToolError::classified(
+            ErrorClass::InvalidInput,
+            "`key` must be a non-empty string",
+        )),
+    }
+}
+
+impl<M: DeserializeOwned> ToolCollection<M> {
+    /// Register the memory tools. Each tool's metadata is deserialized
+    /// from `{}`.
+    pub fn register_memory(&mut self, memory: &MemoryToolSet) -> Result<&mut Self, ToolError> {
+        for def in memory.defs() {
+            self.register_spec(def.into_spec()?)?;
+        }
+        Ok(self)
+    }
+}
@@ -0,0 +1,184 @@
+//! Accepting argument shapes a tool used to take.
+//!
+//! Renaming a parameter breaks conversations resumed from old history:
+//! the model keeps sending the old shape. A migration rewrites old
+//! arguments into the current shape before they are decoded:
+//!
+//! ```ignore
+//! tools.add_migration("search", "q-to-query", |mut args: Value| {
+//!     if let Some(q) = args.as_object_mut().and_then(|a| a.remove("q")) {
+//!         args["query"] = q;
+//!     }
+//!     Ok(args)
+//! })?;
+//! ```
+//!
+//! A tool's migrations run in the order they were added. Under the
+//! default [`MigrationMode::OnFailure`] they only run when the arguments
+//! don't fit the tool as sent: they have keys the schema doesn't declare,
+//! or fail to decode. Arguments in the current shape cost one extra
+//! decode and nothing else; tools without migrations pay nothing.
+//! [`MigrationMode::Always`] runs them on every call instead.
+//!
+//! The names of migrations that changed the arguments are listed in
+//! [`FunctionResponse::migrations`][crate::FunctionResponse::migrations].
+//!
+//! Key renames can also come from configuration, as [`MigrationSpec`]s:
+//!
+//! ```ignore
+//! let specs: Vec<MigrationSpec> = serde_json::from_str(
+//!     r#"[{ "tool": "search", "name": "q-to-query", "rename": { "q": "query" } }]"#,
+//! )?;
+//! tools.add_migrations(specs)?;
+//! ```
+
+use std::{borrow::Cow, collections::BTreeMap, fmt, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{ToolCollection, ToolEntry, ToolError, unknown::unknown_keys};
+
+/// When a tool's migrations run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationMode {
+    /// Only for arguments that don't fit the tool as sent.
+    #[default]
+    OnFailure,
+    /// On every call.
+    Always,
+}
+
+/// A named rewrite of a tool's arguments.
+pub struct Migration {
+    pub name: String,
+    func: Box<dyn Fn(Value) -> Result<Value, ToolError> + Send + Sync>,
+}
+
+impl fmt::Debug for Migration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Migration")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A migration described by data: top-level keys of `tool`'s arguments
+/// renamed from old to new.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MigrationSpec {
+    pub tool: String,
+    pub name: String,
+    #[serde(default)]
+    pub rename: BTreeMap<String, String>,
+}
+
+/// A migration moving each `old` key to `new`, unless `new` is already
+/// set.
+pub fn rename_keys(
+    renames: BTreeMap<String, String>,
+) -> impl Fn(Value) -> Result<Value, ToolError> + Send + Sync + 'static {
+    move |mut arguments| {
+        if let Some(object) = arguments.as_object_mut() {
+            for (old, new) in &renames {
+                if object.contains_key(new) {
+                    continue;
+                }
+                if let Some(value) = object.remove(old) {
+                    object.insert(new.clone(), value);
+                }
+            }
+        }
+        Ok(arguments)
+    }
+}
+
+impl<M> ToolCollection<M> {
+    /// Add a migration to the end of `tool`'s list. See
+    /// [`migrate`][crate::migrate].
+    pub fn add_migration(
+        &mut self,
+        tool: &str,
+        name: impl Into<String>,
+        migration: impl Fn(Value) -> Result<Value, ToolError> + Send + Sync + 'static,
+    ) -> Result<&mut Self, ToolError> {
+        self.entry_mut(tool)?.migrations.push(Arc::new(Migration {
+            name: name.into(),
+            func: Box::new(migration),
+        }));
+        Ok(self)
+    }
+
+    /// Add the key renames in `specs`, in order. Fails on the first spec
+    /// naming an unknown tool, keeping those added before it.
+    pub fn add_migrations(
+        &mut self,
+        specs: impl IntoIterator<Item = MigrationSpec>,
+    ) -> Result<&mut Self, ToolError> {
+        for spec in specs {
+            self.add_migration(&spec.tool, spec.name, rename_keys(spec.rename))?;
+        }
+        Ok(self)
+    }
+
+    pub fn set_migration_mode(&mut self, mode: MigrationMode) {
+        self.migration_mode = mode;
+    }
+
+    pub fn migration_mode(&self) -> MigrationMode {
+        self.migration_mode
+    }
+
+    /// `arguments` after `entry`'s migrations, with the names of those
+    /// that changed them.
+    pub(crate) fn migrate<'a>(
+        &self,
+        entry: &ToolEntry<M>,
+        arguments: Cow<'a, Value>,
+    ) -> Result<(Cow<'a, Value>, Vec<String>), ToolError> {
+        if entry.migrations.is_empty() {
+            return Ok((arguments, Vec::new()));
+        }
+        if self.migration_mode == MigrationMode::OnFailure
+            && unknown_keys(&entry.decl.parameters, &arguments).is_empty()
+            && entry.func.decode_ref(&arguments).is_ok()
+        {
+            return Ok((arguments, Vec::new()));
+        }
+        let mut arguments = arguments.into_owned();
+        let mut applied = Vec::new();
+        for migration in &entry.migrations {
+            let migrated = (migration.func)(arguments.clone())?;
+            if migrated != arguments {
+                applied.push(migration.name.clone());
+                arguments = migrated;
+            }
+        }
+        Ok((Cow::Owned(arguments), applied))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn renames_keep_newer_keys() {
+        let rename = rename_keys(BTreeMap::from([
+            ("q".to_string(), "query".to_string()),
+            ("n".to_string(), "limit".to_string()),
+        ]));
+        assert_eq!(
+            rename(json!({ "q": "rust", "n": 3 })).unwrap(),
+            json!({ "query": "rust", "limit": 3 })
+        );
+        assert_eq!(
+            rename(json!({ "q": "old", "query": "new" })).unwrap(),
+            json!({ "q": "old", "query": "new" })
+        );
+        assert_eq!(rename(json!("q")).unwrap(), json!("q"));
+    }
+}
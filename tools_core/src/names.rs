@@ -0,0 +1,375 @@
+//! Tool names, aliases and how loosely they match.
+//!
+//! Every name a collection answers to lives in its [`NameRegistry`]: the
+//! canonical name of each tool plus any aliases added with
+//! [`ToolCollection::add_alias`]. Under a looser [`NameNormalization`],
+//! models that write `getWeather` or `Get-Weather` still reach
+//! `get_weather`.
+//!
+//! The registry never holds two names that normalize to the same key
+//! unless they belong to the same tool, so every lookup has at most one
+//! answer. Registering a tool or alias that would break this fails with
+//! [`ToolError::NameConflict`], naming the entry it collides with, and so
+//! does switching to a normalization under which existing names collide.
+//!
+//! ```ignore
+//! tools.set_name_normalization(NameNormalization::IgnoreCaseAndSeparators)?;
+//! tools.add_alias("weather", "get_weather")?;
+//! assert_eq!(tools.resolve("GetWeather"), tools.resolve("get_weather"));
+//! ```
+
+use std::{borrow::Cow, collections::HashMap, fmt};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{NameMap, ToolCollection, ToolError, ToolId};
+
+/// How names are compared when looking up a tool. Exact matches always
+/// win, so the hot path is one hash lookup whatever the setting.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NameNormalization {
+    /// Names must match byte for byte.
+    #[default]
+    Exact,
+    /// ASCII case is ignored: `Get_Weather` finds `get_weather`.
+    IgnoreCase,
+    /// ASCII case, `_`, `-` and `.` are ignored: `getWeather` and
+    /// `get-weather` find `get_weather`.
+    IgnoreCaseAndSeparators,
+}
+
+impl NameNormalization {
+    /// The key `name` is compared by.
+    pub fn normalize<'a>(&self, name: &'a str) -> Cow<'a, str> {
+        match self {
+            Self::Exact => Cow::Borrowed(name),
+            Self::IgnoreCase => Cow::Owned(name.to_ascii_lowercase()),
+            Self::IgnoreCaseAndSeparators => Cow::Owned(
+                name.chars()
+                    .filter(|c| !matches!(c, '_' | '-' | '.'))
+                    .map(|c| c.to_ascii_lowercase())
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl fmt::Display for NameNormalization {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Exact => "exact",
+            Self::IgnoreCase => "ignore case",
+            Self::IgnoreCaseAndSeparators => "ignore case and separators",
+        })
+    }
+}
+
+/// A name that is already taken, as reported by
+/// [`ToolError::NameConflict`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameClash {
+    /// The registered name, canonical or alias.
+    pub name: String,
+    /// The tool it belongs to.
+    pub tool: Cow<'static, str>,
+}
+
+/// The names a collection answers to.
+#[derive(Debug, Clone, Default)]
+pub struct NameRegistry {
+    canonical: NameMap,
+    aliases: HashMap<String, ToolId>,
+    normalization: NameNormalization,
+    /// Normalized key → the one name holding it and its tool. Only kept
+    /// when `normalization` isn't `Exact`.
+    normalized: HashMap<String, (String, ToolId)>,
+}
+
+impl NameRegistry {
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        Self {
+            canonical: NameMap::with_capacity_and_hasher(capacity, Default::default()),
+            ..Self::default()
+        }
+    }
+
+    pub fn normalization(&self) -> NameNormalization {
+        self.normalization
+    }
+
+    /// The tool answering to `name`: by canonical name, then by alias,
+    /// then by normalized form.
+    pub fn resolve(&self, name: &str) -> Option<ToolId> {
+        if let Some(&id) = self.canonical.get(name) {
+            return Some(id);
+        }
+        if let Some(&id) = self.aliases.get(name) {
+            return Some(id);
+        }
+        if self.normalization == NameNormalization::Exact {
+            return None;
+        }
+        let key = self.normalization.normalize(name);
+        self.normalized.get(key.as_ref()).map(|&(_, id)| id)
+    }
+
+    /// The tool whose canonical name is exactly `name`.
+    pub fn canonical(&self, name: &str) -> Option<ToolId> {
+        self.canonical.get(name).copied()
+    }
+
+    /// Aliases and the tools they point to, in no particular order.
+    pub fn aliases(&self) -> impl Iterator<Item = (&str, ToolId)> {
+        self.aliases.iter().map(|(alias, &id)| (alias.as_str(), id))
+    }
+
+    /// Number of canonical names.
+    pub fn len(&self) -> usize {
+        self.canonical.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.canonical.is_empty()
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        self.canonical.capacity()
+    }
+
+    pub(crate) fn shrink_to_fit(&mut self) {
+        self.canonical.shrink_to_fit();
+        self.aliases.shrink_to_fit();
+        self.normalized.shrink_to_fit();
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.canonical.clear();
+        self.aliases.clear();
+        self.normalized.clear();
+    }
+
+    /// Every name held, with its tool.
+    fn names(&self) -> impl Iterator<Item = (&str, ToolId)> {
+        self.canonical
+            .iter()
+            .map(|(name, &id)| (&**name, id))
+            .chain(self.aliases())
+    }
+
+    /// The name `name` would collide with if it were added for `id`.
+    fn clash(&self, name: &str, id: Option<ToolId>) -> Option<(String, ToolId)> {
+        let taken = if self.normalization == NameNormalization::Exact {
+            self.canonical
+                .get(name)
+                .or_else(|| self.aliases.get(name))
+                .map(|&other| (name.to_string(), other))
+        } else {
+            self.normalized
+                .get(self.normalization.normalize(name).as_ref())
+                .cloned()
+        };
+        taken.filter(|&(_, other)| Some(other) != id)
+    }
+
+    fn index(&mut self, name: &str, id: ToolId) {
+        if self.normalization != NameNormalization::Exact {
+            let key = self.normalization.normalize(name).into_owned();
+            self.normalized
+                .entry(key)
+                .or_insert_with(|| (name.to_string(), id));
+        }
+    }
+
+    fn unindex(&mut self, name: &str) {
+        if self.normalization == NameNormalization::Exact {
+            return;
+        }
+        let key = self.normalization.normalize(name);
+        if self
+            .normalized
+            .get(key.as_ref())
+            .is_some_and(|(held, _)| held == name)
+        {
+            self.normalized.remove(key.as_ref());
+            // Another name of the same tool may share the key.
+            let heir = self
+                .names()
+                .find(|&(other, _)| other != name && self.normalization.normalize(other) == key)
+                .map(|(other, id)| (other.to_string(), id));
+            if let Some(heir) = heir {
+                self.normalized.insert(key.into_owned(), heir);
+            }
+        }
+    }
+
+    /// Check that `name` can be the canonical name of `id`, where `id` is
+    /// `None` for a tool not registered yet.
+    pub(crate) fn check(&self, name: &str, id: Option<ToolId>) -> Result<(), NameClash> {
+        match self.clash(name, id) {
+            Some((held, other)) => Err(NameClash {
+                tool: self.tool_name(other),
+                name: held,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) fn insert(&mut self, name: Cow<'static, str>, id: ToolId) {
+        self.index(&name, id);
+        self.canonical.insert(name, id);
+    }
+
+    /// Remove the canonical name `name` and every alias of its tool.
+    pub(crate) fn remove(&mut self, name: &str) -> Option<ToolId> {
+        let id = self.canonical.remove(name)?;
+        self.unindex(name);
+        let aliases: Vec<String> = self
+            .aliases
+            .iter()
+            .filter(|&(_, &other)| other == id)
+            .map(|(alias, _)| alias.clone())
+            .collect();
+        for alias in aliases {
+            self.aliases.remove(&alias);
+            self.unindex(&alias);
+        }
+        Some(id)
+    }
+
+    fn tool_name(&self, id: ToolId) -> Cow<'static, str> {
+        self.canonical
+            .iter()
+            .find(|&(_, &other)| other == id)
+            .map_or(Cow::Borrowed(""), |(name, _)| name.clone())
+    }
+
+    fn add_alias(&mut self, alias: &str, id: ToolId) -> Result<(), NameClash> {
+        self.check(alias, Some(id))?;
+        if self.canonical.contains_key(alias) {
+            // Already the tool's own name.
+            return Ok(());
+        }
+        self.aliases.insert(alias.to_string(), id);
+        self.index(alias, id);
+        Ok(())
+    }
+
+    fn remove_alias(&mut self, alias: &str) -> Option<ToolId> {
+        let id = self.aliases.remove(alias)?;
+        self.unindex(alias);
+        Some(id)
+    }
+
+    /// Rebuild the normalized index under `normalization`, leaving the
+    /// registry unchanged if two tools' names would collide.
+    fn set_normalization(
+        &mut self,
+        normalization: NameNormalization,
+    ) -> Result<(), (String, NameClash)> {
+        let mut normalized: HashMap<String, (String, ToolId)> = HashMap::new();
+        if normalization != NameNormalization::Exact {
+            let mut names: Vec<(&str, ToolId)> = self.names().collect();
+            // Deterministic choice of which name holds a shared key.
+            names.sort_unstable();
+            for (name, id) in names {
+                let key = normalization.normalize(name).into_owned();
+                match normalized.get(&key) {
+                    Some((held, other)) if *other != id => {
+                        return Err((
+                            name.to_string(),
+                            NameClash {
+                                name: held.clone(),
+                                tool: self.tool_name(*other),
+                            },
+                        ));
+                    }
+                    Some(_) => {}
+                    None => {
+                        normalized.insert(key, (name.to_string(), id));
+                    }
+                }
+            }
+        }
+        self.normalization = normalization;
+        self.normalized = normalized;
+        Ok(())
+    }
+}
+
+fn conflict(name: &str, clash: NameClash, normalization: NameNormalization) -> ToolError {
+    ToolError::NameConflict {
+        name: name.to_string(),
+        existing: clash.name,
+        tool: clash.tool,
+        normalization,
+    }
+}
+
+impl<M> ToolCollection<M> {
+    pub fn names(&self) -> &NameRegistry {
+        &self.names
+    }
+
+    /// Check that a tool can be registered under `name`, or re-registered
+    /// in place if the name is already its own.
+    pub(crate) fn check_name(&self, name: &str) -> Result<(), ToolError> {
+        self.names
+            .check(name, self.names.canonical(name))
+            .map_err(|clash| conflict(name, clash, self.names.normalization))
+    }
+
+    /// Make `alias` another name of the tool `name`. Fails with
+    /// [`ToolError::NameConflict`] if `alias`, once normalized, is already
+    /// a name of another tool. Aliases are dropped with their tool.
+    pub fn add_alias(&mut self, alias: &str, name: &str) -> Result<(), ToolError> {
+        let Some(id) = self.names.canonical(name) else {
+            return Err(ToolError::FunctionNotFound {
+                name: Cow::Owned(name.to_string()),
+            });
+        };
+        let normalization = self.names.normalization;
+        self.names
+            .add_alias(alias, id)
+            .map_err(|clash| conflict(alias, clash, normalization))
+    }
+
+    /// Remove an alias added with [`add_alias`][Self::add_alias].
+    pub fn remove_alias(&mut self, alias: &str) -> Result<(), ToolError> {
+        self.names
+            .remove_alias(alias)
+            .map(|_| ())
+            .ok_or_else(|| ToolError::FunctionNotFound {
+                name: Cow::Owned(alias.to_string()),
+            })
+    }
+
+    /// Change how names are matched. Fails with
+    /// [`ToolError::NameConflict`], keeping the current setting, if two
+    /// tools' names or aliases would become indistinguishable.
+    pub fn set_name_normalization(
+        &mut self,
+        normalization: NameNormalization,
+    ) -> Result<(), ToolError> {
+        self.names
+            .set_normalization(normalization)
+            .map_err(|(name, clash)| conflict(&name, clash, normalization))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizations() {
+        let n = NameNormalization::IgnoreCaseAndSeparators;
+        assert_eq!(n.normalize("get_weather"), n.normalize("getWeather"));
+        assert_eq!(n.normalize("Get-Weather"), "getweather");
+        assert_eq!(
+            NameNormalization::IgnoreCase.normalize("Get_Weather"),
+            "get_weather"
+        );
+        assert_eq!(NameNormalization::Exact.normalize("Get"), "Get");
+    }
+}
@@ -0,0 +1,710 @@
+//! Import tools from an OpenAPI 3 spec as remote HTTP proxies.
+//!
+//! Enabled with the `openapi` feature. [`import`] turns every operation of
+//! a spec into a [`ToolSpec`]: the name comes from `operationId`, the
+//! description from `summary`, and the parameter schema is synthesized
+//! from the path, query and header parameters plus a `body` property for
+//! a JSON request body.
+//!
+//! tools-rs ships no HTTP client. A [`ToolSpec`] only knows how to turn
+//! call arguments into an [`HttpRequest`]; you supply the function that
+//! sends it:
+//!
+//! ```ignore
+//! let spec: Value = serde_json::from_str(&std::fs::read_to_string("petstore.json")?)?;
+//! let specs = openapi::import(&spec, "https://petstore3.swagger.io/api/v3", Auth::None)?;
+//!
+//! let client = reqwest::Client::new();
+//! let send: Arc<HttpSend> = Arc::new(move |req: HttpRequest| {
+//!     let client = client.clone();
+//!     Box::pin(async move {
+//!         let mut builder = client.request(req.method.as_str().parse().unwrap(), &req.url);
+//!         for (k, v) in &req.headers {
+//!             builder = builder.header(k, v);
+//!         }
+//!         if let Some(body) = &req.body {
+//!             builder = builder.json(body);
+//!         }
+//!         let resp = builder.send().await.map_err(|e| ToolError::Runtime(e.to_string()))?;
+//!         resp.json().await.map_err(|e| ToolError::Runtime(e.to_string()))
+//!     })
+//! });
+//!
+//! let mut tools = ToolCollection::new();
+//! tools.register_openapi(specs, send)?;
+//! ```
+//!
+//! Only `application/json` (and `+json`) request bodies are supported. An
+//! operation whose only body is another content type is imported without a
+//! body when the body is optional, and rejected when it is required.
+
+use std::{collections::HashSet, sync::Arc};
+
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::{Map, Value, json};
+
+pub use crate::http::{Auth, HttpRequest, HttpSend, Method};
+use crate::{
+    ToolCollection, ToolError,
+    ffi::RawToolDef,
+};
+
+/// Property name the request body is exposed under in the parameter schema.
+pub const BODY_PARAM: &str = "body";
+
+const METHODS: [(&str, Method); 7] = [
+    ("get", Method::Get),
+    ("put", Method::Put),
+    ("post", Method::Post),
+    ("delete", Method::Delete),
+    ("patch", Method::Patch),
+    ("head", Method::Head),
+    ("options", Method::Options),
+];
+
+// ============================================================================
+// ERRORS
+// ============================================================================
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum ImportError {
+    #[error("unsupported OpenAPI version `{0}` (only 3.x is supported)")]
+    UnsupportedVersion(String),
+
+    #[error("invalid spec: {0}")]
+    InvalidSpec(String),
+
+    #[error("unresolved reference `{0}`")]
+    UnresolvedRef(String),
+
+    #[error(
+        "operation `{operation}`: parameter `{name}` is in `{location}`, which is not supported"
+    )]
+    UnsupportedParameter {
+        operation: String,
+        name: String,
+        location: String,
+    },
+
+    #[error(
+        "operation `{operation}`: required request body has no JSON content type (found {content_types:?})"
+    )]
+    UnsupportedContentType {
+        operation: String,
+        content_types: Vec<String>,
+    },
+
+    #[error("operation `{operation}`: more than one argument is named `{name}`")]
+    NameClash { operation: String, name: String },
+
+    #[error("two operations map to the tool name `{0}`")]
+    DuplicateName(String),
+}
+
+// ============================================================================
+// REQUEST TEMPLATE
+// ============================================================================
+
+/// Where a parameter goes in the outgoing request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ParamLocation {
+    Path,
+    Query,
+    Header,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ParamBinding {
+    pub name: String,
+    #[serde(rename = "in")]
+    pub location: ParamLocation,
+    pub required: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BodyBinding {
+    pub content_type: String,
+    pub required: bool,
+}
+
+/// Everything needed to turn call arguments into an [`HttpRequest`].
+#[derive(Debug, Clone, Serialize)]
+pub struct RequestTemplate {
+    pub method: Method,
+    pub base_url: String,
+    /// Path with `{param}` placeholders, e.g. `/pet/{petId}`.
+    pub path: String,
+    pub params: Vec<ParamBinding>,
+    pub body: Option<BodyBinding>,
+    #[serde(skip)]
+    pub auth: Auth,
+}
+
+impl RequestTemplate {
+    /// Fill the template from a tool call's arguments.
+    pub fn build(&self, args: &Value) -> Result<HttpRequest, ToolError> {
+        let empty = Map::new();
+        let args = match args {
+            Value::Object(obj) => obj,
+            Value::Null => &empty,
+            other => {
+                return Err(ToolError::Runtime(format!(
+                    "expected an arguments object, got `{other}`"
+                )));
+            }
+        };
+
+        let mut path = self.path.clone();
+        let mut query = Vec::new();
+        let mut headers = Vec::new();
+
+        for param in &self.params {
+            let value = match args.get(&param.name) {
+                None | Some(Value::Null) if param.required => {
+                    return Err(ToolError::Runtime(format!(
+                        "missing required parameter `{}`",
+                        param.name
+                    )));
+                }
+                None | Some(Value::Null) => continue,
+                Some(v) => v,
+            };
+            match param.location {
+                ParamLocation::Path => {
+                    let placeholder = format!("{{{}}}", param.name);
+                    path = path.replace(&placeholder, &encode(&joined(value)));
+                }
+                ParamLocation::Query => match value {
+                    // Default `form` style with `explode`: `?tag=a&tag=b`.
+                    Value::Array(items) => {
+                        for item in items {
+                            query.push(format!(
+                                "{}={}",
+                                encode(&param.name),
+                                encode(&scalar(item))
+                            ));
+                        }
+                    }
+                    other => query.push(format!(
+                        "{}={}",
+                        encode(&param.name),
+                        encode(&scalar(other))
+                    )),
+                },
+                ParamLocation::Header => headers.push((param.name.clone(), joined(value))),
+            }
+        }
+
+        let mut url = format!("{}{}", self.base_url.trim_end_matches('/'), path);
+        if !query.is_empty() {
+            url.push('?');
+            url.push_str(&query.join("&"));
+        }
+
+        let body = match &self.body {
+            Some(binding) => match args.get(BODY_PARAM) {
+                Some(body) if !body.is_null() => {
+                    headers.push(("Content-Type".to_string(), binding.content_type.clone()));
+                    Some(body.clone())
+                }
+                _ if binding.required => {
+                    return Err(ToolError::Runtime(
+                        "missing required request body `body`".to_string(),
+                    ));
+                }
+                _ => None,
+            },
+            None => None,
+        };
+
+        self.auth.apply(&mut headers);
+
+        Ok(HttpRequest {
+            method: self.method,
+            url,
+            headers,
+            body,
+        })
+    }
+}
+
+/// Query values: strings verbatim, everything else as JSON text.
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Path and header values: arrays use the `simple` style (`a,b,c`).
+fn joined(value: &Value) -> String {
+    match value {
+        Value::Array(items) => items.iter().map(scalar).collect::<Vec<_>>().join(","),
+        other => scalar(other),
+    }
+}
+
+/// Percent-encode everything outside RFC 3986's unreserved set.
+fn encode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    out
+}
+
+// ============================================================================
+// TOOL SPEC
+// ============================================================================
+
+/// One imported operation: the tool declaration plus its request template.
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolSpec {
+    pub name: String,
+    pub description: String,
+    pub parameters: Value,
+    pub request: RequestTemplate,
+}
+
+impl ToolSpec {
+    /// Turn the spec into a [`RawToolDef`] whose function builds the request
+    /// and hands it to `send`. `meta` is `{"method": ..., "path": ...}` so a
+    /// typed metadata struct can, say, gate non-`GET` operations.
+    pub fn into_raw(self, send: Arc<HttpSend>) -> RawToolDef {
+        let meta = json!({
+            "method": self.request.method,
+            "path": self.request.path,
+        });
+        let request = self.request;
+        RawToolDef {
+            name: self.name,
+            description: self.description,
+            parameters: self.parameters,
+            meta,
+            func: Box::new(move |args| match request.build(&args) {
+                Ok(req) => send(req),
+                Err(e) => Box::pin(async move { Err(e) }),
+            }),
+        }
+    }
+}
+
+impl<M: DeserializeOwned> ToolCollection<M> {
+    /// Register every imported operation as a remote tool that dispatches
+    /// through `send`. Each tool's metadata is deserialized from
+    /// `{"method": ..., "path": ...}`.
+    pub fn register_openapi(
+        &mut self,
+        specs: Vec<ToolSpec>,
+        send: Arc<HttpSend>,
+    ) -> Result<&mut Self, ToolError> {
+        for spec in specs {
+            self.register_spec(spec.into_raw(send.clone()).into_spec()?)?;
+        }
+        Ok(self)
+    }
+}
+
+// ============================================================================
+// IMPORT
+// ============================================================================
+
+/// Build one [`ToolSpec`] per operation in `spec`, in path order.
+pub fn import(spec: &Value, base_url: &str, auth: Auth) -> Result<Vec<ToolSpec>, ImportError> {
+    let version = spec["openapi"].as_str().unwrap_or_default();
+    if !version.starts_with("3.") {
+        return Err(ImportError::UnsupportedVersion(version.to_string()));
+    }
+    let paths = spec["paths"]
+        .as_object()
+        .ok_or_else(|| ImportError::InvalidSpec("`paths` must be an object".to_string()))?;
+
+    let mut specs = Vec::new();
+    let mut seen = HashSet::new();
+
+    for (path, item) in paths {
+        let item = resolve(spec, item, &[])?;
+        let shared = item["parameters"].as_array().cloned().unwrap_or_default();
+
+        for (key, method) in METHODS {
+            let Some(op) = item.get(key) else {
+                continue;
+            };
+            let tool = import_operation(spec, path, method, op, &shared, base_url, &auth)?;
+            if !seen.insert(tool.name.clone()) {
+                return Err(ImportError::DuplicateName(tool.name));
+            }
+            specs.push(tool);
+        }
+    }
+
+    Ok(specs)
+}
+
+fn import_operation(
+    spec: &Value,
+    path: &str,
+    method: Method,
+    op: &Value,
+    shared: &[Value],
+    base_url: &str,
+    auth: &Auth,
+) -> Result<ToolSpec, ImportError> {
+    let name = match op["operationId"].as_str() {
+        Some(id) => sanitize_name(id),
+        None => sanitize_name(&format!("{}_{}", method.as_str().to_lowercase(), path)),
+    };
+    let description = op["summary"]
+        .as_str()
+        .or_else(|| op["description"].as_str())
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{method} {path}"));
+
+    // Operation-level parameters override path-level ones with the same
+    // name and location.
+    let mut raw_params: Vec<Value> = Vec::new();
+    for param in shared
+        .iter()
+        .chain(op["parameters"].as_array().into_iter().flatten())
+    {
+        let param = resolve(spec, param, &[])?;
+        raw_params.retain(|p| p["name"] != param["name"] || p["in"] != param["in"]);
+        raw_params.push(param);
+    }
+
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+    let mut params = Vec::new();
+
+    for param in raw_params {
+        let pname = param["name"]
+            .as_str()
+            .ok_or_else(|| ImportError::InvalidSpec(format!("{name}: parameter without a name")))?
+            .to_string();
+        let location = match param["in"].as_str() {
+            Some("path") => ParamLocation::Path,
+            Some("query") => ParamLocation::Query,
+            Some("header") => ParamLocation::Header,
+            other => {
+                return Err(ImportError::UnsupportedParameter {
+                    operation: name,
+                    name: pname,
+                    location: other.unwrap_or("<missing>").to_string(),
+                });
+            }
+        };
+        // Path parameters are always required, whatever the spec says.
+        let is_required = location == ParamLocation::Path || param["required"] == true;
+
+        let mut schema = match param.get("schema") {
+            Some(s) => resolve(spec, s, &[])?,
+            None => json!({ "type": "string" }),
+        };
+        if let (Some(desc), Value::Object(obj)) = (param["description"].as_str(), &mut schema) {
+            obj.entry("description").or_insert_with(|| json!(desc));
+        }
+
+        if properties.insert(pname.clone(), schema).is_some() {
+            return Err(ImportError::NameClash {
+                operation: name,
+                name: pname,
+            });
+        }
+        if is_required {
+            required.push(json!(pname));
+        }
+        params.push(ParamBinding {
+            name: pname,
+            location,
+            required: is_required,
+        });
+    }
+
+    let body = match op.get("requestBody") {
+        None => None,
+        Some(body) => {
+            let body = resolve(spec, body, &[])?;
+            let body_required = body["required"] == true;
+            let content = body["content"].as_object().cloned().unwrap_or_default();
+            let json_type = content
+                .keys()
+                .find(|ct| is_json(ct))
+                .or_else(|| content.keys().find(|ct| ct.ends_with("+json")));
+
+            match json_type {
+                Some(ct) => {
+                    if properties.contains_key(BODY_PARAM) {
+                        return Err(ImportError::NameClash {
+                            operation: name,
+                            name: BODY_PARAM.to_string(),
+                        });
+                    }
+                    let mut schema = match content[ct].get("schema") {
+                        Some(s) => resolve(spec, s, &[])?,
+                        None => json!({}),
+                    };
+                    if let (Some(desc), Value::Object(obj)) =
+                        (body["description"].as_str(), &mut schema)
+                    {
+                        obj.entry("description").or_insert_with(|| json!(desc));
+                    }
+                    properties.insert(BODY_PARAM.to_string(), schema);
+                    if body_required {
+                        required.push(json!(BODY_PARAM));
+                    }
+                    Some(BodyBinding {
+                        content_type: ct.clone(),
+                        required: body_required,
+                    })
+                }
+                None if body_required => {
+                    return Err(ImportError::UnsupportedContentType {
+                        operation: name,
+                        content_types: content.keys().cloned().collect(),
+                    });
+                }
+                None => None,
+            }
+        }
+    };
+
+    Ok(ToolSpec {
+        name,
+        description,
+        parameters: json!({
+            "type": "object",
+            "properties": properties,
+            "required": required,
+        }),
+        request: RequestTemplate {
+            method,
+            base_url: base_url.to_string(),
+            path: path.to_string(),
+            params,
+            body,
+            auth: auth.clone(),
+        },
+    })
+}
+
+fn is_json(content_type: &str) -> bool {
+    content_type
+        .split(';')
+        .next()
+        .is_some_and(|ct| ct.trim() == "application/json")
+}
+
+/// Tool names are limited to `[A-Za-z0-9_-]{1,64}` by most providers.
+fn sanitize_name(raw: &str) -> String {
+    let replaced: String = raw
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let mut name = match replaced.trim_matches('_') {
+        "" => "operation".to_string(),
+        trimmed => trimmed.to_string(),
+    };
+    name.truncate(64);
+    name
+}
+
+/// Inline every local `$ref` (`#/components/...`) reachable from `value`.
+/// Recursive references become `{}` at the point where they would loop.
+/// OpenAPI-only `xml` annotations are dropped.
+fn resolve(spec: &Value, value: &Value, stack: &[&str]) -> Result<Value, ImportError> {
+    match value {
+        Value::Object(obj) => {
+            if let Some(target) = obj.get("$ref").and_then(Value::as_str) {
+                if stack.contains(&target) {
+                    return Ok(json!({}));
+                }
+                let found = target
+                    .strip_prefix('#')
+                    .and_then(|pointer| spec.pointer(pointer))
+                    .ok_or_else(|| ImportError::UnresolvedRef(target.to_string()))?;
+                let mut nested = stack.to_vec();
+                nested.push(target);
+                let mut inlined = resolve(spec, found, &nested)?;
+
+                // Sibling keywords next to `$ref` (e.g. a description) win.
+                if let Value::Object(out) = &mut inlined {
+                    for (k, v) in obj {
+                        if k != "$ref" {
+                            out.insert(k.clone(), resolve(spec, v, stack)?);
+                        }
+                    }
+                }
+                return Ok(inlined);
+            }
+
+            let mut out = Map::new();
+            for (k, v) in obj {
+                if k == "xml" {
+                    continue;
+                }
+                out.insert(k.clone(), resolve(spec, v, stack)?);
+            }
+            Ok(Value::Object(out))
+        }
+        Value::Array(items) => items
+            .iter()
+            .map(|v| resolve(spec, v, stack))
+            .collect::<Result<_, _>>()
+            .map(Value::Array),
+        other => Ok(other.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec() -> Value {
+        json!({
+            "openapi": "3.0.3",
+            "paths": {
+                "/items/{id}": {
+                    "parameters": [{ "name": "id", "in": "path", "schema": { "type": "integer" } }],
+                    "get": {
+                        "operationId": "getItem",
+                        "summary": "Get an item.",
+                        "parameters": [
+                            { "name": "fields", "in": "query", "schema": { "type": "array", "items": { "type": "string" } } },
+                            { "name": "X-Trace", "in": "header", "required": true, "schema": { "type": "string" } }
+                        ]
+                    },
+                    "put": {
+                        "operationId": "put item!",
+                        "requestBody": {
+                            "required": true,
+                            "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Item" } } }
+                        }
+                    }
+                }
+            },
+            "components": {
+                "schemas": {
+                    "Item": {
+                        "type": "object",
+                        "properties": { "name": { "type": "string" }, "child": { "$ref": "#/components/schemas/Item" } }
+                    }
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn builds_templated_request() {
+        let specs = import(&spec(), "https://api.test/v1/", Auth::None).unwrap();
+        let get = &specs[0];
+        assert_eq!(get.name, "getItem");
+        assert_eq!(get.parameters["required"], json!(["id", "X-Trace"]));
+
+        let req = get
+            .request
+            .build(&json!({ "id": 7, "fields": ["a b", "c"], "X-Trace": "t1" }))
+            .unwrap();
+        assert_eq!(req.method, Method::Get);
+        assert_eq!(req.url, "https://api.test/v1/items/7?fields=a%20b&fields=c");
+        assert_eq!(req.headers, vec![("X-Trace".to_string(), "t1".to_string())]);
+        assert_eq!(req.body, None);
+    }
+
+    #[test]
+    fn missing_required_params_are_reported() {
+        let specs = import(&spec(), "https://api.test", Auth::None).unwrap();
+        let err = specs[0]
+            .request
+            .build(&json!({ "X-Trace": "t" }))
+            .unwrap_err();
+        assert!(err.to_string().contains("`id`"));
+        let err = specs[1].request.build(&json!({ "id": 1 })).unwrap_err();
+        assert!(err.to_string().contains("body"));
+    }
+
+    #[test]
+    fn json_body_and_recursive_refs() {
+        let specs = import(&spec(), "https://api.test", Auth::None).unwrap();
+        let put = &specs[1];
+        assert_eq!(put.name, "put_item");
+        let body = &put.parameters["properties"]["body"];
+        assert_eq!(body["properties"]["name"]["type"], "string");
+        assert_eq!(body["properties"]["child"], json!({}));
+
+        let req = put
+            .request
+            .build(&json!({ "id": "a/b", "body": { "name": "x" } }))
+            .unwrap();
+        assert_eq!(req.url, "https://api.test/items/a%2Fb");
+        assert_eq!(req.body, Some(json!({ "name": "x" })));
+        assert!(
+            req.headers
+                .contains(&("Content-Type".into(), "application/json".into()))
+        );
+    }
+
+    #[test]
+    fn auth_is_applied_and_redacted() {
+        let auth = Auth::Bearer("s3cret".into());
+        let specs = import(&spec(), "https://api.test", auth).unwrap();
+        let req = specs[0]
+            .request
+            .build(&json!({ "id": 1, "X-Trace": "t" }))
+            .unwrap();
+        assert!(
+            req.headers
+                .contains(&("Authorization".into(), "Bearer s3cret".into()))
+        );
+        assert!(!format!("{req:?}").contains("s3cret"));
+        assert!(!format!("{:?}", specs[0]).contains("s3cret"));
+    }
+
+    #[test]
+    fn rejects_required_non_json_body() {
+        let spec = json!({
+            "openapi": "3.1.0",
+            "paths": { "/upload": { "post": {
+                "operationId": "upload",
+                "requestBody": { "required": true, "content": { "application/octet-stream": {} } }
+            } } }
+        });
+        let err = import(&spec, "https://api.test", Auth::None).unwrap_err();
+        assert!(matches!(err, ImportError::UnsupportedContentType { .. }));
+    }
+
+    #[test]
+    fn rejects_swagger_2_and_bad_refs() {
+        let err = import(&json!({ "swagger": "2.0" }), "x", Auth::None).unwrap_err();
+        assert!(matches!(err, ImportError::UnsupportedVersion(_)));
+
+        let spec = json!({
+            "openapi": "3.0.0",
+            "paths": { "/a": { "get": { "parameters": [{ "$ref": "#/components/parameters/Nope" }] } } }
+        });
+        let err = import(&spec, "x", Auth::None).unwrap_err();
+        assert_eq!(
+            err,
+            ImportError::UnresolvedRef("#/components/parameters/Nope".to_string())
+        );
+    }
+
+    #[test]
+    fn synthesizes_missing_operation_ids() {
+        let spec = json!({ "openapi": "3.0.0", "paths": { "/users/{id}/posts": { "get": {} } } });
+        let specs = import(&spec, "x", Auth::None).unwrap();
+        assert_eq!(specs[0].name, "get__users__id__posts");
+        assert_eq!(specs[0].description, "GET /users/{id}/posts");
+    }
+}
@@ -0,0 +1,266 @@
+//! Per-deployment settings from environment variables.
+//!
+//! One binary can serve several environments: staging exposes the
+//! `debug_*` tools, production doesn't, and a slow upstream gets a longer
+//! timeout in one of them. [`apply_env_overrides`] reads these variables
+//! under a prefix (`TOOLS` here) at startup:
+//!
+//! | Variable | Effect |
+//! |---|---|
+//! | `TOOLS_DISABLE=send_email,debug_*` | unregister the matching tools |
+//! | `TOOLS_TIMEOUT_MS__SEARCH_WEB=3000` | [timeout][ToolCollection::set_timeout] for the matching tools |
+//! | `TOOLS_NAMESPACE=staging` | [namespace][ToolCollection::set_namespace] of usage events |
+//!
+//! Tool names are glob patterns, where `*` matches any run of characters
+//! and `?` one character. The part of a `TIMEOUT_MS__` variable after the
+//! `__` is lowercased, so `SEARCH_WEB` is the tool `search_web`. Lists are
+//! split on commas, with blanks around entries ignored. An empty
+//! `NAMESPACE` removes the namespace. Any other variable under the prefix
+//! is listed as [unrecognized][OverrideReport::unrecognized], so a typo
+//! like `TOOLS_DISABEL` shows up in the report rather than doing nothing.
+//!
+//! ```ignore
+//! let report = tools.apply_env_overrides("TOOLS")?;
+//! for pattern in &report.unmatched_disable {
+//!     log::warn!("TOOLS_DISABLE: `{pattern}` matched no tool");
+//! }
+//! ```
+//!
+//! # Precedence
+//!
+//! Environment over config file over code. Settings made in code are the
+//! starting point; [`Overrides`] deserialize from a config file
+//! (`{"disable": [...], "timeout_ms": {"search_web": 3000}, "namespace":
+//! "staging"}`), and [`over`][Overrides::over] layers the environment on
+//! top before applying:
+//!
+//! ```ignore
+//! let file: Overrides = serde_json::from_str(&fs::read_to_string("tools.json")?)?;
+//! tools.apply_overrides(&Overrides::from_env("TOOLS")?.over(file));
+//! ```
+//!
+//! A layer replaces the layer below setting by setting: a `disable` list,
+//! even an empty one, replaces the lower list, and so does a namespace.
+//! Timeouts replace the lower layer's pattern by pattern; a timeout from
+//! either layer replaces one set in code. Disabling comes first, so a
+//! timeout for a disabled tool matches nothing.
+
+use std::{borrow::Cow, collections::BTreeMap, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{ToolCollection, ToolError};
+
+/// One layer of per-deployment settings; see the [module docs][self].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Overrides {
+    /// Patterns of tools to unregister. `None` leaves the lower layer's.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disable: Option<Vec<String>>,
+    /// Timeouts in milliseconds, by tool pattern.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub timeout_ms: BTreeMap<String, u64>,
+    /// Namespace to set; empty to remove it. `None` leaves the lower
+    /// layer's.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    /// Variables under the prefix that name no setting.
+    #[serde(skip)]
+    pub unrecognized: Vec<String>,
+}
+
+impl Overrides {
+    /// Read the variables under `prefix` from the process environment.
+    pub fn from_env(prefix: &str) -> Result<Self, ToolError> {
+        let vars = std::env::vars_os()
+            .filter_map(|(key, value)| Some((key.into_string().ok()?, value.into_string().ok()?)));
+        Self::from_vars(prefix, vars)
+    }
+
+    /// Read the variables under `prefix` from `vars`, e.g. a map standing
+    /// in for the environment in tests. `prefix` may end in `_` or not.
+    /// Fails on a timeout that isn't a whole number of milliseconds.
+    pub fn from_vars<K, V>(
+        prefix: &str,
+        vars: impl IntoIterator<Item = (K, V)>,
+    ) -> Result<Self, ToolError>
+    where
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let prefix = format!("{}_", prefix.trim_end_matches('_'));
+        let mut overrides = Self::default();
+        for (key, value) in vars {
+            let (key, value) = (key.as_ref(), value.as_ref());
+            let Some(setting) = key.strip_prefix(&prefix) else {
+                continue;
+            };
+            if setting == "DISABLE" {
+                overrides.disable = Some(
+                    value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|p| !p.is_empty())
+                        .map(str::to_string)
+                        .collect(),
+                );
+            } else if setting == "NAMESPACE" {
+                overrides.namespace = Some(value.trim().to_string());
+            } else if let Some(tool) = setting.strip_prefix("TIMEOUT_MS__") {
+                let ms = value.trim().parse().map_err(|_| {
+                    ToolError::Runtime(format!(
+                        "{key}: `{value}` is not a whole number of milliseconds"
+                    ))
+                })?;
+                overrides.timeout_ms.insert(tool.to_ascii_lowercase(), ms);
+            } else {
+                overrides.unrecognized.push(key.to_string());
+            }
+        }
+        overrides.unrecognized.sort_unstable();
+        Ok(overrides)
+    }
+
+    /// These settings on top of `lower`; see [Precedence][self#precedence].
+    pub fn over(self, lower: Overrides) -> Overrides {
+        let mut timeout_ms = lower.timeout_ms;
+        timeout_ms.extend(self.timeout_ms);
+        let mut unrecognized = lower.unrecognized;
+        unrecognized.extend(self.unrecognized);
+        Overrides {
+            disable: self.disable.or(lower.disable),
+            timeout_ms,
+            namespace: self.namespace.or(lower.namespace),
+            unrecognized,
+        }
+    }
+}
+
+/// What [`ToolCollection::apply_overrides`] did.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OverrideReport {
+    /// Tools unregistered, in registration order.
+    pub disabled: Vec<Cow<'static, str>>,
+    /// Tools given a timeout, in registration order for each pattern.
+    pub timeouts: Vec<(Cow<'static, str>, Duration)>,
+    /// The namespace now set, when the overrides changed it.
+    pub namespace: Option<String>,
+    /// `disable` patterns that matched no tool.
+    pub unmatched_disable: Vec<String>,
+    /// Timeout patterns that matched no tool.
+    pub unmatched_timeouts: Vec<String>,
+    /// Variables under the prefix that name no setting.
+    pub unrecognized: Vec<String>,
+}
+
+impl OverrideReport {
+    /// Every pattern matched a tool and every variable was understood.
+    pub fn is_clean(&self) -> bool {
+        self.unmatched_disable.is_empty()
+            && self.unmatched_timeouts.is_empty()
+            && self.unrecognized.is_empty()
+    }
+}
+
+impl<M> ToolCollection<M> {
+    /// Apply the settings in the environment variables under `prefix`;
+    /// see [`overrides`][crate::overrides].
+    pub fn apply_env_overrides(&mut self, prefix: &str) -> Result<OverrideReport, ToolError> {
+        Ok(self.apply_overrides(&Overrides::from_env(prefix)?))
+    }
+
+    /// Apply `overrides`: disable, then set timeouts and the namespace.
+    pub fn apply_overrides(&mut self, overrides: &Overrides) -> OverrideReport {
+        let mut report = OverrideReport {
+            unrecognized: overrides.unrecognized.clone(),
+            ..OverrideReport::default()
+        };
+        for pattern in overrides.disable.iter().flatten() {
+            let matched = self.matching(pattern);
+            if matched.is_empty() {
+                report.unmatched_disable.push(pattern.clone());
+            }
+            for name in matched {
+                if self.unregister(&name).is_ok() {
+                    report.disabled.push(name);
+                }
+            }
+        }
+        for (pattern, &ms) in &overrides.timeout_ms {
+            let matched = self.matching(pattern);
+            if matched.is_empty() {
+                report.unmatched_timeouts.push(pattern.clone());
+            }
+            let timeout = Duration::from_millis(ms);
+            for name in matched {
+                if self.set_timeout(&name, timeout).is_ok() {
+                    report.timeouts.push((name, timeout));
+                }
+            }
+        }
+        if let Some(namespace) = &overrides.namespace {
+            let namespace = (!namespace.is_empty()).then(|| namespace.clone());
+            self.set_namespace(namespace.clone());
+            report.namespace = namespace;
+        }
+        report
+    }
+
+    fn matching(&self, pattern: &str) -> Vec<Cow<'static, str>> {
+        self.iter()
+            .filter(|(name, _)| glob_match(pattern, name))
+            .map(|(_, entry)| entry.decl.name.clone())
+            .collect()
+    }
+}
+
+/// Whether `name` matches `pattern`, with `*` for any run of characters
+/// and `?` for one.
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let (pattern, name): (Vec<char>, Vec<char>) =
+        (pattern.chars().collect(), name.chars().collect());
+    let (mut p, mut n) = (0, 0);
+    // Where the last `*` was, and how much of `name` it has taken.
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    star = Some((star_p, star_n + 1));
+                    p = star_p + 1;
+                    n = star_n + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn globs() {
+        assert!(glob_match("debug_*", "debug_dump"));
+        assert!(glob_match("debug_*", "debug_"));
+        assert!(!glob_match("debug_*", "undebug_dump"));
+        assert!(glob_match("*_email", "send_email"));
+        assert!(glob_match("s?nd_*l", "send_email"));
+        assert!(glob_match("*a*b*", "xaxxbx"));
+        assert!(!glob_match("*a*b", "xaxxbx"));
+        assert!(glob_match("send_email", "send_email"));
+        assert!(!glob_match("send_email", "send_emails"));
+        assert!(glob_match("*", ""));
+    }
+}
@@ -0,0 +1,87 @@
+//! Paging through declarations of very large collections.
+//!
+//! [`ToolCollection::declarations_page`] returns tools sorted by name,
+//! `limit` at a time, together with a cursor for the next page. The
+//! cursor records the last name returned rather than an offset, so tools
+//! registered or removed between requests never cause gaps or
+//! duplicates among the tools present throughout.
+//!
+//! ```ignore
+//! let filter = DeclFilter::default().tag("github");
+//! let mut cursor = None;
+//! loop {
+//!     let page = tools.declarations_page(cursor.as_deref(), 100, &filter);
+//!     send(&page.declarations);
+//!     let Some(next) = page.next_cursor else { break };
+//!     cursor = Some(next);
+//! }
+//! ```
+
+use crate::{FunctionDecl, ToolCollection};
+
+/// Narrows the tools [`ToolCollection::declarations_page`] returns. All
+/// set conditions must hold.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeclFilter {
+    pub tag: Option<String>,
+    pub name_prefix: Option<String>,
+}
+
+impl DeclFilter {
+    /// Keep tools carrying `tag`.
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.tag = Some(tag.into());
+        self
+    }
+
+    /// Keep tools whose name starts with `prefix`, e.g. `"github."` for
+    /// everything imported under that prefix.
+    pub fn name_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.name_prefix = Some(prefix.into());
+        self
+    }
+
+    pub fn matches(&self, decl: &FunctionDecl<'_>) -> bool {
+        self.tag.as_ref().is_none_or(|t| decl.tags.contains(t))
+            && self
+                .name_prefix
+                .as_ref()
+                .is_none_or(|p| decl.name.starts_with(p.as_str()))
+    }
+}
+
+/// One page of declarations.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeclarationPage<'a> {
+    pub declarations: Vec<&'a FunctionDecl<'static>>,
+    /// Pass back to get the next page; `None` on the last page.
+    pub next_cursor: Option<String>,
+}
+
+impl<M> ToolCollection<M> {
+    /// Up to `limit` declarations matching `filter`, sorted by name,
+    /// starting after `cursor` (`None` for the first page). A `limit` of
+    /// zero is treated as one.
+    ///
+    /// Cursors are opaque; only pass back a `next_cursor` from an earlier
+    /// page with the same filter.
+    pub fn declarations_page(
+        &self,
+        cursor: Option<&str>,
+        limit: usize,
+        filter: &DeclFilter,
+    ) -> DeclarationPage<'_> {
+        let limit = limit.max(1);
+        let mut remaining: Vec<&FunctionDecl<'static>> = self
+            .declarations()
+            .filter(|d| cursor.is_none_or(|c| *d.name > *c) && filter.matches(d))
+            .collect();
+
+        let next_cursor = (remaining.len() > limit).then(|| remaining[limit - 1].name.to_string());
+        remaining.truncate(limit);
+        DeclarationPage {
+            declarations: remaining,
+            next_cursor,
+        }
+    }
+}
@@ -0,0 +1,89 @@
+//! Turning tool panics into errors.
+//!
+//! A panicking tool fails its call with [`ToolError::Panicked`] instead of
+//! tearing down the task that called it. The error carries the panic
+//! message and, when backtraces are enabled (`RUST_BACKTRACE` or
+//! `RUST_LIB_BACKTRACE`), the backtrace from the panic site.
+//!
+//! Backtraces are for operators, not for the model: the LLM-facing
+//! [`ErrorReport`][crate::ErrorReport] from [`ToolError::report`] leaves
+//! them out. Use [`ToolError::report_trusted`] in environments where
+//! internals may be shown.
+//!
+//! The backtrace is recorded by a panic hook installed on the first tool
+//! call. It chains to the hook that was set before, so panics are still
+//! printed as usual. Replacing the hook afterwards only loses the
+//! backtraces.
+
+use std::{
+    any::Any,
+    backtrace::{Backtrace, BacktraceStatus},
+    cell::{Cell, RefCell},
+    future::Future,
+    panic::{self, AssertUnwindSafe},
+    sync::Once,
+    task::Poll,
+};
+
+use serde_json::Value;
+
+use crate::ToolError;
+
+thread_local! {
+    /// Depth of [`catch`] polls running on this thread.
+    static CATCHING: Cell<u32> = const { Cell::new(0) };
+    /// Backtrace of the last panic caught on this thread.
+    static BACKTRACE: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+fn install_hook() {
+    static HOOK: Once = Once::new();
+    HOOK.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if CATCHING.with(Cell::get) > 0 {
+                let backtrace = Backtrace::capture();
+                let backtrace = (backtrace.status() == BacktraceStatus::Captured)
+                    .then(|| backtrace.to_string());
+                BACKTRACE.with(|b| *b.borrow_mut() = backtrace);
+            }
+            previous(info);
+        }));
+    });
+}
+
+/// The message of a panic payload: the `&str` or `String` passed to
+/// `panic!`, or a placeholder for other payloads.
+pub fn message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        (*s).to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "non-string panic payload".to_string()
+    }
+}
+
+/// Run `fut`, turning a panic in any of its polls into
+/// [`ToolError::Panicked`].
+pub(crate) async fn catch(
+    tool: &str,
+    fut: impl Future<Output = Result<Value, ToolError>>,
+) -> Result<Value, ToolError> {
+    install_hook();
+    let mut fut = std::pin::pin!(fut);
+    std::future::poll_fn(|cx| {
+        CATCHING.with(|c| c.set(c.get() + 1));
+        let polled = panic::catch_unwind(AssertUnwindSafe(|| fut.as_mut().poll(cx)));
+        CATCHING.with(|c| c.set(c.get() - 1));
+        match polled {
+            Ok(poll) => poll,
+            Err(payload) => Poll::Ready(Err(ToolError::Panicked {
+                tool: tool.to_owned().into(),
+                message: message(payload.as_ref()),
+                backtrace: BACKTRACE.with(|b| b.borrow_mut().take()),
+            })),
+        }
+    })
+    .await
+}
@@ -0,0 +1,202 @@
+//! Three-state values for PATCH-style tools.
+//!
+//! An update tool has to tell apart "leave this field alone", "clear it"
+//! and "set it". JSON expresses that as an absent key, `null` and a value:
+//!
+//! | JSON           | `Patch<T>`        | `Option<Option<T>>` |
+//! |----------------|-------------------|---------------------|
+//! | key absent     | `Patch::Missing`  | `None`              |
+//! | `null`         | `Patch::Null`     | `Some(None)`        |
+//! | value          | `Patch::Value(v)` | `Some(Some(v))`     |
+//!
+//! Both forms produce the schema `anyOf [T, null]`, and the field is never
+//! listed as required. `#[tool]` parameters need no extra attributes. In
+//! your own `#[derive(Deserialize)]` structs, absence must be made
+//! representable by hand:
+//!
+//! ```ignore
+//! #[derive(Deserialize, ToolSchema)]
+//! struct UserUpdate {
+//!     #[serde(default)]
+//!     nickname: Patch<String>,
+//!     #[serde(default, deserialize_with = "tools_core::patch::double_option")]
+//!     age: Option<Option<u32>>,
+//! }
+//! ```
+//!
+//! Serde's built-in `Option<Option<T>>` handling maps `null` to `None`, the
+//! same as an absent key. [`double_option`] keeps the two apart.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+use crate::ToolSchema;
+
+/// An optional, nullable field: absent, explicitly `null`, or a value.
+///
+/// Deserialize it with `#[serde(default)]` so an absent key becomes
+/// [`Patch::Missing`]. When serializing, pair it with
+/// `#[serde(skip_serializing_if = "Patch::is_missing")]`; otherwise
+/// `Missing` is written as `null`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Patch<T> {
+    #[default]
+    Missing,
+    Null,
+    Value(T),
+}
+
+impl<T> Patch<T> {
+    pub fn is_missing(&self) -> bool {
+        matches!(self, Self::Missing)
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+
+    pub fn as_value(&self) -> Option<&T> {
+        match self {
+            Self::Value(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Apply the patch to a stored optional field: `Missing` leaves it as
+    /// is, `Null` clears it and `Value` replaces it.
+    pub fn apply(self, target: &mut Option<T>) {
+        match self {
+            Self::Missing => {}
+            Self::Null => *target = None,
+            Self::Value(v) => *target = Some(v),
+        }
+    }
+
+    pub fn into_option(self) -> Option<Option<T>> {
+        self.into()
+    }
+}
+
+impl<T> From<Option<Option<T>>> for Patch<T> {
+    fn from(value: Option<Option<T>>) -> Self {
+        match value {
+            None => Self::Missing,
+            Some(None) => Self::Null,
+            Some(Some(v)) => Self::Value(v),
+        }
+    }
+}
+
+impl<T> From<Patch<T>> for Option<Option<T>> {
+    fn from(value: Patch<T>) -> Self {
+        match value {
+            Patch::Missing => None,
+            Patch::Null => Some(None),
+            Patch::Value(v) => Some(Some(v)),
+        }
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Patch<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        // Only reached when the key is present; absence comes from
+        // `#[serde(default)]`.
+        Ok(match Option::<T>::deserialize(deserializer)? {
+            Some(v) => Self::Value(v),
+            None => Self::Null,
+        })
+    }
+}
+
+impl<T: Serialize> Serialize for Patch<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Value(v) => v.serialize(serializer),
+            Self::Missing | Self::Null => serializer.serialize_none(),
+        }
+    }
+}
+
+impl<T: ToolSchema> ToolSchema for Patch<T> {
+    fn schema() -> Value {
+        <Option<T> as ToolSchema>::schema()
+    }
+}
+
+/// `deserialize_with` helper for `Option<Option<T>>` fields: a present
+/// `null` becomes `Some(None)` instead of `None`. Use it together with
+/// `#[serde(default)]`.
+pub fn double_option<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[derive(Debug, Deserialize, Serialize, PartialEq)]
+    struct Update {
+        #[serde(default, skip_serializing_if = "Patch::is_missing")]
+        name: Patch<String>,
+        #[serde(default, deserialize_with = "double_option")]
+        age: Option<Option<u32>>,
+    }
+
+    #[test]
+    fn three_states_deserialize() {
+        let absent: Update = serde_json::from_value(json!({})).unwrap();
+        assert_eq!(absent.name, Patch::Missing);
+        assert_eq!(absent.age, None);
+
+        let null: Update = serde_json::from_value(json!({ "name": null, "age": null })).unwrap();
+        assert_eq!(null.name, Patch::Null);
+        assert_eq!(null.age, Some(None));
+
+        let value: Update = serde_json::from_value(json!({ "name": "Ada", "age": 36 })).unwrap();
+        assert_eq!(value.name, Patch::Value("Ada".to_string()));
+        assert_eq!(value.age, Some(Some(36)));
+    }
+
+    #[test]
+    fn missing_is_skipped_on_serialize() {
+        let update = Update {
+            name: Patch::Missing,
+            age: None,
+        };
+        assert_eq!(
+            serde_json::to_value(&update).unwrap(),
+            json!({ "age": null })
+        );
+
+        let update = Update {
+            name: Patch::Null,
+            age: None,
+        };
+        assert_eq!(serde_json::to_value(&update).unwrap()["name"], Value::Null);
+    }
+
+    #[test]
+    fn apply_and_conversions() {
+        let mut stored = Some(1);
+        Patch::Missing.apply(&mut stored);
+        assert_eq!(stored, Some(1));
+        Patch::Value(2).apply(&mut stored);
+        assert_eq!(stored, Some(2));
+        Patch::Null.apply(&mut stored);
+        assert_eq!(stored, None);
+
+        for state in [Patch::Missing, Patch::Null, Patch::Value(3)] {
+            assert_eq!(Patch::from(state.into_option()), state);
+        }
+    }
+
+    #[test]
+    fn schema_matches_option() {
+        assert_eq!(Patch::<i32>::schema(), Option::<i32>::schema());
+    }
+}
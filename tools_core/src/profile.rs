@@ -0,0 +1,247 @@
+//! Per-agent views over one shared collection.
+//!
+//! Several agents often share one [`ToolCollection`] but should neither
+//! see nor spend each other's tools. An [`AgentProfile`] bundles what one
+//! agent may do: which tools it sees, how many calls it may make and how
+//! long each call may take. [`ToolCollection::profile`] returns a
+//! [`Profiled`] view that declares and calls only through those limits.
+//!
+//! ```ignore
+//! tools.set_profile(
+//!     AgentProfile::new("researcher")
+//!         .allow(["search_web", "get_weather"])
+//!         .budget(20)
+//!         .timeout(Duration::from_secs(10)),
+//! );
+//! let researcher = tools.profile("researcher").unwrap();
+//! send(researcher.json()?);
+//! researcher.call(call).await?;
+//! ```
+//!
+//! Tools outside the view are reported as not found. The budget counts
+//! every call that reaches the collection, failed ones included, and is
+//! shared by clones of the collection. Inside a call, [`current`] names the
+//! profile it was made through, so tools and their logs can attribute it.
+
+use std::{
+    collections::BTreeSet,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    ErrorReport, FunctionCall, FunctionDecl, FunctionResponse, ToolCollection, ToolEntry,
+    ToolError, deadline, page::DeclFilter,
+};
+
+tokio::task_local! {
+    static PROFILE: Arc<str>;
+}
+
+/// Name of the profile the current call was made through, if any.
+pub fn current() -> Option<Arc<str>> {
+    PROFILE.try_with(Arc::clone).ok()
+}
+
+/// What one agent may do with a collection.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AgentProfile {
+    pub name: String,
+    /// Tools the agent sees, by name. `None` means every tool passing
+    /// `filter`.
+    pub allow: Option<BTreeSet<String>>,
+    pub filter: DeclFilter,
+    /// Calls the agent may make in total.
+    pub budget: Option<u64>,
+    /// Deadline applied to each call, on top of any enclosing one.
+    pub timeout: Option<Duration>,
+}
+
+impl AgentProfile {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Self::default()
+        }
+    }
+
+    /// See only the named tools, in addition to passing `filter`.
+    pub fn allow(mut self, names: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.allow = Some(names.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// See only tools matching `filter`.
+    pub fn filter(mut self, filter: DeclFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn budget(mut self, calls: u64) -> Self {
+        self.budget = Some(calls);
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Hidden tools are only seen when named in `allow`.
+    fn sees<M>(&self, entry: &ToolEntry<M>) -> bool {
+        let decl = &entry.decl;
+        let allowed = match &self.allow {
+            Some(allow) => allow.contains(&*decl.name),
+            None => !entry.hidden,
+        };
+        allowed && self.filter.matches(decl)
+    }
+}
+
+/// A profile and its running counters, kept by the collection.
+pub(crate) struct ProfileState {
+    profile: AgentProfile,
+    name: Arc<str>,
+    calls: AtomicU64,
+    failures: AtomicU64,
+}
+
+/// Calls made through a profile so far.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ProfileUsage {
+    pub calls: u64,
+    pub failures: u64,
+    /// Calls left in the budget; `None` without a budget.
+    pub remaining: Option<u64>,
+}
+
+/// A collection seen through one [`AgentProfile`], from
+/// [`ToolCollection::profile`].
+pub struct Profiled<'a, M> {
+    tools: &'a ToolCollection<M>,
+    state: &'a ProfileState,
+}
+
+impl<M> Profiled<'_, M> {
+    pub fn profile(&self) -> &AgentProfile {
+        &self.state.profile
+    }
+
+    /// Declarations of the tools in the view, sorted by name. Hidden
+    /// tools are declared as by [`ToolCollection::declarations`].
+    pub fn declarations(&self) -> Vec<&FunctionDecl<'static>> {
+        let mut decls: Vec<_> = self
+            .tools
+            .iter()
+            .filter(|(_, e)| self.state.sees(e) && (!e.hidden || self.tools.declare_hidden()))
+            .map(|(_, e)| &e.decl)
+            .collect();
+        decls.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+        decls
+    }
+
+    /// [`ToolCollection::json`] restricted to the view.
+    pub fn json(&self) -> Result<Value, ToolError> {
+        Ok(serde_json::to_value(self.declarations())?)
+    }
+
+    pub fn usage(&self) -> ProfileUsage {
+        let calls = self.state.calls.load(Ordering::SeqCst);
+        ProfileUsage {
+            calls,
+            failures: self.state.failures.load(Ordering::SeqCst),
+            remaining: self
+                .state
+                .profile
+                .budget
+                .map(|budget| budget.saturating_sub(calls)),
+        }
+    }
+
+    /// Call a tool in the view, charging the profile's budget.
+    pub async fn call(&self, call: FunctionCall) -> Result<FunctionResponse, ToolError> {
+        let state = self.state;
+        if !self.tools.get(&call.name).is_some_and(|e| state.sees(e)) {
+            return Err(ToolError::FunctionNotFound {
+                name: call.name.into(),
+            });
+        }
+        let charged = state
+            .calls
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |calls| {
+                state
+                    .profile
+                    .budget
+                    .is_none_or(|budget| calls < budget)
+                    .then_some(calls + 1)
+            });
+        if charged.is_err() {
+            return Err(ToolError::BudgetExhausted {
+                profile: state.profile.name.clone(),
+                budget: state.profile.budget.unwrap_or_default(),
+            });
+        }
+
+        let fut = PROFILE.scope(state.name.clone(), self.tools.call(call));
+        let result = match state.profile.timeout {
+            Some(timeout) => deadline::with_timeout(timeout, fut).await,
+            None => fut.await,
+        };
+        if result.is_err() {
+            state.failures.fetch_add(1, Ordering::SeqCst);
+        }
+        result
+    }
+
+    /// [`ToolCollection::report_failure`] for a call made through the view.
+    pub fn report_failure(&self, call: &FunctionCall, err: &ToolError) -> ErrorReport {
+        self.tools.report_failure(call, err)
+    }
+}
+
+impl ProfileState {
+    /// Whether a call through the profile may reach `entry`.
+    pub(crate) fn sees<M>(&self, entry: &ToolEntry<M>) -> bool {
+        self.profile.sees(entry)
+    }
+}
+
+impl<M> ToolCollection<M> {
+    /// Add `profile`, replacing one of the same name and resetting its
+    /// counters.
+    pub fn set_profile(&mut self, profile: AgentProfile) {
+        let state = ProfileState {
+            name: profile.name.as_str().into(),
+            profile,
+            calls: AtomicU64::new(0),
+            failures: AtomicU64::new(0),
+        };
+        self.profiles
+            .insert(state.profile.name.clone(), Arc::new(state));
+    }
+
+    pub fn remove_profile(&mut self, name: &str) -> Option<AgentProfile> {
+        self.profiles
+            .remove(name)
+            .map(|state| state.profile.clone())
+    }
+
+    /// The collection as seen through the profile `name`.
+    pub fn profile(&self, name: &str) -> Option<Profiled<'_, M>> {
+        Some(Profiled {
+            tools: self,
+            state: self.profiles.get(name)?,
+        })
+    }
+
+    /// Names of the profiles, sorted.
+    pub fn profile_names(&self) -> impl Iterator<Item = &str> {
+        self.profiles.keys().map(String::as_str)
+    }
+}
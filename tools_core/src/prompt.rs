@@ -0,0 +1,306 @@
+//! Prompt-based tool calling for models without native function calling.
+//!
+//! [`ToolCollection::prompt_snippet`] renders the collection as plain text
+//! for a system prompt; [`parse_freeform_call`] pulls the model's
+//! `{"tool": ..., "arguments": ...}` reply back out of free-form text.
+//!
+//! ```ignore
+//! let system = tools.prompt_snippet(PromptStyle::Json);
+//! let reply = my_local_model(&system, user_msg).await;
+//! if let Some(call) = parse_freeform_call(&reply) {
+//!     let resp = tools.call(call).await?;
+//! }
+//! ```
+
+use std::fmt::Write as _;
+
+use serde_json::Value;
+
+use crate::{FunctionCall, FunctionDecl, ToolCollection};
+
+/// Layout used by [`ToolCollection::prompt_snippet`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PromptStyle {
+    /// One line per tool with a compact signature, followed by
+    /// Thought/Action instructions.
+    #[default]
+    ReAct,
+    /// One JSON declaration per tool, followed by instructions to reply
+    /// with a single JSON object.
+    Json,
+}
+
+impl<M> ToolCollection<M> {
+    /// Render the collection as a system-prompt snippet. Tools are listed
+    /// in name order so the snippet is stable across runs.
+    pub fn prompt_snippet(&self, style: PromptStyle) -> String {
+        let mut decls: Vec<&FunctionDecl<'static>> = self.iter().map(|(_, e)| &e.decl).collect();
+        decls.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut out = String::from("You have access to the following tools:\n\n");
+        match style {
+            PromptStyle::ReAct => {
+                for decl in &decls {
+                    let _ = writeln!(
+                        out,
+                        "- {}: {}",
+                        signature(decl),
+                        first_line(&decl.description)
+                    );
+                }
+                out.push_str(
+                    "\nTo use a tool, reply in this format:\n\
+                     Thought: <your reasoning>\n\
+                     Action: {\"tool\": \"<tool name>\", \"arguments\": {<arguments as JSON>}}\n\
+                     \n\
+                     Wait for the tool result before continuing. \
+                     When no tool is needed, answer directly.\n",
+                );
+            }
+            PromptStyle::Json => {
+                for decl in &decls {
+                    let line = serde_json::json!({
+                        "name": decl.name,
+                        "description": decl.description,
+                        "parameters": decl.parameters,
+                    });
+                    let _ = writeln!(out, "{line}");
+                }
+                out.push_str(
+                    "\nTo call a tool, reply with exactly one JSON object and nothing else:\n\
+                     {\"tool\": \"<tool name>\", \"arguments\": {<arguments matching the parameters schema>}}\n",
+                );
+            }
+        }
+        out
+    }
+}
+
+/// `name(a: integer, b?: string)` from a declaration's parameter schema.
+fn signature(decl: &FunctionDecl<'_>) -> String {
+    // Non-object parameters (e.g. a bare tuple) have no names to list.
+    if decl.parameters.get("properties").is_none() && decl.parameters["type"] != "object" {
+        return format!("{}({})", decl.name, type_label(&decl.parameters));
+    }
+
+    let required: Vec<&str> = decl.parameters["required"]
+        .as_array()
+        .map(|r| r.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+
+    let mut params: Vec<(&String, &Value)> = decl.parameters["properties"]
+        .as_object()
+        .map(|p| p.iter().collect())
+        .unwrap_or_default();
+    params.sort_by_key(|(name, _)| name.as_str());
+
+    let rendered: Vec<String> = params
+        .into_iter()
+        .map(|(name, schema)| {
+            let opt = if required.contains(&name.as_str()) {
+                ""
+            } else {
+                "?"
+            };
+            format!("{name}{opt}: {}", type_label(schema))
+        })
+        .collect();
+
+    format!("{}({})", decl.name, rendered.join(", "))
+}
+
+/// Short human-readable label for a schema: `integer`, `string[]`,
+/// `integer | null`, ...
+fn type_label(schema: &Value) -> String {
+    if let Some(ty) = schema["type"].as_str() {
+        return match ty {
+            "array" if schema.get("prefixItems").is_some() => {
+                let items: Vec<String> = schema["prefixItems"]
+                    .as_array()
+                    .map(|items| items.iter().map(type_label).collect())
+                    .unwrap_or_default();
+                format!("[{}]", items.join(", "))
+            }
+            "array" if schema.get("items").is_some() => {
+                format!("{}[]", type_label(&schema["items"]))
+            }
+            other => other.to_string(),
+        };
+    }
+    if let Some(variants) = schema["anyOf"].as_array() {
+        return variants
+            .iter()
+            .map(type_label)
+            .collect::<Vec<_>>()
+            .join(" | ");
+    }
+    if let Some(values) = schema["enum"].as_array() {
+        return values
+            .iter()
+            .map(Value::to_string)
+            .collect::<Vec<_>>()
+            .join(" | ");
+    }
+    "any".to_string()
+}
+
+fn first_line(s: &str) -> &str {
+    s.lines().next().unwrap_or("").trim()
+}
+
+/// Extract the first `{"tool": "<name>", "arguments": {...}}` object from
+/// model output. Tolerates surrounding prose, markdown code fences and
+/// trailing text. A missing `arguments` key is treated as `{}`.
+///
+/// With the `json-repair` feature, almost-JSON (single quotes, trailing
+/// commas, bare keys) is repaired when no well-formed call is found;
+/// [`FunctionCall::repaired`] reports whether that happened.
+///
+/// Returns `None` when no matching object is found.
+pub fn parse_freeform_call(text: &str) -> Option<FunctionCall> {
+    #[cfg(feature = "json-repair")]
+    {
+        crate::repair::parse_freeform_call_repaired(text).map(|r| r.value)
+    }
+    #[cfg(not(feature = "json-repair"))]
+    {
+        parse_freeform_call_strict(text)
+    }
+}
+
+pub(crate) fn parse_freeform_call_strict(text: &str) -> Option<FunctionCall> {
+    text.match_indices('{')
+        .find_map(|(start, _)| call_from_value(first_json_value(&text[start..])?))
+}
+
+/// Parse the JSON value at the very start of `s`, ignoring whatever follows.
+fn first_json_value(s: &str) -> Option<Value> {
+    serde_json::Deserializer::from_str(s)
+        .into_iter::<Value>()
+        .next()?
+        .ok()
+}
+
+pub(crate) fn call_from_value(value: Value) -> Option<FunctionCall> {
+    let Value::Object(mut obj) = value else {
+        return None;
+    };
+    let name = obj.get("tool")?.as_str()?.to_string();
+    let arguments = match obj.remove("arguments") {
+        None => Value::Object(Default::default()),
+        Some(args @ Value::Object(_)) => args,
+        Some(_) => return None,
+    };
+    Some(FunctionCall::new(name, arguments))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tools() -> ToolCollection {
+        let mut tools = ToolCollection::new();
+        tools
+            .register(
+                "add",
+                "Adds two numbers.\nExtra detail on a second line.",
+                |p: (i32, i32)| async move { p.0 + p.1 },
+                (),
+            )
+            .unwrap();
+        tools
+            .register_raw(
+                "greet",
+                "Greets a person.",
+                json!({
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string" },
+                        "title": { "anyOf": [{ "type": "string" }, { "type": "null" }] }
+                    },
+                    "required": ["name"]
+                }),
+                |v| {
+                    Box::pin(async move {
+                        Ok(json!(format!(
+                            "Hello, {}!",
+                            v["name"].as_str().unwrap_or("")
+                        )))
+                    })
+                },
+                (),
+            )
+            .unwrap();
+        tools
+    }
+
+    #[test]
+    fn react_snippet_lists_signatures_in_name_order() {
+        let snippet = tools().prompt_snippet(PromptStyle::ReAct);
+        let add = snippet
+            .find("- add([integer, integer]): Adds two numbers.")
+            .expect("add line");
+        let greet = snippet
+            .find("- greet(name: string, title?: string | null): Greets a person.")
+            .expect("greet line");
+        assert!(add < greet);
+        assert!(!snippet.contains("Extra detail"));
+        assert!(snippet.contains("Action: {\"tool\""));
+    }
+
+    #[test]
+    fn json_snippet_embeds_declarations() {
+        let snippet = tools().prompt_snippet(PromptStyle::Json);
+        let line = snippet
+            .lines()
+            .find(|l| l.contains("\"name\":\"greet\""))
+            .unwrap();
+        let decl: Value = serde_json::from_str(line).unwrap();
+        assert_eq!(decl["parameters"]["required"], json!(["name"]));
+    }
+
+    #[test]
+    fn parses_plain_object() {
+        let call = parse_freeform_call(r#"{"tool": "add", "arguments": {"a": 1}}"#).unwrap();
+        assert_eq!(call.name, "add");
+        assert_eq!(call.arguments, json!({ "a": 1 }));
+        assert!(call.id.is_some());
+    }
+
+    #[test]
+    fn parses_fenced_object_with_prose() {
+        let reply = "Sure! I'll look that up.\n\n```json\n{\"tool\": \"greet\", \"arguments\": {\"name\": \"Ada\"}}\n```\nLet me know if you need more.";
+        let call = parse_freeform_call(reply).unwrap();
+        assert_eq!(call.name, "greet");
+        assert_eq!(call.arguments, json!({ "name": "Ada" }));
+    }
+
+    #[test]
+    fn skips_unrelated_objects_and_defaults_arguments() {
+        let reply = r#"Context: {"note": "ignore me"} then {"tool": "ping"}"#;
+        let call = parse_freeform_call(reply).unwrap();
+        assert_eq!(call.name, "ping");
+        assert_eq!(call.arguments, json!({}));
+    }
+
+    #[test]
+    fn returns_none_without_a_call() {
+        assert!(parse_freeform_call("The answer is 42.").is_none());
+        assert!(parse_freeform_call(r#"{"tool": 3}"#).is_none());
+        assert!(parse_freeform_call(r#"{"tool": "add", "arguments": [1, 2]}"#).is_none());
+        assert!(parse_freeform_call("{ not json").is_none());
+    }
+
+    #[tokio::test]
+    async fn round_trip_snippet_reply_dispatch() {
+        let tools = tools();
+        let snippet = tools.prompt_snippet(PromptStyle::ReAct);
+        assert!(snippet.contains("greet("));
+
+        let reply = "Thought: the user wants a greeting.\nAction: {\"tool\": \"greet\", \"arguments\": {\"name\": \"Grace\"}}";
+        let call = parse_freeform_call(reply).unwrap();
+        let resp = tools.call(call).await.unwrap();
+        assert_eq!(resp.result, json!("Hello, Grace!"));
+    }
+}
@@ -0,0 +1,188 @@
+//! Tools whose request and response are protobuf messages.
+//!
+//! Enabled with the `prost` feature. Types generated by `prost` have no
+//! [`ToolSchema`][crate::ToolSchema]; instead the bridge reads their
+//! [`prost_reflect`] descriptor, which `prost-reflect-build` adds to the
+//! generated code as an impl of [`ReflectMessage`]. From that descriptor
+//! it builds the argument and result schemas in the canonical protobuf
+//! JSON mapping: fields by their camelCase JSON name, 64-bit integers as
+//! decimal strings, bytes as base64 and enums by value name.
+//!
+//! ```ignore
+//! tools.register_proto::<GetInvoiceRequest, Invoice, _, _>(
+//!     "get_invoice",
+//!     "Look up an invoice.",
+//!     |req| async move { billing.get_invoice(req).await.map_err(to_tool_error) },
+//! )?;
+//! ```
+//!
+//! Arguments are decoded and results encoded through
+//! [`DynamicMessage`]'s serde impls, which follow the same mapping. As the
+//! mapping requires, decoding also accepts fields by their proto name
+//! (`customer_id`), 64-bit integers given as JSON numbers and enums by
+//! number. Unknown fields are left to [`UnknownArgs`][crate::UnknownArgs].
+
+use std::{borrow::Cow, future::Future};
+
+use prost_reflect::{
+    DeserializeOptions, DynamicMessage, FieldDescriptor, Kind, MessageDescriptor, ReflectMessage,
+};
+use serde_json::{Map, Value, json};
+
+use crate::{
+    DecodedInput, DeserializationError, ToolCollection, ToolError, ToolFunc, ToolId,
+    downcast_input, spec::ToolSpec,
+};
+
+// ============================================================================
+// SCHEMAS
+// ============================================================================
+
+/// JSON schema of `message` in the canonical JSON mapping. Every field is
+/// optional, as in proto3. A message nested in itself is declared as a
+/// bare object the second time round.
+pub fn json_schema(message: &MessageDescriptor) -> Value {
+    message_schema(message, &mut Vec::new())
+}
+
+fn message_schema(message: &MessageDescriptor, enclosing: &mut Vec<String>) -> Value {
+    if let Some(schema) = well_known(message, enclosing) {
+        return schema;
+    }
+    let full_name = message.full_name().to_owned();
+    if enclosing.contains(&full_name) {
+        return json!({ "type": "object", "title": full_name });
+    }
+    enclosing.push(full_name.clone());
+    let properties: Map<String, Value> = message
+        .fields()
+        .map(|f| (f.json_name().to_owned(), field_schema(&f, enclosing)))
+        .collect();
+    enclosing.pop();
+    json!({
+        "type": "object",
+        "title": full_name,
+        "properties": properties,
+        "required": []
+    })
+}
+
+fn field_schema(field: &FieldDescriptor, enclosing: &mut Vec<String>) -> Value {
+    match field.kind() {
+        // JSON object keys are always strings, so only the value type
+        // of a map matters.
+        Kind::Message(entry) if field.is_map() => json!({
+            "type": "object",
+            "additionalProperties": kind_schema(&entry.map_entry_value_field().kind(), enclosing)
+        }),
+        kind if field.is_list() => {
+            json!({ "type": "array", "items": kind_schema(&kind, enclosing) })
+        }
+        kind => kind_schema(&kind, enclosing),
+    }
+}
+
+fn kind_schema(kind: &Kind, enclosing: &mut Vec<String>) -> Value {
+    match kind {
+        Kind::Int32 | Kind::Sint32 | Kind::Sfixed32 => {
+            json!({ "type": "integer", "format": "int32" })
+        }
+        Kind::Uint32 | Kind::Fixed32 => {
+            json!({ "type": "integer", "format": "uint32", "minimum": 0 })
+        }
+        Kind::Int64 | Kind::Sint64 | Kind::Sfixed64 => {
+            json!({ "type": "string", "format": "int64", "pattern": "^-?[0-9]+$" })
+        }
+        Kind::Uint64 | Kind::Fixed64 => {
+            json!({ "type": "string", "format": "uint64", "pattern": "^[0-9]+$" })
+        }
+        Kind::Float | Kind::Double => json!({ "type": "number" }),
+        Kind::Bool => json!({ "type": "boolean" }),
+        Kind::String => json!({ "type": "string" }),
+        Kind::Bytes => json!({ "type": "string", "contentEncoding": "base64" }),
+        Kind::Enum(e) => {
+            let values: Vec<String> = e.values().map(|v| v.name().to_owned()).collect();
+            json!({ "type": "string", "enum": values })
+        }
+        Kind::Message(message) => message_schema(message, enclosing),
+    }
+}
+
+/// The `google.protobuf` types the JSON mapping gives their own shape.
+fn well_known(message: &MessageDescriptor, enclosing: &mut Vec<String>) -> Option<Value> {
+    let name = message.full_name().strip_prefix("google.protobuf.")?;
+    Some(match name {
+        "Timestamp" => json!({ "type": "string", "format": "date-time" }),
+        "Duration" => json!({ "type": "string", "pattern": "^-?[0-9]+(\\.[0-9]+)?s$" }),
+        "FieldMask" => json!({ "type": "string" }),
+        "Struct" => json!({ "type": "object" }),
+        "ListValue" => json!({ "type": "array" }),
+        "Value" => json!({}),
+        "Any" => json!({
+            "type": "object",
+            "properties": { "@type": { "type": "string" } },
+            "required": ["@type"]
+        }),
+        // Wrappers are declared as the value they wrap.
+        _ if name.ends_with("Value") => {
+            let value = message.get_field_by_name("value")?;
+            kind_schema(&value.kind(), enclosing)
+        }
+        _ => return None,
+    })
+}
+
+// ============================================================================
+// REGISTRATION
+// ============================================================================
+
+impl<M: serde::de::DeserializeOwned> ToolCollection<M> {
+    /// Register a tool taking a `Req` message and returning a `Resp`
+    /// message, both in the canonical protobuf JSON mapping. Its metadata
+    /// is deserialized from `{}`.
+    pub fn register_proto<Req, Resp, F, Fut>(
+        &mut self,
+        name: impl Into<Cow<'static, str>>,
+        desc: impl Into<Cow<'static, str>>,
+        func: F,
+    ) -> Result<ToolId, ToolError>
+    where
+        Req: ReflectMessage + Default + Send + 'static,
+        Resp: ReflectMessage + Default + 'static,
+        F: Fn(Req) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Resp, ToolError>> + Send + 'static,
+    {
+        let name = name.into();
+        let meta: M = serde_json::from_value(json!({})).map_err(|e| ToolError::BadMeta {
+            tool: name.clone(),
+            error: e.to_string(),
+        })?;
+        let request = Req::default().descriptor();
+        let func = std::sync::Arc::new(func);
+        let spec = ToolSpec::new(name, desc)
+            .params_schema(json_schema(&request))
+            .returns_schema(json_schema(&Resp::default().descriptor()))
+            .func(ToolFunc::new(
+                move |raw: Value| -> Result<DecodedInput, ToolError> {
+                    let options = DeserializeOptions::new().deny_unknown_fields(false);
+                    let message =
+                        DynamicMessage::deserialize_with_options(request.clone(), raw, &options)
+                            .map_err(DeserializationError::from)?;
+                    let req: Req = message
+                        .transcode_to()
+                        .map_err(|e| ToolError::Runtime(e.to_string()))?;
+                    Ok(Box::new(req))
+                },
+                move |input, _ctx| {
+                    let func = func.clone();
+                    Box::pin(async move {
+                        let req: Req = downcast_input(input)?;
+                        let resp = func(req).await?;
+                        serde_json::to_value(resp.transcode_to_dynamic())
+                            .map_err(|e| ToolError::Runtime(e.to_string()))
+                    })
+                },
+            ));
+        self.register_spec(spec.with_meta(meta))
+    }
+}
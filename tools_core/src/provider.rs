@@ -0,0 +1,150 @@
+//! Declarations in the exact shape each provider's API takes.
+//!
+//! [`json`][ToolCollection::json] writes the flat layout. Each API wants
+//! something slightly different, and [`declarations_for`] builds it:
+//!
+//! - [`OpenAi`][Provider::OpenAi]: `{"type": "function", "function":
+//!   {name, description, parameters}}`;
+//! - [`OpenAiStrict`][Provider::OpenAiStrict]: the same with `"strict":
+//!   true` and every object [closed][close_objects];
+//! - [`Gemini`][Provider::Gemini]: `{name, description, parameters}`, the
+//!   parameters in the OpenAPI 3.0 subset;
+//! - [`Anthropic`][Provider::Anthropic]: `{name, description,
+//!   input_schema}`.
+//!
+//! ```ignore
+//! let body = json!({
+//!     "contents": history,
+//!     "tools": { "functionDeclarations": tools.declarations_for(Provider::Gemini)? },
+//! });
+//! ```
+//!
+//! Gemini parameters are [converted][FunctionDecl::parameters_as] from the
+//! stored dialect, with `additionalProperties` removed and per-variant
+//! enum descriptions folded into `description`; a tool without parameters
+//! is sent without `parameters`, since Gemini rejects an object schema
+//! with no properties. Result schemas, tags and the collection's
+//! `$schema` URI and content-hash settings are left out of every format.
+//!
+//! [`declarations_for`]: ToolCollection::declarations_for
+//! [close_objects]: crate::schema::transform::close_objects
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::{
+    DeclTier, FunctionDecl, Profiled, SchemaDialect, ToolCollection, ToolError,
+    schema::transform::{apply, close_objects, fold_enum_descriptions, strip_keywords},
+};
+
+/// An API to format declarations for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Provider {
+    /// OpenAI Chat Completions `tools`.
+    OpenAi,
+    /// OpenAI `tools` with structured outputs (`strict: true`).
+    OpenAiStrict,
+    /// Gemini `functionDeclarations`.
+    Gemini,
+    /// Anthropic Messages `tools`.
+    Anthropic,
+}
+
+impl FunctionDecl<'_> {
+    /// This declaration as one item of `provider`'s tools array.
+    pub fn to_provider(&self, provider: Provider) -> Result<Value, ToolError> {
+        Ok(match provider {
+            Provider::OpenAi => json!({
+                "type": "function",
+                "function": {
+                    "name": self.name,
+                    "description": self.description,
+                    "parameters": self.parameters,
+                },
+            }),
+            Provider::OpenAiStrict => json!({
+                "type": "function",
+                "function": {
+                    "name": self.name,
+                    "description": self.description,
+                    "parameters": close_objects(&self.parameters),
+                    "strict": true,
+                },
+            }),
+            Provider::Gemini => {
+                let parameters = self
+                    .parameters_as(SchemaDialect::OpenApi30)
+                    .ok_or_else(|| {
+                        ToolError::Runtime(format!(
+                            "tool `{}`: no conversion from {:?} for Gemini",
+                            self.name, self.dialect
+                        ))
+                    })?;
+                let strip = strip_keywords(&["additionalProperties"]);
+                let parameters = apply(&parameters, &[&fold_enum_descriptions, &strip]);
+                let mut out = json!({ "name": self.name, "description": self.description });
+                if parameters["properties"]
+                    .as_object()
+                    .is_some_and(|p| !p.is_empty())
+                {
+                    out["parameters"] = parameters;
+                }
+                out
+            }
+            Provider::Anthropic => json!({
+                "name": self.name,
+                "description": self.description,
+                "input_schema": self.parameters,
+            }),
+        })
+    }
+}
+
+fn format<'a>(
+    decls: impl Iterator<Item = &'a FunctionDecl<'static>>,
+    provider: Provider,
+    tier: DeclTier,
+) -> Result<Value, ToolError> {
+    decls
+        .map(|decl| match tier {
+            DeclTier::Full => decl.to_provider(provider),
+            DeclTier::Terse => decl.to_terse().to_provider(provider),
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .map(Value::Array)
+}
+
+impl<M> ToolCollection<M> {
+    /// The [declared][Self::declarations] tools as `provider`'s tools
+    /// array. See [`provider`][crate::provider].
+    pub fn declarations_for(&self, provider: Provider) -> Result<Value, ToolError> {
+        self.declarations_for_tier(provider, DeclTier::Full)
+    }
+
+    /// [`declarations_for`][Self::declarations_for] at the given
+    /// [tier][crate::tier].
+    pub fn declarations_for_tier(
+        &self,
+        provider: Provider,
+        tier: DeclTier,
+    ) -> Result<Value, ToolError> {
+        format(self.declarations(), provider, tier)
+    }
+}
+
+impl<M> Profiled<'_, M> {
+    /// [`ToolCollection::declarations_for`] restricted to the view.
+    pub fn declarations_for(&self, provider: Provider) -> Result<Value, ToolError> {
+        self.declarations_for_tier(provider, DeclTier::Full)
+    }
+
+    /// [`ToolCollection::declarations_for_tier`] restricted to the view.
+    pub fn declarations_for_tier(
+        &self,
+        provider: Provider,
+        tier: DeclTier,
+    ) -> Result<Value, ToolError> {
+        format(self.declarations().into_iter(), provider, tier)
+    }
+}
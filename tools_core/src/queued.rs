@@ -0,0 +1,224 @@
+//! Queued execution for bursty callers.
+//!
+//! A webhook that receives hundreds of calls at once can hand them to a
+//! [`QueuedTools`] instead of running them all immediately. At most
+//! `max_in_flight` calls run at a time; the rest wait in a FIFO queue of
+//! up to `max_queue_len` calls, and anything beyond that fails fast with
+//! [`ToolError::QueueFull`]:
+//!
+//! ```ignore
+//! let queue = tools.queued(8, 512);
+//! let pending = queue.enqueue(call); // queued now, in order
+//! let response = pending.await?;     // resolves once the call has run
+//! // ...
+//! queue.shutdown().await;
+//! ```
+//!
+//! Calls start in the order they were enqueued, so calls to one tool run
+//! first-in, first-out. They run concurrently on a single worker task,
+//! like [`call_many`][ToolCollection::call_many].
+//!
+//! The queue owns the collection. [`shutdown`][QueuedTools::shutdown]
+//! stops taking calls, lets every queued and running call finish, then
+//! runs the collection's own [`shutdown`][ToolCollection::shutdown] so
+//! tool cleanups are awaited.
+
+use std::{
+    future::Future,
+    sync::{
+        Arc, Mutex, PoisonError,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use futures::stream::{FuturesUnordered, StreamExt};
+use serde::Serialize;
+use tokio::{
+    sync::{mpsc, oneshot},
+    task::JoinHandle,
+};
+
+use crate::{FunctionCall, FunctionResponse, NoMeta, ToolCollection, ToolError};
+
+/// Queue depth and throughput of a [`QueuedTools`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct QueuedReport {
+    /// Calls waiting for a slot.
+    pub queued: u64,
+    /// Calls running now.
+    pub in_flight: u64,
+    /// Calls accepted since the queue was created.
+    pub enqueued: u64,
+    /// Calls turned away with [`ToolError::QueueFull`].
+    pub rejected: u64,
+    /// Calls that have finished, successfully or not.
+    pub completed: u64,
+    pub max_in_flight: usize,
+    pub max_queue_len: usize,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    queued: AtomicU64,
+    in_flight: AtomicU64,
+    enqueued: AtomicU64,
+    rejected: AtomicU64,
+    completed: AtomicU64,
+}
+
+struct Job {
+    call: FunctionCall,
+    reply: oneshot::Sender<Result<FunctionResponse, ToolError>>,
+}
+
+/// A collection behind a bounded call queue. Created by
+/// [`ToolCollection::queued`]; see [`queued`][crate::queued].
+pub struct QueuedTools<M = NoMeta> {
+    sender: Mutex<Option<mpsc::Sender<Job>>>,
+    worker: Mutex<Option<JoinHandle<()>>>,
+    counters: Arc<Counters>,
+    max_in_flight: usize,
+    max_queue_len: usize,
+    _meta: std::marker::PhantomData<fn() -> M>,
+}
+
+impl<M: Send + Sync + 'static> ToolCollection<M> {
+    /// Move the collection behind a queue that runs at most
+    /// `max_in_flight` calls at once and holds up to `max_queue_len` more.
+    /// Both are at least 1. Must be called within a tokio runtime.
+    pub fn queued(self, max_in_flight: usize, max_queue_len: usize) -> QueuedTools<M> {
+        let max_in_flight = max_in_flight.max(1);
+        let max_queue_len = max_queue_len.max(1);
+        let (sender, receiver) = mpsc::channel(max_queue_len);
+        let counters = Arc::new(Counters::default());
+        let worker = tokio::spawn(work(self, receiver, counters.clone(), max_in_flight));
+        QueuedTools {
+            sender: Mutex::new(Some(sender)),
+            worker: Mutex::new(Some(worker)),
+            counters,
+            max_in_flight,
+            max_queue_len,
+            _meta: std::marker::PhantomData,
+        }
+    }
+}
+
+/// Take jobs in order while fewer than `max_in_flight` run, until the
+/// queue is closed and drained; then shut the collection down.
+async fn work<M>(
+    mut tools: ToolCollection<M>,
+    mut receiver: mpsc::Receiver<Job>,
+    counters: Arc<Counters>,
+    max_in_flight: usize,
+) {
+    {
+        let tools = &tools;
+        let counters = &counters;
+        let run = |job: Job| async move {
+            let result = tools.call(job.call).await;
+            counters.in_flight.fetch_sub(1, Ordering::Relaxed);
+            counters.completed.fetch_add(1, Ordering::Relaxed);
+            // The caller may have stopped waiting.
+            let _ = job.reply.send(result);
+        };
+        let mut running = FuturesUnordered::new();
+        let mut open = true;
+        while open || !running.is_empty() {
+            tokio::select! {
+                biased;
+                Some(()) = running.next(), if !running.is_empty() => {}
+                job = receiver.recv(), if open && running.len() < max_in_flight => match job {
+                    Some(job) => {
+                        counters.queued.fetch_sub(1, Ordering::Relaxed);
+                        counters.in_flight.fetch_add(1, Ordering::Relaxed);
+                        running.push(run(job));
+                    }
+                    None => open = false,
+                },
+            }
+        }
+    }
+    tools.shutdown().await;
+}
+
+impl<M> QueuedTools<M> {
+    /// Queue `call` and return a future of its response. The call keeps
+    /// its place in the queue whether or not the future is polled or
+    /// kept. Fails with [`ToolError::QueueFull`] when `max_queue_len`
+    /// calls are already waiting, and [`ToolError::QueueClosed`] after
+    /// [`shutdown`][Self::shutdown].
+    pub fn enqueue(
+        &self,
+        call: FunctionCall,
+    ) -> impl Future<Output = Result<FunctionResponse, ToolError>> + Send + 'static {
+        let (reply, response) = oneshot::channel();
+        let accepted = self.push(Job { call, reply });
+        async move {
+            accepted?;
+            response.await.unwrap_or(Err(ToolError::QueueClosed))
+        }
+    }
+
+    fn push(&self, job: Job) -> Result<(), ToolError> {
+        let sender = self.sender.lock().unwrap_or_else(PoisonError::into_inner);
+        let Some(sender) = sender.as_ref() else {
+            return Err(ToolError::QueueClosed);
+        };
+        // Counted first so the worker never sees the queue go negative.
+        self.counters.queued.fetch_add(1, Ordering::Relaxed);
+        match sender.try_send(job) {
+            Ok(()) => {
+                self.counters.enqueued.fetch_add(1, Ordering::Relaxed);
+                Ok(())
+            }
+            Err(err) => {
+                self.counters.queued.fetch_sub(1, Ordering::Relaxed);
+                match err {
+                    mpsc::error::TrySendError::Full(_) => {
+                        self.counters.rejected.fetch_add(1, Ordering::Relaxed);
+                        Err(ToolError::QueueFull {
+                            capacity: self.max_queue_len,
+                        })
+                    }
+                    mpsc::error::TrySendError::Closed(_) => Err(ToolError::QueueClosed),
+                }
+            }
+        }
+    }
+
+    /// Calls waiting for a slot.
+    pub fn depth(&self) -> u64 {
+        self.counters.queued.load(Ordering::Relaxed)
+    }
+
+    pub fn report(&self) -> QueuedReport {
+        let c = &self.counters;
+        QueuedReport {
+            queued: c.queued.load(Ordering::Relaxed),
+            in_flight: c.in_flight.load(Ordering::Relaxed),
+            enqueued: c.enqueued.load(Ordering::Relaxed),
+            rejected: c.rejected.load(Ordering::Relaxed),
+            completed: c.completed.load(Ordering::Relaxed),
+            max_in_flight: self.max_in_flight,
+            max_queue_len: self.max_queue_len,
+        }
+    }
+
+    /// Stop taking calls, wait for every queued and running call, then
+    /// shut the collection down. Later calls return at once.
+    pub async fn shutdown(&self) {
+        self.sender
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+        let worker = self
+            .worker
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+        if let Some(worker) = worker {
+            // A panicking cleanup must not escape into the caller.
+            let _ = worker.await;
+        }
+    }
+}
@@ -0,0 +1,296 @@
+//! Conservative repair of almost-JSON emitted by local models.
+//!
+//! Enabled with the `json-repair` feature. Fixes are deliberately narrow:
+//!
+//! - strip a surrounding markdown code fence,
+//! - drop trailing commas before `}` / `]`,
+//! - turn single-quoted strings into double-quoted ones,
+//! - quote bare object keys (`{name: 1}` → `{"name": 1}`).
+//!
+//! Input that already parses is never rewritten — strict parsing always
+//! runs first and [`Repaired::applied`] reports whether a fix was needed.
+
+use std::borrow::Cow;
+
+use serde_json::Value;
+
+use crate::FunctionCall;
+
+/// A parsed value plus whether repair had to be applied to obtain it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Repaired<T> {
+    pub value: T,
+    pub applied: bool,
+}
+
+/// Parse `input` as JSON, falling back to [`repair`] only when strict
+/// parsing fails.
+pub fn parse_json(input: &str) -> Option<Repaired<Value>> {
+    if let Ok(value) = serde_json::from_str(input) {
+        return Some(Repaired {
+            value,
+            applied: false,
+        });
+    }
+    match repair(input) {
+        Cow::Borrowed(_) => None,
+        Cow::Owned(fixed) => serde_json::from_str(&fixed).ok().map(|value| Repaired {
+            value,
+            applied: true,
+        }),
+    }
+}
+
+/// Like [`parse_freeform_call`][crate::parse_freeform_call], but reports
+/// whether the call object had to be repaired. The call carries the same
+/// flag in [`FunctionCall::repaired`].
+pub fn parse_freeform_call_repaired(text: &str) -> Option<Repaired<FunctionCall>> {
+    if let Some(call) = crate::prompt::parse_freeform_call_strict(text) {
+        return Some(Repaired {
+            value: call,
+            applied: false,
+        });
+    }
+
+    let text = strip_fences(text);
+    text.match_indices('{').find_map(|(start, _)| {
+        let candidate = balanced_object(&text[start..])?;
+        let parsed = parse_json(candidate)?;
+        let call = crate::prompt::call_from_value(parsed.value)?;
+        Some(Repaired {
+            value: FunctionCall {
+                repaired: parsed.applied,
+                ..call
+            },
+            applied: parsed.applied,
+        })
+    })
+}
+
+/// Apply the conservative fixes listed in the module docs. Returns
+/// [`Cow::Borrowed`] when nothing was changed.
+pub fn repair(input: &str) -> Cow<'_, str> {
+    let stripped = strip_fences(input);
+    let mut changed = stripped.len() != input.len();
+
+    let chars: Vec<char> = stripped.chars().collect();
+    let mut out = String::with_capacity(stripped.len() + 8);
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '"' => {
+                let end = string_end(&chars, i, '"');
+                out.extend(&chars[i..end]);
+                i = end;
+            }
+            '\'' => {
+                let end = string_end(&chars, i, '\'');
+                out.push('"');
+                let closed = end > i + 1 && chars[end - 1] == '\'';
+                let body_end = if closed { end - 1 } else { end };
+                let mut j = i + 1;
+                while j < body_end {
+                    match chars[j] {
+                        '\\' if j + 1 < body_end && chars[j + 1] == '\'' => {
+                            out.push('\'');
+                            j += 2;
+                        }
+                        '\\' if j + 1 < body_end => {
+                            out.push('\\');
+                            out.push(chars[j + 1]);
+                            j += 2;
+                        }
+                        '"' => {
+                            out.push_str("\\\"");
+                            j += 1;
+                        }
+                        other => {
+                            out.push(other);
+                            j += 1;
+                        }
+                    }
+                }
+                out.push('"');
+                changed = true;
+                i = end;
+            }
+            ',' => {
+                let next = chars[i + 1..].iter().find(|c| !c.is_whitespace());
+                if matches!(next, Some('}') | Some(']')) {
+                    changed = true;
+                } else {
+                    out.push(',');
+                }
+                i += 1;
+            }
+            c if is_ident_start(c) && matches!(last_significant(&out), Some('{') | Some(',')) => {
+                let start = i;
+                while i < chars.len() && is_ident_continue(chars[i]) {
+                    i += 1;
+                }
+                let ident: String = chars[start..i].iter().collect();
+                let next = chars[i..].iter().find(|c| !c.is_whitespace());
+                if next == Some(&':') {
+                    out.push('"');
+                    out.push_str(&ident);
+                    out.push('"');
+                    changed = true;
+                } else {
+                    out.push_str(&ident);
+                }
+            }
+            other => {
+                out.push(other);
+                i += 1;
+            }
+        }
+    }
+
+    if changed {
+        Cow::Owned(out)
+    } else {
+        Cow::Borrowed(input)
+    }
+}
+
+/// Remove a leading ```` ```lang ```` line and a trailing ```` ``` ````.
+fn strip_fences(input: &str) -> &str {
+    let trimmed = input.trim();
+    let Some(rest) = trimmed.strip_prefix("```") else {
+        return input;
+    };
+    let body = rest.split_once('\n').map_or("", |(_, body)| body);
+    body.trim_end().strip_suffix("```").unwrap_or(body).trim()
+}
+
+/// Index one past the closing `quote` of the string starting at `start`,
+/// or `chars.len()` if it never closes.
+fn string_end(chars: &[char], start: usize, quote: char) -> usize {
+    let mut j = start + 1;
+    while j < chars.len() {
+        match chars[j] {
+            '\\' => j += 2,
+            c if c == quote => return j + 1,
+            _ => j += 1,
+        }
+    }
+    chars.len()
+}
+
+/// The slice of `s` spanning the `{ ... }` that starts at `s[0]`, honouring
+/// both quote styles. `None` if the braces never balance.
+fn balanced_object(s: &str) -> Option<&str> {
+    let mut depth = 0usize;
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    for (idx, c) in s.char_indices() {
+        if let Some(q) = quote {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                _ if c == q => quote = None,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' | '\'' => quote = Some(c),
+            '{' | '[' => depth += 1,
+            '}' | ']' => {
+                depth = depth.checked_sub(1)?;
+                if depth == 0 {
+                    return Some(&s[..idx + c.len_utf8()]);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn last_significant(s: &str) -> Option<char> {
+    s.chars().rev().find(|c| !c.is_whitespace())
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_' || c == '$'
+}
+
+fn is_ident_continue(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '$'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn well_formed_input_is_untouched() {
+        let input = r#"{"a": [1, 2], "b": "it's fine, really"}"#;
+        assert!(matches!(repair(input), Cow::Borrowed(_)));
+        let parsed = parse_json(input).unwrap();
+        assert!(!parsed.applied);
+    }
+
+    #[test]
+    fn trailing_commas() {
+        let parsed = parse_json(r#"{"a": [1, 2,], "b": 3,}"#).unwrap();
+        assert!(parsed.applied);
+        assert_eq!(parsed.value, json!({ "a": [1, 2], "b": 3 }));
+    }
+
+    #[test]
+    fn single_quotes() {
+        let parsed = parse_json(r#"{'name': 'O\'Brien', 'say': 'a "quote"'}"#).unwrap();
+        assert_eq!(
+            parsed.value,
+            json!({ "name": "O'Brien", "say": "a \"quote\"" })
+        );
+    }
+
+    #[test]
+    fn unquoted_keys_leave_literals_alone() {
+        let parsed = parse_json("{name: 'x', flags: [true, false, null], n: 1}").unwrap();
+        assert_eq!(
+            parsed.value,
+            json!({ "name": "x", "flags": [true, false, null], "n": 1 })
+        );
+    }
+
+    #[test]
+    fn commas_inside_strings_survive() {
+        let parsed = parse_json(r#"{"a": "x,}", "b": 1,}"#).unwrap();
+        assert_eq!(parsed.value, json!({ "a": "x,}", "b": 1 }));
+    }
+
+    #[test]
+    fn fenced_call_is_repaired_and_flagged() {
+        let text = "```json\n{'tool': 'add', 'arguments': {'a': 1, 'b': 2,},}\n```";
+        let parsed = parse_freeform_call_repaired(text).unwrap();
+        assert!(parsed.applied);
+        assert_eq!(parsed.value.name, "add");
+        assert_eq!(parsed.value.arguments, json!({ "a": 1, "b": 2 }));
+    }
+
+    #[test]
+    fn prose_apostrophes_do_not_confuse_extraction() {
+        let text = "I'll call it now: {tool: 'ping', arguments: {}} — that's all.";
+        let parsed = parse_freeform_call_repaired(text).unwrap();
+        assert_eq!(parsed.value.name, "ping");
+    }
+
+    #[test]
+    fn strict_call_is_not_flagged() {
+        let parsed = parse_freeform_call_repaired(r#"{"tool": "ping"}"#).unwrap();
+        assert!(!parsed.applied);
+    }
+
+    #[test]
+    fn hopeless_input_stays_none() {
+        assert!(parse_json("{{{").is_none());
+        assert!(parse_freeform_call_repaired("no json here").is_none());
+    }
+}
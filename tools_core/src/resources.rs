@@ -0,0 +1,62 @@
+//! Statics registered with `#[tool_resource]`, looked up by type.
+//!
+//! Shared clients and configuration usually live in a static anyway.
+//! Marking it `#[tool_resource]` lets tools receive it by type without
+//! threading a context through the collection:
+//!
+//! ```ignore
+//! #[tool_resource]
+//! static HTTP: Lazy<reqwest::Client> = Lazy::new(reqwest::Client::new);
+//!
+//! #[tool]
+//! async fn fetch(#[tool(inject)] client: &reqwest::Client, url: String) -> String { ... }
+//! ```
+//!
+//! Injected parameters are left out of the tool's schema. A `Lazy` or
+//! `LazyLock` static is registered under the type it derefs to and is
+//! initialized on first lookup. Each type may be registered once; if two
+//! resources share a type, lookups of it fail.
+
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+};
+
+use once_cell::sync::Lazy;
+
+/// Compile-time registration submitted by `#[tool_resource]`.
+pub struct ResourceRegistration {
+    /// The resource type as written in the source.
+    pub type_name: &'static str,
+    pub type_id: fn() -> TypeId,
+    /// Returns the static, initializing it first if it is lazy.
+    pub get: fn() -> &'static (dyn Any + Send + Sync),
+}
+
+inventory::collect!(ResourceRegistration);
+
+type Getter = fn() -> &'static (dyn Any + Send + Sync);
+
+/// `None` marks a type registered more than once.
+static RESOURCES: Lazy<HashMap<TypeId, Option<Getter>>> = Lazy::new(|| {
+    let mut map = HashMap::new();
+    for reg in inventory::iter::<ResourceRegistration> {
+        map.entry((reg.type_id)())
+            .and_modify(|slot| *slot = None)
+            .or_insert(Some(reg.get));
+    }
+    map
+});
+
+/// The resource of type `T`, if exactly one is registered.
+pub fn get<T: Any + Send + Sync>() -> Option<&'static T> {
+    let get = (*RESOURCES.get(&TypeId::of::<T>())?)?;
+    get().downcast_ref::<T>()
+}
+
+/// Type names of all registered resources.
+pub fn registered() -> impl Iterator<Item = &'static str> {
+    inventory::iter::<ResourceRegistration>
+        .into_iter()
+        .map(|reg| reg.type_name)
+}
@@ -0,0 +1,247 @@
+//! Running a tool on an interval.
+//!
+//! Some tools double as maintenance jobs: refresh a cache, rotate a token.
+//! [`ToolCollection::schedule`] calls one every `every` through the
+//! normal call path, so timeouts, breakers, usage statistics and every
+//! other setting of the collection apply:
+//!
+//! ```ignore
+//! let refresh = tools.schedule("refresh_rates", Duration::from_secs(300), json!({}))?;
+//! // ...
+//! refresh.cancel();
+//! ```
+//!
+//! The first run is one interval after scheduling. Runs don't overlap: if
+//! the previous run is still going when the next is due, that run is
+//! [skipped][ScheduleReport::skipped]. After a failed run, one that
+//! returned an error or a [domain error][crate::domain], the wait
+//! doubles with each consecutive failure, up to
+//! [`max_backoff`][ScheduleConfig::max_backoff], and a success resets it.
+//! Up to [`jitter`][ScheduleConfig::jitter] is added to every wait at
+//! random, so schedules made at the same moment drift apart.
+//!
+//! The schedule runs on a task of its own, with a clone of the collection
+//! made when it was scheduled; later changes to the original don't reach
+//! it. Dropping the [`ScheduleHandle`] cancels the schedule, as does
+//! [`cancel`][ScheduleHandle::cancel]. A run in progress is not
+//! interrupted. [`schedule_with_clock`][ToolCollection::schedule_with_clock]
+//! takes a [`ManualClock`][crate::circuit::ManualClock] for tests.
+
+use std::{
+    borrow::Cow,
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use serde_json::Value;
+use tokio::task::JoinHandle;
+
+use crate::{
+    FunctionCall, ToolCollection, ToolError,
+    circuit::{Clock, SystemClock},
+};
+
+/// How a tool is scheduled.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScheduleConfig {
+    /// Time between runs.
+    pub every: Duration,
+    /// Arguments of every run.
+    pub arguments: Value,
+    /// Most that is added to each wait, at random. Zero by default.
+    pub jitter: Duration,
+    /// Longest wait after failures. Never shorter than `every`.
+    pub max_backoff: Duration,
+}
+
+impl ScheduleConfig {
+    /// Runs every `every` with `arguments`, no jitter, and a backoff of
+    /// up to 16 intervals.
+    pub fn new(every: Duration, arguments: Value) -> Self {
+        Self {
+            every,
+            arguments,
+            jitter: Duration::ZERO,
+            max_backoff: every.saturating_mul(16),
+        }
+    }
+}
+
+/// What a schedule has done so far, from [`ScheduleHandle::report`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ScheduleReport {
+    pub tool: Cow<'static, str>,
+    /// Runs started.
+    pub runs: u64,
+    pub succeeded: u64,
+    pub failed: u64,
+    /// Runs not started because the previous one was still going.
+    pub skipped: u64,
+    /// Failures since the last success; each doubles the wait.
+    pub consecutive_failures: u32,
+    /// Whether a run is going now.
+    pub running: bool,
+    pub cancelled: bool,
+}
+
+#[derive(Debug)]
+struct State {
+    report: ScheduleReport,
+    /// When the last run started.
+    last_start: Option<Instant>,
+}
+
+fn lock<T>(m: &Mutex<T>) -> MutexGuard<'_, T> {
+    m.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// A scheduled tool; cancels the schedule when dropped.
+#[must_use = "dropping the handle cancels the schedule"]
+#[derive(Debug)]
+pub struct ScheduleHandle {
+    state: Arc<Mutex<State>>,
+    task: JoinHandle<()>,
+}
+
+impl ScheduleHandle {
+    /// Stop scheduling runs. A run in progress finishes.
+    pub fn cancel(&self) {
+        lock(&self.state).report.cancelled = true;
+        self.task.abort();
+    }
+
+    pub fn report(&self) -> ScheduleReport {
+        lock(&self.state).report.clone()
+    }
+}
+
+impl Drop for ScheduleHandle {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+impl<M: Clone + Send + Sync + 'static> ToolCollection<M> {
+    /// Call tool `name` with `arguments` every `every`; see
+    /// [`schedule`][crate::schedule]. Fails with
+    /// [`FunctionNotFound`][ToolError::FunctionNotFound] for an unknown
+    /// tool. Must be called within a tokio runtime.
+    pub fn schedule(
+        &self,
+        name: &str,
+        every: Duration,
+        arguments: Value,
+    ) -> Result<ScheduleHandle, ToolError> {
+        self.schedule_with_clock(
+            name,
+            ScheduleConfig::new(every, arguments),
+            Arc::new(SystemClock),
+        )
+    }
+
+    /// Like [`schedule`][Self::schedule], with jitter and backoff from
+    /// `config` and time from `clock`.
+    pub fn schedule_with_clock(
+        &self,
+        name: &str,
+        config: ScheduleConfig,
+        clock: Arc<dyn Clock>,
+    ) -> Result<ScheduleHandle, ToolError> {
+        let tool = self
+            .get(name)
+            .ok_or_else(|| ToolError::FunctionNotFound {
+                name: Cow::Owned(name.to_string()),
+            })?
+            .decl
+            .name
+            .clone();
+        let state = Arc::new(Mutex::new(State {
+            report: ScheduleReport {
+                tool,
+                runs: 0,
+                succeeded: 0,
+                failed: 0,
+                skipped: 0,
+                consecutive_failures: 0,
+                running: false,
+                cancelled: false,
+            },
+            last_start: None,
+        }));
+        let first = clock.now() + wait(config.every, config.jitter);
+        let task = tokio::spawn(run(self.clone(), config, clock, state.clone(), first));
+        Ok(ScheduleHandle { state, task })
+    }
+}
+
+/// Wake when the next run is due, from `due` on, and start it unless the
+/// last one is still going or backing off.
+async fn run<M: Send + Sync + 'static>(
+    tools: ToolCollection<M>,
+    config: ScheduleConfig,
+    clock: Arc<dyn Clock>,
+    state: Arc<Mutex<State>>,
+    mut due: Instant,
+) {
+    let tools = Arc::new(tools);
+    loop {
+        clock.sleep_until(due).await;
+        let now = clock.now();
+        let tool = {
+            let mut state = lock(&state);
+            if state.report.running {
+                state.report.skipped += 1;
+                due = now + wait(config.every, config.jitter);
+                continue;
+            }
+            let failures = state.report.consecutive_failures;
+            if let Some(last_start) = state.last_start.filter(|_| failures > 0) {
+                let earliest = last_start + backoff(&config, failures);
+                if now < earliest {
+                    due = earliest + wait(Duration::ZERO, config.jitter);
+                    continue;
+                }
+            }
+            state.report.running = true;
+            state.report.runs += 1;
+            state.last_start = Some(now);
+            state.report.tool.clone()
+        };
+        let (tools, state) = (tools.clone(), state.clone());
+        let call = FunctionCall::new(tool.to_string(), config.arguments.clone());
+        tokio::spawn(async move {
+            let ok = matches!(tools.call(call).await, Ok(response) if !response.is_error);
+            let mut state = lock(&state);
+            state.report.running = false;
+            if ok {
+                state.report.succeeded += 1;
+                state.report.consecutive_failures = 0;
+            } else {
+                state.report.failed += 1;
+                state.report.consecutive_failures += 1;
+            }
+        });
+        due = now + wait(config.every, config.jitter);
+    }
+}
+
+/// `every` plus up to `jitter`, at random.
+fn wait(every: Duration, jitter: Duration) -> Duration {
+    if jitter.is_zero() {
+        return every;
+    }
+    let random = RandomState::new().build_hasher().finish();
+    every + jitter.mul_f64((random >> 11) as f64 / (1u64 << 53) as f64)
+}
+
+/// Time from the start of a run to the next after `failures` consecutive
+/// failures: `every` doubled per failure, at most `max_backoff`.
+fn backoff(config: &ScheduleConfig, failures: u32) -> Duration {
+    let doubled = config
+        .every
+        .saturating_mul(2u32.saturating_pow(failures.min(31)));
+    doubled.min(config.max_backoff.max(config.every))
+}
@@ -0,0 +1,184 @@
+//! Which JSON Schema dialect a stored schema is written in.
+//!
+//! The `ToolSchema` derive emits JSON Schema 2020-12 (`prefixItems`,
+//! `anyOf [.., {"type": "null"}]`). A schema passed to
+//! [`params_schema`][crate::ToolSpec::params_schema] may instead be
+//! schemars output marked `"$schema": "http://json-schema.org/draft-07/schema#"`,
+//! or already down-converted to the OpenAPI 3.0 subset Gemini accepts.
+//! Each [`FunctionDecl`][crate::FunctionDecl] records its dialect, so an
+//! exporter converts from what the schema is rather than guessing:
+//!
+//! ```ignore
+//! let decl = &tools.get("search").unwrap().decl;
+//! let gemini_params = decl.parameters_as(SchemaDialect::OpenApi30).expect("convertible");
+//! ```
+//!
+//! The dialect is taken from [`ToolSpec::schema_dialect`][crate::ToolSpec::schema_dialect]
+//! when set, else from the schema's `$schema` URI, else 2020-12.
+//! [`set_schema_uris`][crate::ToolCollection::set_schema_uris] makes
+//! [`json`][crate::ToolCollection::json] write the URI into each
+//! declaration's parameters.
+
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    FunctionDecl, ToolCollection,
+    schema::transform::{
+        anyof_null_to_nullable, apply, flatten_single_element_tuples, inline_refs,
+        prefix_items_to_items, strip_keywords,
+    },
+};
+
+/// Dialect of a parameter or result schema.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SchemaDialect {
+    /// JSON Schema 2020-12, as the `ToolSchema` derive emits.
+    #[default]
+    Draft2020_12,
+    /// JSON Schema draft-07, as schemars 0.8 emits.
+    Draft07,
+    /// The OpenAPI 3.0 schema subset: `nullable`, no `prefixItems`, no
+    /// `$ref`. What Gemini accepts.
+    OpenApi30,
+}
+
+impl SchemaDialect {
+    /// The `$schema` URI identifying the dialect. OpenAPI 3.0 schemas have
+    /// none.
+    pub fn uri(self) -> Option<&'static str> {
+        match self {
+            Self::Draft2020_12 => Some("https://json-schema.org/draft/2020-12/schema"),
+            Self::Draft07 => Some("http://json-schema.org/draft-07/schema#"),
+            Self::OpenApi30 => None,
+        }
+    }
+
+    /// The dialect named by `schema`'s `$schema` URI, if it names one this
+    /// crate knows.
+    pub fn from_schema(schema: &Value) -> Option<Self> {
+        let uri = schema["$schema"].as_str()?;
+        if uri.contains("2020-12") {
+            Some(Self::Draft2020_12)
+        } else if uri.contains("draft-07") {
+            Some(Self::Draft07)
+        } else {
+            None
+        }
+    }
+
+    /// `schema`, written in `self`, rewritten for `target`. `None` when
+    /// there is no conversion: only down-conversion to
+    /// [`OpenApi30`][Self::OpenApi30] is supported besides the identity.
+    pub fn convert<'a>(self, schema: &'a Value, target: Self) -> Option<Cow<'a, Value>> {
+        if self == target {
+            return Some(Cow::Borrowed(schema));
+        }
+        if target != Self::OpenApi30 || self == Self::OpenApi30 {
+            return None;
+        }
+        let strip = strip_keywords(&["$schema"]);
+        Some(Cow::Owned(apply(
+            schema,
+            &[
+                &inline_refs,
+                &flatten_single_element_tuples,
+                &prefix_items_to_items,
+                &anyof_null_to_nullable,
+                &strip,
+            ],
+        )))
+    }
+}
+
+impl FunctionDecl<'_> {
+    /// The parameter schema in `target`'s dialect; see
+    /// [`SchemaDialect::convert`].
+    pub fn parameters_as(&self, target: SchemaDialect) -> Option<Cow<'_, Value>> {
+        self.dialect.convert(&self.parameters, target)
+    }
+
+    /// `parameters` with a `$schema` URI for the declaration's dialect, if
+    /// it has one and the schema doesn't already.
+    pub(crate) fn annotated_parameters(&self) -> Cow<'_, Value> {
+        match (self.dialect.uri(), &self.parameters) {
+            (Some(uri), Value::Object(map)) if !map.contains_key("$schema") => {
+                let mut map = map.clone();
+                map.insert("$schema".into(), uri.into());
+                Cow::Owned(Value::Object(map))
+            }
+            _ => Cow::Borrowed(&self.parameters),
+        }
+    }
+}
+
+impl<M> ToolCollection<M> {
+    /// Write each declaration's `$schema` URI into its parameters in
+    /// [`json`][Self::json]. Off by default; not every provider accepts
+    /// the keyword.
+    pub fn set_schema_uris(&mut self, on: bool) {
+        if self.schema_uris != on {
+            self.schema_uris = on;
+            self.json_cache.take();
+        }
+    }
+
+    pub fn schema_uris(&self) -> bool {
+        self.schema_uris
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn detects_known_uris() {
+        let draft07 = json!({ "$schema": "http://json-schema.org/draft-07/schema#" });
+        assert_eq!(
+            SchemaDialect::from_schema(&draft07),
+            Some(SchemaDialect::Draft07)
+        );
+        let current = json!({ "$schema": SchemaDialect::Draft2020_12.uri() });
+        assert_eq!(
+            SchemaDialect::from_schema(&current),
+            Some(SchemaDialect::Draft2020_12)
+        );
+        let other = json!({ "$schema": "https://json-schema.org/draft/2019-09/schema" });
+        assert_eq!(SchemaDialect::from_schema(&other), None);
+        assert_eq!(SchemaDialect::from_schema(&json!({})), None);
+    }
+
+    #[test]
+    fn only_converts_down_to_openapi() {
+        let schema = json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "type": "object",
+            "properties": { "note": { "anyOf": [{ "type": "string" }, { "type": "null" }] } }
+        });
+        let down = SchemaDialect::Draft07
+            .convert(&schema, SchemaDialect::OpenApi30)
+            .unwrap();
+        assert_eq!(
+            *down,
+            json!({
+                "type": "object",
+                "properties": { "note": { "type": "string", "nullable": true } }
+            })
+        );
+        assert!(
+            SchemaDialect::OpenApi30
+                .convert(&down, SchemaDialect::Draft2020_12)
+                .is_none()
+        );
+        assert!(
+            SchemaDialect::Draft07
+                .convert(&schema, SchemaDialect::Draft2020_12)
+                .is_none()
+        );
+    }
+}
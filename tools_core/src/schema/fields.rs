@@ -0,0 +1,66 @@
+//! Object schemas built from a static field table.
+//!
+//! `#[tool]` describes each function's parameters as a `&'static
+//! [FieldDesc]` and builds the schema with [`object_schema`], instead of
+//! expanding its own `json!` construction per tool. With thousands of
+//! generated tools that keeps compile time and binary size down: the only
+//! per-tool code left is the table itself.
+
+use serde_json::{Map, Value, json};
+
+/// One property of an object schema.
+#[derive(Debug, Clone, Copy)]
+pub struct FieldDesc {
+    pub name: &'static str,
+    /// Schema of the field's type, usually `<T as ToolSchema>::schema`.
+    pub schema: fn() -> Value,
+    /// Whether the field must be present.
+    pub required: bool,
+    /// The parameter's doc comment, set as the property's `description`.
+    pub description: Option<&'static str>,
+}
+
+/// `{"type": "object", "properties": ..., "required": [...]}` for
+/// `fields`, with `required` in table order.
+pub fn object_schema(fields: &[FieldDesc]) -> Value {
+    let properties: Map<String, Value> = fields
+        .iter()
+        .map(|f| {
+            let schema = (f.schema)();
+            let schema = match f.description {
+                Some(description) => describe(schema, description),
+                None => schema,
+            };
+            (f.name.to_string(), schema)
+        })
+        .collect();
+    let required: Vec<&str> = fields
+        .iter()
+        .filter(|f| f.required)
+        .map(|f| f.name)
+        .collect();
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required
+    })
+}
+
+/// `schema` with `description` as its `description`, replacing the one
+/// its type gave it. Non-object schemas (`true`) are returned unchanged.
+pub fn describe(mut schema: Value, description: &str) -> Value {
+    if let Some(object) = schema.as_object_mut() {
+        object.insert("description".into(), Value::from(description));
+    }
+    schema
+}
+
+/// Placeholder schema for a `#[tool(schema(param = "opaque"))]`
+/// parameter: its type isn't described, only named.
+pub fn opaque<T: ?Sized>() -> Value {
+    json!({
+        "type": "object",
+        "description": std::any::type_name::<T>(),
+        "x-unvalidated": true
+    })
+}
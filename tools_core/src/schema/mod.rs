@@ -0,0 +1,13 @@
+//! Utilities for working with generated JSON schemas.
+//!
+//! The [`ToolSchema`][crate::ToolSchema] trait and its implementations live
+//! at the crate root; this module holds the post-processing that adapts
+//! those schemas to what individual providers accept, and the table-driven
+//! object builder `#[tool]` expands to.
+
+pub mod dialect;
+pub mod fields;
+pub mod transform;
+
+pub use dialect::SchemaDialect;
+pub use fields::{FieldDesc, object_schema};
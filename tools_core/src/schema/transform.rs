@@ -0,0 +1,507 @@
+//! Composable schema down-conversion passes.
+//!
+//! Providers accept different JSON Schema subsets: Gemini rejects `$defs`
+//! and `prefixItems`, OpenAPI-3.0-style consumers want `nullable` instead
+//! of `anyOf [.., null]`, and so on. Each pass here is a pure
+//! `fn(&Value) -> Value` that rewrites one construct everywhere it appears;
+//! a provider format is a list of passes run by [`apply`].
+//!
+//! ```ignore
+//! use tools_core::schema::transform::*;
+//!
+//! let strip = strip_keywords(&["format", "examples"]);
+//! let gemini = apply(
+//!     &schema,
+//!     &[
+//!         &inline_refs,
+//!         &flatten_single_element_tuples,
+//!         &prefix_items_to_items,
+//!         &anyof_null_to_nullable,
+//!         &strip,
+//!     ],
+//! );
+//! ```
+
+use serde_json::{Map, Value};
+
+/// Run `passes` over `schema` in order.
+pub fn apply(schema: &Value, passes: &[&dyn Fn(&Value) -> Value]) -> Value {
+    passes.iter().fold(schema.clone(), |acc, pass| pass(&acc))
+}
+
+// ============================================================================
+// TRAVERSAL
+// ============================================================================
+
+/// Keywords whose value is a single subschema.
+const SCHEMA_KEYWORDS: &[&str] = &["items", "additionalProperties", "not"];
+/// Keywords whose value is an array of subschemas.
+const SCHEMA_ARRAY_KEYWORDS: &[&str] = &["prefixItems", "anyOf", "oneOf", "allOf", "items"];
+/// Keywords whose value maps names to subschemas. The names themselves are
+/// not keywords and must never be rewritten.
+const SCHEMA_MAP_KEYWORDS: &[&str] = &["properties", "$defs", "definitions"];
+
+/// Rebuild `schema` with `f` applied to every direct subschema. Passes call
+/// this first and then rewrite the node itself, so they work bottom-up.
+fn map_children(schema: &Value, f: &dyn Fn(&Value) -> Value) -> Value {
+    let Value::Object(obj) = schema else {
+        return schema.clone();
+    };
+
+    let mut out = Map::with_capacity(obj.len());
+    for (key, value) in obj {
+        let k = key.as_str();
+        let mapped = match value {
+            Value::Object(_) if SCHEMA_KEYWORDS.contains(&k) => f(value),
+            Value::Array(items) if SCHEMA_ARRAY_KEYWORDS.contains(&k) => {
+                Value::Array(items.iter().map(f).collect())
+            }
+            Value::Object(entries) if SCHEMA_MAP_KEYWORDS.contains(&k) => Value::Object(
+                entries
+                    .iter()
+                    .map(|(name, sub)| (name.clone(), f(sub)))
+                    .collect(),
+            ),
+            _ => value.clone(),
+        };
+        out.insert(key.clone(), mapped);
+    }
+    Value::Object(out)
+}
+
+// ============================================================================
+// PASSES
+// ============================================================================
+
+/// Replace local `$ref`s (`#/$defs/X`, `#/definitions/X`) with the
+/// referenced schema and drop the definition tables. Recursive references
+/// are left as `$ref` at the point where they would loop.
+pub fn inline_refs(schema: &Value) -> Value {
+    let mut defs = Map::new();
+    for table in ["$defs", "definitions"] {
+        if let Some(Value::Object(entries)) = schema.get(table) {
+            for (name, def) in entries {
+                defs.insert(format!("#/{table}/{name}"), def.clone());
+            }
+        }
+    }
+
+    let mut root = schema.clone();
+    if let Value::Object(obj) = &mut root {
+        obj.remove("$defs");
+        obj.remove("definitions");
+    }
+    inline_with(&root, &defs, &[])
+}
+
+/// `stack` holds the `$ref`s currently being expanded, to detect cycles.
+fn inline_with(schema: &Value, defs: &Map<String, Value>, stack: &[&str]) -> Value {
+    if let Some(target) = schema.get("$ref").and_then(Value::as_str) {
+        if let Some(def) = defs.get(target) {
+            if stack.contains(&target) {
+                return schema.clone();
+            }
+            let mut nested = stack.to_vec();
+            nested.push(target);
+            let mut inlined = inline_with(def, defs, &nested);
+
+            // Sibling keywords next to `$ref` (e.g. a description) win.
+            if let (Value::Object(out), Value::Object(siblings)) = (&mut inlined, schema) {
+                for (k, v) in siblings {
+                    if k != "$ref" {
+                        out.insert(k.clone(), v.clone());
+                    }
+                }
+            }
+            return inlined;
+        }
+    }
+    map_children(schema, &|sub| inline_with(sub, defs, stack))
+}
+
+/// Replace `prefixItems` with a single `items` schema: the shared item
+/// schema when all positions agree, otherwise an `anyOf` of the distinct
+/// ones. `minItems` / `maxItems` are kept.
+pub fn prefix_items_to_items(schema: &Value) -> Value {
+    let mut out = map_children(schema, &prefix_items_to_items);
+    let Value::Object(obj) = &mut out else {
+        return out;
+    };
+    let Some(Value::Array(prefix)) = obj.remove("prefixItems") else {
+        return out;
+    };
+
+    let mut distinct: Vec<Value> = Vec::new();
+    for item in prefix {
+        if !distinct.contains(&item) {
+            distinct.push(item);
+        }
+    }
+    let items = match distinct.len() {
+        0 => None,
+        1 => distinct.pop(),
+        _ => Some(serde_json::json!({ "anyOf": distinct })),
+    };
+    if let Some(items) = items {
+        obj.insert("items".to_string(), items);
+    }
+    out
+}
+
+/// Rewrite `{"anyOf": [X, {"type": "null"}]}` as `X` plus
+/// `"nullable": true`. With several non-null variants the `anyOf` stays
+/// (minus the null branch) and gains `"nullable": true`.
+pub fn anyof_null_to_nullable(schema: &Value) -> Value {
+    let mut out = map_children(schema, &anyof_null_to_nullable);
+    let Value::Object(obj) = &mut out else {
+        return out;
+    };
+    let Some(Value::Array(variants)) = obj.get("anyOf") else {
+        return out;
+    };
+
+    let is_null = |v: &Value| v.get("type").and_then(Value::as_str) == Some("null");
+    if !variants.iter().any(is_null) {
+        return out;
+    }
+    let mut rest: Vec<Value> = variants.iter().filter(|v| !is_null(v)).cloned().collect();
+    obj.remove("anyOf");
+
+    if rest.len() == 1 {
+        if let Value::Object(inner) = rest.pop().unwrap() {
+            for (k, v) in inner {
+                obj.entry(k).or_insert(v);
+            }
+        }
+    } else if !rest.is_empty() {
+        obj.insert("anyOf".to_string(), Value::Array(rest));
+    } else {
+        obj.insert("type".to_string(), Value::String("null".to_string()));
+    }
+    obj.insert("nullable".to_string(), Value::Bool(true));
+    out
+}
+
+/// Build a pass that removes the given keywords from every schema node.
+/// Property names that happen to match a keyword are left alone.
+pub fn strip_keywords<'a>(keywords: &'a [&'a str]) -> impl Fn(&Value) -> Value + 'a {
+    move |schema| strip_with(schema, keywords)
+}
+
+fn strip_with(schema: &Value, keywords: &[&str]) -> Value {
+    let mut out = map_children(schema, &|sub| strip_with(sub, keywords));
+    if let Value::Object(obj) = &mut out {
+        obj.retain(|k, _| !keywords.contains(&k.as_str()));
+    }
+    out
+}
+
+/// Close every object schema that lists `properties`, as OpenAI's strict
+/// mode demands: `additionalProperties: false`, and every property
+/// `required`. Properties that were optional become nullable, so a model
+/// leaves them out by sending `null`.
+pub fn close_objects(schema: &Value) -> Value {
+    let mut out = map_children(schema, &close_objects);
+    let Value::Object(obj) = &mut out else {
+        return out;
+    };
+    let Some(Value::Object(properties)) = obj.get_mut("properties") else {
+        return out;
+    };
+    let required: Vec<&str> = schema["required"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .collect();
+    for (name, property) in properties.iter_mut() {
+        if !required.contains(&name.as_str()) && !is_nullable(property) {
+            *property = match property.take() {
+                Value::Object(mut p) if p.get("type").is_some_and(Value::is_string) => {
+                    let ty = p.remove("type").unwrap_or_default();
+                    p.insert("type".to_string(), Value::Array(vec![ty, "null".into()]));
+                    Value::Object(p)
+                }
+                other => serde_json::json!({ "anyOf": [other, { "type": "null" }] }),
+            };
+        }
+    }
+    let names: Vec<Value> = properties.keys().cloned().map(Value::String).collect();
+    obj.insert("required".to_string(), Value::Array(names));
+    obj.insert("additionalProperties".to_string(), Value::Bool(false));
+    out
+}
+
+/// Whether `schema` admits `null` at its top level.
+fn is_nullable(schema: &Value) -> bool {
+    let null = Value::from("null");
+    match schema.get("type") {
+        Some(Value::String(ty)) if ty == "null" => return true,
+        Some(Value::Array(types)) if types.contains(&null) => return true,
+        _ => {}
+    }
+    ["anyOf", "oneOf"].iter().any(|k| {
+        schema[*k]
+            .as_array()
+            .is_some_and(|branches| branches.iter().any(is_nullable))
+    })
+}
+
+/// Collapse one-element tuple schemas — what newtype structs like
+/// `struct UserId(u64)` derive — to their single element.
+pub fn flatten_single_element_tuples(schema: &Value) -> Value {
+    let out = map_children(schema, &flatten_single_element_tuples);
+    match out.get("prefixItems").and_then(Value::as_array) {
+        Some(items) if items.len() == 1 && out.get("type") == Some(&Value::from("array")) => {
+            items[0].clone()
+        }
+        _ => out,
+    }
+}
+
+/// Fold `x-enum-descriptions`, the per-value descriptions the derive emits
+/// next to `enum` for `#[schema(variant_description = "...")]`, into the
+/// node's `description` for providers that reject unknown keywords.
+pub fn fold_enum_descriptions(schema: &Value) -> Value {
+    let mut out = map_children(schema, &fold_enum_descriptions);
+    let Value::Object(obj) = &mut out else {
+        return out;
+    };
+    let Some(Value::Array(descriptions)) = obj.remove("x-enum-descriptions") else {
+        return out;
+    };
+    let values = obj
+        .get("enum")
+        .or_else(|| obj.get("examples"))
+        .and_then(Value::as_array);
+    let lines: Vec<String> = values
+        .into_iter()
+        .flatten()
+        .zip(&descriptions)
+        .filter_map(|(value, description)| {
+            let description = description.as_str().filter(|d| !d.is_empty())?;
+            Some(format!("{value}: {description}"))
+        })
+        .collect();
+    if lines.is_empty() {
+        return out;
+    }
+    let folded = match obj.get("description").and_then(Value::as_str) {
+        Some(existing) => format!("{existing}\n{}", lines.join("\n")),
+        None => lines.join("\n"),
+    };
+    obj.insert("description".to_string(), Value::String(folded));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn inline_refs_resolves_defs_and_drops_table() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "home": { "$ref": "#/$defs/Address", "description": "Home address" },
+                "work": { "$ref": "#/definitions/Address" }
+            },
+            "$defs": { "Address": { "type": "object", "properties": { "city": { "type": "string" } } } },
+            "definitions": { "Address": { "type": "string" } }
+        });
+        let out = inline_refs(&schema);
+        assert!(out.get("$defs").is_none());
+        assert!(out.get("definitions").is_none());
+        assert_eq!(
+            out["properties"]["home"]["properties"]["city"]["type"],
+            "string"
+        );
+        assert_eq!(out["properties"]["home"]["description"], "Home address");
+        assert_eq!(out["properties"]["work"], json!({ "type": "string" }));
+    }
+
+    #[test]
+    fn inline_refs_stops_at_cycles() {
+        let schema = json!({
+            "$ref": "#/$defs/Node",
+            "$defs": {
+                "Node": {
+                    "type": "object",
+                    "properties": { "next": { "$ref": "#/$defs/Node" } }
+                }
+            }
+        });
+        let out = inline_refs(&schema);
+        assert_eq!(out["type"], "object");
+        assert_eq!(out["properties"]["next"], json!({ "$ref": "#/$defs/Node" }));
+    }
+
+    #[test]
+    fn prefix_items_collapse_to_items() {
+        let same = json!({ "type": "array", "prefixItems": [{ "type": "integer" }, { "type": "integer" }], "minItems": 2, "maxItems": 2 });
+        assert_eq!(
+            prefix_items_to_items(&same),
+            json!({ "type": "array", "items": { "type": "integer" }, "minItems": 2, "maxItems": 2 })
+        );
+
+        let mixed = json!({ "type": "array", "prefixItems": [{ "type": "integer" }, { "type": "string" }, { "type": "integer" }] });
+        assert_eq!(
+            prefix_items_to_items(&mixed)["items"],
+            json!({ "anyOf": [{ "type": "integer" }, { "type": "string" }] })
+        );
+    }
+
+    #[test]
+    fn anyof_null_becomes_nullable() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "age": { "anyOf": [{ "type": "integer" }, { "type": "null" }] },
+                "id": { "anyOf": [{ "type": "integer" }, { "type": "string" }, { "type": "null" }] },
+                "plain": { "anyOf": [{ "type": "integer" }, { "type": "string" }] }
+            }
+        });
+        let out = anyof_null_to_nullable(&schema);
+        assert_eq!(
+            out["properties"]["age"],
+            json!({ "type": "integer", "nullable": true })
+        );
+        assert_eq!(
+            out["properties"]["id"],
+            json!({ "anyOf": [{ "type": "integer" }, { "type": "string" }], "nullable": true })
+        );
+        assert_eq!(out["properties"]["plain"], schema["properties"]["plain"]);
+    }
+
+    #[test]
+    fn close_objects_requires_every_property() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "query": { "type": "string" },
+                "limit": { "type": "integer" },
+                "page": { "anyOf": [{ "type": "integer" }, { "type": "null" }] },
+                "filter": {
+                    "type": "object",
+                    "properties": { "tag": { "enum": ["a", "b"] } }
+                }
+            },
+            "required": ["query"]
+        });
+        let out = close_objects(&schema);
+        assert_eq!(out["additionalProperties"], false);
+        assert_eq!(out["required"], json!(["filter", "limit", "page", "query"]));
+        assert_eq!(out["properties"]["query"], json!({ "type": "string" }));
+        assert_eq!(
+            out["properties"]["limit"],
+            json!({ "type": ["integer", "null"] })
+        );
+        assert_eq!(out["properties"]["page"], schema["properties"]["page"]);
+        let filter = &out["properties"]["filter"];
+        assert_eq!(filter["type"], json!(["object", "null"]));
+        assert_eq!(filter["additionalProperties"], false);
+        assert_eq!(
+            filter["properties"]["tag"],
+            json!({ "anyOf": [{ "enum": ["a", "b"] }, { "type": "null" }] })
+        );
+    }
+
+    #[test]
+    fn strip_keywords_spares_property_names() {
+        let schema = json!({
+            "type": "object",
+            "examples": [{}],
+            "properties": {
+                "format": { "type": "string", "format": "date" },
+                "tags": { "type": "array", "items": { "type": "string", "examples": ["a"] } }
+            }
+        });
+        let out = strip_keywords(&["format", "examples"])(&schema);
+        assert!(out.get("examples").is_none());
+        assert_eq!(out["properties"]["format"], json!({ "type": "string" }));
+        assert_eq!(
+            out["properties"]["tags"]["items"],
+            json!({ "type": "string" })
+        );
+    }
+
+    #[test]
+    fn single_element_tuples_flatten() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "id": { "type": "array", "prefixItems": [{ "type": "integer" }], "minItems": 1, "maxItems": 1 },
+                "pair": { "type": "array", "prefixItems": [{ "type": "integer" }, { "type": "integer" }] }
+            }
+        });
+        let out = flatten_single_element_tuples(&schema);
+        assert_eq!(out["properties"]["id"], json!({ "type": "integer" }));
+        assert_eq!(out["properties"]["pair"], schema["properties"]["pair"]);
+    }
+
+    #[test]
+    fn enum_descriptions_fold_into_description() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "level": {
+                    "type": "string",
+                    "description": "Severity.",
+                    "enum": ["LOW", "HIGH", "CRITICAL"],
+                    "x-enum-descriptions": ["Can wait.", "", "Page someone."]
+                }
+            }
+        });
+        let out = fold_enum_descriptions(&schema);
+        assert_eq!(
+            out["properties"]["level"],
+            json!({
+                "type": "string",
+                "description": "Severity.\n\"LOW\": Can wait.\n\"CRITICAL\": Page someone.",
+                "enum": ["LOW", "HIGH", "CRITICAL"]
+            })
+        );
+    }
+
+    #[test]
+    fn passes_compose() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "id": { "$ref": "#/$defs/UserId" },
+                "nick": { "anyOf": [{ "type": "string", "format": "hostname" }, { "type": "null" }] }
+            },
+            "$defs": { "UserId": { "type": "array", "prefixItems": [{ "type": "integer" }], "minItems": 1, "maxItems": 1 } }
+        });
+        let strip = strip_keywords(&["format"]);
+        let out = apply(
+            &schema,
+            &[
+                &inline_refs,
+                &flatten_single_element_tuples,
+                &prefix_items_to_items,
+                &anyof_null_to_nullable,
+                &strip,
+            ],
+        );
+        assert_eq!(
+            out,
+            json!({
+                "type": "object",
+                "properties": {
+                    "id": { "type": "integer" },
+                    "nick": { "type": "string", "nullable": true }
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn passes_leave_input_untouched() {
+        let schema = json!({ "anyOf": [{ "type": "integer" }, { "type": "null" }] });
+        let before = schema.clone();
+        let _ = anyof_null_to_nullable(&schema);
+        assert_eq!(schema, before);
+    }
+}
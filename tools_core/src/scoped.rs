@@ -0,0 +1,101 @@
+//! Tools that live only as long as a guard.
+//!
+//! [`SharedToolCollection::register_scoped`] returns a [`ToolGuard`] that
+//! unregisters the tool when dropped, so a session-scoped tool can't
+//! outlive its session. The guard holds a handle to the shared collection
+//! and derefs to it; use it, or any other clone of the handle, to call
+//! tools while it is alive.
+//!
+//! ```ignore
+//! let tools = SharedToolCollection::new(collect_tools());
+//! {
+//!     let session = tools.register_scoped("cart", "The session's cart.", cart_fn, ())?;
+//!     session.call(FunctionCall::new("cart".into(), json!({}))).await?;
+//! } // `cart` is unregistered here
+//! ```
+//!
+//! Dropping the guard removes the tool through the shared handle, like
+//! [`unregister`][SharedToolCollection::unregister]: calls already running
+//! on it finish before its cleanup starts, and every later call reports it
+//! as not found.
+
+use std::{borrow::Cow, ops::Deref};
+
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::{MetaArg, NoMeta, SharedToolCollection, ToolCollection, ToolError, ToolId, ToolSchema};
+
+/// Unregisters its tool when dropped, unless [`keep`][Self::keep] was
+/// called. Derefs to the shared collection.
+pub struct ToolGuard<M: Clone = NoMeta> {
+    tools: SharedToolCollection<M>,
+    name: Cow<'static, str>,
+    id: ToolId,
+    armed: bool,
+}
+
+impl<M: Clone> ToolGuard<M> {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn id(&self) -> ToolId {
+        self.id
+    }
+
+    /// Disarm the guard: the tool stays registered.
+    pub fn keep(mut self) {
+        self.armed = false;
+    }
+}
+
+impl<M: Clone> Deref for ToolGuard<M> {
+    type Target = SharedToolCollection<M>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.tools
+    }
+}
+
+impl<M: Clone> Drop for ToolGuard<M> {
+    fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+        self.tools.update(|tools| {
+            // A tool registered under the same name since isn't ours.
+            if tools.resolve(&self.name) == Some(self.id) {
+                let _ = tools.unregister(&self.name);
+            }
+        });
+    }
+}
+
+impl<M: Clone> SharedToolCollection<M> {
+    /// [`register`][Self::register] a tool that is unregistered again when
+    /// the returned [`ToolGuard`] is dropped. See [`scoped`][crate::scoped].
+    pub fn register_scoped<A, I, O, F, Fut>(
+        &self,
+        name: impl Into<Cow<'static, str>>,
+        desc: impl Into<Cow<'static, str>>,
+        func: F,
+        meta: A,
+    ) -> Result<ToolGuard<M>, ToolError>
+    where
+        A: MetaArg<M>,
+        I: 'static + DeserializeOwned + Serialize + Send + ToolSchema,
+        O: 'static + Serialize + Send + ToolSchema,
+        F: Fn(I) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = O> + Send + 'static,
+    {
+        let name = name.into();
+        let spec = ToolCollection::typed_spec(name.clone(), desc, func, meta)?;
+        let id = self.register_spec(spec)?;
+        Ok(ToolGuard {
+            tools: self.clone(),
+            name,
+            id,
+            armed: true,
+        })
+    }
+}
@@ -0,0 +1,449 @@
+//! Secret values and pluggable secret lookup for tool configuration.
+//!
+//! Tools that talk to external services need API keys. Rather than calling
+//! `std::env::var` inline, put a [`Secrets`] handle in the shared context
+//! and look keys up through it — tests swap in a [`StaticSecrets`] map
+//! without touching the process environment.
+//!
+//! ```ignore
+//! use std::sync::Arc;
+//! use tools_core::{Secrets, StaticSecrets, ToolCollection};
+//!
+//! #[tool]
+//! /// Calls the weather API.
+//! async fn weather(ctx: Secrets, city: String) -> Result<String, String> {
+//!     let key = ctx.require("WEATHER_API_KEY").map_err(|e| e.to_string())?;
+//!     // use key.expose() ...
+//! }
+//!
+//! // Production: read from the environment.
+//! let tools = ToolCollection::<NoMeta>::builder()
+//!     .with_context(Arc::new(Secrets::env()))
+//!     .collect()?;
+//!
+//! // Tests: a fixed map.
+//! let tools = ToolCollection::<NoMeta>::builder()
+//!     .with_context(Arc::new(Secrets::new(
+//!         StaticSecrets::new().with("WEATHER_API_KEY", "test-key"),
+//!     )))
+//!     .collect()?;
+//! ```
+//!
+//! Tools whose context is something else can reach a provider set on the
+//! collection with [`ToolCollection::set_secrets`]. It is in scope for
+//! every call the collection runs, and [`current`] returns it:
+//!
+//! ```ignore
+//! tools.set_secrets(StaticSecrets::new().with("WEATHER_API_KEY", "test-key"));
+//!
+//! // Inside a tool:
+//! let key = secrets::current()
+//!     .ok_or(ToolError::MissingSecret { key: "WEATHER_API_KEY".into() })?
+//!     .require("WEATHER_API_KEY")?;
+//! ```
+//!
+//! Like other task-locals it doesn't cross `tokio::spawn`.
+
+use core::fmt;
+use std::{collections::HashMap, future::Future, sync::Arc};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+use crate::{ToolCollection, ToolError, ToolSchema};
+
+/// What [`Secret`]s print as, and what [`redact`] puts in their place.
+pub const REDACTED: &str = "[REDACTED]";
+
+// ============================================================================
+// SECRET WRAPPER
+// ============================================================================
+
+/// A value that must not end up in logs. `Debug` and `Display` print
+/// `[REDACTED]`; the inner value is only reachable through
+/// [`expose`][Secret::expose].
+#[derive(Clone, Default)]
+pub struct Secret<T>(T);
+
+/// The common case: a secret string such as an API key.
+pub type SecretString = Secret<String>;
+
+impl<T> Secret<T> {
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    /// Borrow the secret value. Keep the borrow short and never format it.
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret([REDACTED])")
+    }
+}
+
+impl<T> fmt::Display for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+/// Secret arguments decode from their plain JSON value.
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Secret<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(Secret)
+    }
+}
+
+/// Serializes as [`REDACTED`], like `Display`: a secret never goes back
+/// out as JSON.
+impl<T> Serialize for Secret<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(REDACTED)
+    }
+}
+
+/// The inner schema marked `"writeOnly": true`, which [`redact`] looks for.
+impl<T: ToolSchema> ToolSchema for Secret<T> {
+    fn schema() -> Value {
+        let mut schema = T::schema();
+        if let Value::Object(map) = &mut schema {
+            map.insert("writeOnly".into(), Value::Bool(true));
+        }
+        schema
+    }
+}
+
+impl From<String> for SecretString {
+    fn from(value: String) -> Self {
+        Secret(value)
+    }
+}
+
+impl From<&str> for SecretString {
+    fn from(value: &str) -> Self {
+        Secret(value.to_string())
+    }
+}
+
+// ============================================================================
+// REDACTION
+// ============================================================================
+
+/// Replace every value in `value` that `schema` marks `"writeOnly": true`
+/// with [`REDACTED`]. Follows `properties`, `items` and
+/// `anyOf`/`oneOf`/`allOf` branches, so `Option<Secret<T>>` and secrets
+/// inside nested structs are found too.
+pub fn redact(schema: &Value, value: &mut Value) {
+    for keyword in ["anyOf", "oneOf", "allOf"] {
+        if let Some(Value::Array(branches)) = schema.get(keyword) {
+            for branch in branches {
+                redact(branch, value);
+            }
+        }
+    }
+    match value {
+        Value::Object(fields) => {
+            let Some(Value::Object(properties)) = schema.get("properties") else {
+                return;
+            };
+            for (name, field) in fields.iter_mut() {
+                let Some(property) = properties.get(name) else {
+                    continue;
+                };
+                if is_secret(property) {
+                    *field = Value::from(REDACTED);
+                } else {
+                    redact(property, field);
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(item) = schema.get("items") {
+                for value in items {
+                    if is_secret(item) {
+                        *value = Value::from(REDACTED);
+                    } else {
+                        redact(item, value);
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn is_secret(schema: &Value) -> bool {
+    schema.get("writeOnly") == Some(&Value::Bool(true))
+        || ["anyOf", "oneOf"].iter().any(|keyword| {
+            schema
+                .get(keyword)
+                .and_then(Value::as_array)
+                .is_some_and(|branches| branches.iter().any(is_secret))
+        })
+}
+
+// ============================================================================
+// PROVIDERS
+// ============================================================================
+
+/// Source of secret values, looked up by key.
+pub trait SecretsProvider: Send + Sync {
+    fn get(&self, key: &str) -> Option<SecretString>;
+}
+
+/// Reads secrets from process environment variables, optionally behind a
+/// common prefix (`with_prefix("MYAPP_")` maps `API_KEY` → `MYAPP_API_KEY`).
+#[derive(Debug, Clone, Default)]
+pub struct EnvSecrets {
+    prefix: String,
+}
+
+impl EnvSecrets {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_prefix(prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+        }
+    }
+}
+
+impl SecretsProvider for EnvSecrets {
+    fn get(&self, key: &str) -> Option<SecretString> {
+        std::env::var(format!("{}{}", self.prefix, key))
+            .ok()
+            .map(Secret)
+    }
+}
+
+/// Fixed in-memory map of secrets. Intended for tests and for values
+/// loaded once at startup from a config file.
+#[derive(Clone, Default)]
+pub struct StaticSecrets {
+    values: HashMap<String, String>,
+}
+
+impl StaticSecrets {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder-style insert.
+    pub fn with(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.insert(key, value);
+        self
+    }
+
+    pub fn insert(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.values.insert(key.into(), value.into());
+    }
+}
+
+impl fmt::Debug for StaticSecrets {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Keys are fine to show; values are not.
+        f.debug_struct("StaticSecrets")
+            .field("keys", &self.values.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl<K: Into<String>, V: Into<String>> FromIterator<(K, V)> for StaticSecrets {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self {
+            values: iter
+                .into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect(),
+        }
+    }
+}
+
+impl SecretsProvider for StaticSecrets {
+    fn get(&self, key: &str) -> Option<SecretString> {
+        self.values.get(key).cloned().map(Secret)
+    }
+}
+
+// ============================================================================
+// SHARED HANDLE
+// ============================================================================
+
+/// Cheaply cloneable handle to a [`SecretsProvider`].
+///
+/// Use it directly as a tool's context type (`ctx: Secrets`) or embed it
+/// as a field of a larger context struct.
+#[derive(Clone)]
+pub struct Secrets(Arc<dyn SecretsProvider>);
+
+impl Secrets {
+    pub fn new(provider: impl SecretsProvider + 'static) -> Self {
+        Self(Arc::new(provider))
+    }
+
+    /// Shorthand for `Secrets::new(EnvSecrets::new())`.
+    pub fn env() -> Self {
+        Self::new(EnvSecrets::new())
+    }
+
+    pub fn get(&self, key: &str) -> Option<SecretString> {
+        self.0.get(key)
+    }
+
+    /// Like [`get`][Self::get] but fails with [`ToolError::MissingSecret`]
+    /// when the key is not set.
+    pub fn require(&self, key: &str) -> Result<SecretString, ToolError> {
+        self.get(key).ok_or_else(|| ToolError::MissingSecret {
+            key: key.to_string(),
+        })
+    }
+}
+
+impl Default for Secrets {
+    fn default() -> Self {
+        Self::env()
+    }
+}
+
+impl fmt::Debug for Secrets {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secrets(..)")
+    }
+}
+
+impl SecretsProvider for Secrets {
+    fn get(&self, key: &str) -> Option<SecretString> {
+        self.0.get(key)
+    }
+}
+
+// ============================================================================
+// COLLECTION PROVIDER
+// ============================================================================
+
+tokio::task_local! {
+    static SECRETS: Secrets;
+}
+
+/// The provider set with [`ToolCollection::set_secrets`] on the collection
+/// running the current call, if any.
+pub fn current() -> Option<Secrets> {
+    SECRETS.try_with(Clone::clone).ok()
+}
+
+/// Run `fut` with `secrets` in scope. `None` leaves an enclosing provider,
+/// such as that of an outer collection, in place.
+pub(crate) async fn scope<F: Future>(secrets: Option<&Secrets>, fut: F) -> F::Output {
+    match secrets {
+        Some(secrets) => SECRETS.scope(secrets.clone(), fut).await,
+        None => fut.await,
+    }
+}
+
+impl<M> ToolCollection<M> {
+    /// Make `provider` available to every tool this collection runs,
+    /// whatever its context type, through [`current`]. Replaces any
+    /// provider set before.
+    pub fn set_secrets(&mut self, provider: impl SecretsProvider + 'static) {
+        self.secrets = Some(Secrets::new(provider));
+    }
+
+    /// Stop providing secrets to tools.
+    pub fn clear_secrets(&mut self) {
+        self.secrets = None;
+    }
+
+    pub fn secrets(&self) -> Option<&Secrets> {
+        self.secrets.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secret_is_redacted_in_debug_and_display() {
+        let s = SecretString::from("hunter2");
+        assert_eq!(format!("{s:?}"), "Secret([REDACTED])");
+        assert_eq!(format!("{s}"), "[REDACTED]");
+        assert_eq!(s.expose(), "hunter2");
+    }
+
+    #[test]
+    fn redact_follows_items_and_branches() {
+        let schema = serde_json::json!({
+            "type": "object",
+            "properties": {
+                "keys": { "type": "array", "items": { "type": "string", "writeOnly": true } },
+                "auth": { "anyOf": [
+                    { "type": "object", "properties": { "pin": { "type": "integer", "writeOnly": true } } },
+                    { "type": "null" }
+                ] },
+                "name": { "type": "string" }
+            }
+        });
+        let mut value = serde_json::json!({
+            "keys": ["a", "b"],
+            "auth": { "pin": 1234 },
+            "name": "ada",
+            "extra": "kept"
+        });
+        redact(&schema, &mut value);
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "keys": [REDACTED, REDACTED],
+                "auth": { "pin": REDACTED },
+                "name": "ada",
+                "extra": "kept"
+            })
+        );
+        assert_eq!(
+            serde_json::to_value(SecretString::from("x")).unwrap(),
+            REDACTED
+        );
+    }
+
+    #[test]
+    fn static_secrets_lookup() {
+        let secrets = Secrets::new(StaticSecrets::new().with("API_KEY", "abc"));
+        assert_eq!(secrets.get("API_KEY").unwrap().expose(), "abc");
+        assert!(secrets.get("OTHER").is_none());
+    }
+
+    #[test]
+    fn static_secrets_debug_hides_values() {
+        let secrets = StaticSecrets::new().with("API_KEY", "abc");
+        let dbg = format!("{secrets:?}");
+        assert!(dbg.contains("API_KEY"));
+        assert!(!dbg.contains("abc"));
+    }
+
+    #[test]
+    fn require_reports_missing_key() {
+        let secrets = Secrets::new(StaticSecrets::new());
+        let err = secrets.require("NOPE").unwrap_err();
+        assert!(matches!(err, ToolError::MissingSecret { ref key } if key == "NOPE"));
+    }
+
+    #[test]
+    fn env_secrets_honours_prefix() {
+        // `set_var` is unsafe in edition 2024 (and the crate denies unsafe),
+        // so only read variables that already exist.
+        let provider = EnvSecrets::with_prefix("TOOLS_RS_TEST_");
+        assert!(provider.get("DEFINITELY_UNSET_KEY").is_none());
+        let path = EnvSecrets::new().get("PATH");
+        assert_eq!(path.map(Secret::into_inner), std::env::var("PATH").ok());
+    }
+}
@@ -0,0 +1,224 @@
+//! Ordering keys for concurrent calls.
+//!
+//! When many calls run at once, their log lines interleave and the true
+//! order of starts and completions is lost. Every call dispatched by a
+//! [`ToolCollection`] takes a `dispatch` number when it starts and a
+//! `completion` number when its result is produced. Both come from one
+//! process-wide counter, so sorting by them gives a single total order
+//! of starts and completions, across collections.
+//!
+//! The numbers appear on [`FunctionResponse::seq`][crate::FunctionResponse],
+//! on journal [`Intent`][crate::Intent]s and in
+//! [`ToolCollection::recent_calls`], a small history of the last calls
+//! that is also part of [`introspect`][ToolCollection::introspect].
+//!
+//! While debugging, [`enable_history`][ToolCollection::enable_history]
+//! makes that history keep each call's arguments, result and error
+//! message as well, with [`Secret`][crate::Secret] fields redacted:
+//!
+//! ```ignore
+//! tools.enable_history(100);
+//! tools.call(call).await?;
+//! for call in tools.recent_calls(5) {
+//!     println!("{} {}ms {:?} -> {:?}", call.tool, call.duration_ms, call.arguments, call.result);
+//! }
+//! ```
+
+use std::{
+    borrow::Cow,
+    collections::VecDeque,
+    sync::{
+        Mutex, PoisonError,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    time::Instant,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    CallId, ErrorClass, FunctionDecl, FunctionResponse, ToolCollection, ToolError, secrets,
+};
+
+/// Calls kept by [`ToolCollection::recent_calls`] unless changed with
+/// [`set_recent_calls_capacity`][ToolCollection::set_recent_calls_capacity].
+pub const DEFAULT_RECENT_CALLS: usize = 32;
+
+static NEXT: AtomicU64 = AtomicU64::new(1);
+
+/// Take the next number from the process-wide sequence.
+pub fn next() -> u64 {
+    NEXT.fetch_add(1, Ordering::SeqCst)
+}
+
+/// Where a call falls in the process-wide sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct CallSeq {
+    /// Taken when the call was dispatched to its tool.
+    pub dispatch: u64,
+    /// Taken when its result was produced.
+    pub completion: u64,
+}
+
+/// One finished call in [`ToolCollection::recent_calls`].
+///
+/// `arguments`, `result` and `message` are only kept while
+/// [`enable_history`][ToolCollection::enable_history] is on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RecentCall {
+    pub seq: CallSeq,
+    pub tool: Cow<'static, str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub call_id: Option<String>,
+    /// Time from dispatch to result.
+    pub duration_ms: u64,
+    /// Class of the error the call failed with, if it failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorClass>,
+    /// Arguments as sent, with secret fields redacted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<Value>,
+    /// The tool's result, redacted against its result schema if it has
+    /// one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    /// The error the call failed with.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// Bounded history of finished calls, oldest first.
+pub(crate) struct RecentCalls {
+    history: Mutex<History>,
+    /// Whether calls keep their arguments and results.
+    payloads: AtomicBool,
+}
+
+struct History {
+    capacity: usize,
+    calls: VecDeque<RecentCall>,
+}
+
+impl Default for RecentCalls {
+    fn default() -> Self {
+        Self {
+            history: Mutex::new(History {
+                capacity: DEFAULT_RECENT_CALLS,
+                calls: VecDeque::new(),
+            }),
+            payloads: AtomicBool::new(false),
+        }
+    }
+}
+
+impl RecentCalls {
+    fn with<R>(&self, f: impl FnOnce(&mut History) -> R) -> R {
+        f(&mut self.history.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// The redacted copy of `arguments` to keep, when payloads are kept.
+    pub(crate) fn arguments(&self, decl: &FunctionDecl, arguments: &Value) -> Option<Value> {
+        if !self.payloads.load(Ordering::Relaxed) {
+            return None;
+        }
+        let mut arguments = arguments.clone();
+        secrets::redact(&decl.parameters, &mut arguments);
+        Some(arguments)
+    }
+
+    /// Record the end of a call to `decl` dispatched at `dispatch`. Failed
+    /// calls take their completion number here.
+    pub(crate) fn record(
+        &self,
+        decl: &FunctionDecl<'static>,
+        call_id: Option<&CallId>,
+        dispatch: u64,
+        started: Instant,
+        arguments: Option<Value>,
+        result: &Result<FunctionResponse, ToolError>,
+    ) {
+        let duration_ms = started.elapsed().as_millis().try_into().unwrap_or(u64::MAX);
+        let (seq, error) = match result {
+            Ok(response) => (response.seq, None),
+            Err(e) => (None, Some(e.classification())),
+        };
+        let seq = seq.unwrap_or_else(|| CallSeq {
+            dispatch,
+            completion: next(),
+        });
+        let payloads = arguments.is_some();
+        let (result, message) = match result {
+            Ok(response) if payloads => {
+                let mut result = response.result.clone();
+                if let Some(returns) = &decl.returns {
+                    secrets::redact(returns, &mut result);
+                }
+                (Some(result), None)
+            }
+            Err(e) if payloads => (None, Some(e.to_string())),
+            _ => (None, None),
+        };
+        self.with(|history| {
+            if history.capacity == 0 {
+                return;
+            }
+            if history.calls.len() == history.capacity {
+                history.calls.pop_front();
+            }
+            // Calls finishing together may arrive out of order.
+            let at = history
+                .calls
+                .iter()
+                .rposition(|c| c.seq.completion < seq.completion)
+                .map_or(0, |i| i + 1);
+            history.calls.insert(
+                at,
+                RecentCall {
+                    seq,
+                    tool: decl.name.clone(),
+                    call_id: call_id.map(CallId::to_string),
+                    duration_ms,
+                    error,
+                    arguments,
+                    result,
+                    message,
+                },
+            );
+        });
+    }
+}
+
+impl<M> ToolCollection<M> {
+    /// The last `n` finished calls, in completion order. Shared with
+    /// clones of the collection.
+    pub fn recent_calls(&self, n: usize) -> Vec<RecentCall> {
+        self.recent.with(|history| {
+            let skip = history.calls.len().saturating_sub(n);
+            history.calls.iter().skip(skip).cloned().collect()
+        })
+    }
+
+    /// Keep the last `capacity` calls; `0` stops recording.
+    pub fn set_recent_calls_capacity(&self, capacity: usize) {
+        self.recent.with(|history| {
+            history.capacity = capacity;
+            let excess = history.calls.len().saturating_sub(capacity);
+            history.calls.drain(..excess);
+        });
+    }
+
+    /// Keep the last `capacity` calls together with their arguments,
+    /// results and error messages. Meant for development: every call
+    /// clones its arguments and result while this is on.
+    pub fn enable_history(&self, capacity: usize) {
+        self.set_recent_calls_capacity(capacity);
+        self.recent.payloads.store(true, Ordering::Relaxed);
+    }
+
+    /// Stop keeping arguments and results. Calls already recorded keep
+    /// theirs.
+    pub fn disable_history(&self) {
+        self.recent.payloads.store(false, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,123 @@
+//! A collection shared across tasks that can still change.
+//!
+//! A [`ToolCollection`] behind an `Arc` can be called from any task but no
+//! longer registered into. [`SharedToolCollection`] is a cheap-to-clone
+//! handle that allows both, for apps that enable and disable tools per
+//! session at runtime:
+//!
+//! ```ignore
+//! let shared = SharedToolCollection::new(collect_tools());
+//! let worker = shared.clone();
+//! tokio::spawn(async move { worker.call(call).await });
+//!
+//! shared.unregister("send_email")?;
+//! ```
+//!
+//! The collection sits behind a read-write lock that is never held across
+//! an `await`. A call takes the lock just long enough to clone an `Arc` of
+//! the current collection, then runs on that snapshot, through the whole
+//! [`call`][ToolCollection::call] path, with the lock released. A slow
+//! tool never blocks a writer. A change made while calls are in flight
+//! copies the collection first (clones share breaker, cache and usage
+//! state), so in-flight calls finish on the collection they started with
+//! and every later call sees the change.
+
+use std::{
+    borrow::Cow,
+    sync::{Arc, PoisonError, RwLock},
+};
+
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::Value;
+
+use crate::{
+    FunctionCall, FunctionResponse, MetaArg, NoMeta, ToolCollection, ToolError, ToolId, ToolRef,
+    ToolSchema, ToolSpec,
+};
+
+/// A [`ToolCollection`] that clones of this handle call and change
+/// together; see [`shared`][crate::shared].
+pub struct SharedToolCollection<M = NoMeta> {
+    inner: Arc<RwLock<Arc<ToolCollection<M>>>>,
+}
+
+impl<M> Clone for SharedToolCollection<M> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<M> From<ToolCollection<M>> for SharedToolCollection<M> {
+    fn from(tools: ToolCollection<M>) -> Self {
+        Self::new(tools)
+    }
+}
+
+impl<M> SharedToolCollection<M> {
+    pub fn new(tools: ToolCollection<M>) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(Arc::new(tools))),
+        }
+    }
+
+    /// The collection as it is now. Later changes don't reach it.
+    pub fn snapshot(&self) -> Arc<ToolCollection<M>> {
+        self.inner
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Call a tool on the current collection, without holding the lock
+    /// while it runs.
+    pub async fn call(&self, call: FunctionCall) -> Result<FunctionResponse, ToolError> {
+        self.snapshot().call(call).await
+    }
+
+    /// Declarations of the current collection; see
+    /// [`ToolCollection::json`].
+    pub fn json(&self) -> Result<Value, ToolError> {
+        self.snapshot().json()
+    }
+}
+
+impl<M: Clone> SharedToolCollection<M> {
+    /// Change the collection with `f` under the write lock. The collection
+    /// is copied first if calls are running on it.
+    pub fn update<R>(&self, f: impl FnOnce(&mut ToolCollection<M>) -> R) -> R {
+        let mut current = self.inner.write().unwrap_or_else(PoisonError::into_inner);
+        f(Arc::make_mut(&mut current))
+    }
+
+    /// [`ToolCollection::register`] on the shared collection.
+    pub fn register<A, I, O, F, Fut>(
+        &self,
+        name: impl Into<Cow<'static, str>>,
+        desc: impl Into<Cow<'static, str>>,
+        func: F,
+        meta: A,
+    ) -> Result<ToolRef<I, O>, ToolError>
+    where
+        A: MetaArg<M>,
+        I: 'static + DeserializeOwned + Serialize + Send + ToolSchema,
+        O: 'static + Serialize + Send + ToolSchema,
+        F: Fn(I) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = O> + Send + 'static,
+    {
+        self.update(|tools| tools.register(name, desc, func, meta))
+    }
+
+    /// [`ToolCollection::register_spec`] on the shared collection.
+    pub fn register_spec(&self, spec: ToolSpec<M>) -> Result<ToolId, ToolError> {
+        self.update(|tools| tools.register_spec(spec))
+    }
+
+    /// [`ToolCollection::unregister`] on the shared collection. Calls
+    /// already running keep the tool until they finish, and its
+    /// [cleanup][crate::cleanup] starts after the last of them returns.
+    pub fn unregister(&self, name: &str) -> Result<(), ToolError> {
+        self.update(|tools| tools.unregister(name))
+    }
+}
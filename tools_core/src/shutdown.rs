@@ -0,0 +1,210 @@
+//! Graceful shutdown of a collection that is still serving calls.
+//!
+//! [`ToolCollection::shutdown_gracefully`] takes `&self`, so it works on
+//! the `Arc`-wrapped collection a server shares between its handlers:
+//!
+//! 1. New calls fail at once with [`ToolError::ShuttingDown`].
+//! 2. Calls already running get up to `grace` to finish.
+//! 3. Calls still running after that are cancelled: their futures are
+//!    dropped and they fail with [`ToolError::ShuttingDown`] too.
+//! 4. Every tool's [cleanup][crate::cleanup] runs and is awaited, with
+//!    those started earlier by `unregister` or `clear`.
+//! 5. The [failure][crate::failures] and [usage][crate::usage] sinks are
+//!    flushed.
+//!
+//! ```ignore
+//! let tools = Arc::new(tools);
+//! let server = broker::serve_unix(tools.clone(), path)?;
+//! // ... on SIGTERM:
+//! let report = tools.shutdown_gracefully(Duration::from_secs(10)).await;
+//! server.abort();
+//! log::info!("{} calls drained, {} cancelled", report.drained, report.cancelled);
+//! ```
+//!
+//! Clones share the shutdown: once one clone starts it, calls through any
+//! of them are refused. The tools stay registered, so declarations can
+//! still be read.
+
+use std::{
+    sync::atomic::{self, AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use tokio::sync::watch;
+
+use crate::{ToolCollection, ToolError};
+
+/// What [`ToolCollection::shutdown_gracefully`] did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ShutdownReport {
+    /// Calls running when shutdown started.
+    pub in_flight: u64,
+    /// Of those, the ones that finished within the grace period.
+    pub drained: u64,
+    /// Of those, the ones cancelled after it.
+    pub cancelled: u64,
+    /// Cleanups awaited.
+    pub cleanups: usize,
+    /// Time taken by the whole shutdown.
+    pub elapsed_ms: u64,
+}
+
+/// Shared by a collection's clones.
+pub(crate) struct Lifecycle {
+    closing: AtomicBool,
+    in_flight: watch::Sender<u64>,
+    cancel: watch::Sender<bool>,
+}
+
+impl Default for Lifecycle {
+    fn default() -> Self {
+        Self {
+            closing: AtomicBool::new(false),
+            in_flight: watch::Sender::new(0),
+            cancel: watch::Sender::new(false),
+        }
+    }
+}
+
+impl Lifecycle {
+    /// Admit a call, or refuse it once shutdown has started. The call
+    /// counts as in flight until the guard drops.
+    ///
+    /// The call is counted before `closing` is read, and [`close`] sets
+    /// `closing` before reading the count, so either the call sees
+    /// `closing` and is refused, or shutdown sees the call and waits for
+    /// it. Checking first would let a call slip in after shutdown found
+    /// nothing running and ran the cleanups.
+    ///
+    /// [`close`]: Self::close
+    pub(crate) fn enter(&self) -> Result<InFlight<'_>, ToolError> {
+        self.in_flight.send_modify(|n| *n += 1);
+        let guard = InFlight(self);
+        atomic::fence(Ordering::SeqCst);
+        if self.closing.load(Ordering::SeqCst) {
+            // Dropping the guard takes the call back out of the count.
+            return Err(ToolError::ShuttingDown);
+        }
+        Ok(guard)
+    }
+
+    /// Refuse new calls and return how many are running.
+    fn close(&self) -> u64 {
+        self.closing.store(true, Ordering::SeqCst);
+        atomic::fence(Ordering::SeqCst);
+        self.count()
+    }
+
+    /// Resolves when running calls are to be cancelled.
+    pub(crate) async fn cancelled(&self) {
+        let mut cancel = self.cancel.subscribe();
+        // The sender lives as long as `self`, so this can't fail.
+        let _ = cancel.wait_for(|c| *c).await;
+    }
+
+    fn count(&self) -> u64 {
+        *self.in_flight.borrow()
+    }
+
+    async fn idle(&self) {
+        let mut in_flight = self.in_flight.subscribe();
+        let _ = in_flight.wait_for(|n| *n == 0).await;
+    }
+}
+
+pub(crate) struct InFlight<'a>(&'a Lifecycle);
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.0.in_flight.send_modify(|n| *n -= 1);
+    }
+}
+
+impl<M> ToolCollection<M> {
+    /// Stop taking calls, give running calls up to `grace` to finish,
+    /// cancel the rest, then await every cleanup and flush the sinks. See
+    /// [`shutdown`][crate::shutdown]. Calling it again only reruns the
+    /// later steps.
+    pub async fn shutdown_gracefully(&self, grace: Duration) -> ShutdownReport {
+        let started = Instant::now();
+        let lifecycle = &self.lifecycle;
+        let in_flight = lifecycle.close();
+
+        let cancelled = match tokio::time::timeout(grace, lifecycle.idle()).await {
+            Ok(()) => 0,
+            Err(_) => {
+                let stragglers = lifecycle.count();
+                lifecycle.cancel.send_replace(true);
+                lifecycle.idle().await;
+                stragglers
+            }
+        };
+
+        let cleanups = self.run_cleanups().await;
+        self.flush_failures();
+        if let Some(usage) = &self.usage {
+            usage.flush();
+        }
+
+        ShutdownReport {
+            in_flight,
+            drained: in_flight.saturating_sub(cancelled),
+            cancelled,
+            cleanups,
+            elapsed_ms: started.elapsed().as_millis().try_into().unwrap_or(u64::MAX),
+        }
+    }
+
+    /// Whether [`shutdown_gracefully`][Self::shutdown_gracefully] has
+    /// started, here or on a clone.
+    pub fn is_shutting_down(&self) -> bool {
+        self.lifecycle.closing.load(Ordering::SeqCst)
+    }
+
+    /// Calls running now, through this collection or its clones.
+    pub fn in_flight(&self) -> u64 {
+        self.lifecycle.count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, atomic::AtomicU64};
+
+    use super::*;
+
+    /// No call is admitted after shutdown saw none running and moved on
+    /// to the cleanups.
+    #[test]
+    fn no_call_is_admitted_after_the_drain() {
+        for _ in 0..200 {
+            let lifecycle = Arc::new(Lifecycle::default());
+            let cleaned = Arc::new(AtomicBool::new(false));
+            let late = Arc::new(AtomicU64::new(0));
+            let callers: Vec<_> = (0..4)
+                .map(|_| {
+                    let (lifecycle, cleaned, late) =
+                        (lifecycle.clone(), cleaned.clone(), late.clone());
+                    std::thread::spawn(move || {
+                        while let Ok(_call) = lifecycle.enter() {
+                            if cleaned.load(Ordering::SeqCst) {
+                                late.fetch_add(1, Ordering::SeqCst);
+                            }
+                        }
+                    })
+                })
+                .collect();
+            std::thread::yield_now();
+            lifecycle.close();
+            while lifecycle.count() > 0 {
+                std::hint::spin_loop();
+            }
+            cleaned.store(true, Ordering::SeqCst);
+            for caller in callers {
+                caller.join().unwrap();
+            }
+            assert_eq!(late.load(Ordering::SeqCst), 0);
+        }
+    }
+}
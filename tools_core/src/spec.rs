@@ -0,0 +1,375 @@
+//! Everything needed to register one tool, as a single value.
+//!
+//! [`ToolCollection::register_spec`] is the primitive every registration
+//! path goes through: `#[tool]` inventory collection, typed
+//! [`register`][ToolCollection::register], [`register_raw`][ToolCollection::register_raw]
+//! and the importers. Build a [`ToolSpec`] directly when the name, schema or
+//! handler are only known at runtime:
+//!
+//! ```ignore
+//! let id = tools.register_spec(
+//!     ToolSpec::new("echo", "Echoes its input.")
+//!         .params_schema(json!({ "type": "object", "properties": { "text": { "type": "string" } } }))
+//!         .tag("debug")
+//!         .handler(|args| Box::pin(async move { Ok(args) })),
+//! )?;
+//! ```
+//!
+//! A tool defined entirely by data, say a row holding its declaration,
+//! starts from [`ToolSpec::from_declaration`] instead. Either way a
+//! [`handler`][ToolSpec::handler] tool never goes through `ToolSchema`: its
+//! parameter schema is the one given, and checks such as
+//! [`UnknownArgs`][crate::UnknownArgs] read it as the source of truth.
+
+use std::{borrow::Cow, sync::Arc};
+
+use futures::future::BoxFuture;
+use serde::de::DeserializeOwned;
+use serde_json::{Value, json};
+
+use crate::{
+    DecodedInput, FunctionDecl, NoMeta, SchemaDialect, ToolCollection, ToolEntry, ToolError,
+    ToolFunc, ToolId, ToolOrigin,
+    cleanup::{Cleanup, CleanupFn},
+    downcast_input,
+    ffi::RawToolDef,
+    wrap,
+};
+
+/// Name, description, schemas, tags, metadata and handler of a tool that
+/// has not been registered yet.
+///
+/// Per-tool flags (approval gates, cost tiers, ...) live in the metadata
+/// `M`, exactly like `#[tool(flag)]` attributes.
+pub struct ToolSpec<M = NoMeta> {
+    name: Cow<'static, str>,
+    description: Cow<'static, str>,
+    parameters: Value,
+    returns: Option<Value>,
+    dialect: Option<SchemaDialect>,
+    tags: Vec<String>,
+    func: Option<ToolFunc>,
+    cleanup: Option<CleanupFn>,
+    origin: Option<ToolOrigin>,
+    external_schema: bool,
+    meta: M,
+}
+
+impl ToolSpec<NoMeta> {
+    /// Start a spec with an empty-object parameter schema, no handler and
+    /// [`NoMeta`]. Set metadata with [`with_meta`][Self::with_meta].
+    pub fn new(
+        name: impl Into<Cow<'static, str>>,
+        description: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            description: description.into(),
+            parameters: json!({ "type": "object", "properties": {}, "required": [] }),
+            returns: None,
+            dialect: None,
+            tags: Vec::new(),
+            func: None,
+            cleanup: None,
+            origin: None,
+            external_schema: false,
+            meta: NoMeta {},
+        }
+    }
+
+    /// Start a spec from a stored declaration in either layout of
+    /// [`layout`][crate::layout]: name, description, parameters, and
+    /// `returns` and `tags` when present. Add the handler with
+    /// [`handler`][Self::handler].
+    pub fn from_declaration(declaration: &Value) -> Result<Self, ToolError> {
+        let decl = FunctionDecl::from_any_layout(declaration)?;
+        let mut spec = Self::new(decl.name.to_string(), decl.description.to_string())
+            .params_schema(decl.parameters)
+            .tags(decl.tags);
+        spec.returns = decl.returns;
+        Ok(spec)
+    }
+}
+
+impl<M> ToolSpec<M> {
+    /// JSON schema of the arguments object.
+    pub fn params_schema(mut self, schema: Value) -> Self {
+        self.parameters = schema;
+        self
+    }
+
+    /// JSON schema of the result. Included in the declaration as
+    /// `returns`.
+    pub fn returns_schema(mut self, schema: Value) -> Self {
+        self.returns = Some(schema);
+        self
+    }
+
+    /// Dialect the schemas are written in, when their `$schema` doesn't
+    /// say. See [`schema::dialect`][crate::schema::dialect].
+    pub fn schema_dialect(mut self, dialect: SchemaDialect) -> Self {
+        self.dialect = Some(dialect);
+        self
+    }
+
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    pub fn tags(mut self, tags: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.tags.extend(tags.into_iter().map(Into::into));
+        self
+    }
+
+    /// Handle calls with a closure over the raw JSON arguments. The
+    /// closure receives no context. The tool is reported as having an
+    /// [`external_schema`][ToolEntry::external_schema].
+    pub fn handler(
+        mut self,
+        f: impl Fn(Value) -> BoxFuture<'static, Result<Value, ToolError>> + Send + Sync + 'static,
+    ) -> Self {
+        self.func = Some(ToolFunc::new(
+            |raw: Value| Ok(Box::new(raw) as DecodedInput),
+            move |input, _ctx| match downcast_input::<Value>(input) {
+                Ok(raw) => f(raw),
+                Err(e) => Box::pin(async move { Err(e) }),
+            },
+        ));
+        self.external_schema = true;
+        self
+    }
+
+    /// Handle calls with a two-stage [`ToolFunc`], e.g. one that decodes
+    /// into a typed input or reads the collection's context.
+    pub fn func(mut self, func: ToolFunc) -> Self {
+        self.func = Some(func);
+        self.external_schema = false;
+        self
+    }
+
+    /// Run `f` once when the tool is removed from the collection. See
+    /// [`cleanup`][crate::cleanup].
+    pub fn cleanup(mut self, f: impl FnOnce() -> BoxFuture<'static, ()> + Send + 'static) -> Self {
+        self.cleanup = Some(Box::new(f));
+        self
+    }
+
+    /// Record where the tool is defined; shown by
+    /// [`introspect`][ToolCollection::introspect].
+    pub fn origin(mut self, origin: ToolOrigin) -> Self {
+        self.origin = Some(origin);
+        self
+    }
+
+    /// Replace the metadata, changing the spec's metadata type.
+    pub fn with_meta<N>(self, meta: N) -> ToolSpec<N> {
+        ToolSpec {
+            name: self.name,
+            description: self.description,
+            parameters: self.parameters,
+            returns: self.returns,
+            dialect: self.dialect,
+            tags: self.tags,
+            func: self.func,
+            cleanup: self.cleanup,
+            origin: self.origin,
+            external_schema: self.external_schema,
+            meta,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn parameters(&self) -> &Value {
+        &self.parameters
+    }
+
+    pub fn returns(&self) -> Option<&Value> {
+        self.returns.as_ref()
+    }
+
+    /// The dialect set with [`schema_dialect`][Self::schema_dialect], else
+    /// the one named by the parameters' `$schema`, else 2020-12.
+    pub fn dialect(&self) -> SchemaDialect {
+        self.dialect
+            .or_else(|| SchemaDialect::from_schema(&self.parameters))
+            .unwrap_or_default()
+    }
+
+    pub fn tag_list(&self) -> &[String] {
+        &self.tags
+    }
+
+    pub fn meta(&self) -> &M {
+        &self.meta
+    }
+}
+
+impl RawToolDef {
+    /// Convert into a [`ToolSpec`], deserializing the metadata blob into
+    /// `M`.
+    pub fn into_spec<M: DeserializeOwned>(self) -> Result<ToolSpec<M>, ToolError> {
+        let meta: M = serde_json::from_value(self.meta).map_err(|e| ToolError::BadMeta {
+            tool: Cow::Owned(self.name.clone()),
+            error: e.to_string(),
+        })?;
+        Ok(ToolSpec::new(self.name, self.description)
+            .params_schema(self.parameters)
+            .handler(self.func)
+            .with_meta(meta))
+    }
+}
+
+impl<M> ToolCollection<M> {
+    /// Register a tool from its [`ToolSpec`]. Fails with
+    /// [`ToolError::AlreadyRegistered`] if the name is taken, with
+    /// [`ToolError::NameConflict`] if it collides with another name after
+    /// normalization or aliasing, with [`ToolError::MissingHandler`] if no
+    /// handler was set, and in strict mode with
+    /// [`ToolError::RegistrationRejected`] if the registration checks in
+    /// [`checks`][crate::checks] find problems.
+    pub fn register_spec(&mut self, spec: ToolSpec<M>) -> Result<ToolId, ToolError> {
+        let taken = self.names.canonical(&spec.name);
+        if let Some(existing) = taken.and_then(|id| self.get_id(id)) {
+            return Err(ToolError::AlreadyRegistered {
+                name: existing.decl.name.clone(),
+                existing: existing.decl.description.clone(),
+                attempted: spec.description,
+            });
+        }
+        self.insert_spec(spec)
+    }
+
+    /// [`register_spec`][Self::register_spec] without the duplicate check:
+    /// a spec whose name is taken replaces the existing tool in place.
+    /// Inventory collection uses this, so the last `#[tool]` of a name in
+    /// a crate wins.
+    pub(crate) fn insert_spec(&mut self, spec: ToolSpec<M>) -> Result<ToolId, ToolError> {
+        let dialect = spec.dialect();
+        let (name, description) = (spec.name, spec.description);
+        let Some(mut func) = spec.func else {
+            return Err(ToolError::MissingHandler { tool: name });
+        };
+        let mut parameters = spec.parameters;
+        let mut wrapped = None;
+        if self.wrap_bare_inputs {
+            if let Some((schema, wrapped_func, param)) = wrap::wrap(&parameters, &func) {
+                (parameters, func, wrapped) = (schema, wrapped_func, Some(param));
+            }
+        }
+
+        let findings = self.check_registration(&name, &description, &parameters, &func)?;
+
+        let mut decl = FunctionDecl::new(name.clone(), description, parameters);
+        decl.returns = spec.returns;
+        decl.tags = spec.tags;
+        decl.dialect = dialect;
+        let id = self.insert(ToolEntry {
+            func,
+            decl,
+            meta: spec.meta,
+            circuit: None,
+            cleanup: spec.cleanup.map(|f| Arc::new(Cleanup::new(f))),
+            cache: None,
+            journal: None,
+            origin: spec.origin,
+            queue: None,
+            migrations: Vec::new(),
+            wrapped,
+            compaction_exempt: false,
+            hidden: false,
+            external_schema: spec.external_schema,
+            timeout: None,
+        })?;
+        self.record_warnings(&name, findings);
+        Ok(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FunctionCall;
+
+    fn echo() -> ToolSpec {
+        ToolSpec::new("echo", "Echoes its input.")
+            .params_schema(json!({ "type": "object" }))
+            .handler(|args| Box::pin(async move { Ok(args) }))
+    }
+
+    #[tokio::test]
+    async fn register_spec_is_callable() {
+        let mut tools: ToolCollection = ToolCollection::new();
+        let id = tools.register_spec(echo()).unwrap();
+        assert_eq!(tools.resolve("echo"), Some(id));
+
+        let out = tools
+            .call(FunctionCall::new("echo".into(), json!({ "a": 1 })))
+            .await
+            .unwrap();
+        assert_eq!(out.result, json!({ "a": 1 }));
+    }
+
+    #[test]
+    fn returns_and_tags_reach_the_declaration() {
+        let mut tools: ToolCollection = ToolCollection::new();
+        tools
+            .register_spec(
+                echo()
+                    .returns_schema(json!({ "type": "object" }))
+                    .tags(["debug", "io"]),
+            )
+            .unwrap();
+        let decl = &tools.json().unwrap()[0];
+        assert_eq!(decl["returns"], json!({ "type": "object" }));
+        assert_eq!(decl["tags"], json!(["debug", "io"]));
+    }
+
+    #[test]
+    fn untagged_declaration_is_unchanged() {
+        let mut tools: ToolCollection = ToolCollection::new();
+        tools.register_spec(echo()).unwrap();
+        let decl = tools.json().unwrap()[0].clone();
+        assert_eq!(
+            decl,
+            json!({ "name": "echo", "description": "Echoes its input.", "parameters": { "type": "object" } })
+        );
+    }
+
+    #[test]
+    fn missing_handler_and_duplicates_fail() {
+        let mut tools: ToolCollection = ToolCollection::new();
+        let err = tools
+            .register_spec(ToolSpec::new("idle", "No handler."))
+            .unwrap_err();
+        assert!(matches!(err, ToolError::MissingHandler { ref tool } if tool == "idle"));
+
+        tools.register_spec(echo()).unwrap();
+        let err = tools.register_spec(echo()).unwrap_err();
+        assert!(matches!(
+            err,
+            ToolError::AlreadyRegistered { ref name, .. } if name == "echo"
+        ));
+    }
+
+    #[test]
+    fn owned_names_are_accepted() {
+        let mut tools: ToolCollection<u8> = ToolCollection::new();
+        let name = format!("tool_{}", 7);
+        tools
+            .register_spec(
+                ToolSpec::new(name, String::from("Built at runtime."))
+                    .handler(|_| Box::pin(async { Ok(Value::Null) }))
+                    .with_meta(3),
+            )
+            .unwrap();
+        assert_eq!(tools.meta("tool_7"), Some(&3));
+    }
+}
@@ -0,0 +1,130 @@
+//! Snapshot assertions for downstream test suites (`testing` feature).
+//!
+//! ```ignore
+//! #[test]
+//! fn declarations_are_stable() {
+//!     let tools = ToolCollection::<NoMeta>::collect_tools().unwrap();
+//!     tools_rs::assert_declarations_snapshot!(tools, "snapshots/tools.json");
+//! }
+//! ```
+//!
+//! A missing snapshot is written and the test passes; commit the file.
+//! A mismatch panics with a line diff. Rerun with `UPDATE_GOLDEN=1` to
+//! accept the new output.
+//!
+//! Contract tests replay calls recorded during development; see
+//! [`contract`][crate::contract]. Differential checks between schema
+//! validation and decoding are in [`differential`][crate::differential].
+
+use std::{fs, path::Path};
+
+pub use crate::contract::{
+    Contract, ContractMismatch, ContractOutcome, ContractRecorder, check_contracts,
+    load_contracts, run_contracts,
+};
+pub use crate::differential::{
+    Divergence, Entropy, arbitrary_arguments, check_acceptance, divergence, fuzz_one, minimize,
+    run_acceptance, tricky_arguments,
+};
+
+/// Lines of unchanged context kept around each change in a diff.
+const CONTEXT: usize = 3;
+
+/// Compare `actual` with the file at `path`, writing it when it is missing
+/// or `UPDATE_GOLDEN` is set.
+#[track_caller]
+pub fn assert_snapshot_text(path: &Path, actual: &str) {
+    let expected = match fs::read_to_string(path) {
+        Ok(expected) if std::env::var_os("UPDATE_GOLDEN").is_none() => expected,
+        _ => {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)
+                    .unwrap_or_else(|e| panic!("cannot create {}: {e}", dir.display()));
+            }
+            fs::write(path, actual)
+                .unwrap_or_else(|e| panic!("cannot write {}: {e}", path.display()));
+            return;
+        }
+    };
+    if expected != actual {
+        panic!(
+            "snapshot {} changed; rerun with UPDATE_GOLDEN=1 if intended\n{}",
+            path.display(),
+            diff(&expected, actual)
+        );
+    }
+}
+
+/// Unified-style line diff of `expected` (`-`) against `actual` (`+`).
+pub fn diff(expected: &str, actual: &str) -> String {
+    let old: Vec<&str> = expected.lines().collect();
+    let new: Vec<&str> = actual.lines().collect();
+
+    // Longest common subsequence table over line suffixes.
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            ops.push((' ', old[i]));
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i == old.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            ops.push(('+', new[j]));
+            j += 1;
+        } else {
+            ops.push(('-', old[i]));
+            i += 1;
+        }
+    }
+
+    let near_change = |k: usize| {
+        let lo = k.saturating_sub(CONTEXT);
+        let hi = (k + CONTEXT + 1).min(ops.len());
+        ops[lo..hi].iter().any(|(op, _)| *op != ' ')
+    };
+    let mut out = String::new();
+    let mut skipped = false;
+    for (k, (op, line)) in ops.iter().enumerate() {
+        if near_change(k) {
+            if skipped {
+                out.push_str("  ...\n");
+                skipped = false;
+            }
+            out.push(*op);
+            out.push(' ');
+            out.push_str(line);
+            out.push('\n');
+        } else {
+            skipped = true;
+        }
+    }
+    out
+}
+
+/// Snapshot a collection's [`to_canonical_string`] at `path`, relative to
+/// the calling crate's manifest directory. See the [module docs].
+///
+/// [`to_canonical_string`]: crate::ToolCollection::to_canonical_string
+/// [module docs]: crate::testing
+#[macro_export]
+macro_rules! assert_declarations_snapshot {
+    ($tools:expr, $path:expr $(,)?) => {
+        $crate::testing::assert_snapshot_text(
+            &::std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join($path),
+            &$tools
+                .to_canonical_string()
+                .expect("declarations serialize"),
+        )
+    };
+}
@@ -0,0 +1,132 @@
+//! Full and terse declarations for long conversations.
+//!
+//! Most chat APIs take the tools array with every request, so a long
+//! conversation resends the same parameter descriptions each turn. Once
+//! the history shows the tools in use, a [`Terse`][DeclTier::Terse]
+//! declaration, cut down from the same [`FunctionDecl`], is usually
+//! enough:
+//!
+//! 1. the description is cut to its first line;
+//! 2. the annotation keywords in [`TERSE_STRIPPED`] are removed from every
+//!    node of the parameter schema, property descriptions included;
+//! 3. the result schema and tags are dropped.
+//!
+//! Names, property names, types, `required`, `enum` and the other
+//! validation keywords stay, so a terse declaration accepts exactly the
+//! calls the full one does.
+//!
+//! A [`TierTracker`] per conversation makes the choice: full on the first
+//! turn and again whenever [`registry_hash`][ToolCollection::registry_hash]
+//! changes, terse otherwise. The APIs keep no state between requests, so
+//! [`reset`][TierTracker::reset] it when the history is trimmed.
+//!
+//! ```ignore
+//! let mut tiers = TierTracker::new();
+//! loop {
+//!     let tier = tiers.tier(tools.registry_hash());
+//!     request.tools = tools.json_tier(tier)?;
+//!     // ...
+//! }
+//! ```
+
+use serde_json::Value;
+
+use crate::{FunctionDecl, Profiled, ToolCollection, ToolError, hash, schema::transform};
+
+/// Schema keywords a terse declaration leaves out. None of them changes
+/// which arguments are valid.
+pub const TERSE_STRIPPED: &[&str] = &[
+    "description",
+    "title",
+    "examples",
+    "default",
+    "$comment",
+    "$schema",
+    "x-enum-descriptions",
+];
+
+/// How much of each declaration to send.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DeclTier {
+    /// The declarations as [`json`][ToolCollection::json] writes them.
+    #[default]
+    Full,
+    /// Cut down as described in the [module docs][self].
+    Terse,
+}
+
+impl<'a> FunctionDecl<'a> {
+    /// This declaration cut down for the [`Terse`][DeclTier::Terse] tier.
+    pub fn to_terse(&self) -> FunctionDecl<'a> {
+        let description = self.description.lines().next().unwrap_or("").trim();
+        let mut terse = FunctionDecl::new(
+            self.name.clone(),
+            description.to_owned(),
+            transform::strip_keywords(TERSE_STRIPPED)(&self.parameters),
+        );
+        terse.dialect = self.dialect;
+        terse
+    }
+}
+
+impl<M> ToolCollection<M> {
+    /// [`json`][Self::json] at the given tier.
+    pub fn json_tier(&self, tier: DeclTier) -> Result<Value, ToolError> {
+        match tier {
+            DeclTier::Full => self.json(),
+            DeclTier::Terse => terse_json(self.declarations()),
+        }
+    }
+}
+
+impl<M> Profiled<'_, M> {
+    /// [`ToolCollection::registry_hash`] over the tools in the view.
+    pub fn registry_hash(&self) -> u64 {
+        hash::combined_hash(self.declarations().into_iter())
+    }
+
+    /// [`json`][Self::json] at the given tier.
+    pub fn json_tier(&self, tier: DeclTier) -> Result<Value, ToolError> {
+        match tier {
+            DeclTier::Full => self.json(),
+            DeclTier::Terse => terse_json(self.declarations().into_iter()),
+        }
+    }
+}
+
+fn terse_json<'a>(
+    decls: impl Iterator<Item = &'a FunctionDecl<'static>>,
+) -> Result<Value, ToolError> {
+    let terse: Vec<FunctionDecl> = decls.map(FunctionDecl::to_terse).collect();
+    Ok(serde_json::to_value(terse)?)
+}
+
+/// Picks the tier for each turn of one conversation; see the
+/// [module docs][self].
+#[derive(Debug, Clone, Default)]
+pub struct TierTracker {
+    sent: Option<u64>,
+}
+
+impl TierTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The tier for a turn sending tools whose registry hash is
+    /// `registry_hash`: [`Full`][DeclTier::Full] when the model hasn't
+    /// seen these declarations yet, [`Terse`][DeclTier::Terse] otherwise.
+    pub fn tier(&mut self, registry_hash: u64) -> DeclTier {
+        if self.sent.replace(registry_hash) == Some(registry_hash) {
+            DeclTier::Terse
+        } else {
+            DeclTier::Full
+        }
+    }
+
+    /// Send full declarations on the next turn, e.g. after the history was
+    /// summarized and the first turn dropped.
+    pub fn reset(&mut self) {
+        self.sent = None;
+    }
+}
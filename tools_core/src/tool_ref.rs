@@ -0,0 +1,139 @@
+//! Typed handles to registered tools.
+//!
+//! [`register`][ToolCollection::register] returns a [`ToolRef`] carrying
+//! the tool's name and its input and output types, and `#[tool]` emits one
+//! as a constant named after the function (`ADD_TOOL` for `add`). [`invoke`][ToolCollection::invoke]
+//! takes the handle, so Rust-side callers get the output back typed and a
+//! mismatched input is a compile error:
+//!
+//! ```ignore
+//! #[tool]
+//! /// Adds two numbers.
+//! async fn add(a: i32, b: i32) -> i32 { a + b }
+//!
+//! let sum: i32 = tools.invoke(&ADD_TOOL, (1, 2)).await?;
+//! ```
+//!
+//! The call goes through [`call`][ToolCollection::call] like any other, so
+//! hooks, limits and history see it as JSON. A tool returning `Result`
+//! comes back as that `Result`, domain error included; see
+//! [`domain`][crate::domain].
+//!
+//! Without a handle, [`call_typed`][ToolCollection::call_typed] decodes the
+//! result of a plain [`FunctionCall`] the same way.
+
+use std::{any::type_name, borrow::Cow, fmt, marker::PhantomData};
+
+use serde::{
+    Serialize,
+    de::{DeserializeOwned, Error as _},
+};
+
+use crate::{FunctionCall, FunctionResponse, ToolCollection, ToolError, domain::to_result, wrap};
+
+/// The name of a tool taking `I` and returning `O`.
+pub struct ToolRef<I, O> {
+    name: Cow<'static, str>,
+    _io: PhantomData<fn(I) -> O>,
+}
+
+impl<I, O> ToolRef<I, O> {
+    /// A handle to the tool registered as `name`. Nothing checks that
+    /// `name` takes `I` and returns `O`; prefer the handles returned by
+    /// `register` and emitted by `#[tool]`.
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name: Cow::Borrowed(name),
+            _io: PhantomData,
+        }
+    }
+
+    /// [`new`][Self::new] for a name built at runtime.
+    pub(crate) fn named(name: Cow<'static, str>) -> Self {
+        Self {
+            name,
+            _io: PhantomData,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl<I, O> Clone for ToolRef<I, O> {
+    fn clone(&self) -> Self {
+        Self::named(self.name.clone())
+    }
+}
+
+impl<I, O> fmt::Debug for ToolRef<I, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ToolRef")
+            .field("name", &self.name)
+            .field("input", &type_name::<I>())
+            .field("output", &type_name::<O>())
+            .finish()
+    }
+}
+
+impl<M> ToolCollection<M> {
+    /// Call `tool` with `input` and decode its result as `O`. `#[tool]`
+    /// inputs convert from a tuple of the JSON parameters in declaration
+    /// order, e.g. `(1, 2)` for `add(a, b)` and `()` for a zero-arg tool.
+    pub async fn invoke<I, O>(
+        &self,
+        tool: &ToolRef<I, O>,
+        input: impl Into<I>,
+    ) -> Result<O, ToolError>
+    where
+        I: Serialize,
+        O: DeserializeOwned,
+    {
+        let arguments = serde_json::to_value(input.into())?;
+        let wrapped = self.get(tool.name()).and_then(|entry| entry.wrapped);
+        let arguments = wrap::wrap_arguments(arguments, wrapped);
+        let response = self
+            .call(FunctionCall::new(tool.name().to_owned(), arguments))
+            .await?;
+        if response.is_error {
+            return Ok(serde_json::from_value(to_result(response.result, true))?);
+        }
+        match serde_json::from_value(response.result.clone()) {
+            Ok(output) => Ok(output),
+            // `O` may be a `Result`, whose `Ok` value is sent unwrapped.
+            Err(e) => serde_json::from_value(to_result(response.result, false)).map_err(|_| e.into()),
+        }
+    }
+
+    /// [`call`][Self::call], with the result decoded as `O`. A tool
+    /// returning `Result` decodes into that `Result`, as with
+    /// [`invoke`][Self::invoke]; when `O` isn't one, a domain error comes
+    /// back as [`ToolError::Domain`]. A result that doesn't decode fails
+    /// with [`ToolError::Deserialize`] naming the tool and `O`.
+    pub async fn call_typed<O: DeserializeOwned>(
+        &self,
+        call: FunctionCall,
+    ) -> Result<O, ToolError> {
+        let FunctionResponse {
+            name,
+            result,
+            is_error,
+            ..
+        } = self.call(call).await?;
+        if is_error {
+            let err = to_result(result, true);
+            return serde_json::from_value(err.clone())
+                .map_err(|_| ToolError::Domain(err["Err"].clone()));
+        }
+        serde_json::from_value(result.clone())
+            .or_else(|e| serde_json::from_value(to_result(result, false)).map_err(|_| e))
+            .map_err(|e| {
+                let e = serde_json::Error::custom(format!(
+                    "result of tool `{name}` is not a `{}`: {e}",
+                    type_name::<O>()
+                ));
+                ToolError::Deserialize(e.into())
+            })
+    }
+}
@@ -0,0 +1,166 @@
+//! Feedback on argument names a tool doesn't accept.
+//!
+//! Tool input structs don't usually `deny_unknown_fields`, so a model that
+//! sends `{"lattitude": 48.8}` to a tool taking `lat` gets a missing-field
+//! error, or a default silently filled in, and never learns the right
+//! spelling. With [`UnknownArgs::Warn`] or [`UnknownArgs::Reject`] each
+//! call's top-level keys are checked against the tool's parameter schema
+//! first:
+//!
+//! ```ignore
+//! tools.set_unknown_args(UnknownArgs::Reject);
+//! let err = tools.call(FunctionCall::new("weather".into(), json!({ "lattitude": 48.8 }))).await;
+//! // tool `weather` got unknown arguments: `lattitude` (did you mean `lat`?); accepted: `lat`, `lon`
+//! ```
+//!
+//! `Warn` runs the call and lists the ignored keys in
+//! [`FunctionResponse::warnings`][crate::FunctionResponse::warnings].
+//! Schemas without `properties`, or that allow `additionalProperties`, are
+//! not checked.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{ToolCollection, ToolEntry, ToolError, lint::top_level_params};
+
+/// What a [`ToolCollection`] does with arguments its tool's schema
+/// doesn't declare.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnknownArgs {
+    /// Leave them to the tool's deserializer.
+    #[default]
+    Ignore,
+    /// Run the call and add a warning to the response.
+    Warn,
+    /// Fail with [`ToolError::UnknownArguments`] before decoding.
+    Reject,
+}
+
+/// Top-level keys of `arguments` not declared by `parameters`.
+pub fn unknown_keys<'a>(parameters: &Value, arguments: &'a Value) -> Vec<&'a str> {
+    let (Some(properties), Some(arguments)) =
+        (parameters["properties"].as_object(), arguments.as_object())
+    else {
+        return Vec::new();
+    };
+    if !matches!(
+        parameters.get("additionalProperties"),
+        None | Some(Value::Bool(false))
+    ) {
+        return Vec::new();
+    }
+    arguments
+        .keys()
+        .filter(|key| !properties.contains_key(key.as_str()))
+        .map(String::as_str)
+        .collect()
+}
+
+/// The accepted name `key` was most likely meant to be: one that starts
+/// with the other (`lat`/`lattitude`), or that is a few edits away.
+/// Case-insensitive.
+pub fn suggest<'a>(key: &str, accepted: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let key = key.to_lowercase();
+    accepted
+        .into_iter()
+        .filter_map(|name| {
+            let lower = name.to_lowercase();
+            let distance = edit_distance(&key, &lower);
+            let (short, long) = if key.len() <= lower.len() {
+                (&key, &lower)
+            } else {
+                (&lower, &key)
+            };
+            let prefix = short.len() >= 3 && long.starts_with(short.as_str());
+            let close = distance <= (long.chars().count() / 3).max(1);
+            (prefix || close).then_some((distance, name))
+        })
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, name)| name)
+}
+
+/// Levenshtein distance over chars.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitute.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// `` `key` `` with a suggestion from `accepted`, if there is one.
+fn describe(key: &str, accepted: &[&str]) -> String {
+    match suggest(key, accepted.iter().copied()) {
+        Some(name) => format!("`{key}` (did you mean `{name}`?)"),
+        None => format!("`{key}`"),
+    }
+}
+
+impl<M> ToolCollection<M> {
+    /// What to do with undeclared arguments; see [`unknown`][crate::unknown].
+    pub fn set_unknown_args(&mut self, mode: UnknownArgs) {
+        self.unknown_args = mode;
+    }
+
+    pub fn unknown_args(&self) -> UnknownArgs {
+        self.unknown_args
+    }
+
+    /// Check `arguments` for `entry` under the collection's
+    /// [`UnknownArgs`] mode: the warnings to attach, or the rejection.
+    pub(crate) fn check_unknown_args(
+        &self,
+        entry: &ToolEntry<M>,
+        arguments: &Value,
+    ) -> Result<Vec<String>, ToolError> {
+        if self.unknown_args == UnknownArgs::Ignore {
+            return Ok(Vec::new());
+        }
+        let parameters = &entry.decl.parameters;
+        let unknown = unknown_keys(parameters, arguments);
+        if unknown.is_empty() {
+            return Ok(Vec::new());
+        }
+        let accepted: Vec<&str> = top_level_params(parameters)
+            .map(|(name, _)| name.as_str())
+            .collect();
+        if self.unknown_args == UnknownArgs::Warn {
+            return Ok(unknown
+                .iter()
+                .map(|key| format!("unknown argument {} ignored", describe(key, &accepted)))
+                .collect());
+        }
+        let listed: Vec<String> = unknown.iter().map(|key| describe(key, &accepted)).collect();
+        let names: Vec<String> = accepted.iter().map(|name| format!("`{name}`")).collect();
+        Err(ToolError::UnknownArguments {
+            tool: entry.decl.name.clone(),
+            unknown: unknown.iter().map(|key| key.to_string()).collect(),
+            accepted: accepted.iter().map(|name| name.to_string()).collect(),
+            summary: format!("{}; accepted: {}", listed.join(", "), names.join(", ")),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suggestions() {
+        let accepted = ["lat", "lon", "units", "query"];
+        assert_eq!(suggest("lattitude", accepted), Some("lat"));
+        assert_eq!(suggest("long", accepted), Some("lon"));
+        assert_eq!(suggest("Units", accepted), Some("units"));
+        assert_eq!(suggest("querry", accepted), Some("query"));
+        assert_eq!(suggest("verbose", accepted), None);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+}
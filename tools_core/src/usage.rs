@@ -0,0 +1,269 @@
+//! Per-call usage events for offline analysis.
+//!
+//! A [`UsageSink`] attached with
+//! [`set_usage_sink`][ToolCollection::set_usage_sink] gets one
+//! [`UsageEvent`] for every call that reached a tool. Unlike
+//! [`failures`][crate::failures] and the [`journal`][crate::journal],
+//! events never carry argument or result contents, only their sizes and
+//! metadata, so they can be shipped to an analytics store as they are:
+//!
+//! ```ignore
+//! tools.set_usage_sink(Arc::new(JsonlUsageSink::create("usage.jsonl")?));
+//!
+//! let out = conversation::with_conversation("conv-42", tools.call(call)).await?;
+//! ```
+//!
+//! `conversation_id` comes from
+//! [`with_conversation`][crate::conversation::with_conversation], and
+//! `namespace` from the collection.
+//! `retries` counts the failed calls with the same tool and arguments
+//! that came right before this one in the same conversation; a success
+//! resets it. Calls naming an unknown tool and background cache refreshes
+//! produce no event.
+
+use std::{
+    collections::HashMap,
+    io,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{ErrorClass, FunctionResponse, ToolCollection, ToolError, conversation, hash};
+
+/// One call, without its payloads.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageEvent {
+    /// When the call started, in milliseconds since the Unix epoch.
+    pub timestamp: u64,
+    pub conversation_id: Option<String>,
+    /// The collection's [namespace][ToolCollection::set_namespace], e.g.
+    /// the deployment the call ran in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
+    /// The tool's registered name, even when called by an alias.
+    pub tool: String,
+    pub duration_ms: u64,
+    pub outcome: UsageOutcome,
+    /// Set when `outcome` is [`Error`][UsageOutcome::Error].
+    pub error_class: Option<ErrorClass>,
+    /// Size of the arguments as compact JSON.
+    pub args_bytes: u64,
+    /// Size of the result handed back as compact JSON; `0` on error.
+    pub result_bytes: u64,
+    pub cache_hit: bool,
+    pub retries: u32,
+}
+
+/// How a call ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageOutcome {
+    Ok,
+    /// The tool answered with a [domain error][crate::domain].
+    DomainError,
+    Error,
+}
+
+/// Receives usage events; see [`usage`][crate::usage]. Called on the
+/// calling task after the call completes.
+pub trait UsageSink: Send + Sync {
+    fn record(&self, event: UsageEvent);
+
+    /// Write out anything buffered. Called by
+    /// [`shutdown_gracefully`][ToolCollection::shutdown_gracefully].
+    fn flush(&self) {}
+}
+
+/// Failing argument sets remembered for `retries`. Past this the streaks
+/// are forgotten and counting starts over.
+const MAX_STREAKS: usize = 4096;
+
+type StreakKey = (Option<String>, String, u64);
+
+/// What is known about a call before it runs.
+pub(crate) struct Started {
+    timestamp: u64,
+    args_bytes: u64,
+    args_hash: u64,
+}
+
+/// A sink and the failure streaks seen so far.
+pub(crate) struct UsageLog {
+    sink: Arc<dyn UsageSink>,
+    streaks: Mutex<HashMap<StreakKey, u32>>,
+}
+
+fn millis(d: Duration) -> u64 {
+    d.as_millis().try_into().unwrap_or(u64::MAX)
+}
+
+/// Length of `value` as compact JSON, without building the string.
+fn json_len(value: &Value) -> u64 {
+    struct Count(u64);
+    impl io::Write for Count {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0 += buf.len() as u64;
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+    let mut count = Count(0);
+    serde_json::to_writer(&mut count, value).map_or(0, |()| count.0)
+}
+
+impl UsageLog {
+    pub(crate) fn flush(&self) {
+        self.sink.flush();
+    }
+
+    pub(crate) fn start(&self, arguments: &Value) -> Started {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        Started {
+            timestamp: millis(now),
+            args_bytes: json_len(arguments),
+            args_hash: hash::canonical_hash(arguments),
+        }
+    }
+
+    /// Report a finished call to `tool`.
+    pub(crate) fn finish(
+        &self,
+        started: Started,
+        tool: &str,
+        namespace: Option<&str>,
+        duration: Duration,
+        result: &Result<FunctionResponse, ToolError>,
+        cache_hit: bool,
+    ) {
+        let conversation_id = conversation::conversation();
+        let key = (conversation_id.clone(), tool.to_owned(), started.args_hash);
+        let failed = !matches!(result, Ok(response) if !response.is_error);
+        let retries = {
+            let mut streaks = self.streaks.lock().unwrap_or_else(PoisonError::into_inner);
+            let retries = if failed {
+                streaks.get(&key).copied()
+            } else {
+                streaks.remove(&key)
+            }
+            .unwrap_or(0);
+            if failed {
+                if streaks.len() >= MAX_STREAKS && !streaks.contains_key(&key) {
+                    streaks.clear();
+                }
+                streaks.insert(key, retries + 1);
+            }
+            retries
+        };
+        let (outcome, error_class, result_bytes) = match result {
+            Ok(response) if response.is_error => {
+                (UsageOutcome::DomainError, None, json_len(&response.result))
+            }
+            Ok(response) => (UsageOutcome::Ok, None, json_len(&response.result)),
+            Err(e) => (UsageOutcome::Error, Some(e.classification()), 0),
+        };
+        self.sink.record(UsageEvent {
+            timestamp: started.timestamp,
+            conversation_id,
+            namespace: namespace.map(str::to_string),
+            tool: tool.to_string(),
+            duration_ms: millis(duration),
+            outcome,
+            error_class,
+            args_bytes: started.args_bytes,
+            result_bytes,
+            cache_hit,
+            retries,
+        });
+    }
+}
+
+impl<M> ToolCollection<M> {
+    /// Report every call to `sink`; see [`usage`][crate::usage]. Replaces
+    /// any previous sink. Shared with clones made afterwards.
+    pub fn set_usage_sink(&mut self, sink: Arc<dyn UsageSink>) {
+        self.usage = Some(Arc::new(UsageLog {
+            sink,
+            streaks: Mutex::default(),
+        }));
+    }
+
+    /// Label every usage event with `namespace`, such as the deployment
+    /// (`staging`, `prod`) the collection serves. `None` removes the label.
+    pub fn set_namespace(&mut self, namespace: Option<String>) {
+        self.namespace = namespace;
+    }
+
+    pub fn namespace(&self) -> Option<&str> {
+        self.namespace.as_deref()
+    }
+}
+
+// ============================================================================
+// JSONL FILE SINK
+// ============================================================================
+
+#[cfg(feature = "usage-jsonl")]
+pub use jsonl::JsonlUsageSink;
+
+#[cfg(feature = "usage-jsonl")]
+mod jsonl {
+    use std::{
+        fs::{File, OpenOptions},
+        io::{self, Write},
+        path::Path,
+        sync::{
+            Mutex, PoisonError,
+            atomic::{AtomicU64, Ordering},
+        },
+    };
+
+    use super::{UsageEvent, UsageSink};
+
+    /// [`UsageSink`] appending one JSON object per line to a file.
+    /// Enabled with the `usage-jsonl` feature.
+    pub struct JsonlUsageSink {
+        file: Mutex<File>,
+        dropped: AtomicU64,
+    }
+
+    impl JsonlUsageSink {
+        /// Append to the file at `path`, creating it if needed.
+        pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+            let file = OpenOptions::new().append(true).create(true).open(path)?;
+            Ok(Self {
+                file: Mutex::new(file),
+                dropped: AtomicU64::new(0),
+            })
+        }
+
+        /// Events that could not be written and were dropped.
+        pub fn dropped(&self) -> u64 {
+            self.dropped.load(Ordering::Relaxed)
+        }
+    }
+
+    impl UsageSink for JsonlUsageSink {
+        fn record(&self, event: UsageEvent) {
+            let mut line = serde_json::to_vec(&event).expect("usage events serialize");
+            line.push(b'\n');
+            // One write per event under the lock, so lines from concurrent
+            // calls never interleave.
+            let mut file = self.file.lock().unwrap_or_else(PoisonError::into_inner);
+            if file.write_all(&line).is_err() {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        fn flush(&self) {
+            let file = self.file.lock().unwrap_or_else(PoisonError::into_inner);
+            let _ = file.sync_data();
+        }
+    }
+}
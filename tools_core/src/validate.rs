@@ -0,0 +1,56 @@
+//! Checking arguments against the declared schema before decoding.
+//!
+//! A serde error names the first problem it hits, often without the field
+//! ("invalid type: string \"48.8\", expected f64"). With validation on,
+//! every call's arguments are first compared with the tool's parameter
+//! schema, and a mismatch fails with [`ToolError::SchemaValidation`]
+//! listing each problem by JSON pointer:
+//!
+//! ```ignore
+//! tools.set_validation(true);
+//! let err = tools.call(FunctionCall::new("weather".into(), json!({ "lat": "48.8" }))).await;
+//! // arguments for tool `weather` don't match its schema:
+//! // `/lon`: required, expected number; `/lat`: expected number, found string
+//! ```
+//!
+//! The check covers required properties, `type`, `enum`, `const` and
+//! `anyOf`/`oneOf` branches, recursing into properties and array items;
+//! `$ref` is not followed. Undeclared arguments are the business of
+//! [`UnknownArgs`][crate::UnknownArgs]. For tools registered with a
+//! [`handler`][crate::ToolSpec::handler], whose decoder accepts any JSON,
+//! this is the only check the arguments get.
+
+use serde_json::Value;
+
+use crate::{ToolCollection, ToolEntry, ToolError, diagnostics};
+
+impl<M> ToolCollection<M> {
+    /// Validate arguments against each tool's schema before decoding them
+    /// (`true`), or leave it to the decoder (`false`, the default).
+    pub fn set_validation(&mut self, on: bool) {
+        self.validation = on;
+    }
+
+    pub fn validation(&self) -> bool {
+        self.validation
+    }
+
+    pub(crate) fn check_schema(
+        &self,
+        entry: &ToolEntry<M>,
+        arguments: &Value,
+    ) -> Result<(), ToolError> {
+        if !self.validation {
+            return Ok(());
+        }
+        let errors = diagnostics::violations(&entry.decl.parameters, arguments);
+        if errors.is_empty() {
+            return Ok(());
+        }
+        Err(ToolError::SchemaValidation {
+            tool: entry.decl.name.clone(),
+            summary: errors.join("; "),
+            errors,
+        })
+    }
+}
@@ -0,0 +1,158 @@
+//! Eager schema generation at startup.
+//!
+//! Derived schemas are cached in `Lazy` statics that initialize on first
+//! use, and [`ToolCollection::json`] serializes the declarations on first
+//! call. With many derived types that first request is noticeably slow.
+//! Call [`ToolCollection::warm_up`] (or the async variant for very large
+//! registries) during startup to pay that cost up front.
+//!
+//! ```ignore
+//! let tools = collect_tools();
+//! let report = tools.warm_up()?;
+//! tracing::info!("schemas ready: {} tools in {:?}", report.tools, report.elapsed);
+//! ```
+
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+
+use crate::{ToolCollection, ToolError, ToolRegistration};
+
+/// Outcome of a warm-up pass.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WarmUpReport {
+    /// Number of tools whose schemas were evaluated.
+    pub tools: usize,
+    /// Wall-clock time spent.
+    pub elapsed: Duration,
+}
+
+/// Evaluate the `param_schema` function of every tool registered through
+/// `#[tool]`, initializing all `Lazy` schema statics they touch.
+///
+/// Useful when collections are built later (or repeatedly) and only the
+/// static schemas should be primed now.
+pub fn warm_up_inventory() -> WarmUpReport {
+    let start = Instant::now();
+    let mut tools = 0;
+    for reg in inventory::iter::<ToolRegistration> {
+        let _ = (reg.param_schema)();
+        tools += 1;
+    }
+    WarmUpReport {
+        tools,
+        elapsed: start.elapsed(),
+    }
+}
+
+/// Like [`warm_up_inventory`], but spreads the registrations across
+/// `tokio::task::spawn_blocking` in chunks of `chunk_size` so a large
+/// registry does not stall the calling task's worker thread.
+pub async fn warm_up_inventory_async(chunk_size: usize) -> Result<WarmUpReport, ToolError> {
+    let start = Instant::now();
+    let schemas: Vec<fn() -> Value> = inventory::iter::<ToolRegistration>
+        .into_iter()
+        .map(|reg| reg.param_schema)
+        .collect();
+    let tools = schemas.len();
+
+    let handles: Vec<_> = schemas
+        .chunks(chunk_size.max(1))
+        .map(|chunk| {
+            let chunk = chunk.to_vec();
+            tokio::task::spawn_blocking(move || {
+                for schema in chunk {
+                    let _ = schema();
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle
+            .await
+            .map_err(|e| ToolError::Runtime(format!("warm-up task failed: {e}")))?;
+    }
+
+    Ok(WarmUpReport {
+        tools,
+        elapsed: start.elapsed(),
+    })
+}
+
+impl<M> ToolCollection<M> {
+    /// Evaluate every registered tool's schema and fill the serialized
+    /// declaration cache used by [`json`][Self::json].
+    pub fn warm_up(&self) -> Result<WarmUpReport, ToolError> {
+        let start = Instant::now();
+        warm_up_inventory();
+        self.json()?;
+        Ok(WarmUpReport {
+            tools: self.len(),
+            elapsed: start.elapsed(),
+        })
+    }
+
+    /// Async variant of [`warm_up`][Self::warm_up]; schema generation runs
+    /// on the blocking pool via [`warm_up_inventory_async`].
+    pub async fn warm_up_async(&self, chunk_size: usize) -> Result<WarmUpReport, ToolError> {
+        let start = Instant::now();
+        warm_up_inventory_async(chunk_size).await?;
+        self.json()?;
+        Ok(WarmUpReport {
+            tools: self.len(),
+            elapsed: start.elapsed(),
+        })
+    }
+
+    /// `true` once the serialized declarations are cached, i.e. the next
+    /// [`json`][Self::json] call is a clone rather than a serialization.
+    pub fn is_warm(&self) -> bool {
+        self.json_cache.get().is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tools() -> ToolCollection {
+        let mut tools = ToolCollection::new();
+        tools
+            .register("add", "Adds.", |p: (i32, i32)| async move { p.0 + p.1 }, ())
+            .unwrap();
+        tools
+    }
+
+    #[test]
+    fn warm_up_fills_json_cache() {
+        let tools = tools();
+        assert!(!tools.is_warm());
+        let report = tools.warm_up().unwrap();
+        assert_eq!(report.tools, 1);
+        assert!(tools.is_warm());
+    }
+
+    #[test]
+    fn register_and_unregister_invalidate_cache() {
+        let mut tools = tools();
+        tools.warm_up().unwrap();
+        tools
+            .register("neg", "Negates.", |x: i32| async move { -x }, ())
+            .unwrap();
+        assert!(!tools.is_warm());
+        assert_eq!(tools.json().unwrap().as_array().unwrap().len(), 2);
+
+        tools.unregister("neg").unwrap();
+        assert!(!tools.is_warm());
+        assert_eq!(tools.json().unwrap().as_array().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn async_warm_up_matches_sync() {
+        let cold = tools().json().unwrap();
+        let tools = tools();
+        tools.warm_up_async(1).await.unwrap();
+        assert!(tools.is_warm());
+        assert_eq!(tools.json().unwrap(), cold);
+    }
+}
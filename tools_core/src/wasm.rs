@@ -0,0 +1,273 @@
+//! Tools shipped as a JSON manifest plus a WASM module.
+//!
+//! Enabled with the `wasm-host` feature. The manifest carries everything
+//! the model sees (name, description, schemas, tags); the module only
+//! runs, on [`wasmtime`]. It is compiled once, at registration, and each
+//! call gets a fresh instance.
+//!
+//! The guest exports its `memory`, an `alloc(len: i32) -> i32` the host
+//! copies the arguments into, and `call(ptr: i32, len: i32) -> i32`. The
+//! arguments are a JSON string, and `call` returns a pointer to the result:
+//! a little-endian `u32` length followed by that many bytes of JSON. The
+//! module may import nothing.
+//!
+//! Every call runs under the caps in [`WasmLimits`]: fuel, checked by
+//! wasmtime as the guest executes, a ceiling on linear memory, and a
+//! wall-clock limit enforced by epoch interruption, so a guest stuck in a
+//! loop is stopped rather than left running on its thread. A
+//! [`WasmFailure`] becomes [`ToolError::Runtime`].
+//!
+//! ```ignore
+//! let manifest = WasmManifest::from_json(&std::fs::read_to_string("to_upper/manifest.json")?)?;
+//! let module = std::fs::read("to_upper/to_upper.wasm")?;
+//! tools.register_wasm(manifest, module, WasmLimits::default())?;
+//! ```
+
+use std::{
+    fmt,
+    sync::{Arc, LazyLock},
+    time::Duration,
+};
+
+use serde::{Deserialize, de::DeserializeOwned};
+use serde_json::{Value, json};
+use wasmtime::{Config, Engine, Instance, Module, ResourceLimiter, Store, Trap};
+
+use crate::{ToolCollection, ToolError, ToolId, spec::ToolSpec};
+
+// ============================================================================
+// MANIFEST AND LIMITS
+// ============================================================================
+
+/// The declaration half of a WASM tool, read from `manifest.json`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct WasmManifest {
+    pub name: String,
+    pub description: String,
+    /// JSON schema of the arguments object.
+    pub parameters: Value,
+    #[serde(default)]
+    pub returns: Option<Value>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl WasmManifest {
+    pub fn from_json(json: &str) -> Result<Self, ToolError> {
+        serde_json::from_str(json)
+            .map_err(|e| ToolError::Runtime(format!("invalid WASM tool manifest: {e}")))
+    }
+}
+
+/// Resource caps for one call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WasmLimits {
+    /// Fuel the guest may consume; wasmtime charges roughly one unit per
+    /// instruction.
+    pub fuel: u64,
+    /// Largest linear memory the guest may grow to.
+    pub max_memory_bytes: usize,
+    /// Wall-clock limit, including instantiation. Enforced in steps of
+    /// [`EPOCH_TICK`].
+    pub timeout: Duration,
+}
+
+impl Default for WasmLimits {
+    fn default() -> Self {
+        Self {
+            fuel: 100_000_000,
+            max_memory_bytes: 16 * 1024 * 1024,
+            timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+// ============================================================================
+// HOST
+// ============================================================================
+
+/// Why a guest call produced no output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WasmFailure {
+    /// The module failed to compile or instantiate, or lacks the expected
+    /// exports.
+    Load(String),
+    /// The guest trapped.
+    Trap(String),
+    FuelExhausted,
+    MemoryLimit,
+    TimedOut(Duration),
+}
+
+impl fmt::Display for WasmFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Load(msg) => write!(f, "module failed to load: {msg}"),
+            Self::Trap(msg) => write!(f, "trapped: {msg}"),
+            Self::FuelExhausted => f.write_str("ran out of fuel"),
+            Self::MemoryLimit => f.write_str("exceeded its memory limit"),
+            Self::TimedOut(limit) => write!(f, "timed out after {limit:?}"),
+        }
+    }
+}
+
+/// How often the engine's epoch advances. Timeouts are rounded up to a
+/// whole number of ticks.
+pub const EPOCH_TICK: Duration = Duration::from_millis(10);
+
+/// The engine every WASM tool is compiled for, with fuel metering and
+/// epoch interruption on. A background thread advances its epoch every
+/// [`EPOCH_TICK`] for as long as the process runs.
+static ENGINE: LazyLock<Engine> = LazyLock::new(|| {
+    let mut config = Config::new();
+    config.consume_fuel(true).epoch_interruption(true);
+    let engine = Engine::new(&config).expect("WASM engine config is valid");
+    let ticker = engine.clone();
+    std::thread::Builder::new()
+        .name("tools-wasm-epoch".into())
+        .spawn(move || {
+            loop {
+                std::thread::sleep(EPOCH_TICK);
+                ticker.increment_epoch();
+            }
+        })
+        .expect("failed to spawn the WASM epoch thread");
+    engine
+});
+
+/// Store state for one call: refuses memory growth past the cap and
+/// remembers that it did, so the resulting trap reads as
+/// [`WasmFailure::MemoryLimit`].
+struct Limiter {
+    max_memory_bytes: usize,
+    exceeded: bool,
+}
+
+impl ResourceLimiter for Limiter {
+    fn memory_growing(
+        &mut self,
+        _current: usize,
+        desired: usize,
+        _maximum: Option<usize>,
+    ) -> wasmtime::Result<bool> {
+        if desired > self.max_memory_bytes {
+            self.exceeded = true;
+            return Err(wasmtime::Error::msg(format!(
+                "memory of {desired} bytes exceeds the limit"
+            )));
+        }
+        Ok(true)
+    }
+
+    fn table_growing(
+        &mut self,
+        _current: usize,
+        desired: usize,
+        maximum: Option<usize>,
+    ) -> wasmtime::Result<bool> {
+        Ok(maximum.is_none_or(|max| desired <= max))
+    }
+}
+
+fn compile(module: &[u8]) -> Result<Module, WasmFailure> {
+    Module::new(&ENGINE, module).map_err(|e| WasmFailure::Load(format!("{e:#}")))
+}
+
+/// Instantiate `module` and run its `call` export on `input`, under
+/// `limits`. Blocks until the guest returns, traps or is interrupted.
+fn call_guest(module: &Module, input: &str, limits: &WasmLimits) -> Result<String, WasmFailure> {
+    let mut store = Store::new(
+        module.engine(),
+        Limiter {
+            max_memory_bytes: limits.max_memory_bytes,
+            exceeded: false,
+        },
+    );
+    store.limiter(|limiter| limiter);
+    store
+        .set_fuel(limits.fuel)
+        .map_err(|e| WasmFailure::Load(e.to_string()))?;
+    let ticks = limits.timeout.as_nanos().div_ceil(EPOCH_TICK.as_nanos());
+    // One extra tick: the epoch may advance just after the deadline is set.
+    store.set_epoch_deadline(u64::try_from(ticks).unwrap_or(u64::MAX).saturating_add(1));
+    store.epoch_deadline_trap();
+
+    let result = (|| {
+        let instance = Instance::new(&mut store, module, &[])?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or_else(|| wasmtime::Error::msg("missing export `memory`"))?;
+        let alloc = instance.get_typed_func::<i32, i32>(&mut store, "alloc")?;
+        let call = instance.get_typed_func::<(i32, i32), i32>(&mut store, "call")?;
+
+        let len = i32::try_from(input.len())?;
+        let ptr = alloc.call(&mut store, len)?;
+        memory.write(&mut store, ptr as u32 as usize, input.as_bytes())?;
+        let out = call.call(&mut store, (ptr, len))? as u32 as usize;
+
+        let mut header = [0; 4];
+        memory.read(&store, out, &mut header)?;
+        let mut output = vec![0; u32::from_le_bytes(header) as usize];
+        memory.read(&store, out + header.len(), &mut output)?;
+        Ok(String::from_utf8(output)?)
+    })();
+
+    result.map_err(|e: wasmtime::Error| {
+        if store.data().exceeded {
+            return WasmFailure::MemoryLimit;
+        }
+        match e.downcast_ref::<Trap>() {
+            Some(Trap::OutOfFuel) => WasmFailure::FuelExhausted,
+            Some(Trap::Interrupt) => WasmFailure::TimedOut(limits.timeout),
+            Some(trap) => WasmFailure::Trap(trap.to_string()),
+            None => WasmFailure::Load(format!("{e:#}")),
+        }
+    })
+}
+
+async fn run(
+    tool: Arc<str>,
+    module: Module,
+    args: Value,
+    limits: WasmLimits,
+) -> Result<Value, ToolError> {
+    let input = args.to_string();
+    // Not abandoned on timeout: epoch interruption stops the guest, so
+    // the thread is always joined.
+    let output = tokio::task::spawn_blocking(move || call_guest(&module, &input, &limits))
+        .await
+        .map_err(|e| ToolError::Runtime(format!("WASM tool `{tool}` panicked: {e}")))?
+        .map_err(|failure| ToolError::Runtime(format!("WASM tool `{tool}` {failure}")))?;
+    serde_json::from_str(&output)
+        .map_err(|e| ToolError::Runtime(format!("WASM tool `{tool}` returned invalid JSON: {e}")))
+}
+
+impl<M: DeserializeOwned> ToolCollection<M> {
+    /// Register the tool described by `manifest`, run from `module`, WASM
+    /// in binary or text format. Fails with [`ToolError::Runtime`] if the
+    /// module doesn't compile. Its metadata is deserialized from `{}`.
+    pub fn register_wasm(
+        &mut self,
+        manifest: WasmManifest,
+        module: impl AsRef<[u8]>,
+        limits: WasmLimits,
+    ) -> Result<ToolId, ToolError> {
+        let meta: M = serde_json::from_value(json!({})).map_err(|e| ToolError::BadMeta {
+            tool: manifest.name.clone().into(),
+            error: e.to_string(),
+        })?;
+        let module = compile(module.as_ref()).map_err(|failure| {
+            ToolError::Runtime(format!("WASM tool `{}` {failure}", manifest.name))
+        })?;
+        let tool: Arc<str> = manifest.name.as_str().into();
+
+        let mut spec = ToolSpec::new(manifest.name, manifest.description)
+            .params_schema(manifest.parameters)
+            .tags(manifest.tags)
+            .handler(move |args| Box::pin(run(tool.clone(), module.clone(), args, limits)));
+        if let Some(returns) = manifest.returns {
+            spec = spec.returns_schema(returns);
+        }
+        self.register_spec(spec.with_meta(meta))
+    }
+}
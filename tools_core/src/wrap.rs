@@ -0,0 +1,131 @@
+//! Tools whose input is a bare array or primitive.
+//!
+//! `register("sum", .., |xs: Vec<i64>| ..)` declares an array as the
+//! tool's parameters. OpenAI strict mode rejects that, since parameters
+//! must be an object, and Gemini mangles it. Such registrations are
+//! flagged with [`codes::PRIMITIVE_PARAMS`][crate::checks::codes::PRIMITIVE_PARAMS].
+//! Taking a struct with a named field fixes it in the tool. Alternatively
+//! the collection can wrap the input under one synthesized parameter:
+//!
+//! ```ignore
+//! tools.set_wrap_bare_inputs(true);
+//! tools.register("sum", "Adds numbers.", |xs: Vec<i64>| async move { xs.iter().sum::<i64>() }, ())?;
+//! // parameters: {"type": "object", "properties": {"items": {"type": "array", ..}}, "required": ["items"]}
+//! tools.call(FunctionCall::new("sum".into(), json!({ "items": [1, 2, 3] }))).await?;
+//! ```
+//!
+//! Arrays are wrapped as `items` and anything else as `value`. Calls must
+//! use the wrapped shape the declaration advertises; the tool still
+//! receives the bare input. [`invoke`][ToolCollection::invoke] wraps its
+//! typed input the same way.
+
+use serde::de::Error as _;
+use serde_json::{Map, Value, json};
+
+use crate::{DeserializationError, ToolCollection, ToolError, ToolFunc, checks};
+
+/// The type of a `parameters` schema that isn't an object, if any.
+pub fn bare_type(parameters: &Value) -> Option<&str> {
+    parameters["type"].as_str().filter(|ty| *ty != "object")
+}
+
+/// The parameter a bare input of type `ty` is wrapped under.
+pub fn param_name(ty: &str) -> &'static str {
+    if ty == "array" { "items" } else { "value" }
+}
+
+/// `parameters` as the schema of an object with one required `param`.
+pub fn wrap_schema(parameters: Value, param: &str) -> Value {
+    json!({
+        "type": "object",
+        "properties": { param: parameters },
+        "required": [param]
+    })
+}
+
+/// `func` decoding its input from the `param` field of an object.
+pub fn wrap_func(func: ToolFunc, param: &'static str) -> ToolFunc {
+    let owned = func.clone();
+    let borrowed = func.clone();
+    ToolFunc::new(
+        move |arguments: Value| owned.decode(unwrap(arguments, param)?),
+        move |input, ctx| func.run(input, ctx),
+    )
+    .with_decode_ref(move |arguments: &Value| match arguments.get(param) {
+        Some(input) if arguments.is_object() => borrowed.decode_ref(input),
+        _ => Err(missing(arguments, param)),
+    })
+}
+
+fn unwrap(arguments: Value, param: &'static str) -> Result<Value, ToolError> {
+    match arguments {
+        Value::Object(mut map) => map
+            .remove(param)
+            .ok_or_else(|| missing(&Value::Null, param)),
+        other => Err(missing(&other, param)),
+    }
+}
+
+fn missing(arguments: &Value, param: &'static str) -> ToolError {
+    let error = if arguments.is_object() || arguments.is_null() {
+        serde_json::Error::missing_field(param)
+    } else {
+        serde_json::Error::custom(format!(
+            "expected an object with `{param}`, found {arguments}"
+        ))
+    };
+    DeserializationError::from(error).into()
+}
+
+/// The wrapped parameters and decoder for a bare tool, if it is one.
+pub(crate) fn wrap(parameters: &Value, func: &ToolFunc) -> Option<(Value, ToolFunc, &'static str)> {
+    let param = param_name(bare_type(parameters)?);
+    Some((
+        wrap_schema(parameters.clone(), param),
+        wrap_func(func.clone(), param),
+        param,
+    ))
+}
+
+/// `arguments` for a tool wrapped under `param`.
+pub(crate) fn wrap_arguments(arguments: Value, param: Option<&str>) -> Value {
+    match param {
+        Some(param) => Value::Object(Map::from_iter([(param.to_string(), arguments)])),
+        None => arguments,
+    }
+}
+
+impl<M> ToolCollection<M> {
+    /// Wrap bare array and primitive inputs in an object; see
+    /// [`wrap`][crate::wrap]. Turning it on also wraps tools already
+    /// registered. Turning it off only affects later registrations.
+    pub fn set_wrap_bare_inputs(&mut self, wrap: bool) {
+        self.wrap_bare_inputs = wrap;
+        if !wrap {
+            return;
+        }
+        let mut wrapped = Vec::new();
+        for entry in self.entries.iter_mut().flatten() {
+            if let Some((parameters, func, param)) = self::wrap(&entry.decl.parameters, &entry.func)
+            {
+                entry.decl.parameters = parameters;
+                entry.func = func;
+                entry.wrapped = Some(param);
+                wrapped.push(entry.decl.name.clone());
+            }
+        }
+        if wrapped.is_empty() {
+            return;
+        }
+        self.json_cache.take();
+        for name in wrapped {
+            let entry = self.get(&name).expect("wrapped tool is registered");
+            let findings = checks::check(&name, &entry.decl.parameters, &entry.func);
+            self.record_warnings(&name, findings);
+        }
+    }
+
+    pub fn wrap_bare_inputs(&self) -> bool {
+        self.wrap_bare_inputs
+    }
+}
@@ -1,15 +1,17 @@
 //! Procedural macros for **tools-rs**
 #![forbid(unsafe_code)]
 
+mod manifest;
+
 use proc_macro::TokenStream;
 use proc_macro2::{Ident, Span};
 use proc_macro_crate::{crate_name, FoundCrate};
-use proc_macro_error::{abort, proc_macro_error};
+use proc_macro_error::{abort, emit_warning, proc_macro_error};
 use quote::quote;
 use syn::{
-    parse::Parser, parse_macro_input, punctuated::Punctuated, Attribute, Data, DeriveInput, Expr,
-    ExprLit, Fields, FieldsNamed, FieldsUnnamed, FnArg, ItemFn, Lit, LitStr, Meta, Pat, PatIdent,
-    PatType, Token, Type, TypePath,
+    ext::IdentExt, meta::ParseNestedMeta, parse::Parser, parse_macro_input, punctuated::Punctuated, Attribute,
+    Data, DataEnum, DeriveInput, Expr, ExprLit, Fields, FieldsNamed, FieldsUnnamed, FnArg, ItemFn,
+    Lit, LitStr, Meta, Pat, PatIdent, PatType, Token, Type, TypePath,
 };
 
 // ============================================================================
@@ -17,7 +19,7 @@ use syn::{
 // ============================================================================
 
 #[proc_macro_error]
-#[proc_macro_derive(ToolSchema)]
+#[proc_macro_derive(ToolSchema, attributes(serde, schema))]
 pub fn derive_tool_schema(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
 
@@ -27,9 +29,7 @@ pub fn derive_tool_schema(input: TokenStream) -> TokenStream {
             Fields::Unnamed(fields) => generate_tuple_struct_schema(&input, fields),
             Fields::Unit => generate_unit_struct_schema(&input),
         },
-        Data::Enum(_) => {
-            abort!(input.ident, "Enum schemas are not yet supported");
-        }
+        Data::Enum(data_enum) => generate_enum_schema(&input, data_enum),
         Data::Union(_) => {
             abort!(input.ident, "Union schemas are not supported");
         }
@@ -41,26 +41,46 @@ fn generate_struct_schema(input: &DeriveInput, fields: &FieldsNamed) -> TokenStr
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
 
     let crate_path = get_crate_path();
+    let rename_all = parse_struct_attrs(&input.attrs);
 
-    let mut field_names = Vec::new();
-    let mut field_types = Vec::new();
-    let mut required_fields = Vec::new();
-
-    for field in &fields.named {
-        let field_name = field.ident.as_ref().unwrap();
-        let field_name_str = field_name.to_string();
-        let field_type = &field.ty;
-
-        // Check if field is Option<T> to determine if it's required
-        let is_optional = is_option_type(field_type);
+    let fields: Vec<_> = fields
+        .named
+        .iter()
+        .map(|field| {
+            let field_type = &field.ty;
+            (
+                field_wire_name(field, rename_all.as_deref()),
+                member_schema(field, &crate_path),
+                // Option<T>, Option<Option<T>> and Patch<T> fields may be omitted
+                !is_optional_field(field_type),
+            )
+        })
+        .collect();
+    let body = object_schema_body(&fields);
 
-        if !is_optional {
-            required_fields.push(field_name_str.clone());
+    TokenStream::from(quote! {
+        impl #impl_generics #crate_path::ToolSchema for #name #ty_generics #where_clause {
+            fn schema() -> #crate_path::serde_json::Value {
+                static SCHEMA: #crate_path::once_cell::sync::Lazy<#crate_path::serde_json::Value> = #crate_path::once_cell::sync::Lazy::new(|| {
+                    #body
+                });
+                SCHEMA.clone()
+            }
         }
+    })
+}
 
-        field_names.push(field_name_str);
-        field_types.push(field_type);
-    }
+/// Build the `{"type": "object", ...}` value from `(name, schema, required)`
+/// triples.
+fn object_schema_body(fields: &[(String, proc_macro2::TokenStream, bool)]) -> proc_macro2::TokenStream {
+    let crate_path = get_crate_path();
+    let field_names = fields.iter().map(|(name, _, _)| name);
+    let field_types = fields.iter().map(|(_, schema, _)| schema);
+    let required_fields: Vec<_> = fields
+        .iter()
+        .filter(|(_, _, required)| *required)
+        .map(|(name, _, _)| name)
+        .collect();
 
     let required_array = if required_fields.is_empty() {
         quote! { ::std::vec::Vec::<&str>::new() }
@@ -68,23 +88,16 @@ fn generate_struct_schema(input: &DeriveInput, fields: &FieldsNamed) -> TokenStr
         quote! { vec![#(#required_fields),*] }
     };
 
-    TokenStream::from(quote! {
-        impl #impl_generics #crate_path::ToolSchema for #name #ty_generics #where_clause {
-            fn schema() -> ::serde_json::Value {
-                static SCHEMA: #crate_path::once_cell::sync::Lazy<::serde_json::Value> = #crate_path::once_cell::sync::Lazy::new(|| {
-                    let mut properties = ::std::collections::HashMap::<String, ::serde_json::Value>::new();
-                    #(properties.insert(#field_names.to_string(), <#field_types as #crate_path::ToolSchema>::schema());)*
+    quote! {
+        let mut properties = ::std::collections::HashMap::<String, #crate_path::serde_json::Value>::new();
+        #(properties.insert(#field_names.to_string(), #field_types);)*
 
-                    ::serde_json::json!({
-                        "type": "object",
-                        "properties": properties,
-                        "required": #required_array
-                    })
-                });
-                SCHEMA.clone()
-            }
-        }
-    })
+        #crate_path::serde_json::json!({
+            "type": "object",
+            "properties": properties,
+            "required": #required_array
+        })
+    }
 }
 
 fn generate_tuple_struct_schema(input: &DeriveInput, fields: &FieldsUnnamed) -> TokenStream {
@@ -95,19 +108,16 @@ fn generate_tuple_struct_schema(input: &DeriveInput, fields: &FieldsUnnamed) ->
     let field_schemas: Vec<_> = fields
         .unnamed
         .iter()
-        .map(|field| {
-            let field_type = &field.ty;
-            quote! { <#field_type as #crate_path::ToolSchema>::schema() }
-        })
+        .map(|field| member_schema(field, &crate_path))
         .collect();
 
     let field_count = fields.unnamed.len();
 
     TokenStream::from(quote! {
         impl #impl_generics #crate_path::ToolSchema for #name #ty_generics #where_clause {
-            fn schema() -> ::serde_json::Value {
-                static SCHEMA: #crate_path::once_cell::sync::Lazy<::serde_json::Value> = #crate_path::once_cell::sync::Lazy::new(|| {
-                    ::serde_json::json!({
+            fn schema() -> #crate_path::serde_json::Value {
+                static SCHEMA: #crate_path::once_cell::sync::Lazy<#crate_path::serde_json::Value> = #crate_path::once_cell::sync::Lazy::new(|| {
+                    #crate_path::serde_json::json!({
                         "type": "array",
                         "prefixItems": [#(#field_schemas),*],
                         "minItems": #field_count,
@@ -127,9 +137,9 @@ fn generate_unit_struct_schema(input: &DeriveInput) -> TokenStream {
 
     TokenStream::from(quote! {
         impl #impl_generics #crate_path::ToolSchema for #name #ty_generics #where_clause {
-            fn schema() -> ::serde_json::Value {
-                static SCHEMA: #crate_path::once_cell::sync::Lazy<::serde_json::Value> = #crate_path::once_cell::sync::Lazy::new(|| {
-                    ::serde_json::json!({
+            fn schema() -> #crate_path::serde_json::Value {
+                static SCHEMA: #crate_path::once_cell::sync::Lazy<#crate_path::serde_json::Value> = #crate_path::once_cell::sync::Lazy::new(|| {
+                    #crate_path::serde_json::json!({
                         "type": "object",
                         "properties": {},
                         "required": ::std::vec::Vec::<&str>::new()
@@ -141,6 +151,525 @@ fn generate_unit_struct_schema(input: &DeriveInput) -> TokenStream {
     })
 }
 
+// ============================================================================
+// ENUM SCHEMAS
+// ============================================================================
+
+/// The `#[serde(...)]` attributes that decide an enum's JSON shape.
+#[derive(Default)]
+struct SerdeEnumAttrs {
+    untagged: Option<Span>,
+    tag: Option<(String, Span)>,
+    content: Option<(String, Span)>,
+    rename_all: Option<String>,
+    rename_all_fields: Option<String>,
+}
+
+#[derive(Default)]
+struct SerdeVariantAttrs {
+    rename: Option<String>,
+    rename_all: Option<String>,
+    other: bool,
+    skip: bool,
+}
+
+/// How serde represents the enum; see
+/// <https://serde.rs/enum-representations.html>.
+enum Repr {
+    External,
+    Internal(String),
+    Adjacent(String, String),
+    Untagged,
+}
+
+/// Walk every `#[serde(...)]` attribute, handing each nested meta to `f`.
+/// Keys `f` does not consume are skipped along with their values.
+fn for_each_serde_meta(
+    attrs: &[Attribute],
+    mut f: impl FnMut(&ParseNestedMeta) -> syn::Result<bool>,
+) {
+    for attr in attrs.iter().filter(|a| a.path().is_ident("serde")) {
+        let parsed = attr.parse_nested_meta(|meta| {
+            if f(&meta)? {
+                Ok(())
+            } else {
+                skip_meta_value(&meta)
+            }
+        });
+        if let Err(e) = parsed {
+            abort!(e.span(), "{}", e);
+        }
+    }
+}
+
+fn skip_meta_value(meta: &ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(Token![=]) {
+        meta.value()?.parse::<Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        meta.parse_nested_meta(|nested| skip_meta_value(&nested))?;
+    }
+    Ok(())
+}
+
+/// `key = "..."`, or the `deserialize` half of `key(deserialize = "...")`.
+fn serde_str_value(meta: &ParseNestedMeta) -> syn::Result<Option<String>> {
+    if meta.input.peek(Token![=]) {
+        return Ok(Some(meta.value()?.parse::<LitStr>()?.value()));
+    }
+    let mut found = None;
+    meta.parse_nested_meta(|nested| {
+        if nested.path.is_ident("deserialize") {
+            found = Some(nested.value()?.parse::<LitStr>()?.value());
+            Ok(())
+        } else {
+            skip_meta_value(&nested)
+        }
+    })?;
+    Ok(found)
+}
+
+fn parse_enum_attrs(attrs: &[Attribute]) -> SerdeEnumAttrs {
+    let mut out = SerdeEnumAttrs::default();
+    for_each_serde_meta(attrs, |meta| {
+        let span = meta.path.get_ident().map_or_else(Span::call_site, Ident::span);
+        if meta.path.is_ident("untagged") {
+            out.untagged = Some(span);
+        } else if meta.path.is_ident("tag") {
+            out.tag = Some((meta.value()?.parse::<LitStr>()?.value(), span));
+        } else if meta.path.is_ident("content") {
+            out.content = Some((meta.value()?.parse::<LitStr>()?.value(), span));
+        } else if meta.path.is_ident("rename_all") {
+            out.rename_all = serde_str_value(meta)?;
+        } else if meta.path.is_ident("rename_all_fields") {
+            out.rename_all_fields = serde_str_value(meta)?;
+        } else {
+            return Ok(false);
+        }
+        Ok(true)
+    });
+    out
+}
+
+fn parse_variant_attrs(attrs: &[Attribute]) -> SerdeVariantAttrs {
+    let mut out = SerdeVariantAttrs::default();
+    for_each_serde_meta(attrs, |meta| {
+        if meta.path.is_ident("rename") {
+            out.rename = serde_str_value(meta)?;
+        } else if meta.path.is_ident("rename_all") {
+            out.rename_all = serde_str_value(meta)?;
+        } else if meta.path.is_ident("other") {
+            out.other = true;
+        } else if meta.path.is_ident("skip") || meta.path.is_ident("skip_deserializing") {
+            out.skip = true;
+        } else {
+            return Ok(false);
+        }
+        Ok(true)
+    });
+    out
+}
+
+/// A struct's `#[serde(rename_all = "...")]` rule, if any.
+fn parse_struct_attrs(attrs: &[Attribute]) -> Option<String> {
+    let mut rename_all = None;
+    for_each_serde_meta(attrs, |meta| {
+        if !meta.path.is_ident("rename_all") {
+            return Ok(false);
+        }
+        rename_all = serde_str_value(meta)?;
+        Ok(true)
+    });
+    rename_all
+}
+
+/// The key a named field has in JSON: its `#[serde(rename = "...")]`, else
+/// its Rust name under the container's `rename_all` rule.
+fn field_wire_name(field: &syn::Field, rule: Option<&str>) -> String {
+    let mut rename = None;
+    for_each_serde_meta(&field.attrs, |meta| {
+        if !meta.path.is_ident("rename") {
+            return Ok(false);
+        }
+        rename = serde_str_value(meta)?;
+        Ok(true)
+    });
+    let ident = field.ident.as_ref().unwrap();
+    match (rename, rule) {
+        (Some(rename), _) => rename,
+        (None, Some(rule)) => rename_field(&ident.unraw().to_string(), rule, ident.span()),
+        (None, None) => ident.unraw().to_string(),
+    }
+}
+
+/// `#[schema(variant_description = "...")]` on an enum variant.
+fn variant_description(variant: &syn::Variant) -> Option<String> {
+    let mut description = None;
+    for attr in variant.attrs.iter().filter(|a| a.path().is_ident("schema")) {
+        let parsed = attr.parse_nested_meta(|meta| {
+            if !meta.path.is_ident("variant_description") {
+                return Err(meta.error(
+                    "unknown schema attribute on a variant; expected `variant_description = \"...\"`",
+                ));
+            }
+            if description.is_some() {
+                return Err(meta.error("duplicate `variant_description` in schema attribute"));
+            }
+            description = Some(meta.value()?.parse::<LitStr>()?.value());
+            Ok(())
+        });
+        if let Err(e) = parsed {
+            abort!(e.span(), "{}", e);
+        }
+    }
+    description
+}
+
+/// Apply a serde `rename_all` rule to a (PascalCase) variant name.
+fn rename_variant(name: &str, rule: &str, span: Span) -> String {
+    let snake = || {
+        let mut out = String::new();
+        for (i, ch) in name.char_indices() {
+            if ch.is_uppercase() && i > 0 {
+                out.push('_');
+            }
+            out.push(ch.to_ascii_lowercase());
+        }
+        out
+    };
+    match rule {
+        "lowercase" => name.to_ascii_lowercase(),
+        "UPPERCASE" => name.to_ascii_uppercase(),
+        "PascalCase" => name.to_string(),
+        "camelCase" => {
+            let mut chars = name.chars();
+            chars
+                .next()
+                .map(|first| first.to_lowercase().chain(chars).collect())
+                .unwrap_or_default()
+        }
+        "snake_case" => snake(),
+        "SCREAMING_SNAKE_CASE" => snake().to_ascii_uppercase(),
+        "kebab-case" => snake().replace('_', "-"),
+        "SCREAMING-KEBAB-CASE" => snake().replace('_', "-").to_ascii_uppercase(),
+        other => abort!(span, "unknown rename rule `rename_all = {:?}`", other),
+    }
+}
+
+/// Apply a serde `rename_all` rule to a (snake_case) field name.
+fn rename_field(name: &str, rule: &str, span: Span) -> String {
+    let capitalized = |word: &str| {
+        let mut chars = word.chars();
+        chars
+            .next()
+            .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+            .unwrap_or_default()
+    };
+    match rule {
+        "lowercase" | "snake_case" => name.to_string(),
+        "UPPERCASE" | "SCREAMING_SNAKE_CASE" => name.to_ascii_uppercase(),
+        "PascalCase" => name.split('_').map(capitalized).collect(),
+        "camelCase" => {
+            let pascal: String = name.split('_').map(capitalized).collect();
+            rename_variant(&pascal, "camelCase", span)
+        }
+        "kebab-case" => name.replace('_', "-"),
+        "SCREAMING-KEBAB-CASE" => name.replace('_', "-").to_ascii_uppercase(),
+        other => abort!(span, "unknown rename rule `rename_all = {:?}`", other),
+    }
+}
+
+/// Decide the representation, rejecting the attribute combinations serde
+/// itself rejects (with serde's wording).
+fn enum_repr(attrs: &SerdeEnumAttrs) -> Repr {
+    match (&attrs.untagged, &attrs.tag, &attrs.content) {
+        (None, None, None) => Repr::External,
+        (None, Some((tag, _)), None) => Repr::Internal(tag.clone()),
+        (None, Some((tag, _)), Some((content, _))) => Repr::Adjacent(tag.clone(), content.clone()),
+        (Some(_), None, None) => Repr::Untagged,
+        (Some(span), Some(_), None) => {
+            abort!(*span, "enum cannot be both untagged and internally tagged")
+        }
+        (Some(span), None, Some(_)) => {
+            abort!(*span, "untagged enum cannot have #[serde(content = \"...\")]")
+        }
+        (Some(span), Some(_), Some(_)) => abort!(
+            *span,
+            "untagged enum cannot have #[serde(tag = \"...\", content = \"...\")]"
+        ),
+        (None, None, Some((_, span))) => abort!(
+            *span,
+            "#[serde(tag = \"...\", content = \"...\")] must be used together"
+        ),
+    }
+}
+
+/// Schema expression for a variant's payload; `None` for unit variants.
+/// `rule` renames the fields of a struct variant.
+fn variant_payload(
+    fields: &Fields,
+    rule: Option<&str>,
+    crate_path: &proc_macro2::TokenStream,
+) -> Option<proc_macro2::TokenStream> {
+    match fields {
+        Fields::Unit => None,
+        Fields::Unnamed(f) if f.unnamed.len() == 1 => Some(member_schema(&f.unnamed[0], crate_path)),
+        Fields::Unnamed(f) => {
+            let schemas = f.unnamed.iter().map(|field| member_schema(field, crate_path));
+            let count = f.unnamed.len();
+            Some(quote! {
+                #crate_path::serde_json::json!({
+                    "type": "array",
+                    "prefixItems": [#((#schemas)),*],
+                    "minItems": #count,
+                    "maxItems": #count
+                })
+            })
+        }
+        Fields::Named(f) => Some(object_schema(f, None, rule, crate_path)),
+    }
+}
+
+/// Object schema for named fields, optionally with a leading tag property.
+fn object_schema(
+    fields: &FieldsNamed,
+    tag: Option<(&str, proc_macro2::TokenStream)>,
+    rule: Option<&str>,
+    crate_path: &proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let mut names = Vec::new();
+    let mut types = Vec::new();
+    let mut required = Vec::new();
+    if let Some((tag, _)) = &tag {
+        required.push(tag.to_string());
+    }
+    for field in &fields.named {
+        let name = field_wire_name(field, rule);
+        if !is_optional_field(&field.ty) {
+            required.push(name.clone());
+        }
+        names.push(name);
+        types.push(member_schema(field, crate_path));
+    }
+    let tag_insert = tag.map(|(tag, schema)| quote! { properties.insert(#tag.to_string(), #schema); });
+    quote! {{
+        let mut properties = #crate_path::serde_json::Map::new();
+        #tag_insert
+        #(properties.insert(#names.to_string(), #types);)*
+        #crate_path::serde_json::json!({
+            "type": "object",
+            "properties": properties,
+            "required": [#(#required),*]
+        })
+    }}
+}
+
+/// `branch` with the variant's `variant_description`, if any, as its
+/// `description`.
+fn described_branch(variant: &syn::Variant, branch: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    let crate_path = get_crate_path();
+    match variant_description(variant) {
+        Some(description) => quote! {{
+            let mut branch = #branch;
+            branch["description"] = #crate_path::serde_json::Value::from(#description);
+            branch
+        }},
+        None => branch,
+    }
+}
+
+fn generate_enum_schema(input: &DeriveInput, data: &DataEnum) -> TokenStream {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let crate_path = get_crate_path();
+
+    let attrs = parse_enum_attrs(&input.attrs);
+    let repr = enum_repr(&attrs);
+
+    let variants: Vec<_> = data
+        .variants
+        .iter()
+        .map(|v| {
+            let mut a = parse_variant_attrs(&v.attrs);
+            // A struct variant's fields follow its own `rename_all`, else the
+            // enum's `rename_all_fields`.
+            a.rename_all = a.rename_all.or_else(|| attrs.rename_all_fields.clone());
+            (v, a)
+        })
+        .filter(|(_, a)| !a.skip)
+        .collect();
+
+    // `#[serde(other)]` rules, in serde's order of checks.
+    for (i, (variant, a)) in variants.iter().enumerate() {
+        if !a.other {
+            continue;
+        }
+        if matches!(repr, Repr::Untagged) {
+            abort!(variant, "#[serde(other)] cannot appear on untagged enum");
+        }
+        if !matches!(variant.fields, Fields::Unit) {
+            abort!(variant, "#[serde(other)] must be on a unit variant");
+        }
+        if i + 1 < variants.len() {
+            abort!(variant, "#[serde(other)] must be on the last variant");
+        }
+    }
+    if let Repr::Internal(_) = repr {
+        for (variant, _) in &variants {
+            if matches!(&variant.fields, Fields::Unnamed(f) if f.unnamed.len() != 1) {
+                abort!(variant, "#[serde(tag = \"...\")] cannot be used with tuple variants");
+            }
+        }
+    }
+    let has_other = variants.iter().any(|(_, a)| a.other);
+
+    let wire_name = |variant: &syn::Variant, a: &SerdeVariantAttrs| match (&a.rename, &attrs.rename_all) {
+        (Some(rename), _) => rename.clone(),
+        (None, Some(rule)) => rename_variant(&variant.ident.to_string(), rule, variant.ident.span()),
+        (None, None) => variant.ident.to_string(),
+    };
+    // The tag schema of a variant: its name, or any string for `other`.
+    let tag_schema = |wire: &str, other: bool| {
+        if other {
+            quote! { #crate_path::serde_json::json!({ "type": "string" }) }
+        } else {
+            quote! { #crate_path::serde_json::json!({ "const": #wire }) }
+        }
+    };
+
+    let mut branches = Vec::new();
+    match &repr {
+        Repr::External => {
+            let units: Vec<_> = variants
+                .iter()
+                .filter(|(v, a)| matches!(v.fields, Fields::Unit) && !a.other)
+                .collect();
+            let names: Vec<String> = units.iter().map(|(v, a)| wire_name(v, a)).collect();
+            let descriptions: Vec<Option<String>> =
+                units.iter().map(|(v, _)| variant_description(v)).collect();
+            // Parallel to `enum` (or `examples`), one entry per value.
+            let described = descriptions.iter().any(Option::is_some).then(|| {
+                let descriptions = descriptions.iter().map(|d| d.clone().unwrap_or_default());
+                quote! { "x-enum-descriptions": [#(#descriptions),*], }
+            });
+            if has_other {
+                // Unknown names deserialize into the `other` variant, so any
+                // string is valid; the known names stay as examples.
+                branches.push(quote! {
+                    #crate_path::serde_json::json!({ "type": "string", "examples": [#(#names),*], #described })
+                });
+            } else if !names.is_empty() {
+                branches.push(quote! {
+                    #crate_path::serde_json::json!({ "type": "string", "enum": [#(#names),*], #described })
+                });
+            }
+            for (variant, a) in &variants {
+                let Some(payload) = variant_payload(&variant.fields, a.rename_all.as_deref(), &crate_path) else {
+                    continue;
+                };
+                let wire = wire_name(variant, a);
+                branches.push(described_branch(
+                    variant,
+                    quote! {
+                        #crate_path::serde_json::json!({
+                            "type": "object",
+                            "properties": { #wire: (#payload) },
+                            "required": [#wire],
+                            "additionalProperties": false
+                        })
+                    },
+                ));
+            }
+        }
+        Repr::Internal(tag) => {
+            for (variant, a) in &variants {
+                let tag_value = tag_schema(&wire_name(variant, a), a.other);
+                let branch = match &variant.fields {
+                    Fields::Named(f) => object_schema(f, Some((tag, tag_value)), a.rename_all.as_deref(), &crate_path),
+                    Fields::Unit => quote! {
+                        #crate_path::serde_json::json!({
+                            "type": "object",
+                            "properties": { #tag: (#tag_value) },
+                            "required": [#tag]
+                        })
+                    },
+                    Fields::Unnamed(f) => {
+                        let inner = member_schema(&f.unnamed[0], &crate_path);
+                        quote! {
+                            #crate_path::serde_json::json!({
+                                "allOf": [
+                                    {
+                                        "type": "object",
+                                        "properties": { #tag: (#tag_value) },
+                                        "required": [#tag]
+                                    },
+                                    (#inner)
+                                ]
+                            })
+                        }
+                    }
+                };
+                branches.push(described_branch(variant, branch));
+            }
+        }
+        Repr::Adjacent(tag, content) => {
+            for (variant, a) in &variants {
+                let tag_value = tag_schema(&wire_name(variant, a), a.other);
+                let branch = match variant_payload(&variant.fields, a.rename_all.as_deref(), &crate_path) {
+                    Some(payload) => quote! {
+                        #crate_path::serde_json::json!({
+                            "type": "object",
+                            "properties": { #tag: (#tag_value), #content: (#payload) },
+                            "required": [#tag, #content]
+                        })
+                    },
+                    None => quote! {
+                        #crate_path::serde_json::json!({
+                            "type": "object",
+                            "properties": { #tag: (#tag_value) },
+                            "required": [#tag]
+                        })
+                    },
+                };
+                branches.push(described_branch(variant, branch));
+            }
+        }
+        Repr::Untagged => {
+            for (variant, a) in &variants {
+                let branch = variant_payload(&variant.fields, a.rename_all.as_deref(), &crate_path)
+                    .unwrap_or_else(|| quote! { #crate_path::serde_json::json!({ "type": "null" }) });
+                branches.push(described_branch(variant, branch));
+            }
+        }
+    }
+
+    // Untagged branches may overlap, as may a tagged `other` branch with the
+    // known ones, so only disjoint tagged variants use `oneOf`.
+    let combinator = if matches!(repr, Repr::Untagged) || has_other {
+        "anyOf"
+    } else {
+        "oneOf"
+    };
+    let body = match branches.len() {
+        0 => quote! { #crate_path::serde_json::json!({ "not": {} }) },
+        1 => branches.pop().unwrap(),
+        _ => quote! { #crate_path::serde_json::json!({ #combinator: [#((#branches)),*] }) },
+    };
+
+    TokenStream::from(quote! {
+        impl #impl_generics #crate_path::ToolSchema for #name #ty_generics #where_clause {
+            fn schema() -> #crate_path::serde_json::Value {
+                static SCHEMA: #crate_path::once_cell::sync::Lazy<#crate_path::serde_json::Value> = #crate_path::once_cell::sync::Lazy::new(|| {
+                    #body
+                });
+                SCHEMA.clone()
+            }
+        }
+    })
+}
+
+/// Path to `tools_core` from the calling crate: the crate itself, or
+/// the `__core` re-export when only the `tools-rs` facade is a
+/// dependency.
 fn get_crate_path() -> proc_macro2::TokenStream {
     match crate_name("tools_core") {
         Ok(FoundCrate::Itself) => quote!(crate),
@@ -148,7 +677,153 @@ fn get_crate_path() -> proc_macro2::TokenStream {
             let ident = proc_macro2::Ident::new(&name, proc_macro2::Span::call_site());
             quote!(#ident)
         }
-        Err(_) => quote!(::tools_core),
+        Err(_) => match crate_name("tools-rs") {
+            Ok(FoundCrate::Name(name)) => {
+                let ident = proc_macro2::Ident::new(&name, proc_macro2::Span::call_site());
+                quote!(::#ident::__core)
+            }
+            _ => quote!(::tools_core),
+        },
+    }
+}
+
+/// Fields that may be left out of the JSON object.
+fn is_optional_field(ty: &Type) -> bool {
+    is_option_type(ty) || is_patch_type(ty)
+}
+
+/// Schema expression for a derived type's field, honouring
+/// `#[schema(as = "Type")]`: the field is described by `Type`'s schema,
+/// for fields whose serde representation differs from their Rust type
+/// (`#[serde(with = ...)]`, hand-written `Deserialize` impls). The field's
+/// doc comment becomes the schema's `description`.
+fn member_schema(field: &syn::Field, crate_path: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    let mut proxy: Option<Type> = None;
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("schema")) {
+        let parsed = attr.parse_nested_meta(|meta| {
+            if !meta.path.is_ident("as") {
+                return Err(meta.error("unknown schema attribute; expected `as = \"Type\"`"));
+            }
+            if proxy.is_some() {
+                return Err(meta.error("duplicate `as` in schema attribute"));
+            }
+            proxy = Some(meta.value()?.parse::<LitStr>()?.parse::<Type>()?);
+            Ok(())
+        });
+        if let Err(e) = parsed {
+            abort!(e.span(), "{}", e);
+        }
+    }
+    let schema = match proxy {
+        Some(ty) => quote! { <#ty as #crate_path::ToolSchema>::schema() },
+        None => field_schema(&field.ty, crate_path),
+    };
+    let doc = docs(&field.attrs);
+    let doc = doc.trim();
+    if doc.is_empty() {
+        schema
+    } else {
+        quote! { #crate_path::schema::fields::describe(#schema, #doc) }
+    }
+}
+
+/// Schema expression for a struct field.
+fn field_schema(ty: &Type, crate_path: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    let schema_fn = field_schema_fn(ty, crate_path);
+    quote! { #schema_fn() }
+}
+
+/// The `ToolSchema::schema` function describing a struct field.
+/// `Option<Option<T>>` collapses to the schema of `Option<T>`: absence and
+/// `null` are told apart by the key being present, not by the schema.
+fn field_schema_fn(ty: &Type, crate_path: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    let ty = option_inner(ty).filter(|inner| is_option_type(inner)).unwrap_or(ty);
+    quote! { <#ty as #crate_path::ToolSchema>::schema }
+}
+
+/// `T` for a type spelled `Option<T>` (any path accepted by
+/// [`is_option_type`]).
+fn option_inner(ty: &Type) -> Option<&Type> {
+    if !is_option_type(ty) {
+        return None;
+    }
+    single_generic_arg(ty)
+}
+
+fn single_generic_arg(ty: &Type) -> Option<&Type> {
+    let Type::Path(TypePath { path, .. }) = ty else {
+        return None;
+    };
+    let syn::PathArguments::AngleBracketed(args) = &path.segments.last()?.arguments else {
+        return None;
+    };
+    match args.args.first() {
+        Some(syn::GenericArgument::Type(inner)) if args.args.len() == 1 => Some(inner),
+        _ => None,
+    }
+}
+
+/// `Patch<T>`, matched by name like `Option` (`Patch`, `tools_rs::Patch`,
+/// `tools_core::patch::Patch`, ...).
+fn is_patch_type(ty: &Type) -> bool {
+    let Type::Path(TypePath { qself: None, path }) = ty else {
+        return false;
+    };
+    path.segments.last().is_some_and(|s| s.ident == "Patch") && single_generic_arg(ty).is_some()
+}
+
+/// `Result<T, E>` spelled with both arguments, so aliases like
+/// `anyhow::Result<T>` keep their plain serde encoding.
+fn is_result_type(ty: &Type) -> bool {
+    let Type::Path(TypePath { qself: None, path }) = ty else {
+        return false;
+    };
+    let Some(last) = path.segments.last() else {
+        return false;
+    };
+    match &last.arguments {
+        syn::PathArguments::AngleBracketed(args) => last.ident == "Result" && args.args.len() == 2,
+        _ => false,
+    }
+}
+
+/// The `Ok` type of a `Result<T, ToolError>` (any path ending in
+/// `ToolError`): a tool whose `Err` fails the call instead of being a
+/// domain error.
+fn fallible_ok_type(ty: &Type) -> Option<&Type> {
+    let Type::Path(TypePath { qself: None, path }) = ty else {
+        return None;
+    };
+    let last = path.segments.last()?;
+    let syn::PathArguments::AngleBracketed(args) = &last.arguments else {
+        return None;
+    };
+    if last.ident != "Result" || args.args.len() != 2 {
+        return None;
+    }
+    let (syn::GenericArgument::Type(ok), syn::GenericArgument::Type(Type::Path(err))) =
+        (&args.args[0], &args.args[1])
+    else {
+        return None;
+    };
+    err.path
+        .segments
+        .last()
+        .is_some_and(|s| s.ident == "ToolError")
+        .then_some(ok)
+}
+
+/// Field attributes the `#[tool]` wrapper needs so that an absent key is
+/// representable for three-state parameters, both ways.
+fn wrapper_field_attrs(ty: &Type, crate_path: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    if is_patch_type(ty) {
+        let skip = format!("{}::patch::Patch::is_missing", crate_path).replace(' ', "");
+        quote! { #[serde(default, skip_serializing_if = #skip)] }
+    } else if option_inner(ty).is_some_and(is_option_type) {
+        let with = format!("{}::patch::double_option", crate_path).replace(' ', "");
+        quote! { #[serde(default, deserialize_with = #with, skip_serializing_if = "Option::is_none")] }
+    } else {
+        quote! {}
     }
 }
 
@@ -220,14 +895,64 @@ fn docs(attrs: &[Attribute]) -> String {
 #[proc_macro_attribute]
 pub fn tool(attr: TokenStream, item: TokenStream) -> TokenStream {
     // ───────── Parse #[tool(key = value, ...)] attributes ─────────
-    let meta_json = parse_tool_attrs(attr);
+    let (meta_json, schema_opts, description, name) = parse_tool_attrs(attr);
     let meta_lit = LitStr::new(&meta_json, Span::call_site());
 
     // ───────── Parse the user function ─────────
-    let func: ItemFn = parse_macro_input!(item);
+    let mut func: ItemFn = parse_macro_input!(item);
+    // `#[tool(inject)]` marks parameters filled from `#[tool_resource]`s.
+    // Strip the marker so the emitted fn compiles, and the parameters' doc
+    // comments, which rustc rejects there; they describe the properties.
+    let (injected, param_docs): (Vec<bool>, Vec<String>) = func
+        .sig
+        .inputs
+        .iter_mut()
+        .map(|arg| match arg {
+            FnArg::Typed(pat_type) => {
+                let doc = docs(&pat_type.attrs).trim().to_string();
+                pat_type.attrs.retain(|a| !a.path().is_ident("doc"));
+                let before = pat_type.attrs.len();
+                pat_type.attrs.retain(|a| !is_inject_attr(a));
+                (pat_type.attrs.len() != before, doc)
+            }
+            FnArg::Receiver(_) => (false, String::new()),
+        })
+        .unzip();
     let fn_name = &func.sig.ident;
-    let fn_name_str = fn_name.to_string();
+    // `#[tool(name = "...")]` replaces the function name as the tool's
+    // registered name.
+    let tool_name = name.map_or_else(|| fn_name.to_string(), |name| name.value());
     let doc_lit = LitStr::new(&docs(&func.attrs), Span::call_site());
+    // `description_from` / `description_const` take precedence over the
+    // doc comment. A file is read here, so the manifest gets its text, and
+    // also `include_str!`ed so editing it triggers a rebuild.
+    let (doc_expr, manifest_doc, include_description) = match &description {
+        None => (quote!(#doc_lit), doc_lit.value(), quote!()),
+        Some(Description::File(path)) => {
+            let text = description_file(path);
+            let text_lit = LitStr::new(&text, path.span());
+            let path_lit = LitStr::new(&resolve_description_path(path).display().to_string(), path.span());
+            (
+                quote!(#text_lit),
+                text,
+                quote!(const _: &str = ::core::include_str!(#path_lit);),
+            )
+        }
+        // The const's value isn't known here; the manifest keeps the doc
+        // comment.
+        Some(Description::Const(path)) => (quote!(#path), doc_lit.value(), quote!()),
+        Some(Description::Literal(text)) => (quote!(#text), text.value(), quote!()),
+    };
+    // A const's value isn't known here. Warnings only surface on nightly;
+    // on stable `Strictness::RequireDescriptions` catches the tool at
+    // registration instead.
+    let is_const = matches!(description, Some(Description::Const(_)));
+    if !is_const && manifest_doc.trim().is_empty() {
+        emit_warning!(
+            fn_name, "tool `{}` has an empty description", tool_name;
+            note = "models choose tools by their description; add a doc comment or `#[tool(description = \"...\")]`"
+        );
+    }
 
     // ───────── Inputs → wrapper struct fields ─────────
     // Detect reserved `ctx` first parameter.
@@ -235,12 +960,13 @@ pub fn tool(attr: TokenStream, item: TokenStream) -> TokenStream {
         .sig
         .inputs
         .iter()
-        .map(|arg| match arg {
+        .zip(injected.into_iter().zip(param_docs))
+        .map(|(arg, (inject, doc))| match arg {
             FnArg::Typed(PatType { pat, ty, .. }) => {
                 let Pat::Ident(PatIdent { ident, .. }) = &**pat else {
                     abort!(pat, "`#[tool]` supports only identifier patterns");
                 };
-                (ident.clone(), (**ty).clone())
+                (ident.clone(), (**ty).clone(), inject, doc)
             }
             _ => abort!(arg, "`#[tool]` may not be used on `self` methods"),
         })
@@ -251,7 +977,7 @@ pub fn tool(attr: TokenStream, item: TokenStream) -> TokenStream {
     // so that field access and method calls work via Deref.
     let (ctx_inner_ty, param_pairs) = if all_params
         .first()
-        .map_or(false, |(ident, _)| ident == "ctx")
+        .is_some_and(|(ident, _, inject, _)| ident == "ctx" && !inject)
     {
         let ctx_ty = &all_params[0].1;
         // Reject `ctx: Arc<T>` — we wrap in Arc internally, so the user
@@ -267,23 +993,199 @@ pub fn tool(attr: TokenStream, item: TokenStream) -> TokenStream {
         (None, all_params)
     };
 
-    let (idents, types): (Vec<_>, Vec<_>) = param_pairs.into_iter().unzip();
+    let crate_path = get_crate_path();
+    let serde_crate = format!("{}::serde", crate_path).replace(' ', "");
+
+    // Arguments in declaration order: wrapper fields for JSON parameters,
+    // resource lookups for injected ones.
+    let mut idents = Vec::new();
+    let mut types = Vec::new();
+    let mut param_docs = Vec::new();
+    let mut call_args = Vec::new();
+    let mut inject_bindings = Vec::new();
+    for (ident, ty, inject, doc) in param_pairs {
+        if !inject {
+            call_args.push(quote!(arg.#ident));
+            idents.push(ident);
+            types.push(ty);
+            param_docs.push(doc);
+            continue;
+        }
+        let Type::Reference(syn::TypeReference {
+            mutability: None,
+            elem,
+            ..
+        }) = &ty
+        else {
+            abort!(
+                ty,
+                "`#[tool(inject)]` parameters must be shared references, e.g. `client: &Client`"
+            );
+        };
+        let local = Ident::new(&format!("__inject_{ident}"), Span::call_site());
+        let resource_name = LitStr::new(&quote!(#elem).to_string(), Span::call_site());
+        inject_bindings.push(quote! {
+            let #local: &'static #elem = #crate_path::resources::get::<#elem>().ok_or(
+                #crate_path::ToolError::MissingResource {
+                    tool: ::std::borrow::Cow::Borrowed(#tool_name),
+                    resource: #resource_name,
+                },
+            )?;
+        });
+        call_args.push(quote!(#local));
+    }
+    let field_attrs: Vec<_> = types
+        .iter()
+        .map(|ty| wrapper_field_attrs(ty, &crate_path))
+        .collect();
+
+    for param in &schema_opts.opaque {
+        if !idents.contains(param) {
+            abort!(param, "`{}` is not a parameter of `{}`", param, fn_name);
+        }
+    }
+    // The wrapper's schema is a field table handed to the shared
+    // `object_schema` builder, so each tool expands to data rather than
+    // its own `json!` construction. Opaque parameters get a placeholder
+    // schema instead of requiring `ToolSchema`; deserialization still goes
+    // through serde.
+    let field_descs: Vec<_> = idents
+        .iter()
+        .zip(&types)
+        .zip(&param_docs)
+        .map(|((ident, ty), doc)| {
+            let name = LitStr::new(&ident.to_string(), Span::call_site());
+            let schema = if schema_opts.is_opaque(ident) {
+                quote! { #crate_path::schema::fields::opaque::<#ty> }
+            } else {
+                field_schema_fn(ty, &crate_path)
+            };
+            let required = !is_optional_field(ty);
+            let description = if doc.is_empty() {
+                quote!(None)
+            } else {
+                quote!(Some(#doc))
+            };
+            quote! {
+                #crate_path::schema::FieldDesc {
+                    name: #name,
+                    schema: #schema,
+                    required: #required,
+                    description: #description,
+                }
+            }
+        })
+        .collect();
 
     // ───────── Generated helper idents ─────────
     let wrapper_ident = Ident::new(&format!("__TOOL_INPUT_{fn_name}"), Span::call_site());
-    let schema_fn = Ident::new(&format!("__SCHEMA_FOR_{fn_name}"), Span::call_site());
-    let crate_path = get_crate_path();
+    let vis = func.vis.clone();
+
+    // ───────── Typed handle ─────────
+    // `ADD_TOOL: ToolRef<input, i32>` for `async fn add(..) -> i32`, with the
+    // input built from a tuple of the JSON parameters. Skipped when the
+    // output type can't be named.
+    // A fallible tool's handle decodes the `Ok` type.
+    let fallible_ok = match &func.sig.output {
+        syn::ReturnType::Type(_, ty) => fallible_ok_type(ty).cloned(),
+        syn::ReturnType::Default => None,
+    };
+    let output_ty = match (&fallible_ok, &func.sig.output) {
+        (Some(ok), _) => Some(ok.clone()),
+        (None, syn::ReturnType::Default) => Some(syn::parse_quote!(())),
+        (None, syn::ReturnType::Type(_, ty)) if !matches!(**ty, Type::ImplTrait(_)) => Some((**ty).clone()),
+        (None, syn::ReturnType::Type(..)) => None,
+    };
+    let tool_ref = match output_ty {
+        Some(output_ty) if func.sig.asyncness.is_some() => {
+            let const_ident = Ident::new(&format!("{}_TOOL", fn_name.to_string().to_uppercase()), fn_name.span());
+            let const_doc = LitStr::new(
+                &format!("Typed handle to the `{tool_name}` tool, for `ToolCollection::invoke`."),
+                Span::call_site(),
+            );
+            quote! {
+                impl ::core::convert::From<( #( #types, )* )> for #wrapper_ident {
+                    fn from(( #( #idents, )* ): ( #( #types, )* )) -> Self {
+                        Self { #( #idents ),* }
+                    }
+                }
+
+                #[doc = #const_doc]
+                #vis const #const_ident: #crate_path::ToolRef<#wrapper_ident, #output_ty> =
+                    #crate_path::ToolRef::new(#tool_name);
+            }
+        }
+        _ => quote! {},
+    };
+
+    // ───────── Compile-time manifest ─────────
+    manifest::record(manifest::Entry {
+        name: &tool_name,
+        doc: &manifest_doc,
+        schema_fn: format!("<{wrapper_ident} as ToolSchema>::schema"),
+        params: idents
+            .iter()
+            .zip(&types)
+            .map(|(ident, ty)| (ident.to_string(), quote!(#ty).to_string()))
+            .collect(),
+        meta_json: &meta_json,
+    });
+
+    // ───────── Domain errors ─────────
+    // A `Result<T, E>` output declares `oneOf [T, {"error": E}]` as its
+    // result and reports `Err` as a domain error rather than `{"Err": ..}`.
+    // `Result<T, ToolError>` instead declares `T` and fails the call.
+    let returns_result = match &func.sig.output {
+        syn::ReturnType::Type(_, ty) => is_result_type(ty),
+        syn::ReturnType::Default => false,
+    };
+    let returns_schema = match (&fallible_ok, &func.sig.output) {
+        (Some(ok), _) => {
+            quote!(::core::option::Option::Some(<#ok as #crate_path::ToolSchema>::schema))
+        }
+        (None, syn::ReturnType::Type(_, ty)) if returns_result => {
+            quote!(::core::option::Option::Some(<#ty as #crate_path::ToolSchema>::schema))
+        }
+        _ => quote!(::core::option::Option::None),
+    };
+    let encode_out = if fallible_ok.is_some() {
+        quote! {
+            out.and_then(|out| {
+                #crate_path::serde_json::to_value(out)
+                    .map_err(|e| #crate_path::ToolError::Runtime(e.to_string()))
+            })
+        }
+    } else if returns_result {
+        quote! {
+            #crate_path::serde_json::to_value(out)
+                .map_err(|e| #crate_path::ToolError::Runtime(e.to_string()))
+                .and_then(#crate_path::domain::from_result)
+        }
+    } else {
+        quote! {
+            #crate_path::serde_json::to_value(out)
+                .map_err(|e| #crate_path::ToolError::Runtime(e.to_string()))
+        }
+    };
 
     // ───────── Context-dependent codegen ─────────
-    let (closure_body, needs_ctx_lit, ctx_type_id_expr, ctx_type_name_lit) =
+    let decode_body = quote! {
+        |v| {
+            let arg: #wrapper_ident = #crate_path::serde_json::from_value(v)
+                .map_err(#crate_path::DeserializationError::from)?;
+            ::std::result::Result::Ok(::std::boxed::Box::new(arg))
+        }
+    };
+
+    let (run_body, needs_ctx_lit, ctx_type_id_expr, ctx_type_name_lit) =
         if let Some(ref inner_ty) = ctx_inner_ty {
             let type_name_str = quote!(#inner_ty).to_string();
             let type_name_lit = LitStr::new(&type_name_str, Span::call_site());
             (
                 quote! {
-                    |v, ctx_opt| ::std::boxed::Box::pin(async move {
+                    |input, ctx_opt| ::std::boxed::Box::pin(async move {
                         let ctx_any = ctx_opt.ok_or_else(|| #crate_path::ToolError::MissingCtx {
-                            tool: #fn_name_str,
+                            tool: ::std::borrow::Cow::Borrowed(#tool_name),
                         })?;
                         let ctx: ::std::sync::Arc<#inner_ty> =
                             ctx_any.downcast::<#inner_ty>().map_err(|_| {
@@ -291,12 +1193,10 @@ pub fn tool(attr: TokenStream, item: TokenStream) -> TokenStream {
                                     "context downcast failed".to_string(),
                                 )
                             })?;
-                        let arg: #wrapper_ident =
-                            ::serde_json::from_value(v)
-                                .map_err(#crate_path::DeserializationError::from)?;
-                        let out = #fn_name(ctx, #( arg.#idents ),* ).await;
-                        ::serde_json::to_value(out)
-                            .map_err(|e| #crate_path::ToolError::Runtime(e.to_string()))
+                        let arg: #wrapper_ident = #crate_path::downcast_input(input)?;
+                        #( #inject_bindings )*
+                        let out = #fn_name(ctx, #( #call_args ),* ).await;
+                        #encode_out
                     })
                 },
                 quote!(true),
@@ -307,13 +1207,11 @@ pub fn tool(attr: TokenStream, item: TokenStream) -> TokenStream {
             let empty_name = LitStr::new("", Span::call_site());
             (
                 quote! {
-                    |v, _ctx| ::std::boxed::Box::pin(async move {
-                        let arg: #wrapper_ident =
-                            ::serde_json::from_value(v)
-                                .map_err(#crate_path::DeserializationError::from)?;
-                        let out = #fn_name( #( arg.#idents ),* ).await;
-                        ::serde_json::to_value(out)
-                            .map_err(|e| #crate_path::ToolError::Runtime(e.to_string()))
+                    |input, _ctx| ::std::boxed::Box::pin(async move {
+                        let arg: #wrapper_ident = #crate_path::downcast_input(input)?;
+                        #( #inject_bindings )*
+                        let out = #fn_name( #( #call_args ),* ).await;
+                        #encode_out
                     })
                 },
                 quote!(false),
@@ -326,10 +1224,8 @@ pub fn tool(attr: TokenStream, item: TokenStream) -> TokenStream {
     // User wrote `ctx: T`, emit `ctx: Arc<T>` so Deref covers .field / .method().
     let emitted_func = if let Some(ref inner_ty) = ctx_inner_ty {
         let mut func_out = func.clone();
-        if let Some(first_arg) = func_out.sig.inputs.first_mut() {
-            if let FnArg::Typed(pat_type) = first_arg {
-                pat_type.ty = Box::new(syn::parse_quote!(::std::sync::Arc<#inner_ty>));
-            }
+        if let Some(FnArg::Typed(pat_type)) = func_out.sig.inputs.first_mut() {
+            *pat_type.ty = syn::parse_quote!(::std::sync::Arc<#inner_ty>);
         }
         func_out
     } else {
@@ -340,36 +1236,166 @@ pub fn tool(attr: TokenStream, item: TokenStream) -> TokenStream {
     TokenStream::from(quote! {
         #emitted_func
 
+        #[doc(hidden)]
         #[allow(non_camel_case_types)]
-        #[derive(::serde::Deserialize, tools_macros::ToolSchema)]
-        struct #wrapper_ident { #( pub #idents : #types ),* }
+        #[derive(#crate_path::serde::Deserialize, #crate_path::serde::Serialize)]
+        #[serde(crate = #serde_crate)]
+        #vis struct #wrapper_ident { #( #field_attrs pub #idents : #types ),* }
+
+        #tool_ref
 
-        #[inline(always)]
-        fn #schema_fn<T: #crate_path::ToolSchema>() -> ::serde_json::Value {
-            T::schema()
+        #include_description
+
+        impl #crate_path::ToolSchema for #wrapper_ident {
+            fn schema() -> #crate_path::serde_json::Value {
+                #crate_path::schema::object_schema(&[ #( #field_descs ),* ])
+            }
         }
 
-        inventory::submit! {
+        #crate_path::inventory::submit! {
             #crate_path::ToolRegistration {
-                name: #fn_name_str,
-                doc: #doc_lit,
-                f: #closure_body,
-                param_schema: || #schema_fn::<#wrapper_ident>(),
+                name: #tool_name,
+                doc: #doc_expr,
+                decode: #decode_body,
+                decode_ref: #crate_path::decode_typed::<#wrapper_ident>,
+                run: #run_body,
+                param_schema: <#wrapper_ident as #crate_path::ToolSchema>::schema,
+                returns_schema: #returns_schema,
                 meta_json: #meta_lit,
                 needs_ctx: #needs_ctx_lit,
                 ctx_type_id: #ctx_type_id_expr,
                 ctx_type_name: #ctx_type_name_lit,
+                module_path: ::core::module_path!(),
             }
         }
     })
 }
 
+/// Schema opt-outs parsed from `#[tool(no_schema)]` and
+/// `#[tool(schema(param = "opaque"))]`. Neither ends up in the metadata.
+#[derive(Default)]
+struct SchemaOpts {
+    no_schema: bool,
+    opaque: Vec<Ident>,
+}
+
+impl SchemaOpts {
+    fn is_opaque(&self, param: &Ident) -> bool {
+        self.no_schema || self.opaque.contains(param)
+    }
+}
+
+fn parse_schema_opts(list: &syn::MetaList, opts: &mut SchemaOpts) {
+    let parsed = list.parse_nested_meta(|meta| {
+        let Some(param) = meta.path.get_ident().cloned() else {
+            return Err(meta.error("expected a parameter name"));
+        };
+        let mode: LitStr = meta.value()?.parse()?;
+        if mode.value() != "opaque" {
+            return Err(syn::Error::new(
+                mode.span(),
+                "the only supported schema override is `\"opaque\"`",
+            ));
+        }
+        if opts.opaque.contains(&param) {
+            return Err(meta.error(format!("duplicate schema override for `{param}`")));
+        }
+        opts.opaque.push(param);
+        Ok(())
+    });
+    if let Err(e) = parsed {
+        abort!(e.span(), "{}", e);
+    }
+}
+
+/// Where a tool's description comes from instead of its doc comment.
+enum Description {
+    /// `#[tool(description_from = "prompts/search.txt")]`, relative to the
+    /// crate root.
+    File(LitStr),
+    /// `#[tool(description_const = SEARCH_DESC)]`, a `&'static str` const.
+    Const(syn::Path),
+    /// `#[tool(description = "Searches the web")]`.
+    Literal(LitStr),
+}
+
+fn resolve_description_path(path: &LitStr) -> std::path::PathBuf {
+    let root = std::env::var_os("CARGO_MANIFEST_DIR").unwrap_or_default();
+    std::path::Path::new(&root).join(path.value())
+}
+
+/// The text of a `description_from` file, without surrounding whitespace.
+fn description_file(path: &LitStr) -> String {
+    let resolved = resolve_description_path(path);
+    match std::fs::read_to_string(&resolved) {
+        Ok(text) => text.trim().to_string(),
+        Err(e) => abort!(
+            path,
+            "cannot read `description_from` file `{}`: {}",
+            resolved.display(),
+            e
+        ),
+    }
+}
+
+fn parse_description(nv: &syn::MetaNameValue, key: &str) -> Description {
+    match (key, &nv.value) {
+        (
+            "description_from",
+            Expr::Lit(ExprLit {
+                lit: Lit::Str(path), ..
+            }),
+        ) => Description::File(path.clone()),
+        ("description_from", other) => {
+            abort!(other, "`description_from` takes a string literal path")
+        }
+        ("description_const", Expr::Path(path)) => Description::Const(path.path.clone()),
+        ("description_const", other) => {
+            abort!(other, "`description_const` takes the path of a `&'static str` const")
+        }
+        (
+            _,
+            Expr::Lit(ExprLit {
+                lit: Lit::Str(text), ..
+            }),
+        ) => Description::Literal(text.clone()),
+        (_, other) => abort!(other, "`description` takes a string literal"),
+    }
+}
+
+/// A `#[tool(name = "...")]` override: 1 to 64 ASCII letters, digits,
+/// `_`, `-` or `.`.
+fn parse_name(nv: &syn::MetaNameValue) -> LitStr {
+    let Expr::Lit(ExprLit {
+        lit: Lit::Str(name), ..
+    }) = &nv.value
+    else {
+        abort!(nv.value, "`name` takes a string literal");
+    };
+    let value = name.value();
+    let valid = (1..=64).contains(&value.len())
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+    if !valid {
+        abort!(
+            name,
+            "invalid tool name {:?}: use 1 to 64 ASCII letters, digits, `_`, `-` or `.`",
+            value
+        );
+    }
+    name.clone()
+}
+
 /// Parse `#[tool(key = value, key2 = value2, flag, ...)]` into a JSON
 /// object literal that gets stored on `ToolRegistration::meta_json`.
 /// Returns `"{}"` for empty attribute lists.
-fn parse_tool_attrs(attr: TokenStream) -> String {
+fn parse_tool_attrs(attr: TokenStream) -> (String, SchemaOpts, Option<Description>, Option<LitStr>) {
+    let mut opts = SchemaOpts::default();
+    let mut description = None;
+    let mut name = None;
     if attr.is_empty() {
-        return "{}".to_string();
+        return ("{}".to_string(), opts, description, name);
     }
 
     let parser = Punctuated::<Meta, Token![,]>::parse_terminated;
@@ -386,25 +1412,37 @@ fn parse_tool_attrs(attr: TokenStream) -> String {
                     Some(id) => id.to_string(),
                     None => abort!(nv.path, "attribute key must be a single identifier"),
                 };
-                if key == "name" || key == "description" {
-                    abort!(
-                        nv.path,
-                        "`{}` is reserved — set it via the function name and doc comment",
-                        key
-                    );
+                if key == "name" {
+                    if name.is_some() {
+                        abort!(nv.path, "duplicate attribute key `name`");
+                    }
+                    name = Some(parse_name(&nv));
+                    continue;
+                }
+                if key == "description" || key == "description_from" || key == "description_const" {
+                    if description.is_some() {
+                        abort!(
+                            nv.path,
+                            "only one of `description`, `description_from` and `description_const` may be given"
+                        );
+                    }
+                    description = Some(parse_description(&nv, &key));
+                    continue;
                 }
                 if map.contains_key(&key) {
                     abort!(nv.path, "duplicate attribute key `{}`", key);
                 }
                 map.insert(key, attr_expr_to_json(&nv.value));
             }
+            Meta::Path(p) if p.is_ident("no_schema") => opts.no_schema = true,
+            Meta::List(l) if l.path.is_ident("schema") => parse_schema_opts(&l, &mut opts),
             Meta::Path(p) => {
                 let key = match p.get_ident() {
                     Some(id) => id.to_string(),
                     None => abort!(p, "attribute key must be a single identifier"),
                 };
                 if key == "name" || key == "description" {
-                    abort!(p, "`{}` is reserved", key);
+                    abort!(p, "`{}` takes a string literal: `{} = \"...\"`", key, key);
                 }
                 if map.contains_key(&key) {
                     abort!(p, "duplicate attribute key `{}`", key);
@@ -418,7 +1456,7 @@ fn parse_tool_attrs(attr: TokenStream) -> String {
         }
     }
 
-    serde_json::Value::Object(map).to_string()
+    (serde_json::Value::Object(map).to_string(), opts, description, name)
 }
 
 fn attr_expr_to_json(e: &Expr) -> serde_json::Value {
@@ -472,6 +1510,18 @@ fn attr_expr_to_json(e: &Expr) -> serde_json::Value {
     }
 }
 
+/// Returns `true` for `#[tool(inject)]`. Any other `#[tool(...)]` on a
+/// parameter is an error.
+fn is_inject_attr(attr: &Attribute) -> bool {
+    if !attr.path().is_ident("tool") {
+        return false;
+    }
+    match attr.parse_args::<Ident>() {
+        Ok(ident) if ident == "inject" => true,
+        _ => abort!(attr, "the only parameter attribute is `#[tool(inject)]`"),
+    }
+}
+
 /// Returns `true` if the type looks like `Arc<_>` (or `std::sync::Arc<_>`).
 fn is_arc_type(ty: &Type) -> bool {
     if let Type::Path(TypePath { path, .. }) = ty {
@@ -483,6 +1533,79 @@ fn is_arc_type(ty: &Type) -> bool {
     false
 }
 
+// ============================================================================
+// TOOL RESOURCE ATTRIBUTE MACRO
+// ============================================================================
+
+/// Register a `static` (or `const`) as a resource that `#[tool]` functions
+/// receive through `#[tool(inject)] name: &Type` parameters.
+///
+/// `Lazy<T>` and `LazyLock<T>` statics are registered as `T`.
+#[proc_macro_error]
+#[proc_macro_attribute]
+pub fn tool_resource(attr: TokenStream, item: TokenStream) -> TokenStream {
+    if !attr.is_empty() {
+        abort!(
+            proc_macro2::TokenStream::from(attr),
+            "`#[tool_resource]` takes no arguments"
+        );
+    }
+    let item: syn::Item = parse_macro_input!(item);
+    let crate_path = get_crate_path();
+
+    // A const has no address of its own; back it with a hidden static.
+    let (name, ty, backing) = match &item {
+        syn::Item::Static(s) => (s.ident.clone(), (*s.ty).clone(), quote!()),
+        syn::Item::Const(c) => {
+            let ident = &c.ident;
+            let ty = &c.ty;
+            let backing = Ident::new(&format!("__TOOL_RESOURCE_{ident}"), Span::call_site());
+            (
+                backing.clone(),
+                (**ty).clone(),
+                quote!(static #backing: #ty = #ident;),
+            )
+        }
+        other => abort!(other, "`#[tool_resource]` applies to a `static` or `const`"),
+    };
+
+    let (resource_ty, access) = match lazy_inner(&ty) {
+        Some(inner) => (inner.clone(), quote!(&*#name)),
+        None => (ty, quote!(&#name)),
+    };
+    let type_name = LitStr::new(&quote!(#resource_ty).to_string(), Span::call_site());
+
+    TokenStream::from(quote! {
+        #item
+        #backing
+
+        #crate_path::inventory::submit! {
+            #crate_path::resources::ResourceRegistration {
+                type_name: #type_name,
+                type_id: || ::std::any::TypeId::of::<#resource_ty>(),
+                get: {
+                    fn get() -> &'static (dyn ::std::any::Any + ::std::marker::Send + ::std::marker::Sync) {
+                        #access
+                    }
+                    get
+                },
+            }
+        }
+    })
+}
+
+/// `T` for `Lazy<T>` / `LazyLock<T>`, matched by the last path segment.
+fn lazy_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(TypePath { path, .. }) = ty else {
+        return None;
+    };
+    let last = path.segments.last()?;
+    if last.ident != "Lazy" && last.ident != "LazyLock" {
+        return None;
+    }
+    single_generic_arg(ty)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -566,13 +1689,7 @@ mod tests {
         };
 
         // We can't easily test the abort! macro, but we can verify the enum detection
-        match &input.data {
-            syn::Data::Enum(_) => {
-                // This is expected - enums should be detected
-                assert!(true);
-            }
-            _ => panic!("Expected enum"),
-        }
+        assert!(matches!(input.data, syn::Data::Enum(_)), "Expected enum");
     }
 
     #[test]
@@ -584,12 +1701,6 @@ mod tests {
             }
         };
 
-        match &input.data {
-            syn::Data::Union(_) => {
-                // This is expected - unions should be detected
-                assert!(true);
-            }
-            _ => panic!("Expected union"),
-        }
+        assert!(matches!(input.data, syn::Data::Union(_)), "Expected union");
     }
 }
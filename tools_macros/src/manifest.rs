@@ -0,0 +1,127 @@
+//! Compile-time manifest of `#[tool]` functions.
+//!
+//! With the `manifest-gen` feature, or `TOOLS_RS_MANIFEST=1` in rustc's
+//! environment (a build script's `cargo:rustc-env`), each expansion
+//! appends one JSON line to `$OUT_DIR/tools-rs-manifest.jsonl`. The line
+//! format and lock protocol are shared with `tools_core::manifest`, which
+//! reads the file back.
+
+use std::{
+    env,
+    fs::{self, OpenOptions},
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde_json::{Value, json};
+
+/// Must match `tools_core::manifest::FILE_NAME`.
+const FILE_NAME: &str = "tools-rs-manifest.jsonl";
+
+/// Must match `tools_core::manifest::ENV_VAR`.
+const ENV_VAR: &str = "TOOLS_RS_MANIFEST";
+
+/// How long to wait for another expansion's lock before taking it over.
+const LOCK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// One `#[tool]` as it appears in the manifest.
+pub(crate) struct Entry<'a> {
+    pub name: &'a str,
+    pub doc: &'a str,
+    pub schema_fn: String,
+    pub params: Vec<(String, String)>,
+    pub meta_json: &'a str,
+}
+
+/// The manifest file for the crate being compiled, if manifests are on
+/// and the crate has a build script (and so an `OUT_DIR`).
+fn path() -> Option<PathBuf> {
+    let enabled = cfg!(feature = "manifest-gen")
+        || env::var(ENV_VAR).is_ok_and(|v| !matches!(v.as_str(), "" | "0" | "false"));
+    if !enabled {
+        return None;
+    }
+    Some(Path::new(&env::var_os("OUT_DIR")?).join(FILE_NAME))
+}
+
+/// Append `entry` to the manifest, if enabled. Errors are ignored: the
+/// manifest is a build aid and must never fail the build.
+pub(crate) fn record(entry: Entry<'_>) {
+    let Some(path) = path() else {
+        return;
+    };
+    let params: Vec<Value> = entry
+        .params
+        .iter()
+        .map(|(name, ty)| json!({ "name": name, "type": ty }))
+        .collect();
+    let line = json!({
+        "crate": env::var("CARGO_PKG_NAME").unwrap_or_default(),
+        "name": entry.name,
+        "doc": entry.doc,
+        "schema_fn": entry.schema_fn,
+        "params": params,
+        "meta": serde_json::from_str::<Value>(entry.meta_json).unwrap_or_default(),
+    });
+    let _ = append(&path, &line.to_string());
+}
+
+/// Append `line` under `<path>.lock`, created exclusively so parallel
+/// expansions don't interleave their writes.
+fn append(path: &Path, line: &str) -> std::io::Result<()> {
+    let lock = path.with_extension("jsonl.lock");
+    let start = Instant::now();
+    loop {
+        match OpenOptions::new().write(true).create_new(true).open(&lock) {
+            Ok(_) => break,
+            Err(e) if e.kind() == ErrorKind::AlreadyExists && start.elapsed() < LOCK_TIMEOUT => {
+                thread::sleep(Duration::from_millis(1));
+            }
+            // A lock left behind by an aborted build.
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => break,
+            Err(e) => return Err(e),
+        }
+    }
+    let written = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| writeln!(file, "{line}"));
+    let _ = fs::remove_file(&lock);
+    written
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parallel_appends_keep_whole_lines() {
+        let path = env::temp_dir().join(format!("tools-rs-manifest-{}.jsonl", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let threads: Vec<_> = (0..8)
+            .map(|t| {
+                let path = path.clone();
+                thread::spawn(move || {
+                    for i in 0..50 {
+                        let line =
+                            json!({ "name": format!("tool_{t}_{i}"), "doc": "x".repeat(512) });
+                        append(&path, &line.to_string()).unwrap();
+                    }
+                })
+            })
+            .collect();
+        threads.into_iter().for_each(|t| t.join().unwrap());
+
+        let text = fs::read_to_string(&path).unwrap();
+        let lines: Vec<Value> = text
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 400);
+        assert!(!path.with_extension("jsonl.lock").exists());
+        fs::remove_file(&path).unwrap();
+    }
+}